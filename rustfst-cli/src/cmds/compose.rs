@@ -1,7 +1,7 @@
 use anyhow::Result;
 
 use rustfst::algorithms::compose::{
-    compose, ComposeFst, ComposeFstOpOptions, LabelReachableData, MatcherFst,
+    acceptor_intersect, compose, ComposeFst, ComposeFstOpOptions, LabelReachableData, MatcherFst,
 };
 use rustfst::fst_impls::VectorFst;
 use rustfst::semirings::TropicalWeight;
@@ -26,6 +26,7 @@ use rustfst::algorithms::tr_sort;
 pub enum ComposeType {
     Default,
     LookAhead,
+    Acceptor,
 }
 
 pub struct ComposeAlgorithm {
@@ -74,6 +75,7 @@ impl BinaryFstAlgorithm for ComposeAlgorithm {
             ComposeType::Default => {
                 compose::<TropicalWeight, VectorFst<_>, VectorFst<_>, _, _, _>(&fst_1, &fst_2)
             }
+            ComposeType::Acceptor => acceptor_intersect(&fst_1, &fst_2),
             ComposeType::LookAhead => {
                 type TLaFst<'a, S, F> = MatcherFst<
                     S,
@@ -188,6 +190,7 @@ impl ComposeAlgorithm {
         let compose_type = match compose_type {
             "default" => ComposeType::Default,
             "lookahead" => ComposeType::LookAhead,
+            "acceptor" => ComposeType::Acceptor,
             _ => panic!("Unexpected compose_type : {}", compose_type),
         };
         Self {
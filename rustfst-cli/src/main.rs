@@ -167,7 +167,7 @@ fn main() {
         .arg(
             Arg::with_name("compose_type")
                 .long("compose_type")
-                .possible_values(&["default", "lookahead"])
+                .possible_values(&["default", "lookahead", "acceptor"])
                 .takes_value(true)
                 .default_value("default"),
         );
@@ -1,6 +1,6 @@
 use anyhow::{anyhow, Result};
 
-use super::EnumConversionError;
+use super::{CProgressCallback, CProgressCallbackFn, EnumConversionError};
 use crate::fst::CFst;
 use crate::{get, wrap, CLabel, RUSTFST_FFI_RESULT};
 
@@ -127,10 +127,14 @@ impl AsRust<MatcherConfig> for CMatcherConfig {
         if let Some(v) = &self.sigma_matcher_config {
             Ok(MatcherConfig {
                 sigma_matcher_config: Some(v.as_rust()?),
+                phi_matcher_config: None,
+                rho_matcher_config: None,
             })
         } else {
             Ok(MatcherConfig {
                 sigma_matcher_config: None,
+                phi_matcher_config: None,
+                rho_matcher_config: None,
             })
         }
     }
@@ -160,6 +164,28 @@ impl CReprOf<MatcherConfig> for CMatcherConfig {
     }
 }
 
+/// Maximum number of states of the composed FST, 0 meaning no limit.
+#[derive(RawPointerConverter, Debug)]
+pub struct CMaxStates(usize);
+
+impl AsRust<Option<usize>> for CMaxStates {
+    fn as_rust(&self) -> Result<Option<usize>, AsRustError> {
+        Ok(if self.0 == 0 { None } else { Some(self.0) })
+    }
+}
+
+impl CDrop for CMaxStates {
+    fn do_drop(&mut self) -> Result<(), CDropError> {
+        Ok(())
+    }
+}
+
+impl CReprOf<Option<usize>> for CMaxStates {
+    fn c_repr_of(value: Option<usize>) -> Result<CMaxStates, CReprOfError> {
+        Ok(CMaxStates(value.unwrap_or(0)))
+    }
+}
+
 #[derive(AsRust, CReprOf, CDrop, RawPointerConverter, Debug)]
 #[target_type(ComposeConfig)]
 pub struct CComposeConfig {
@@ -167,6 +193,8 @@ pub struct CComposeConfig {
     pub connect: bool,
     pub matcher1_config: CMatcherConfig,
     pub matcher2_config: CMatcherConfig,
+    pub max_states: CMaxStates,
+    pub progress: CProgressCallback,
 }
 
 #[derive(Debug)]
@@ -225,6 +253,9 @@ pub extern "C" fn fst_compose_config_new(
     connect: bool,
     matcher1_config: *const CMatcherConfig,
     matcher2_config: *const CMatcherConfig,
+    max_states: libc::size_t,
+    progress: Option<CProgressCallbackFn>,
+    progress_user_data: *mut libc::c_void,
     config: *mut *const CComposeConfig,
 ) -> RUSTFST_FFI_RESULT {
     wrap(|| {
@@ -255,6 +286,8 @@ pub extern "C" fn fst_compose_config_new(
             matcher2_config,
             compose_filter: CComposeFilterEnum(compose_filter as usize),
             connect,
+            max_states: CMaxStates(max_states as usize),
+            progress: CProgressCallback::new(progress, progress_user_data),
         };
         unsafe { *config = compose_config.into_raw_pointer() };
         Ok(())
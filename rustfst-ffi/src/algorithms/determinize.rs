@@ -1,12 +1,12 @@
 use anyhow::{anyhow, Result};
 
-use super::EnumConversionError;
+use super::{CProgressCallback, CProgressCallbackFn, EnumConversionError};
 use crate::fst::CFst;
 use crate::{get, wrap, RUSTFST_FFI_RESULT};
 
 use ffi_convert::*;
 use rustfst::algorithms::determinize::{
-    determinize, determinize_with_config, DeterminizeConfig, DeterminizeType,
+    determinize, determinize_with_config, DeterminizeConfig, DeterminizeOutput, DeterminizeType,
 };
 use rustfst::fst_impls::VectorFst;
 use rustfst::semirings::TropicalWeight;
@@ -42,23 +42,59 @@ impl CReprOf<DeterminizeType> for CDeterminizeType {
     }
 }
 
+#[derive(RawPointerConverter)]
+pub struct CDeterminizeOutput(usize);
+
+impl AsRust<DeterminizeOutput> for CDeterminizeOutput {
+    fn as_rust(&self) -> Result<DeterminizeOutput, AsRustError> {
+        match self.0 {
+            0 => Ok(DeterminizeOutput::ArcWeights),
+            1 => Ok(DeterminizeOutput::FinalWeights),
+            _ => Err(AsRustError::Other(Box::new(EnumConversionError {}))),
+        }
+    }
+}
+
+impl CDrop for CDeterminizeOutput {
+    fn do_drop(&mut self) -> Result<(), CDropError> {
+        Ok(())
+    }
+}
+
+impl CReprOf<DeterminizeOutput> for CDeterminizeOutput {
+    fn c_repr_of(value: DeterminizeOutput) -> Result<CDeterminizeOutput, CReprOfError> {
+        let variant = match value {
+            DeterminizeOutput::ArcWeights => 0,
+            DeterminizeOutput::FinalWeights => 1,
+        };
+        Ok(CDeterminizeOutput(variant))
+    }
+}
+
 #[derive(AsRust, CReprOf, CDrop, RawPointerConverter)]
 #[target_type(DeterminizeConfig)]
 pub struct CDeterminizeConfig {
     delta: f32,
     det_type: CDeterminizeType,
+    output: CDeterminizeOutput,
+    progress: CProgressCallback,
 }
 
 #[no_mangle]
 pub extern "C" fn fst_determinize_config_new(
     delta: libc::c_float,
     det_type: libc::size_t,
+    output: libc::size_t,
+    progress: Option<CProgressCallbackFn>,
+    progress_user_data: *mut libc::c_void,
     config: *mut *const CDeterminizeConfig,
 ) -> RUSTFST_FFI_RESULT {
     wrap(|| {
         let determinize_config = CDeterminizeConfig {
             delta: delta as f32,
             det_type: CDeterminizeType(det_type as usize),
+            output: CDeterminizeOutput(output as usize),
+            progress: CProgressCallback::new(progress, progress_user_data),
         };
         unsafe { *config = determinize_config.into_raw_pointer() };
         Ok(())
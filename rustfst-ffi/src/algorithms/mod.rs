@@ -1,3 +1,6 @@
+use ffi_convert::*;
+use rustfst::algorithms::{ProgressAction, ProgressCallback};
+
 pub mod compose;
 pub mod concat;
 pub mod connect;
@@ -25,3 +28,73 @@ impl std::fmt::Display for EnumConversionError {
         write!(f, "Unexpected enum variant")
     }
 }
+
+/// C function observing the progress of an algorithm. It is called with the number of states
+/// processed so far and the `user_data` pointer registered with it. It returns `0` to let the
+/// algorithm go on and any other value to abort it.
+pub type CProgressCallbackFn =
+    extern "C" fn(states_done: libc::size_t, user_data: *mut libc::c_void) -> libc::size_t;
+
+/// Optional progress callback of a configuration. `user_data` is passed back as is to
+/// `callback` : it must stay valid, and `callback` must be safe to call from any thread, as
+/// long as the configuration is used.
+#[derive(Debug)]
+pub struct CProgressCallback {
+    pub callback: Option<CProgressCallbackFn>,
+    pub user_data: *mut libc::c_void,
+}
+
+impl CProgressCallback {
+    pub fn new(callback: Option<CProgressCallbackFn>, user_data: *mut libc::c_void) -> Self {
+        Self {
+            callback,
+            user_data,
+        }
+    }
+}
+
+impl Default for CProgressCallback {
+    fn default() -> Self {
+        Self::new(None, std::ptr::null_mut())
+    }
+}
+
+struct CUserData(*mut libc::c_void);
+
+// The C caller is responsible for the thread safety of the user data.
+unsafe impl Send for CUserData {}
+unsafe impl Sync for CUserData {}
+
+impl CUserData {
+    fn get(&self) -> *mut libc::c_void {
+        self.0
+    }
+}
+
+impl AsRust<Option<ProgressCallback>> for CProgressCallback {
+    fn as_rust(&self) -> Result<Option<ProgressCallback>, AsRustError> {
+        Ok(self.callback.map(|callback| {
+            let user_data = CUserData(self.user_data);
+            ProgressCallback::new(move |states_done| {
+                if callback(states_done, user_data.get()) == 0 {
+                    ProgressAction::Continue
+                } else {
+                    ProgressAction::Abort
+                }
+            })
+        }))
+    }
+}
+
+impl CDrop for CProgressCallback {
+    fn do_drop(&mut self) -> Result<(), CDropError> {
+        Ok(())
+    }
+}
+
+impl CReprOf<Option<ProgressCallback>> for CProgressCallback {
+    /// A Rust callback can't be called from C : it is always converted to a null callback.
+    fn c_repr_of(_: Option<ProgressCallback>) -> Result<CProgressCallback, CReprOfError> {
+        Ok(CProgressCallback::default())
+    }
+}
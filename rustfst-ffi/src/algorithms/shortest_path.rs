@@ -1,19 +1,50 @@
-use anyhow::anyhow;
+use anyhow::{anyhow, Result};
 
+use super::EnumConversionError;
 use crate::fst::CFst;
 use crate::{get, wrap, RUSTFST_FFI_RESULT};
 
 use ffi_convert::*;
-use rustfst::algorithms::{shortest_path, shortest_path_with_config, ShortestPathConfig};
+use rustfst::algorithms::{shortest_path, shortest_path_with_config, ShortestPathConfig, TieBreak};
 use rustfst::fst_impls::VectorFst;
 use rustfst::semirings::TropicalWeight;
 
+#[derive(RawPointerConverter)]
+pub struct CTieBreak(usize);
+
+impl AsRust<TieBreak> for CTieBreak {
+    fn as_rust(&self) -> Result<TieBreak, AsRustError> {
+        match self.0 {
+            0 => Ok(TieBreak::Disabled),
+            1 => Ok(TieBreak::Lexicographic),
+            _ => Err(AsRustError::Other(Box::new(EnumConversionError {}))),
+        }
+    }
+}
+
+impl CDrop for CTieBreak {
+    fn do_drop(&mut self) -> Result<(), CDropError> {
+        Ok(())
+    }
+}
+
+impl CReprOf<TieBreak> for CTieBreak {
+    fn c_repr_of(value: TieBreak) -> Result<CTieBreak, CReprOfError> {
+        let variant = match value {
+            TieBreak::Disabled => 0,
+            TieBreak::Lexicographic => 1,
+        };
+        Ok(CTieBreak(variant))
+    }
+}
+
 #[derive(AsRust, CReprOf, CDrop, RawPointerConverter)]
 #[target_type(ShortestPathConfig)]
 pub struct CShortestPathConfig {
     delta: f32,
     nshortest: usize,
     unique: bool,
+    tie_break: CTieBreak,
 }
 
 #[no_mangle]
@@ -28,6 +59,7 @@ pub extern "C" fn fst_shortest_path_config_new(
             delta,
             nshortest,
             unique,
+            tie_break: CTieBreak(0),
         };
         unsafe { *ptr = config.into_raw_pointer() };
         Ok(())
@@ -15,7 +15,8 @@ class ComposeAlgorithm:
         return "compose"
 
     def get_openfst_bench_cli(self):
-        if self.compose_type == "default":
+        # OpenFST intersects acceptors with the default composition.
+        if self.compose_type in ["default", "acceptor"]:
             return "bench_compose", []
         elif self.compose_type == "lookahead":
             return "bench_compose_lookahead", []
@@ -70,6 +70,7 @@ class ComposeConfig:
         connect: bool = True,
         matcher1_config: Optional[MatcherConfig] = None,
         matcher2_config: Optional[MatcherConfig] = None,
+        max_states: Optional[int] = None,
     ):
         config = ctypes.pointer(ctypes.c_void_p())
 
@@ -86,6 +87,9 @@ class ComposeConfig:
             ctypes.c_bool(connect),
             m1_ptr,
             m2_ptr,
+            ctypes.c_size_t(max_states or 0),
+            None,
+            None,
             ctypes.byref(config),
         )
         err_msg = "Error creating ComposeConfig"
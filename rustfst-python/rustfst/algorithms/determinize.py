@@ -33,17 +33,38 @@ class DeterminizeType(Enum):
     """
 
 
+class DeterminizeOutput(Enum):
+    """
+    Enumeration defining where the residual weights of the determinized subsets end up.
+    """
+
+    ARC_WEIGHTS = 0
+    """
+    Residuals are emitted as soon as possible, on the transitions. This is what OpenFST does.
+    """
+    FINAL_WEIGHTS = 1
+    """
+    Residuals are delayed and accumulated on the final states.
+    """
+
+
 class DeterminizeConfig:
     """
     Struct containing the parameters controlling the determinization algorithm.
     """
 
-    def __init__(self, det_type: DeterminizeType, delta: Optional[float] = None):
+    def __init__(
+        self,
+        det_type: DeterminizeType,
+        delta: Optional[float] = None,
+        output: DeterminizeOutput = DeterminizeOutput.ARC_WEIGHTS,
+    ):
         """
         Creates the configuration object.
         Args:
             det_type: Type of determinization to perform.
             delta:
+            output: Where the residual weights end up.
         """
         if delta is None:
             delta = KDELTA
@@ -52,6 +73,9 @@ class DeterminizeConfig:
         ret_code = lib.fst_determinize_config_new(
             ctypes.c_float(delta),
             ctypes.c_size_t(det_type.value),
+            ctypes.c_size_t(output.value),
+            None,
+            None,
             ctypes.byref(config),
         )
         err_msg = "Error creating DeterminizeConfig"
@@ -1,3 +1,5 @@
+import pytest
+
 from rustfst import VectorFst, Tr
 from rustfst.algorithms.compose import (
     ComposeFilter,
@@ -212,3 +214,15 @@ def test_sigma_compose_with_allowlist():
     # Radiohead should NOT work
     res_radiohead = compose_with_config(query_fst_radiohead, sigma_fst, compose_config)
     assert res_radiohead != query_fst_radiohead
+
+
+def test_compose_max_states():
+    symt = SymbolTable.from_symbols(["<eps>", "play", "queen", "please"])
+    query_fst = acceptor("play queen please", symt)
+
+    compose_config = ComposeConfig(max_states=2)
+    with pytest.raises(ValueError):
+        compose_with_config(query_fst, query_fst, compose_config)
+
+    compose_config = ComposeConfig(max_states=4)
+    assert compose_with_config(query_fst, query_fst, compose_config) == query_fst
@@ -1,6 +1,10 @@
 from rustfst import VectorFst, Tr
 
-from rustfst.algorithms.determinize import DeterminizeConfig, DeterminizeType
+from rustfst.algorithms.determinize import (
+    DeterminizeConfig,
+    DeterminizeOutput,
+    DeterminizeType,
+)
 
 
 def test_determinize_fst():
@@ -61,3 +65,41 @@ def test_determinize_fst():
     det_fst = fst1.determinize(config)
 
     assert det_fst == expected_fst
+
+
+def test_determinize_fst_output():
+    fst = VectorFst()
+    s1 = fst.add_state()
+    s2 = fst.add_state()
+    s3 = fst.add_state()
+    fst.set_start(s1)
+    fst.set_final(s2, 0.0)
+    fst.set_final(s3, 0.5)
+    fst.add_tr(s1, Tr(1, 1, 1.0, s2))
+    fst.add_tr(s1, Tr(1, 1, 2.0, s3))
+
+    # The residual weight 1.0 is emitted on the transition.
+    expected_fst = VectorFst()
+    s1 = expected_fst.add_state()
+    s2 = expected_fst.add_state()
+    expected_fst.set_start(s1)
+    expected_fst.set_final(s2, 0.0)
+    expected_fst.add_tr(s1, Tr(1, 1, 1.0, s2))
+
+    config = DeterminizeConfig(
+        DeterminizeType.DETERMINIZE_FUNCTIONAL, output=DeterminizeOutput.ARC_WEIGHTS
+    )
+    assert fst.determinize(config) == expected_fst
+
+    # The residual weight 1.0 is delayed on the final state.
+    expected_fst = VectorFst()
+    s1 = expected_fst.add_state()
+    s2 = expected_fst.add_state()
+    expected_fst.set_start(s1)
+    expected_fst.set_final(s2, 1.0)
+    expected_fst.add_tr(s1, Tr(1, 1, 0.0, s2))
+
+    config = DeterminizeConfig(
+        DeterminizeType.DETERMINIZE_FUNCTIONAL, output=DeterminizeOutput.FINAL_WEIGHTS
+    )
+    assert fst.determinize(config) == expected_fst
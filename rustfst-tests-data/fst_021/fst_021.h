@@ -0,0 +1,61 @@
+#ifndef FST_021
+#define FST_021
+
+class FstTestData021 {
+public:
+    using MyWeight = fst::TropicalWeight;
+    using MyArc = fst::ArcTpl<MyWeight>;
+    using MyFst = fst::VectorFst<MyArc>;
+
+    FstTestData021() {}
+
+    // Non-deterministic but functional weighted transducer.
+    MyFst get_fst() const {
+        fst::VectorFst<fst::StdArc> f;
+
+        auto s0 = f.AddState();
+        auto s1 = f.AddState();
+        auto s2 = f.AddState();
+        auto s3 = f.AddState();
+        auto s4 = f.AddState();
+
+        f.SetStart(s0);
+        f.SetFinal(s3, 0.5);
+        f.SetFinal(s4, 2.0);
+
+        f.AddArc(s0, fst::StdArc(1, 2, 1.0, s1));
+        f.AddArc(s0, fst::StdArc(1, 2, 2.0, s2));
+        f.AddArc(s0, fst::StdArc(6, 7, 1.5, s4));
+        f.AddArc(s1, fst::StdArc(4, 5, 1.0, s3));
+        f.AddArc(s2, fst::StdArc(4, 5, 0.5, s3));
+
+        return f;
+    }
+
+    fst::VectorFst<MyArc> get_fst_compose() const {
+        return fst::VectorFst<MyArc>();
+    }
+
+    MyWeight get_weight_plus_mapper() const {
+        return MyWeight(1.5);
+    }
+
+    MyWeight get_weight_times_mapper() const {
+        return MyWeight(1.5);
+    }
+
+    fst::VectorFst<MyArc> get_fst_concat() const {
+        return get_fst_compose();
+    }
+
+    fst::VectorFst<MyArc> get_fst_union() const {
+        return get_fst_concat();
+    }
+
+    MyWeight random_weight() const {
+        return MyWeight(custom_random_float());
+    }
+};
+
+
+#endif
@@ -33,6 +33,7 @@
 #include "fst_018/fst_018.h"
 #include "fst_019/fst_019.h"
 #include "fst_020/fst_020.h"
+#include "fst_021/fst_021.h"
 
 #include "symt_000/symt_000.h"
 #include "symt_001/symt_001.h"
@@ -404,6 +405,12 @@ void compute_fst_minimization(const F& raw_fst, json& j, const string& dir_path)
     std::vector<bool> v = {true, false};
     for(bool allow_nondet: v) {
         auto fst_out = *raw_fst.Copy();
+        // rustfst determinizes the non-deterministic FSTs before minimizing them.
+        if (allow_nondet && !prop_to_bool(raw_fst.Properties(fst::kIDeterministic, true), fst::kIDeterministic)) {
+            fst::DeterminizeOptions<typename F::Arc> opts;
+            opts.type = fst::DeterminizeType::DETERMINIZE_NONFUNCTIONAL;
+            fst::Determinize(raw_fst, &fst_out, opts);
+        }
         fst::Minimize(&fst_out, (fst::VectorFst<typename F::Arc>*)nullptr, delta, allow_nondet);
         bool error = prop_to_bool(fst_out.Properties(fst::kError, true), fst::kError);
 
@@ -1185,8 +1192,8 @@ void compute_fst_data(const F& fst_test_data, const string fst_name) {
     std::cout << "Closure Star" << std::endl;
     compute_fst_closure_star(raw_fst, data, dir_path);
 
-//    std::cout << "Matcher" << std::endl;
-//    compute_fst_matcher(raw_fst, data);
+    std::cout << "Matcher" << std::endl;
+    compute_fst_matcher(raw_fst, data);
 
     std::cout << "Compose" << std::endl;
     auto fst_compose = fst_test_data.get_fst_compose();
@@ -1350,4 +1357,5 @@ int main() {
     compute_fst_data(FstTestData018(), "fst_018");
     compute_fst_data(FstTestData019(), "fst_019");
     compute_fst_data(FstTestData020(), "fst_020");
+    compute_fst_data(FstTestData021(), "fst_021");
 }
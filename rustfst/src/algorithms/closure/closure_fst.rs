@@ -2,9 +2,8 @@ use std::sync::Arc;
 
 use anyhow::Result;
 
-use crate::algorithms::closure::ClosureType;
+use crate::algorithms::closure::{ClosureConfig, ClosureType};
 use crate::algorithms::replace::ReplaceFst;
-use crate::fst_properties::mutable_properties::closure_properties;
 use crate::fst_properties::FstProperties;
 use crate::fst_traits::{AllocableFst, CoreFst, Fst, FstIterator, MutableFst, StateIterator};
 use crate::semirings::Semiring;
@@ -26,6 +25,13 @@ where
     //TODO: Use a borrow and not a move
     //TODO: Allow fsts of different types
     pub fn new(fst: F, closure_type: ClosureType) -> Result<Self> {
+        Self::new_with_config(fst, ClosureConfig::new(closure_type))
+    }
+
+    /// Same as [`new`](#method.new) but the transitions looping back from the final states
+    /// to the start state are labeled with `config.iterate_ilabel` and
+    /// `config.iterate_olabel`, as in [`closure_with_config`](fn.closure_with_config.html).
+    pub fn new_with_config(fst: F, config: ClosureConfig) -> Result<Self> {
         let props = fst.properties();
         let mut rfst = F::new();
         if let Some(isymt) = fst.input_symbols() {
@@ -34,7 +40,21 @@ where
         if let Some(osymt) = fst.output_symbols() {
             rfst.set_output_symbols(Arc::clone(osymt));
         }
-        match closure_type {
+        let (ilabel, olabel) = (config.iterate_ilabel, config.iterate_olabel);
+        match config.closure_type {
+            ClosureType::ClosureStar if !config.has_epsilon_iterate_labels() => {
+                // A single self-looping call can't tell the first repetition from the
+                // following ones : the iterate labels need their own transition.
+                rfst.add_states(3);
+                unsafe {
+                    rfst.set_start_unchecked(0);
+                    rfst.set_final_unchecked(0, W::one());
+                    rfst.set_final_unchecked(2, W::one());
+                    rfst.add_tr_unchecked(0, Tr::new(EPS_LABEL, EPS_LABEL, W::one(), 1));
+                    rfst.add_tr_unchecked(1, Tr::new(EPS_LABEL, NO_LABEL, W::one(), 2));
+                    rfst.add_tr_unchecked(2, Tr::new(ilabel, olabel, W::one(), 1));
+                }
+            }
             ClosureType::ClosureStar => {
                 rfst.add_state();
                 unsafe {
@@ -49,7 +69,7 @@ where
                     rfst.set_start_unchecked(0);
                     rfst.set_final_unchecked(1, W::one());
                     rfst.add_tr_unchecked(0, Tr::new(EPS_LABEL, NO_LABEL, W::one(), 1));
-                    rfst.add_tr_unchecked(1, Tr::new(EPS_LABEL, EPS_LABEL, W::one(), 0));
+                    rfst.add_tr_unchecked(1, Tr::new(ilabel, olabel, W::one(), 0));
                 }
             }
         };
@@ -59,7 +79,7 @@ where
 
         Ok(ClosureFst(
             ReplaceFst::new(fst_tuples, 0, false)?,
-            closure_properties(props, true),
+            config.closure_properties(props, true),
         ))
     }
 
@@ -173,8 +193,62 @@ where
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::algorithms::closure::closure_with_config;
+    use crate::algorithms::determinize::determinize;
+    use crate::algorithms::equivalent;
+    use crate::algorithms::rm_epsilon::rm_epsilon;
     use crate::fst_impls::VectorFst;
     use crate::semirings::TropicalWeight;
+    use crate::utils::acceptor;
+    use crate::KDELTA;
+
+    fn normalize(mut fst: VectorFst<TropicalWeight>) -> Result<VectorFst<TropicalWeight>> {
+        rm_epsilon(&mut fst)?;
+        determinize(&fst)
+    }
+
+    fn check_closure_static_lazy(config: ClosureConfig) -> Result<()> {
+        let mut fst: VectorFst<TropicalWeight> = acceptor(&[1, 2], TropicalWeight::new(1.0));
+        fst.add_tr(0, Tr::new(4, 4, 2.0, 2))?;
+
+        let mut fst_static = fst.clone();
+        closure_with_config(&mut fst_static, config);
+        let fst_lazy: VectorFst<_> = ClosureFst::new_with_config(fst, config)?.compute()?;
+
+        assert!(equivalent(
+            &normalize(fst_static)?,
+            &normalize(fst_lazy)?,
+            KDELTA
+        )?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_closure_fst_iterate_labels() -> Result<()> {
+        for closure_type in &[ClosureType::ClosurePlus, ClosureType::ClosureStar] {
+            check_closure_static_lazy(ClosureConfig::new(*closure_type))?;
+            check_closure_static_lazy(ClosureConfig::new(*closure_type).with_iterate_labels(3, 3))?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_closure_iterate_labels_count_repetitions() -> Result<()> {
+        let mut fst: VectorFst<TropicalWeight> = acceptor(&[1, 2], TropicalWeight::one());
+        closure_with_config(
+            &mut fst,
+            ClosureConfig::new(ClosureType::ClosureStar).with_iterate_labels(EPS_LABEL, 3),
+        );
+        assert!(!fst.properties().contains(FstProperties::ACCEPTOR));
+
+        let paths: Vec<_> = fst.paths_iter().take(4).collect();
+        assert_eq!(paths[0].ilabels, Vec::<u32>::new());
+        assert_eq!(paths[1].ilabels, vec![1, 2]);
+        assert_eq!(paths[2].ilabels, vec![1, 2, 1, 2]);
+        assert_eq!(paths[2].olabels, vec![1, 2, 3, 1, 2]);
+        assert_eq!(paths[3].olabels, vec![1, 2, 3, 1, 2, 3, 1, 2]);
+        Ok(())
+    }
 
     #[test]
     fn test_closure_fst_sync() {
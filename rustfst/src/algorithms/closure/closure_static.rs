@@ -1,7 +1,6 @@
 use unsafe_unwrap::UnsafeUnwrap;
 
-use crate::algorithms::closure::ClosureType;
-use crate::fst_properties::mutable_properties::closure_properties;
+use crate::algorithms::closure::{ClosureConfig, ClosureType};
 use crate::fst_properties::FstProperties;
 use crate::fst_traits::MutableFst;
 use crate::semirings::Semiring;
@@ -29,6 +28,39 @@ where
     W: Semiring,
     F: MutableFst<W>,
 {
+    closure_with_config(fst, ClosureConfig::new(closure_type))
+}
+
+/// Same as [`closure`](fn.closure.html) but the transitions looping back from the final
+/// states to the start state are labeled with `config.iterate_ilabel` and
+/// `config.iterate_olabel`, which makes the boundaries between the repetitions observable.
+///
+/// # Example
+///
+/// ```
+/// # use anyhow::Result;
+/// # use rustfst::prelude::*;
+/// # use rustfst::algorithms::closure::{closure_with_config, ClosureConfig, ClosureType};
+/// # use rustfst::utils::acceptor;
+/// # fn main() -> Result<()> {
+/// let mut fst: VectorFst<TropicalWeight> = acceptor(&[1, 2], TropicalWeight::one());
+/// closure_with_config(
+///     &mut fst,
+///     ClosureConfig::new(ClosureType::ClosurePlus).with_iterate_labels(0, 3),
+/// );
+///
+/// let paths: Vec<_> = fst.paths_iter().take(2).collect();
+/// assert_eq!(paths[0].olabels, vec![1, 2]);
+/// assert_eq!(paths[1].olabels, vec![1, 2, 3, 1, 2]);
+/// # Ok(())
+/// # }
+/// ```
+pub fn closure_with_config<W, F>(fst: &mut F, config: ClosureConfig)
+where
+    W: Semiring,
+    F: MutableFst<W>,
+{
+    let closure_type = config.closure_type;
     let props = fst.properties();
     if let Some(start_state) = fst.start() {
         let final_states_id: Vec<_> = fst
@@ -39,7 +71,12 @@ where
             unsafe {
                 fst.add_tr_unchecked(
                     final_state_id,
-                    Tr::new(EPS_LABEL, EPS_LABEL, final_weight, start_state),
+                    Tr::new(
+                        config.iterate_ilabel,
+                        config.iterate_olabel,
+                        final_weight,
+                        start_state,
+                    ),
                 )
             };
         }
@@ -65,7 +102,7 @@ where
     }
 
     fst.set_properties_with_mask(
-        closure_properties(props, false),
+        config.closure_properties(props, false),
         FstProperties::all_properties(),
     );
 }
@@ -0,0 +1,54 @@
+use crate::algorithms::closure::ClosureType;
+use crate::fst_properties::mutable_properties::closure_properties;
+use crate::fst_properties::FstProperties;
+use crate::{Label, EPS_LABEL};
+
+/// Configuration for [`closure_with_config`](fn.closure_with_config.html) and
+/// [`ClosureFst::new_with_config`](struct.ClosureFst.html#method.new_with_config).
+#[derive(Clone, Debug, Copy, PartialEq)]
+pub struct ClosureConfig {
+    /// Whether the empty string is accepted as well (star) or not (plus).
+    pub closure_type: ClosureType,
+    /// Input label put on the transitions looping back from the final states to the
+    /// start state, i.e. between two repetitions. Epsilon by default.
+    pub iterate_ilabel: Label,
+    /// Output label put on the transitions looping back from the final states to the
+    /// start state, i.e. between two repetitions. Epsilon by default.
+    pub iterate_olabel: Label,
+}
+
+impl ClosureConfig {
+    pub fn new(closure_type: ClosureType) -> Self {
+        Self {
+            closure_type,
+            iterate_ilabel: EPS_LABEL,
+            iterate_olabel: EPS_LABEL,
+        }
+    }
+
+    pub fn with_iterate_labels(self, iterate_ilabel: Label, iterate_olabel: Label) -> Self {
+        Self {
+            iterate_ilabel,
+            iterate_olabel,
+            ..self
+        }
+    }
+
+    pub(crate) fn has_epsilon_iterate_labels(&self) -> bool {
+        self.iterate_ilabel == EPS_LABEL && self.iterate_olabel == EPS_LABEL
+    }
+
+    /// Properties of the closure. Different iterate labels can turn an acceptor into a
+    /// transducer.
+    pub(crate) fn closure_properties(
+        &self,
+        inprops: FstProperties,
+        delayed: bool,
+    ) -> FstProperties {
+        let mut outprops = closure_properties(inprops, delayed);
+        if self.iterate_ilabel != self.iterate_olabel {
+            outprops &= !FstProperties::ACCEPTOR;
+        }
+        outprops
+    }
+}
@@ -1,8 +1,10 @@
 mod closure_fst;
 mod closure_static;
+mod config;
 
 pub use closure_fst::ClosureFst;
-pub use closure_static::closure;
+pub use closure_static::{closure, closure_with_config};
+pub use config::ClosureConfig;
 
 /// Defines the different types of closure : Star or Plus.
 #[derive(Clone, Debug, Copy, PartialEq)]
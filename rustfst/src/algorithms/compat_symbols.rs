@@ -0,0 +1,98 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+
+use crate::fst_traits::Fst;
+use crate::semirings::Semiring;
+use crate::SymbolTable;
+
+/// Checks that `fst1` and `fst2` can be composed from the point of view of their symbol
+/// tables, i.e that the output symbols of `fst1` are the same as the input symbols of `fst2`.
+/// The tables are compared through their [`labeled_checksum`](../struct.SymbolTable.html#method.labeled_checksum).
+///
+/// The check is skipped if one of the two tables is missing. An error naming the mismatching
+/// tables is returned otherwise.
+///
+/// # Example
+/// ```
+/// # #[macro_use] extern crate rustfst;
+/// # use std::sync::Arc;
+/// # use rustfst::fst_impls::VectorFst;
+/// # use rustfst::fst_traits::{Fst, MutableFst};
+/// # use rustfst::semirings::TropicalWeight;
+/// # use rustfst::algorithms::check_symbol_compatibility;
+/// # use rustfst::SymbolTable;
+/// # fn main() {
+/// let mut fst1 = VectorFst::<TropicalWeight>::new();
+/// let mut fst2 = VectorFst::<TropicalWeight>::new();
+/// assert!(check_symbol_compatibility(&fst1, &fst2).is_ok());
+///
+/// fst1.set_output_symbols(Arc::new(symt!["a", "b"]));
+/// fst2.set_input_symbols(Arc::new(symt!["b", "a"]));
+/// assert!(check_symbol_compatibility(&fst1, &fst2).is_err());
+/// # }
+/// ```
+pub fn check_symbol_compatibility<W, F1, F2>(fst1: &F1, fst2: &F2) -> Result<()>
+where
+    W: Semiring,
+    F1: Fst<W>,
+    F2: Fst<W>,
+{
+    if !compat_symbols(fst1.output_symbols(), fst2.input_symbols()) {
+        bail!(
+            "Symbol tables mismatch : the output symbols of the first FST are not the same \
+             as the input symbols of the second FST"
+        );
+    }
+    Ok(())
+}
+
+fn compat_symbols(symt1: Option<&Arc<SymbolTable>>, symt2: Option<&Arc<SymbolTable>>) -> bool {
+    match (symt1, symt2) {
+        (Some(symt1), Some(symt2)) => {
+            Arc::ptr_eq(symt1, symt2) || symt1.labeled_checksum() == symt2.labeled_checksum()
+        }
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fst_impls::VectorFst;
+    use crate::fst_traits::MutableFst;
+    use crate::semirings::TropicalWeight;
+
+    #[test]
+    fn test_check_symbol_compatibility() -> Result<()> {
+        let mut symt = SymbolTable::new();
+        symt.add_symbols(vec!["a", "b"]);
+        let symt = Arc::new(symt);
+        let mut other_symt = SymbolTable::new();
+        other_symt.add_symbols(vec!["a", "c"]);
+        let other_symt = Arc::new(other_symt);
+
+        let mut fst1 = VectorFst::<TropicalWeight>::new();
+        let mut fst2 = VectorFst::<TropicalWeight>::new();
+
+        // Missing tables are not checked.
+        check_symbol_compatibility(&fst1, &fst2)?;
+        fst1.set_output_symbols(Arc::clone(&symt));
+        check_symbol_compatibility(&fst1, &fst2)?;
+
+        // Same content in a different table.
+        fst2.set_input_symbols(Arc::new(symt.as_ref().clone()));
+        check_symbol_compatibility(&fst1, &fst2)?;
+
+        // Only the output symbols of fst1 and the input symbols of fst2 matter.
+        fst1.set_input_symbols(Arc::clone(&other_symt));
+        fst2.set_output_symbols(Arc::clone(&other_symt));
+        check_symbol_compatibility(&fst1, &fst2)?;
+
+        fst2.set_input_symbols(Arc::clone(&other_symt));
+        let err = check_symbol_compatibility(&fst1, &fst2).unwrap_err();
+        assert!(err.to_string().contains("output symbols of the first FST"));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,302 @@
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+
+use anyhow::Result;
+use superslice::Ext;
+
+use crate::algorithms::connect;
+use crate::fst_properties::{compute_fst_properties, FstProperties};
+use crate::fst_traits::{ExpandedFst, MutableFst};
+use crate::semirings::Semiring;
+use crate::{StateId, Tr, Trs, EPS_LABEL};
+
+/// State of the intersection : (state of fst1, state of fst2, filter state).
+type IntersectTuple = (StateId, StateId, u8);
+
+#[derive(Default)]
+struct IntersectStateTable {
+    tuples: Vec<IntersectTuple>,
+    tuple_to_state: HashMap<IntersectTuple, StateId>,
+}
+
+impl IntersectStateTable {
+    fn find_state<W: Semiring, F: MutableFst<W>>(
+        &mut self,
+        tuple: IntersectTuple,
+        ofst: &mut F,
+    ) -> StateId {
+        match self.tuple_to_state.entry(tuple) {
+            Entry::Occupied(e) => *e.get(),
+            Entry::Vacant(e) => {
+                self.tuples.push(tuple);
+                *e.insert(ofst.add_state())
+            }
+        }
+    }
+}
+
+/// Computes the intersection of two acceptors. This produces the same FST as
+/// [`compose`](fn.compose.html) on acceptors but is faster as the transitions are matched
+/// directly on their label, without going through the generic matchers and compose filters.
+///
+/// As with `compose`, the epsilon transitions are handled like OpenFST's
+/// `SequenceComposeFilter` so that each path is generated only once, and the result is
+/// connected. When both FSTs have an epsilon transition, the one of `fst1` is taken first and
+/// then the one of `fst2`.
+///
+/// An error is returned if one of the FSTs is not an acceptor or if neither of them
+/// is sorted by label.
+///
+/// # Example
+/// ```
+/// # #[macro_use] extern crate rustfst;
+/// # use rustfst::utils::acceptor;
+/// # use rustfst::fst_impls::VectorFst;
+/// # use rustfst::fst_traits::Fst;
+/// # use rustfst::semirings::{Semiring, TropicalWeight};
+/// # use rustfst::algorithms::compose::acceptor_intersect;
+/// # use rustfst::FstPath;
+/// # use anyhow::Result;
+/// # fn main() -> Result<()> {
+/// let fst1: VectorFst<TropicalWeight> = fst![1, 2, 3; 1.0];
+/// let fst2: VectorFst<TropicalWeight> = fst![1, 2, 3; 2.0];
+/// let fst3: VectorFst<TropicalWeight> = fst![1, 2; 2.0];
+///
+/// let res: VectorFst<_> = acceptor_intersect(&fst1, &fst2)?;
+/// assert_eq!(res.paths_iter().collect::<Vec<_>>(), vec![fst_path![1, 2, 3; 3.0]]);
+///
+/// let res: VectorFst<_> = acceptor_intersect(&fst1, &fst3)?;
+/// assert_eq!(res.paths_iter().count(), 0);
+/// # Ok(())
+/// # }
+/// ```
+pub fn acceptor_intersect<W, F1, F2, F3>(fst1: &F1, fst2: &F2) -> Result<F3>
+where
+    W: Semiring,
+    F1: ExpandedFst<W>,
+    F2: ExpandedFst<W>,
+    F3: MutableFst<W>,
+{
+    let mask = FstProperties::ACCEPTOR
+        | FstProperties::NOT_ACCEPTOR
+        | FstProperties::I_LABEL_SORTED
+        | FstProperties::NOT_I_LABEL_SORTED;
+    let props1 = compute_fst_properties(fst1, mask, &mut FstProperties::empty(), true)?;
+    let props2 = compute_fst_properties(fst2, mask, &mut FstProperties::empty(), true)?;
+    if !props1.contains(FstProperties::ACCEPTOR) || !props2.contains(FstProperties::ACCEPTOR) {
+        bail!("AcceptorIntersect : Both FSTs must be acceptors");
+    }
+    // Labels of the transitions of one FST are searched among the transitions of the other.
+    let search_in_fst2 = props2.contains(FstProperties::I_LABEL_SORTED);
+    if !search_in_fst2 && !props1.contains(FstProperties::I_LABEL_SORTED) {
+        bail!("AcceptorIntersect : At least one of the FSTs must be sorted by label");
+    }
+
+    let mut ofst = F3::new();
+    if let Some(symt) = fst1.input_symbols() {
+        ofst.set_input_symbols(symt.clone());
+    }
+    if let Some(symt) = fst2.output_symbols() {
+        ofst.set_output_symbols(symt.clone());
+    }
+
+    let (start1, start2) = match (fst1.start(), fst2.start()) {
+        (Some(s1), Some(s2)) => (s1, s2),
+        _ => return Ok(ofst),
+    };
+
+    let mut state_table = IntersectStateTable::default();
+    let start = state_table.find_state((start1, start2, 0), &mut ofst);
+    ofst.set_start(start)?;
+
+    let mut state = 0;
+    while let Some(&(s1, s2, fs)) = state_table.tuples.get(state as usize) {
+        let trs1 = fst1.get_trs(s1)?;
+        let trs2 = fst2.get_trs(s2)?;
+        let final1 = fst1.final_weight(s1)?;
+
+        if let (Some(w1), Some(w2)) = (&final1, fst2.final_weight(s2)?) {
+            ofst.set_final(state, w1.times(w2)?)?;
+        }
+
+        let neps1 = fst1.num_output_epsilons(s1)?;
+        // Sequence filter : fst2 can only move alone on an epsilon if fst1 can't anymore.
+        let alleps1 = neps1 == trs1.len() && final1.is_none();
+        let next_fs = if neps1 == 0 { 0 } else { 1 };
+
+        let mut trs = vec![];
+        for tr1 in trs1.trs() {
+            if tr1.ilabel == EPS_LABEL {
+                if fs == 0 {
+                    let n = state_table.find_state((tr1.nextstate, s2, 0), &mut ofst);
+                    trs.push(Tr::new(EPS_LABEL, EPS_LABEL, tr1.weight.clone(), n));
+                }
+            } else if search_in_fst2 {
+                let range = trs2.trs().equal_range_by(|x| x.ilabel.cmp(&tr1.ilabel));
+                for tr2 in &trs2.trs()[range] {
+                    let n = state_table.find_state((tr1.nextstate, tr2.nextstate, 0), &mut ofst);
+                    trs.push(Tr::new(
+                        tr1.ilabel,
+                        tr1.ilabel,
+                        tr1.weight.times(&tr2.weight)?,
+                        n,
+                    ));
+                }
+            }
+        }
+        for tr2 in trs2.trs() {
+            if tr2.ilabel == EPS_LABEL {
+                if !alleps1 {
+                    let n = state_table.find_state((s1, tr2.nextstate, next_fs), &mut ofst);
+                    trs.push(Tr::new(EPS_LABEL, EPS_LABEL, tr2.weight.clone(), n));
+                }
+            } else if !search_in_fst2 {
+                let range = trs1.trs().equal_range_by(|x| x.ilabel.cmp(&tr2.ilabel));
+                for tr1 in &trs1.trs()[range] {
+                    let n = state_table.find_state((tr1.nextstate, tr2.nextstate, 0), &mut ofst);
+                    trs.push(Tr::new(
+                        tr2.ilabel,
+                        tr2.ilabel,
+                        tr1.weight.times(&tr2.weight)?,
+                        n,
+                    ));
+                }
+            }
+        }
+        for tr in trs {
+            ofst.add_tr(state, tr)?;
+        }
+
+        state += 1;
+    }
+
+    connect(&mut ofst)?;
+    Ok(ofst)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::algorithms::compose::compose;
+    use crate::algorithms::tr_compares::ILabelCompare;
+    use crate::algorithms::tr_sort;
+    use crate::fst_impls::VectorFst;
+    use crate::fst_traits::{CoreFst, ExpandedFst, Fst};
+    use crate::semirings::TropicalWeight;
+    use crate::utils::transducer;
+    use crate::Label;
+
+    type F = VectorFst<TropicalWeight>;
+
+    // Acyclic so that the paths can be enumerated.
+    fn random_acceptor(seed: u32, num_states: usize, with_epsilons: bool) -> Result<F> {
+        // Simple LCG to stay deterministic without depending on the rng of the tests.
+        let mut x = seed;
+        let mut next = move |n: u32| {
+            x = x.wrapping_mul(1_103_515_245).wrapping_add(12345);
+            (x >> 16) % n
+        };
+        let mut fst = F::new();
+        fst.add_states(num_states);
+        fst.set_start(0)?;
+        for s in 0..(num_states - 1) as StateId {
+            for _ in 0..3 {
+                let label = if with_epsilons { next(4) } else { next(3) + 1 };
+                let nextstate = s + 1 + next(num_states as u32 - s - 1);
+                fst.add_tr(s, Tr::new(label, label, next(5) as f32, nextstate))?;
+            }
+            if next(3) == 0 {
+                fst.set_final(s, next(5) as f32)?;
+            }
+        }
+        fst.set_final(num_states as StateId - 1, TropicalWeight::one())?;
+        tr_sort(&mut fst, ILabelCompare {});
+        Ok(fst)
+    }
+
+    fn total_num_trs(fst: &F) -> usize {
+        fst.states_range().map(|s| fst.num_trs(s).unwrap()).sum()
+    }
+
+    fn paths(fst: &F) -> Vec<(Vec<Label>, TropicalWeight)> {
+        let mut paths: Vec<_> = fst.paths_iter().map(|p| (p.ilabels, p.weight)).collect();
+        paths.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        paths
+    }
+
+    #[test]
+    fn test_acceptor_intersect_same_as_compose() -> Result<()> {
+        for seed in 0..20 {
+            let with_epsilons = seed % 2 == 0;
+            let fst1 = random_acceptor(seed, 6, with_epsilons)?;
+            let fst2 = random_acceptor(seed + 100, 5, with_epsilons)?;
+
+            let res: F = acceptor_intersect(&fst1, &fst2)?;
+            let res_ref: F = compose::<_, F, F, _, _, _>(&fst1, &fst2)?;
+            assert_eq!(res.num_states(), res_ref.num_states());
+            assert_eq!(total_num_trs(&res), total_num_trs(&res_ref));
+            assert_eq!(paths(&res), paths(&res_ref));
+
+            // Only one of the FSTs needs to be sorted.
+            let mut fst2_unsorted = fst2.clone();
+            for s in fst2_unsorted.states_range() {
+                let mut trs = fst2_unsorted.pop_trs(s)?;
+                trs.reverse();
+                for tr in trs {
+                    fst2_unsorted.add_tr(s, tr)?;
+                }
+            }
+            let res: F = acceptor_intersect(&fst1, &fst2_unsorted)?;
+            assert_eq!(paths(&res), paths(&res_ref));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_acceptor_intersect_epsilons_on_both_sides() -> Result<()> {
+        // Both FSTs accept 1 2 with epsilon transitions before and between the labels.
+        let mut fst1 = F::new();
+        fst1.add_states(5);
+        fst1.set_start(0)?;
+        fst1.set_final(4, 0.5)?;
+        fst1.add_tr(0, Tr::new(EPS_LABEL, EPS_LABEL, 1.0, 1))?;
+        fst1.add_tr(1, Tr::new(1, 1, 1.0, 2))?;
+        fst1.add_tr(2, Tr::new(EPS_LABEL, EPS_LABEL, 1.0, 3))?;
+        fst1.add_tr(3, Tr::new(2, 2, 1.0, 4))?;
+
+        let mut fst2 = F::new();
+        fst2.add_states(5);
+        fst2.set_start(0)?;
+        fst2.set_final(4, 0.5)?;
+        fst2.add_tr(0, Tr::new(EPS_LABEL, EPS_LABEL, 2.0, 1))?;
+        fst2.add_tr(1, Tr::new(1, 1, 2.0, 2))?;
+        fst2.add_tr(2, Tr::new(EPS_LABEL, EPS_LABEL, 2.0, 3))?;
+        fst2.add_tr(3, Tr::new(2, 2, 2.0, 4))?;
+
+        for (fst1, fst2) in [(&fst1, &fst2), (&fst2, &fst1)].iter() {
+            let res: F = acceptor_intersect(*fst1, *fst2)?;
+            let res_ref: F = compose::<_, F, F, _, _, _>(*fst1, *fst2)?;
+            assert_eq!(paths(&res), vec![(vec![1, 2], TropicalWeight::new(13.0))]);
+            assert_eq!(res.num_states(), res_ref.num_states());
+            assert_eq!(total_num_trs(&res), total_num_trs(&res_ref));
+            assert_eq!(paths(&res), paths(&res_ref));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_acceptor_intersect_errors() -> Result<()> {
+        let fst1 = random_acceptor(1, 4, false)?;
+        let fst2: F = transducer(&[1, 2], &[2, 1], TropicalWeight::one());
+        assert!(acceptor_intersect::<_, _, _, F>(&fst1, &fst2).is_err());
+
+        let mut fst_unsorted = F::new();
+        fst_unsorted.add_states(2);
+        fst_unsorted.set_start(0)?;
+        fst_unsorted.add_tr(0, Tr::new(2, 2, 1.0, 1))?;
+        fst_unsorted.add_tr(0, Tr::new(1, 1, 1.0, 1))?;
+        assert!(acceptor_intersect::<_, _, _, F>(&fst_unsorted, &fst_unsorted).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,224 @@
+use std::borrow::Borrow;
+use std::fmt::Debug;
+use std::marker::PhantomData;
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+
+use crate::algorithms::compose::ComposeFst;
+use crate::algorithms::lazy::{FstOp, LazyFst, SimpleVecCache};
+use crate::algorithms::{connect, FinalTr, MapFinalAction, WeightConverter};
+use crate::fst_impls::VectorFst;
+use crate::fst_properties::FstProperties;
+use crate::fst_traits::{ExpandedFst, Fst};
+use crate::semirings::Semiring;
+use crate::{StateId, Trs, TrsVec, EPS_LABEL};
+
+/// Lazily converts the weights of an FST, one state at a time.
+struct WeightConvertFstOp<W1, W2, F, B, M>
+where
+    W1: Semiring,
+    W2: Semiring,
+    F: Fst<W1>,
+    B: Borrow<F>,
+    M: WeightConverter<W1, W2>,
+{
+    fst: B,
+    converter: Mutex<M>,
+    properties: FstProperties,
+    ghost: PhantomData<(W1, W2, F)>,
+}
+
+impl<W1, W2, F, B, M> Debug for WeightConvertFstOp<W1, W2, F, B, M>
+where
+    W1: Semiring,
+    W2: Semiring,
+    F: Fst<W1>,
+    B: Borrow<F>,
+    M: WeightConverter<W1, W2>,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "WeightConvertFstOp {{ fst : {:?}, properties : {:?} }}",
+            self.fst.borrow(),
+            self.properties
+        )
+    }
+}
+
+impl<W1, W2, F, B, M> FstOp<W2> for WeightConvertFstOp<W1, W2, F, B, M>
+where
+    W1: Semiring,
+    W2: Semiring,
+    F: Fst<W1>,
+    B: Borrow<F>,
+    M: WeightConverter<W1, W2>,
+{
+    fn compute_start(&self) -> Result<Option<StateId>> {
+        Ok(self.fst.borrow().start())
+    }
+
+    fn compute_trs(&self, id: StateId) -> Result<TrsVec<W2>> {
+        let mut converter = self.converter.lock().unwrap();
+        let trs = self
+            .fst
+            .borrow()
+            .get_trs(id)?
+            .trs()
+            .iter()
+            .map(|tr| converter.tr_map(tr))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(TrsVec(Arc::new(trs)))
+    }
+
+    fn compute_final_weight(&self, id: StateId) -> Result<Option<W2>> {
+        match self.fst.borrow().final_weight(id)? {
+            None => Ok(None),
+            Some(weight) => {
+                let final_tr = FinalTr {
+                    ilabel: EPS_LABEL,
+                    olabel: EPS_LABEL,
+                    weight,
+                };
+                let mapped_final_tr = self.converter.lock().unwrap().final_tr_map(&final_tr)?;
+                if mapped_final_tr.ilabel != EPS_LABEL || mapped_final_tr.olabel != EPS_LABEL {
+                    bail!("ComposeConverting: Non-zero tr labels for superfinal tr")
+                }
+                Ok(Some(mapped_final_tr.weight))
+            }
+        }
+    }
+
+    fn properties(&self) -> FstProperties {
+        self.properties
+    }
+}
+
+type ConvertedFst<W1, W2, F1, B1, M> =
+    LazyFst<W2, WeightConvertFstOp<W1, W2, F1, B1, M>, SimpleVecCache<W2>>;
+
+/// Composes two FSTs in different semirings, the weights of `fst1` being converted to the
+/// semiring of `fst2` with `converter`.
+///
+/// The conversion is performed lazily, as the composition visits the states of `fst1` :
+/// the converted version of `fst1` is never built as a whole. Only converters that don't
+/// require a superfinal state (`MapFinalAction::MapNoSuperfinal`) are supported.
+///
+/// As for the other lazy operations, `fst1` can be anything that borrows an FST, for
+/// instance the FST itself or an `Arc` of it, but not a plain `&F1` : it has to be `'static`.
+/// The converted FST is the first input of a `ComposeFst`, which requires it to implement
+/// `Fst`, and the lazy FSTs only implement `Fst` when their content is `'static` (the state
+/// and transition iterators of `Fst` are required for any lifetime). Pass an `Arc` to share
+/// `fst1` with the caller without copying it.
+///
+/// # Example
+///
+/// ```
+/// # use anyhow::Result;
+/// # use rustfst::algorithms::compose::compose_converting;
+/// # use rustfst::algorithms::weight_converters::SimpleWeightConverter;
+/// # use rustfst::fst_impls::VectorFst;
+/// # use rustfst::semirings::{LogWeight, Semiring, TropicalWeight};
+/// # use rustfst::utils::transducer;
+/// # fn main() -> Result<()> {
+/// let fst1: VectorFst<TropicalWeight> = transducer(&[1], &[2], TropicalWeight::new(1.0));
+/// let fst2: VectorFst<LogWeight> = transducer(&[2], &[3], LogWeight::new(2.0));
+///
+/// let fst: VectorFst<LogWeight> = compose_converting(fst1, &fst2, SimpleWeightConverter {})?;
+/// assert_eq!(fst, transducer(&[1], &[3], LogWeight::new(3.0)));
+/// # Ok(())
+/// # }
+/// ```
+pub fn compose_converting<W1, W2, F1, F2, B1, M>(
+    fst1: B1,
+    fst2: &F2,
+    converter: M,
+) -> Result<VectorFst<W2>>
+where
+    W1: Semiring,
+    W2: Semiring,
+    F1: Fst<W1> + 'static,
+    F2: ExpandedFst<W2>,
+    B1: Borrow<F1> + 'static,
+    M: WeightConverter<W1, W2> + 'static,
+{
+    if converter.final_action() != MapFinalAction::MapNoSuperfinal {
+        bail!("ComposeConverting: Only converters without superfinal state are supported")
+    }
+    let isymt = fst1.borrow().input_symbols().cloned();
+    let osymt = fst1.borrow().output_symbols().cloned();
+    let properties = converter.properties(fst1.borrow().properties());
+    let op = WeightConvertFstOp {
+        fst: fst1,
+        converter: Mutex::new(converter),
+        properties,
+        ghost: PhantomData,
+    };
+    let converted_fst1: ConvertedFst<W1, W2, F1, B1, M> =
+        LazyFst::from_op_and_cache(op, SimpleVecCache::default(), isymt, osymt);
+
+    let mut ofst: VectorFst<W2> = ComposeFst::<
+        W2,
+        ConvertedFst<W1, W2, F1, B1, M>,
+        F2,
+        &ConvertedFst<W1, W2, F1, B1, M>,
+        &F2,
+        _,
+        _,
+        _,
+    >::new_auto(&converted_fst1, fst2)?
+    .compute()?;
+    connect(&mut ofst)?;
+    Ok(ofst)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::weight_converters::SimpleWeightConverter;
+    use crate::fst_path::FstPath;
+    use crate::fst_traits::MutableFst;
+    use crate::semirings::{LogWeight, TropicalWeight};
+    use crate::utils::transducer;
+    use crate::Tr;
+
+    #[test]
+    fn test_compose_converting_tropical_log() -> Result<()> {
+        // Lexicon : 1 2 -> 10 with weight 1.0, 3 -> 11 with weight 2.0.
+        let mut lexicon = VectorFst::<TropicalWeight>::new();
+        lexicon.add_states(3);
+        lexicon.set_start(0)?;
+        lexicon.set_final(2, 0.5)?;
+        lexicon.add_tr(0, Tr::new(1, 10, 1.0, 1))?;
+        lexicon.add_tr(1, Tr::new(2, EPS_LABEL, TropicalWeight::one(), 2))?;
+        lexicon.add_tr(0, Tr::new(3, 11, 2.0, 2))?;
+
+        // Grammar : 10 with two different paths.
+        let mut grammar = VectorFst::<LogWeight>::new();
+        grammar.add_states(2);
+        grammar.set_start(0)?;
+        grammar.set_final(1, LogWeight::one())?;
+        grammar.add_tr(0, Tr::new(10, 10, 1.0, 1))?;
+        grammar.add_tr(0, Tr::new(10, 10, 3.0, 1))?;
+
+        let fst: VectorFst<LogWeight> =
+            compose_converting(lexicon.clone(), &grammar, SimpleWeightConverter {})?;
+        let mut paths: Vec<_> = fst.paths_iter().collect();
+        paths.sort_by(|p1, p2| p1.weight.value().partial_cmp(p2.weight.value()).unwrap());
+        assert_eq!(
+            paths,
+            vec![
+                FstPath::new(vec![1, 2], vec![10], LogWeight::new(2.5)),
+                FstPath::new(vec![1, 2], vec![10], LogWeight::new(4.5)),
+            ]
+        );
+
+        let fst_ref: VectorFst<LogWeight> = transducer(&[11], &[12], LogWeight::new(2.5));
+        let fst: VectorFst<LogWeight> =
+            compose_converting(lexicon, &fst_ref, SimpleWeightConverter {})?;
+        assert_eq!(fst.paths_iter().count(), 1);
+
+        Ok(())
+    }
+}
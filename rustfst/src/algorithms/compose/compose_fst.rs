@@ -8,11 +8,13 @@ use crate::algorithms::compose::compose_filters::{
 };
 use crate::algorithms::compose::matchers::{GenericMatcher, Matcher};
 use crate::algorithms::compose::{
-    ComposeFstOp, ComposeFstOpOptions, ComposeFstOpState, ComposeStateTuple,
+    ComposeConfig, ComposeError, ComposeFstOp, ComposeFstOpOptions, ComposeFstOpState,
+    ComposeStateTuple,
 };
 use crate::algorithms::lazy::{
     FstCache, LazyFst, SerializableCache, SerializableLazyFst, SimpleVecCache,
 };
+use crate::algorithms::progress::ProgressCallback;
 use crate::fst_properties::FstProperties;
 use crate::fst_traits::{AllocableFst, CoreFst, Fst, FstIterator, MutableFst, StateIterator};
 use crate::parsers::SerializeBinary;
@@ -162,6 +164,45 @@ where
     pub fn compute<F: MutableFst<W> + AllocableFst<W>>(&self) -> Result<F> {
         self.0.compute()
     }
+
+    /// Turns the Lazy FST into a static one. Returns a `ComposeError::TooLarge` error as soon
+    /// as more than `max_states` states are produced if it is set.
+    pub fn compute_with_max_states<F: MutableFst<W> + AllocableFst<W>>(
+        &self,
+        max_states: Option<usize>,
+    ) -> Result<F> {
+        self.compute_with_limits(max_states, None)
+    }
+
+    /// Same as [`compute_with_max_states`](#method.compute_with_max_states) but `progress` is
+    /// called regularly with the number of states computed so far. The computation fails with
+    /// a `Cancelled` error if it returns `ProgressAction::Abort`.
+    pub fn compute_with_limits<F: MutableFst<W> + AllocableFst<W>>(
+        &self,
+        max_states: Option<usize>,
+        progress: Option<ProgressCallback>,
+    ) -> Result<F> {
+        self.0
+            .compute_with_limits(max_states, progress)?
+            .ok_or_else(|| ComposeError::TooLarge(max_states.unwrap_or_default()).into())
+    }
+
+    /// Turns the Lazy FST into a static one, honoring the `connect`, `max_states` and
+    /// `progress` fields of `config`. With `connect: false`, the raw product is returned,
+    /// including the states from which no final state can be reached.
+    ///
+    /// The compose filter and the matchers are already fixed by the type of the `ComposeFst`,
+    /// the corresponding fields of `config` are ignored.
+    pub fn compute_with_config<F: MutableFst<W> + AllocableFst<W>>(
+        &self,
+        config: ComposeConfig,
+    ) -> Result<F> {
+        let mut ofst: F = self.compute_with_limits(config.max_states, config.progress)?;
+        if config.connect {
+            crate::algorithms::connect(&mut ofst)?;
+        }
+        Ok(ofst)
+    }
 }
 
 impl<W, F1, F2, B1, B2>
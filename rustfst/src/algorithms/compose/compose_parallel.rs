@@ -0,0 +1,132 @@
+use std::borrow::Borrow;
+use std::fmt::Debug;
+
+use anyhow::Result;
+use rayon::prelude::*;
+
+use crate::algorithms::compose::ComposeFst;
+use crate::fst_traits::{AllocableFst, CoreFst, ExpandedFst, MutableFst};
+use crate::semirings::Semiring;
+use crate::{StateId, Trs};
+
+/// Computes the composition of two transducers like [`compose`](fn.compose.html), expanding
+/// the states of the composed FST in parallel.
+///
+/// The composed FST is explored breadth first and all the states of a level are expanded in
+/// parallel. The threads share the cache and the state table of the lazy
+/// [`ComposeFst`](struct.ComposeFst.html), both being thread-safe. As the states are numbered
+/// in the order they are discovered, the numbering depends on the scheduling of the threads :
+/// the result is isomorphic to the one of `compose` but not necessarily equal.
+///
+/// Only available with the `rayon` feature.
+///
+/// # Example
+/// ```
+/// # use anyhow::Result;
+/// # use rustfst::utils::{acceptor, transducer};
+/// # use rustfst::semirings::{Semiring, TropicalWeight};
+/// # use rustfst::fst_impls::VectorFst;
+/// # use rustfst::algorithms::compose::{compose, compose_parallel};
+/// # use rustfst::algorithms::isomorphic;
+/// # fn main() -> Result<()> {
+/// let fst_1: VectorFst<TropicalWeight> = transducer(&[1, 2], &[2, 3], TropicalWeight::one());
+/// let fst_2: VectorFst<TropicalWeight> = acceptor(&[2, 3], TropicalWeight::new(1.0));
+///
+/// let composed: VectorFst<_> = compose_parallel::<_, VectorFst<_>, VectorFst<_>, _, _, _>(
+///     &fst_1, &fst_2,
+/// )?;
+/// let composed_ref: VectorFst<_> = compose::<_, VectorFst<_>, VectorFst<_>, _, _, _>(
+///     &fst_1, &fst_2,
+/// )?;
+/// assert!(isomorphic(&composed, &composed_ref)?);
+/// # Ok(())
+/// # }
+/// ```
+pub fn compose_parallel<W, F1, F2, B1, B2, F3>(fst1: B1, fst2: B2) -> Result<F3>
+where
+    W: Semiring + Send,
+    F1: ExpandedFst<W> + Send + Sync,
+    F2: ExpandedFst<W> + Send + Sync,
+    B1: Borrow<F1> + Debug + Clone + Send + Sync,
+    B2: Borrow<F2> + Debug + Clone + Send + Sync,
+    F3: MutableFst<W> + AllocableFst<W>,
+{
+    let compose_fst = ComposeFst::new_auto(fst1, fst2)?;
+
+    if let Some(start) = compose_fst.start() {
+        let mut visited = vec![false; start as usize + 1];
+        visited[start as usize] = true;
+        let mut level = vec![start];
+        while !level.is_empty() {
+            // Expanding a state stores its trs and final weight in the cache. Only the
+            // nextstates are sent back to build the next level.
+            let nextstates = level
+                .par_iter()
+                .map(|s| {
+                    compose_fst.final_weight(*s)?;
+                    let trs = compose_fst.get_trs(*s)?;
+                    Ok(trs.trs().iter().map(|tr| tr.nextstate).collect())
+                })
+                .collect::<Result<Vec<Vec<StateId>>>>()?;
+
+            level = vec![];
+            for nextstate in nextstates.into_iter().flatten() {
+                if nextstate as usize >= visited.len() {
+                    visited.resize(nextstate as usize + 1, false);
+                }
+                if !visited[nextstate as usize] {
+                    visited[nextstate as usize] = true;
+                    level.push(nextstate);
+                }
+            }
+        }
+    }
+
+    // All the reachable states are in the cache, this is a copy.
+    let mut ofst: F3 = compose_fst.compute()?;
+    crate::algorithms::connect(&mut ofst)?;
+    Ok(ofst)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::compose::compose;
+    use crate::algorithms::isomorphic;
+    use crate::fst_impls::VectorFst;
+    use crate::semirings::TropicalWeight;
+    use crate::{Label, Tr};
+
+    type F = VectorFst<TropicalWeight>;
+
+    fn sigma_star(n: Label, shift: Label) -> Result<F> {
+        // Transducer with n states, each of them being final and having a transition to
+        // another one for every label in 1..=n, the output label being shifted.
+        let mut fst = VectorFst::new();
+        fst.add_states(n as usize);
+        fst.set_start(0)?;
+        for s in 0..n {
+            fst.set_final(s, TropicalWeight::new(s as f32))?;
+            for l in 1..=n {
+                let olabel = if l == n { 0 } else { (l + shift - 1) % n + 1 };
+                fst.add_tr(s, Tr::new(l, olabel, l as f32, (s + l) % n))?;
+            }
+        }
+        Ok(fst)
+    }
+
+    #[test]
+    fn test_compose_parallel() -> Result<()> {
+        for (fst1, fst2) in &[
+            (sigma_star(5, 1)?, sigma_star(7, 2)?),
+            (sigma_star(8, 3)?, sigma_star(4, 0)?),
+            (sigma_star(3, 0)?, F::new()),
+        ] {
+            let composed: F = compose_parallel::<_, F, F, _, _, _>(fst1, fst2)?;
+            let composed_ref: F = compose::<_, F, F, _, _, _>(fst1, fst2)?;
+            assert_eq!(composed.num_states(), composed_ref.num_states());
+            assert!(isomorphic(&composed, &composed_ref)?);
+        }
+        Ok(())
+    }
+}
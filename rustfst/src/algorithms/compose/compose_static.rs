@@ -8,8 +8,11 @@ use crate::algorithms::compose::compose_filters::{
     AltSequenceComposeFilterBuilder, MatchComposeFilterBuilder, NoMatchComposeFilterBuilder,
     NullComposeFilterBuilder, SequenceComposeFilterBuilder, TrivialComposeFilterBuilder,
 };
-use crate::algorithms::compose::matchers::{Matcher, SigmaMatcher, SortedMatcher};
+use crate::algorithms::compose::matchers::{
+    Matcher, PhiMatcher, RhoMatcher, SigmaMatcher, SortedMatcher,
+};
 use crate::algorithms::compose::ComposeFst;
+use crate::algorithms::progress::ProgressCallback;
 use crate::fst_traits::{AllocableFst, ExpandedFst, Fst, MutableFst};
 use crate::prelude::compose::matchers::{MatchType, MatcherRewriteMode};
 use crate::prelude::compose::ComposeFstOpOptions;
@@ -34,14 +37,33 @@ pub struct SigmaMatcherConfig {
     pub sigma_allowed_matches: Option<Vec<Label>>,
 }
 
+#[derive(PartialEq, PartialOrd, Debug, Clone)]
+pub struct PhiMatcherConfig {
+    pub phi_label: Label,
+    pub phi_loop: bool,
+    pub rewrite_mode: MatcherRewriteMode,
+}
+
+#[derive(PartialEq, PartialOrd, Debug, Clone)]
+pub struct RhoMatcherConfig {
+    pub rho_label: Label,
+    pub rewrite_mode: MatcherRewriteMode,
+}
+
+/// At most one of the special matchers can be configured. The `SortedMatcher` is used on its
+/// own if none is.
 #[derive(Default, PartialEq, PartialOrd, Debug, Clone)]
 pub struct MatcherConfig {
     pub sigma_matcher_config: Option<SigmaMatcherConfig>,
+    pub phi_matcher_config: Option<PhiMatcherConfig>,
+    pub rho_matcher_config: Option<RhoMatcherConfig>,
 }
 
 impl MatcherConfig {
     pub fn empty(&self) -> bool {
         self.sigma_matcher_config.is_none()
+            && self.phi_matcher_config.is_none()
+            && self.rho_matcher_config.is_none()
     }
 }
 
@@ -50,9 +72,39 @@ pub struct ComposeConfig {
     pub compose_filter: ComposeFilterEnum,
     pub matcher1_config: MatcherConfig,
     pub matcher2_config: MatcherConfig,
+    /// Whether the composed FST is trimmed with `connect`. Disabling it keeps the raw product,
+    /// which helps understanding why a composition is empty.
     pub connect: bool,
+    /// If set, the composition fails with `ComposeError::TooLarge` as soon as the composed
+    /// FST has more states than this (before connection).
+    pub max_states: Option<usize>,
+    /// If set, called regularly with the number of states computed so far. The composition
+    /// fails with a `Cancelled` error if it returns `ProgressAction::Abort`.
+    pub progress: Option<ProgressCallback>,
+}
+
+/// Errors specific to the composition that can be retrieved by downcasting the returned
+/// `anyhow::Error`.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum ComposeError {
+    /// The composed FST has more states than the `max_states` of the `ComposeConfig`.
+    TooLarge(usize),
 }
 
+impl std::fmt::Display for ComposeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ComposeError::TooLarge(max_states) => write!(
+                f,
+                "Compose : The composed FST has more than {} states",
+                max_states
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ComposeError {}
+
 impl Default for ComposeConfig {
     fn default() -> Self {
         Self {
@@ -60,10 +112,13 @@ impl Default for ComposeConfig {
             matcher1_config: MatcherConfig::default(),
             matcher2_config: MatcherConfig::default(),
             connect: true,
+            max_states: None,
+            progress: None,
         }
     }
 }
 
+#[allow(clippy::enum_variant_names)]
 #[derive(Clone)]
 pub enum MatcherEnum<W, F, B>
 where
@@ -73,6 +128,8 @@ where
 {
     SortedMatcher(SortedMatcher<W, F, B>),
     SigmaMatcher(SigmaMatcher<W, F, B, SortedMatcher<W, F, B>>),
+    PhiMatcher(PhiMatcher<W, F, B, SortedMatcher<W, F, B>>),
+    RhoMatcher(RhoMatcher<W, F, B, SortedMatcher<W, F, B>>),
 }
 
 impl MatcherConfig {
@@ -86,7 +143,39 @@ impl MatcherConfig {
         F: Fst<W>,
         B: Borrow<F> + Debug,
     {
-        if self.sigma_matcher_config.is_none() {
+        let num_special_matchers = [
+            self.sigma_matcher_config.is_some(),
+            self.phi_matcher_config.is_some(),
+            self.rho_matcher_config.is_some(),
+        ]
+        .iter()
+        .filter(|configured| **configured)
+        .count();
+        if num_special_matchers > 1 {
+            bail!("MatcherConfig : Only one of the sigma, phi and rho matchers can be configured")
+        }
+        if let Some(rho_config) = &self.rho_matcher_config {
+            let matcher = SortedMatcher::new(fst, match_type)?;
+            let matcher = RhoMatcher::new(
+                match_type,
+                rho_config.rho_label,
+                rho_config.rewrite_mode,
+                Arc::new(matcher),
+            )?;
+
+            Ok(MatcherEnum::RhoMatcher(matcher))
+        } else if let Some(phi_config) = &self.phi_matcher_config {
+            let matcher = SortedMatcher::new(fst, match_type)?;
+            let matcher = PhiMatcher::new(
+                match_type,
+                phi_config.phi_label,
+                phi_config.phi_loop,
+                phi_config.rewrite_mode,
+                Arc::new(matcher),
+            )?;
+
+            Ok(MatcherEnum::PhiMatcher(matcher))
+        } else if self.sigma_matcher_config.is_none() {
             let matcher = SortedMatcher::new(fst, match_type)?;
 
             Ok(MatcherEnum::SortedMatcher(matcher))
@@ -114,7 +203,8 @@ macro_rules! run_compose {
         $f1: ty, $f2: ty,
         $builder: tt,
         $matcher1: expr, $matcher1_ty: ty,
-        $matcher2: expr, $matcher2_ty: ty
+        $matcher2: expr, $matcher2_ty: ty,
+        $max_states: expr, $progress: expr
     ) => {{
         let compose_fst_op_opts = ComposeFstOpOptions::new($matcher1, $matcher2, None, None);
         ComposeFst::<
@@ -127,35 +217,69 @@ macro_rules! run_compose {
             _,
             $builder<_, _, _, _, _, $matcher1_ty, $matcher2_ty>,
         >::new_with_options($fst1, $fst2, compose_fst_op_opts)?
-        .compute()?
+        .compute_with_limits($max_states, $progress)?
     }};
 }
 
-macro_rules! compose_generate_matchers {
+macro_rules! compose_generate_matcher2 {
     (
         $fst1: expr, $fst2: expr, $f1: ty, $f2: ty,
-        $builder: tt, $matcher1_enum: expr, $matcher2_enum: expr
+        $builder: tt, $matcher1: expr, $matcher1_ty: ty, $matcher2_enum: expr,
+        $max_states: expr, $progress: expr
     ) => {
         {
-            match ($matcher1_enum, $matcher2_enum) {
-                (MatcherEnum::SortedMatcher(m1), MatcherEnum::SortedMatcher(m2)) => {
+            match $matcher2_enum {
+                MatcherEnum::SortedMatcher(m2) => {
                     run_compose!(
-                        $fst1.borrow(), $fst2.borrow(), $f1, $f2, $builder, Some(m1), SortedMatcher<_, _, _>, Some(m2), SortedMatcher<_,_,_>
+                        $fst1, $fst2, $f1, $f2, $builder, $matcher1, $matcher1_ty, Some(m2), SortedMatcher<_, _, _>, $max_states, $progress
                     )
                 },
-                (MatcherEnum::SigmaMatcher(m1), MatcherEnum::SortedMatcher(m2)) => {
+                MatcherEnum::SigmaMatcher(m2) => {
                     run_compose!(
-                        $fst1.borrow(), $fst2.borrow(), $f1, $f2, $builder, Some(m1), SigmaMatcher<_, _, _, _>, Some(m2), SortedMatcher<_,_,_>
+                        $fst1, $fst2, $f1, $f2, $builder, $matcher1, $matcher1_ty, Some(m2), SigmaMatcher<_, _, _, _>, $max_states, $progress
                     )
                 },
-                (MatcherEnum::SortedMatcher(m1), MatcherEnum::SigmaMatcher(m2)) => {
+                MatcherEnum::PhiMatcher(m2) => {
                     run_compose!(
-                        $fst1.borrow(), $fst2.borrow(), $f1, $f2, $builder, Some(m1), SortedMatcher<_, _, _>, Some(m2), SigmaMatcher<_,_,_,_>
+                        $fst1, $fst2, $f1, $f2, $builder, $matcher1, $matcher1_ty, Some(m2), PhiMatcher<_, _, _, _>, $max_states, $progress
                     )
                 },
-                (MatcherEnum::SigmaMatcher(m1), MatcherEnum::SigmaMatcher(m2)) => {
+                MatcherEnum::RhoMatcher(m2) => {
                     run_compose!(
-                        $fst1.borrow(), $fst2.borrow(), $f1, $f2, $builder, Some(m1), SigmaMatcher<_, _, _, _>, Some(m2), SigmaMatcher<_,_,_,_>
+                        $fst1, $fst2, $f1, $f2, $builder, $matcher1, $matcher1_ty, Some(m2), RhoMatcher<_, _, _, _>, $max_states, $progress
+                    )
+                }
+            }
+        }
+    };
+}
+
+macro_rules! compose_generate_matchers {
+    (
+        $fst1: expr, $fst2: expr, $f1: ty, $f2: ty,
+        $builder: tt, $matcher1_enum: expr, $matcher2_enum: expr,
+        $max_states: expr, $progress: expr
+    ) => {
+        {
+            match $matcher1_enum {
+                MatcherEnum::SortedMatcher(m1) => {
+                    compose_generate_matcher2!(
+                        $fst1.borrow(), $fst2.borrow(), $f1, $f2, $builder, Some(m1), SortedMatcher<_, _, _>, $matcher2_enum, $max_states, $progress
+                    )
+                },
+                MatcherEnum::SigmaMatcher(m1) => {
+                    compose_generate_matcher2!(
+                        $fst1.borrow(), $fst2.borrow(), $f1, $f2, $builder, Some(m1), SigmaMatcher<_, _, _, _>, $matcher2_enum, $max_states, $progress
+                    )
+                },
+                MatcherEnum::PhiMatcher(m1) => {
+                    compose_generate_matcher2!(
+                        $fst1.borrow(), $fst2.borrow(), $f1, $f2, $builder, Some(m1), PhiMatcher<_, _, _, _>, $matcher2_enum, $max_states, $progress
+                    )
+                },
+                MatcherEnum::RhoMatcher(m1) => {
+                    compose_generate_matcher2!(
+                        $fst1.borrow(), $fst2.borrow(), $f1, $f2, $builder, Some(m1), RhoMatcher<_, _, _, _>, $matcher2_enum, $max_states, $progress
                     )
                 }
             }
@@ -185,7 +309,8 @@ pub fn compose_with_config<
     let mut ofst: F3 = match config.compose_filter {
         ComposeFilterEnum::AutoFilter => {
             if config.matcher1_config.empty() && config.matcher2_config.empty() {
-                ComposeFst::new_auto(fst1, fst2)?.compute()?
+                ComposeFst::new_auto(fst1, fst2)?
+                    .compute_with_limits(config.max_states, config.progress)?
             } else {
                 bail!("Custom MatcherConfig not supported with AutoFilter")
             }
@@ -198,7 +323,9 @@ pub fn compose_with_config<
                 F2,
                 NullComposeFilterBuilder,
                 matcher1,
-                matcher2
+                matcher2,
+                config.max_states,
+                config.progress
             )
         }
         ComposeFilterEnum::SequenceFilter => {
@@ -209,7 +336,9 @@ pub fn compose_with_config<
                 F2,
                 SequenceComposeFilterBuilder,
                 matcher1,
-                matcher2
+                matcher2,
+                config.max_states,
+                config.progress
             )
         }
         ComposeFilterEnum::AltSequenceFilter => {
@@ -220,7 +349,9 @@ pub fn compose_with_config<
                 F2,
                 AltSequenceComposeFilterBuilder,
                 matcher1,
-                matcher2
+                matcher2,
+                config.max_states,
+                config.progress
             )
         }
         ComposeFilterEnum::MatchFilter => {
@@ -231,7 +362,9 @@ pub fn compose_with_config<
                 F2,
                 MatchComposeFilterBuilder,
                 matcher1,
-                matcher2
+                matcher2,
+                config.max_states,
+                config.progress
             )
         }
         ComposeFilterEnum::NoMatchFilter => {
@@ -242,7 +375,9 @@ pub fn compose_with_config<
                 F2,
                 NoMatchComposeFilterBuilder,
                 matcher1,
-                matcher2
+                matcher2,
+                config.max_states,
+                config.progress
             )
         }
         ComposeFilterEnum::TrivialFilter => {
@@ -253,7 +388,9 @@ pub fn compose_with_config<
                 F2,
                 TrivialComposeFilterBuilder,
                 matcher1,
-                matcher2
+                matcher2,
+                config.max_states,
+                config.progress
             )
         }
     };
@@ -304,3 +441,195 @@ pub fn compose<
     let config = ComposeConfig::default();
     compose_with_config(fst1, fst2, config)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fst_impls::VectorFst;
+    use crate::fst_traits::{ExpandedFst, Fst, MutableFst};
+    use crate::semirings::TropicalWeight;
+    use crate::Tr;
+
+    type F = VectorFst<TropicalWeight>;
+
+    fn sigma_star(n: Label) -> Result<F> {
+        // Acceptor with n states, each of them being final and having a transition to the
+        // next one for every label in 1..=n.
+        let mut fst = VectorFst::new();
+        fst.add_states(n as usize);
+        fst.set_start(0)?;
+        for s in 0..n {
+            fst.set_final(s, TropicalWeight::one())?;
+            for l in 1..=n {
+                fst.add_tr(s, Tr::new(l, l, TropicalWeight::one(), (s + l) % n))?;
+            }
+        }
+        Ok(fst)
+    }
+
+    #[test]
+    fn test_compose_max_states() -> Result<()> {
+        let fst1 = sigma_star(5)?;
+        let fst2 = sigma_star(7)?;
+
+        let res = compose::<_, F, F, F, _, _>(&fst1, &fst2)?;
+        assert_eq!(res.num_states(), 35);
+
+        let config = ComposeConfig {
+            max_states: Some(35),
+            ..ComposeConfig::default()
+        };
+        let res = compose_with_config::<_, F, F, _, _, F>(&fst1, &fst2, config)?;
+        assert_eq!(res.num_states(), 35);
+
+        for compose_filter in &[
+            ComposeFilterEnum::AutoFilter,
+            ComposeFilterEnum::SequenceFilter,
+        ] {
+            let config = ComposeConfig {
+                compose_filter: *compose_filter,
+                max_states: Some(10),
+                ..ComposeConfig::default()
+            };
+            let err = compose_with_config::<_, F, F, _, _, F>(&fst1, &fst2, config).unwrap_err();
+            assert_eq!(
+                err.downcast_ref::<ComposeError>(),
+                Some(&ComposeError::TooLarge(10))
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_compose_epsilon_filters() -> Result<()> {
+        // The epsilon output of fst1 and the epsilon input of fst2 can be matched in any
+        // order.
+        let mut fst1 = F::new();
+        fst1.add_states(3);
+        fst1.set_start(0)?;
+        fst1.set_final(2, TropicalWeight::one())?;
+        fst1.add_tr(0, Tr::new(1, 0, 1.0, 1))?;
+        fst1.add_tr(1, Tr::new(2, 3, 1.0, 2))?;
+
+        let mut fst2 = F::new();
+        fst2.add_states(3);
+        fst2.set_start(0)?;
+        fst2.set_final(2, TropicalWeight::one())?;
+        fst2.add_tr(0, Tr::new(0, 4, 1.0, 1))?;
+        fst2.add_tr(1, Tr::new(3, 5, 1.0, 2))?;
+
+        let expected = vec![(vec![1, 2], vec![4, 5], TropicalWeight::new(4.0))];
+        for compose_filter in &[
+            ComposeFilterEnum::AutoFilter,
+            ComposeFilterEnum::SequenceFilter,
+            ComposeFilterEnum::AltSequenceFilter,
+            ComposeFilterEnum::MatchFilter,
+        ] {
+            let config = ComposeConfig {
+                compose_filter: *compose_filter,
+                ..ComposeConfig::default()
+            };
+            let res = compose_with_config::<_, F, F, _, _, F>(&fst1, &fst2, config)?;
+            let paths: Vec<_> = res
+                .paths_iter()
+                .map(|p| (p.ilabels, p.olabels, p.weight))
+                .collect();
+            assert_eq!(paths, expected, "{:?}", compose_filter);
+        }
+
+        // Without epsilon sequencing, the same path is produced several times.
+        let config = ComposeConfig {
+            compose_filter: ComposeFilterEnum::TrivialFilter,
+            ..ComposeConfig::default()
+        };
+        let res = compose_with_config::<_, F, F, _, _, F>(&fst1, &fst2, config)?;
+        assert!(res.paths_iter().count() > 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_compose_no_connect() -> Result<()> {
+        // The path 1:1 can't be completed in fst2, leaving a dead end in the product.
+        let mut fst1 = F::new();
+        fst1.add_states(3);
+        fst1.set_start(0)?;
+        fst1.set_final(2, TropicalWeight::one())?;
+        fst1.add_tr(0, Tr::new(1, 1, 1.0, 1))?;
+        fst1.add_tr(0, Tr::new(2, 2, 1.0, 2))?;
+        fst1.add_tr(1, Tr::new(3, 3, 1.0, 2))?;
+
+        let mut fst2 = F::new();
+        fst2.add_states(3);
+        fst2.set_start(0)?;
+        fst2.set_final(2, TropicalWeight::one())?;
+        fst2.add_tr(0, Tr::new(1, 1, 1.0, 1))?;
+        fst2.add_tr(0, Tr::new(2, 2, 1.0, 2))?;
+
+        let connected = compose::<_, F, F, F, _, _>(&fst1, &fst2)?;
+        assert_eq!(connected.num_states(), 2);
+
+        let config = ComposeConfig {
+            connect: false,
+            ..ComposeConfig::default()
+        };
+        let raw = compose_with_config::<_, F, F, _, _, F>(&fst1, &fst2, config.clone())?;
+        assert_eq!(raw.num_states(), 3);
+        assert_eq!(raw.paths_iter().count(), 1);
+
+        let lazy = ComposeFst::<_, F, F, _, _, _, _, _>::new_auto(&fst1, &fst2)?;
+        let lazy_raw: F = lazy.compute_with_config(config)?;
+        assert_eq!(lazy_raw, raw);
+        let lazy_connected: F = lazy.compute_with_config(ComposeConfig::default())?;
+        assert_eq!(lazy_connected, connected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_compose_fst_with_gc_cache() -> Result<()> {
+        use crate::algorithms::lazy::{CacheOptions, GcCache};
+        use crate::fst_traits::CoreFst;
+        use crate::{StateId, Trs};
+
+        let fst1 = sigma_star(4)?;
+        let fst2 = sigma_star(3)?;
+        let reference = ComposeFst::<_, F, F, _, _, _, _, _>::new_auto(&fst1, &fst2)?;
+        // With a one byte limit, every insertion evicts all the other states of the cache.
+        let lazy = ComposeFst::<
+            _,
+            F,
+            F,
+            _,
+            _,
+            SortedMatcher<_, _, _>,
+            SortedMatcher<_, _, _>,
+            SequenceComposeFilterBuilder<_, _, _, _, _, _, _>,
+            GcCache<_>,
+        >::new_with_options_and_cache(
+            &fst1,
+            &fst2,
+            ComposeFstOpOptions::default(),
+            GcCache::new(CacheOptions::new(true, 1)),
+        )?;
+
+        let expected: F = reference.compute()?;
+        assert_eq!(lazy.compute::<F>()?, expected);
+
+        // Every state but the last expanded one has been evicted and is expanded again.
+        for _ in 0..2 {
+            for s in 0..expected.num_states() as StateId {
+                assert_eq!(
+                    lazy.get_trs(s)?.trs(),
+                    reference.get_trs(s)?.trs(),
+                    "state {}",
+                    s
+                );
+                assert_eq!(lazy.num_trs(s)?, expected.num_trs(s)?);
+                assert_eq!(unsafe { lazy.num_trs_unchecked(s) }, expected.num_trs(s)?);
+                assert_eq!(lazy.num_input_epsilons(s)?, expected.num_input_epsilons(s)?);
+                assert_eq!(lazy.final_weight(s)?, expected.final_weight(s)?);
+            }
+        }
+        Ok(())
+    }
+}
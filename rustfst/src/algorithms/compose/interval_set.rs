@@ -1,3 +1,4 @@
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
 use std::collections::HashSet;
@@ -7,7 +8,8 @@ use superslice::Ext;
 use unsafe_unwrap::UnsafeUnwrap;
 
 /// Half-open integral interval [a, b) of signed integers of type T.
-#[derive(PartialEq, Clone, Eq, Debug, Serialize, Deserialize)]
+#[derive(PartialEq, Clone, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct IntInterval {
     pub begin: usize,
     pub end: usize,
@@ -227,3 +227,129 @@ where
         la_matcher_data.default_lookahead_prefix(tr)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::algorithms::compose::compose_filters::{
+        AltSequenceComposeFilterBuilder, ComposeFilterBuilder,
+    };
+    use crate::algorithms::compose::lookahead_filters::lookahead_selector::SMatchOutput;
+    use crate::algorithms::compose::lookahead_filters::LookAheadComposeFilterBuilder;
+    use crate::algorithms::compose::matchers::SortedMatcher;
+    use crate::algorithms::compose::{compose, ComposeFst, ComposeFstOpOptions, MatcherFst};
+    use crate::algorithms::lazy::SimpleHashMapCache;
+    use crate::algorithms::tr_compares::ILabelCompare;
+    use crate::algorithms::{connect, tr_sort};
+    use crate::fst_impls::VectorFst;
+    use crate::fst_traits::{Fst, MutableFst};
+    use crate::semirings::TropicalWeight;
+
+    #[derive(Debug, Clone, PartialOrd, PartialEq)]
+    struct OLabelLookAheadFlags {}
+
+    impl MatcherFlagsTrait for OLabelLookAheadFlags {
+        fn flags() -> MatcherFlags {
+            MatcherFlags::OLABEL_LOOKAHEAD_FLAGS
+        }
+    }
+
+    type F = VectorFst<TropicalWeight>;
+    type TLaMatcher<FI> = LabelLookAheadMatcher<
+        TropicalWeight,
+        FI,
+        Arc<FI>,
+        SortedMatcher<TropicalWeight, FI, Arc<FI>>,
+        OLabelLookAheadFlags,
+    >;
+    type TLaFst = MatcherFst<TropicalWeight, F, Arc<F>, TLaMatcher<F>, LabelReachableData>;
+    type TMatcher1 = TLaMatcher<TLaFst>;
+    type TMatcher2 = SortedMatcher<TropicalWeight, F, Arc<F>>;
+    type TSeqFilter = AltSequenceComposeFilterBuilder<
+        TropicalWeight,
+        TLaFst,
+        F,
+        Arc<TLaFst>,
+        Arc<F>,
+        TMatcher1,
+        TMatcher2,
+    >;
+    type TLookFilter = LookAheadComposeFilterBuilder<
+        TropicalWeight,
+        TLaFst,
+        F,
+        Arc<TLaFst>,
+        Arc<F>,
+        TMatcher1,
+        TMatcher2,
+        TSeqFilter,
+        SMatchOutput,
+    >;
+
+    fn paths(fst: &F) -> Vec<(Vec<Label>, Vec<Label>, TropicalWeight)> {
+        let mut paths: Vec<_> = fst
+            .paths_iter()
+            .map(|p| (p.ilabels, p.olabels, p.weight))
+            .collect();
+        paths.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        paths
+    }
+
+    #[test]
+    fn test_label_lookahead_compose() -> Result<()> {
+        // Lexicon : "1 2" -> 10, "1 3" -> 11 and "4" -> 12, the words being output on the
+        // first transition.
+        let mut fst1 = F::new();
+        fst1.add_states(4);
+        fst1.set_start(0)?;
+        fst1.set_final(3, TropicalWeight::one())?;
+        fst1.add_tr(0, Tr::new(1, 10, 1.0, 1))?;
+        fst1.add_tr(1, Tr::new(2, EPS_LABEL, 1.0, 3))?;
+        fst1.add_tr(0, Tr::new(1, 11, 2.0, 2))?;
+        fst1.add_tr(2, Tr::new(3, EPS_LABEL, 1.0, 3))?;
+        fst1.add_tr(0, Tr::new(4, 12, 3.0, 3))?;
+        fst1.add_tr(3, Tr::new(EPS_LABEL, EPS_LABEL, 1.0, 0))?;
+
+        // Grammar accepting "11 12" and "13", the latter not being in the lexicon.
+        let mut fst2 = F::new();
+        fst2.add_states(3);
+        fst2.set_start(0)?;
+        fst2.set_final(2, TropicalWeight::one())?;
+        fst2.add_tr(0, Tr::new(11, 11, 0.5, 1))?;
+        fst2.add_tr(1, Tr::new(12, 12, 0.5, 2))?;
+        fst2.add_tr(0, Tr::new(13, 13, 0.5, 2))?;
+
+        let expected: F = compose(fst1.clone(), fst2.clone())?;
+
+        let graph1look = Arc::new(TLaFst::new_with_relabeling(fst1, &mut fst2, true)?);
+        tr_sort(&mut fst2, ILabelCompare {});
+        let fst2 = Arc::new(fst2);
+
+        let matcher1 = TMatcher1::new_with_data(
+            Arc::clone(&graph1look),
+            MatchType::MatchOutput,
+            graph1look.data(MatchType::MatchOutput).cloned(),
+        )?;
+        let matcher2 = TMatcher2::new(Arc::clone(&fst2), MatchType::MatchInput)?;
+        let compose_filter = TLookFilter::new(
+            Arc::clone(&graph1look),
+            Arc::clone(&fst2),
+            Some(matcher1),
+            Some(matcher2),
+        )?;
+        let compose_options = ComposeFstOpOptions::new(None, None, compose_filter, None);
+        let dyn_fst =
+            ComposeFst::<_, _, _, _, _, _, _, _, SimpleHashMapCache<_>>::new_with_options(
+                graph1look,
+                fst2,
+                compose_options,
+            )?;
+        let mut res: F = dyn_fst.compute()?;
+        connect(&mut res)?;
+
+        assert!(!paths(&expected).is_empty());
+        assert_eq!(paths(&res), paths(&expected));
+        Ok(())
+    }
+}
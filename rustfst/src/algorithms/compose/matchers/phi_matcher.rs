@@ -0,0 +1,441 @@
+use std::borrow::Borrow;
+use std::fmt::Debug;
+use std::iter::Peekable;
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use anyhow::Result;
+
+use crate::algorithms::compose::matchers::{
+    IterItemMatcher, MatchType, Matcher, MatcherFlags, MatcherRewriteMode, REQUIRE_PRIORITY,
+};
+use crate::fst_properties::FstProperties;
+use crate::fst_traits::Fst;
+use crate::{Label, Semiring, StateId, Tr, EPS_LABEL, NO_LABEL};
+
+/// Matcher handling failure transitions. A transition labeled with `phi_label` is followed
+/// only when no transition of the state matches the requested label, without consuming it.
+/// Typically used for the backoff transitions of n-gram language models.
+///
+/// The weights of the followed failure transitions are multiplied with the weight of the
+/// matching transition. The final weight of a state is computed the same way when the state
+/// is not final. When `phi_loop` is set, a failure self-loop matches any label and consumes it.
+///
+/// The wrapped matcher must have at most one failure transition per state and the failure
+/// transitions must not form a cycle (apart from self-loops), otherwise an error is returned.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PhiMatcher<W, F, B, M>
+where
+    W: Semiring,
+    F: Fst<W>,
+    B: Borrow<F>,
+    M: Matcher<W, F, B>,
+{
+    match_type: MatchType,
+    w: PhantomData<(W, F, B)>,
+    phi_label: Label,
+    phi_loop: bool,
+    matcher: Arc<M>,
+    rewrite_both: bool,
+}
+
+impl<W, F, B, M> PhiMatcher<W, F, B, M>
+where
+    W: Semiring,
+    F: Fst<W>,
+    B: Borrow<F>,
+    M: Matcher<W, F, B>,
+{
+    pub fn new(
+        match_type: MatchType,
+        phi_label: Label,
+        phi_loop: bool,
+        rewrite_mode: MatcherRewriteMode,
+        matcher: Arc<M>,
+    ) -> Result<Self> {
+        if match_type == MatchType::MatchBoth {
+            bail!("PhiMatcher: Bad match type")
+        }
+        if phi_label == EPS_LABEL {
+            bail!("PhiMatcher: {} cannot be used as phi_label", EPS_LABEL)
+        }
+        let rewrite_both = match rewrite_mode {
+            MatcherRewriteMode::MatcherRewriteAuto => matcher
+                .fst()
+                .borrow()
+                .properties()
+                .contains(FstProperties::ACCEPTOR),
+            MatcherRewriteMode::MatcherRewriteAlways => true,
+            MatcherRewriteMode::MatcherRewriteNever => false,
+        };
+        Ok(Self {
+            match_type,
+            w: PhantomData,
+            phi_label,
+            phi_loop,
+            matcher,
+            rewrite_both,
+        })
+    }
+
+    pub fn phi_label(&self) -> Label {
+        self.phi_label
+    }
+
+    /// Returns the failure transition leaving `state` if any.
+    fn phi_tr(&self, state: StateId) -> Result<Option<Tr<W>>> {
+        let mut it = self.matcher.iter(state, self.phi_label)?;
+        let phi_tr = match it.next() {
+            Some(e) => e.into_tr(state, self.match_type)?,
+            None => return Ok(None),
+        };
+        if it.next().is_some() {
+            bail!("PhiMatcher: Phi non-determinism not supported")
+        }
+        Ok(Some(phi_tr))
+    }
+
+    fn rewrite_phi_tr(&self, mut phi_tr: Tr<W>, phi_match: Label) -> Tr<W> {
+        if self.rewrite_both {
+            if phi_tr.ilabel == self.phi_label {
+                phi_tr.ilabel = phi_match;
+            }
+            if phi_tr.olabel == self.phi_label {
+                phi_tr.olabel = phi_match;
+            }
+        } else if self.match_type == MatchType::MatchInput {
+            phi_tr.ilabel = phi_match;
+        } else {
+            phi_tr.olabel = phi_match;
+        }
+        phi_tr
+    }
+}
+
+impl<W, F, B, M> Matcher<W, F, B> for PhiMatcher<W, F, B, M>
+where
+    W: Semiring,
+    F: Fst<W>,
+    B: Borrow<F> + Debug,
+    M: Matcher<W, F, B>,
+{
+    type Iter = IteratorPhiMatcher<W, Peekable<M::Iter>>;
+
+    fn new(_fst: B, _match_type: MatchType) -> Result<Self>
+    where
+        Self: Sized,
+    {
+        bail!("This constructor can't be used for initializing PhiMatcher.")
+    }
+
+    fn iter(&self, state: StateId, label: Label) -> Result<Self::Iter> {
+        if label == self.phi_label && self.phi_label != NO_LABEL {
+            bail!("PhiMatcher::Find: bad label (phi)")
+        }
+        if self.phi_label == NO_LABEL || label == EPS_LABEL || label == NO_LABEL {
+            return Ok(IteratorPhiMatcher::from_matcher(
+                self.matcher.iter(state, label)?.peekable(),
+            ));
+        }
+
+        let mut s = state;
+        let mut phi_weight = W::one();
+        let mut cycle_detector = PhiCycleDetector::new(s);
+        loop {
+            let mut matched = self.matcher.iter(s, label)?.peekable();
+            if matched.peek().is_some() {
+                if s == state {
+                    return Ok(IteratorPhiMatcher::from_matcher(matched));
+                }
+                let trs = matched
+                    .map(|e| {
+                        let mut tr = e.into_tr(s, self.match_type)?;
+                        tr.weight = phi_weight.times(&tr.weight)?;
+                        Ok(IterItemMatcher::Tr(tr))
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                return Ok(IteratorPhiMatcher::from_trs(trs));
+            }
+
+            let mut phi_tr = match self.phi_tr(s)? {
+                Some(phi_tr) => phi_tr,
+                None => return Ok(IteratorPhiMatcher::from_trs(vec![])),
+            };
+            if phi_tr.nextstate == s {
+                if !self.phi_loop {
+                    return Ok(IteratorPhiMatcher::from_trs(vec![]));
+                }
+                phi_tr.weight = phi_weight.times(&phi_tr.weight)?;
+                let phi_tr = self.rewrite_phi_tr(phi_tr, label);
+                return Ok(IteratorPhiMatcher::from_trs(vec![IterItemMatcher::Tr(
+                    phi_tr,
+                )]));
+            }
+            phi_weight.times_assign(&phi_tr.weight)?;
+            s = phi_tr.nextstate;
+            if cycle_detector.closes_cycle(s) {
+                bail!("PhiMatcher: Cycle of phi transitions reaching state {}", s)
+            }
+        }
+    }
+
+    fn final_weight(&self, state: StateId) -> Result<Option<W>> {
+        let final_weight = self.matcher.final_weight(state)?;
+        if self.phi_label == NO_LABEL || final_weight.is_some() {
+            return Ok(final_weight);
+        }
+        let mut s = state;
+        let mut weight = W::one();
+        let mut cycle_detector = PhiCycleDetector::new(s);
+        while let Some(phi_tr) = self.phi_tr(s)? {
+            // Phi self-loops are not followed.
+            if phi_tr.nextstate == s || cycle_detector.closes_cycle(phi_tr.nextstate) {
+                return Ok(None);
+            }
+            weight.times_assign(&phi_tr.weight)?;
+            s = phi_tr.nextstate;
+            if let Some(final_weight) = self.matcher.final_weight(s)? {
+                return Ok(Some(weight.times(final_weight)?));
+            }
+        }
+        Ok(None)
+    }
+
+    fn match_type(&self, test: bool) -> Result<MatchType> {
+        self.matcher.match_type(test)
+    }
+
+    fn flags(&self) -> MatcherFlags {
+        if self.phi_label == NO_LABEL || self.match_type == MatchType::MatchNone {
+            self.matcher.flags()
+        } else {
+            self.matcher.flags() | MatcherFlags::REQUIRE_MATCH
+        }
+    }
+
+    fn priority(&self, state: StateId) -> Result<usize> {
+        if self.phi_label != NO_LABEL && self.matcher.iter(state, self.phi_label)?.next().is_some()
+        {
+            Ok(REQUIRE_PRIORITY)
+        } else {
+            self.matcher.priority(state)
+        }
+    }
+
+    fn fst(&self) -> &B {
+        self.matcher.fst()
+    }
+}
+
+/// Detects a cycle of failure transitions without storing the visited states, using Brent's
+/// algorithm : the states reached are compared with a saved state that is moved forward after
+/// a number of steps doubling each time.
+struct PhiCycleDetector {
+    saved_state: StateId,
+    power: usize,
+    steps: usize,
+}
+
+impl PhiCycleDetector {
+    fn new(state: StateId) -> Self {
+        Self {
+            saved_state: state,
+            power: 1,
+            steps: 0,
+        }
+    }
+
+    /// Registers that `state` is reached. Returns true if a cycle is detected.
+    fn closes_cycle(&mut self, state: StateId) -> bool {
+        if state == self.saved_state {
+            return true;
+        }
+        self.steps += 1;
+        if self.steps == self.power {
+            self.saved_state = state;
+            self.power *= 2;
+            self.steps = 0;
+        }
+        false
+    }
+}
+
+/// Iterator over the transitions matched by a [`PhiMatcher`]. When no failure transition had
+/// to be followed, the iterator of the wrapped matcher is used as is.
+pub struct IteratorPhiMatcher<W: Semiring, I: Iterator<Item = IterItemMatcher<W>>> {
+    matcher_iterator: Option<I>,
+    trs: std::vec::IntoIter<IterItemMatcher<W>>,
+}
+
+impl<W: Semiring, I: Iterator<Item = IterItemMatcher<W>>> IteratorPhiMatcher<W, I> {
+    fn from_matcher(matcher_iterator: I) -> Self {
+        Self {
+            matcher_iterator: Some(matcher_iterator),
+            trs: vec![].into_iter(),
+        }
+    }
+
+    fn from_trs(trs: Vec<IterItemMatcher<W>>) -> Self {
+        Self {
+            matcher_iterator: None,
+            trs: trs.into_iter(),
+        }
+    }
+}
+
+impl<W: Semiring, I: Iterator<Item = IterItemMatcher<W>>> Iterator for IteratorPhiMatcher<W, I> {
+    type Item = IterItemMatcher<W>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.matcher_iterator {
+            Some(it) => it.next(),
+            None => self.trs.next(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::algorithms::compose::matchers::test_utils::compose_with_matcher2;
+    use crate::algorithms::compose::matchers::SortedMatcher;
+    use crate::algorithms::compose::{
+        compose_with_config, ComposeConfig, ComposeFilterEnum, MatcherConfig, PhiMatcherConfig,
+    };
+    use crate::algorithms::tr_compares::ILabelCompare;
+    use crate::algorithms::tr_sort;
+    use crate::fst_impls::VectorFst;
+    use crate::fst_traits::MutableFst;
+    use crate::semirings::TropicalWeight;
+    use crate::utils::acceptor;
+
+    use super::*;
+
+    // Labels of the backoff model.
+    const A: Label = 1;
+    const B: Label = 2;
+    const C: Label = 3;
+    const PHI: Label = 4;
+
+    // Bigram-like model with a backoff from state 0 to the unigram state 1.
+    fn backoff_fst(phi_self_loop: bool) -> Result<VectorFst<TropicalWeight>> {
+        let mut fst = VectorFst::new();
+        fst.add_states(2);
+        fst.set_start(0)?;
+        fst.emplace_tr(0, A, A, 0.5, 0)?;
+        fst.emplace_tr(0, PHI, PHI, 1.0, 1)?;
+        fst.emplace_tr(1, A, A, 2.0, 0)?;
+        fst.emplace_tr(1, B, B, 3.0, 1)?;
+        if phi_self_loop {
+            fst.emplace_tr(1, PHI, PHI, 0.25, 1)?;
+        }
+        fst.set_final(1, 0.5)?;
+        tr_sort(&mut fst, ILabelCompare {});
+        Ok(fst)
+    }
+
+    fn compose_phi(
+        query: &[Label],
+        fst: VectorFst<TropicalWeight>,
+        phi_loop: bool,
+    ) -> Result<VectorFst<TropicalWeight>> {
+        let q_fst: VectorFst<TropicalWeight> = acceptor(query, TropicalWeight::one());
+        let matcher = PhiMatcher::new(
+            MatchType::MatchInput,
+            PHI,
+            phi_loop,
+            MatcherRewriteMode::MatcherRewriteAuto,
+            Arc::new(SortedMatcher::new(fst.clone(), MatchType::MatchInput)?),
+        )?;
+        compose_with_matcher2(q_fst, fst, matcher)
+    }
+
+    #[test]
+    fn test_phi_matcher_backoff() -> Result<()> {
+        let composed = compose_phi(&[A, B], backoff_fst(false)?, false)?;
+        let paths: Vec<_> = composed.paths_iter().collect();
+        assert_eq!(paths.len(), 1);
+        assert_eq!(paths[0].ilabels, vec![A, B]);
+        assert_eq!(paths[0].olabels, vec![A, B]);
+        // a: 0.5, backoff: 1.0, b: 3.0, final: 0.5
+        assert_eq!(paths[0].weight, TropicalWeight::new(5.0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_phi_matcher_final_weight_through_backoff() -> Result<()> {
+        let composed = compose_phi(&[A], backoff_fst(false)?, false)?;
+        let paths: Vec<_> = composed.paths_iter().collect();
+        assert_eq!(paths.len(), 1);
+        // a: 0.5, backoff: 1.0, final: 0.5
+        assert_eq!(paths[0].weight, TropicalWeight::new(2.0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_phi_matcher_no_match() -> Result<()> {
+        let composed = compose_phi(&[A, C], backoff_fst(false)?, false)?;
+        assert_eq!(composed.paths_iter().count(), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_phi_matcher_phi_loop() -> Result<()> {
+        let composed = compose_phi(&[A, C], backoff_fst(true)?, true)?;
+        let paths: Vec<_> = composed.paths_iter().collect();
+        assert_eq!(paths.len(), 1);
+        assert_eq!(paths[0].ilabels, vec![A, C]);
+        assert_eq!(paths[0].olabels, vec![A, C]);
+        // a: 0.5, backoff: 1.0, phi loop: 0.25, final: 0.5
+        assert_eq!(paths[0].weight, TropicalWeight::new(2.25));
+
+        let composed = compose_phi(&[A, C], backoff_fst(true)?, false)?;
+        assert_eq!(composed.paths_iter().count(), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_phi_matcher_compose_config() -> Result<()> {
+        let q_fst: VectorFst<TropicalWeight> = acceptor(&[A, B], TropicalWeight::one());
+        let config = ComposeConfig {
+            compose_filter: ComposeFilterEnum::SequenceFilter,
+            matcher2_config: MatcherConfig {
+                phi_matcher_config: Some(PhiMatcherConfig {
+                    phi_label: PHI,
+                    phi_loop: false,
+                    rewrite_mode: MatcherRewriteMode::MatcherRewriteAuto,
+                }),
+                ..MatcherConfig::default()
+            },
+            ..ComposeConfig::default()
+        };
+        let composed: VectorFst<_> = compose_with_config::<_, VectorFst<_>, VectorFst<_>, _, _, _>(
+            &q_fst,
+            &backoff_fst(false)?,
+            config,
+        )?;
+        let paths: Vec<_> = composed.paths_iter().collect();
+        assert_eq!(paths.len(), 1);
+        assert_eq!(paths[0].ilabels, vec![A, B]);
+        assert_eq!(paths[0].weight, TropicalWeight::new(5.0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_phi_matcher_cycle() -> Result<()> {
+        let mut fst = VectorFst::<TropicalWeight>::new();
+        fst.add_states(2);
+        fst.set_start(0)?;
+        fst.emplace_tr(0, PHI, PHI, 1.0, 1)?;
+        fst.emplace_tr(1, PHI, PHI, 1.0, 0)?;
+        fst.set_final(1, 0.0)?;
+        let matcher = PhiMatcher::new(
+            MatchType::MatchInput,
+            PHI,
+            true,
+            MatcherRewriteMode::MatcherRewriteAuto,
+            Arc::new(SortedMatcher::new(fst, MatchType::MatchInput)?),
+        )?;
+        assert!(matcher.iter(0, A).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,308 @@
+use std::borrow::Borrow;
+use std::fmt::Debug;
+use std::iter::Peekable;
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use anyhow::Result;
+
+use crate::algorithms::compose::matchers::{
+    IterItemMatcher, MatchType, Matcher, MatcherFlags, MatcherRewriteMode, REQUIRE_PRIORITY,
+};
+use crate::fst_properties::FstProperties;
+use crate::fst_traits::Fst;
+use crate::{Label, Semiring, StateId, EPS_LABEL, NO_LABEL};
+
+/// Matcher handling "rest" transitions. A transition labeled with `rho_label` matches, and
+/// consumes, any label that no other transition of the state matches. The matched label
+/// replaces `rho_label` on the returned transitions.
+///
+/// Epsilon transitions are never matched by a rho transition: they are handled by the
+/// composition filter as usual. As the wrapped matcher is queried for each label, it imposes
+/// its own requirements : with a `SortedMatcher`, the FST must be sorted by input labels
+/// (`ILabelCompare`) for `MatchInput` and by output labels (`OLabelCompare`) for `MatchOutput`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RhoMatcher<W, F, B, M>
+where
+    W: Semiring,
+    F: Fst<W>,
+    B: Borrow<F>,
+    M: Matcher<W, F, B>,
+{
+    match_type: MatchType,
+    w: PhantomData<(W, F, B)>,
+    rho_label: Label,
+    matcher: Arc<M>,
+    rewrite_both: bool,
+}
+
+impl<W, F, B, M> RhoMatcher<W, F, B, M>
+where
+    W: Semiring,
+    F: Fst<W>,
+    B: Borrow<F>,
+    M: Matcher<W, F, B>,
+{
+    pub fn new(
+        match_type: MatchType,
+        rho_label: Label,
+        rewrite_mode: MatcherRewriteMode,
+        matcher: Arc<M>,
+    ) -> Result<Self> {
+        if match_type == MatchType::MatchBoth {
+            bail!("RhoMatcher: Bad match type")
+        }
+        if rho_label == EPS_LABEL {
+            bail!("RhoMatcher: {} cannot be used as rho_label", EPS_LABEL)
+        }
+        let rewrite_both = match rewrite_mode {
+            MatcherRewriteMode::MatcherRewriteAuto => matcher
+                .fst()
+                .borrow()
+                .properties()
+                .contains(FstProperties::ACCEPTOR),
+            MatcherRewriteMode::MatcherRewriteAlways => true,
+            MatcherRewriteMode::MatcherRewriteNever => false,
+        };
+        Ok(Self {
+            match_type,
+            w: PhantomData,
+            rho_label,
+            matcher,
+            rewrite_both,
+        })
+    }
+
+    pub fn rho_label(&self) -> Label {
+        self.rho_label
+    }
+}
+
+impl<W, F, B, M> Matcher<W, F, B> for RhoMatcher<W, F, B, M>
+where
+    W: Semiring,
+    F: Fst<W>,
+    B: Borrow<F> + Debug,
+    M: Matcher<W, F, B>,
+{
+    type Iter = IteratorRhoMatcher<W, Peekable<M::Iter>>;
+
+    fn new(_fst: B, _match_type: MatchType) -> Result<Self>
+    where
+        Self: Sized,
+    {
+        bail!("This constructor can't be used for initializing RhoMatcher.")
+    }
+
+    fn iter(&self, state: StateId, label: Label) -> Result<Self::Iter> {
+        if label == self.rho_label && self.rho_label != NO_LABEL {
+            bail!("RhoMatcher::Find: bad label (rho)")
+        }
+        let mut matcher_iterator = self.matcher.iter(state, label)?.peekable();
+        let rho_match = if matcher_iterator.peek().is_some()
+            || self.rho_label == NO_LABEL
+            || label == EPS_LABEL
+            || label == NO_LABEL
+        {
+            None
+        } else {
+            matcher_iterator = self.matcher.iter(state, self.rho_label)?.peekable();
+            Some(label)
+        };
+        Ok(IteratorRhoMatcher {
+            matcher_iterator,
+            rho_match,
+            rho_label: self.rho_label,
+            match_type: self.match_type,
+            rewrite_both: self.rewrite_both,
+            w: PhantomData,
+        })
+    }
+
+    fn final_weight(&self, state: StateId) -> Result<Option<W>> {
+        self.matcher.final_weight(state)
+    }
+
+    fn match_type(&self, test: bool) -> Result<MatchType> {
+        self.matcher.match_type(test)
+    }
+
+    fn flags(&self) -> MatcherFlags {
+        if self.rho_label == NO_LABEL || self.match_type == MatchType::MatchNone {
+            self.matcher.flags()
+        } else {
+            self.matcher.flags() | MatcherFlags::REQUIRE_MATCH
+        }
+    }
+
+    fn priority(&self, state: StateId) -> Result<usize> {
+        if self.rho_label != NO_LABEL && self.matcher.iter(state, self.rho_label)?.next().is_some()
+        {
+            Ok(REQUIRE_PRIORITY)
+        } else {
+            self.matcher.priority(state)
+        }
+    }
+
+    fn fst(&self) -> &B {
+        self.matcher.fst()
+    }
+}
+
+/// Iterator over the transitions matched by a [`RhoMatcher`]. When the rho transitions are
+/// used, `rho_match` holds the matched label.
+pub struct IteratorRhoMatcher<W: Semiring, I: Iterator<Item = IterItemMatcher<W>>> {
+    matcher_iterator: I,
+    rho_match: Option<Label>,
+    rho_label: Label,
+    match_type: MatchType,
+    rewrite_both: bool,
+    w: PhantomData<W>,
+}
+
+impl<W: Semiring, I: Iterator<Item = IterItemMatcher<W>>> Iterator for IteratorRhoMatcher<W, I> {
+    type Item = IterItemMatcher<W>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self.matcher_iterator.next()?;
+        let rho_match = match self.rho_match {
+            Some(rho_match) => rho_match,
+            None => return Some(item),
+        };
+        let mut rho_tr = match item {
+            IterItemMatcher::Tr(tr) => tr,
+            IterItemMatcher::EpsLoop => return Some(IterItemMatcher::EpsLoop),
+        };
+        if self.rewrite_both {
+            if rho_tr.ilabel == self.rho_label {
+                rho_tr.ilabel = rho_match;
+            }
+            if rho_tr.olabel == self.rho_label {
+                rho_tr.olabel = rho_match;
+            }
+        } else if self.match_type == MatchType::MatchInput {
+            rho_tr.ilabel = rho_match;
+        } else {
+            rho_tr.olabel = rho_match;
+        }
+        Some(IterItemMatcher::Tr(rho_tr))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::algorithms::compose::matchers::test_utils::compose_with_matcher2;
+    use crate::algorithms::compose::matchers::{SigmaMatcher, SortedMatcher};
+    use crate::algorithms::compose::{
+        compose_with_config, ComposeConfig, ComposeFilterEnum, MatcherConfig, RhoMatcherConfig,
+    };
+    use crate::algorithms::tr_compares::ILabelCompare;
+    use crate::algorithms::tr_sort;
+    use crate::fst_impls::VectorFst;
+    use crate::fst_traits::MutableFst;
+    use crate::semirings::TropicalWeight;
+    use crate::utils::acceptor;
+
+    use super::*;
+
+    const PLAY: Label = 1;
+    const QUEEN: Label = 2;
+    const BOWIE: Label = 3;
+    const PLEASE: Label = 4;
+    const WILDCARD: Label = 5;
+
+    // "play queen please" has its own transition, any other artist goes through the wildcard.
+    fn grammar_fst() -> Result<VectorFst<TropicalWeight>> {
+        let mut fst = VectorFst::new();
+        fst.add_states(4);
+        fst.set_start(0)?;
+        fst.emplace_tr(0, PLAY, PLAY, 0.0, 1)?;
+        fst.emplace_tr(1, QUEEN, QUEEN, 1.0, 2)?;
+        fst.emplace_tr(1, WILDCARD, WILDCARD, 2.0, 2)?;
+        fst.emplace_tr(2, PLEASE, PLEASE, 0.0, 3)?;
+        fst.set_final(3, 0.0)?;
+        tr_sort(&mut fst, ILabelCompare {});
+        Ok(fst)
+    }
+
+    fn compose_with<M2>(query: &[Label], matcher: M2) -> Result<VectorFst<TropicalWeight>>
+    where
+        M2: Matcher<TropicalWeight, VectorFst<TropicalWeight>, VectorFst<TropicalWeight>>,
+    {
+        let q_fst: VectorFst<TropicalWeight> = acceptor(query, TropicalWeight::one());
+        compose_with_matcher2(q_fst, grammar_fst()?, matcher)
+    }
+
+    fn compose_rho(query: &[Label]) -> Result<VectorFst<TropicalWeight>> {
+        let matcher = RhoMatcher::new(
+            MatchType::MatchInput,
+            WILDCARD,
+            MatcherRewriteMode::MatcherRewriteAuto,
+            Arc::new(SortedMatcher::new(grammar_fst()?, MatchType::MatchInput)?),
+        )?;
+        compose_with(query, matcher)
+    }
+
+    #[test]
+    fn test_rho_matcher_rest() -> Result<()> {
+        let composed = compose_rho(&[PLAY, BOWIE, PLEASE])?;
+        let paths: Vec<_> = composed.paths_iter().collect();
+        assert_eq!(paths.len(), 1);
+        assert_eq!(paths[0].ilabels, vec![PLAY, BOWIE, PLEASE]);
+        assert_eq!(paths[0].olabels, vec![PLAY, BOWIE, PLEASE]);
+        assert_eq!(paths[0].weight, TropicalWeight::new(2.0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_rho_matcher_explicit_label_wins() -> Result<()> {
+        let composed = compose_rho(&[PLAY, QUEEN, PLEASE])?;
+        let paths: Vec<_> = composed.paths_iter().collect();
+        assert_eq!(paths.len(), 1);
+        assert_eq!(paths[0].weight, TropicalWeight::new(1.0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_rho_matcher_compose_config() -> Result<()> {
+        let q_fst: VectorFst<TropicalWeight> =
+            acceptor(&[PLAY, BOWIE, PLEASE], TropicalWeight::one());
+        let config = ComposeConfig {
+            compose_filter: ComposeFilterEnum::SequenceFilter,
+            matcher2_config: MatcherConfig {
+                rho_matcher_config: Some(RhoMatcherConfig {
+                    rho_label: WILDCARD,
+                    rewrite_mode: MatcherRewriteMode::MatcherRewriteAuto,
+                }),
+                ..MatcherConfig::default()
+            },
+            ..ComposeConfig::default()
+        };
+        let composed: VectorFst<_> = compose_with_config::<_, VectorFst<_>, VectorFst<_>, _, _, _>(
+            &q_fst,
+            &grammar_fst()?,
+            config,
+        )?;
+        let paths: Vec<_> = composed.paths_iter().collect();
+        assert_eq!(paths.len(), 1);
+        assert_eq!(paths[0].olabels, vec![PLAY, BOWIE, PLEASE]);
+        assert_eq!(paths[0].weight, TropicalWeight::new(2.0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_sigma_matcher_matches_explicit_label_too() -> Result<()> {
+        let matcher = SigmaMatcher::new(
+            MatchType::MatchInput,
+            WILDCARD,
+            MatcherRewriteMode::MatcherRewriteAuto,
+            Arc::new(SortedMatcher::new(grammar_fst()?, MatchType::MatchInput)?),
+            None,
+        )?;
+        let composed = compose_with(&[PLAY, QUEEN, PLEASE], matcher)?;
+        let mut weights: Vec<_> = composed.paths_iter().map(|p| *p.weight.value()).collect();
+        weights.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        assert_eq!(weights, vec![1.0, 2.0]);
+        Ok(())
+    }
+}
@@ -14,6 +14,15 @@ use crate::fst_traits::Fst;
 use crate::{Label, Semiring, StateId, Tr, EPS_LABEL, NO_LABEL};
 use std::collections::HashSet;
 
+/// Matcher handling "any" transitions. A transition labeled with `sigma_label` matches, and
+/// consumes, any label, in addition to the transitions explicitly labeled with it. The matched
+/// label replaces `sigma_label` on the returned transitions. If `sigma_allowed_matches` is set,
+/// only the labels it contains are matched by the sigma transitions.
+///
+/// Epsilon transitions are never matched by a sigma transition: they are handled by the
+/// composition filter as usual. As the wrapped matcher is queried for each label, it imposes
+/// its own requirements : with a `SortedMatcher`, the FST must be sorted by input labels
+/// (`ILabelCompare`) for `MatchInput` and by output labels (`OLabelCompare`) for `MatchOutput`.
 #[derive(Debug, Clone, PartialEq)]
 pub struct SigmaMatcher<W, F, B, M>
 where
@@ -165,6 +174,7 @@ where
     sigma_match: Option<Label>,
     matcher_iterator: Peekable<M::Iter>,
     has_sigma: bool,
+    match_label_allowed: bool,
     rewrite_both: bool,
     w: PhantomData<(W, F, B)>,
 }
@@ -202,6 +212,7 @@ where
 
         let mut find_empty = false;
         let has_sigma = has_sigma(state, &matcher, sigma_label)?;
+        let match_label_allowed = is_match_label_allowed(sigma_allowed_matches, match_label);
 
         let mut matcher_iterator_match_label = matcher.iter(state, match_label)?.peekable();
         let (sigma_match, matcher_iterator) = if matcher_iterator_match_label.peek().is_some() {
@@ -211,7 +222,7 @@ where
             if has_sigma
                 && match_label != EPS_LABEL
                 && match_label != NO_LABEL
-                && is_match_label_allowed(sigma_allowed_matches, match_label)
+                && match_label_allowed
                 && matcher_iterator_sigma_label.peek().is_some()
             {
                 (Some(match_label), matcher_iterator_sigma_label)
@@ -233,6 +244,7 @@ where
             sigma_match,
             matcher_iterator,
             has_sigma,
+            match_label_allowed,
             rewrite_both,
             w: PhantomData,
         })
@@ -268,11 +280,15 @@ where
     }
 
     pub fn next_openfst(&mut self) {
-        let r = self.matcher_iterator.next();
-        if r.is_none()
+        self.matcher_iterator.next();
+        // Once the transitions explicitly labeled with the match label are exhausted, the
+        // sigma transitions are matched too.
+        if self.matcher_iterator.peek().is_none()
             && self.has_sigma
             && self.sigma_match.unwrap() == NO_LABEL
-            && self.match_label > 0
+            && self.match_label != EPS_LABEL
+            && self.match_label != NO_LABEL
+            && self.match_label_allowed
         {
             self.matcher_iterator = self
                 .matcher
@@ -1,6 +1,7 @@
 use std::borrow::Borrow;
 use std::fmt::Debug;
 use std::marker::PhantomData;
+use std::ops::Range;
 use std::sync::Arc;
 
 use anyhow::Result;
@@ -97,6 +98,26 @@ where
     }
 }
 
+impl<W, F, B> SortedMatcher<W, F, B>
+where
+    W: Semiring,
+    F: Fst<W>,
+    B: Borrow<F> + Debug,
+{
+    /// Returns the range of the positions, in the trs of `state`, of all the transitions whose
+    /// matched label is `label`. As the trs are sorted, transitions sharing the same label are
+    /// contiguous : the range covers all of them, it is empty if there is none.
+    pub fn match_range(&self, state: StateId, label: Label) -> Result<Range<usize>> {
+        let trs = self.fst.borrow().get_trs(state)?;
+        let range = match self.match_type {
+            MatchType::MatchInput => trs.trs().equal_range_by(|x| x.ilabel.cmp(&label)),
+            MatchType::MatchOutput => trs.trs().equal_range_by(|x| x.olabel.cmp(&label)),
+            _ => bail!("Unsupported match_type : {:?}", self.match_type),
+        };
+        Ok(range)
+    }
+}
+
 pub struct IteratorSortedMatcher<W: Semiring, T: Trs<W>> {
     trs: T,
     match_label: Label,
@@ -239,3 +260,44 @@ where
         unreachable!()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::compose::compose;
+    use crate::fst_impls::VectorFst;
+    use crate::fst_traits::MutableFst;
+    use crate::semirings::TropicalWeight;
+    use crate::utils::transducer;
+
+    #[test]
+    fn test_sorted_matcher_ties() -> Result<()> {
+        let mut fst = VectorFst::<TropicalWeight>::new();
+        fst.add_states(3);
+        fst.set_start(0)?;
+        fst.set_final(1, TropicalWeight::one())?;
+        fst.set_final(2, TropicalWeight::one())?;
+        fst.add_tr(0, Tr::new(1, 5, 1.0, 1))?;
+        fst.add_tr(0, Tr::new(2, 6, 2.0, 1))?;
+        fst.add_tr(0, Tr::new(2, 7, 3.0, 2))?;
+        fst.add_tr(0, Tr::new(3, 8, 4.0, 2))?;
+
+        let matcher = SortedMatcher::<_, VectorFst<_>, _>::new(&fst, MatchType::MatchInput)?;
+        let trs: Vec<_> = matcher
+            .iter(0, 2)?
+            .map(|item| item.into_tr(0, MatchType::MatchInput))
+            .collect::<Result<_>>()?;
+        assert_eq!(trs, vec![Tr::new(2, 6, 2.0, 1), Tr::new(2, 7, 3.0, 2)]);
+        assert_eq!(matcher.match_range(0, 2)?, 1..3);
+        assert_eq!(matcher.match_range(0, 4)?, 4..4);
+
+        // Composition keeps both parallel transitions matching label 2.
+        let fst_in: VectorFst<TropicalWeight> = transducer(&[2], &[2], TropicalWeight::one());
+        let res: VectorFst<TropicalWeight> = compose(fst_in, fst)?;
+        let mut olabels: Vec<_> = res.paths_iter().map(|p| p.olabels).collect();
+        olabels.sort();
+        assert_eq!(olabels, vec![vec![6], vec![7]]);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,31 @@
+use anyhow::Result;
+
+use crate::algorithms::compose::compose_filters::SequenceComposeFilterBuilder;
+use crate::algorithms::compose::matchers::{Matcher, SortedMatcher};
+use crate::algorithms::compose::{ComposeFst, ComposeFstOpOptions};
+use crate::fst_impls::VectorFst;
+use crate::semirings::TropicalWeight;
+
+/// Lazily composes `fst1` and `fst2` with the default matcher on `fst1` and `matcher2` on
+/// `fst2`, then computes the result without connecting it.
+pub(crate) fn compose_with_matcher2<M2>(
+    fst1: VectorFst<TropicalWeight>,
+    fst2: VectorFst<TropicalWeight>,
+    matcher2: M2,
+) -> Result<VectorFst<TropicalWeight>>
+where
+    M2: Matcher<TropicalWeight, VectorFst<TropicalWeight>, VectorFst<TropicalWeight>>,
+{
+    let compose_fst_op_opts = ComposeFstOpOptions::new(None, matcher2, None, None);
+    let compose_lazy = ComposeFst::<
+        _,                                                 // W
+        _,                                                 // F1
+        _,                                                 // F2
+        _,                                                 // B1
+        _,                                                 // B2
+        SortedMatcher<_, _, _>,                            // M1
+        M2,                                                // M2
+        SequenceComposeFilterBuilder<_, _, _, _, _, _, _>, // CFB
+    >::new_with_options(fst1, fst2, compose_fst_op_opts)?;
+    compose_lazy.compute()
+}
@@ -1,11 +1,15 @@
+pub use self::acceptor_intersect::acceptor_intersect;
 pub use self::add_on::FstAddOn;
+pub use self::compose_converting::compose_converting;
 pub use self::compose_fst::ComposeFst;
 pub use self::compose_fst_op::{ComposeFstOp, ComposeFstOpState};
 pub use self::compose_fst_op_options::ComposeFstOpOptions;
+#[cfg(feature = "rayon")]
+pub use self::compose_parallel::compose_parallel;
 pub use self::compose_state_tuple::ComposeStateTuple;
 pub use self::compose_static::{
-    compose, compose_with_config, ComposeConfig, ComposeFilterEnum, MatcherConfig,
-    SigmaMatcherConfig,
+    compose, compose_with_config, ComposeConfig, ComposeError, ComposeFilterEnum, MatcherConfig,
+    PhiMatcherConfig, RhoMatcherConfig, SigmaMatcherConfig,
 };
 pub use self::interval_reach_visitor::IntervalReachVisitor;
 pub use self::interval_set::{IntInterval, IntervalSet};
@@ -20,9 +24,13 @@ pub mod lookahead_filters;
 pub mod lookahead_matchers;
 pub mod matchers;
 
+mod acceptor_intersect;
 mod add_on;
+mod compose_converting;
 mod compose_fst;
 mod compose_fst_op;
+#[cfg(feature = "rayon")]
+mod compose_parallel;
 mod compose_state_tuple;
 mod compose_static;
 mod interval_reach_visitor;
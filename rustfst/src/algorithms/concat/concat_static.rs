@@ -4,7 +4,7 @@ use crate::fst_properties::mutable_properties::concat_properties;
 use crate::fst_properties::FstProperties;
 use crate::fst_traits::{AllocableFst, ExpandedFst, MutableFst};
 use crate::semirings::Semiring;
-use crate::{StateId, Tr, Trs, EPS_LABEL};
+use crate::{Label, StateId, Tr, Trs, EPS_LABEL};
 
 /// Performs the concatenation of two wFSTs. If `A` transduces string `x` to `y` with weight `a`
 /// and `B` transduces string `w` to `v` with weight `b`, then their concatenation
@@ -107,3 +107,114 @@ where
 
     Ok(())
 }
+
+/// Concatenates a sequence of wFSTs, inserting a transition labeled `sep` on both sides and
+/// weighted by `weight` between two consecutive operands.
+///
+/// No separator is added before the first operand nor after the last one. With a single
+/// operand, a copy of it is returned. With no operand, the returned FST only accepts the
+/// empty string.
+///
+/// # Example
+/// ```
+/// # use rustfst::utils::acceptor;
+/// # use rustfst::semirings::{Semiring, TropicalWeight};
+/// # use rustfst::fst_impls::VectorFst;
+/// # use rustfst::fst_traits::Fst;
+/// # use rustfst::algorithms::concat::concat_with_separator;
+/// # use anyhow::Result;
+/// # fn main() -> Result<()> {
+/// let word_1: VectorFst<TropicalWeight> = acceptor(&[1, 2], TropicalWeight::one());
+/// let word_2: VectorFst<TropicalWeight> = acceptor(&[3], TropicalWeight::one());
+///
+/// let sentence = concat_with_separator(&[word_1, word_2], 10, TropicalWeight::new(0.5))?;
+/// let paths: Vec<_> = sentence.paths_iter().collect();
+/// assert_eq!(paths.len(), 1);
+/// assert_eq!(paths[0].ilabels, vec![1, 2, 10, 3]);
+/// assert_eq!(paths[0].weight, TropicalWeight::new(0.5));
+/// # Ok(())
+/// # }
+/// ```
+pub fn concat_with_separator<W, F>(fsts: &[F], sep: Label, weight: W) -> Result<F>
+where
+    W: Semiring,
+    F: ExpandedFst<W> + MutableFst<W> + AllocableFst<W>,
+{
+    let mut fst_sep = F::new();
+    let s0 = fst_sep.add_state();
+    let s1 = fst_sep.add_state();
+    fst_sep.set_start(s0)?;
+    fst_sep.set_final(s1, W::one())?;
+    fst_sep.add_tr(s0, Tr::new(sep, sep, weight, s1))?;
+
+    let mut fsts_iter = fsts.iter();
+    let mut res = match fsts_iter.next() {
+        Some(fst) => fst.clone(),
+        None => {
+            let mut fst = F::new();
+            let s = fst.add_state();
+            fst.set_start(s)?;
+            fst.set_final(s, W::one())?;
+            return Ok(fst);
+        }
+    };
+    for fst in fsts_iter {
+        concat(&mut res, &fst_sep)?;
+        concat(&mut res, fst)?;
+    }
+    Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::union::union;
+    use crate::fst_impls::VectorFst;
+    use crate::fst_traits::Fst;
+    use crate::semirings::TropicalWeight;
+    use crate::utils::acceptor;
+
+    #[test]
+    fn test_concat_with_separator() -> Result<()> {
+        let sep = 100;
+        let mut word_1: VectorFst<TropicalWeight> = acceptor(&[1, 2], TropicalWeight::new(1.0));
+        let word_1_bis: VectorFst<TropicalWeight> = acceptor(&[4], TropicalWeight::new(2.0));
+        union(&mut word_1, &word_1_bis)?;
+        let word_2: VectorFst<TropicalWeight> = acceptor(&[3], TropicalWeight::one());
+        let word_3: VectorFst<TropicalWeight> = acceptor(&[5, 6], TropicalWeight::one());
+
+        let fst = concat_with_separator(&[word_1, word_2, word_3], sep, TropicalWeight::new(0.5))?;
+        let mut paths: Vec<_> = fst.paths_iter().collect();
+        paths.sort_by(|p1, p2| p1.ilabels.cmp(&p2.ilabels));
+        assert_eq!(paths.len(), 2);
+        assert_eq!(paths[0].ilabels, vec![1, 2, sep, 3, sep, 5, 6]);
+        assert_eq!(paths[0].weight, TropicalWeight::new(2.0));
+        assert_eq!(paths[1].ilabels, vec![4, sep, 3, sep, 5, 6]);
+        assert_eq!(paths[1].weight, TropicalWeight::new(3.0));
+        for path in paths.iter() {
+            assert_ne!(path.ilabels.first(), Some(&sep));
+            assert_ne!(path.ilabels.last(), Some(&sep));
+            assert_eq!(path.ilabels.iter().filter(|l| **l == sep).count(), 2);
+            assert_eq!(path.ilabels, path.olabels);
+        }
+
+        let single = concat_with_separator(
+            &[acceptor::<TropicalWeight, VectorFst<_>>(
+                &[7],
+                TropicalWeight::one(),
+            )],
+            sep,
+            TropicalWeight::one(),
+        )?;
+        let paths: Vec<_> = single.paths_iter().collect();
+        assert_eq!(paths[0].ilabels, vec![7]);
+
+        let empty: VectorFst<TropicalWeight> =
+            concat_with_separator(&[], sep, TropicalWeight::one())?;
+        let paths: Vec<_> = empty.paths_iter().collect();
+        assert_eq!(paths.len(), 1);
+        assert!(paths[0].ilabels.is_empty());
+
+        Ok(())
+    }
+}
@@ -2,4 +2,4 @@ mod concat_fst;
 mod concat_static;
 
 pub use concat_fst::ConcatFst;
-pub use concat_static::concat;
+pub use concat_static::{concat, concat_with_separator};
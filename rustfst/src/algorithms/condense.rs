@@ -7,6 +7,53 @@ use crate::fst_traits::{ExpandedFst, Fst, MutableFst};
 use crate::semirings::Semiring;
 use crate::{StateId, Trs};
 
+/// Computes the strongly connected components (SCCs) of an FST with a depth-first search.
+///
+/// Returns the component of each state and the number of components. The components are
+/// numbered in topological order : a transition never goes from a component to a component
+/// with a lower number. The FST is cyclic if and only if there are less components than
+/// states or a state has a self-loop. An error is returned if the FST has states but no start
+/// state.
+///
+/// # Example
+/// ```
+/// # use rustfst::fst_impls::VectorFst;
+/// # use rustfst::fst_traits::MutableFst;
+/// # use rustfst::semirings::{Semiring, TropicalWeight};
+/// # use rustfst::algorithms::scc;
+/// # use rustfst::Tr;
+/// # use anyhow::Result;
+/// # fn main() -> Result<()> {
+/// let mut fst = VectorFst::<TropicalWeight>::new();
+/// fst.add_states(3);
+/// fst.set_start(0)?;
+/// fst.add_tr(0, Tr::new(1, 1, TropicalWeight::one(), 1))?;
+/// fst.add_tr(1, Tr::new(2, 2, TropicalWeight::one(), 0))?;
+/// fst.add_tr(1, Tr::new(3, 3, TropicalWeight::one(), 2))?;
+///
+/// assert_eq!(scc(&fst)?, (vec![0, 0, 1], 2));
+/// # Ok(())
+/// # }
+/// ```
+pub fn scc<W: Semiring, F: ExpandedFst<W>>(fst: &F) -> Result<(Vec<StateId>, usize)> {
+    if fst.start().is_none() {
+        if fst.num_states() > 0 {
+            bail!("Scc : The FST has states but no start state");
+        }
+        return Ok((vec![], 0));
+    }
+    let mut visitor = SccVisitor::new(fst, true, false);
+    dfs_visit(fst, &mut visitor, &AnyTrFilter {}, false);
+    let nscc = visitor.nscc as usize;
+    let scc = visitor
+        .scc
+        .unwrap()
+        .into_iter()
+        .map(|c| c as StateId)
+        .collect();
+    Ok((scc, nscc))
+}
+
 /// Return an acyclic FST where each SCC in the input FST has been condensed to
 /// a single state with transitions between SCCs retained and within SCCs
 /// dropped.
@@ -27,7 +74,7 @@ pub fn condense<W: Semiring, FI: Fst<W> + ExpandedFst<W>, FO: MutableFst<W>>(
                 let c = c as StateId;
                 let s = s as StateId;
 
-                if s == ifst.start().unwrap() {
+                if Some(s) == ifst.start() {
                     ofst.set_start_unchecked(c);
                 }
                 if let Some(final_weight) = ifst.final_weight_unchecked(s) {
@@ -50,3 +97,53 @@ pub fn condense<W: Semiring, FI: Fst<W> + ExpandedFst<W>, FO: MutableFst<W>>(
     }
     Ok((scc, ofst))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::fst_impls::VectorFst;
+    use crate::fst_traits::CoreFst;
+    use crate::semirings::TropicalWeight;
+    use crate::Tr;
+
+    #[test]
+    fn test_scc() -> Result<()> {
+        // 0 -> {1 <-> 2} -> 3 <- 4 (not accessible), 3 has a self-loop.
+        let mut fst = VectorFst::<TropicalWeight>::new();
+        fst.add_states(5);
+        fst.set_start(0)?;
+        fst.set_final(3, 1.0)?;
+        fst.add_tr(0, Tr::new(1, 1, 1.0, 1))?;
+        fst.add_tr(1, Tr::new(2, 2, 1.0, 2))?;
+        fst.add_tr(2, Tr::new(3, 3, 1.0, 1))?;
+        fst.add_tr(2, Tr::new(4, 4, 1.0, 3))?;
+        fst.add_tr(3, Tr::new(5, 5, 1.0, 3))?;
+        fst.add_tr(4, Tr::new(6, 6, 1.0, 3))?;
+
+        let (components, nscc) = scc(&fst)?;
+        assert_eq!(nscc, 4);
+        assert_eq!(components.len(), 5);
+        assert_eq!(components[1], components[2]);
+        for s in fst.states_range() {
+            for tr in fst.get_trs(s)?.trs() {
+                assert!(components[s as usize] <= components[tr.nextstate as usize]);
+            }
+        }
+
+        let (condensed_scc, condensed): (_, VectorFst<TropicalWeight>) = condense(&fst)?;
+        let condensed_scc: Vec<StateId> = condensed_scc.into_iter().map(|c| c as StateId).collect();
+        assert_eq!(condensed_scc, components);
+        assert_eq!(condensed.num_states(), nscc);
+        Ok(())
+    }
+
+    #[test]
+    fn test_scc_no_start_state() -> Result<()> {
+        let mut fst = VectorFst::<TropicalWeight>::new();
+        assert_eq!(scc(&fst)?, (vec![], 0));
+        fst.add_states(2);
+        assert!(scc(&fst).is_err());
+        Ok(())
+    }
+}
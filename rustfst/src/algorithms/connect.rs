@@ -50,11 +50,10 @@ use crate::NO_STATE_ID;
 /// ![connect_out](https://raw.githubusercontent.com/Garvys/rustfst-images-doc/master/images/connect_out.svg?sanitize=true)
 ///
 pub fn connect<W: Semiring, F: ExpandedFst<W> + MutableFst<W>>(fst: &mut F) -> Result<()> {
-    let mut visitor = ConnectVisitor::new(fst);
-    dfs_visit(fst, &mut visitor, &AnyTrFilter {}, false);
-    let mut dstates = Vec::with_capacity(visitor.access.len());
-    for s in 0..visitor.access.len() {
-        if !visitor.access[s] || !visitor.coaccess[s] {
+    let (access, coaccess) = compute_access(fst);
+    let mut dstates = Vec::with_capacity(access.len());
+    for s in 0..access.len() {
+        if !access[s] || !coaccess[s] {
             dstates.push(s as StateId);
         }
     }
@@ -66,6 +65,51 @@ pub fn connect<W: Semiring, F: ExpandedFst<W> + MutableFst<W>>(fst: &mut F) -> R
     Ok(())
 }
 
+/// Remove the states that can't be reached from the start state, along with their trs.
+/// States that can't reach a final state are kept.
+///
+/// Calling `accessible` then [`coaccessible`](fn.coaccessible.html) is equivalent to
+/// calling [`connect`](fn.connect.html).
+pub fn accessible<W: Semiring, F: ExpandedFst<W> + MutableFst<W>>(fst: &mut F) -> Result<()> {
+    let (access, _) = compute_access(fst);
+    let dstates: Vec<_> = (0..access.len())
+        .filter(|s| !access[*s])
+        .map(|s| s as StateId)
+        .collect();
+    fst.del_states(dstates)?;
+    fst.set_properties_with_mask(
+        FstProperties::ACCESSIBLE,
+        FstProperties::ACCESSIBLE | FstProperties::NOT_ACCESSIBLE,
+    );
+    Ok(())
+}
+
+/// Remove the states from which no final state can be reached, along with their trs.
+/// States that can't be reached from the start state are kept.
+///
+/// Calling [`accessible`](fn.accessible.html) then `coaccessible` is equivalent to
+/// calling [`connect`](fn.connect.html).
+pub fn coaccessible<W: Semiring, F: ExpandedFst<W> + MutableFst<W>>(fst: &mut F) -> Result<()> {
+    let (_, coaccess) = compute_access(fst);
+    let dstates: Vec<_> = (0..coaccess.len())
+        .filter(|s| !coaccess[*s])
+        .map(|s| s as StateId)
+        .collect();
+    fst.del_states(dstates)?;
+    fst.set_properties_with_mask(
+        FstProperties::COACCESSIBLE,
+        FstProperties::COACCESSIBLE | FstProperties::NOT_COACCESSIBLE,
+    );
+    Ok(())
+}
+
+/// Returns for each state whether it is accessible and whether it is coaccessible.
+fn compute_access<W: Semiring, F: ExpandedFst<W>>(fst: &F) -> (Vec<bool>, Vec<bool>) {
+    let mut visitor = ConnectVisitor::new(fst);
+    dfs_visit(fst, &mut visitor, &AnyTrFilter {}, false);
+    (visitor.access, visitor.coaccess)
+}
+
 struct ConnectVisitor<'a, W: Semiring, F: Fst<W>> {
     access: Vec<bool>,
     coaccess: Vec<bool>,
@@ -199,6 +243,52 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn test_accessible_coaccessible() -> Result<()> {
+        // 0 -> 1 -> 2 (final), 0 -> 3 (dead end), 4 (unreachable) -> 2.
+        let mut fst = VectorFst::<TropicalWeight>::new();
+        fst.add_states(5);
+        fst.set_start(0)?;
+        fst.set_final(2, TropicalWeight::one())?;
+        fst.add_tr(0, Tr::new(1, 1, 1.0, 1))?;
+        fst.add_tr(1, Tr::new(2, 2, 2.0, 2))?;
+        fst.add_tr(0, Tr::new(3, 3, 3.0, 3))?;
+        fst.add_tr(4, Tr::new(4, 4, 4.0, 2))?;
+
+        let mut fst_access = fst.clone();
+        accessible(&mut fst_access)?;
+        let mut expected = VectorFst::<TropicalWeight>::new();
+        expected.add_states(4);
+        expected.set_start(0)?;
+        expected.set_final(2, TropicalWeight::one())?;
+        expected.add_tr(0, Tr::new(1, 1, 1.0, 1))?;
+        expected.add_tr(1, Tr::new(2, 2, 2.0, 2))?;
+        expected.add_tr(0, Tr::new(3, 3, 3.0, 3))?;
+        assert_eq!(fst_access, expected);
+        assert!(fst_access.properties().contains(FstProperties::ACCESSIBLE));
+
+        let mut fst_coaccess = fst.clone();
+        coaccessible(&mut fst_coaccess)?;
+        let mut expected = VectorFst::<TropicalWeight>::new();
+        expected.add_states(4);
+        expected.set_start(0)?;
+        expected.set_final(2, TropicalWeight::one())?;
+        expected.add_tr(0, Tr::new(1, 1, 1.0, 1))?;
+        expected.add_tr(1, Tr::new(2, 2, 2.0, 2))?;
+        expected.add_tr(3, Tr::new(4, 4, 4.0, 2))?;
+        assert_eq!(fst_coaccess, expected);
+        assert!(fst_coaccess
+            .properties()
+            .contains(FstProperties::COACCESSIBLE));
+
+        let mut fst_connect = fst.clone();
+        connect(&mut fst_connect)?;
+        coaccessible(&mut fst_access)?;
+        assert_eq!(fst_access, fst_connect);
+
+        Ok(())
+    }
+
     proptest! {
         #[test]
         fn test_connect_proptest(mut fst in any::<VectorFst::<TropicalWeight>>()) {
@@ -1,6 +1,7 @@
 use crate::algorithms::determinize::divisors::CommonDivisor;
 use crate::algorithms::determinize::DeterminizeFsaOp;
 use crate::algorithms::lazy::{LazyFst, SimpleHashMapCache};
+use crate::algorithms::progress::ProgressCallback;
 use crate::fst_properties::FstProperties;
 use crate::fst_traits::{AllocableFst, CoreFst, Fst, FstIterator, MutableFst, StateIterator};
 use crate::semirings::{WeaklyDivisibleSemiring, WeightQuantize};
@@ -159,6 +160,16 @@ where
         self.0.compute()
     }
 
+    /// Turns the Lazy FST into a static one. `progress` is called regularly with the number
+    /// of states computed so far and the computation fails with a `Cancelled` error if it
+    /// returns `ProgressAction::Abort`.
+    pub fn compute_with_progress<F2: MutableFst<W> + AllocableFst<W>>(
+        &self,
+        progress: Option<ProgressCallback>,
+    ) -> Result<F2> {
+        Ok(self.0.compute_with_limits(None, progress)?.unwrap())
+    }
+
     pub fn out_dist(self) -> Result<Vec<W>> {
         self.0.op.out_dist()
     }
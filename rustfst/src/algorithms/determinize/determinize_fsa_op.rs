@@ -1,7 +1,6 @@
 use std::borrow::Borrow;
 use std::collections::btree_map::Entry as EntryBTreeMap;
-use std::collections::hash_map::Entry as EntryHashMap;
-use std::collections::{BTreeMap, HashMap};
+use std::collections::BTreeMap;
 use std::fmt::Debug;
 use std::marker::PhantomData;
 use std::sync::Arc;
@@ -155,19 +154,18 @@ where
             det_tr.weight = CD::common_divisor(&det_tr.weight, &dest_elt.weight)?;
         }
 
-        let mut new_pairs = HashMap::new();
-        for x in &mut det_tr.dest_tuple.subset.pairs {
-            match new_pairs.entry(x.state) {
-                EntryHashMap::Vacant(e) => {
-                    e.insert(x.clone());
-                }
-                EntryHashMap::Occupied(mut e) => {
-                    e.get_mut().weight.plus_assign(&x.weight)?;
-                }
-            };
+        // The pairs are sorted by state so the duplicates are next to each other. Keeping the
+        // order makes the subsets of equal states equal.
+        let mut new_pairs: Vec<DeterminizeElement<W>> =
+            Vec::with_capacity(det_tr.dest_tuple.subset.pairs.len());
+        for x in det_tr.dest_tuple.subset.pairs.drain(..) {
+            match new_pairs.last_mut() {
+                Some(last) if last.state == x.state => last.weight.plus_assign(&x.weight)?,
+                _ => new_pairs.push(x),
+            }
         }
 
-        det_tr.dest_tuple.subset.pairs = new_pairs.values().cloned().collect();
+        det_tr.dest_tuple.subset.pairs = new_pairs;
 
         for dest_elt in det_tr.dest_tuple.subset.pairs.iter_mut() {
             dest_elt.weight = dest_elt
@@ -0,0 +1,234 @@
+use std::borrow::Borrow;
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use anyhow::Result;
+
+use crate::algorithms::determinize::{DefaultCommonDivisor, DeterminizeFsa};
+use crate::fst_properties::mutable_properties::determinize_properties;
+use crate::fst_properties::FstProperties;
+use crate::fst_traits::{AllocableFst, CoreFst, Fst, FstIterator, MutableFst, StateIterator};
+use crate::semirings::{SemiringProperties, WeaklyDivisibleSemiring, WeightQuantize};
+use crate::{StateId, SymbolTable, TrsVec};
+
+type InnerDeterminizeFsa<W, F, B> = DeterminizeFsa<W, F, DefaultCommonDivisor, B, Vec<W>>;
+
+/// Lazy version of [`determinize`](fn.determinize.html) : the weighted subset construction is
+/// performed on demand, when the transitions of a state are first requested. Only the states
+/// that are visited are computed, which is useful to determinize a lattice while decoding.
+///
+/// Only acceptors are supported, in any weakly divisible semiring (e.g. tropical or log).
+/// Once fully expanded with [`compute`](#method.compute), the result is the same as the one of
+/// `determinize`.
+#[derive(Debug)]
+pub struct DeterminizeFst<W, F, B>
+where
+    W: WeaklyDivisibleSemiring + WeightQuantize,
+    F: Fst<W>,
+    B: Borrow<F> + Debug,
+{
+    fst: InnerDeterminizeFsa<W, F, B>,
+    properties: FstProperties,
+}
+
+impl<W, F, B> CoreFst<W> for DeterminizeFst<W, F, B>
+where
+    W: WeaklyDivisibleSemiring + WeightQuantize,
+    F: Fst<W>,
+    B: Borrow<F> + Debug,
+{
+    type TRS = TrsVec<W>;
+
+    fn start(&self) -> Option<StateId> {
+        self.fst.start()
+    }
+
+    fn final_weight(&self, state_id: StateId) -> Result<Option<W>> {
+        self.fst.final_weight(state_id)
+    }
+
+    unsafe fn final_weight_unchecked(&self, state_id: StateId) -> Option<W> {
+        self.fst.final_weight_unchecked(state_id)
+    }
+
+    fn num_trs(&self, s: StateId) -> Result<usize> {
+        self.fst.num_trs(s)
+    }
+
+    unsafe fn num_trs_unchecked(&self, s: StateId) -> usize {
+        self.fst.num_trs_unchecked(s)
+    }
+
+    fn get_trs(&self, state_id: StateId) -> Result<Self::TRS> {
+        self.fst.get_trs(state_id)
+    }
+
+    unsafe fn get_trs_unchecked(&self, state_id: StateId) -> Self::TRS {
+        self.fst.get_trs_unchecked(state_id)
+    }
+
+    fn properties(&self) -> FstProperties {
+        self.properties
+    }
+
+    fn num_input_epsilons(&self, state: StateId) -> Result<usize> {
+        self.fst.num_input_epsilons(state)
+    }
+
+    fn num_output_epsilons(&self, state: StateId) -> Result<usize> {
+        self.fst.num_output_epsilons(state)
+    }
+}
+
+impl<'a, W, F, B> StateIterator<'a> for DeterminizeFst<W, F, B>
+where
+    W: WeaklyDivisibleSemiring + WeightQuantize + 'a,
+    F: Fst<W> + 'a,
+    B: Borrow<F> + Debug + 'a,
+{
+    type Iter = <InnerDeterminizeFsa<W, F, B> as StateIterator<'a>>::Iter;
+
+    fn states_iter(&'a self) -> Self::Iter {
+        self.fst.states_iter()
+    }
+}
+
+impl<'a, W, F, B> FstIterator<'a, W> for DeterminizeFst<W, F, B>
+where
+    W: WeaklyDivisibleSemiring + WeightQuantize,
+    F: Fst<W> + 'a,
+    B: Borrow<F> + Debug + 'a,
+{
+    type FstIter = <InnerDeterminizeFsa<W, F, B> as FstIterator<'a, W>>::FstIter;
+
+    fn fst_iter(&'a self) -> Self::FstIter {
+        self.fst.fst_iter()
+    }
+}
+
+impl<W, F, B> Fst<W> for DeterminizeFst<W, F, B>
+where
+    W: WeaklyDivisibleSemiring + WeightQuantize,
+    F: Fst<W> + 'static,
+    B: Borrow<F> + Debug + 'static,
+{
+    fn input_symbols(&self) -> Option<&Arc<SymbolTable>> {
+        self.fst.input_symbols()
+    }
+
+    fn output_symbols(&self) -> Option<&Arc<SymbolTable>> {
+        self.fst.output_symbols()
+    }
+
+    fn set_input_symbols(&mut self, symt: Arc<SymbolTable>) {
+        self.fst.set_input_symbols(symt)
+    }
+
+    fn set_output_symbols(&mut self, symt: Arc<SymbolTable>) {
+        self.fst.set_output_symbols(symt)
+    }
+
+    fn take_input_symbols(&mut self) -> Option<Arc<SymbolTable>> {
+        self.fst.take_input_symbols()
+    }
+
+    fn take_output_symbols(&mut self) -> Option<Arc<SymbolTable>> {
+        self.fst.take_output_symbols()
+    }
+}
+
+impl<W, F, B> DeterminizeFst<W, F, B>
+where
+    W: WeaklyDivisibleSemiring + WeightQuantize,
+    F: Fst<W>,
+    B: Borrow<F> + Debug,
+{
+    /// Creates the lazy determinization of an acceptor. `delta` is the quantization delta
+    /// used to compare the residual weights of the subsets.
+    pub fn new(fst: B, delta: f32) -> Result<Self> {
+        if !W::properties().contains(SemiringProperties::LEFT_SEMIRING) {
+            bail!("DeterminizeFst : weight must be left distributive")
+        }
+        let iprops = fst.borrow().properties();
+        if !iprops.contains(FstProperties::ACCEPTOR) {
+            bail!("DeterminizeFst : Only acceptors can be determinized lazily, use determinize for transducers")
+        }
+        let properties = determinize_properties(iprops, false, true);
+        let fst = DeterminizeFsa::new(fst, None, delta)?;
+        Ok(Self { fst, properties })
+    }
+
+    /// Turns the Lazy FST into a static one.
+    pub fn compute<F2: MutableFst<W> + AllocableFst<W>>(&self) -> Result<F2> {
+        let mut fst_res: F2 = self.fst.compute()?;
+        fst_res.set_properties(self.properties);
+        Ok(fst_res)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::determinize::determinize;
+    use crate::fst_impls::VectorFst;
+    use crate::fst_traits::ExpandedFst;
+    use crate::semirings::{LogWeight, Semiring, TropicalWeight};
+    use crate::{Tr, KDELTA};
+
+    fn build_fst<W: Semiring<Type = f32>>() -> Result<VectorFst<W>> {
+        let mut fst = VectorFst::<W>::new();
+        fst.add_states(5);
+        fst.set_start(0)?;
+        fst.add_tr(0, Tr::new(1, 1, W::new(1.0), 1))?;
+        fst.add_tr(0, Tr::new(1, 1, W::new(2.0), 2))?;
+        fst.add_tr(0, Tr::new(2, 2, W::new(0.5), 3))?;
+        fst.add_tr(1, Tr::new(3, 3, W::new(3.0), 4))?;
+        fst.add_tr(2, Tr::new(3, 3, W::new(1.0), 4))?;
+        fst.add_tr(2, Tr::new(4, 4, W::new(1.5), 4))?;
+        fst.add_tr(3, Tr::new(4, 4, W::new(2.0), 4))?;
+        fst.set_final(4, W::new(0.25))?;
+        Ok(fst)
+    }
+
+    fn test_determinize_fst_static_equality<W>() -> Result<()>
+    where
+        W: WeaklyDivisibleSemiring + WeightQuantize + Semiring<Type = f32> + 'static,
+    {
+        let fst = build_fst::<W>()?;
+        let fst_static: VectorFst<W> = determinize(&fst)?;
+
+        let fst_lazy = DeterminizeFst::<_, VectorFst<W>, _>::new(Arc::new(fst.clone()), KDELTA)?;
+        assert!(fst_lazy
+            .properties()
+            .contains(FstProperties::I_DETERMINISTIC));
+        // The start state goes to the subsets {1, 2} on label 1 and {3} on label 2.
+        let start = fst_lazy.start().unwrap();
+        assert_eq!(fst_lazy.num_trs(start)?, 2);
+
+        let fst_lazy: VectorFst<W> = fst_lazy.compute()?;
+        assert_eq!(fst_lazy, fst_static);
+        assert!(fst_lazy.num_states() < fst.num_states());
+        Ok(())
+    }
+
+    #[test]
+    fn test_determinize_fst_tropical() -> Result<()> {
+        test_determinize_fst_static_equality::<TropicalWeight>()
+    }
+
+    #[test]
+    fn test_determinize_fst_log() -> Result<()> {
+        test_determinize_fst_static_equality::<LogWeight>()
+    }
+
+    #[test]
+    fn test_determinize_fst_transducer() -> Result<()> {
+        let mut fst = VectorFst::<TropicalWeight>::new();
+        fst.add_states(2);
+        fst.set_start(0)?;
+        fst.set_final(1, TropicalWeight::one())?;
+        fst.add_tr(0, Tr::new(1, 2, 1.0, 1))?;
+        assert!(DeterminizeFst::<_, VectorFst<_>, _>::new(&fst, KDELTA).is_err());
+        Ok(())
+    }
+}
@@ -9,8 +9,11 @@ use crate::algorithms::factor_weight::factor_iterators::{
     GallicFactor, GallicFactorMin, GallicFactorRestrict,
 };
 use crate::algorithms::factor_weight::{factor_weight, FactorWeightOptions, FactorWeightType};
-use crate::algorithms::weight_convert;
+use crate::algorithms::progress::{Cancelled, ProgressCallback};
 use crate::algorithms::weight_converters::{FromGallicConverter, ToGallicConverter};
+use crate::algorithms::{
+    push_weights_with_config, weight_convert, PushWeightsConfig, ReweightType,
+};
 use crate::fst_impls::VectorFst;
 use crate::fst_properties::mutable_properties::determinize_properties;
 use crate::fst_properties::FstProperties;
@@ -38,7 +41,11 @@ where
     fst.compute_with_distance()
 }
 
-pub fn determinize_fsa<W, F1, F2, CD>(fst_in: &F1, delta: f32) -> Result<F2>
+pub fn determinize_fsa<W, F1, F2, CD>(
+    fst_in: &F1,
+    delta: f32,
+    progress: Option<ProgressCallback>,
+) -> Result<F2>
 where
     W: WeaklyDivisibleSemiring + WeightQuantize,
     F1: Fst<W>,
@@ -49,10 +56,15 @@ where
         bail!("determinize_fsa : weight must be left distributive")
     }
     let det_fsa: DeterminizeFsa<W, F1, CD, _, Vec<W>> = DeterminizeFsa::new(fst_in, None, delta)?;
-    det_fsa.compute()
+    det_fsa.compute_with_progress(progress)
 }
 
-pub fn determinize_fst<W, F1, F2>(fst_in: &F1, det_type: DeterminizeType, delta: f32) -> Result<F2>
+pub fn determinize_fst<W, F1, F2>(
+    fst_in: &F1,
+    det_type: DeterminizeType,
+    delta: f32,
+    progress: Option<ProgressCallback>,
+) -> Result<F2>
 where
     W: WeaklyDivisibleSemiring + WeightQuantize + 'static,
     F1: ExpandedFst<W>,
@@ -80,7 +92,7 @@ where
             let fsa: VectorFst<GallicWeightMin<W>> =
                 weight_convert(fst_in.borrow(), &mut to_gallic)?;
             let determinized_fsa: VectorFst<GallicWeightMin<W>> =
-                determinize_fsa::<_, VectorFst<_>, _, GallicCommonDivisor>(&fsa, delta)?;
+                determinize_fsa::<_, VectorFst<_>, _, GallicCommonDivisor>(&fsa, delta, progress)?;
             let factored_determinized_fsa: VectorFst<GallicWeightMin<W>> =
                 factor_weight::<_, VectorFst<GallicWeightMin<W>>, _, _, GallicFactorMin<W>>(
                     &determinized_fsa,
@@ -92,7 +104,20 @@ where
             let fsa: VectorFst<GallicWeightRestrict<W>> =
                 weight_convert(fst_in.borrow(), &mut to_gallic)?;
             let determinized_fsa: VectorFst<GallicWeightRestrict<W>> =
-                determinize_fsa::<_, VectorFst<_>, _, GallicCommonDivisor>(&fsa, delta)?;
+                determinize_fsa::<_, VectorFst<_>, _, GallicCommonDivisor>(&fsa, delta, progress)
+                    .map_err(|e| {
+                    // Only the failures of the gallic semiring point to a non-functional
+                    // input, a cancellation is returned as is.
+                    if e.is::<Cancelled>() {
+                        e
+                    } else {
+                        e.context(
+                            "determinize : DeterminizeFunctional failed, the input transducer \
+                                 may not be functional. Use DeterminizeNonFunctional or \
+                                 DeterminizeDisambiguate instead.",
+                        )
+                    }
+                })?;
             let factored_determinized_fsa: VectorFst<GallicWeightRestrict<W>> =
                 factor_weight::<
                     _,
@@ -106,7 +131,7 @@ where
         DeterminizeType::DeterminizeNonFunctional => {
             let fsa: VectorFst<GallicWeight<W>> = weight_convert(fst_in.borrow(), &mut to_gallic)?;
             let determinized_fsa: VectorFst<GallicWeight<W>> =
-                determinize_fsa::<_, VectorFst<_>, _, GallicCommonDivisor>(&fsa, delta)?;
+                determinize_fsa::<_, VectorFst<_>, _, GallicCommonDivisor>(&fsa, delta, progress)?;
             let factored_determinized_fsa: VectorFst<GallicWeight<W>> =
                 factor_weight::<_, VectorFst<GallicWeight<W>>, _, _, GallicFactor<W>>(
                     &determinized_fsa,
@@ -117,15 +142,40 @@ where
     }
 }
 
-#[derive(Clone, Debug, Copy, PartialOrd, PartialEq)]
+/// Where the residual weights of the determinized subsets end up.
+///
+/// Both placements produce FSTs that are weight-equivalent : the total weight
+/// of every string is the same, only its distribution along the paths differs.
+#[derive(Clone, Debug, Copy, PartialOrd, PartialEq, Default)]
+pub enum DeterminizeOutput {
+    /// Residuals are emitted as soon as possible, on the transitions. This is
+    /// what OpenFST does.
+    #[default]
+    ArcWeights,
+    /// Residuals are delayed and accumulated on the final states. This is
+    /// equivalent to pushing the weights of the determinized FST towards the
+    /// final states.
+    FinalWeights,
+}
+
+#[derive(Clone, Debug, PartialOrd, PartialEq)]
 pub struct DeterminizeConfig {
     pub delta: f32,
     pub det_type: DeterminizeType,
+    pub output: DeterminizeOutput,
+    /// If set, called regularly with the number of states computed so far. The
+    /// determinization fails with a `Cancelled` error if it returns `ProgressAction::Abort`.
+    pub progress: Option<ProgressCallback>,
 }
 
 impl DeterminizeConfig {
     pub fn new(delta: f32, det_type: DeterminizeType) -> Self {
-        Self { delta, det_type }
+        Self {
+            delta,
+            det_type,
+            output: DeterminizeOutput::default(),
+            progress: None,
+        }
     }
 
     pub fn with_delta(self, delta: f32) -> Self {
@@ -135,6 +185,14 @@ impl DeterminizeConfig {
     pub fn with_det_type(self, det_type: DeterminizeType) -> Self {
         Self { det_type, ..self }
     }
+
+    pub fn with_output(self, output: DeterminizeOutput) -> Self {
+        Self { output, ..self }
+    }
+
+    pub fn with_progress(self, progress: Option<ProgressCallback>) -> Self {
+        Self { progress, ..self }
+    }
 }
 
 impl Default for DeterminizeConfig {
@@ -142,6 +200,8 @@ impl Default for DeterminizeConfig {
         Self {
             delta: KDELTA,
             det_type: DeterminizeType::DeterminizeFunctional,
+            output: DeterminizeOutput::default(),
+            progress: None,
         }
     }
 }
@@ -159,6 +219,15 @@ where
 /// state has two transitions with the same input label. For this algorithm,
 /// epsilon transitions are treated as regular symbols.
 ///
+/// The placement of the residual weights is controlled by `config.output`,
+/// see [`DeterminizeOutput`](enum.DeterminizeOutput.html).
+///
+/// Acceptors are determinized with the weighted subset construction and `config.det_type` is
+/// ignored. Transducers are determinized as acceptors over the gallic semiring, the residuals
+/// being pairs of output strings and weights. `config.det_type` then selects how the
+/// residuals of the paths sharing an input string are combined, see
+/// [`DeterminizeType`](enum.DeterminizeType.html).
+///
 /// # Example
 ///
 /// ## Input
@@ -179,11 +248,19 @@ where
     let det_type = config.det_type;
     let iprops = fst_in.borrow().properties();
     let mut fst_res: F2 = if iprops.contains(FstProperties::ACCEPTOR) {
-        determinize_fsa::<_, F1, _, DefaultCommonDivisor>(fst_in, delta)?
+        determinize_fsa::<_, F1, _, DefaultCommonDivisor>(fst_in, delta, config.progress)?
     } else {
-        determinize_fst(fst_in, det_type, delta)?
+        determinize_fst(fst_in, det_type, delta, config.progress)?
     };
 
+    if config.output == DeterminizeOutput::FinalWeights {
+        push_weights_with_config(
+            &mut fst_res,
+            ReweightType::ReweightToFinal,
+            PushWeightsConfig::default().with_delta(delta),
+        )?;
+    }
+
     let distinct_psubsequential_labels = !(det_type == DeterminizeType::DeterminizeNonFunctional);
     fst_res.set_properties(determinize_properties(
         iprops,
@@ -197,10 +274,13 @@ where
 #[cfg(test)]
 mod tests {
     use crate::fst_impls::VectorFst;
+    use crate::fst_traits::CoreFst;
     use crate::semirings::TropicalWeight;
     use crate::tr::Tr;
+    use crate::Label;
     use crate::Semiring;
     use crate::SymbolTable;
+    use crate::Trs;
     use proptest::prelude::any;
     use proptest::proptest;
     use std::sync::Arc;
@@ -269,6 +349,175 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_determinize_same_subset_from_different_labels() -> Result<()> {
+        // Labels 1 and 2 both lead to the subset {1, 2, 3, 4, 5, 6}, which must be a single state.
+        let mut input_fst = VectorFst::<TropicalWeight>::new();
+        input_fst.add_states(7);
+        input_fst.set_start(0)?;
+        for s in 1..7 {
+            input_fst.set_final(s, TropicalWeight::one())?;
+            input_fst.add_tr(0, Tr::new(1, 1, 1.0, s))?;
+        }
+        for s in (1..7).rev() {
+            input_fst.add_tr(0, Tr::new(2, 2, 1.0, s))?;
+        }
+
+        for _ in 0..20 {
+            let determinized_fst: VectorFst<TropicalWeight> = determinize(&input_fst)?;
+            assert_eq!(determinized_fst.num_states(), 2);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_determinize_output_final_weights() -> Result<()> {
+        let mut input_fst = VectorFst::<TropicalWeight>::new();
+        let s0 = input_fst.add_state();
+        let s1 = input_fst.add_state();
+        let s2 = input_fst.add_state();
+        let s3 = input_fst.add_state();
+
+        input_fst.set_start(s0)?;
+        input_fst.set_final(s3, TropicalWeight::new(0.5))?;
+
+        input_fst.add_tr(s0, Tr::new(1, 1, 2.0, s1))?;
+        input_fst.add_tr(s0, Tr::new(1, 1, 3.0, s2))?;
+        input_fst.add_tr(s1, Tr::new(2, 2, 4.0, s3))?;
+        input_fst.add_tr(s2, Tr::new(2, 2, 1.0, s3))?;
+        input_fst.add_tr(s2, Tr::new(3, 3, 1.5, s3))?;
+
+        let config = DeterminizeConfig::default();
+        let arc_fst: VectorFst<TropicalWeight> = determinize_with_config(
+            &input_fst,
+            config.clone().with_output(DeterminizeOutput::ArcWeights),
+        )?;
+        let final_fst: VectorFst<TropicalWeight> = determinize_with_config(
+            &input_fst,
+            config.with_output(DeterminizeOutput::FinalWeights),
+        )?;
+
+        let start = final_fst.start().unwrap();
+        for tr in final_fst.get_trs(start)?.trs() {
+            assert!(tr.weight.is_one());
+        }
+
+        let mut arc_paths: Vec<_> = arc_fst
+            .paths_iter()
+            .map(|p| (p.ilabels, p.weight))
+            .collect();
+        let mut final_paths: Vec<_> = final_fst
+            .paths_iter()
+            .map(|p| (p.ilabels, p.weight))
+            .collect();
+        arc_paths.sort_by(|a, b| a.0.cmp(&b.0));
+        final_paths.sort_by(|a, b| a.0.cmp(&b.0));
+
+        assert_eq!(arc_paths.len(), 2);
+        assert_eq!(arc_paths, final_paths);
+        Ok(())
+    }
+
+    #[test]
+    fn test_determinize_progress_abort() -> Result<()> {
+        use crate::algorithms::{Cancelled, ProgressAction, PROGRESS_REPORT_INTERVAL};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        let n = 2500;
+        let labels: Vec<_> = (0..n)
+            .map(|i| (i % 3 + 1, i % 3 + 1, TropicalWeight::new(1.0)))
+            .collect();
+        let fst: VectorFst<TropicalWeight> = crate::utils::chain(&labels);
+
+        let det_ref: VectorFst<_> = determinize(&fst)?;
+        assert!(det_ref.num_states() > PROGRESS_REPORT_INTERVAL);
+
+        let num_calls = Arc::new(AtomicUsize::new(0));
+        let num_calls_callback = Arc::clone(&num_calls);
+        let keep_going = ProgressCallback::new(move |_states_done| {
+            num_calls_callback.fetch_add(1, Ordering::SeqCst);
+            ProgressAction::Continue
+        });
+        let config = DeterminizeConfig::default().with_progress(Some(keep_going));
+        let det: VectorFst<_> = determinize_with_config(&fst, config)?;
+        assert_eq!(det, det_ref);
+        assert_eq!(
+            num_calls.load(Ordering::SeqCst),
+            det_ref.num_states() / PROGRESS_REPORT_INTERVAL
+        );
+
+        let num_calls = Arc::new(AtomicUsize::new(0));
+        let num_calls_callback = Arc::clone(&num_calls);
+        let abort = ProgressCallback::new(move |_states_done| {
+            num_calls_callback.fetch_add(1, Ordering::SeqCst);
+            ProgressAction::Abort
+        });
+        let config = DeterminizeConfig::default().with_progress(Some(abort));
+        let err = determinize_with_config::<_, _, VectorFst<_>>(&fst, config).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<Cancelled>(),
+            Some(&Cancelled {
+                states_done: PROGRESS_REPORT_INTERVAL
+            })
+        );
+        assert_eq!(num_calls.load(Ordering::SeqCst), 1);
+        Ok(())
+    }
+
+    // Maps the input label 1 either to 1 or to 2 : not functional.
+    fn non_functional_fst() -> Result<VectorFst<TropicalWeight>> {
+        let mut fst = VectorFst::new();
+        fst.add_states(2);
+        fst.set_start(0)?;
+        fst.emplace_tr(0, 1, 1, 1.0, 1)?;
+        fst.emplace_tr(0, 1, 2, 2.0, 1)?;
+        fst.set_final(1, TropicalWeight::one())?;
+        Ok(fst)
+    }
+
+    // Input labels, output labels and weight of each path.
+    type Paths = Vec<(Vec<Label>, Vec<Label>, f32)>;
+
+    fn det_paths(fst: &VectorFst<TropicalWeight>, det_type: DeterminizeType) -> Result<Paths> {
+        let config = DeterminizeConfig::default().with_det_type(det_type);
+        let det: VectorFst<_> = determinize_with_config(fst, config)?;
+        let mut paths: Vec<_> = det
+            .paths_iter()
+            .map(|p| (p.ilabels, p.olabels, *p.weight.value()))
+            .collect();
+        paths.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        Ok(paths)
+    }
+
+    #[test]
+    fn test_determinize_functional_errors_on_non_functional_fst() -> Result<()> {
+        let fst = non_functional_fst()?;
+        let err = det_paths(&fst, DeterminizeType::DeterminizeFunctional).unwrap_err();
+        assert!(err.to_string().contains("may not be functional"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_determinize_non_functional_keeps_all_outputs() -> Result<()> {
+        let fst = non_functional_fst()?;
+        assert_eq!(
+            det_paths(&fst, DeterminizeType::DeterminizeNonFunctional)?,
+            vec![(vec![1], vec![1], 1.0), (vec![1], vec![2], 2.0)]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_determinize_disambiguate_keeps_best_output() -> Result<()> {
+        let fst = non_functional_fst()?;
+        assert_eq!(
+            det_paths(&fst, DeterminizeType::DeterminizeDisambiguate)?,
+            vec![(vec![1], vec![1], 1.0)]
+        );
+        Ok(())
+    }
+
     proptest! {
         #[test]
         fn test_proptest_determinize_keeps_symts(mut fst in any::<VectorFst::<TropicalWeight>>()) {
@@ -1,7 +1,9 @@
 pub(self) use determinize_fsa::DeterminizeFsa;
 pub(self) use determinize_fsa_op::DeterminizeFsaOp;
+pub use determinize_fst::DeterminizeFst;
 pub use determinize_static::{
     determinize, determinize_with_config, determinize_with_distance, DeterminizeConfig,
+    DeterminizeOutput,
 };
 pub(self) use divisors::{DefaultCommonDivisor, GallicCommonDivisor};
 pub(self) use element::{DeterminizeElement, DeterminizeStateTuple, DeterminizeTr, WeightedSubset};
@@ -9,19 +11,25 @@ pub(self) use state_table::DeterminizeStateTable;
 
 mod determinize_fsa;
 mod determinize_fsa_op;
+mod determinize_fst;
 mod determinize_static;
 mod divisors;
 mod element;
 mod state_table;
 
-/// Determinization type.
+/// Determinization type. Only used when determinizing transducers : the paths sharing an
+/// input string may carry different output strings, and this selects what to do with them.
 #[derive(Debug, Clone, PartialEq, PartialOrd, Copy)]
 pub enum DeterminizeType {
-    /// Input transducer is known to be functional (or error).
+    /// Input transducer is known to be functional (or error). An error is returned when two
+    /// paths with the same input string have different output strings.
     DeterminizeFunctional,
-    /// Input transducer is NOT known to be functional.
+    /// Input transducer is NOT known to be functional. All the output strings are kept : the
+    /// output is deterministic on the input labels, and the different outputs of an input
+    /// string are emitted when reaching a final state.
     DeterminizeNonFunctional,
     /// Input transducer is not known to be functional but only keep the min of
-    /// of ambiguous outputs.
+    /// of ambiguous outputs. A single path is kept per input string, which requires a
+    /// semiring with the path property (e.g. tropical).
     DeterminizeDisambiguate,
 }
@@ -0,0 +1,223 @@
+use std::collections::BTreeSet;
+
+use anyhow::Result;
+
+use crate::algorithms::compose::compose;
+use crate::algorithms::tr_compares::ILabelCompare;
+use crate::algorithms::tr_sort;
+use crate::fst_impls::VectorFst;
+use crate::fst_properties::{compute_fst_properties, FstProperties};
+use crate::fst_traits::{ExpandedFst, Fst, MutableFst};
+use crate::semirings::Semiring;
+use crate::{Label, Tr, Trs, EPS_LABEL};
+
+/// Labels of the transitions of an FST, epsilon excluded.
+fn alphabet<W: Semiring, F: ExpandedFst<W>>(fst: &F, labels: &mut BTreeSet<Label>) -> Result<()> {
+    for s in fst.states_iter() {
+        for tr in fst.get_trs(s)?.trs() {
+            if tr.ilabel != EPS_LABEL {
+                labels.insert(tr.ilabel);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Complements a deterministic epsilon-free unweighted acceptor over `alphabet` : the result
+/// accepts the strings over `alphabet` not accepted by `fst`. The states of `fst` are kept and
+/// a sink state reached by all the missing transitions is added.
+fn complement<W: Semiring, F: ExpandedFst<W>>(
+    fst: &F,
+    alphabet: &BTreeSet<Label>,
+) -> Result<VectorFst<W>> {
+    let mut ofst = VectorFst::new();
+    ofst.add_states(fst.num_states());
+    let sink = ofst.add_state();
+    ofst.set_start(fst.start().unwrap_or(sink))?;
+    ofst.set_final(sink, W::one())?;
+    for &label in alphabet {
+        ofst.add_tr(sink, Tr::new(label, label, W::one(), sink))?;
+    }
+
+    for s in fst.states_iter() {
+        if !fst.is_final(s)? {
+            ofst.set_final(s, W::one())?;
+        }
+        let trs = fst.get_trs(s)?;
+        let mut labels = BTreeSet::new();
+        for tr in trs.trs() {
+            labels.insert(tr.ilabel);
+            ofst.add_tr(s, Tr::new(tr.ilabel, tr.ilabel, W::one(), tr.nextstate))?;
+        }
+        for &label in alphabet.difference(&labels) {
+            ofst.add_tr(s, Tr::new(label, label, W::one(), sink))?;
+        }
+    }
+    tr_sort(&mut ofst, ILabelCompare {});
+    Ok(ofst)
+}
+
+/// Computes the difference of two acceptors : the result accepts the strings accepted by
+/// `fst1` that are not accepted by `fst2`, with their weight in `fst1`.
+///
+/// `fst2` must be an unweighted, deterministic and epsilon-free acceptor. It is complemented
+/// over the labels of both FSTs, so that the strings containing labels unknown to `fst2` are
+/// kept, and the complement is composed with `fst1`.
+///
+/// # Example
+/// ```
+/// # #[macro_use] extern crate rustfst;
+/// # use rustfst::fst_impls::VectorFst;
+/// # use rustfst::fst_traits::{Fst, MutableFst};
+/// # use rustfst::utils::acceptor;
+/// # use rustfst::FstPath;
+/// # use rustfst::semirings::{Semiring, TropicalWeight};
+/// # use rustfst::algorithms::{difference, union::union};
+/// # use anyhow::Result;
+/// # fn main() -> Result<()> {
+/// let mut fst1: VectorFst<TropicalWeight> = fst![1, 2; 1.0];
+/// let fst3: VectorFst<TropicalWeight> = fst![3; 2.0];
+/// union(&mut fst1, &fst3)?;
+/// let fst2: VectorFst<TropicalWeight> = fst![1, 2];
+///
+/// let res = difference(&fst1, &fst2)?;
+/// assert_eq!(res.paths_iter().collect::<Vec<_>>(), vec![fst_path![3; 2.0]]);
+/// # Ok(())
+/// # }
+/// ```
+pub fn difference<W, F1, F2>(fst1: &F1, fst2: &F2) -> Result<VectorFst<W>>
+where
+    W: Semiring,
+    F1: ExpandedFst<W>,
+    F2: ExpandedFst<W>,
+{
+    let props1 = compute_fst_properties(
+        fst1,
+        FstProperties::ACCEPTOR | FstProperties::NOT_ACCEPTOR,
+        &mut FstProperties::empty(),
+        true,
+    )?;
+    if !props1.contains(FstProperties::ACCEPTOR) {
+        bail!("Difference : The first FST must be an acceptor");
+    }
+    let mask = FstProperties::ACCEPTOR
+        | FstProperties::NO_EPSILONS
+        | FstProperties::I_DETERMINISTIC
+        | FstProperties::UNWEIGHTED;
+    let props2 = compute_fst_properties(fst2, mask, &mut FstProperties::empty(), true)?;
+    if !props2.contains(mask) {
+        bail!(
+            "Difference : The second FST must be an unweighted, deterministic and epsilon-free acceptor"
+        );
+    }
+
+    let mut labels = BTreeSet::new();
+    alphabet(fst1, &mut labels)?;
+    alphabet(fst2, &mut labels)?;
+    let mut complement_fst2 = complement(fst2, &labels)?;
+    if let Some(symt) = fst2.input_symbols() {
+        complement_fst2.set_input_symbols(symt.clone());
+        complement_fst2.set_output_symbols(symt.clone());
+    }
+    compose::<_, F1, VectorFst<W>, _, _, _>(fst1, &complement_fst2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::algorithms::union::union;
+    use crate::fst_path::FstPath;
+    use crate::semirings::TropicalWeight;
+    use crate::utils::acceptor;
+
+    type F = VectorFst<TropicalWeight>;
+
+    fn paths(fst: &F) -> Vec<(Vec<Label>, TropicalWeight)> {
+        let mut paths: Vec<_> = fst
+            .paths_iter()
+            .map(|p: FstPath<TropicalWeight>| (p.ilabels, p.weight))
+            .collect();
+        paths.sort_by(|a, b| a.0.cmp(&b.0));
+        paths
+    }
+
+    #[test]
+    fn test_difference_alphabet() -> Result<()> {
+        // fst1 accepts 1 2, 1 3, 4 and 1 4 where 3 and 4 are unknown to fst2.
+        let mut fst1: F = acceptor(&[1, 2], TropicalWeight::new(1.0));
+        union(
+            &mut fst1,
+            &acceptor::<_, F>(&[1, 3], TropicalWeight::new(2.0)),
+        )?;
+        union(&mut fst1, &acceptor::<_, F>(&[4], TropicalWeight::new(3.0)))?;
+        union(
+            &mut fst1,
+            &acceptor::<_, F>(&[1, 4], TropicalWeight::new(4.0)),
+        )?;
+        let fst2: F = acceptor(&[1, 2], TropicalWeight::one());
+
+        let res = difference(&fst1, &fst2)?;
+        assert_eq!(
+            paths(&res),
+            vec![
+                (vec![1, 3], TropicalWeight::new(2.0)),
+                (vec![1, 4], TropicalWeight::new(4.0)),
+                (vec![4], TropicalWeight::new(3.0))
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_difference_prefixes_and_empty() -> Result<()> {
+        // Strings of 1 of length 0 to 3, minus the ones of length 1 or 2.
+        let mut fst1: F = acceptor(&[], TropicalWeight::one());
+        for n in 1..4 {
+            union(
+                &mut fst1,
+                &acceptor::<_, F>(&vec![1; n], TropicalWeight::new(n as f32)),
+            )?;
+        }
+        let mut fst2 = F::new();
+        fst2.add_states(3);
+        fst2.set_start(0)?;
+        fst2.set_final(1, TropicalWeight::one())?;
+        fst2.set_final(2, TropicalWeight::one())?;
+        fst2.add_tr(0, Tr::new(1, 1, TropicalWeight::one(), 1))?;
+        fst2.add_tr(1, Tr::new(1, 1, TropicalWeight::one(), 2))?;
+
+        let res = difference(&fst1, &fst2)?;
+        assert_eq!(
+            paths(&res),
+            vec![
+                (vec![], TropicalWeight::one()),
+                (vec![1, 1, 1], TropicalWeight::new(3.0))
+            ]
+        );
+
+        // Removing nothing keeps everything.
+        let res = difference(&fst2, &F::new())?;
+        assert_eq!(paths(&res).len(), 2);
+        // Removing everything gives an empty FST.
+        let res = difference(&fst2, &fst2)?;
+        assert_eq!(res.paths_iter().count(), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_difference_errors() -> Result<()> {
+        let fst1: F = acceptor(&[1, 2], TropicalWeight::one());
+        let weighted: F = acceptor(&[1, 2], TropicalWeight::new(1.0));
+        assert!(difference(&fst1, &weighted).is_err());
+
+        let mut non_det = fst1.clone();
+        non_det.add_tr(0, Tr::new(1, 1, TropicalWeight::one(), 0))?;
+        assert!(difference(&fst1, &non_det).is_err());
+
+        let mut transducer = fst1.clone();
+        transducer.add_tr(0, Tr::new(1, 2, TropicalWeight::one(), 0))?;
+        assert!(difference(&transducer, &fst1).is_err());
+        Ok(())
+    }
+}
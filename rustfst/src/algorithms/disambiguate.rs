@@ -0,0 +1,533 @@
+use std::cmp::Ordering;
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use anyhow::Result;
+use superslice::Ext;
+
+use crate::algorithms::encode::{decode, encode, EncodeType};
+use crate::algorithms::split_components::UnionFind;
+use crate::algorithms::tr_compares::{ILabelCompare, TrCompare};
+use crate::algorithms::{connect, fst_convert_from_ref, tr_sort};
+use crate::fst_impls::VectorFst;
+use crate::fst_properties::FstProperties;
+use crate::fst_traits::{AllocableFst, CoreFst, ExpandedFst, Fst, MutableFst};
+use crate::semirings::{
+    DivideType, Semiring, SemiringProperties, WeaklyDivisibleSemiring, WeightQuantize,
+};
+use crate::{StateId, Tr, Trs, KDELTA};
+
+/// Configuration for [`disambiguate_with_config`](fn.disambiguate_with_config.html).
+#[derive(Clone, Copy, Debug, PartialOrd, PartialEq)]
+pub struct DisambiguateConfig {
+    pub delta: f32,
+}
+
+impl DisambiguateConfig {
+    pub fn new(delta: f32) -> Self {
+        Self { delta }
+    }
+
+    pub fn with_delta(self, delta: f32) -> Self {
+        Self { delta }
+    }
+}
+
+impl Default for DisambiguateConfig {
+    fn default() -> Self {
+        Self { delta: KDELTA }
+    }
+}
+
+/// Compare input labels and then next states.
+struct ILabelNextStateCompare {}
+
+impl TrCompare for ILabelNextStateCompare {
+    fn compare<W: Semiring>(a: &Tr<W>, b: &Tr<W>) -> Ordering {
+        a.ilabel
+            .cmp(&b.ilabel)
+            .then_with(|| a.nextstate.cmp(&b.nextstate))
+    }
+
+    fn properties(inprops: FstProperties) -> FstProperties {
+        ILabelCompare::properties(inprops)
+    }
+}
+
+/// State of the pre-disambiguated FST : a state of the input FST (the head) and the
+/// weighted subset of the states related to the head reached with the same string.
+type PreDisambiguateTuple<W> = (StateId, Vec<(StateId, W)>);
+
+/// A transition of the pre-disambiguated FST, identified by its source state and its
+/// position. `None` stands for the super-final transition.
+type TrId = (StateId, Option<usize>);
+
+struct PreDisambiguateStateTable<W: Semiring> {
+    tuples: Vec<PreDisambiguateTuple<W>>,
+    tuple_to_state: HashMap<PreDisambiguateTuple<W>, StateId>,
+}
+
+impl<W: Semiring> PreDisambiguateStateTable<W> {
+    fn new() -> Self {
+        Self {
+            tuples: vec![],
+            tuple_to_state: HashMap::new(),
+        }
+    }
+
+    fn find_state(&mut self, tuple: PreDisambiguateTuple<W>, ofst: &mut VectorFst<W>) -> StateId {
+        match self.tuple_to_state.entry(tuple) {
+            Entry::Occupied(e) => *e.get(),
+            Entry::Vacant(e) => {
+                self.tuples.push(e.key().clone());
+                *e.insert(ofst.add_state())
+            }
+        }
+    }
+}
+
+/// Pairs of states `(p, q)` that can both be reached with the same string from the start
+/// state and from which the same string leads to a final state, i.e. the states of the
+/// intersection of the acceptor with itself that are both accessible and coaccessible.
+fn compute_relation<W: Semiring>(fst: &VectorFst<W>) -> Result<HashSet<(StateId, StateId)>> {
+    let start = match fst.start() {
+        Some(s) => s,
+        None => return Ok(HashSet::new()),
+    };
+    let mut pairs = vec![(start, start)];
+    let mut pair_ids = HashMap::new();
+    pair_ids.insert((start, start), 0);
+    let mut predecessors: Vec<Vec<usize>> = vec![vec![]];
+    let mut final_pairs = vec![];
+
+    let mut idx = 0;
+    while idx < pairs.len() {
+        let (p, q) = pairs[idx];
+        if fst.is_final(p)? && fst.is_final(q)? {
+            final_pairs.push(idx);
+        }
+        let trs_p = fst.get_trs(p)?;
+        let trs_q = fst.get_trs(q)?;
+        for tr_p in trs_p.trs() {
+            let range = trs_q.trs().equal_range_by(|x| x.ilabel.cmp(&tr_p.ilabel));
+            for tr_q in &trs_q.trs()[range] {
+                let next = (tr_p.nextstate, tr_q.nextstate);
+                let next_id = match pair_ids.entry(next) {
+                    Entry::Occupied(e) => *e.get(),
+                    Entry::Vacant(e) => {
+                        pairs.push(next);
+                        predecessors.push(vec![]);
+                        *e.insert(pairs.len() - 1)
+                    }
+                };
+                predecessors[next_id].push(idx);
+            }
+        }
+        idx += 1;
+    }
+
+    let mut coaccessible = vec![false; pairs.len()];
+    let mut queue = VecDeque::new();
+    for idx in final_pairs {
+        coaccessible[idx] = true;
+        queue.push_back(idx);
+    }
+    while let Some(idx) = queue.pop_front() {
+        for &pred in &predecessors[idx] {
+            if !coaccessible[pred] {
+                coaccessible[pred] = true;
+                queue.push_back(pred);
+            }
+        }
+    }
+
+    Ok(pairs
+        .into_iter()
+        .zip(coaccessible.into_iter())
+        .filter(|(_, c)| *c)
+        .map(|(pair, _)| pair)
+        .collect())
+}
+
+#[derive(Default)]
+struct Disambiguator {
+    // State of the input FST at the head of each state of the pre-disambiguated FST.
+    head: Vec<StateId>,
+    coreachable: HashSet<(StateId, StateId)>,
+    queue: VecDeque<(StateId, StateId)>,
+    // Pairs of ambiguous transitions (a1, a2) : a1 is removed unless a2 is.
+    candidates: Vec<(TrId, TrId)>,
+    // Co-reachable states with the same head that need to be merged.
+    merge: Option<UnionFind>,
+    ambiguous: HashSet<TrId>,
+}
+
+impl Disambiguator {
+    /// Determinizes the acceptor keeping track of the state of the input FST followed by
+    /// each path : a subset only contains the states related to its head. The result has the
+    /// same strings with the same weights but only ambiguities between states with distinct
+    /// heads remain.
+    fn pre_disambiguate<W: WeaklyDivisibleSemiring + WeightQuantize>(
+        &mut self,
+        fst: &VectorFst<W>,
+        delta: f32,
+    ) -> Result<VectorFst<W>> {
+        let relation = compute_relation(fst)?;
+        let mut ofst = VectorFst::new();
+        let start = match fst.start() {
+            Some(s) => s,
+            None => return Ok(ofst),
+        };
+
+        let mut state_table = PreDisambiguateStateTable::new();
+        let ostart = state_table.find_state((start, vec![(start, W::one())]), &mut ofst);
+        ofst.set_start(ostart)?;
+
+        let mut state = 0;
+        while let Some((head, subset)) = state_table.tuples.get(state as usize).cloned() {
+            if fst.is_final(head)? {
+                let mut final_weight = W::zero();
+                for (s, w) in &subset {
+                    if let Some(fw) = fst.final_weight(*s)? {
+                        final_weight.plus_assign(w.times(fw)?)?;
+                    }
+                }
+                ofst.set_final(state, final_weight)?;
+            }
+
+            let head_trs = fst.get_trs(head)?;
+            let mut previous = None;
+            for tr in head_trs.trs() {
+                // Parallel trs lead to the same subset.
+                if previous == Some((tr.ilabel, tr.nextstate)) {
+                    continue;
+                }
+                previous = Some((tr.ilabel, tr.nextstate));
+
+                let mut dest_subset: Vec<(StateId, W)> = vec![];
+                for (s, w) in &subset {
+                    let trs = fst.get_trs(*s)?;
+                    let range = trs.trs().equal_range_by(|x| x.ilabel.cmp(&tr.ilabel));
+                    for dest_tr in &trs.trs()[range] {
+                        if relation.contains(&(tr.nextstate, dest_tr.nextstate)) {
+                            dest_subset.push((dest_tr.nextstate, w.times(&dest_tr.weight)?));
+                        }
+                    }
+                }
+                dest_subset.sort_by_key(|e| e.0);
+                let mut normalized_subset: Vec<(StateId, W)> = vec![];
+                for (s, w) in dest_subset {
+                    match normalized_subset.last_mut() {
+                        Some((last_s, last_w)) if *last_s == s => last_w.plus_assign(w)?,
+                        _ => normalized_subset.push((s, w)),
+                    }
+                }
+                let mut weight = W::zero();
+                for (_, w) in &normalized_subset {
+                    weight.plus_assign(w)?;
+                }
+                for (_, w) in normalized_subset.iter_mut() {
+                    w.divide_assign(&weight, DivideType::DivideLeft)?;
+                    w.quantize_assign(delta)?;
+                }
+
+                let nextstate =
+                    state_table.find_state((tr.nextstate, normalized_subset), &mut ofst);
+                ofst.add_tr(state, Tr::new(tr.ilabel, tr.olabel, weight, nextstate))?;
+            }
+            state += 1;
+        }
+
+        self.head = state_table.tuples.into_iter().map(|t| t.0).collect();
+        Ok(ofst)
+    }
+
+    /// Explores the intersection of the FST with itself to find the pairs of transitions
+    /// leaving distinct states that lead to the same state with the same string.
+    fn find_ambiguities<W: Semiring>(&mut self, fst: &VectorFst<W>) -> Result<()> {
+        let start = match fst.start() {
+            Some(s) => s,
+            None => return Ok(()),
+        };
+        self.coreachable.insert((start, start));
+        self.queue.push_back((start, start));
+        while let Some((s1, s2)) = self.queue.pop_front() {
+            self.find_ambiguous_pairs(fst, s1, s2)?;
+        }
+        Ok(())
+    }
+
+    fn find_ambiguous_pairs<W: Semiring>(
+        &mut self,
+        fst: &VectorFst<W>,
+        s1: StateId,
+        s2: StateId,
+    ) -> Result<()> {
+        let trs1 = fst.get_trs(s1)?;
+        let trs2 = fst.get_trs(s2)?;
+        for (pos1, tr1) in trs1.trs().iter().enumerate() {
+            let range = trs2.trs().equal_range_by(|x| x.ilabel.cmp(&tr1.ilabel));
+            for pos2 in range {
+                let tr2 = &trs2.trs()[pos2];
+                if s1 != s2 && tr1.nextstate == tr2.nextstate {
+                    self.insert_candidate((s1, Some(pos1)), (s2, Some(pos2)));
+                }
+                let pair = if tr1.nextstate <= tr2.nextstate {
+                    (tr1.nextstate, tr2.nextstate)
+                } else {
+                    (tr2.nextstate, tr1.nextstate)
+                };
+                if self.coreachable.insert(pair) {
+                    if pair.0 != pair.1 && self.head[pair.0 as usize] == self.head[pair.1 as usize]
+                    {
+                        self.merge
+                            .get_or_insert_with(|| UnionFind::new(fst.num_states()))
+                            .union(pair.0, pair.1);
+                    } else {
+                        self.queue.push_back(pair);
+                    }
+                }
+            }
+        }
+        if s1 != s2 && fst.is_final(s1)? && fst.is_final(s2)? {
+            self.insert_candidate((s1, None), (s2, None));
+        }
+        Ok(())
+    }
+
+    fn insert_candidate(&mut self, a1: TrId, a2: TrId) {
+        if self.head[a1.0 as usize] > self.head[a2.0 as usize] {
+            self.candidates.push((a1, a2));
+        } else {
+            self.candidates.push((a2, a1));
+        }
+    }
+
+    /// Merges the co-reachable states sharing the same head as they only produce spurious
+    /// ambiguities, then searches the ambiguities again.
+    fn remove_splits<W: Semiring>(&mut self, fst: &mut VectorFst<W>) -> Result<()> {
+        let mut merge = match self.merge.take() {
+            Some(merge) => merge,
+            None => return Ok(()),
+        };
+        for s in 0..fst.num_states() as StateId {
+            let mut it_tr = fst.tr_iter_mut(s)?;
+            for idx in 0..it_tr.len() {
+                let nextstate = it_tr.get(idx).unwrap().nextstate;
+                let merged_nextstate = merge.find(nextstate);
+                if merged_nextstate != nextstate {
+                    it_tr.set_nextstate(idx, merged_nextstate)?;
+                }
+            }
+        }
+
+        self.coreachable.clear();
+        self.candidates.clear();
+        self.find_ambiguities(fst)?;
+        if self.merge.is_some() {
+            bail!("Disambiguate : Unable to remove spurious ambiguities");
+        }
+        Ok(())
+    }
+
+    fn mark_ambiguities(&mut self) {
+        let head = &self.head;
+        self.candidates
+            .sort_by_key(|(a1, _)| (head[a1.0 as usize], a1.0, a1.1));
+        for (a1, a2) in self.candidates.drain(..) {
+            if !self.ambiguous.contains(&a2) {
+                self.ambiguous.insert(a1);
+            }
+        }
+        self.coreachable.clear();
+    }
+
+    fn remove_ambiguities<W: Semiring>(&mut self, fst: &mut VectorFst<W>) -> Result<()> {
+        if self.ambiguous.is_empty() {
+            return Ok(());
+        }
+        // The ambiguous transitions are redirected to a dead state removed by connect.
+        let dead = fst.add_state();
+        for (s, pos) in self.ambiguous.drain() {
+            match pos {
+                Some(pos) => fst.tr_iter_mut(s)?.set_nextstate(pos, dead)?,
+                None => fst.delete_final_weight(s)?,
+            }
+        }
+        connect(fst)
+    }
+}
+
+/// Creates an equivalent FST with no two successful paths with the same input and output
+/// strings, using the default configuration.
+pub fn disambiguate<W, F1, F2>(ifst: &F1) -> Result<F2>
+where
+    W: WeaklyDivisibleSemiring + WeightQuantize,
+    F1: Fst<W>,
+    F2: MutableFst<W> + AllocableFst<W>,
+{
+    disambiguate_with_config(ifst, DisambiguateConfig::default())
+}
+
+/// Creates an equivalent FST with no two successful paths with the same input and output
+/// strings. For each such pair of strings, a single path is kept with the weight of the
+/// pair in the input FST. Contrary to [`determinize`](determinize/fn.determinize.html), the
+/// result is not deterministic but the algorithm terminates on some FSTs that can't be
+/// determinized and the result is usually smaller.
+///
+/// This follows the algorithm of Mohri and Riley, "On the disambiguation of weighted automata"
+/// as implemented in OpenFST : the labels are encoded so that the FST is seen as an acceptor
+/// over pairs of labels, a determinization keeps track of the state followed by each path
+/// and the remaining ambiguous transitions are then removed. Epsilon transitions are treated
+/// as regular symbols.
+///
+/// The semiring must have the path property, e.g. the tropical semiring.
+///
+/// # Example
+/// ```
+/// # use rustfst::fst_impls::VectorFst;
+/// # use rustfst::fst_traits::{CoreFst, MutableFst, Fst};
+/// # use rustfst::semirings::{Semiring, TropicalWeight};
+/// # use rustfst::algorithms::disambiguate;
+/// # use rustfst::Tr;
+/// # use anyhow::Result;
+/// # fn main() -> Result<()> {
+/// let mut fst = VectorFst::<TropicalWeight>::new();
+/// fst.add_states(4);
+/// fst.set_start(0)?;
+/// fst.set_final(3, TropicalWeight::one())?;
+/// fst.add_tr(0, Tr::new(1, 1, 1.0, 1))?;
+/// fst.add_tr(0, Tr::new(1, 1, 2.0, 2))?;
+/// fst.add_tr(1, Tr::new(2, 2, 3.0, 3))?;
+/// fst.add_tr(2, Tr::new(2, 2, 1.0, 3))?;
+///
+/// let res: VectorFst<_> = disambiguate(&fst)?;
+/// let paths: Vec<_> = res.paths_iter().collect();
+/// assert_eq!(paths.len(), 1);
+/// assert_eq!(paths[0].ilabels, vec![1, 2]);
+/// assert_eq!(paths[0].weight, TropicalWeight::new(3.0));
+/// # Ok(())
+/// # }
+/// ```
+pub fn disambiguate_with_config<W, F1, F2>(ifst: &F1, config: DisambiguateConfig) -> Result<F2>
+where
+    W: WeaklyDivisibleSemiring + WeightQuantize,
+    F1: Fst<W>,
+    F2: MutableFst<W> + AllocableFst<W>,
+{
+    if !W::properties().contains(SemiringProperties::PATH) {
+        bail!("Disambiguate : Weight must have the path property");
+    }
+    let mut fst: VectorFst<W> = fst_convert_from_ref(ifst);
+    connect(&mut fst)?;
+    let encode_table = encode(&mut fst, EncodeType::EncodeLabels)?;
+    tr_sort(&mut fst, ILabelNextStateCompare {});
+
+    let mut disambiguator = Disambiguator::default();
+    let mut ofst = disambiguator.pre_disambiguate(&fst, config.delta)?;
+    tr_sort(&mut ofst, ILabelNextStateCompare {});
+    disambiguator.find_ambiguities(&ofst)?;
+    disambiguator.remove_splits(&mut ofst)?;
+    disambiguator.mark_ambiguities();
+    disambiguator.remove_ambiguities(&mut ofst)?;
+
+    decode(&mut ofst, encode_table)?;
+    let mut res: F2 = fst_convert_from_ref(&ofst);
+    res.set_symts_from_fst(ifst);
+    Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::algorithms::determinize::determinize;
+    use crate::algorithms::isomorphic;
+    use crate::semirings::TropicalWeight;
+    use crate::Label;
+
+    type F = VectorFst<TropicalWeight>;
+
+    fn paths(fst: &F) -> Vec<(Vec<Label>, Vec<Label>, TropicalWeight)> {
+        let mut paths: Vec<_> = fst
+            .paths_iter()
+            .map(|p| (p.ilabels, p.olabels, p.weight))
+            .collect();
+        paths.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        paths
+    }
+
+    #[test]
+    fn test_disambiguate_deterministic() -> Result<()> {
+        let mut fst = F::new();
+        fst.add_states(4);
+        fst.set_start(0)?;
+        fst.set_final(3, 0.5)?;
+        fst.add_tr(0, Tr::new(1, 1, 1.0, 1))?;
+        fst.add_tr(0, Tr::new(2, 2, 2.0, 2))?;
+        fst.add_tr(1, Tr::new(3, 3, 1.0, 3))?;
+        fst.add_tr(2, Tr::new(3, 3, 1.0, 3))?;
+        fst.add_tr(3, Tr::new(4, 4, 1.0, 1))?;
+
+        let res: F = disambiguate(&fst)?;
+        assert!(isomorphic(&res, &fst)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_disambiguate_weighted_acceptor() -> Result<()> {
+        // Several paths for "1 2" and "1 3", some of them with equal weights.
+        let mut fst = F::new();
+        fst.add_states(5);
+        fst.set_start(0)?;
+        fst.set_final(4, TropicalWeight::one())?;
+        fst.set_final(3, 1.0)?;
+        fst.add_tr(0, Tr::new(1, 1, 1.0, 1))?;
+        fst.add_tr(0, Tr::new(1, 1, 2.0, 2))?;
+        fst.add_tr(0, Tr::new(1, 1, 1.0, 3))?;
+        fst.add_tr(1, Tr::new(2, 2, 3.0, 4))?;
+        fst.add_tr(2, Tr::new(2, 2, 1.0, 4))?;
+        fst.add_tr(3, Tr::new(2, 2, 2.0, 4))?;
+        fst.add_tr(1, Tr::new(3, 3, 1.0, 4))?;
+        fst.add_tr(3, Tr::new(3, 3, 1.0, 4))?;
+
+        let res: F = disambiguate(&fst)?;
+        let res_det: F = determinize(&fst)?;
+        assert_eq!(paths(&res), paths(&res_det));
+        assert_eq!(paths(&res).len(), 3);
+
+        // Ties are broken the same way each time.
+        let res_2: F = disambiguate(&fst)?;
+        assert_eq!(res, res_2);
+
+        // The output labels are part of the strings.
+        let mut fst_2 = fst.clone();
+        fst_2.add_tr(0, Tr::new(1, 5, 0.5, 1))?;
+        let res: F = disambiguate(&fst_2)?;
+        let mut expected = paths(&res_det);
+        expected.push((vec![1, 2], vec![5, 2], TropicalWeight::new(3.5)));
+        expected.push((vec![1, 3], vec![5, 3], TropicalWeight::new(1.5)));
+        expected.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        assert_eq!(paths(&res), expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_disambiguate_not_determinizable() -> Result<()> {
+        // Unambiguous but doesn't have the twins property : determinize doesn't terminate.
+        let mut fst = F::new();
+        fst.add_states(4);
+        fst.set_start(0)?;
+        fst.set_final(3, TropicalWeight::one())?;
+        fst.add_tr(0, Tr::new(1, 1, 1.0, 1))?;
+        fst.add_tr(0, Tr::new(1, 1, 2.0, 2))?;
+        fst.add_tr(1, Tr::new(2, 2, 1.0, 1))?;
+        fst.add_tr(2, Tr::new(2, 2, 2.0, 2))?;
+        fst.add_tr(1, Tr::new(3, 3, 1.0, 3))?;
+        fst.add_tr(2, Tr::new(4, 4, 1.0, 3))?;
+
+        let res: F = disambiguate(&fst)?;
+        assert!(isomorphic(&res, &fst)?);
+        Ok(())
+    }
+}
@@ -6,12 +6,12 @@ use crate::fst_properties::FstProperties;
 use crate::fst_traits::MutableFst;
 use crate::{Semiring, Tr};
 
-struct DecodeMapper<W: Semiring> {
-    encode_table: EncodeTable<W>,
+struct DecodeMapper<'a, W: Semiring> {
+    encode_table: &'a EncodeTable<W>,
 }
 
-impl<W: Semiring> DecodeMapper<W> {
-    pub fn new(encode_table: EncodeTable<W>) -> Self {
+impl<'a, W: Semiring> DecodeMapper<'a, W> {
+    pub fn new(encode_table: &'a EncodeTable<W>) -> Self {
         DecodeMapper { encode_table }
     }
 
@@ -24,12 +24,12 @@ impl<W: Semiring> DecodeMapper<W> {
     }
 }
 
-impl<W: Semiring> TrMapper<W> for DecodeMapper<W> {
+impl<'a, W: Semiring> TrMapper<W> for DecodeMapper<'a, W> {
     fn tr_map(&self, tr: &mut Tr<W>) -> Result<()> {
         let tuple = self
             .encode_table
             .0
-            .borrow_mut()
+            .borrow()
             .decode(tr.ilabel as usize)
             .ok_or_else(|| format_err!("Can't decode ilabel : {:?}", tr.ilabel))?
             .clone();
@@ -70,6 +70,16 @@ impl<W: Semiring> TrMapper<W> for DecodeMapper<W> {
 /// The `decode` operation takes as input an encoded FST and the corresponding `EncodeTable` object
 /// and reverts the encoding.
 pub fn decode<W, F>(fst: &mut F, encode_table: EncodeTable<W>) -> Result<()>
+where
+    W: Semiring,
+    F: MutableFst<W>,
+{
+    decode_with_table(fst, &encode_table)
+}
+
+/// Same as [`decode`](fn.decode.html) but the `EncodeTable` is borrowed, so that it can be
+/// used to decode other FSTs encoded with it.
+pub fn decode_with_table<W, F>(fst: &mut F, encode_table: &EncodeTable<W>) -> Result<()>
 where
     W: Semiring,
     F: MutableFst<W>,
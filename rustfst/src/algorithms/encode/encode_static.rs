@@ -1,22 +1,18 @@
-use std::cell::RefCell;
-
 use anyhow::{Context, Result};
 
-use crate::algorithms::encode::{EncodeTable, EncodeTableMut, EncodeType};
+use crate::algorithms::encode::{EncodeTable, EncodeType};
 use crate::algorithms::{FinalTr, MapFinalAction, TrMapper};
 use crate::fst_properties::FstProperties;
 use crate::fst_traits::MutableFst;
 use crate::{Label, Semiring, Tr};
 
-struct EncodeMapper<W: Semiring> {
-    encode_table: EncodeTable<W>,
+pub(crate) struct EncodeMapper<'a, W: Semiring> {
+    encode_table: &'a EncodeTable<W>,
 }
 
-impl<W: Semiring> EncodeMapper<W> {
-    pub fn new(encode_type: EncodeType) -> Self {
-        EncodeMapper {
-            encode_table: EncodeTable(RefCell::new(EncodeTableMut::new(encode_type))),
-        }
+impl<'a, W: Semiring> EncodeMapper<'a, W> {
+    pub fn new(encode_table: &'a EncodeTable<W>) -> Self {
+        EncodeMapper { encode_table }
     }
 
     pub fn encode_weights(&self) -> bool {
@@ -28,7 +24,7 @@ impl<W: Semiring> EncodeMapper<W> {
     }
 }
 
-impl<W: Semiring> TrMapper<W> for EncodeMapper<W> {
+impl<'a, W: Semiring> TrMapper<W> for EncodeMapper<'a, W> {
     fn tr_map(&self, tr: &mut Tr<W>) -> Result<()> {
         let tuple = self.encode_table.0.borrow().tr_to_tuple(tr);
         let label = self.encode_table.0.borrow_mut().encode(tuple) as Label;
@@ -89,13 +85,108 @@ impl<W: Semiring> TrMapper<W> for EncodeMapper<W> {
 ///
 /// The encoding of each pair or triple of labels and/or weights as a unique key is stored
 /// in an `EncodeTable` object.
+///
+/// With `EncodeType::EncodeWeights`, the weights are moved into the input labels and the
+/// output labels are left untouched : a weighted transducer becomes an unweighted one. With
+/// `EncodeType::EncodeLabels`, the output labels are moved into the input labels and the
+/// weights are left untouched : a transducer becomes an acceptor.
 pub fn encode<W, F>(fst: &mut F, encode_type: EncodeType) -> Result<EncodeTable<W>>
 where
     W: Semiring,
     F: MutableFst<W>,
 {
-    let mut encode_mapper = EncodeMapper::new(encode_type);
+    let encode_table = EncodeTable::new(encode_type);
+    encode_with_table(fst, &encode_table)?;
+    Ok(encode_table)
+}
+
+/// Same as [`encode`](fn.encode.html) but the keys are taken from, and added to, an existing
+/// `EncodeTable`. The encode type is the one of the table.
+///
+/// Two FSTs encoded with the same table use the same key for the same tuple. Hence they can
+/// be combined in their encoded form, and the result decoded with this table.
+pub fn encode_with_table<W, F>(fst: &mut F, encode_table: &EncodeTable<W>) -> Result<()>
+where
+    W: Semiring,
+    F: MutableFst<W>,
+{
+    let mut encode_mapper = EncodeMapper::new(encode_table);
     fst.tr_map(&mut encode_mapper)
-        .with_context(|| format_err!("Error calling TrMap with EncodeMapper."))?;
-    Ok(encode_mapper.encode_table)
+        .with_context(|| format_err!("Error calling TrMap with EncodeMapper."))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::encode::decode_with_table;
+    use crate::fst_impls::VectorFst;
+    use crate::fst_traits::CoreFst;
+    use crate::semirings::TropicalWeight;
+
+    fn transducer(weight: f32) -> Result<VectorFst<TropicalWeight>> {
+        let mut fst = VectorFst::new();
+        fst.add_states(2);
+        fst.set_start(0)?;
+        fst.emplace_tr(0, 1, 2, weight, 1)?;
+        fst.emplace_tr(0, 3, 4, 1.0, 1)?;
+        fst.set_final(1, 2.0)?;
+        Ok(fst)
+    }
+
+    #[test]
+    fn test_encode_weights_only() -> Result<()> {
+        let raw = transducer(0.5)?;
+        let mut fst = raw.clone();
+        let table = encode(&mut fst, EncodeType::EncodeWeights)?;
+        assert_eq!(table.encode_type(), EncodeType::EncodeWeights);
+
+        let props = fst.compute_and_update_properties_all()?;
+        assert!(props.contains(FstProperties::UNWEIGHTED));
+        assert!(!props.contains(FstProperties::ACCEPTOR));
+        let olabels: Vec<_> = fst.get_trs(0)?.iter().map(|tr| tr.olabel).collect();
+        assert_eq!(olabels, vec![2, 4]);
+
+        decode_with_table(&mut fst, &table)?;
+        assert_eq!(fst, raw);
+        Ok(())
+    }
+
+    #[test]
+    fn test_encode_labels_only() -> Result<()> {
+        let raw = transducer(0.5)?;
+        let mut fst = raw.clone();
+        let table = encode(&mut fst, EncodeType::EncodeLabels)?;
+
+        let props = fst.compute_and_update_properties_all()?;
+        assert!(props.contains(FstProperties::ACCEPTOR));
+        assert!(props.contains(FstProperties::WEIGHTED));
+        assert_eq!(fst.final_weight(1)?, Some(TropicalWeight::new(2.0)));
+
+        decode_with_table(&mut fst, &table)?;
+        assert_eq!(fst, raw);
+        Ok(())
+    }
+
+    #[test]
+    fn test_encode_table_reused_for_another_fst() -> Result<()> {
+        let raw_1 = transducer(0.5)?;
+        let raw_2 = transducer(3.0)?;
+        let mut fst_1 = raw_1.clone();
+        let mut fst_2 = raw_2.clone();
+
+        let table = EncodeTable::new(EncodeType::EncodeWeightsAndLabels);
+        encode_with_table(&mut fst_1, &table)?;
+        let n = table.len();
+        encode_with_table(&mut fst_2, &table)?;
+
+        // The (3, 4, 1.0) tuple is shared, the (1, 2, 3.0) one is new.
+        assert_eq!(table.len(), n + 1);
+        assert_eq!(fst_1.get_trs(0)?[1].ilabel, fst_2.get_trs(0)?[1].ilabel);
+
+        decode_with_table(&mut fst_2, &table)?;
+        decode_with_table(&mut fst_1, &table)?;
+        assert_eq!(fst_1, raw_1);
+        assert_eq!(fst_2, raw_2);
+        Ok(())
+    }
 }
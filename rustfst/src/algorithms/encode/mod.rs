@@ -1,8 +1,8 @@
-pub use decode_static::decode;
-pub use encode_static::encode;
+pub use decode_static::{decode, decode_with_table};
+pub(crate) use encode_static::EncodeMapper;
+pub use encode_static::{encode, encode_with_table};
 pub use encode_type::EncodeType;
 pub use table::EncodeTable;
-pub(self) use table::EncodeTableMut;
 
 mod decode_static;
 mod encode_static;
@@ -20,8 +20,34 @@ pub struct EncodeTableMut<W: Semiring> {
     tuple_to_id: HashMap<EncodeTuple<W>, usize>,
 }
 
+/// Mapping between the keys used as labels by [`encode`](fn.encode.html) and the encoded
+/// (input label, output label, weight) tuples.
+///
+/// The table can be reused to encode several FSTs with the same keys using
+/// [`encode_with_table`](fn.encode_with_table.html), and to decode any FST encoded with it
+/// using [`decode_with_table`](fn.decode_with_table.html).
 pub struct EncodeTable<W: Semiring>(pub RefCell<EncodeTableMut<W>>);
 
+impl<W: Semiring> EncodeTable<W> {
+    /// Creates an empty table encoding the labels and/or the weights depending on `encode_type`.
+    pub fn new(encode_type: EncodeType) -> Self {
+        EncodeTable(RefCell::new(EncodeTableMut::new(encode_type)))
+    }
+
+    pub fn encode_type(&self) -> EncodeType {
+        self.0.borrow().encode_type
+    }
+
+    /// Number of distinct tuples encoded so far.
+    pub fn len(&self) -> usize {
+        self.0.borrow().id_to_tuple.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
 impl<W: Semiring> EncodeTableMut<W> {
     pub fn new(encode_type: EncodeType) -> Self {
         EncodeTableMut {
@@ -75,8 +101,10 @@ impl<W: Semiring> EncodeTableMut<W> {
         a + 1
     }
 
-    pub fn decode(&mut self, tuple_id: usize) -> Option<&EncodeTuple<W>> {
-        self.id_to_tuple.get(tuple_id - 1)
+    pub fn decode(&self, tuple_id: usize) -> Option<&EncodeTuple<W>> {
+        tuple_id
+            .checked_sub(1)
+            .and_then(|idx| self.id_to_tuple.get(idx))
     }
 }
 
@@ -0,0 +1,192 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+
+use crate::fst_traits::MutableFst;
+use crate::semirings::Semiring;
+use crate::{Label, SymbolTable, Trs, EPS_LABEL};
+
+/// Symbol added to the symbol tables by [`epsilon_to_symbol`](fn.epsilon_to_symbol.html).
+pub const VISIBLE_EPS_SYMBOL: &str = "<visible_eps>";
+
+/// Replaces the epsilon input and output labels of the FST with `label`, so that the
+/// epsilon transitions are visible when drawing or diffing the FST. This is meant for
+/// debugging only and is reverted by [`symbol_to_epsilon`](fn.symbol_to_epsilon.html).
+///
+/// An error is returned if `label` is already used by a transition. When the FST has
+/// symbol tables, `label` must be the next free label of each of them and is added
+/// with the symbol [`VISIBLE_EPS_SYMBOL`](constant.VISIBLE_EPS_SYMBOL.html).
+///
+/// # Example
+/// ```
+/// # use rustfst::fst_impls::VectorFst;
+/// # use rustfst::fst_traits::{CoreFst, MutableFst};
+/// # use rustfst::semirings::{Semiring, TropicalWeight};
+/// # use rustfst::algorithms::{epsilon_to_symbol, symbol_to_epsilon};
+/// # use rustfst::{Tr, Trs, EPS_LABEL};
+/// # use anyhow::Result;
+/// # fn main() -> Result<()> {
+/// let mut fst = VectorFst::<TropicalWeight>::new();
+/// fst.add_states(2);
+/// fst.set_start(0)?;
+/// fst.set_final(1, TropicalWeight::one())?;
+/// fst.add_tr(0, Tr::new(1, EPS_LABEL, TropicalWeight::one(), 1))?;
+/// let fst_ref = fst.clone();
+///
+/// epsilon_to_symbol(&mut fst, 100)?;
+/// assert_eq!(fst.get_trs(0)?.trs()[0].olabel, 100);
+///
+/// symbol_to_epsilon(&mut fst, 100)?;
+/// assert_eq!(fst, fst_ref);
+/// # Ok(())
+/// # }
+/// ```
+pub fn epsilon_to_symbol<W: Semiring, F: MutableFst<W>>(fst: &mut F, label: Label) -> Result<()> {
+    if label == EPS_LABEL {
+        bail!("EpsilonToSymbol : The placeholder label can't be epsilon");
+    }
+    for s in fst.states_range() {
+        for tr in fst.get_trs(s)?.trs() {
+            if tr.ilabel == label || tr.olabel == label {
+                bail!(
+                    "EpsilonToSymbol : Label {} is already used by a transition of state {}",
+                    label,
+                    s
+                );
+            }
+        }
+    }
+    let isymt = fst
+        .input_symbols()
+        .map(|symt| add_visible_eps(symt, label))
+        .transpose()?;
+    let osymt = fst
+        .output_symbols()
+        .map(|symt| add_visible_eps(symt, label))
+        .transpose()?;
+
+    relabel(fst, EPS_LABEL, label)?;
+
+    if let Some(symt) = isymt {
+        fst.set_input_symbols(symt);
+    }
+    if let Some(symt) = osymt {
+        fst.set_output_symbols(symt);
+    }
+    Ok(())
+}
+
+/// Reverts [`epsilon_to_symbol`](fn.epsilon_to_symbol.html) : replaces `label` with
+/// epsilon on both sides of the transitions and removes the
+/// [`VISIBLE_EPS_SYMBOL`](constant.VISIBLE_EPS_SYMBOL.html) added to the symbol tables.
+pub fn symbol_to_epsilon<W: Semiring, F: MutableFst<W>>(fst: &mut F, label: Label) -> Result<()> {
+    if label == EPS_LABEL {
+        bail!("SymbolToEpsilon : The placeholder label can't be epsilon");
+    }
+    relabel(fst, label, EPS_LABEL)?;
+
+    if let Some(symt) = fst
+        .input_symbols()
+        .and_then(|symt| remove_visible_eps(symt, label))
+    {
+        fst.set_input_symbols(symt);
+    }
+    if let Some(symt) = fst
+        .output_symbols()
+        .and_then(|symt| remove_visible_eps(symt, label))
+    {
+        fst.set_output_symbols(symt);
+    }
+    Ok(())
+}
+
+fn relabel<W: Semiring, F: MutableFst<W>>(fst: &mut F, from: Label, to: Label) -> Result<()> {
+    for s in fst.states_range() {
+        let mut it_tr = fst.tr_iter_mut(s)?;
+        for idx_tr in 0..it_tr.len() {
+            let tr = unsafe { it_tr.get_unchecked(idx_tr) };
+            let ilabel = if tr.ilabel == from { to } else { tr.ilabel };
+            let olabel = if tr.olabel == from { to } else { tr.olabel };
+            unsafe { it_tr.set_labels_unchecked(idx_tr, ilabel, olabel) };
+        }
+    }
+    Ok(())
+}
+
+fn add_visible_eps(symt: &Arc<SymbolTable>, label: Label) -> Result<Arc<SymbolTable>> {
+    if symt.contains_label(label) {
+        bail!(
+            "EpsilonToSymbol : Label {} is already used by the symbol {:?}",
+            label,
+            symt.get_symbol(label)
+        );
+    }
+    if label as usize != symt.len() {
+        bail!(
+            "EpsilonToSymbol : Label {} must be the next free label of the symbol table : {}",
+            label,
+            symt.len()
+        );
+    }
+    let mut symt = symt.as_ref().clone();
+    symt.add_symbol(VISIBLE_EPS_SYMBOL);
+    Ok(Arc::new(symt))
+}
+
+fn remove_visible_eps(symt: &Arc<SymbolTable>, label: Label) -> Option<Arc<SymbolTable>> {
+    if symt.get_symbol(label) != Some(VISIBLE_EPS_SYMBOL) || label as usize + 1 != symt.len() {
+        return None;
+    }
+    let mut new_symt = SymbolTable::empty();
+    for l in 0..label {
+        new_symt.add_symbol(symt.get_symbol(l)?);
+    }
+    Some(Arc::new(new_symt))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fst_impls::VectorFst;
+    use crate::fst_traits::{CoreFst, Fst};
+    use crate::semirings::TropicalWeight;
+    use crate::Tr;
+
+    #[test]
+    fn test_epsilon_to_symbol_round_trip() -> Result<()> {
+        let mut fst = VectorFst::<TropicalWeight>::new();
+        fst.add_states(3);
+        fst.set_start(0)?;
+        fst.set_final(2, TropicalWeight::one())?;
+        fst.add_tr(0, Tr::new(1, EPS_LABEL, 1.0, 1))?;
+        fst.add_tr(0, Tr::new(EPS_LABEL, EPS_LABEL, 2.0, 2))?;
+        fst.add_tr(1, Tr::new(EPS_LABEL, 2, 3.0, 2))?;
+        let mut symt = SymbolTable::new();
+        symt.add_symbols(vec!["a", "b"]);
+        let symt = Arc::new(symt);
+        fst.set_input_symbols(Arc::clone(&symt));
+        fst.set_output_symbols(Arc::clone(&symt));
+        let fst_ref = fst.clone();
+
+        // Already used by the symbol tables.
+        assert!(epsilon_to_symbol(&mut fst.clone(), 2).is_err());
+
+        epsilon_to_symbol(&mut fst, 3)?;
+        assert!(fst.get_trs(0)?.trs().iter().all(|tr| tr.olabel == 3));
+        assert_eq!(fst.get_trs(1)?.trs()[0].ilabel, 3);
+        assert_eq!(
+            fst.input_symbols().unwrap().get_symbol(3),
+            Some(VISIBLE_EPS_SYMBOL)
+        );
+
+        // Already used by a transition.
+        assert!(epsilon_to_symbol(&mut fst.clone(), 3).is_err());
+
+        symbol_to_epsilon(&mut fst, 3)?;
+        assert_eq!(fst, fst_ref);
+        assert_eq!(fst.input_symbols(), Some(&symt));
+        assert_eq!(fst.output_symbols(), Some(&symt));
+
+        Ok(())
+    }
+}
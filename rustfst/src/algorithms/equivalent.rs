@@ -0,0 +1,272 @@
+use std::collections::VecDeque;
+
+use anyhow::Result;
+
+use crate::algorithms::encode::{EncodeMapper, EncodeTable, EncodeType};
+use crate::algorithms::split_components::UnionFind;
+use crate::algorithms::tr_compares::ILabelCompare;
+use crate::algorithms::tr_mappers::QuantizeMapper;
+use crate::algorithms::{
+    connect, fst_convert_from_ref, push_weights_with_config, tr_map, tr_sort, PushWeightsConfig,
+    ReweightType,
+};
+use crate::fst_impls::VectorFst;
+use crate::fst_properties::FstProperties;
+use crate::fst_traits::{CoreFst, ExpandedFst, MutableFst};
+use crate::semirings::{WeaklyDivisibleSemiring, WeightQuantize};
+use crate::{StateId, Tr, Trs, EPS_LABEL};
+
+fn check_equivalent_input<W: WeaklyDivisibleSemiring>(
+    fst: &mut VectorFst<W>,
+    name: &str,
+) -> Result<FstProperties> {
+    let props = fst.compute_and_update_properties(
+        FstProperties::ACCEPTOR
+            | FstProperties::NO_EPSILONS
+            | FstProperties::I_DETERMINISTIC
+            | FstProperties::UNWEIGHTED,
+    )?;
+    if !props.contains(FstProperties::ACCEPTOR) {
+        bail!("Equivalent : The {} FST is not an acceptor", name);
+    }
+    if !props.contains(FstProperties::NO_EPSILONS) {
+        bail!(
+            "Equivalent : The {} FST has epsilon transitions, they can be removed with `rm_epsilon`",
+            name
+        );
+    }
+    if !props.contains(FstProperties::I_DETERMINISTIC) {
+        bail!(
+            "Equivalent : The {} FST is not deterministic, it can be determinized with `determinize`",
+            name
+        );
+    }
+    Ok(props)
+}
+
+/// Determines whether two epsilon-free deterministic weighted acceptors are equivalent, that is
+/// if they accept the same strings with the same weights, up to `delta`.
+///
+/// The weights of both FSTs are pushed towards the initial state and quantized with `delta`,
+/// then the labels and weights are encoded together so that the equivalence of the resulting
+/// unweighted automata is checked with the algorithm of Hopcroft and Karp.
+///
+/// An error is returned if one of the FSTs is not an acceptor, has epsilon transitions or is
+/// not deterministic : [`rm_epsilon`](rm_epsilon/fn.rm_epsilon.html) and
+/// [`determinize`](determinize/fn.determinize.html) can be applied beforehand.
+///
+/// # Example
+/// ```
+/// # use rustfst::fst_impls::VectorFst;
+/// # use rustfst::fst_traits::MutableFst;
+/// # use rustfst::semirings::{Semiring, TropicalWeight};
+/// # use rustfst::algorithms::equivalent;
+/// # use rustfst::{Tr, KDELTA};
+/// # use anyhow::Result;
+/// # fn main() -> Result<()> {
+/// let mut fst_1 = VectorFst::<TropicalWeight>::new();
+/// fst_1.add_states(2);
+/// fst_1.set_start(0)?;
+/// fst_1.set_final(1, 2.0)?;
+/// fst_1.add_tr(0, Tr::new(1, 1, 1.0, 1))?;
+///
+/// let mut fst_2 = VectorFst::<TropicalWeight>::new();
+/// fst_2.add_states(2);
+/// fst_2.set_start(0)?;
+/// fst_2.set_final(1, TropicalWeight::one())?;
+/// fst_2.add_tr(0, Tr::new(1, 1, 3.0, 1))?;
+///
+/// assert!(equivalent(&fst_1, &fst_2, KDELTA)?);
+/// # Ok(())
+/// # }
+/// ```
+pub fn equivalent<W, F1, F2>(fst_1: &F1, fst_2: &F2, delta: f32) -> Result<bool>
+where
+    W: WeaklyDivisibleSemiring + WeightQuantize,
+    F1: ExpandedFst<W>,
+    F2: ExpandedFst<W>,
+{
+    let mut efst_1: VectorFst<W> = fst_convert_from_ref(fst_1);
+    let mut efst_2: VectorFst<W> = fst_convert_from_ref(fst_2);
+    let props_1 = check_equivalent_input(&mut efst_1, "first")?;
+    let props_2 = check_equivalent_input(&mut efst_2, "second")?;
+
+    // Useless states would be wrongly considered as accepting different strings.
+    connect(&mut efst_1)?;
+    connect(&mut efst_2)?;
+    match (efst_1.start(), efst_2.start()) {
+        (Some(_), Some(_)) => {}
+        (None, None) => return Ok(true),
+        _ => return Ok(false),
+    };
+
+    if !(props_1 & props_2).contains(FstProperties::UNWEIGHTED) {
+        let quantize_mapper = QuantizeMapper::new(delta);
+        for efst in [&mut efst_1, &mut efst_2].iter_mut() {
+            // The total weight is pushed on a new initial transition, whether the start state
+            // is on a cycle or not.
+            let start = efst.start().unwrap();
+            let new_start = efst.add_state();
+            efst.add_tr(new_start, Tr::new(EPS_LABEL, EPS_LABEL, W::one(), start))?;
+            efst.set_start(new_start)?;
+            push_weights_with_config(
+                *efst,
+                ReweightType::ReweightToInitial,
+                PushWeightsConfig::default().with_delta(delta),
+            )?;
+            tr_map(*efst, &quantize_mapper)?;
+        }
+    }
+
+    // Both FSTs must share the same encoding.
+    let encode_table = EncodeTable::new(EncodeType::EncodeWeightsAndLabels);
+    let encode_mapper = EncodeMapper::new(&encode_table);
+    tr_map(&mut efst_1, &encode_mapper)?;
+    tr_map(&mut efst_2, &encode_mapper)?;
+    tr_sort(&mut efst_1, ILabelCompare {});
+    tr_sort(&mut efst_2, ILabelCompare {});
+
+    let start_1 = efst_1.start().unwrap();
+    let start_2 = efst_2.start().unwrap();
+    // The states of the second FST are numbered after the ones of the first FST.
+    let offset = efst_1.num_states() as StateId;
+    let mut union_find = UnionFind::new(efst_1.num_states() + efst_2.num_states());
+    union_find.union(start_1, start_2 + offset);
+    let mut queue = VecDeque::new();
+    queue.push_back((start_1, start_2));
+
+    while let Some((s1, s2)) = queue.pop_front() {
+        // After encoding, all the final weights are equal to One.
+        if efst_1.is_final(s1)? != efst_2.is_final(s2)? {
+            return Ok(false);
+        }
+        let trs_1 = efst_1.get_trs(s1)?;
+        let trs_2 = efst_2.get_trs(s2)?;
+        if trs_1.len() != trs_2.len() {
+            return Ok(false);
+        }
+        for (tr_1, tr_2) in trs_1.trs().iter().zip(trs_2.trs().iter()) {
+            if tr_1.ilabel != tr_2.ilabel {
+                return Ok(false);
+            }
+            let root_1 = union_find.find(tr_1.nextstate);
+            let root_2 = union_find.find(tr_2.nextstate + offset);
+            if root_1 != root_2 {
+                union_find.union(root_1, root_2);
+                queue.push_back((tr_1.nextstate, tr_2.nextstate));
+            }
+        }
+    }
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::algorithms::determinize::determinize;
+    use crate::semirings::{Semiring, TropicalWeight};
+    use crate::KDELTA;
+
+    type F = VectorFst<TropicalWeight>;
+
+    fn cyclic_fst() -> Result<F> {
+        // (1 2)* 3 with weights 1 and 2 on the loop and 0.5 on the final state.
+        let mut fst = F::new();
+        fst.add_states(3);
+        fst.set_start(0)?;
+        fst.set_final(2, 0.5)?;
+        fst.add_tr(0, Tr::new(1, 1, 1.0, 1))?;
+        fst.add_tr(1, Tr::new(2, 2, 2.0, 0))?;
+        fst.add_tr(0, Tr::new(3, 3, TropicalWeight::one(), 2))?;
+        Ok(fst)
+    }
+
+    #[test]
+    fn test_equivalent_unrolled_cycle() -> Result<()> {
+        let fst_1 = cyclic_fst()?;
+
+        // Same language with the loop unrolled once and the weights moved around.
+        let mut fst_2 = F::new();
+        fst_2.add_states(5);
+        fst_2.set_start(0)?;
+        fst_2.set_final(2, TropicalWeight::one())?;
+        fst_2.add_tr(0, Tr::new(1, 1, 3.0, 1))?;
+        fst_2.add_tr(1, Tr::new(2, 2, TropicalWeight::one(), 3))?;
+        fst_2.add_tr(3, Tr::new(1, 1, 1.0, 4))?;
+        fst_2.add_tr(4, Tr::new(2, 2, 2.0, 0))?;
+        fst_2.add_tr(3, Tr::new(3, 3, 0.5, 2))?;
+        fst_2.add_tr(0, Tr::new(3, 3, 0.5, 2))?;
+        // Useless state.
+        fst_2.add_states(1);
+        fst_2.add_tr(0, Tr::new(4, 4, 1.0, 5))?;
+
+        assert!(equivalent(&fst_1, &fst_2, KDELTA)?);
+        assert!(equivalent(&fst_2, &fst_1, KDELTA)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_equivalent_cyclic_start_state() -> Result<()> {
+        let mut fst_1 = F::new();
+        fst_1.add_states(1);
+        fst_1.set_start(0)?;
+        fst_1.set_final(0, 0.5)?;
+        fst_1.add_tr(0, Tr::new(1, 1, 1.0, 0))?;
+
+        let mut fst_2 = F::new();
+        fst_2.add_states(2);
+        fst_2.set_start(0)?;
+        fst_2.set_final(0, 0.5)?;
+        fst_2.set_final(1, 0.5)?;
+        fst_2.add_tr(0, Tr::new(1, 1, 1.0, 1))?;
+        fst_2.add_tr(1, Tr::new(1, 1, 1.0, 1))?;
+
+        assert!(equivalent(&fst_1, &fst_2, KDELTA)?);
+        fst_2.set_final(0, 1.5)?;
+        assert!(!equivalent(&fst_1, &fst_2, KDELTA)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_equivalent_different_weights_or_strings() -> Result<()> {
+        let fst_1 = cyclic_fst()?;
+
+        let mut fst_2 = fst_1.clone();
+        fst_2.set_final(2, 0.6)?;
+        assert!(!equivalent(&fst_1, &fst_2, KDELTA)?);
+        assert!(equivalent(&fst_1, &fst_2, 1.0)?);
+
+        let mut fst_3 = fst_1.clone();
+        fst_3.add_tr(2, Tr::new(4, 4, 1.0, 2))?;
+        assert!(!equivalent(&fst_1, &fst_3, KDELTA)?);
+
+        let empty = F::new();
+        assert!(!equivalent(&fst_1, &empty, KDELTA)?);
+        assert!(equivalent(&empty, &F::new(), KDELTA)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_equivalent_errors() -> Result<()> {
+        let fst_1 = cyclic_fst()?;
+
+        let mut with_epsilon = fst_1.clone();
+        with_epsilon.add_tr(2, Tr::new(EPS_LABEL, EPS_LABEL, 1.0, 0))?;
+        let err = equivalent(&fst_1, &with_epsilon, KDELTA).unwrap_err();
+        assert!(err.to_string().contains("rm_epsilon"));
+
+        let mut non_det = fst_1.clone();
+        non_det.add_tr(0, Tr::new(1, 1, 1.0, 2))?;
+        let err = equivalent(&non_det, &fst_1, KDELTA).unwrap_err();
+        assert!(err.to_string().contains("determinize"));
+
+        let det: F = determinize(&non_det)?;
+        assert!(!equivalent(&det, &fst_1, KDELTA)?);
+
+        let mut transducer = fst_1.clone();
+        transducer.add_tr(2, Tr::new(1, 2, 1.0, 0))?;
+        assert!(equivalent(&transducer, &fst_1, KDELTA).is_err());
+        Ok(())
+    }
+}
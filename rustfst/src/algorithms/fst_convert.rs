@@ -3,6 +3,10 @@ use crate::semirings::Semiring;
 use crate::Trs;
 
 /// Generic method to convert an Fst into any other types implementing the MutableFst trait.
+///
+/// Every tr is cloned : the memory used by the input FST is doubled while both exist. Algorithms
+/// only reading an FST don't need it to be converted first, they accept any
+/// [`ExpandedFst`](../fst_traits/trait.ExpandedFst.html) such as a `ConstFst`.
 pub fn fst_convert_from_ref<W, F1, F2>(ifst: &F1) -> F2
 where
     W: Semiring,
@@ -38,6 +42,8 @@ where
 }
 
 /// Generic method to convert an Fst into any other types implementing the MutableFst trait.
+///
+/// The input FST is consumed but its trs are still moved one by one into the output FST.
 pub fn fst_convert<W, F1, F2>(ifst: F1) -> F2
 where
     W: Semiring,
@@ -0,0 +1,115 @@
+use std::marker::PhantomData;
+
+use crate::fst_traits::ExpandedFst;
+use crate::semirings::Semiring;
+use crate::{StateId, Tr, Trs};
+
+/// Index of the incoming transitions of every state of an FST.
+///
+/// The index is built in a single pass over the transitions of the FST and
+/// borrows it : it can't outlive the FST nor be used after it has been modified.
+///
+/// # Example
+///
+/// ```
+/// # use anyhow::Result;
+/// # use rustfst::fst_impls::VectorFst;
+/// # use rustfst::fst_traits::MutableFst;
+/// # use rustfst::semirings::{Semiring, TropicalWeight};
+/// # use rustfst::algorithms::InTrIndex;
+/// # use rustfst::Tr;
+/// # fn main() -> Result<()> {
+/// let mut fst = VectorFst::<TropicalWeight>::new();
+/// fst.add_states(3);
+/// fst.add_tr(0, Tr::new(1, 1, 1.0, 2))?;
+/// fst.add_tr(1, Tr::new(2, 2, 2.0, 2))?;
+///
+/// let index = InTrIndex::new(&fst);
+/// let sources: Vec<_> = index.in_trs(2).map(|(s, _)| s).collect();
+/// assert_eq!(sources, vec![0, 1]);
+/// # Ok(())
+/// # }
+/// ```
+pub struct InTrIndex<'a, W: Semiring, F: ExpandedFst<W>> {
+    trs: Vec<F::TRS>,
+    in_trs: Vec<Vec<(StateId, usize)>>,
+    fst: PhantomData<&'a F>,
+    w: PhantomData<W>,
+}
+
+impl<'a, W: Semiring, F: ExpandedFst<W>> InTrIndex<'a, W, F> {
+    /// Builds the index of the incoming transitions of `fst`.
+    pub fn new(fst: &'a F) -> Self {
+        let num_states = fst.num_states();
+        let mut trs = Vec::with_capacity(num_states);
+        let mut in_trs = vec![vec![]; num_states];
+        for s in fst.states_range() {
+            let trs_s = unsafe { fst.get_trs_unchecked(s) };
+            for (idx, tr) in trs_s.trs().iter().enumerate() {
+                in_trs[tr.nextstate as usize].push((s, idx));
+            }
+            trs.push(trs_s);
+        }
+        Self {
+            trs,
+            in_trs,
+            fst: PhantomData,
+            w: PhantomData,
+        }
+    }
+
+    /// Iterates over the transitions pointing at `state`, along with their source state.
+    /// The transitions are ordered by source state, then by position in the source state.
+    pub fn in_trs(&self, state: StateId) -> impl Iterator<Item = (StateId, &Tr<W>)> {
+        self.in_trs[state as usize]
+            .iter()
+            .map(move |(s, idx)| (*s, &self.trs[*s as usize].trs()[*idx]))
+    }
+
+    /// Number of transitions pointing at `state`.
+    pub fn num_in_trs(&self, state: StateId) -> usize {
+        self.in_trs[state as usize].len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use super::*;
+    use crate::fst_impls::VectorFst;
+    use crate::fst_traits::{CoreFst, MutableFst};
+    use crate::semirings::TropicalWeight;
+
+    #[test]
+    fn test_in_tr_index() -> Result<()> {
+        let mut fst = VectorFst::<TropicalWeight>::new();
+        fst.add_states(4);
+        fst.set_start(0)?;
+        fst.set_final(3, TropicalWeight::one())?;
+        fst.add_tr(0, Tr::new(1, 1, 1.0, 1))?;
+        fst.add_tr(0, Tr::new(2, 2, 2.0, 2))?;
+        fst.add_tr(1, Tr::new(3, 3, 3.0, 3))?;
+        fst.add_tr(2, Tr::new(4, 4, 4.0, 3))?;
+        fst.add_tr(2, Tr::new(5, 5, 5.0, 2))?;
+        fst.add_tr(3, Tr::new(6, 6, 6.0, 0))?;
+        fst.add_tr(3, Tr::new(7, 7, 7.0, 3))?;
+
+        let index = InTrIndex::new(&fst);
+        for state in fst.states_range() {
+            let mut expected = vec![];
+            for s in fst.states_range() {
+                for tr in fst.get_trs(s)?.trs() {
+                    if tr.nextstate == state {
+                        expected.push((s, tr.clone()));
+                    }
+                }
+            }
+            let in_trs: Vec<_> = index.in_trs(state).map(|(s, tr)| (s, tr.clone())).collect();
+            assert_eq!(in_trs, expected);
+            assert_eq!(index.num_in_trs(state), expected.len());
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,318 @@
+use std::borrow::Borrow;
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use anyhow::Result;
+
+use crate::algorithms::compose::compose_filters::SequenceComposeFilterBuilder;
+use crate::algorithms::compose::matchers::GenericMatcher;
+use crate::algorithms::compose::{acceptor_intersect, ComposeFst};
+use crate::fst_impls::VectorFst;
+use crate::fst_properties::{compute_fst_properties, FstProperties};
+use crate::fst_traits::{
+    AllocableFst, CoreFst, ExpandedFst, Fst, FstIterator, MutableFst, StateIterator,
+};
+use crate::semirings::Semiring;
+use crate::{StateId, SymbolTable, TrsVec};
+
+fn check_acceptors<W, F1, F2>(fst1: &F1, fst2: &F2) -> Result<()>
+where
+    W: Semiring,
+    F1: ExpandedFst<W>,
+    F2: ExpandedFst<W>,
+{
+    let mask = FstProperties::ACCEPTOR | FstProperties::NOT_ACCEPTOR;
+    let props1 = compute_fst_properties(fst1, mask, &mut FstProperties::empty(), true)?;
+    let props2 = compute_fst_properties(fst2, mask, &mut FstProperties::empty(), true)?;
+    if !props1.contains(FstProperties::ACCEPTOR) || !props2.contains(FstProperties::ACCEPTOR) {
+        bail!("Intersect : Both FSTs must be acceptors");
+    }
+    Ok(())
+}
+
+/// Computes the intersection (Hadamard product) of two acceptors. If `A` accepts string `x`
+/// with weight `a` and `B` accepts `x` with weight `b`, then their intersection accepts `x`
+/// with weight `a ⊗ b`.
+///
+/// The transitions of the two FSTs are matched directly on their label with
+/// [`acceptor_intersect`](compose/fn.acceptor_intersect.html), and the result is connected.
+/// The transitions of at least one of the FSTs must be sorted by label. An error is returned
+/// if one of the FSTs is not an acceptor.
+///
+/// # Example
+/// ```
+/// # #[macro_use] extern crate rustfst;
+/// # use rustfst::utils::acceptor;
+/// # use rustfst::fst_impls::VectorFst;
+/// # use rustfst::fst_traits::Fst;
+/// # use rustfst::semirings::{Semiring, TropicalWeight};
+/// # use rustfst::algorithms::intersect;
+/// # use rustfst::FstPath;
+/// # use anyhow::Result;
+/// # fn main() -> Result<()> {
+/// let fst1: VectorFst<TropicalWeight> = fst![1, 2, 3; 1.0];
+/// let fst2: VectorFst<TropicalWeight> = fst![1, 2, 3; 2.0];
+/// let fst3: VectorFst<TropicalWeight> = fst![1, 2; 2.0];
+///
+/// let res = intersect(&fst1, &fst2)?;
+/// assert_eq!(res.paths_iter().collect::<Vec<_>>(), vec![fst_path![1, 2, 3; 3.0]]);
+///
+/// let res = intersect(&fst1, &fst3)?;
+/// assert_eq!(res.paths_iter().count(), 0);
+/// # Ok(())
+/// # }
+/// ```
+pub fn intersect<W, F1, F2>(fst1: &F1, fst2: &F2) -> Result<VectorFst<W>>
+where
+    W: Semiring,
+    F1: ExpandedFst<W>,
+    F2: ExpandedFst<W>,
+{
+    acceptor_intersect(fst1, fst2)
+}
+
+type IntersectMatcher<W, F, B> = GenericMatcher<W, F, B>;
+type IntersectFilterBuilder<W, F1, F2, B1, B2> = SequenceComposeFilterBuilder<
+    W,
+    F1,
+    F2,
+    B1,
+    B2,
+    IntersectMatcher<W, F1, B1>,
+    IntersectMatcher<W, F2, B2>,
+>;
+type InnerComposeFst<W, F1, F2, B1, B2> = ComposeFst<
+    W,
+    F1,
+    F2,
+    B1,
+    B2,
+    IntersectMatcher<W, F1, B1>,
+    IntersectMatcher<W, F2, B2>,
+    IntersectFilterBuilder<W, F1, F2, B1, B2>,
+>;
+
+/// Computes the intersection of two acceptors. This version is a delayed FST : the states
+/// are computed when they are visited. See [`intersect`](fn.intersect.html) for the static
+/// version, which also connects the result.
+#[derive(Debug)]
+pub struct IntersectFst<W, F1, F2, B1, B2>(InnerComposeFst<W, F1, F2, B1, B2>)
+where
+    W: Semiring,
+    F1: Fst<W>,
+    F2: Fst<W>,
+    B1: Borrow<F1> + Debug + Clone,
+    B2: Borrow<F2> + Debug + Clone;
+
+impl<W, F1, F2, B1, B2> IntersectFst<W, F1, F2, B1, B2>
+where
+    W: Semiring,
+    F1: ExpandedFst<W>,
+    F2: ExpandedFst<W>,
+    B1: Borrow<F1> + Debug + Clone,
+    B2: Borrow<F2> + Debug + Clone,
+{
+    /// Creates the delayed intersection. Fails if one of the FSTs is not an acceptor or if
+    /// none of them is sorted.
+    pub fn new(fst1: B1, fst2: B2) -> Result<Self> {
+        check_acceptors(fst1.borrow(), fst2.borrow())?;
+        Ok(IntersectFst(ComposeFst::new(fst1, fst2)?))
+    }
+
+    /// Turns the Lazy FST into a static one.
+    pub fn compute<F: MutableFst<W> + AllocableFst<W>>(&self) -> Result<F> {
+        self.0.compute()
+    }
+}
+
+impl<W, F1, F2, B1, B2> CoreFst<W> for IntersectFst<W, F1, F2, B1, B2>
+where
+    W: Semiring,
+    F1: Fst<W>,
+    F2: Fst<W>,
+    B1: Borrow<F1> + Debug + Clone,
+    B2: Borrow<F2> + Debug + Clone,
+{
+    type TRS = TrsVec<W>;
+
+    fn start(&self) -> Option<StateId> {
+        self.0.start()
+    }
+
+    fn final_weight(&self, state_id: StateId) -> Result<Option<W>> {
+        self.0.final_weight(state_id)
+    }
+
+    unsafe fn final_weight_unchecked(&self, state_id: StateId) -> Option<W> {
+        self.0.final_weight_unchecked(state_id)
+    }
+
+    fn num_trs(&self, s: StateId) -> Result<usize> {
+        self.0.num_trs(s)
+    }
+
+    unsafe fn num_trs_unchecked(&self, s: StateId) -> usize {
+        self.0.num_trs_unchecked(s)
+    }
+
+    fn get_trs(&self, state_id: StateId) -> Result<Self::TRS> {
+        self.0.get_trs(state_id)
+    }
+
+    unsafe fn get_trs_unchecked(&self, state_id: StateId) -> Self::TRS {
+        self.0.get_trs_unchecked(state_id)
+    }
+
+    fn properties(&self) -> FstProperties {
+        self.0.properties()
+    }
+
+    fn num_input_epsilons(&self, state: StateId) -> Result<usize> {
+        self.0.num_input_epsilons(state)
+    }
+
+    fn num_output_epsilons(&self, state: StateId) -> Result<usize> {
+        self.0.num_output_epsilons(state)
+    }
+}
+
+impl<'a, W, F1, F2, B1, B2> StateIterator<'a> for IntersectFst<W, F1, F2, B1, B2>
+where
+    W: Semiring,
+    F1: Fst<W> + 'a,
+    F2: Fst<W> + 'a,
+    B1: Borrow<F1> + Debug + Clone + 'a,
+    B2: Borrow<F2> + Debug + Clone + 'a,
+{
+    type Iter = <InnerComposeFst<W, F1, F2, B1, B2> as StateIterator<'a>>::Iter;
+
+    fn states_iter(&'a self) -> Self::Iter {
+        self.0.states_iter()
+    }
+}
+
+impl<'a, W, F1, F2, B1, B2> FstIterator<'a, W> for IntersectFst<W, F1, F2, B1, B2>
+where
+    W: Semiring,
+    F1: Fst<W> + 'a,
+    F2: Fst<W> + 'a,
+    B1: Borrow<F1> + Debug + Clone + 'a,
+    B2: Borrow<F2> + Debug + Clone + 'a,
+{
+    type FstIter = <InnerComposeFst<W, F1, F2, B1, B2> as FstIterator<'a, W>>::FstIter;
+
+    fn fst_iter(&'a self) -> Self::FstIter {
+        self.0.fst_iter()
+    }
+}
+
+impl<W, F1, F2, B1, B2> Fst<W> for IntersectFst<W, F1, F2, B1, B2>
+where
+    W: Semiring,
+    F1: Fst<W> + 'static,
+    F2: Fst<W> + 'static,
+    B1: Borrow<F1> + Debug + Clone + 'static,
+    B2: Borrow<F2> + Debug + Clone + 'static,
+{
+    fn input_symbols(&self) -> Option<&Arc<SymbolTable>> {
+        self.0.input_symbols()
+    }
+
+    fn output_symbols(&self) -> Option<&Arc<SymbolTable>> {
+        self.0.output_symbols()
+    }
+
+    fn set_input_symbols(&mut self, symt: Arc<SymbolTable>) {
+        self.0.set_input_symbols(symt)
+    }
+
+    fn set_output_symbols(&mut self, symt: Arc<SymbolTable>) {
+        self.0.set_output_symbols(symt)
+    }
+
+    fn take_input_symbols(&mut self) -> Option<Arc<SymbolTable>> {
+        self.0.take_input_symbols()
+    }
+
+    fn take_output_symbols(&mut self) -> Option<Arc<SymbolTable>> {
+        self.0.take_output_symbols()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::algorithms::tr_compares::ILabelCompare;
+    use crate::algorithms::union::union;
+    use crate::algorithms::{connect, tr_sort};
+    use crate::semirings::TropicalWeight;
+    use crate::utils::acceptor;
+    use crate::{Label, Tr, EPS_LABEL};
+
+    type F = VectorFst<TropicalWeight>;
+
+    fn paths<FST: Fst<TropicalWeight>>(fst: &FST) -> Vec<(Vec<Label>, TropicalWeight)> {
+        let mut paths: Vec<_> = fst.paths_iter().map(|p| (p.ilabels, p.weight)).collect();
+        paths.sort_by(|a, b| a.0.cmp(&b.0));
+        paths
+    }
+
+    fn fsts() -> Result<(F, F)> {
+        let mut fst1: F = acceptor(&[1, 2], TropicalWeight::new(1.0));
+        union(
+            &mut fst1,
+            &acceptor::<_, F>(&[1, 3], TropicalWeight::new(2.0)),
+        )?;
+        union(&mut fst1, &acceptor::<_, F>(&[4], TropicalWeight::new(3.0)))?;
+
+        // Accepts 1 2 and 1 3 through epsilon transitions.
+        let mut fst2 = F::new();
+        fst2.add_states(4);
+        fst2.set_start(0)?;
+        fst2.set_final(3, 0.5)?;
+        fst2.add_tr(0, Tr::new(EPS_LABEL, EPS_LABEL, 0.5, 1))?;
+        fst2.add_tr(1, Tr::new(1, 1, TropicalWeight::new(1.0), 2))?;
+        fst2.add_tr(
+            2,
+            Tr::new(EPS_LABEL, EPS_LABEL, TropicalWeight::new(1.0), 1),
+        )?;
+        fst2.add_tr(2, Tr::new(2, 2, TropicalWeight::new(1.0), 3))?;
+        fst2.add_tr(1, Tr::new(3, 3, TropicalWeight::new(2.0), 3))?;
+        tr_sort(&mut fst1, ILabelCompare {});
+        Ok((fst1, fst2))
+    }
+
+    #[test]
+    fn test_intersect() -> Result<()> {
+        let (fst1, fst2) = fsts()?;
+        let expected = vec![
+            (vec![1, 2], TropicalWeight::new(4.0)),
+            (vec![1, 3], TropicalWeight::new(7.0)),
+        ];
+        assert_eq!(paths(&intersect(&fst1, &fst2)?), expected);
+        assert_eq!(paths(&intersect(&fst2, &fst1)?), expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_intersect_fst() -> Result<()> {
+        let (fst1, fst2) = fsts()?;
+        let lazy =
+            IntersectFst::<_, F, F, _, _>::new(Arc::new(fst1.clone()), Arc::new(fst2.clone()))?;
+        let mut computed: F = lazy.compute()?;
+        connect(&mut computed)?;
+        assert_eq!(computed, intersect(&fst1, &fst2)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_intersect_not_acceptors() -> Result<()> {
+        let (fst1, mut fst2) = fsts()?;
+        fst2.add_tr(3, Tr::new(1, 2, TropicalWeight::new(1.0), 3))?;
+        assert!(intersect(&fst1, &fst2).is_err());
+        assert!(intersect(&fst2, &fst1).is_err());
+        assert!(IntersectFst::<_, F, F, _, _>::new(&fst1, &fst2).is_err());
+        Ok(())
+    }
+}
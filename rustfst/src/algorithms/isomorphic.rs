@@ -253,4 +253,38 @@ mod test {
 
         Ok(())
     }
+
+    #[test]
+    fn test_isomorphic_final_weights_and_delta() -> Result<()> {
+        let fst_1: VectorFst<LogWeight> = SerializableFst::from_text_string(
+            "0\t1\t12\t25\t0.5\n\
+             0\t2\t13\t26\t0.5\n\
+             1\t1.0\n\
+             2\t2.0\n",
+        )?;
+
+        // Same FST with its states renumbered and its trs reordered.
+        let fst_2: VectorFst<LogWeight> = SerializableFst::from_text_string(
+            "2\t0\t13\t26\t0.5\n\
+             2\t1\t12\t25\t0.5\n\
+             0\t2.0\n\
+             1\t1.0\n",
+        )?;
+        assert!(isomorphic(&fst_1, &fst_2)?);
+
+        let mut fst_3 = fst_1.clone();
+        fst_3.set_final(2, 2.001)?;
+        assert!(!isomorphic(&fst_1, &fst_3)?);
+        assert!(isomorphic_with_config(
+            &fst_1,
+            &fst_3,
+            IsomorphicConfig::new(0.01)
+        )?);
+
+        let mut fst_4 = fst_1.clone();
+        fst_4.delete_final_weight(2)?;
+        assert!(!isomorphic(&fst_1, &fst_4)?);
+
+        Ok(())
+    }
 }
@@ -0,0 +1,341 @@
+use std::collections::HashMap;
+use std::mem::size_of;
+use std::sync::{Arc, Mutex};
+
+use crate::algorithms::lazy::cache::cache_internal_types::{
+    CacheTrs, CachedData, FinalWeight, StartState,
+};
+use crate::algorithms::lazy::{CacheStatus, FstCache};
+use crate::semirings::Semiring;
+use crate::{StateId, Tr, Trs, TrsVec, EPS_LABEL};
+
+/// Fraction of `gc_limit` the cache is shrunk to when a garbage collection is triggered.
+const GC_CACHE_FRACTION: f32 = 0.666;
+
+/// Options controlling the memory used by a [`GcCache`].
+///
+/// Mirrors the `CacheOptions` of OpenFST.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CacheOptions {
+    /// Enables the garbage collection of the expanded states.
+    pub gc: bool,
+    /// Number of bytes taken by the cached transitions above which a garbage
+    /// collection is triggered. Ignored when `gc` is false.
+    pub gc_limit: usize,
+}
+
+impl CacheOptions {
+    pub fn new(gc: bool, gc_limit: usize) -> Self {
+        Self { gc, gc_limit }
+    }
+}
+
+impl Default for CacheOptions {
+    fn default() -> Self {
+        Self {
+            gc: false,
+            gc_limit: 1 << 20,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct GcCacheTrs<W: Semiring> {
+    cache_trs: CacheTrs<W>,
+    last_access: u64,
+}
+
+#[derive(Debug, Clone)]
+struct GcTrsData<W: Semiring> {
+    states: CachedData<HashMap<StateId, GcCacheTrs<W>>>,
+    // Approximate number of bytes taken by the cached transitions.
+    size: usize,
+    // Logical clock used to find the least recently used states.
+    clock: u64,
+}
+
+impl<W: Semiring> Default for GcTrsData<W> {
+    fn default() -> Self {
+        Self {
+            states: CachedData::default(),
+            size: 0,
+            clock: 0,
+        }
+    }
+}
+
+/// Cache whose memory can be bounded by evicting the transitions of the least recently
+/// used states.
+///
+/// When `gc` is enabled and the cached transitions take more than `gc_limit` bytes, the
+/// transitions of the least recently accessed states are dropped until the cache takes
+/// less than two thirds of the limit. An evicted state is recomputed by the lazy FST the
+/// next time its transitions are requested. The state ids are left unchanged as the
+/// state table of the lazy FST is never garbage collected.
+///
+/// The transitions of a state are never evicted while they are still referenced outside of
+/// the cache, for instance by a `TrsVec` returned by `get_trs` and still being iterated.
+/// The same goes for the state that has just been inserted. Hence the limit may be exceeded
+/// if a lot of states are in use at the same time. The start state and the final weights
+/// are small and always kept.
+#[derive(Debug)]
+pub struct GcCache<W: Semiring> {
+    opts: CacheOptions,
+    start: Mutex<CachedData<CacheStatus<StartState>>>,
+    trs: Mutex<GcTrsData<W>>,
+    final_weights: Mutex<CachedData<HashMap<StateId, FinalWeight<W>>>>,
+}
+
+impl<W: Semiring> GcCache<W> {
+    pub fn new(opts: CacheOptions) -> Self {
+        Self {
+            opts,
+            start: Mutex::new(CachedData::default()),
+            trs: Mutex::new(GcTrsData::default()),
+            final_weights: Mutex::new(CachedData::default()),
+        }
+    }
+
+    pub fn options(&self) -> CacheOptions {
+        self.opts
+    }
+
+    /// Approximate number of bytes taken by the cached transitions.
+    pub fn size_trs(&self) -> usize {
+        self.trs.lock().unwrap().size
+    }
+
+    pub fn clear(&self) {
+        let mut data_start = self.start.lock().unwrap();
+        data_start.clear();
+
+        let mut data_trs = self.trs.lock().unwrap();
+        *data_trs = GcTrsData::default();
+
+        let mut data_final_weights = self.final_weights.lock().unwrap();
+        data_final_weights.clear();
+    }
+
+    fn size_of_trs(trs: &TrsVec<W>) -> usize {
+        size_of::<GcCacheTrs<W>>() + trs.len() * size_of::<Tr<W>>()
+    }
+
+    fn gc(&self, data: &mut GcTrsData<W>, current: StateId) {
+        let target = (self.opts.gc_limit as f32 * GC_CACHE_FRACTION) as usize;
+        let mut candidates: Vec<_> = data
+            .states
+            .data
+            .iter()
+            .filter(|(s, e)| **s != current && Arc::strong_count(&e.cache_trs.trs.0) == 1)
+            .map(|(s, e)| (e.last_access, *s))
+            .collect();
+        candidates.sort_unstable();
+        for (_, s) in candidates {
+            if data.size <= target {
+                break;
+            }
+            if let Some(e) = data.states.data.remove(&s) {
+                data.size -= Self::size_of_trs(&e.cache_trs.trs);
+            }
+        }
+    }
+}
+
+impl<W: Semiring> Clone for GcCache<W> {
+    fn clone(&self) -> Self {
+        Self {
+            opts: self.opts,
+            start: Mutex::new(self.start.lock().unwrap().clone()),
+            trs: Mutex::new(self.trs.lock().unwrap().clone()),
+            final_weights: Mutex::new(self.final_weights.lock().unwrap().clone()),
+        }
+    }
+}
+
+impl<W: Semiring> Default for GcCache<W> {
+    fn default() -> Self {
+        Self::new(CacheOptions::default())
+    }
+}
+
+impl<W: Semiring> FstCache<W> for GcCache<W> {
+    fn get_start(&self) -> CacheStatus<StartState> {
+        let res = self.start.lock().unwrap();
+        res.data
+    }
+
+    fn insert_start(&self, id: StartState) {
+        let mut data = self.start.lock().unwrap();
+        if let Some(s) = id {
+            data.num_known_states = std::cmp::max(data.num_known_states, s as usize + 1);
+        }
+        data.data = CacheStatus::Computed(id);
+    }
+
+    fn get_trs(&self, id: StateId) -> CacheStatus<TrsVec<W>> {
+        let mut data = self.trs.lock().unwrap();
+        data.clock += 1;
+        let clock = data.clock;
+        match data.states.data.get_mut(&id) {
+            Some(e) => {
+                e.last_access = clock;
+                CacheStatus::Computed(e.cache_trs.trs.shallow_clone())
+            }
+            None => CacheStatus::NotComputed,
+        }
+    }
+
+    fn insert_trs(&self, id: StateId, trs: TrsVec<W>) {
+        let mut data = self.trs.lock().unwrap();
+        let mut niepsilons = 0;
+        let mut noepsilons = 0;
+        for tr in trs.trs() {
+            data.states.num_known_states =
+                std::cmp::max(data.states.num_known_states, tr.nextstate as usize + 1);
+            if tr.ilabel == EPS_LABEL {
+                niepsilons += 1;
+            }
+            if tr.olabel == EPS_LABEL {
+                noepsilons += 1;
+            }
+        }
+        data.clock += 1;
+        let clock = data.clock;
+        data.size += Self::size_of_trs(&trs);
+        let previous = data.states.data.insert(
+            id,
+            GcCacheTrs {
+                cache_trs: CacheTrs {
+                    trs,
+                    niepsilons,
+                    noepsilons,
+                },
+                last_access: clock,
+            },
+        );
+        if let Some(e) = previous {
+            data.size -= Self::size_of_trs(&e.cache_trs.trs);
+        }
+        if self.opts.gc && data.size > self.opts.gc_limit {
+            self.gc(&mut data, id);
+        }
+    }
+
+    fn compute_num_known_trs(&self) -> usize {
+        let data = self.trs.lock().unwrap();
+        data.states
+            .data
+            .values()
+            .map(|it| it.cache_trs.trs.len())
+            .sum()
+    }
+
+    fn get_final_weight(&self, id: StateId) -> CacheStatus<FinalWeight<W>> {
+        match self.final_weights.lock().unwrap().get(id) {
+            Some(e) => CacheStatus::Computed(e.clone()),
+            None => CacheStatus::NotComputed,
+        }
+    }
+
+    fn insert_final_weight(&self, id: StateId, weight: FinalWeight<W>) {
+        let mut cached_data = self.final_weights.lock().unwrap();
+        cached_data.num_known_states = std::cmp::max(cached_data.num_known_states, id as usize + 1);
+        cached_data.data.insert(id, weight);
+    }
+
+    fn num_known_states(&self) -> usize {
+        let mut n = 0;
+        n = std::cmp::max(n, self.start.lock().unwrap().num_known_states);
+        n = std::cmp::max(n, self.trs.lock().unwrap().states.num_known_states);
+        n = std::cmp::max(n, self.final_weights.lock().unwrap().num_known_states);
+        n
+    }
+
+    fn num_trs(&self, id: StateId) -> Option<usize> {
+        let data = self.trs.lock().unwrap();
+        data.states.data.get(&id).map(|v| v.cache_trs.trs.len())
+    }
+
+    fn num_input_epsilons(&self, id: StateId) -> Option<usize> {
+        let data = self.trs.lock().unwrap();
+        data.states.data.get(&id).map(|v| v.cache_trs.niepsilons)
+    }
+
+    fn num_output_epsilons(&self, id: StateId) -> Option<usize> {
+        let data = self.trs.lock().unwrap();
+        data.states.data.get(&id).map(|v| v.cache_trs.noepsilons)
+    }
+
+    fn len_trs(&self) -> usize {
+        let data = self.trs.lock().unwrap();
+        data.states.data.len()
+    }
+
+    fn len_final_weights(&self) -> usize {
+        let cached_data = self.final_weights.lock().unwrap();
+        cached_data.data.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::semirings::TropicalWeight;
+
+    fn trs(n: usize) -> TrsVec<TropicalWeight> {
+        let mut trs = TrsVec::default();
+        for i in 0..n {
+            trs.push(Tr::new(1, 1, TropicalWeight::one(), i as StateId));
+        }
+        trs
+    }
+
+    #[test]
+    fn test_gc_cache_no_gc() {
+        let cache = GcCache::new(CacheOptions::new(false, 0));
+        for s in 0..10 {
+            cache.insert_trs(s, trs(5));
+        }
+        assert_eq!(cache.len_trs(), 10);
+        assert_eq!(cache.compute_num_known_trs(), 50);
+    }
+
+    #[test]
+    fn test_gc_cache_evicts_least_recently_used() {
+        let one_state = GcCache::<TropicalWeight>::size_of_trs(&trs(5));
+        let cache = GcCache::new(CacheOptions::new(true, 4 * one_state));
+        for s in 0..4 {
+            cache.insert_trs(s, trs(5));
+        }
+        assert_eq!(cache.len_trs(), 4);
+
+        // Makes state 0 the most recently used one.
+        assert!(cache.get_trs(0).is_computed());
+        cache.insert_trs(4, trs(5));
+
+        assert!(cache.size_trs() <= 4 * one_state);
+        assert!(cache.get_trs(0).is_computed());
+        assert!(cache.get_trs(1).is_not_computed());
+        assert!(cache.get_trs(2).is_not_computed());
+        assert!(cache.get_trs(4).is_computed());
+        assert_eq!(cache.num_known_states(), 5);
+    }
+
+    #[test]
+    fn test_gc_cache_keeps_referenced_states() {
+        let one_state = GcCache::<TropicalWeight>::size_of_trs(&trs(5));
+        let cache = GcCache::new(CacheOptions::new(true, one_state));
+        cache.insert_trs(0, trs(5));
+        let in_use = cache.get_trs(0).into_option().unwrap();
+        cache.insert_trs(1, trs(5));
+        cache.insert_trs(2, trs(5));
+
+        assert!(cache.get_trs(0).is_computed());
+        assert!(cache.get_trs(1).is_not_computed());
+        assert!(cache.get_trs(2).is_computed());
+        drop(in_use);
+
+        cache.insert_trs(3, trs(5));
+        assert!(cache.get_trs(0).is_not_computed());
+    }
+}
@@ -13,6 +13,7 @@ use unsafe_unwrap::UnsafeUnwrap;
 use crate::algorithms::lazy::cache::CacheStatus;
 use crate::algorithms::lazy::fst_op::{AccessibleOpState, FstOp, SerializableOpState};
 use crate::algorithms::lazy::{FstCache, SerializableCache};
+use crate::algorithms::progress::{Progress, ProgressCallback};
 use crate::fst_properties::FstProperties;
 use crate::fst_traits::{
     AllocableFst, CoreFst, Fst, FstIterData, FstIterator, MutableFst, StateIterator,
@@ -61,13 +62,15 @@ impl<W: Semiring, Op: FstOp<W>, Cache: FstCache<W>> CoreFst<W> for LazyFst<W, Op
     }
 
     fn num_trs(&self, s: StateId) -> Result<usize> {
-        self.cache
-            .num_trs(s)
-            .ok_or_else(|| format_err!("State {:?} doesn't exist", s))
+        if let Some(n) = self.cache.num_trs(s) {
+            return Ok(n);
+        }
+        // The trs of the state haven't been computed yet or have been evicted from the cache.
+        Ok(self.get_trs(s)?.len())
     }
 
     unsafe fn num_trs_unchecked(&self, s: StateId) -> usize {
-        self.cache.num_trs(s).unsafe_unwrap()
+        self.num_trs(s).unsafe_unwrap()
     }
 
     fn get_trs(&self, state_id: StateId) -> Result<Self::TRS> {
@@ -90,12 +93,22 @@ impl<W: Semiring, Op: FstOp<W>, Cache: FstCache<W>> CoreFst<W> for LazyFst<W, Op
     }
 
     fn num_input_epsilons(&self, state: StateId) -> Result<usize> {
+        if let Some(n) = self.cache.num_input_epsilons(state) {
+            return Ok(n);
+        }
+        // The trs of the state haven't been computed yet.
+        self.get_trs(state)?;
         self.cache
             .num_input_epsilons(state)
             .ok_or_else(|| format_err!("State {:?} doesn't exist", state))
     }
 
     fn num_output_epsilons(&self, state: StateId) -> Result<usize> {
+        if let Some(n) = self.cache.num_output_epsilons(state) {
+            return Ok(n);
+        }
+        // The trs of the state haven't been computed yet.
+        self.get_trs(state)?;
         self.cache
             .num_output_epsilons(state)
             .ok_or_else(|| format_err!("State {:?} doesn't exist", state))
@@ -225,12 +238,38 @@ where
 
     /// Turns the Lazy FST into a static one.
     pub fn compute<F2: MutableFst<W> + AllocableFst<W>>(&self) -> Result<F2> {
+        Ok(self.compute_with_max_states(None)?.unwrap())
+    }
+
+    /// Turns the Lazy FST into a static one. If `max_states` is set and the static FST would
+    /// have more states, the computation is stopped and `None` is returned.
+    pub fn compute_with_max_states<F2: MutableFst<W> + AllocableFst<W>>(
+        &self,
+        max_states: Option<usize>,
+    ) -> Result<Option<F2>> {
+        self.compute_with_limits(max_states, None)
+    }
+
+    /// Same as [`compute_with_max_states`](#method.compute_with_max_states) but `progress` is
+    /// called regularly with the number of states computed so far. If it returns
+    /// `ProgressAction::Abort`, the computation fails with a `Cancelled` error.
+    pub fn compute_with_limits<F2: MutableFst<W> + AllocableFst<W>>(
+        &self,
+        max_states: Option<usize>,
+        progress: Option<ProgressCallback>,
+    ) -> Result<Option<F2>> {
+        let mut progress = Progress::new(progress);
         let start_state = self.start();
         let mut fst_out = F2::new();
         let start_state = match start_state {
             Some(s) => s,
-            None => return Ok(fst_out),
+            None => return Ok(Some(fst_out)),
         };
+        if let Some(max_states) = max_states {
+            if start_state as usize >= max_states {
+                return Ok(None);
+            }
+        }
         fst_out.add_states(start_state as usize + 1);
         fst_out.set_start(start_state)?;
         let mut queue = VecDeque::new();
@@ -250,13 +289,19 @@ where
                 }
                 let n = fst_out.num_states();
                 if (tr.nextstate as usize) >= n {
-                    fst_out.add_states(tr.nextstate as usize - n + 1)
+                    if let Some(max_states) = max_states {
+                        if tr.nextstate as usize >= max_states {
+                            return Ok(None);
+                        }
+                    }
+                    fst_out.add_states(tr.nextstate as usize - n + 1);
                 }
             }
             unsafe { fst_out.set_trs_unchecked(s, trs_owner.trs().to_vec()) };
             if let Some(f_w) = self.final_weight(s)? {
                 fst_out.set_final(s, f_w)?;
             }
+            progress.tick()?;
         }
         fst_out.set_properties(self.properties());
 
@@ -266,7 +311,7 @@ where
         if let Some(osymt) = &self.osymt {
             fst_out.set_output_symbols(Arc::clone(osymt));
         }
-        Ok(fst_out)
+        Ok(Some(fst_out))
     }
 }
 
@@ -56,13 +56,15 @@ impl<W: Semiring, Op: FstOp2<W>, Cache: FstCache<W>> CoreFst<W> for LazyFst2<W,
     }
 
     fn num_trs(&self, s: StateId) -> Result<usize> {
-        self.cache
-            .num_trs(s)
-            .ok_or_else(|| format_err!("State {:?} doesn't exist", s))
+        if let Some(n) = self.cache.num_trs(s) {
+            return Ok(n);
+        }
+        // The trs of the state haven't been computed yet or have been evicted from the cache.
+        Ok(self.get_trs(s)?.len())
     }
 
     unsafe fn num_trs_unchecked(&self, s: StateId) -> usize {
-        self.cache.num_trs(s).unsafe_unwrap()
+        self.num_trs(s).unsafe_unwrap()
     }
 
     fn get_trs(&self, state_id: StateId) -> Result<Self::TRS> {
@@ -86,12 +88,22 @@ impl<W: Semiring, Op: FstOp2<W>, Cache: FstCache<W>> CoreFst<W> for LazyFst2<W,
     }
 
     fn num_input_epsilons(&self, state: StateId) -> Result<usize> {
+        if let Some(n) = self.cache.num_input_epsilons(state) {
+            return Ok(n);
+        }
+        // The trs of the state haven't been computed yet.
+        self.get_trs(state)?;
         self.cache
             .num_input_epsilons(state)
             .ok_or_else(|| format_err!("State {:?} doesn't exist", state))
     }
 
     fn num_output_epsilons(&self, state: StateId) -> Result<usize> {
+        if let Some(n) = self.cache.num_output_epsilons(state) {
+            return Ok(n);
+        }
+        // The trs of the state haven't been computed yet.
+        self.get_trs(state)?;
         self.cache
             .num_output_epsilons(state)
             .ok_or_else(|| format_err!("State {:?} doesn't exist", state))
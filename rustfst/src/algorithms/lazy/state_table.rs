@@ -188,6 +188,13 @@ mod test {
     use crate::StateId;
     use anyhow::Result;
 
+    #[test]
+    fn test_state_table_sync() {
+        // Lazy FSTs are expanded from several threads by `compose_parallel`.
+        fn is_sync<T: Sync + Send>() {}
+        is_sync::<StateTable<ComposeStateTuple<IntegerFilterState>>>();
+    }
+
     #[test]
     fn test_read_write_state_table_empty() -> Result<()> {
         let state_table = StateTable::<ComposeStateTuple<IntegerFilterState>>::new();
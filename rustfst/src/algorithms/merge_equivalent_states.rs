@@ -0,0 +1,151 @@
+use std::cmp::Ordering;
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, VecDeque};
+
+use anyhow::Result;
+
+use crate::fst_traits::MutableFst;
+use crate::semirings::Semiring;
+use crate::{StateId, Tr, Trs};
+
+/// Merges the states of the FST that are exact duplicates : same final weight and same
+/// outgoing transitions (same labels, weights and destination states). The merge is repeated
+/// until a fixpoint is reached as merging states can turn their predecessors into duplicates.
+///
+/// This is much cheaper than a minimization but only merges states that are identical,
+/// not all the equivalent ones (e.g two states looping on themselves are never merged).
+/// The language and the weights of the FST are left unchanged. Useful after a `union`
+/// followed by a `rm_epsilon` for instance.
+pub fn merge_equivalent_states<W: Semiring, F: MutableFst<W>>(fst: &mut F) -> Result<()> {
+    let num_states = fst.num_states();
+    let mut predecessors: Vec<Vec<StateId>> = vec![vec![]; num_states];
+    for s in fst.states_range() {
+        for tr in fst.get_trs(s)?.trs() {
+            predecessors[tr.nextstate as usize].push(s);
+        }
+    }
+
+    // A state is only (re)visited when one of its successors has been merged, as it is
+    // the only way for its signature to change.
+    let mut representatives: Vec<StateId> = (0..num_states as StateId).collect();
+    let mut signatures: HashMap<(Option<W>, Vec<Tr<W>>), StateId> = HashMap::new();
+    let mut queue: VecDeque<StateId> = (0..num_states as StateId).collect();
+    let mut in_queue = vec![true; num_states];
+    let mut duplicates = vec![];
+    while let Some(s) = queue.pop_front() {
+        in_queue[s as usize] = false;
+        if representatives[s as usize] != s {
+            continue;
+        }
+        let mut trs = fst.get_trs(s)?.trs().to_vec();
+        for tr in trs.iter_mut() {
+            tr.nextstate = find_representative(&representatives, tr.nextstate);
+        }
+        trs.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+        // The signatures computed before a merge reference a merged state and thus can't be
+        // matched anymore.
+        let signature = (fst.final_weight(s)?, trs);
+        let representative = match signatures.entry(signature) {
+            Entry::Occupied(e) => find_representative(&representatives, *e.get()),
+            Entry::Vacant(e) => {
+                e.insert(s);
+                continue;
+            }
+        };
+        if representative == s {
+            continue;
+        }
+        representatives[s as usize] = representative;
+        duplicates.push(s);
+        let preds = std::mem::take(&mut predecessors[s as usize]);
+        for &p in preds.iter() {
+            if !in_queue[p as usize] {
+                in_queue[p as usize] = true;
+                queue.push_back(p);
+            }
+        }
+        predecessors[representative as usize].extend(preds);
+    }
+    if duplicates.is_empty() {
+        return Ok(());
+    }
+
+    for s in fst.states_range() {
+        let mut it_tr = fst.tr_iter_mut(s)?;
+        for idx_tr in 0..it_tr.len() {
+            let nextstate = unsafe { it_tr.get_unchecked(idx_tr) }.nextstate;
+            let representative = find_representative(&representatives, nextstate);
+            if representative != nextstate {
+                unsafe { it_tr.set_nextstate_unchecked(idx_tr, representative) };
+            }
+        }
+    }
+    if let Some(start) = fst.start() {
+        fst.set_start(find_representative(&representatives, start))?;
+    }
+    fst.del_states(duplicates)?;
+    Ok(())
+}
+
+fn find_representative(representatives: &[StateId], mut s: StateId) -> StateId {
+    while representatives[s as usize] != s {
+        s = representatives[s as usize];
+    }
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::algorithms::rm_epsilon::rm_epsilon;
+    use crate::algorithms::union::union;
+    use crate::fst_impls::VectorFst;
+    use crate::fst_traits::{ExpandedFst, Fst};
+    use crate::semirings::TropicalWeight;
+    use crate::utils::acceptor;
+
+    #[test]
+    fn test_merge_equivalent_states_after_union() -> Result<()> {
+        let mut fst: VectorFst<TropicalWeight> = acceptor(&[1, 2, 3], TropicalWeight::one());
+        let fst2: VectorFst<TropicalWeight> = acceptor(&[4, 2, 3], TropicalWeight::one());
+        let fst3: VectorFst<TropicalWeight> = acceptor(&[5, 6, 3], TropicalWeight::new(2.0));
+        union(&mut fst, &fst2)?;
+        union(&mut fst, &fst3)?;
+        rm_epsilon(&mut fst)?;
+        let paths_ref: HashSet<_> = fst.paths_iter().collect();
+        let num_states = fst.num_states();
+
+        merge_equivalent_states(&mut fst)?;
+
+        // All the states of the second acceptor are merged with the ones of the first one,
+        // the third one has a different final weight and thus shares no state.
+        assert_eq!(num_states, 10);
+        assert_eq!(fst.num_states(), 7);
+        let paths: HashSet<_> = fst.paths_iter().collect();
+        assert_eq!(paths, paths_ref);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_merge_equivalent_states_long_chain() -> Result<()> {
+        // The merges propagate from the final states back to the start state.
+        let labels: Vec<_> = (1..=100).collect();
+        let mut fst: VectorFst<TropicalWeight> = acceptor(&labels, TropicalWeight::one());
+        let fst2: VectorFst<TropicalWeight> = acceptor(&labels, TropicalWeight::one());
+        union(&mut fst, &fst2)?;
+        rm_epsilon(&mut fst)?;
+        let paths_ref: Vec<_> = fst.paths_iter().collect();
+
+        merge_equivalent_states(&mut fst)?;
+
+        assert_eq!(fst.num_states(), 101);
+        let paths: Vec<_> = fst.paths_iter().collect();
+        assert_eq!(paths.len(), 2);
+        assert_eq!(paths, paths_ref);
+
+        Ok(())
+    }
+}
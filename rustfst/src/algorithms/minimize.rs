@@ -9,10 +9,12 @@ use anyhow::Result;
 use binary_heap_plus::BinaryHeap;
 use stable_bst::TreeMap;
 
+use crate::algorithms::determinize::{determinize_with_config, DeterminizeConfig, DeterminizeType};
 use crate::algorithms::encode::EncodeType;
 use crate::algorithms::factor_weight::factor_iterators::GallicFactorLeft;
 use crate::algorithms::factor_weight::{factor_weight, FactorWeightOptions, FactorWeightType};
 use crate::algorithms::partition::Partition;
+use crate::algorithms::progress::{Progress, ProgressCallback};
 use crate::algorithms::queues::LifoQueue;
 use crate::algorithms::tr_compares::ILabelCompare;
 use crate::algorithms::tr_mappers::QuantizeMapper;
@@ -40,10 +42,11 @@ use std::cell::RefCell;
 use std::rc::Rc;
 
 /// Configuration for minimization.
-#[derive(Clone, Copy, PartialOrd, PartialEq)]
+#[derive(Clone, PartialOrd, PartialEq)]
 pub struct MinimizeConfig {
     delta: f32,
     allow_nondet: bool,
+    progress: Option<ProgressCallback>,
 }
 
 impl MinimizeConfig {
@@ -51,6 +54,7 @@ impl MinimizeConfig {
         Self {
             delta,
             allow_nondet,
+            progress: None,
         }
     }
 
@@ -64,6 +68,12 @@ impl MinimizeConfig {
             ..self
         }
     }
+
+    /// Sets a callback called regularly with the number of states processed so far. The
+    /// minimization fails with a `Cancelled` error if it returns `ProgressAction::Abort`.
+    pub fn with_progress(self, progress: Option<ProgressCallback>) -> Self {
+        Self { progress, ..self }
+    }
 }
 
 impl Default for MinimizeConfig {
@@ -71,13 +81,15 @@ impl Default for MinimizeConfig {
         Self {
             delta: KSHORTESTDELTA,
             allow_nondet: false,
+            progress: None,
         }
     }
 }
 
-/// In place minimization of deterministic weighted automata and transducers,
-/// and also non-deterministic ones if they use an idempotent semiring.
+/// In place minimization of deterministic weighted automata and transducers.
 /// For transducers, the algorithm produces a compact factorization of the minimal transducer.
+/// Use [`minimize_with_config`](fn.minimize_with_config.html) with `allow_nondet` to minimize
+/// non-deterministic ones.
 pub fn minimize<W, F>(ifst: &mut F) -> Result<()>
 where
     F: MutableFst<W> + ExpandedFst<W> + AllocableFst<W>,
@@ -88,8 +100,22 @@ where
 }
 
 /// In place minimization of deterministic weighted automata and transducers,
-/// and also non-deterministic ones if they use an idempotent semiring.
+/// and also non-deterministic ones once determinized if `allow_nondet` is set.
 /// For transducers, the algorithm produces a compact factorization of the minimal transducer.
+///
+/// The path taken depends on the properties of `ifst` :
+/// - A transducer is converted to the gallic semiring, encoded, minimized as an unweighted
+///   acceptor, decoded and then factorized back.
+/// - A weighted acceptor is encoded, each (label, weight) pair becoming a single label,
+///   minimized as an unweighted acceptor and then decoded.
+/// - An unweighted acceptor is minimized directly.
+///
+/// A non-deterministic input is only accepted if `allow_nondet` is set in `config`, it is then
+/// determinized first with `DeterminizeNonFunctional` so that all the outputs of a transducer
+/// are kept. Otherwise an error is returned and `ifst` is left untouched.
+///
+/// If the minimization is cancelled by the progress callback of `config`, `ifst` is left
+/// equivalent to the input but its weights may have been pushed and quantized.
 pub fn minimize_with_config<W, F>(ifst: &mut F, config: MinimizeConfig) -> Result<()>
 where
     F: MutableFst<W> + ExpandedFst<W> + AllocableFst<W>,
@@ -98,24 +124,39 @@ where
 {
     let delta = config.delta;
     let allow_nondet = config.allow_nondet;
+    let mut progress = Progress::new(config.progress.clone());
+
+    let props_mask = FstProperties::ACCEPTOR
+        | FstProperties::I_DETERMINISTIC
+        | FstProperties::WEIGHTED
+        | FstProperties::UNWEIGHTED;
+    let mut props = ifst.compute_and_update_properties(props_mask)?;
+
+    if !props.contains(FstProperties::I_DETERMINISTIC) {
+        if !allow_nondet {
+            bail!(
+                "Refusing to minimize a non-deterministic FST with allow_nondet = false. \
+                 Either determinize it first or set allow_nondet."
+            )
+        }
+        let det_config = DeterminizeConfig::default()
+            .with_det_type(DeterminizeType::DeterminizeNonFunctional)
+            .with_progress(config.progress);
+        *ifst = determinize_with_config(ifst, det_config)?;
+        props = ifst.compute_and_update_properties(props_mask)?;
+    }
 
-    let props = ifst.compute_and_update_properties(
-        FstProperties::ACCEPTOR
-            | FstProperties::I_DETERMINISTIC
-            | FstProperties::WEIGHTED
-            | FstProperties::UNWEIGHTED,
-    )?;
-
+    // The determinization of a non-functional transducer emits the different outputs of an
+    // input string on epsilon transitions towards the final states.
     let allow_acyclic_minimization = if props.contains(FstProperties::I_DETERMINISTIC) {
         true
-    } else {
-        if !W::properties().contains(SemiringProperties::IDEMPOTENT) {
-            bail!("Cannot minimize a non-deterministic FST over a non-idempotent semiring")
-        } else if !allow_nondet {
-            bail!("Refusing to minimize a non-deterministic FST with allow_nondet = false")
-        }
-
+    } else if W::properties().contains(SemiringProperties::IDEMPOTENT) {
         false
+    } else {
+        bail!(
+            "Cannot minimize a non-deterministic FST over a non-idempotent semiring. \
+             The FST is still non-deterministic once determinized."
+        )
     };
 
     if !props.contains(FstProperties::ACCEPTOR) {
@@ -134,7 +175,7 @@ where
 
         let encode_table = encode(&mut gfst, EncodeType::EncodeWeightsAndLabels)?;
 
-        acceptor_minimize(&mut gfst, allow_acyclic_minimization)?;
+        acceptor_minimize_with_progress(&mut gfst, allow_acyclic_minimization, &mut progress)?;
 
         decode(&mut gfst, encode_table)?;
 
@@ -166,12 +207,56 @@ where
         let quantize_mapper = QuantizeMapper::new(delta);
         tr_map(ifst, &quantize_mapper)?;
         let encode_table = encode(ifst, EncodeType::EncodeWeightsAndLabels)?;
-        acceptor_minimize(ifst, allow_acyclic_minimization)?;
-        decode(ifst, encode_table)
+        let res = acceptor_minimize_with_progress(ifst, allow_acyclic_minimization, &mut progress);
+        // Decoded even when the minimization is cancelled so that `ifst` can still be used.
+        decode(ifst, encode_table)?;
+        res
     } else {
         // Unweighted acceptor
-        acceptor_minimize(ifst, allow_acyclic_minimization)
+        acceptor_minimize_with_progress(ifst, allow_acyclic_minimization, &mut progress)
+    }
+}
+
+/// In place minimization of a lattice, seen as a weighted acceptor over pairs of input and
+/// output labels.
+///
+/// The weights are first pushed towards the initial state and quantized with `delta`, so that
+/// the near-equal weights typically found in lattices don't prevent equivalent states from
+/// being merged. Each (input label, output label, weight) triple is then encoded as a single
+/// label, the resulting unweighted acceptor is minimized and finally decoded. Contrary to
+/// [`minimize`](fn.minimize.html) on transducers, no gallic conversion is performed : the
+/// labels of the transitions are preserved and only states are merged.
+///
+/// The weight of every string is preserved up to `delta`. An error is returned if the encoded
+/// lattice is non-deterministic and the semiring is not idempotent, `fst` being left unchanged.
+pub fn minimize_lattice<W, F>(fst: &mut F, delta: f32) -> Result<()>
+where
+    F: MutableFst<W> + ExpandedFst<W> + AllocableFst<W>,
+    W: WeaklyDivisibleSemiring + WeightQuantize,
+{
+    // The determinism of the lattice is only known once it is encoded : work on a copy so
+    // that `fst` is left unchanged on error.
+    let mut lattice = fst.clone();
+    let push_weights_config = PushWeightsConfig::default().with_delta(delta);
+    push_weights_with_config(
+        &mut lattice,
+        ReweightType::ReweightToInitial,
+        push_weights_config,
+    )?;
+    let quantize_mapper = QuantizeMapper::new(delta);
+    tr_map(&mut lattice, &quantize_mapper)?;
+
+    let encode_table = encode(&mut lattice, EncodeType::EncodeWeightsAndLabels)?;
+    let props = lattice.compute_and_update_properties(FstProperties::I_DETERMINISTIC)?;
+    if !props.contains(FstProperties::I_DETERMINISTIC)
+        && !W::properties().contains(SemiringProperties::IDEMPOTENT)
+    {
+        bail!("Cannot minimize a non-deterministic lattice over a non-idempotent semiring")
     }
+    acceptor_minimize(&mut lattice, true)?;
+    decode(&mut lattice, encode_table)?;
+    *fst = lattice;
+    Ok(())
 }
 
 /// In place minimization for weighted final state acceptor.
@@ -182,6 +267,14 @@ where
 pub fn acceptor_minimize<W: Semiring, F: MutableFst<W> + ExpandedFst<W>>(
     ifst: &mut F,
     allow_acyclic_minimization: bool,
+) -> Result<()> {
+    acceptor_minimize_with_progress(ifst, allow_acyclic_minimization, &mut Progress::new(None))
+}
+
+fn acceptor_minimize_with_progress<W: Semiring, F: MutableFst<W> + ExpandedFst<W>>(
+    ifst: &mut F,
+    allow_acyclic_minimization: bool,
+    progress: &mut Progress,
 ) -> Result<()> {
     let props = ifst.compute_and_update_properties(
         FstProperties::ACCEPTOR | FstProperties::UNWEIGHTED | FstProperties::ACYCLIC,
@@ -199,10 +292,10 @@ pub fn acceptor_minimize<W: Semiring, F: MutableFst<W> + ExpandedFst<W>>(
     if allow_acyclic_minimization && props.contains(FstProperties::ACYCLIC) {
         // Acyclic minimization
         tr_sort(ifst, ILabelCompare {});
-        let minimizer = AcyclicMinimizer::new(ifst)?;
+        let minimizer = AcyclicMinimizer::new(ifst, progress)?;
         merge_states(minimizer.get_partition(), ifst)?;
     } else {
-        let p = cyclic_minimize(ifst)?;
+        let p = cyclic_minimize(ifst, progress)?;
         merge_states(p, ifst)?;
     }
 
@@ -308,12 +401,15 @@ struct AcyclicMinimizer {
 }
 
 impl AcyclicMinimizer {
-    pub fn new<W: Semiring, F: MutableFst<W>>(fst: &mut F) -> Result<Self> {
+    pub fn new<W: Semiring, F: MutableFst<W>>(
+        fst: &mut F,
+        progress: &mut Progress,
+    ) -> Result<Self> {
         let mut c = Self {
             partition: Rc::new(RefCell::new(Partition::empty_new())),
         };
         c.initialize(fst)?;
-        c.refine(fst);
+        c.refine(fst, progress)?;
         Ok(c)
     }
 
@@ -338,7 +434,11 @@ impl AcyclicMinimizer {
         Ok(())
     }
 
-    fn refine<W: Semiring, F: MutableFst<W>>(&mut self, fst: &mut F) {
+    fn refine<W: Semiring, F: MutableFst<W>>(
+        &mut self,
+        fst: &mut F,
+        progress: &mut Progress,
+    ) -> Result<()> {
         let state_cmp = StateComparator {
             fst,
             partition: Rc::clone(&self.partition),
@@ -373,8 +473,10 @@ impl AcyclicMinimizer {
                         .borrow_mut()
                         .move_element(s, new_class as usize);
                 }
+                progress.tick()?;
             }
         }
+        Ok(())
     }
 
     pub fn get_partition(self) -> Rc<RefCell<Partition>> {
@@ -512,7 +614,10 @@ fn pre_partition<W: Semiring, F: MutableFst<W>>(
     }
 }
 
-fn cyclic_minimize<W: Semiring, F: MutableFst<W>>(fst: &mut F) -> Result<Rc<RefCell<Partition>>> {
+fn cyclic_minimize<W: Semiring, F: MutableFst<W>>(
+    fst: &mut F,
+    progress: &mut Progress,
+) -> Result<Rc<RefCell<Partition>>> {
     // Initialize
     let mut tr: VectorFst<W::ReverseWeight> = reverse(fst)?;
     tr_sort(&mut tr, ILabelCompare {});
@@ -542,6 +647,7 @@ fn cyclic_minimize<W: Semiring, F: MutableFst<W>>(fst: &mut F) -> Result<Rc<RefC
                     w: PhantomData,
                 });
             }
+            progress.tick()?;
         }
 
         let mut prev_label = -1;
@@ -614,6 +720,7 @@ impl TrIterCompare {
 
 #[cfg(test)]
 mod tests {
+    use crate::fst_properties::FstProperties;
     use crate::prelude::*;
     use ::proptest::prelude::*;
     use algorithms::determinize::*;
@@ -681,6 +788,104 @@ mod tests {
         assert_eq!(accept1, accept2);
     }
 
+    fn nondet_transducer() -> VectorFst<TropicalWeight> {
+        let text_fst = "0\t1\t1\t2\t1.0
+0\t2\t1\t3\t1.0
+1\t3\t4\t4\t0.5
+2\t4\t4\t4\t0.5
+3\t0.0
+4\t0.0
+";
+        VectorFst::from_text_string(text_fst).unwrap()
+    }
+
+    #[test]
+    fn test_minimize_nondet_transducer() -> anyhow::Result<()> {
+        let fst = nondet_transducer();
+        let mut min_fst = fst.clone();
+        minimize_with_config(
+            &mut min_fst,
+            MinimizeConfig::default().with_allow_nondet(true),
+        )?;
+
+        // Not functional : all the outputs are kept.
+        let paths = |fst: &VectorFst<TropicalWeight>| {
+            let mut paths: Vec<_> = fst.paths_iter().collect();
+            paths.sort_by(|a, b| (&a.ilabels, &a.olabels).cmp(&(&b.ilabels, &b.olabels)));
+            paths
+        };
+        let paths_ref = paths(&fst);
+        let paths_min = paths(&min_fst);
+        assert_eq!(paths_ref.len(), paths_min.len());
+        for (p_ref, p_min) in paths_ref.iter().zip(paths_min.iter()) {
+            assert_eq!(p_ref.ilabels, p_min.ilabels);
+            assert_eq!(p_ref.olabels, p_min.olabels);
+            assert!(p_ref.weight.approx_equal(&p_min.weight, KDELTA));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_minimize_nondet_functional_transducer() -> anyhow::Result<()> {
+        // Two paths with the same input and output strings.
+        let text_fst = "0\t1\t1\t2\t1.0
+0\t2\t1\t2\t2.0
+0\t4\t6\t7\t1.5
+1\t3\t4\t5\t1.0
+2\t3\t4\t5\t0.5
+3\t0.5
+4\t2.0
+";
+        let fst: VectorFst<TropicalWeight> = VectorFst::from_text_string(text_fst)?;
+        let mut min_fst = fst.clone();
+        minimize_with_config(
+            &mut min_fst,
+            MinimizeConfig::default().with_allow_nondet(true),
+        )?;
+        assert!(min_fst
+            .compute_and_update_properties(FstProperties::I_DETERMINISTIC)?
+            .contains(FstProperties::I_DETERMINISTIC));
+        assert_eq!(min_fst.num_states(), 3);
+
+        let paths: Vec<_> = min_fst.paths_iter().collect();
+        assert_eq!(paths.len(), 2);
+        for path in paths {
+            let weight = path_weight(&fst, &path.ilabels)?;
+            assert!(path.weight.approx_equal(weight, KDELTA));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_minimize_nondet_transducer_not_allowed() {
+        let mut fst = nondet_transducer();
+        let fst_ref = fst.clone();
+        assert!(minimize(&mut fst).is_err());
+        assert_eq!(fst, fst_ref);
+    }
+
+    #[test]
+    fn test_minimize_cancelled_keeps_fst_decoded() -> anyhow::Result<()> {
+        let labels: Vec<_> = (0..2500)
+            .map(|i| (i % 3 + 1, i % 3 + 1, TropicalWeight::new(1.0)))
+            .collect();
+        let fst: VectorFst<TropicalWeight> = utils::chain(&labels);
+
+        let mut min_fst = fst.clone();
+        let config = MinimizeConfig::default()
+            .with_progress(Some(ProgressCallback::new(|_| ProgressAction::Abort)));
+        let err = minimize_with_config(&mut min_fst, config).unwrap_err();
+        assert!(err.downcast_ref::<Cancelled>().is_some());
+
+        let path_ref = fst.paths_iter().next().unwrap();
+        let paths_min: Vec<_> = min_fst.paths_iter().collect();
+        assert_eq!(paths_min.len(), 1);
+        assert_eq!(paths_min[0].ilabels, path_ref.ilabels);
+        assert_eq!(paths_min[0].olabels, path_ref.olabels);
+        assert!(paths_min[0].weight.approx_equal(&path_ref.weight, KDELTA));
+        Ok(())
+    }
+
     proptest! {
         #[test]
         fn test_proptest_minimize_timeout(mut fst in any::<VectorFst::<TropicalWeight>>()) {
@@ -689,6 +894,58 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_minimize_lattice() -> anyhow::Result<()> {
+        // Two hypotheses sharing the same suffix, with near-equal weights.
+        let text_fst = "0\t1\t1\t10\t1.0
+0\t2\t2\t11\t1.5
+1\t3\t3\t12\t2.0
+2\t4\t3\t12\t2.0000002
+3\t5\t4\t13\t0.5
+4\t6\t4\t13\t0.5
+5\t0.0
+6\t0.0000001
+";
+        let fst: VectorFst<TropicalWeight> = VectorFst::from_text_string(text_fst)?;
+        let mut min_fst = fst.clone();
+        minimize_lattice(&mut min_fst, KDELTA)?;
+        assert_eq!(min_fst.num_states(), 4);
+
+        for ilabels in &[vec![1, 3, 4], vec![2, 3, 4], vec![1, 3], vec![3, 4]] {
+            let weight = path_weight(&fst, ilabels)?;
+            let min_weight = path_weight(&min_fst, ilabels)?;
+            assert!(weight == min_weight || weight.approx_equal(min_weight, KDELTA));
+        }
+
+        let labels = |fst: &VectorFst<TropicalWeight>| {
+            let mut labels: Vec<_> = fst.paths_iter().map(|p| (p.ilabels, p.olabels)).collect();
+            labels.sort();
+            labels
+        };
+        assert_eq!(labels(&fst), labels(&min_fst));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_minimize_lattice_non_deterministic_error() -> anyhow::Result<()> {
+        // Same labels and weights towards two states : the encoded lattice is non-deterministic.
+        let mut fst = VectorFst::<LogWeight>::new();
+        fst.add_states(4);
+        fst.set_start(0)?;
+        fst.add_tr(0, Tr::new(1, 2, 1.0, 1))?;
+        fst.add_tr(0, Tr::new(1, 2, 1.0, 2))?;
+        fst.add_tr(1, Tr::new(3, 3, 1.0, 3))?;
+        fst.add_tr(2, Tr::new(4, 4, 1.0, 3))?;
+        fst.set_final(3, 0.5)?;
+        let fst_ref = fst.clone();
+
+        assert!(minimize_lattice(&mut fst, KDELTA).is_err());
+        assert_eq!(fst, fst_ref);
+
+        Ok(())
+    }
+
     proptest! {
         #[test]
         #[ignore] // falls into the same infinite loop as the timeout test
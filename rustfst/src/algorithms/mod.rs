@@ -1,38 +1,70 @@
 pub use self::{
     add_super_final_state::add_super_final_state,
     all_pairs_shortest_distance::all_pairs_shortest_distance,
-    condense::condense,
-    connect::connect,
+    compat_symbols::check_symbol_compatibility,
+    condense::{condense, scc},
+    connect::{accessible, coaccessible, connect},
+    difference::difference,
+    disambiguate::{disambiguate, disambiguate_with_config, DisambiguateConfig},
+    epsilon_to_symbol::{epsilon_to_symbol, symbol_to_epsilon, VISIBLE_EPS_SYMBOL},
+    equivalent::equivalent,
     fst_convert::{fst_convert, fst_convert_from_ref},
+    in_tr_index::InTrIndex,
+    intersect::{intersect, IntersectFst},
     inversion::invert,
     isomorphic::{isomorphic, isomorphic_with_config, IsomorphicConfig},
-    minimize::{acceptor_minimize, minimize, minimize_with_config, MinimizeConfig},
+    merge_equivalent_states::merge_equivalent_states,
+    minimize::{
+        acceptor_minimize, minimize, minimize_lattice, minimize_with_config, MinimizeConfig,
+    },
+    normalize_log::normalize_log,
     optimize::optimize,
+    path_weight::path_weight,
+    progress::{Cancelled, ProgressAction, ProgressCallback, PROGRESS_REPORT_INTERVAL},
+    project_fst::ProjectFst,
     projection::{project, ProjectType},
+    prune::{prune, prune_fst},
     push::{
         push, push_weights, push_weights_with_config, push_with_config, PushConfig, PushType,
         PushWeightsConfig,
     },
     queue::{Queue, QueueType},
-    relabel_pairs::relabel_pairs,
+    relabel_pairs::{relabel_pairs, relabel_pairs_with_config, RelabelPairsConfig},
     reverse::reverse,
     reweight::{reweight, ReweightType},
     rm_final_epsilon::rm_final_epsilon,
-    shortest_distance::{shortest_distance, shortest_distance_with_config, ShortestDistanceConfig},
-    shortest_path::{shortest_path, shortest_path_with_config, ShortestPathConfig},
+    set_initial_weight::set_initial_weight,
+    shortest_distance::{
+        shortest_distance, shortest_distance_with_config, shortest_distance_with_queue,
+        ShortestDistanceConfig,
+    },
+    shortest_path::{
+        best_path_features, best_path_features_with_final, nbest_outputs, prune_to_nbest,
+        shortest_path, shortest_path_iter, shortest_path_with_config, ShortestPathConfig, TieBreak,
+    },
+    split_components::{split_components, split_components_with_config, SplitComponentsConfig},
     state_sort::state_sort,
-    top_sort::top_sort,
+    stats::degrees,
+    synchronize::synchronize,
+    top_sort::{top_order_ignoring_self_loops, top_sort, top_sort_order},
     tr_map::{tr_map, FinalTr, MapFinalAction, TrMapper},
-    tr_sort::tr_sort,
+    tr_map_fst::TrMapFst,
+    tr_sort::{tr_sort, tr_sort_by},
+    tr_sort_fst::TrSortFst,
     tr_sum::tr_sum,
     tr_unique::tr_unique,
+    verify::verify,
     weight_convert::{weight_convert, WeightConverter},
 };
 
+#[cfg(feature = "rayon")]
+pub use self::shortest_distance::shortest_distance_parallel;
+
 mod add_super_final_state;
 mod all_pairs_shortest_distance;
 /// Functions to compute Kleene closure (star or plus) of an FST.
 pub mod closure;
+mod compat_symbols;
 #[allow(clippy::type_complexity)]
 /// Functions to compose FSTs.
 pub mod compose;
@@ -43,41 +75,60 @@ mod connect;
 /// Functions to determinize FSTs.
 pub mod determinize;
 pub(crate) mod dfs_visit;
+mod difference;
+mod disambiguate;
 /// Functions to encode FSTs as FSAs and vice versa.
 pub mod encode;
+mod epsilon_to_symbol;
+mod equivalent;
 /// Functions to factor various weight types.
 pub mod factor_weight;
 mod fst_convert;
+mod in_tr_index;
+mod intersect;
 mod inversion;
 mod isomorphic;
+mod merge_equivalent_states;
 mod minimize;
+mod normalize_log;
 mod optimize;
 mod partition;
+mod path_weight;
+mod progress;
+mod project_fst;
 mod projection;
+mod prune;
 mod push;
 mod queue;
 
 /// Functions to randomly generate paths through an Fst. A static and a delayed version are available.
 pub mod randgen;
-mod relabel_pairs;
+pub(crate) mod relabel_pairs;
 /// Functions for lazy replacing transitions in an FST.
 pub mod replace;
 mod reverse;
 mod reweight;
+mod set_initial_weight;
 
 /// Functions to remove epsilon transitions from an Fst. A static and a delayed version are available.
 pub mod rm_epsilon;
 mod rm_final_epsilon;
 mod shortest_distance;
 mod shortest_path;
+mod split_components;
 mod state_sort;
+mod stats;
+mod synchronize;
 mod top_sort;
 mod tr_map;
+mod tr_map_fst;
 mod tr_sort;
+mod tr_sort_fst;
 mod tr_sum;
 pub(crate) mod tr_unique;
 /// Functions to compute the union of FSTs.
 pub mod union;
+mod verify;
 mod weight_convert;
 
 /// Module providing different structures implementing the `Queue` trait.
@@ -97,7 +148,9 @@ pub mod weight_converters;
 /// Functions to compare / sort the Trs of an FST.
 pub mod tr_compares {
     pub use super::isomorphic::tr_compare;
-    pub use super::tr_sort::{ILabelCompare, OLabelCompare, TrCompare};
+    pub use super::tr_sort::{
+        ILabelCompare, ILabelWeightCompare, OLabelCompare, OLabelWeightCompare, TrCompare,
+    };
 }
 
 /// Module providing the necessary functions to implement a new Delayed Fst.
@@ -0,0 +1,131 @@
+use anyhow::Result;
+
+use crate::fst_traits::MutableFst;
+use crate::semirings::{DivideType, LogWeight, Semiring, WeaklyDivisibleSemiring};
+use crate::Trs;
+
+/// Locally normalizes an FST over the log semiring : for every state, the (negated log)
+/// log-sum-exp of the weights of its outgoing transitions and of its final weight is
+/// subtracted from each of them. Afterwards, the weights leaving each state form a probability
+/// distribution, i.e their `plus` is `LogWeight::one()`.
+///
+/// Contrary to weight pushing, the weights are not moved along the paths : the weight of a path
+/// becomes its probability conditioned on the choices made at each state. States with no
+/// outgoing transitions and no final weight are left untouched, a state with only a final
+/// weight gets a final weight of `LogWeight::one()`.
+///
+/// # Example
+/// ```
+/// # use rustfst::fst_impls::VectorFst;
+/// # use rustfst::fst_traits::{CoreFst, MutableFst};
+/// # use rustfst::semirings::{LogWeight, Semiring};
+/// # use rustfst::algorithms::normalize_log;
+/// # use rustfst::{Tr, Trs};
+/// # use anyhow::Result;
+/// # fn main() -> Result<()> {
+/// let mut fst = VectorFst::<LogWeight>::new();
+/// fst.add_states(2);
+/// fst.set_start(0)?;
+/// fst.set_final(1, 5.0)?;
+/// fst.add_tr(0, Tr::new(1, 1, 3.0, 1))?;
+/// fst.add_tr(0, Tr::new(2, 2, 3.0, 1))?;
+///
+/// normalize_log(&mut fst)?;
+/// assert!((fst.get_trs(0)?.trs()[0].weight.value() - 2f32.ln()).abs() < 1e-5);
+/// assert_eq!(fst.final_weight(1)?, Some(LogWeight::one()));
+/// # Ok(())
+/// # }
+/// ```
+pub fn normalize_log<F: MutableFst<LogWeight>>(fst: &mut F) -> Result<()> {
+    for s in fst.states_range() {
+        let final_weight = fst.final_weight(s)?;
+        let total = {
+            let trs = fst.get_trs(s)?;
+            neg_log_sum_exp(
+                trs.trs()
+                    .iter()
+                    .map(|tr| *tr.weight.value())
+                    .chain(final_weight.as_ref().map(|w| *w.value())),
+            )
+        };
+        if !total.is_finite() {
+            continue;
+        }
+        let total = LogWeight::new(total);
+
+        let mut it_tr = fst.tr_iter_mut(s)?;
+        for idx_tr in 0..it_tr.len() {
+            let tr = unsafe { it_tr.get_unchecked(idx_tr) };
+            let weight = tr.weight.divide(&total, DivideType::DivideAny)?;
+            unsafe { it_tr.set_weight_unchecked(idx_tr, weight) };
+        }
+        if let Some(final_weight) = final_weight {
+            fst.set_final(s, final_weight.divide(&total, DivideType::DivideAny)?)?;
+        }
+    }
+    Ok(())
+}
+
+/// Computes `-ln(sum(exp(-v)))` by factoring out the smallest value to avoid underflows.
+/// Returns infinity (the log zero) if there are no finite values.
+fn neg_log_sum_exp<I: Iterator<Item = f32> + Clone>(values: I) -> f32 {
+    let min = values.clone().fold(f32::INFINITY, f32::min);
+    if !min.is_finite() {
+        return min;
+    }
+    let min = min as f64;
+    let sum: f64 = values.map(|v| (-(v as f64 - min)).exp()).sum();
+    (min - sum.ln()) as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fst_impls::VectorFst;
+    use crate::fst_traits::CoreFst;
+    use crate::Tr;
+
+    #[test]
+    fn test_normalize_log() -> Result<()> {
+        let mut fst = VectorFst::<LogWeight>::new();
+        fst.add_states(4);
+        fst.set_start(0)?;
+        fst.add_tr(0, Tr::new(1, 1, 0.5, 1))?;
+        fst.add_tr(0, Tr::new(2, 2, 1.5, 2))?;
+        fst.add_tr(0, Tr::new(3, 3, 1000.0, 2))?;
+        fst.set_final(0, 2.0)?;
+        // Large weights which would underflow with a naive log-sum-exp.
+        fst.add_tr(1, Tr::new(1, 1, 200.0, 2))?;
+        fst.add_tr(1, Tr::new(2, 2, 201.0, 1))?;
+        // Only a final weight.
+        fst.set_final(2, 3.0)?;
+        // Neither transitions nor final weight.
+
+        normalize_log(&mut fst)?;
+
+        for s in 0..3 {
+            let mut sum = LogWeight::zero();
+            for tr in fst.get_trs(s)?.trs() {
+                sum.plus_assign(&tr.weight)?;
+            }
+            if let Some(final_weight) = fst.final_weight(s)? {
+                sum.plus_assign(final_weight)?;
+            }
+            assert!(
+                sum.approx_equal(LogWeight::one(), 1e-4),
+                "State {} sums to {:?}",
+                s,
+                sum
+            );
+        }
+        assert_eq!(fst.final_weight(2)?, Some(LogWeight::one()));
+        assert_eq!(fst.final_weight(3)?, None);
+
+        // Relative weights are preserved.
+        let trs = fst.get_trs(1)?;
+        let diff = trs.trs()[1].weight.value() - trs.trs()[0].weight.value();
+        assert!((diff - 1.0).abs() < 1e-4);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,123 @@
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+
+use anyhow::Result;
+
+use crate::algorithms::shortest_distance;
+use crate::fst_impls::VectorFst;
+use crate::fst_traits::{CoreFst, ExpandedFst, Fst, MutableFst};
+use crate::semirings::Semiring;
+use crate::{Label, StateId, Tr, Trs, EPS_LABEL};
+
+/// Computes the sum (w.r.t. `plus`) of the weights of all the successful paths of the FST
+/// whose input labels spell `ilabels`.
+///
+/// The FST doesn't need to be deterministic and transitions with an epsilon input label
+/// are followed without consuming any label. This is equivalent to composing a linear
+/// acceptor of `ilabels` with the FST and computing the total weight of the result.
+/// `zero()` is returned if the sequence is not accepted.
+///
+/// # Example
+///
+/// ```
+/// # use anyhow::Result;
+/// # use rustfst::fst_impls::VectorFst;
+/// # use rustfst::fst_traits::MutableFst;
+/// # use rustfst::semirings::{ProbabilityWeight, Semiring};
+/// # use rustfst::algorithms::path_weight;
+/// # use rustfst::Tr;
+/// # fn main() -> Result<()> {
+/// let mut fst = VectorFst::<ProbabilityWeight>::new();
+/// fst.add_states(2);
+/// fst.set_start(0)?;
+/// fst.set_final(1, ProbabilityWeight::one())?;
+/// fst.add_tr(0, Tr::new(1, 1, 0.25, 1))?;
+/// fst.add_tr(0, Tr::new(1, 2, 0.5, 1))?;
+///
+/// assert_eq!(path_weight(&fst, &[1])?, ProbabilityWeight::new(0.75));
+/// assert_eq!(path_weight(&fst, &[2])?, ProbabilityWeight::zero());
+/// # Ok(())
+/// # }
+/// ```
+pub fn path_weight<W: Semiring, F: ExpandedFst<W>>(fst: &F, ilabels: &[Label]) -> Result<W> {
+    let start = match fst.start() {
+        Some(start) => start,
+        None => return Ok(W::zero()),
+    };
+
+    // Intersection of the FST with a linear acceptor of `ilabels`. A state of the
+    // intersection is a pair (number of labels consumed, state of the FST).
+    let mut ifst = VectorFst::<W>::new();
+    let mut states: HashMap<(usize, StateId), StateId> = HashMap::new();
+    let mut queue = vec![(0, start)];
+    states.insert((0, start), ifst.add_state());
+    ifst.set_start(0)?;
+
+    while let Some((pos, s)) = queue.pop() {
+        let is = states[&(pos, s)];
+        if pos == ilabels.len() {
+            if let Some(final_weight) = fst.final_weight(s)? {
+                ifst.set_final(is, final_weight)?;
+            }
+        }
+        for tr in fst.get_trs(s)?.trs() {
+            let next_pos = if tr.ilabel == EPS_LABEL {
+                pos
+            } else if pos < ilabels.len() && tr.ilabel == ilabels[pos] {
+                pos + 1
+            } else {
+                continue;
+            };
+            let next_is = match states.entry((next_pos, tr.nextstate)) {
+                Entry::Occupied(e) => *e.get(),
+                Entry::Vacant(e) => {
+                    queue.push((next_pos, tr.nextstate));
+                    *e.insert(ifst.add_state())
+                }
+            };
+            ifst.add_tr(
+                is,
+                Tr::new(EPS_LABEL, EPS_LABEL, tr.weight.clone(), next_is),
+            )?;
+        }
+    }
+
+    let distance = shortest_distance(&ifst, false)?;
+    let mut weight = W::zero();
+    for s in ifst.final_states_iter() {
+        let final_weight = unsafe { ifst.final_weight_unchecked(s).unwrap() };
+        weight.plus_assign(distance[s as usize].times(final_weight)?)?;
+    }
+    Ok(weight)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::semirings::{LogWeight, TropicalWeight};
+
+    #[test]
+    fn test_path_weight_nondeterministic() -> Result<()> {
+        let mut fst = VectorFst::<LogWeight>::new();
+        fst.add_states(4);
+        fst.set_start(0)?;
+        // Two paths spelling 1 2: 0 -1-> 1 -2-> 3 and 0 -eps-> 2 -1-> 1 -2-> 3.
+        fst.add_tr(0, Tr::new(1, 1, 1.0, 1))?;
+        fst.add_tr(0, Tr::new(EPS_LABEL, 5, 0.5, 2))?;
+        fst.add_tr(2, Tr::new(1, 2, 2.0, 1))?;
+        fst.add_tr(1, Tr::new(2, 3, 0.25, 3))?;
+        fst.add_tr(1, Tr::new(3, 3, 0.25, 3))?;
+        fst.set_final(3, 0.125)?;
+
+        let expected =
+            LogWeight::new(1.0 + 0.25 + 0.125).plus(LogWeight::new(0.5 + 2.0 + 0.25 + 0.125))?;
+        assert!(path_weight(&fst, &[1, 2])?.approx_equal(expected, 1e-5));
+        assert_eq!(path_weight(&fst, &[1])?, LogWeight::zero());
+        assert_eq!(path_weight(&fst, &[2, 1])?, LogWeight::zero());
+
+        let fst = VectorFst::<TropicalWeight>::new();
+        assert_eq!(path_weight(&fst, &[1])?, TropicalWeight::zero());
+
+        Ok(())
+    }
+}
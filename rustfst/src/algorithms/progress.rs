@@ -0,0 +1,105 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+
+/// Number of states processed between two calls of a [`ProgressCallback`](struct.ProgressCallback.html).
+pub const PROGRESS_REPORT_INTERVAL: usize = 1000;
+
+/// Value returned by a [`ProgressCallback`](struct.ProgressCallback.html) to tell the
+/// algorithm whether it should go on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ProgressAction {
+    Continue,
+    /// Stops the algorithm which then returns a [`Cancelled`](struct.Cancelled.html) error.
+    Abort,
+}
+
+/// Callback used to observe the progress of long-running algorithms. It is called with the
+/// number of states processed so far, every
+/// [`PROGRESS_REPORT_INTERVAL`](constant.PROGRESS_REPORT_INTERVAL.html) states.
+///
+/// The callback is a closure and can therefore capture state, for instance a counter or a
+/// cancellation flag shared with another thread. Two callbacks are compared by address so
+/// that the configurations holding them can still be compared.
+#[derive(Clone)]
+pub struct ProgressCallback(pub Arc<dyn Fn(usize) -> ProgressAction + Send + Sync>);
+
+impl ProgressCallback {
+    pub fn new<F: Fn(usize) -> ProgressAction + Send + Sync + 'static>(callback: F) -> Self {
+        Self(Arc::new(callback))
+    }
+
+    fn address(&self) -> usize {
+        Arc::as_ptr(&self.0) as *const () as usize
+    }
+}
+
+impl std::fmt::Debug for ProgressCallback {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ProgressCallback({:#x})", self.address())
+    }
+}
+
+impl PartialEq for ProgressCallback {
+    fn eq(&self, other: &Self) -> bool {
+        self.address() == other.address()
+    }
+}
+
+impl PartialOrd for ProgressCallback {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        self.address().partial_cmp(&other.address())
+    }
+}
+
+/// Error returned when an algorithm is aborted by its
+/// [`ProgressCallback`](struct.ProgressCallback.html). It can be retrieved by downcasting the
+/// returned `anyhow::Error`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Cancelled {
+    /// Number of states processed when the algorithm was aborted.
+    pub states_done: usize,
+}
+
+impl std::fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Cancelled by the progress callback after {} states",
+            self.states_done
+        )
+    }
+}
+
+impl std::error::Error for Cancelled {}
+
+/// Counts the states processed by an algorithm and calls the callback when needed.
+pub(crate) struct Progress {
+    callback: Option<ProgressCallback>,
+    states_done: usize,
+}
+
+impl Progress {
+    pub(crate) fn new(callback: Option<ProgressCallback>) -> Self {
+        Self {
+            callback,
+            states_done: 0,
+        }
+    }
+
+    /// To be called each time a state has been processed.
+    pub(crate) fn tick(&mut self) -> Result<()> {
+        self.states_done += 1;
+        if let Some(callback) = &self.callback {
+            if self.states_done % PROGRESS_REPORT_INTERVAL == 0
+                && (callback.0)(self.states_done) == ProgressAction::Abort
+            {
+                return Err(Cancelled {
+                    states_done: self.states_done,
+                }
+                .into());
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,327 @@
+use std::borrow::Borrow;
+use std::fmt::Debug;
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use anyhow::Result;
+
+use crate::algorithms::lazy::{FstOp2, LazyFst2, SimpleVecCache};
+use crate::algorithms::ProjectType;
+use crate::fst_properties::mutable_properties::project_properties;
+use crate::fst_properties::FstProperties;
+use crate::fst_traits::{CoreFst, Fst, FstIterator, MutableFst, StateIterator};
+use crate::semirings::Semiring;
+use crate::{StateId, SymbolTable, Trs, TrsVec};
+
+pub struct ProjectOp<W: Semiring, F: CoreFst<W>, B: Borrow<F>> {
+    fst: B,
+    project_type: ProjectType,
+    properties: FstProperties,
+    w: PhantomData<W>,
+    f: PhantomData<F>,
+}
+
+impl<W: Semiring, F: CoreFst<W>, B: Borrow<F>> Debug for ProjectOp<W, F, B> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "ProjectOp {{ project_type : {:?}, properties : {:?} }}",
+            self.project_type, self.properties
+        )
+    }
+}
+
+impl<W: Semiring, F: CoreFst<W>, B: Borrow<F>> ProjectOp<W, F, B> {
+    pub fn new(fst: B, project_type: ProjectType) -> Self {
+        let properties = project_properties(fst.borrow().properties(), project_type);
+        Self {
+            fst,
+            project_type,
+            properties,
+            w: PhantomData,
+            f: PhantomData,
+        }
+    }
+}
+
+impl<W: Semiring, F: CoreFst<W>, B: Borrow<F>> FstOp2<W> for ProjectOp<W, F, B> {
+    fn compute_start(&self) -> Result<Option<StateId>> {
+        Ok(self.fst.borrow().start())
+    }
+
+    fn compute_trs_and_final_weight(&self, state: StateId) -> Result<(TrsVec<W>, Option<W>)> {
+        let fst = self.fst.borrow();
+        let mut trs = fst.get_trs(state)?.trs().to_vec();
+        match self.project_type {
+            ProjectType::ProjectInput => trs.iter_mut().for_each(|tr| tr.olabel = tr.ilabel),
+            ProjectType::ProjectOutput => trs.iter_mut().for_each(|tr| tr.ilabel = tr.olabel),
+        }
+        Ok((TrsVec(Arc::new(trs)), fst.final_weight(state)?))
+    }
+
+    fn properties(&self) -> FstProperties {
+        self.properties
+    }
+}
+
+type InnerLazyFst<W, F, B> = LazyFst2<W, ProjectOp<W, F, B>, SimpleVecCache<W>>;
+
+/// Read-only view of an FST projected onto its domain or range, without modifying or
+/// copying the underlying FST. The labels of the trs of a state are rewritten the first
+/// time they are accessed and then cached. This is the delayed version of
+/// [`project`](fn.project.html), useful when the projection is only an input of another
+/// lazy operation such as a composition.
+///
+/// # Example
+/// ```
+/// # use rustfst::fst_impls::VectorFst;
+/// # use rustfst::fst_traits::{CoreFst, MutableFst};
+/// # use rustfst::semirings::{Semiring, TropicalWeight};
+/// # use rustfst::algorithms::{ProjectFst, ProjectType};
+/// # use rustfst::{Tr, Trs};
+/// # use anyhow::Result;
+/// # fn main() -> Result<()> {
+/// let mut fst = VectorFst::<TropicalWeight>::new();
+/// fst.add_states(2);
+/// fst.set_start(0)?;
+/// fst.set_final(1, TropicalWeight::one())?;
+/// fst.add_tr(0, Tr::new(1, 2, 1.0, 1))?;
+///
+/// let projected_fst = ProjectFst::<_, VectorFst<_>, _>::new(&fst, ProjectType::ProjectOutput);
+/// assert_eq!(projected_fst.get_trs(0)?.trs(), &[Tr::new(2, 2, 1.0, 1)]);
+/// # Ok(())
+/// # }
+/// ```
+pub struct ProjectFst<W: Semiring, F: CoreFst<W>, B: Borrow<F>>(InnerLazyFst<W, F, B>);
+
+impl<W, F, B> CoreFst<W> for ProjectFst<W, F, B>
+where
+    W: Semiring,
+    F: CoreFst<W>,
+    B: Borrow<F>,
+{
+    type TRS = TrsVec<W>;
+
+    fn start(&self) -> Option<StateId> {
+        self.0.start()
+    }
+
+    fn final_weight(&self, state_id: StateId) -> Result<Option<W>> {
+        self.0.final_weight(state_id)
+    }
+
+    unsafe fn final_weight_unchecked(&self, state_id: StateId) -> Option<W> {
+        self.0.final_weight_unchecked(state_id)
+    }
+
+    fn num_trs(&self, s: StateId) -> Result<usize> {
+        self.0.num_trs(s)
+    }
+
+    unsafe fn num_trs_unchecked(&self, s: StateId) -> usize {
+        self.0.num_trs_unchecked(s)
+    }
+
+    fn get_trs(&self, state_id: StateId) -> Result<Self::TRS> {
+        self.0.get_trs(state_id)
+    }
+
+    unsafe fn get_trs_unchecked(&self, state_id: StateId) -> Self::TRS {
+        self.0.get_trs_unchecked(state_id)
+    }
+
+    fn properties(&self) -> FstProperties {
+        self.0.properties()
+    }
+
+    fn num_input_epsilons(&self, state: StateId) -> Result<usize> {
+        self.0.num_input_epsilons(state)
+    }
+
+    fn num_output_epsilons(&self, state: StateId) -> Result<usize> {
+        self.0.num_output_epsilons(state)
+    }
+}
+
+impl<'a, W, F, B> StateIterator<'a> for ProjectFst<W, F, B>
+where
+    W: Semiring,
+    F: CoreFst<W> + 'a,
+    B: Borrow<F> + 'a,
+{
+    type Iter = <InnerLazyFst<W, F, B> as StateIterator<'a>>::Iter;
+
+    fn states_iter(&'a self) -> Self::Iter {
+        self.0.states_iter()
+    }
+}
+
+impl<'a, W, F, B> FstIterator<'a, W> for ProjectFst<W, F, B>
+where
+    W: Semiring,
+    F: CoreFst<W> + 'a,
+    B: Borrow<F> + 'a,
+{
+    type FstIter = <InnerLazyFst<W, F, B> as FstIterator<'a, W>>::FstIter;
+
+    fn fst_iter(&'a self) -> Self::FstIter {
+        self.0.fst_iter()
+    }
+}
+
+impl<W, F, B> Fst<W> for ProjectFst<W, F, B>
+where
+    W: Semiring,
+    F: CoreFst<W> + 'static,
+    B: Borrow<F> + 'static,
+{
+    fn input_symbols(&self) -> Option<&Arc<SymbolTable>> {
+        self.0.input_symbols()
+    }
+
+    fn output_symbols(&self) -> Option<&Arc<SymbolTable>> {
+        self.0.output_symbols()
+    }
+
+    fn set_input_symbols(&mut self, symt: Arc<SymbolTable>) {
+        self.0.set_input_symbols(symt)
+    }
+
+    fn set_output_symbols(&mut self, symt: Arc<SymbolTable>) {
+        self.0.set_output_symbols(symt)
+    }
+
+    fn take_input_symbols(&mut self) -> Option<Arc<SymbolTable>> {
+        self.0.take_input_symbols()
+    }
+
+    fn take_output_symbols(&mut self) -> Option<Arc<SymbolTable>> {
+        self.0.take_output_symbols()
+    }
+}
+
+impl<W, F, B> Debug for ProjectFst<W, F, B>
+where
+    W: Semiring,
+    F: CoreFst<W>,
+    B: Borrow<F>,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<W, F, B> ProjectFst<W, F, B>
+where
+    W: Semiring,
+    F: Fst<W>,
+    B: Borrow<F>,
+{
+    pub fn new(fst: B, project_type: ProjectType) -> Self {
+        let isymt = fst.borrow().input_symbols().cloned();
+        let osymt = fst.borrow().output_symbols().cloned();
+        let fst_op = ProjectOp::new(fst, project_type);
+        let fst_cache = SimpleVecCache::default();
+        ProjectFst(LazyFst2::from_op_and_cache(fst_op, fst_cache, isymt, osymt))
+    }
+
+    /// Turns the Lazy FST into a static one.
+    pub fn compute<F2: MutableFst<W>>(&self) -> Result<F2> {
+        self.0.compute()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::compose::{compose, ComposeFst};
+    use crate::algorithms::project;
+    use crate::fst_impls::VectorFst;
+    use crate::fst_traits::ExpandedFst;
+    use crate::semirings::TropicalWeight;
+    use crate::Tr;
+
+    #[test]
+    fn test_project_fst() -> Result<()> {
+        let mut fst = VectorFst::<TropicalWeight>::new();
+        fst.add_states(3);
+        fst.set_start(0)?;
+        fst.set_final(2, 0.5)?;
+        fst.add_tr(0, Tr::new(3, 1, 1.0, 1))?;
+        fst.add_tr(0, Tr::new(0, 2, 2.0, 2))?;
+        fst.add_tr(1, Tr::new(5, 0, 1.0, 2))?;
+        fst.add_tr(2, Tr::new(2, 1, 1.0, 0))?;
+        let fst_ref = fst.clone();
+
+        for &project_type in &[ProjectType::ProjectInput, ProjectType::ProjectOutput] {
+            let mut fst_projected = fst.clone();
+            project(&mut fst_projected, project_type);
+
+            let projected_fst = ProjectFst::<_, VectorFst<_>, _>::new(&fst, project_type);
+            for s in fst.states_range() {
+                assert_eq!(
+                    projected_fst.get_trs(s)?.trs(),
+                    fst_projected.get_trs(s)?.trs()
+                );
+                assert_eq!(
+                    projected_fst.num_input_epsilons(s)?,
+                    fst_projected.num_input_epsilons(s)?
+                );
+            }
+            assert!(projected_fst.properties().contains(FstProperties::ACCEPTOR));
+
+            let computed: VectorFst<_> = projected_fst.compute()?;
+            assert_eq!(computed, fst_projected);
+        }
+
+        // The underlying FST is untouched.
+        assert_eq!(fst, fst_ref);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_project_fst_compose() -> Result<()> {
+        let mut fst = VectorFst::<TropicalWeight>::new();
+        fst.add_states(3);
+        fst.set_start(0)?;
+        fst.set_final(2, 0.5)?;
+        fst.add_tr(0, Tr::new(3, 1, 1.0, 1))?;
+        fst.add_tr(0, Tr::new(0, 2, 2.0, 2))?;
+        fst.add_tr(1, Tr::new(5, 0, 1.0, 2))?;
+        fst.add_tr(2, Tr::new(2, 1, 1.0, 0))?;
+
+        let mut fst2 = VectorFst::<TropicalWeight>::new();
+        fst2.add_states(2);
+        fst2.set_start(0)?;
+        fst2.set_final(1, 1.5)?;
+        fst2.add_tr(0, Tr::new(1, 4, 1.0, 1))?;
+        fst2.add_tr(0, Tr::new(2, 5, 3.0, 1))?;
+        fst2.add_tr(0, Tr::new(3, 6, 0.5, 1))?;
+        fst2.add_tr(1, Tr::new(5, 7, 2.0, 0))?;
+
+        for &project_type in &[ProjectType::ProjectInput, ProjectType::ProjectOutput] {
+            let mut fst_projected = fst.clone();
+            project(&mut fst_projected, project_type);
+            let expected: VectorFst<_> = compose(fst_projected, fst2.clone())?;
+
+            // The projection is only computed for the states visited by the composition.
+            let projected_fst = ProjectFst::<_, VectorFst<_>, _>::new(fst.clone(), project_type);
+            let composed: VectorFst<_> = ComposeFst::<
+                _,
+                ProjectFst<_, VectorFst<_>, VectorFst<_>>,
+                VectorFst<_>,
+                &ProjectFst<_, VectorFst<_>, VectorFst<_>>,
+                &VectorFst<_>,
+                _,
+                _,
+                _,
+            >::new_auto(&projected_fst, &fst2)?
+            .compute()?;
+            assert_eq!(composed, expected);
+            assert!(composed.num_states() > 0);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,201 @@
+use anyhow::Result;
+
+use crate::algorithms::queues::natural_less;
+use crate::algorithms::{connect, fst_convert_from_ref, shortest_distance};
+use crate::fst_traits::{AllocableFst, ExpandedFst, MutableFst};
+use crate::semirings::{Semiring, SemiringProperties, WeightQuantize};
+use crate::StateId;
+
+/// Removes the states and transitions that are not on a successful path whose weight is
+/// within `weight_threshold` of the shortest path, i.e. whose weight `w` verifies
+/// `w <= shortest_path ⊗ weight_threshold` with respect to the natural order of the semiring.
+/// For the tropical semiring, this keeps the paths whose cost is at most the cost of the
+/// shortest path plus `weight_threshold`.
+///
+/// The distances from the start state and to the final states are computed with
+/// [`shortest_distance`](fn.shortest_distance.html). An FST without any successful path
+/// becomes empty.
+///
+/// # Example
+/// ```
+/// # use rustfst::fst_impls::VectorFst;
+/// # use rustfst::fst_traits::{CoreFst, ExpandedFst, MutableFst};
+/// # use rustfst::semirings::{Semiring, TropicalWeight};
+/// # use rustfst::algorithms::prune;
+/// # use rustfst::Tr;
+/// # use anyhow::Result;
+/// # fn main() -> Result<()> {
+/// let mut fst = VectorFst::<TropicalWeight>::new();
+/// fst.add_states(4);
+/// fst.set_start(0)?;
+/// fst.set_final(3, TropicalWeight::one())?;
+/// fst.add_tr(0, Tr::new(1, 1, 1.0, 1))?;
+/// fst.add_tr(0, Tr::new(2, 2, 5.0, 2))?;
+/// fst.add_tr(1, Tr::new(3, 3, 1.0, 3))?;
+/// fst.add_tr(2, Tr::new(3, 3, 1.0, 3))?;
+///
+/// prune(&mut fst, TropicalWeight::new(2.0))?;
+/// assert_eq!(fst.num_states(), 3);
+/// assert_eq!(fst.num_trs(0)?, 1);
+/// # Ok(())
+/// # }
+/// ```
+pub fn prune<W, F>(fst: &mut F, weight_threshold: W) -> Result<()>
+where
+    W: Semiring + WeightQuantize,
+    F: MutableFst<W>,
+{
+    if !W::properties().contains(SemiringProperties::PATH | SemiringProperties::SEMIRING) {
+        bail!("Prune: Weight needs to have the path property and be distributive");
+    }
+
+    let start = match fst.start() {
+        Some(s) => s,
+        None => return Ok(()),
+    };
+    let fdistance = shortest_distance(fst, false)?;
+    let rdistance = shortest_distance(fst, true)?;
+    let rdistance_start = match rdistance.get(start as usize) {
+        Some(d) if !d.is_zero() => d,
+        _ => {
+            // No successful path.
+            fst.del_all_states();
+            return Ok(());
+        }
+    };
+    let limit = rdistance_start.times(&weight_threshold)?;
+
+    let zero = W::zero();
+    let distance = |v: &Vec<W>, s: StateId| v.get(s as usize).cloned().unwrap_or_else(W::zero);
+    let mut dstates = vec![];
+    for s in 0..(fst.num_states() as StateId) {
+        let fd = distance(&fdistance, s);
+        if fd == zero || natural_less(&limit, &fd.times(distance(&rdistance, s))?)? {
+            dstates.push(s);
+            continue;
+        }
+        if let Some(final_weight) = fst.final_weight(s)? {
+            if natural_less(&limit, &fd.times(final_weight)?)? {
+                fst.delete_final_weight(s)?;
+            }
+        }
+        let trs = fst.pop_trs(s)?;
+        for tr in trs {
+            let weight = fd
+                .times(&tr.weight)?
+                .times(distance(&rdistance, tr.nextstate))?;
+            if !natural_less(&limit, &weight)? {
+                fst.add_tr(s, tr)?;
+            }
+        }
+    }
+    fst.del_states(dstates)?;
+    connect(fst)
+}
+
+/// Non-mutating version of [`prune`](fn.prune.html) : the pruned FST is returned and the
+/// input FST is left untouched.
+pub fn prune_fst<W, F1, F2>(ifst: &F1, weight_threshold: W) -> Result<F2>
+where
+    W: Semiring + WeightQuantize,
+    F1: ExpandedFst<W>,
+    F2: MutableFst<W> + AllocableFst<W>,
+{
+    let mut ofst: F2 = fst_convert_from_ref(ifst);
+    prune(&mut ofst, weight_threshold)?;
+    Ok(ofst)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::fst_impls::VectorFst;
+    use crate::fst_traits::{CoreFst, Fst};
+    use crate::semirings::TropicalWeight;
+    use crate::Tr;
+
+    type F = VectorFst<TropicalWeight>;
+
+    fn fst_with_three_paths() -> Result<F> {
+        // Paths of weight 2, 3.5 and 6.
+        let mut fst = F::new();
+        fst.add_states(5);
+        fst.set_start(0)?;
+        fst.set_final(3, TropicalWeight::one())?;
+        fst.set_final(4, 1.0)?;
+        fst.add_tr(0, Tr::new(1, 1, 1.0, 1))?;
+        fst.add_tr(0, Tr::new(2, 2, 5.0, 2))?;
+        fst.add_tr(1, Tr::new(3, 3, 1.0, 3))?;
+        fst.add_tr(1, Tr::new(4, 4, 1.5, 4))?;
+        fst.add_tr(2, Tr::new(3, 3, 1.0, 3))?;
+        Ok(fst)
+    }
+
+    fn path_weights(fst: &F) -> Vec<TropicalWeight> {
+        let mut weights: Vec<_> = fst.paths_iter().map(|p| p.weight).collect();
+        weights.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        weights
+    }
+
+    #[test]
+    fn test_prune_weight_threshold() -> Result<()> {
+        let fst = fst_with_three_paths()?;
+
+        let res: F = prune_fst(&fst, TropicalWeight::new(0.5))?;
+        assert_eq!(path_weights(&res), vec![TropicalWeight::new(2.0)]);
+        assert_eq!(res.num_states(), 3);
+
+        let res: F = prune_fst(&fst, TropicalWeight::new(1.5))?;
+        assert_eq!(
+            path_weights(&res),
+            vec![TropicalWeight::new(2.0), TropicalWeight::new(3.5)]
+        );
+        assert_eq!(res.num_states(), 4);
+
+        // The input is left untouched and a large threshold keeps everything.
+        let res: F = prune_fst(&fst, TropicalWeight::new(10.0))?;
+        assert_eq!(res, fst);
+
+        let mut fst_2 = fst.clone();
+        prune(&mut fst_2, TropicalWeight::one())?;
+        assert_eq!(path_weights(&fst_2), vec![TropicalWeight::new(2.0)]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_prune_cyclic() -> Result<()> {
+        let mut fst = F::new();
+        fst.add_states(3);
+        fst.set_start(0)?;
+        fst.set_final(2, TropicalWeight::one())?;
+        fst.add_tr(0, Tr::new(1, 1, 1.0, 1))?;
+        fst.add_tr(1, Tr::new(2, 2, 1.0, 1))?;
+        fst.add_tr(1, Tr::new(3, 3, 1.0, 2))?;
+        fst.add_tr(0, Tr::new(4, 4, 4.0, 2))?;
+
+        let res: F = prune_fst(&fst, TropicalWeight::new(1.0))?;
+        assert_eq!(res.num_states(), 3);
+        assert_eq!(res.num_trs(0)?, 1);
+        assert_eq!(res.num_trs(1)?, 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_prune_no_final_states() -> Result<()> {
+        let mut fst = F::new();
+        fst.add_states(2);
+        fst.set_start(0)?;
+        fst.add_tr(0, Tr::new(1, 1, 1.0, 1))?;
+        fst.add_tr(1, Tr::new(2, 2, 1.0, 0))?;
+
+        let res: F = prune_fst(&fst, TropicalWeight::new(10.0))?;
+        assert_eq!(res.num_states(), 0);
+        assert_eq!(res.start(), None);
+
+        let mut empty = F::new();
+        prune(&mut empty, TropicalWeight::one())?;
+        assert_eq!(empty.num_states(), 0);
+        Ok(())
+    }
+}
@@ -314,3 +314,78 @@ where
         Ok(fst_convert_from_ref(ifst))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::fst_traits::{CoreFst, Fst};
+    use crate::semirings::TropicalWeight;
+    use crate::{Label, Tr, EPS_LABEL};
+
+    type F = VectorFst<TropicalWeight>;
+
+    fn paths(fst: &F) -> Vec<(Vec<Label>, Vec<Label>, TropicalWeight)> {
+        let mut paths: Vec<_> = fst
+            .paths_iter()
+            .map(|p| (p.ilabels, p.olabels, p.weight))
+            .collect();
+        paths.sort_by(|a, b| a.0.cmp(&b.0));
+        paths
+    }
+
+    fn lexicon() -> Result<F> {
+        // 1:eps then either 2:3 or 4:3 4:5, the output label 3 is common to both paths.
+        let mut fst = F::new();
+        fst.add_states(4);
+        fst.set_start(0)?;
+        fst.set_final(2, TropicalWeight::one())?;
+        fst.add_tr(0, Tr::new(1, EPS_LABEL, 1.0, 1))?;
+        fst.add_tr(1, Tr::new(2, 3, 2.0, 2))?;
+        fst.add_tr(1, Tr::new(4, 3, 3.0, 3))?;
+        fst.add_tr(3, Tr::new(4, 5, 1.0, 2))?;
+        Ok(fst)
+    }
+
+    #[test]
+    fn test_push_labels_to_initial() -> Result<()> {
+        let fst = lexicon()?;
+        let pushed: F = push(&fst, ReweightType::ReweightToInitial, PushType::PUSH_LABELS)?;
+        assert_eq!(paths(&pushed), paths(&fst));
+
+        // The common output label is emitted by the first transition, the weights are kept.
+        let start = pushed.start().unwrap();
+        let trs = pushed.get_trs(start)?;
+        assert_eq!(trs.len(), 1);
+        assert_eq!(trs[0].olabel, 3);
+        assert_eq!(trs[0].weight, TropicalWeight::new(1.0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_push_labels_and_weights() -> Result<()> {
+        let fst = lexicon()?;
+        let push_type = PushType::PUSH_LABELS | PushType::PUSH_WEIGHTS;
+        let pushed: F = push(&fst, ReweightType::ReweightToInitial, push_type)?;
+        assert_eq!(paths(&pushed), paths(&fst));
+
+        let start = pushed.start().unwrap();
+        let trs = pushed.get_trs(start)?;
+        assert_eq!(trs.len(), 1);
+        assert_eq!(trs[0].olabel, 3);
+        assert_eq!(trs[0].weight, TropicalWeight::new(3.0));
+
+        // The total weight and the common prefix can be removed too.
+        let push_type = push_type | PushType::REMOVE_TOTAL_WEIGHT | PushType::REMOVE_COMMON_AFFIX;
+        let pushed: F = push(&fst, ReweightType::ReweightToInitial, push_type)?;
+        let paths = paths(&pushed);
+        assert_eq!(
+            paths,
+            vec![
+                (vec![1, 2], vec![], TropicalWeight::one()),
+                (vec![1, 4, 4], vec![5], TropicalWeight::new(2.0))
+            ]
+        );
+        Ok(())
+    }
+}
@@ -3,7 +3,7 @@ use std::fmt::Debug;
 use crate::StateId;
 
 /// Defines the different types of Queues usable.
-#[derive(PartialOrd, PartialEq, Clone)]
+#[derive(Debug, PartialOrd, PartialEq, Clone, Copy)]
 pub enum QueueType {
     /// Single state queue.
     TrivialQueue,
@@ -3,11 +3,34 @@ use std::collections::HashMap;
 
 use anyhow::{bail, format_err, Context, Result};
 
+use std::sync::Arc;
+
 use crate::fst_traits::MutableFst;
 use crate::semirings::Semiring;
-use crate::StateId;
+use crate::{Label, StateId, SymbolTable};
+
+/// Configuration for relabeling an FST with pairs of labels.
+#[derive(Clone, Debug, Copy, PartialOrd, PartialEq, Default)]
+pub struct RelabelPairsConfig {
+    /// Whether the attached `SymbolTable`s are relabeled too.
+    pub relabel_symbol_tables: bool,
+}
 
-fn iterator_to_hashmap<I>(pairs: I) -> Result<HashMap<StateId, StateId>>
+impl RelabelPairsConfig {
+    pub fn new(relabel_symbol_tables: bool) -> Self {
+        Self {
+            relabel_symbol_tables,
+        }
+    }
+
+    pub fn with_relabel_symbol_tables(self, relabel_symbol_tables: bool) -> Self {
+        Self {
+            relabel_symbol_tables,
+        }
+    }
+}
+
+pub(crate) fn iterator_to_hashmap<I>(pairs: I) -> Result<HashMap<StateId, StateId>>
 where
     I: IntoIterator<Item = (StateId, StateId)>,
 {
@@ -27,7 +50,8 @@ where
 ///
 /// This operation destructively relabels the input and/or output labels of the
 /// FST using pairs of the form (old_ID, new_ID); omitted indices are
-/// identity-mapped.
+/// identity-mapped. Several labels can be mapped to the same label, the transitions
+/// are left untouched otherwise. The attached `SymbolTable`s are not modified.
 ///
 /// # Example
 /// ```
@@ -49,8 +73,30 @@ pub fn relabel_pairs<W, F, I, J>(fst: &mut F, ipairs: I, opairs: J) -> Result<()
 where
     W: Semiring,
     F: MutableFst<W>,
-    I: IntoIterator<Item = (StateId, StateId)>,
-    J: IntoIterator<Item = (StateId, StateId)>,
+    I: IntoIterator<Item = (Label, Label)>,
+    J: IntoIterator<Item = (Label, Label)>,
+{
+    relabel_pairs_with_config(fst, ipairs, opairs, RelabelPairsConfig::default())
+}
+
+/// Replace input and/or output labels using pairs of labels, optionally relabeling the
+/// attached `SymbolTable`s.
+///
+/// When `relabel_symbol_tables` is set, the symbol of each label of a `SymbolTable` is moved
+/// to its new label. As a `SymbolTable` can't have holes nor associate a label to several
+/// symbols, an error is returned if two symbols are mapped to the same label or if a label
+/// lower than the biggest new label has no symbol. The FST isn't modified in that case.
+pub fn relabel_pairs_with_config<W, F, I, J>(
+    fst: &mut F,
+    ipairs: I,
+    opairs: J,
+    config: RelabelPairsConfig,
+) -> Result<()>
+where
+    W: Semiring,
+    F: MutableFst<W>,
+    I: IntoIterator<Item = (Label, Label)>,
+    J: IntoIterator<Item = (Label, Label)>,
 {
     let map_ilabels = iterator_to_hashmap(ipairs)
         .with_context(|| format_err!("Error while creating the HashMap for ipairs"))?;
@@ -58,6 +104,22 @@ where
     let map_olabels = iterator_to_hashmap(opairs)
         .with_context(|| format_err!("Error while creating the HashMap for opairs"))?;
 
+    let (isymt, osymt) = if config.relabel_symbol_tables {
+        let isymt = fst
+            .input_symbols()
+            .map(|symt| relabel_symbol_table(symt, &map_ilabels))
+            .transpose()
+            .with_context(|| format_err!("Error while relabeling the input SymbolTable"))?;
+        let osymt = fst
+            .output_symbols()
+            .map(|symt| relabel_symbol_table(symt, &map_olabels))
+            .transpose()
+            .with_context(|| format_err!("Error while relabeling the output SymbolTable"))?;
+        (isymt, osymt)
+    } else {
+        (None, None)
+    };
+
     for state_id in fst.states_range() {
         unsafe {
             let mut it_tr = fst.tr_iter_unchecked_mut(state_id);
@@ -75,14 +137,49 @@ where
             }
         }
     }
+
+    if let Some(isymt) = isymt {
+        fst.set_input_symbols(Arc::new(isymt));
+    }
+    if let Some(osymt) = osymt {
+        fst.set_output_symbols(Arc::new(osymt));
+    }
     Ok(())
 }
 
+fn relabel_symbol_table(symt: &SymbolTable, map: &HashMap<Label, Label>) -> Result<SymbolTable> {
+    let mut symbols: Vec<Option<&str>> = vec![];
+    for (label, symbol) in symt.iter() {
+        let new_label = map.get(&label).cloned().unwrap_or(label) as usize;
+        if symbols.len() <= new_label {
+            symbols.resize(new_label + 1, None);
+        }
+        if let Some(other) = symbols[new_label] {
+            bail!(
+                "Symbols {:?} and {:?} are both mapped to label {}",
+                other,
+                symbol,
+                new_label
+            );
+        }
+        symbols[new_label] = Some(symbol);
+    }
+
+    let mut new_symt = SymbolTable::empty();
+    for (label, symbol) in symbols.into_iter().enumerate() {
+        let symbol = symbol.ok_or_else(|| format_err!("No symbol is mapped to label {}", label))?;
+        new_symt.add_symbol(symbol);
+    }
+    Ok(new_symt)
+}
+
 #[cfg(test)]
 mod tests {
     use crate::fst_impls::VectorFst;
+    use crate::fst_traits::{CoreFst, Fst};
     use crate::semirings::{IntegerWeight, Semiring};
     use crate::tr::Tr;
+    use crate::Trs;
 
     use super::*;
 
@@ -125,4 +222,65 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_relabel_pairs_same_new_label() -> Result<()> {
+        let mut fst = VectorFst::<IntegerWeight>::new();
+        let s0 = fst.add_state();
+        let s1 = fst.add_state();
+        fst.set_start(s0)?;
+        fst.set_final(s1, 1)?;
+        fst.add_tr(s0, Tr::new(1, 1, 2, s1))?;
+        fst.add_tr(s0, Tr::new(2, 2, 3, s1))?;
+
+        relabel_pairs(&mut fst, vec![(1, 3), (2, 3)], vec![(2, 1)])?;
+        let trs = fst.get_trs(s0)?;
+        assert_eq!(
+            trs.trs(),
+            &[
+                Tr::new(3, 1, IntegerWeight::new(2), s1),
+                Tr::new(3, 1, IntegerWeight::new(3), s1)
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_relabel_pairs_symbol_tables() -> Result<()> {
+        let mut symt = SymbolTable::new();
+        symt.add_symbols(vec!["a", "b", "c"]);
+        let symt = Arc::new(symt);
+
+        let mut fst = VectorFst::<IntegerWeight>::new();
+        let s0 = fst.add_state();
+        fst.set_start(s0)?;
+        fst.add_tr(s0, Tr::new(1, 3, 1, s0))?;
+        fst.set_input_symbols(Arc::clone(&symt));
+        fst.set_output_symbols(Arc::clone(&symt));
+
+        // Without the option, the SymbolTables are kept.
+        let mut fst_1 = fst.clone();
+        relabel_pairs(&mut fst_1, vec![(1, 3), (3, 1)], vec![])?;
+        assert_eq!(fst_1.input_symbols(), Some(&symt));
+
+        // Swap a and c on the input side.
+        let config = RelabelPairsConfig::default().with_relabel_symbol_tables(true);
+        relabel_pairs_with_config(&mut fst, vec![(1, 3), (3, 1)], vec![], config)?;
+        let trs = fst.get_trs(s0)?;
+        let tr = &trs.trs()[0];
+        let isymt = fst.input_symbols().unwrap();
+        let osymt = fst.output_symbols().unwrap();
+        assert_eq!(isymt.get_symbol(tr.ilabel), Some("a"));
+        assert_eq!(osymt.get_symbol(tr.olabel), Some("c"));
+        assert_eq!(isymt.get_symbol(1), Some("c"));
+        assert_eq!(osymt, &symt);
+
+        // Two symbols can't share a label and the SymbolTables can't have holes.
+        let before = fst.clone();
+        assert!(relabel_pairs_with_config(&mut fst, vec![(1, 2)], vec![], config).is_err());
+        assert!(relabel_pairs_with_config(&mut fst, vec![], vec![(3, 5)], config).is_err());
+        assert_eq!(fst, before);
+        assert_eq!(fst.input_symbols(), before.input_symbols());
+        Ok(())
+    }
 }
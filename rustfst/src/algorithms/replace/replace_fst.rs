@@ -1,4 +1,5 @@
 use std::borrow::Borrow;
+use std::collections::HashSet;
 use std::fmt::Debug;
 use std::sync::Arc;
 
@@ -10,13 +11,19 @@ use crate::algorithms::replace::replace_fst_op::ReplaceFstOp;
 use crate::fst_properties::FstProperties;
 use crate::fst_traits::{AllocableFst, CoreFst, Fst, FstIterator, MutableFst, StateIterator};
 use crate::semirings::Semiring;
-use crate::{Label, StateId, SymbolTable, TrsVec};
+use crate::{Label, StateId, SymbolTable, Trs, TrsVec};
 
 type InnerLazyFst<W, F, B> = LazyFst<W, ReplaceFstOp<W, F, B>, SimpleHashMapCache<W>>;
 
 /// ReplaceFst supports lazy replacement of trs in one FST with another FST.
 /// This replacement is recursive. ReplaceFst can be used to support a variety of
 /// delayed constructions such as recursive transition networks, union, or closure.
+///
+/// When the non-terminals have cyclic dependencies (e.g a non-terminal calling itself), the
+/// replaced FST has an infinite number of states. It can then only be accessed lazily,
+/// state by state from the start state : `compute` returns an error and iterating over all
+/// the states never ends. Use [`states_up_to_depth`](#method.states_up_to_depth) to explore
+/// a bounded part of the FST.
 pub struct ReplaceFst<W: Semiring, F: Fst<W>, B: Borrow<F>>(InnerLazyFst<W, F, B>);
 
 impl<W, F, B> ReplaceFst<W, F, B>
@@ -40,10 +47,55 @@ where
         )))
     }
 
+    /// Returns true if the non-terminals reachable from the root have cyclic dependencies,
+    /// in which case the replaced FST is infinite and can only be accessed lazily.
+    pub fn cyclic_dependencies(&self) -> bool {
+        self.0.op.cyclic_dependencies()
+    }
+
     /// Turns the Lazy FST into a static one.
+    ///
+    /// An error is returned if the non-terminals have cyclic dependencies as the
+    /// replaced FST would be infinite.
     pub fn compute<F2: MutableFst<W> + AllocableFst<W>>(&self) -> Result<F2> {
+        if self.cyclic_dependencies() {
+            bail!(
+                "ReplaceFst : The non-terminals have cyclic dependencies, the replaced FST is \
+                 infinite and can only be accessed lazily"
+            );
+        }
         self.0.compute()
     }
+
+    /// Returns the states reachable from the start state with at most `max_depth`
+    /// transitions, in breadth-first order. Only those states get expanded, which makes it
+    /// possible to explore an FST with cyclic dependencies.
+    pub fn states_up_to_depth(&self, max_depth: usize) -> Result<Vec<StateId>> {
+        let start = match self.0.start() {
+            Some(s) => s,
+            None => return Ok(vec![]),
+        };
+        let mut visited = HashSet::new();
+        visited.insert(start);
+        let mut states = vec![start];
+        let mut frontier = vec![start];
+        for _ in 0..max_depth {
+            let mut next_frontier = vec![];
+            for s in frontier {
+                for tr in self.0.get_trs(s)?.trs() {
+                    if visited.insert(tr.nextstate) {
+                        states.push(tr.nextstate);
+                        next_frontier.push(tr.nextstate);
+                    }
+                }
+            }
+            if next_frontier.is_empty() {
+                break;
+            }
+            frontier = next_frontier;
+        }
+        Ok(states)
+    }
 }
 
 impl<W, F, B> CoreFst<W> for ReplaceFst<W, F, B>
@@ -165,8 +217,10 @@ where
 
 #[cfg(test)]
 mod test {
+    use crate::algorithms::replace::replace;
     use crate::fst_impls::VectorFst;
     use crate::semirings::TropicalWeight;
+    use crate::Tr;
 
     use super::*;
 
@@ -175,4 +229,56 @@ mod test {
         fn is_sync<T: Sync>() {}
         is_sync::<ReplaceFst<TropicalWeight, VectorFst<_>, VectorFst<_>>>();
     }
+
+    #[test]
+    fn test_replace_fst_cyclic_dependencies() -> Result<()> {
+        // S -> a S b | c : the language a^n c b^n is not regular.
+        let mut fst_s = VectorFst::<TropicalWeight>::new();
+        fst_s.add_states(4);
+        fst_s.set_start(0)?;
+        fst_s.set_final(3, TropicalWeight::one())?;
+        fst_s.add_tr(0, Tr::new(1, 1, TropicalWeight::one(), 1))?;
+        fst_s.add_tr(1, Tr::new(0, 10, TropicalWeight::one(), 2))?;
+        fst_s.add_tr(2, Tr::new(2, 2, TropicalWeight::one(), 3))?;
+        fst_s.add_tr(0, Tr::new(3, 3, TropicalWeight::one(), 3))?;
+
+        let fst_list = vec![(10, fst_s.clone())];
+        let replaced = ReplaceFst::<_, VectorFst<_>, _>::new(fst_list.clone(), 10, true)?;
+        assert!(replaced.cyclic_dependencies());
+        assert!(replaced.compute::<VectorFst<_>>().is_err());
+        assert!(replace::<_, VectorFst<_>, VectorFst<_>, _>(fst_list, 10, true).is_err());
+
+        // Each call to S adds a new level to the stack, and thus new states.
+        let states_3 = replaced.states_up_to_depth(3)?;
+        let states_6 = replaced.states_up_to_depth(6)?;
+        assert!(states_3.len() < states_6.len());
+        assert_eq!(states_6[..states_3.len()], states_3[..]);
+
+        // Following "a" twice then "c" leads to a state from which "b b" is final.
+        let mut s = replaced.start().unwrap();
+        for label in &[1, 0, 1, 0, 3, 0, 2, 0, 2] {
+            let trs = replaced.get_trs(s)?;
+            let tr = trs.trs().iter().find(|tr| tr.ilabel == *label).unwrap();
+            s = tr.nextstate;
+        }
+        assert!(replaced.is_final(s)?);
+
+        // No cycle : the root calls S once.
+        let mut fst_root = VectorFst::<TropicalWeight>::new();
+        fst_root.add_states(2);
+        fst_root.set_start(0)?;
+        fst_root.set_final(1, TropicalWeight::one())?;
+        fst_root.add_tr(0, Tr::new(0, 11, TropicalWeight::one(), 1))?;
+        let mut fst_t = VectorFst::<TropicalWeight>::new();
+        fst_t.add_states(2);
+        fst_t.set_start(0)?;
+        fst_t.set_final(1, TropicalWeight::one())?;
+        fst_t.add_tr(0, Tr::new(3, 3, TropicalWeight::one(), 1))?;
+        let replaced =
+            ReplaceFst::<_, VectorFst<_>, _>::new(vec![(10, fst_root), (11, fst_t)], 10, true)?;
+        assert!(!replaced.cyclic_dependencies());
+        assert!(replaced.compute::<VectorFst<_>>().is_ok());
+
+        Ok(())
+    }
 }
@@ -29,6 +29,7 @@ pub struct ReplaceFstOp<W: Semiring, F: Fst<W>, B: Borrow<F>> {
     root: Label,
     state_table: ReplaceStateTable,
     properties: FstProperties,
+    cyclic_dependencies: bool,
     fst_type: PhantomData<F>,
     w: PhantomData<W>,
 }
@@ -220,6 +221,7 @@ impl<W: Semiring, F: Fst<W>, B: Borrow<F>> ReplaceFstOp<W, F, B> {
             root: 0,
             state_table: ReplaceStateTable::new(),
             properties,
+            cyclic_dependencies: false,
             fst_type: PhantomData,
             w: PhantomData,
         };
@@ -252,9 +254,62 @@ impl<W: Semiring, F: Fst<W>, B: Borrow<F>> ReplaceFstOp<W, F, B> {
             }
         };
 
+        replace_fst_impl.cyclic_dependencies = replace_fst_impl.compute_cyclic_dependencies()?;
+
         Ok(replace_fst_impl)
     }
 
+    /// Returns true if a non-terminal reachable from the root can (indirectly) call itself.
+    pub fn cyclic_dependencies(&self) -> bool {
+        self.cyclic_dependencies
+    }
+
+    fn compute_cyclic_dependencies(&self) -> Result<bool> {
+        // Dependency graph between the FSTs : an edge i -> j means that the FST i contains
+        // a transition calling the non-terminal of the FST j.
+        let mut deps = Vec::with_capacity(self.fst_array.len());
+        for fst in self.fst_array.iter() {
+            let fst = fst.borrow();
+            let mut fst_deps = BTreeSet::new();
+            for s in fst.states_iter() {
+                for tr in fst.get_trs(s)?.trs() {
+                    if tr.olabel == EPS_LABEL {
+                        continue;
+                    }
+                    if let Some(nonterminal) = self.nonterminal_hash.get(&tr.olabel) {
+                        fst_deps.insert(*nonterminal as usize);
+                    }
+                }
+            }
+            deps.push(fst_deps);
+        }
+
+        // Iterative DFS from the root looking for a back edge.
+        let mut on_stack = vec![false; deps.len()];
+        let mut visited = vec![false; deps.len()];
+        let root = self.root as usize;
+        let mut stack = vec![(root, deps[root].iter())];
+        visited[root] = true;
+        on_stack[root] = true;
+        while let Some((n, it)) = stack.last_mut() {
+            let n = *n;
+            if let Some(&m) = it.next() {
+                if on_stack[m] {
+                    return Ok(true);
+                }
+                if !visited[m] {
+                    visited[m] = true;
+                    on_stack[m] = true;
+                    stack.push((m, deps[m].iter()));
+                }
+            } else {
+                on_stack[n] = false;
+                stack.pop();
+            }
+        }
+        Ok(false)
+    }
+
     fn compute_final_tr(&self, state: StateId) -> Option<Tr<W>> {
         let tuple = self.state_table.tuple_table.find_tuple(state);
         let fst_state = tuple.fst_state?;
@@ -20,6 +20,9 @@ use crate::Label;
 /// Note that input argument is a vector of pairs. These correspond to the tuple
 /// of non-terminal Label and corresponding FST.
 ///
+/// An error is returned if the non-terminals have cyclic dependencies as the result would
+/// be infinite. Such grammars can be expanded lazily with [`ReplaceFst`](struct.ReplaceFst.html).
+///
 /// # Example
 ///
 /// ## Root Fst
@@ -1,14 +1,14 @@
+use crate::algorithms::progress::ProgressCallback;
 use crate::algorithms::shortest_distance::ShortestDistanceInternalConfig;
 use crate::algorithms::tr_filters::EpsilonTrFilter;
-use crate::algorithms::Queue;
+use crate::algorithms::{Queue, QueueType};
 use crate::semirings::Semiring;
 use crate::{StateId, KSHORTESTDELTA};
 
 pub(crate) struct RmEpsilonInternalConfig<W: Semiring, Q: Queue> {
     pub(crate) sd_opts: ShortestDistanceInternalConfig<W, Q, EpsilonTrFilter>,
     pub connect: bool,
-    pub weight_threshold: W,
-    pub state_threshold: Option<StateId>,
+    pub progress: Option<ProgressCallback>,
 }
 
 impl<W: Semiring, Q: Queue> RmEpsilonInternalConfig<W, Q> {
@@ -19,15 +19,14 @@ impl<W: Semiring, Q: Queue> RmEpsilonInternalConfig<W, Q> {
         state_threshold: Option<StateId>,
         delta: f32,
     ) -> Self {
+        let mut sd_opts =
+            ShortestDistanceInternalConfig::new_with_default(EpsilonTrFilter {}, queue, delta);
+        sd_opts.weight_threshold = weight_threshold;
+        sd_opts.state_threshold = state_threshold;
         Self {
-            sd_opts: ShortestDistanceInternalConfig::new_with_default(
-                EpsilonTrFilter {},
-                queue,
-                delta,
-            ),
+            sd_opts,
             connect,
-            weight_threshold,
-            state_threshold,
+            progress: None,
         }
     }
 
@@ -35,3 +34,96 @@ impl<W: Semiring, Q: Queue> RmEpsilonInternalConfig<W, Q> {
         Self::new(queue, true, W::zero(), None, KSHORTESTDELTA)
     }
 }
+
+/// Side of the transitions considered by [`rm_epsilon_with_config`](fn.rm_epsilon_with_config.html).
+#[derive(Clone, Debug, Copy, PartialOrd, PartialEq, Default)]
+pub enum EpsilonSide {
+    /// Removes the transitions with an epsilon input label. The output labels of such
+    /// transitions are delayed onto the next transitions of the path.
+    Input,
+    /// Removes the transitions with an epsilon output label. The input labels of such
+    /// transitions are delayed onto the next transitions of the path.
+    Output,
+    /// Removes the transitions with an epsilon on both sides. This is what
+    /// [`rm_epsilon`](fn.rm_epsilon.html) does.
+    #[default]
+    Both,
+}
+
+/// Configuration for [`rm_epsilon_with_config`](fn.rm_epsilon_with_config.html).
+#[derive(Clone, Debug, PartialOrd, PartialEq)]
+pub struct RmEpsilonConfig<W: Semiring> {
+    /// Which epsilon transitions are removed.
+    pub side: EpsilonSide,
+    /// If set, called regularly with the number of states processed so far. The epsilon
+    /// removal fails with a `Cancelled` error if it returns `ProgressAction::Abort`. The
+    /// FST is then left half-processed : it is equivalent to the input but only some of its
+    /// epsilon transitions have been removed, it is not connected and its properties are
+    /// reset.
+    pub progress: Option<ProgressCallback>,
+    /// Queue discipline used to compute the epsilon-closures. Supported values are
+    /// `AutoQueue` (the default), `FifoQueue`, `LifoQueue`, `StateOrderQueue` and
+    /// `TopOrderQueue`, the latter requiring the epsilon transitions to be acyclic.
+    pub queue_type: QueueType,
+    /// Whether the states that are no longer accessible or coaccessible are removed.
+    pub connect: bool,
+    /// If not zero, the epsilon-closure of a state only follows the epsilon paths whose weight
+    /// is lower than or equal to `weight_threshold` with respect to the natural order, i.e. at
+    /// most `weight_threshold` for the tropical semiring. Requires the path property.
+    pub weight_threshold: W,
+    /// If set, the epsilon-closure of a state reaches at most this number of states, the state
+    /// itself included. The states are reached in the order of the queue.
+    pub state_threshold: Option<StateId>,
+}
+
+impl<W: Semiring> Default for RmEpsilonConfig<W> {
+    fn default() -> Self {
+        Self {
+            side: EpsilonSide::default(),
+            progress: None,
+            queue_type: QueueType::AutoQueue,
+            connect: true,
+            weight_threshold: W::zero(),
+            state_threshold: None,
+        }
+    }
+}
+
+impl<W: Semiring> RmEpsilonConfig<W> {
+    pub fn new(side: EpsilonSide) -> Self {
+        Self {
+            side,
+            ..Self::default()
+        }
+    }
+
+    pub fn with_side(self, side: EpsilonSide) -> Self {
+        Self { side, ..self }
+    }
+
+    pub fn with_progress(self, progress: Option<ProgressCallback>) -> Self {
+        Self { progress, ..self }
+    }
+
+    pub fn with_queue_type(self, queue_type: QueueType) -> Self {
+        Self { queue_type, ..self }
+    }
+
+    pub fn with_connect(self, connect: bool) -> Self {
+        Self { connect, ..self }
+    }
+
+    pub fn with_weight_threshold(self, weight_threshold: W) -> Self {
+        Self {
+            weight_threshold,
+            ..self
+        }
+    }
+
+    pub fn with_state_threshold(self, state_threshold: Option<StateId>) -> Self {
+        Self {
+            state_threshold,
+            ..self
+        }
+    }
+}
@@ -6,7 +6,8 @@ mod rm_epsilon_state;
 mod rm_epsilon_static;
 
 pub(crate) use config::RmEpsilonInternalConfig;
+pub use config::{EpsilonSide, RmEpsilonConfig};
 pub(self) use element::Element;
 pub use rm_epsilon_fst::RmEpsilonFst;
 pub(self) use rm_epsilon_state::RmEpsilonState;
-pub use rm_epsilon_static::rm_epsilon;
+pub use rm_epsilon_static::{rm_epsilon, rm_epsilon_with_config};
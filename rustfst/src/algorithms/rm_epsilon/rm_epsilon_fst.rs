@@ -156,3 +156,69 @@ where
         self.0.compute()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use proptest::prelude::any;
+    use proptest::proptest;
+
+    use crate::algorithms::rm_epsilon::rm_epsilon;
+    use crate::algorithms::{connect, isomorphic, shortest_distance};
+    use crate::fst_impls::VectorFst;
+    use crate::fst_traits::ExpandedFst;
+    use crate::semirings::TropicalWeight;
+    use crate::{Tr, EPS_LABEL, KDELTA};
+
+    /// Removes the epsilons both statically and lazily. The lazy version is connected to be
+    /// comparable with the static one.
+    fn rm_epsilon_static_lazy(
+        fst: &VectorFst<TropicalWeight>,
+    ) -> Result<(VectorFst<TropicalWeight>, VectorFst<TropicalWeight>)> {
+        let mut fst_static = fst.clone();
+        rm_epsilon(&mut fst_static)?;
+
+        let mut fst_lazy: VectorFst<_> = RmEpsilonFst::new(fst.clone())?.compute()?;
+        connect(&mut fst_lazy)?;
+        Ok((fst_static, fst_lazy))
+    }
+
+    #[test]
+    fn test_rm_epsilon_fst_static_equality() -> Result<()> {
+        let mut fst = VectorFst::<TropicalWeight>::new();
+        fst.add_states(5);
+        fst.set_start(0)?;
+        fst.set_final(4, 0.5)?;
+        fst.add_tr(0, Tr::new(EPS_LABEL, EPS_LABEL, 1.0, 1))?;
+        fst.add_tr(1, Tr::new(EPS_LABEL, EPS_LABEL, 0.5, 0))?;
+        fst.add_tr(1, Tr::new(1, 1, 1.0, 2))?;
+        fst.add_tr(0, Tr::new(2, 3, 5.0, 3))?;
+        fst.add_tr(2, Tr::new(EPS_LABEL, EPS_LABEL, 1.0, 4))?;
+        fst.add_tr(3, Tr::new(EPS_LABEL, EPS_LABEL, 1.0, 4))?;
+        fst.add_tr(3, Tr::new(3, 3, 2.0, 4))?;
+
+        let fst_lazy = RmEpsilonFst::new(fst.clone())?;
+        let start = fst_lazy.start().unwrap();
+        assert_eq!(fst_lazy.num_trs(start)?, 2);
+
+        let (fst_static, fst_lazy) = rm_epsilon_static_lazy(&fst)?;
+        assert!(isomorphic(&fst_static, &fst_lazy)?);
+        Ok(())
+    }
+
+    proptest! {
+        #[test]
+        fn test_proptest_rm_epsilon_fst_static_lazy(fst in any::<VectorFst::<TropicalWeight>>()) {
+            let (fst_static, fst_lazy) = rm_epsilon_static_lazy(&fst).unwrap();
+            assert_eq!(fst_static.num_states(), fst_lazy.num_states());
+            if let (Some(start_static), Some(start_lazy)) = (fst_static.start(), fst_lazy.start()) {
+                // Same total weight.
+                let d_static = shortest_distance(&fst_static, true).unwrap();
+                let d_lazy = shortest_distance(&fst_lazy, true).unwrap();
+                assert!(d_static[start_static as usize]
+                    .approx_equal(d_lazy[start_lazy as usize], KDELTA));
+            }
+        }
+    }
+}
@@ -68,6 +68,10 @@ impl<W: Semiring, Q: Queue> RmEpsilonState<W, Q> {
                 let mut tr = tr.clone();
                 tr.weight = distance[state as usize].times(&tr.weight)?;
                 if tr_filter.keep(&tr) {
+                    // The states beyond the thresholds of the config are not in the closure.
+                    if !self.sd_state.is_reached(tr.nextstate) {
+                        continue;
+                    }
                     while self.visited.len() <= (tr.nextstate as usize) {
                         self.visited.push(false);
                     }
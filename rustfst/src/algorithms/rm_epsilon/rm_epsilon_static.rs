@@ -1,17 +1,22 @@
+use std::collections::{HashMap, VecDeque};
+
 use anyhow::Result;
 
 use crate::algorithms::dfs_visit::dfs_visit;
-use crate::algorithms::queues::AutoQueue;
-use crate::algorithms::rm_epsilon::{RmEpsilonInternalConfig, RmEpsilonState};
+use crate::algorithms::progress::Progress;
+use crate::algorithms::queues::{AutoQueue, FifoQueue, LifoQueue, StateOrderQueue, TopOrderQueue};
+use crate::algorithms::rm_epsilon::{
+    EpsilonSide, RmEpsilonConfig, RmEpsilonInternalConfig, RmEpsilonState,
+};
 use crate::algorithms::top_sort::TopOrderVisitor;
 use crate::algorithms::tr_filters::EpsilonTrFilter;
 use crate::algorithms::visitors::SccVisitor;
-use crate::algorithms::Queue;
+use crate::algorithms::{Queue, QueueType};
 use crate::fst_properties::mutable_properties::rmepsilon_properties;
 use crate::fst_properties::FstProperties;
 use crate::fst_traits::MutableFst;
 use crate::semirings::Semiring;
-use crate::{StateId, Trs, EPS_LABEL};
+use crate::{Label, StateId, Tr, Trs, EPS_LABEL, KSHORTESTDELTA};
 
 /// This operation removes epsilon-transitions (when both the input and
 /// output labels are an epsilon) from a transducer. The result will be an
@@ -63,18 +68,269 @@ use crate::{StateId, Trs, EPS_LABEL};
 /// ![rmepsilon_out](https://raw.githubusercontent.com/Garvys/rustfst-images-doc/master/images/rmepsilon_out.svg?sanitize=true)
 ///
 pub fn rm_epsilon<W: Semiring, F: MutableFst<W>>(fst: &mut F) -> Result<()> {
+    rm_epsilon_both(fst, &RmEpsilonConfig::default())
+}
+
+/// Removes the transitions with an epsilon on both sides using the queue, connect and
+/// pruning options of the config.
+fn rm_epsilon_both<W: Semiring, F: MutableFst<W>>(
+    fst: &mut F,
+    config: &RmEpsilonConfig<W>,
+) -> Result<()> {
     let tr_filter = EpsilonTrFilter {};
-    let queue = AutoQueue::new(fst, None, &tr_filter)?;
-    let opts = RmEpsilonInternalConfig::new_with_default(queue);
+    match config.queue_type {
+        QueueType::AutoQueue => {
+            let queue = AutoQueue::new(fst, None, &tr_filter)?;
+            rm_epsilon_with_queue(fst, queue, config)
+        }
+        QueueType::FifoQueue => rm_epsilon_with_queue(fst, FifoQueue::default(), config),
+        QueueType::LifoQueue => rm_epsilon_with_queue(fst, LifoQueue::default(), config),
+        QueueType::StateOrderQueue => {
+            rm_epsilon_with_queue(fst, StateOrderQueue::default(), config)
+        }
+        QueueType::TopOrderQueue => {
+            let mut visitor = TopOrderVisitor::new();
+            dfs_visit(fst, &mut visitor, &tr_filter, false);
+            if !visitor.acyclic {
+                bail!("RmEpsilon : TopOrderQueue requires the epsilon transitions to be acyclic");
+            }
+            let queue = TopOrderQueue::from_precomputed_order(visitor.order);
+            rm_epsilon_with_queue(fst, queue, config)
+        }
+        queue_type => bail!("RmEpsilon : Unsupported queue type {:?}", queue_type),
+    }
+}
+
+fn rm_epsilon_with_queue<W: Semiring, F: MutableFst<W>, Q: Queue>(
+    fst: &mut F,
+    queue: Q,
+    config: &RmEpsilonConfig<W>,
+) -> Result<()> {
+    let mut opts = RmEpsilonInternalConfig::new(
+        queue,
+        config.connect,
+        config.weight_threshold.clone(),
+        config.state_threshold,
+        KSHORTESTDELTA,
+    );
+    opts.progress = config.progress.clone();
     rm_epsilon_with_internal_config(fst, opts)
 }
+/// Same as [`rm_epsilon`](fn.rm_epsilon.html) but `config.side` selects which epsilon
+/// transitions are removed. The config also selects the queue discipline used to compute the
+/// epsilon-closures, whether the result is connected and optional weight and state thresholds
+/// limiting the epsilon-closures, which keeps the result small when they are large.
+/// The default config gives the same result as [`rm_epsilon`](fn.rm_epsilon.html).
+///
+/// With [`EpsilonSide::Input`](enum.EpsilonSide.html), every transition with an epsilon
+/// input label is removed, whatever its output label. The output labels of the removed
+/// transitions are delayed, in order, onto the next transitions of the path with a
+/// non-epsilon input label and an epsilon output label. The labels of the other transitions
+/// are never moved : when a delayed label reaches a transition with two non-epsilon labels or
+/// a final state, it is emitted just before on a new transition with an epsilon input label.
+/// An error is returned when a cycle of transitions keeps delaying output labels.
+/// [`EpsilonSide::Output`](enum.EpsilonSide.html) is the same with the roles of the input
+/// and output labels swapped.
+///
+/// # Example
+/// ```
+/// # use rustfst::semirings::{Semiring, TropicalWeight};
+/// # use rustfst::fst_impls::VectorFst;
+/// # use rustfst::fst_traits::{CoreFst, MutableFst};
+/// # use rustfst::algorithms::rm_epsilon::{rm_epsilon_with_config, EpsilonSide, RmEpsilonConfig};
+/// # use rustfst::{Tr, Trs, EPS_LABEL};
+/// # use anyhow::Result;
+/// # fn main() -> Result<()> {
+/// let mut fst = VectorFst::<TropicalWeight>::new();
+/// fst.add_states(3);
+/// fst.set_start(0)?;
+/// fst.set_final(2, TropicalWeight::one())?;
+/// fst.add_tr(0, Tr::new(EPS_LABEL, 1, 1.0, 1))?;
+/// fst.add_tr(1, Tr::new(2, EPS_LABEL, 2.0, 2))?;
+///
+/// rm_epsilon_with_config(&mut fst, RmEpsilonConfig::new(EpsilonSide::Input))?;
+///
+/// let trs = fst.get_trs(fst.start().unwrap())?;
+/// assert_eq!(trs.len(), 1);
+/// let tr = &trs.trs()[0];
+/// assert_eq!((tr.ilabel, tr.olabel), (2, 1));
+/// assert_eq!(tr.weight, TropicalWeight::new(3.0));
+/// # Ok(())
+/// # }
+/// ```
+pub fn rm_epsilon_with_config<W: Semiring, F: MutableFst<W>>(
+    fst: &mut F,
+    config: RmEpsilonConfig<W>,
+) -> Result<()> {
+    match config.side {
+        EpsilonSide::Both => {}
+        EpsilonSide::Input => move_epsilon_side_labels(fst, true)?,
+        EpsilonSide::Output => move_epsilon_side_labels(fst, false)?,
+    };
+    rm_epsilon_both(fst, &config)
+}
+
+/// Builds a transition from its label on the epsilon side and its other label.
+fn side_tr<W: Semiring>(
+    input: bool,
+    side_label: Label,
+    other_label: Label,
+    weight: W,
+    nextstate: StateId,
+) -> Tr<W> {
+    if input {
+        Tr::new(side_label, other_label, weight, nextstate)
+    } else {
+        Tr::new(other_label, side_label, weight, nextstate)
+    }
+}
+
+/// States of the FST built by `move_epsilon_side_labels`. They are only numbered here, the
+/// FST is modified once all of them are known.
+struct PendingLabelsStates {
+    pair_to_state: HashMap<(StateId, VecDeque<Label>), StateId>,
+    queue: Vec<(StateId, StateId, VecDeque<Label>)>,
+    num_states: StateId,
+}
+
+impl PendingLabelsStates {
+    fn new_state(&mut self) -> StateId {
+        self.num_states += 1;
+        self.num_states - 1
+    }
+
+    /// State of the pair `(state, pending)`, `state` itself if no label is pending.
+    fn pair_state(&mut self, state: StateId, pending: VecDeque<Label>) -> StateId {
+        if pending.is_empty() {
+            return state;
+        }
+        let pair = (state, pending);
+        if let Some(s) = self.pair_to_state.get(&pair) {
+            return *s;
+        }
+        let s = self.new_state();
+        self.pair_to_state.insert(pair.clone(), s);
+        self.queue.push((s, pair.0, pair.1));
+        s
+    }
+
+    /// Adds a chain of new states emitting `labels`, the first transition having the weight
+    /// `weight`. Returns this first transition and the last state of the chain.
+    fn chain<W: Semiring>(
+        &mut self,
+        labels: &VecDeque<Label>,
+        weight: W,
+        input: bool,
+        new_trs: &mut Vec<(StateId, Tr<W>)>,
+    ) -> (Tr<W>, StateId) {
+        let mut last = self.new_state();
+        let first = side_tr(input, EPS_LABEL, labels[0], weight, last);
+        for label in labels.iter().skip(1) {
+            let s = self.new_state();
+            new_trs.push((last, side_tr(input, EPS_LABEL, *label, W::one(), s)));
+            last = s;
+        }
+        (first, last)
+    }
+}
+
+/// Turns the transitions with an epsilon on the `input` side into epsilon transitions by
+/// delaying their other label onto the next transitions of the path. The labels waiting to
+/// be emitted are stored in a queue in the state : `(s, [])` is the original state `s` and
+/// the other pairs are added to the FST.
+///
+/// Only the transitions with an epsilon on the `input` side have their labels moved. The
+/// other transitions receive the first pending label if their other label is an epsilon.
+/// Otherwise, as on the final states, the pending labels are emitted on a chain of new
+/// transitions.
+fn move_epsilon_side_labels<W: Semiring, F: MutableFst<W>>(fst: &mut F, input: bool) -> Result<()> {
+    // (label on the epsilon side, other label)
+    let split = |tr: &Tr<W>| {
+        if input {
+            (tr.ilabel, tr.olabel)
+        } else {
+            (tr.olabel, tr.ilabel)
+        }
+    };
+
+    let num_states = fst.num_states();
+    let mut states = PendingLabelsStates {
+        pair_to_state: HashMap::new(),
+        queue: (0..num_states as StateId)
+            .map(|s| (s, s, VecDeque::new()))
+            .collect(),
+        num_states: num_states as StateId,
+    };
+    let mut new_trs = vec![];
+    let mut new_final_states = vec![];
+
+    let mut idx = 0;
+    while idx < states.queue.len() {
+        let (pair_state, state, pending) = states.queue[idx].clone();
+        if !pending.is_empty() {
+            if let Some(final_weight) = fst.final_weight(state)? {
+                // The pending labels are emitted on the way to a new final state.
+                let (first, last) = states.chain(&pending, final_weight, input, &mut new_trs);
+                new_trs.push((pair_state, first));
+                new_final_states.push(last);
+            }
+        }
+        for tr in fst.get_trs(state)?.trs() {
+            let (side_label, other_label) = split(tr);
+            if side_label == EPS_LABEL {
+                let mut next_pending = pending.clone();
+                if other_label != EPS_LABEL {
+                    next_pending.push_back(other_label);
+                }
+                // More pending labels than states means that a cycle of transitions keeps
+                // delaying labels, the result would be infinite.
+                if next_pending.len() > num_states {
+                    bail!(
+                        "RmEpsilon : The labels of a cycle leaving state {} can't be moved onto other transitions",
+                        state
+                    );
+                }
+                let nextstate = states.pair_state(tr.nextstate, next_pending);
+                let tr = side_tr(input, EPS_LABEL, EPS_LABEL, tr.weight.clone(), nextstate);
+                new_trs.push((pair_state, tr));
+            } else if pending.is_empty() {
+                new_trs.push((pair_state, tr.clone()));
+            } else if other_label == EPS_LABEL {
+                let mut next_pending = pending.clone();
+                let label = next_pending.pop_front().unwrap();
+                let nextstate = states.pair_state(tr.nextstate, next_pending);
+                let tr = side_tr(input, side_label, label, tr.weight.clone(), nextstate);
+                new_trs.push((pair_state, tr));
+            } else {
+                // The labels of the transition are kept, the pending ones are emitted before.
+                let (first, last) = states.chain(&pending, tr.weight.clone(), input, &mut new_trs);
+                new_trs.push((pair_state, first));
+                let tr = side_tr(input, side_label, other_label, W::one(), tr.nextstate);
+                new_trs.push((last, tr));
+            }
+        }
+        idx += 1;
+    }
+
+    fst.add_states(states.num_states as usize - num_states);
+    for state in 0..num_states {
+        fst.delete_trs(state as StateId)?;
+    }
+    for (state, tr) in new_trs {
+        fst.add_tr(state, tr)?;
+    }
+    for state in new_final_states {
+        fst.set_final(state, W::one())?;
+    }
+    Ok(())
+}
+
 pub(crate) fn rm_epsilon_with_internal_config<W: Semiring, F: MutableFst<W>, Q: Queue>(
     fst: &mut F,
     opts: RmEpsilonInternalConfig<W, Q>,
 ) -> Result<()> {
     let connect = opts.connect;
-    let weight_threshold = opts.weight_threshold.clone();
-    let state_threshold = opts.state_threshold;
+    let mut progress = Progress::new(opts.progress.clone());
 
     let start_state = match fst.start() {
         None => return Ok(()),
@@ -138,9 +394,7 @@ pub(crate) fn rm_epsilon_with_internal_config<W: Semiring, F: MutableFst<W>, Q:
     let zero = W::zero();
 
     for state in states.into_iter().rev() {
-        if !noneps_in[state as usize]
-            && (connect || weight_threshold != W::zero() || state_threshold != None)
-        {
+        if !noneps_in[state as usize] && connect {
             continue;
         }
         let (trs, final_weight) = rmeps_state.expand::<F, _>(state, &*fst)?;
@@ -154,9 +408,14 @@ pub(crate) fn rm_epsilon_with_internal_config<W: Semiring, F: MutableFst<W>, Q:
                 fst.delete_final_weight_unchecked(state);
             }
         }
+        if let Err(e) = progress.tick() {
+            // The FST is still equivalent to the input but its properties are not known anymore.
+            fst.set_properties(FstProperties::empty());
+            return Err(e);
+        }
     }
 
-    if connect || weight_threshold != W::zero() || state_threshold != None {
+    if connect {
         for s in 0..(fst.num_states() as StateId) {
             if !noneps_in[s as usize] {
                 fst.delete_trs(s)?;
@@ -166,11 +425,7 @@ pub(crate) fn rm_epsilon_with_internal_config<W: Semiring, F: MutableFst<W>, Q:
 
     fst.set_properties(rmepsilon_properties(fst.properties(), false));
 
-    if weight_threshold != W::zero() || state_threshold != None {
-        todo!("Implement Prune!")
-    }
-
-    if connect && weight_threshold == W::zero() && state_threshold == None {
+    if connect {
         crate::algorithms::connect(fst)?;
     }
     Ok(())
@@ -179,9 +434,10 @@ pub(crate) fn rm_epsilon_with_internal_config<W: Semiring, F: MutableFst<W>, Q:
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::fst_traits::Fst;
+    use crate::fst_traits::{CoreFst, ExpandedFst, Fst};
     use crate::prelude::{TropicalWeight, VectorFst};
     use crate::SymbolTable;
+    use crate::Tr;
     use proptest::prelude::any;
     use proptest::proptest;
     use std::sync::Arc;
@@ -199,4 +455,297 @@ mod tests {
             assert!(fst.output_symbols().is_some());
         }
     }
+
+    #[test]
+    fn test_rm_epsilon_with_config_side() -> Result<()> {
+        let mut fst = VectorFst::<TropicalWeight>::new();
+        fst.add_states(4);
+        fst.set_start(0)?;
+        fst.set_final(3, TropicalWeight::one())?;
+        fst.add_tr(0, Tr::new(EPS_LABEL, EPS_LABEL, 1.0, 1))?;
+        fst.add_tr(0, Tr::new(EPS_LABEL, EPS_LABEL, 2.0, 1))?;
+        fst.add_tr(0, Tr::new(4, EPS_LABEL, 3.0, 3))?;
+        fst.add_tr(1, Tr::new(EPS_LABEL, 5, 1.0, 2))?;
+        fst.add_tr(2, Tr::new(3, EPS_LABEL, 1.0, 3))?;
+
+        // Both
+        let mut fst_both = fst.clone();
+        rm_epsilon_with_config(&mut fst_both, RmEpsilonConfig::default())?;
+        let mut fst_ref = fst.clone();
+        rm_epsilon(&mut fst_ref)?;
+        assert_eq!(fst_both, fst_ref);
+
+        // Input
+        let mut fst_input = fst.clone();
+        rm_epsilon_with_config(&mut fst_input, RmEpsilonConfig::new(EpsilonSide::Input))?;
+        let start = fst_input.start().unwrap();
+        let mut trs = fst_input.get_trs(start)?.trs().to_vec();
+        trs.sort_by_key(|tr| tr.ilabel);
+        assert_eq!(trs.len(), 2);
+        assert_eq!((trs[0].ilabel, trs[0].olabel), (3, 5));
+        assert_eq!(trs[0].weight, TropicalWeight::new(3.0));
+        assert_eq!((trs[1].ilabel, trs[1].olabel), (4, EPS_LABEL));
+        assert_eq!(trs[1].weight, TropicalWeight::new(3.0));
+        for tr in trs {
+            assert_eq!(
+                fst_input.final_weight(tr.nextstate)?,
+                Some(TropicalWeight::one())
+            );
+        }
+        for s in fst_input.states_range() {
+            assert!(fst_input
+                .get_trs(s)?
+                .trs()
+                .iter()
+                .all(|tr| tr.ilabel != EPS_LABEL));
+        }
+
+        // Output : the input labels 3 and 4 are emitted on the way to new final states.
+        let mut fst_output = fst.clone();
+        rm_epsilon_with_config(&mut fst_output, RmEpsilonConfig::new(EpsilonSide::Output))?;
+        let labels = |fst: &VectorFst<TropicalWeight>| {
+            let mut paths: Vec<_> = fst
+                .paths_iter()
+                .map(|p| (p.ilabels, p.olabels, p.weight))
+                .collect();
+            paths.sort_by(|p1, p2| p1.0.cmp(&p2.0));
+            paths
+        };
+        assert_eq!(labels(&fst_output), labels(&fst_ref));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_rm_epsilon_with_config_side_pending_label_on_final_state() -> Result<()> {
+        let mut fst = VectorFst::<TropicalWeight>::new();
+        fst.add_states(3);
+        fst.set_start(0)?;
+        fst.set_final(2, 0.5)?;
+        fst.add_tr(0, Tr::new(1, EPS_LABEL, 1.0, 1))?;
+        fst.add_tr(1, Tr::new(EPS_LABEL, 2, 2.0, 2))?;
+        fst.add_tr(2, Tr::new(3, 4, 1.0, 0))?;
+
+        let mut fst_input = fst.clone();
+        rm_epsilon_with_config(&mut fst_input, RmEpsilonConfig::new(EpsilonSide::Input))?;
+        let path = fst_input.paths_iter().next().unwrap();
+        assert_eq!(path.ilabels, vec![1]);
+        assert_eq!(path.olabels, vec![2]);
+        assert_eq!(path.weight, TropicalWeight::new(3.5));
+        // The label 2 can't be delayed onto the transition 3:4.
+        assert!(fst_input
+            .paths_iter()
+            .any(|p| p.ilabels == vec![1, 3, 1] && p.olabels == vec![2, 4, 2]));
+        Ok(())
+    }
+
+    #[test]
+    fn test_rm_epsilon_with_config_side_keeps_labels() -> Result<()> {
+        let mut fst = VectorFst::<TropicalWeight>::new();
+        fst.add_states(4);
+        fst.set_start(0)?;
+        fst.set_final(3, TropicalWeight::one())?;
+        fst.add_tr(0, Tr::new(EPS_LABEL, 5, 1.0, 1))?;
+        fst.add_tr(1, Tr::new(2, 7, 1.0, 2))?;
+        fst.add_tr(2, Tr::new(3, EPS_LABEL, 1.0, 3))?;
+
+        let mut fst_input = fst.clone();
+        rm_epsilon_with_config(&mut fst_input, RmEpsilonConfig::new(EpsilonSide::Input))?;
+        let paths: Vec<_> = fst_input.paths_iter().collect();
+        assert_eq!(paths.len(), 1);
+        assert_eq!(paths[0].ilabels, vec![2, 3]);
+        assert_eq!(paths[0].olabels, vec![5, 7]);
+        assert_eq!(paths[0].weight, TropicalWeight::new(3.0));
+
+        // The label 7 stays on the transition 2:7, the label 5 is emitted before it.
+        let mut labels = vec![];
+        for s in fst_input.states_range() {
+            for tr in fst_input.get_trs(s)?.trs() {
+                labels.push((tr.ilabel, tr.olabel));
+            }
+        }
+        labels.sort_unstable();
+        assert_eq!(labels, vec![(EPS_LABEL, 5), (2, 7), (3, EPS_LABEL)]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_rm_epsilon_with_config_side_delays_several_labels() -> Result<()> {
+        let mut fst = VectorFst::<TropicalWeight>::new();
+        fst.add_states(5);
+        fst.set_start(0)?;
+        fst.set_final(4, TropicalWeight::one())?;
+        fst.add_tr(0, Tr::new(EPS_LABEL, 5, 1.0, 1))?;
+        fst.add_tr(1, Tr::new(EPS_LABEL, 6, 1.0, 2))?;
+        fst.add_tr(2, Tr::new(3, EPS_LABEL, 1.0, 3))?;
+        fst.add_tr(3, Tr::new(4, EPS_LABEL, 1.0, 4))?;
+
+        let mut fst_input = fst.clone();
+        rm_epsilon_with_config(&mut fst_input, RmEpsilonConfig::new(EpsilonSide::Input))?;
+        let paths: Vec<_> = fst_input.paths_iter().collect();
+        assert_eq!(paths.len(), 1);
+        assert_eq!(paths[0].ilabels, vec![3, 4]);
+        assert_eq!(paths[0].olabels, vec![5, 6]);
+        assert_eq!(paths[0].weight, TropicalWeight::new(4.0));
+
+        // A cycle delaying labels would require an infinite number of states.
+        fst.add_tr(1, Tr::new(EPS_LABEL, 7, 1.0, 1))?;
+        let mut fst_cycle = fst.clone();
+        assert!(
+            rm_epsilon_with_config(&mut fst_cycle, RmEpsilonConfig::new(EpsilonSide::Input))
+                .is_err()
+        );
+        assert_eq!(fst_cycle, fst);
+        Ok(())
+    }
+
+    #[test]
+    fn test_rm_epsilon_cancelled() -> Result<()> {
+        use crate::algorithms::{Cancelled, ProgressAction, ProgressCallback};
+
+        let labels: Vec<_> = (0..2500)
+            .map(|i| (i % 2, i % 2, TropicalWeight::new(1.0)))
+            .collect();
+        let fst: VectorFst<TropicalWeight> = crate::utils::chain(&labels);
+
+        let mut fst_cancelled = fst.clone();
+        let config = RmEpsilonConfig::default()
+            .with_progress(Some(ProgressCallback::new(|_| ProgressAction::Abort)));
+        let err = rm_epsilon_with_config(&mut fst_cancelled, config).unwrap_err();
+        assert!(err.downcast_ref::<Cancelled>().is_some());
+        assert_eq!(fst_cancelled.properties(), FstProperties::empty());
+
+        // Half-processed but still equivalent.
+        let path_ref = fst.paths_iter().next().unwrap();
+        let paths: Vec<_> = fst_cancelled.paths_iter().collect();
+        assert_eq!(paths.len(), 1);
+        assert_eq!(paths[0].ilabels, path_ref.ilabels);
+        assert_eq!(paths[0].weight, path_ref.weight);
+        Ok(())
+    }
+
+    fn fst_with_epsilon_cycle() -> Result<VectorFst<TropicalWeight>> {
+        // Paths of weight 2 (with an epsilon cycle of weight 1.5) and 6.
+        let mut fst = VectorFst::<TropicalWeight>::new();
+        fst.add_states(4);
+        fst.set_start(0)?;
+        fst.set_final(3, TropicalWeight::one())?;
+        fst.add_tr(0, Tr::new(EPS_LABEL, EPS_LABEL, 1.0, 1))?;
+        fst.add_tr(1, Tr::new(EPS_LABEL, EPS_LABEL, 0.5, 0))?;
+        fst.add_tr(1, Tr::new(1, 1, 1.0, 3))?;
+        fst.add_tr(0, Tr::new(2, 2, 5.0, 2))?;
+        fst.add_tr(2, Tr::new(EPS_LABEL, EPS_LABEL, 1.0, 3))?;
+        Ok(fst)
+    }
+
+    fn sorted_paths(fst: &VectorFst<TropicalWeight>) -> Vec<(Vec<Label>, TropicalWeight)> {
+        let mut paths: Vec<_> = fst.paths_iter().map(|p| (p.ilabels, p.weight)).collect();
+        paths.sort_by(|p1, p2| p1.0.cmp(&p2.0));
+        paths
+    }
+
+    #[test]
+    fn test_rm_epsilon_with_config_queue_type() -> Result<()> {
+        let fst = fst_with_epsilon_cycle()?;
+        let mut fst_ref = fst.clone();
+        rm_epsilon(&mut fst_ref)?;
+        assert_eq!(
+            sorted_paths(&fst_ref),
+            vec![
+                (vec![1], TropicalWeight::new(2.0)),
+                (vec![2], TropicalWeight::new(6.0))
+            ]
+        );
+
+        for queue_type in [
+            QueueType::AutoQueue,
+            QueueType::FifoQueue,
+            QueueType::LifoQueue,
+            QueueType::StateOrderQueue,
+        ] {
+            let mut fst_rmeps = fst.clone();
+            let config = RmEpsilonConfig::default().with_queue_type(queue_type);
+            rm_epsilon_with_config(&mut fst_rmeps, config)?;
+            assert_eq!(fst_rmeps.num_states(), fst_ref.num_states());
+            assert_eq!(sorted_paths(&fst_rmeps), sorted_paths(&fst_ref));
+        }
+
+        // The epsilon transitions are cyclic.
+        let mut fst_top = fst.clone();
+        let config = RmEpsilonConfig::default().with_queue_type(QueueType::TopOrderQueue);
+        assert!(rm_epsilon_with_config(&mut fst_top, config).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_rm_epsilon_with_config_connect_and_thresholds() -> Result<()> {
+        let fst = fst_with_epsilon_cycle()?;
+
+        // Without connect, the state only reached by epsilon transitions is kept.
+        let mut fst_no_connect = fst.clone();
+        rm_epsilon_with_config(
+            &mut fst_no_connect,
+            RmEpsilonConfig::default().with_connect(false),
+        )?;
+        assert_eq!(fst_no_connect.num_states(), 4);
+        assert_eq!(
+            fst_no_connect.properties() & FstProperties::NO_EPSILONS,
+            FstProperties::NO_EPSILONS
+        );
+
+        // Path of weight 1 and path of weight 3 starting with two epsilon transitions.
+        let mut fst = VectorFst::<TropicalWeight>::new();
+        fst.add_states(4);
+        fst.set_start(0)?;
+        fst.set_final(3, TropicalWeight::one())?;
+        fst.add_tr(0, Tr::new(1, 1, 1.0, 3))?;
+        fst.add_tr(0, Tr::new(EPS_LABEL, EPS_LABEL, 1.0, 1))?;
+        fst.add_tr(1, Tr::new(EPS_LABEL, EPS_LABEL, 1.0, 2))?;
+        fst.add_tr(2, Tr::new(3, 3, 1.0, 3))?;
+        let mut fst_ref = fst.clone();
+        rm_epsilon(&mut fst_ref)?;
+        assert_eq!(
+            sorted_paths(&fst_ref),
+            vec![
+                (vec![1], TropicalWeight::new(1.0)),
+                (vec![3], TropicalWeight::new(3.0))
+            ]
+        );
+
+        // The epsilon-closure of the start state stops at state 1 : the epsilon path to state 2
+        // weighs 2.
+        let mut fst_weight_threshold = fst.clone();
+        rm_epsilon_with_config(
+            &mut fst_weight_threshold,
+            RmEpsilonConfig::default().with_weight_threshold(TropicalWeight::new(1.5)),
+        )?;
+        assert_eq!(
+            sorted_paths(&fst_weight_threshold),
+            vec![(vec![1], TropicalWeight::new(1.0))]
+        );
+        let mut fst_weight_threshold = fst.clone();
+        rm_epsilon_with_config(
+            &mut fst_weight_threshold,
+            RmEpsilonConfig::default().with_weight_threshold(TropicalWeight::new(2.0)),
+        )?;
+        assert_eq!(sorted_paths(&fst_weight_threshold), sorted_paths(&fst_ref));
+
+        // The epsilon-closure of the start state only reaches states 0 and 1.
+        let mut fst_state_threshold = fst.clone();
+        rm_epsilon_with_config(
+            &mut fst_state_threshold,
+            RmEpsilonConfig::default().with_state_threshold(Some(2)),
+        )?;
+        assert_eq!(
+            sorted_paths(&fst_state_threshold),
+            vec![(vec![1], TropicalWeight::new(1.0))]
+        );
+        let mut fst_state_threshold = fst.clone();
+        rm_epsilon_with_config(
+            &mut fst_state_threshold,
+            RmEpsilonConfig::default().with_state_threshold(Some(3)),
+        )?;
+        assert_eq!(sorted_paths(&fst_state_threshold), sorted_paths(&fst_ref));
+        Ok(())
+    }
 }
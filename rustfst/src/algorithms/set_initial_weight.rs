@@ -0,0 +1,127 @@
+use anyhow::Result;
+
+use crate::fst_traits::MutableFst;
+use crate::semirings::Semiring;
+use crate::{Tr, Trs, EPS_LABEL};
+
+/// Sets an initial weight on the FST : `weight` is (left) multiplied to the weight of every
+/// path. This is equivalent to prepending a single transition FST with this weight.
+///
+/// # Behaviour
+/// If the start state has no incoming transitions, `weight` is pushed onto the transitions
+/// leaving the start state and onto its final weight.
+///
+/// Otherwise, a new start state is added with an epsilon transition of weight `weight`
+/// leading to the former start state.
+///
+/// Nothing is done if the FST has no start state or if `weight` is `W::one()`.
+///
+/// # Example
+/// ```
+/// # use rustfst::fst_impls::VectorFst;
+/// # use rustfst::fst_traits::{CoreFst, MutableFst};
+/// # use rustfst::semirings::{Semiring, TropicalWeight};
+/// # use rustfst::algorithms::set_initial_weight;
+/// # use rustfst::utils::acceptor;
+/// # use rustfst::Trs;
+/// # use anyhow::Result;
+/// # fn main() -> Result<()> {
+/// let mut fst: VectorFst<TropicalWeight> = acceptor(&[1, 2], TropicalWeight::new(1.0));
+/// set_initial_weight(&mut fst, TropicalWeight::new(3.0))?;
+/// assert_eq!(fst.get_trs(0)?.trs()[0].weight, TropicalWeight::new(3.0));
+/// # Ok(())
+/// # }
+/// ```
+pub fn set_initial_weight<W: Semiring, F: MutableFst<W>>(fst: &mut F, weight: W) -> Result<()> {
+    let start = match fst.start() {
+        Some(s) => s,
+        None => return Ok(()),
+    };
+    if weight.is_one() {
+        return Ok(());
+    }
+
+    let mut start_has_incoming_trs = false;
+    for s in fst.states_range() {
+        if fst.get_trs(s)?.trs().iter().any(|tr| tr.nextstate == start) {
+            start_has_incoming_trs = true;
+            break;
+        }
+    }
+
+    if start_has_incoming_trs {
+        let new_start = fst.add_state();
+        fst.add_tr(new_start, Tr::new(EPS_LABEL, EPS_LABEL, weight, start))?;
+        fst.set_start(new_start)?;
+    } else {
+        let mut it_tr = fst.tr_iter_mut(start)?;
+        for idx_tr in 0..it_tr.len() {
+            let tr = unsafe { it_tr.get_unchecked(idx_tr) };
+            let new_weight = weight.times(&tr.weight)?;
+            unsafe { it_tr.set_weight_unchecked(idx_tr, new_weight) };
+        }
+        if let Some(final_weight) = fst.final_weight(start)? {
+            fst.set_final(start, weight.times(final_weight)?)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::concat::concat;
+    use crate::algorithms::shortest_distance;
+    use crate::fst_impls::VectorFst;
+    use crate::fst_traits::{CoreFst, ExpandedFst};
+    use crate::semirings::TropicalWeight;
+    use crate::utils::acceptor;
+
+    fn total_weight(fst: &VectorFst<TropicalWeight>) -> Result<TropicalWeight> {
+        let dist = shortest_distance(fst, true)?;
+        Ok(dist[fst.start().unwrap() as usize])
+    }
+
+    #[test]
+    fn test_set_initial_weight() -> Result<()> {
+        let mut fst = VectorFst::<TropicalWeight>::new();
+        fst.add_states(3);
+        fst.set_start(0)?;
+        fst.set_final(0, 5.0)?;
+        fst.set_final(2, 1.0)?;
+        fst.add_tr(0, Tr::new(1, 1, 1.0, 1))?;
+        fst.add_tr(1, Tr::new(2, 2, 2.0, 2))?;
+        assert_eq!(total_weight(&fst)?, TropicalWeight::new(4.0));
+
+        // Equivalent to prepending a single transition FST with the initial weight.
+        let mut fst_ref: VectorFst<TropicalWeight> = acceptor(&[], TropicalWeight::new(3.0));
+        concat(&mut fst_ref, &fst)?;
+
+        set_initial_weight(&mut fst, TropicalWeight::new(3.0))?;
+        assert_eq!(fst.num_states(), 3);
+        assert_eq!(total_weight(&fst)?, TropicalWeight::new(7.0));
+        assert_eq!(total_weight(&fst)?, total_weight(&fst_ref)?);
+        assert_eq!(fst.final_weight(0)?, Some(TropicalWeight::new(8.0)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_initial_weight_start_with_incoming_trs() -> Result<()> {
+        let mut fst = VectorFst::<TropicalWeight>::new();
+        fst.add_states(2);
+        fst.set_start(0)?;
+        fst.set_final(1, 1.0)?;
+        fst.add_tr(0, Tr::new(1, 1, 1.0, 1))?;
+        fst.add_tr(1, Tr::new(2, 2, 0.5, 0))?;
+
+        set_initial_weight(&mut fst, TropicalWeight::new(3.0))?;
+        assert_eq!(fst.num_states(), 3);
+        assert_eq!(fst.start(), Some(2));
+        // The weight is only applied once, even on the paths going through the loop.
+        assert_eq!(fst.get_trs(0)?.trs()[0].weight, TropicalWeight::new(1.0));
+        assert_eq!(total_weight(&fst)?, TropicalWeight::new(5.0));
+
+        Ok(())
+    }
+}
@@ -1,8 +1,6 @@
-use std::marker::PhantomData;
-
 use anyhow::Result;
 
-use crate::algorithms::queues::AutoQueue;
+use crate::algorithms::queues::{natural_less, AutoQueue};
 use crate::algorithms::tr_filters::{AnyTrFilter, TrFilter};
 use crate::algorithms::Queue;
 use crate::fst_impls::VectorFst;
@@ -17,8 +15,11 @@ pub(crate) struct ShortestDistanceInternalConfig<W: Semiring, Q: Queue, A: TrFil
     pub source: Option<StateId>,
     pub first_path: bool,
     pub delta: f32,
-    // TODO: Shouldn't need that
-    weight: PhantomData<W>,
+    /// If not zero, the paths whose weight is greater than this threshold (with respect to the
+    /// natural order) are not followed.
+    pub weight_threshold: W,
+    /// If set, at most this number of states are reached from the source.
+    pub state_threshold: Option<StateId>,
 }
 
 impl<W: Semiring, Q: Queue, A: TrFilter<W>> ShortestDistanceInternalConfig<W, Q, A> {
@@ -35,7 +36,8 @@ impl<W: Semiring, Q: Queue, A: TrFilter<W>> ShortestDistanceInternalConfig<W, Q,
             source,
             first_path,
             delta,
-            weight: PhantomData,
+            weight_threshold: W::zero(),
+            state_threshold: None,
         }
     }
 
@@ -57,6 +59,8 @@ pub(crate) struct ShortestDistanceState<W: Semiring, Q: Queue, A: TrFilter<W>> {
     retain: bool,
     source_id: usize,
     delta: f32,
+    weight_threshold: W,
+    state_threshold: Option<StateId>,
 }
 
 impl<W: Semiring, Q: Queue, A: TrFilter<W>> std::fmt::Debug for ShortestDistanceState<W, Q, A> {
@@ -72,7 +76,9 @@ impl<W: Semiring, Q: Queue, A: TrFilter<W>> std::fmt::Debug for ShortestDistance
         write!(f, "sources : {:?}, ", self.sources)?;
         write!(f, "retain : {:?}, ", self.retain)?;
         write!(f, "source_id : {:?} ", self.source_id)?;
-        write!(f, "delta : {:?}", self.delta)?;
+        write!(f, "delta : {:?}, ", self.delta)?;
+        write!(f, "weight_threshold : {:?}, ", self.weight_threshold)?;
+        write!(f, "state_threshold : {:?}", self.state_threshold)?;
         write!(f, "}}")?;
         Ok(())
     }
@@ -118,6 +124,8 @@ impl<W: Semiring, Q: Queue, A: TrFilter<W>> ShortestDistanceState<W, Q, A> {
             source_id: 0,
             retain,
             delta,
+            weight_threshold: W::zero(),
+            state_threshold: None,
         }
     }
     pub fn new_from_config(
@@ -125,14 +133,27 @@ impl<W: Semiring, Q: Queue, A: TrFilter<W>> ShortestDistanceState<W, Q, A> {
         opts: ShortestDistanceInternalConfig<W, Q, A>,
         retain: bool,
     ) -> Self {
-        Self::new(
-            fst_num_states,
-            opts.state_queue,
-            opts.tr_filter,
-            opts.first_path,
-            retain,
-            opts.delta,
-        )
+        Self {
+            weight_threshold: opts.weight_threshold,
+            state_threshold: opts.state_threshold,
+            ..Self::new(
+                fst_num_states,
+                opts.state_queue,
+                opts.tr_filter,
+                opts.first_path,
+                retain,
+                opts.delta,
+            )
+        }
+    }
+
+    /// Whether `state` was reached by the last computation, when the distances are retained
+    /// between the sources. The states beyond the thresholds are not reached.
+    pub fn is_reached(&self, state: StateId) -> bool {
+        let state = state as usize;
+        self.source_id > 0
+            && self.sources.get(state) == Some(&Some((self.source_id - 1) as StateId))
+            && self.distance[state] != W::zero()
     }
 
     fn ensure_distance_index_is_valid(&mut self, index: usize) {
@@ -166,6 +187,15 @@ impl<W: Semiring, Q: Queue, A: TrFilter<W>> ShortestDistanceState<W, Q, A> {
         if self.first_path && !weight_properties.contains(SemiringProperties::PATH) {
             bail!("ShortestDistance: The first_path option is disallowed when Weight does not have the path property")
         }
+        let weight_threshold = if self.weight_threshold != W::zero() {
+            if !weight_properties.contains(SemiringProperties::PATH) {
+                bail!("ShortestDistance: The weight threshold is disallowed when Weight does not have the path property")
+            }
+            Some(self.weight_threshold.clone())
+        } else {
+            None
+        };
+        let mut num_reached = 1;
         self.state_queue.clear();
         if !self.retain {
             self.distance.clear();
@@ -217,6 +247,19 @@ impl<W: Semiring, Q: Queue, A: TrFilter<W>> ShortestDistanceState<W, Q, A> {
                 let na = self.adder.get_mut(nextstate).unwrap();
                 let nr = self.radder.get_mut(nextstate).unwrap();
                 let weight = r.times(&tr.weight)?;
+                if let Some(weight_threshold) = &weight_threshold {
+                    if natural_less(weight_threshold, &weight)? {
+                        continue;
+                    }
+                }
+                if nd.is_zero() {
+                    if let Some(state_threshold) = self.state_threshold {
+                        if num_reached >= state_threshold {
+                            continue;
+                        }
+                    }
+                    num_reached += 1;
+                }
                 if !nd.approx_equal(nd.plus(&weight)?, self.delta) {
                     na.plus_assign(&weight)?;
                     *nd = na.clone();
@@ -310,6 +353,12 @@ pub fn shortest_distance<W: Semiring, F: ExpandedFst<W>>(fst: &F, reverse: bool)
 
 /// Compute the shortest distance from the initial state to every
 /// state, with configurable delta for comparison.
+///
+/// For weights with the path property (e.g. `TropicalWeight`), weights smaller
+/// than `one()` w.r.t. the natural order (e.g. negative tropical weights) are supported.
+/// In that case, the FST is first checked for cycles with such a weight using a
+/// Bellman-Ford relaxation and an error is returned if one is found, as the shortest
+/// distance is then not defined. FSTs without such weights skip that check.
 pub fn shortest_distance_with_config<W: Semiring, F: ExpandedFst<W>>(
     fst: &F,
     reverse: bool,
@@ -318,11 +367,13 @@ pub fn shortest_distance_with_config<W: Semiring, F: ExpandedFst<W>>(
     let delta = config.delta;
     let tr_filter = AnyTrFilter {};
     if !reverse {
+        ensure_no_negative_cycle(fst, delta)?;
         let queue = AutoQueue::new(fst, None, &tr_filter)?;
         let config = ShortestDistanceInternalConfig::new_with_default(tr_filter, queue, delta);
         shortest_distance_with_internal_config(fst, config)
     } else {
         let rfst: VectorFst<_> = crate::algorithms::reverse(fst)?;
+        ensure_no_negative_cycle(&rfst, delta)?;
         let state_queue = AutoQueue::new(&rfst, None, &tr_filter)?;
         let ropts = ShortestDistanceInternalConfig::new_with_default(tr_filter, state_queue, delta);
         let rdistance = shortest_distance_with_internal_config(&rfst, ropts)?;
@@ -334,6 +385,240 @@ pub fn shortest_distance_with_config<W: Semiring, F: ExpandedFst<W>>(
     }
 }
 
+/// Compute the shortest distance from the initial state to every state, relaxing the
+/// states of an acyclic FST in parallel.
+///
+/// The states are grouped in layers such that every transition goes from a layer to a
+/// later one. The distances of all the states of a layer only depend on the previous
+/// layers and are computed in parallel. For commutative semirings, the result is the same
+/// as the one of [`shortest_distance`](fn.shortest_distance.html). Cyclic FSTs can't be
+/// layered and fall back to the sequential algorithm.
+///
+/// Only available with the `rayon` feature.
+#[cfg(feature = "rayon")]
+pub fn shortest_distance_parallel<W, F>(fst: &F, reverse: bool) -> Result<Vec<W>>
+where
+    W: Semiring + Send,
+    W::ReverseWeight: Send,
+    F: ExpandedFst<W>,
+{
+    if !reverse {
+        shortest_distance_layered(fst, KSHORTESTDELTA)
+    } else {
+        let rfst: VectorFst<_> = crate::algorithms::reverse(fst)?;
+        let rdistance = shortest_distance_layered(&rfst, KSHORTESTDELTA)?;
+        let mut distance = Vec::with_capacity(rdistance.len() - 1); //reversing added one state
+        while distance.len() < rdistance.len() - 1 {
+            distance.push(rdistance[distance.len() + 1].reverse_back()?);
+        }
+        Ok(distance)
+    }
+}
+
+#[cfg(feature = "rayon")]
+fn shortest_distance_layered<W: Semiring + Send, F: ExpandedFst<W>>(
+    fst: &F,
+    delta: f32,
+) -> Result<Vec<W>> {
+    use crate::algorithms::dfs_visit::dfs_visit;
+    use crate::algorithms::top_sort::TopOrderVisitor;
+    use crate::fst_properties::FstProperties;
+    use rayon::prelude::*;
+
+    let start = match fst.start() {
+        Some(start) => start,
+        None => return Ok(vec![]),
+    };
+    if !W::properties().contains(SemiringProperties::RIGHT_SEMIRING) {
+        bail!("ShortestDistance: Weight needs to be right distributive")
+    }
+
+    // Same topological order as the one used by the queue of the sequential algorithm.
+    let num_states = fst.num_states();
+    let order: Vec<StateId> = if fst.properties().contains(FstProperties::TOP_SORTED) {
+        (0..num_states as StateId).collect()
+    } else {
+        let mut visitor = TopOrderVisitor::new();
+        dfs_visit(fst, &mut visitor, &AnyTrFilter {}, false);
+        if !visitor.acyclic {
+            return shortest_distance(fst, false);
+        }
+        visitor.order
+    };
+    let mut states_in_order = vec![0 as StateId; num_states];
+    for (s, rank) in order.iter().enumerate() {
+        states_in_order[*rank as usize] = s as StateId;
+    }
+
+    // Incoming transitions in the order the sequential algorithm relaxes them.
+    let mut in_trs: Vec<Vec<(StateId, W)>> = vec![vec![]; num_states];
+    let mut layer_of = vec![0usize; num_states];
+    let mut num_layers = 0;
+    for s in states_in_order.iter() {
+        let layer = layer_of[*s as usize];
+        num_layers = num_layers.max(layer + 1);
+        for tr in unsafe { fst.get_trs_unchecked(*s).trs() } {
+            in_trs[tr.nextstate as usize].push((*s, tr.weight.clone()));
+            let next_layer = &mut layer_of[tr.nextstate as usize];
+            *next_layer = (*next_layer).max(layer + 1);
+        }
+    }
+    let mut layers: Vec<Vec<StateId>> = vec![vec![]; num_layers];
+    for s in states_in_order.iter() {
+        layers[layer_of[*s as usize]].push(*s);
+    }
+
+    let mut distance = vec![W::zero(); num_states];
+    let mut reached = vec![false; num_states];
+    distance[start as usize] = W::one();
+    reached[start as usize] = true;
+    for layer in layers.iter() {
+        let relaxed = layer
+            .par_iter()
+            .map(|s| {
+                let mut d = W::zero();
+                let mut reached_s = false;
+                if *s == start {
+                    return Ok((W::one(), true));
+                }
+                for (p, w) in in_trs[*s as usize].iter() {
+                    if !reached[*p as usize] {
+                        continue;
+                    }
+                    let weight = distance[*p as usize].times(w)?;
+                    if !d.approx_equal(d.plus(&weight)?, delta) {
+                        d.plus_assign(&weight)?;
+                        reached_s = true;
+                    }
+                }
+                Ok((d, reached_s))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        for (s, (d, reached_s)) in layer.iter().zip(relaxed.into_iter()) {
+            distance[*s as usize] = d;
+            reached[*s as usize] = reached_s;
+        }
+    }
+
+    // The sequential algorithm only grows the distances up to the states it has seen.
+    let mut len = start as usize + 1;
+    for s in fst.states_range() {
+        if reached[s as usize] {
+            for tr in unsafe { fst.get_trs_unchecked(s).trs() } {
+                len = len.max(tr.nextstate as usize + 1);
+            }
+        }
+    }
+    distance.truncate(len);
+    Ok(distance)
+}
+
+/// Compute the shortest distance from the initial state to every state, visiting the states
+/// in the order given by `queue`.
+///
+/// [`shortest_distance`](fn.shortest_distance.html) uses an [`AutoQueue`](queues/struct.AutoQueue.html),
+/// which picks the discipline from the properties of the FST. Any other [`Queue`](trait.Queue.html)
+/// built for `fst` can be used instead, e.g. a [`FifoQueue`](queues/struct.FifoQueue.html) or a
+/// [`TopOrderQueue`](queues/struct.TopOrderQueue.html) for acyclic FSTs. For idempotent
+/// semirings, e.g. the tropical semiring, the distances are the same whatever the queue, only
+/// the number of relaxations changes. For the other semirings, e.g. the log semiring, they are
+/// only computed up to the `delta` of the config and can slightly differ from one queue to
+/// another.
+///
+/// # Example
+/// ```
+/// # use rustfst::semirings::{Semiring, TropicalWeight};
+/// # use rustfst::fst_impls::VectorFst;
+/// # use rustfst::fst_traits::MutableFst;
+/// # use rustfst::algorithms::{shortest_distance, shortest_distance_with_queue, ShortestDistanceConfig};
+/// # use rustfst::algorithms::queues::FifoQueue;
+/// # use rustfst::Tr;
+/// # use anyhow::Result;
+/// # fn main() -> Result<()> {
+/// let mut fst = VectorFst::<TropicalWeight>::new();
+/// fst.add_states(3);
+/// fst.set_start(0)?;
+/// fst.add_tr(0, Tr::new(1, 1, 1.0, 1))?;
+/// fst.add_tr(0, Tr::new(2, 2, 4.0, 2))?;
+/// fst.add_tr(1, Tr::new(3, 3, 2.0, 2))?;
+///
+/// let dists = shortest_distance_with_queue(&fst, FifoQueue::default(), ShortestDistanceConfig::default())?;
+/// assert_eq!(dists, shortest_distance(&fst, false)?);
+/// # Ok(())
+/// # }
+/// ```
+pub fn shortest_distance_with_queue<W: Semiring, F: ExpandedFst<W>, Q: Queue>(
+    fst: &F,
+    queue: Q,
+    config: ShortestDistanceConfig,
+) -> Result<Vec<W>> {
+    ensure_no_negative_cycle(fst, config.delta)?;
+    let config =
+        ShortestDistanceInternalConfig::new_with_default(AnyTrFilter {}, queue, config.delta);
+    shortest_distance_with_internal_config(fst, config)
+}
+
+/// Ensure that no cycle with a weight smaller than `one()` w.r.t. the natural order is
+/// accessible from the start state. Otherwise, relaxing the distances would never end.
+///
+/// Only weights with the path property are checked. The check only runs a Bellman-Ford
+/// relaxation if at least one transition has a weight smaller than `one()`.
+pub(crate) fn ensure_no_negative_cycle<W: Semiring, F: ExpandedFst<W>>(
+    fst: &F,
+    delta: f32,
+) -> Result<()> {
+    if !W::properties().contains(SemiringProperties::PATH) {
+        return Ok(());
+    }
+    let start = match fst.start() {
+        Some(start) => start,
+        None => return Ok(()),
+    };
+
+    let one = W::one();
+    let mut has_negative_weight = false;
+    for s in fst.states_range() {
+        for tr in unsafe { fst.get_trs_unchecked(s).trs() } {
+            if natural_less(&tr.weight, &one)? {
+                has_negative_weight = true;
+                break;
+            }
+        }
+        if has_negative_weight {
+            break;
+        }
+    }
+    if !has_negative_weight {
+        return Ok(());
+    }
+
+    // Without a negative cycle, the distances are stable after at most num_states - 1 passes.
+    let num_states = fst.num_states();
+    let mut distance = vec![W::zero(); num_states];
+    distance[start as usize] = W::one();
+    for _ in 0..num_states {
+        let mut changed = false;
+        for s in fst.states_range() {
+            if distance[s as usize].is_zero() {
+                continue;
+            }
+            let d = distance[s as usize].clone();
+            for tr in unsafe { fst.get_trs_unchecked(s).trs() } {
+                let nd = d.times(&tr.weight)?;
+                let next = &mut distance[tr.nextstate as usize];
+                if natural_less(&nd, next)? && !nd.approx_equal(&*next, delta) {
+                    *next = nd;
+                    changed = true;
+                }
+            }
+        }
+        if !changed {
+            return Ok(());
+        }
+    }
+    bail!("ShortestDistance: The FST contains a negative weight cycle, the shortest distance is not defined")
+}
+
 #[allow(unused)]
 /// Return the sum of the weight of all successful paths in an FST, i.e., the
 /// shortest-distance from the initial state to the final states..
@@ -371,3 +656,174 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::shortest_path;
+    use crate::fst_traits::Fst;
+    use crate::semirings::TropicalWeight;
+    use crate::Tr;
+
+    #[test]
+    fn test_shortest_distance_negative_weight() -> Result<()> {
+        let mut fst = VectorFst::<TropicalWeight>::new();
+        fst.add_states(3);
+        fst.set_start(0)?;
+        fst.set_final(1, TropicalWeight::one())?;
+        fst.add_tr(0, Tr::new(1, 1, 1.0, 1))?;
+        fst.add_tr(0, Tr::new(2, 2, 3.0, 2))?;
+        fst.add_tr(2, Tr::new(3, 3, -4.0, 1))?;
+        fst.add_tr(1, Tr::new(4, 4, 5.0, 2))?;
+
+        let distance = shortest_distance(&fst, false)?;
+        assert_eq!(
+            distance,
+            vec![
+                TropicalWeight::one(),
+                TropicalWeight::new(-1.0),
+                TropicalWeight::new(3.0)
+            ]
+        );
+
+        let path: VectorFst<TropicalWeight> = shortest_path(&fst)?;
+        let paths: Vec<_> = path.paths_iter().collect();
+        assert_eq!(paths.len(), 1);
+        assert_eq!(paths[0].ilabels, vec![2, 3]);
+        assert_eq!(paths[0].weight, TropicalWeight::new(-1.0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_shortest_distance_negative_cycle() -> Result<()> {
+        let mut fst = VectorFst::<TropicalWeight>::new();
+        fst.add_states(3);
+        fst.set_start(0)?;
+        fst.set_final(2, TropicalWeight::one())?;
+        fst.add_tr(0, Tr::new(1, 1, 1.0, 1))?;
+        fst.add_tr(1, Tr::new(2, 2, 1.0, 2))?;
+        fst.add_tr(2, Tr::new(3, 3, -3.0, 1))?;
+
+        assert!(shortest_distance(&fst, false).is_err());
+        assert!(shortest_distance(&fst, true).is_err());
+        assert!(shortest_path::<_, _, VectorFst<_>>(&fst).is_err());
+
+        // The same cycle with a positive weight is fine.
+        fst.tr_iter_mut(2)?
+            .set_weight(0, TropicalWeight::new(3.0))?;
+        assert!(shortest_distance(&fst, false).is_ok());
+        assert!(shortest_path::<_, _, VectorFst<_>>(&fst).is_ok());
+        Ok(())
+    }
+
+    #[cfg(feature = "rayon")]
+    fn build_acyclic_fst<W: Semiring<Type = f32>>() -> Result<VectorFst<W>> {
+        let mut fst = VectorFst::<W>::new();
+        fst.add_states(6);
+        fst.set_start(0)?;
+        fst.add_tr(0, Tr::new(1, 1, W::new(0.5), 1))?;
+        fst.add_tr(0, Tr::new(2, 2, W::new(1.5), 2))?;
+        fst.add_tr(1, Tr::new(3, 3, W::new(2.0), 2))?;
+        fst.add_tr(1, Tr::new(4, 4, W::new(0.3), 3))?;
+        fst.add_tr(2, Tr::new(5, 5, W::new(1.2), 3))?;
+        fst.add_tr(3, Tr::new(6, 6, W::new(0.7), 4))?;
+        fst.add_tr(5, Tr::new(7, 7, W::new(0.1), 4))?;
+        fst.set_final(4, W::new(0.2))?;
+        Ok(fst)
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_shortest_distance_parallel() -> Result<()> {
+        use crate::semirings::LogWeight;
+
+        let fst = build_acyclic_fst::<TropicalWeight>()?;
+        for reverse in &[false, true] {
+            assert_eq!(
+                shortest_distance_parallel(&fst, *reverse)?,
+                shortest_distance(&fst, *reverse)?
+            );
+        }
+
+        let fst = build_acyclic_fst::<LogWeight>()?;
+        for reverse in &[false, true] {
+            assert_eq!(
+                shortest_distance_parallel(&fst, *reverse)?,
+                shortest_distance(&fst, *reverse)?
+            );
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_shortest_distance_parallel_cyclic() -> Result<()> {
+        let mut fst = VectorFst::<TropicalWeight>::new();
+        fst.add_states(3);
+        fst.set_start(0)?;
+        fst.add_tr(0, Tr::new(1, 1, 1.0, 1))?;
+        fst.add_tr(1, Tr::new(2, 2, 2.0, 0))?;
+        fst.add_tr(1, Tr::new(3, 3, 3.0, 2))?;
+        fst.set_final(2, 0.0)?;
+
+        let distance = shortest_distance_parallel(&fst, false)?;
+        assert_eq!(distance, shortest_distance(&fst, false)?);
+        assert_eq!(distance[2], TropicalWeight::new(4.0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_shortest_distance_with_queue() -> Result<()> {
+        use crate::algorithms::queues::{
+            AutoQueue, FifoQueue, LifoQueue, StateOrderQueue, TopOrderQueue,
+        };
+        use crate::semirings::LogWeight;
+
+        let mut fst = VectorFst::<LogWeight>::new();
+        fst.add_states(5);
+        fst.set_start(0)?;
+        fst.add_tr(0, Tr::new(1, 1, 0.5, 1))?;
+        fst.add_tr(0, Tr::new(2, 2, 1.5, 2))?;
+        fst.add_tr(1, Tr::new(3, 3, 2.0, 2))?;
+        fst.add_tr(1, Tr::new(4, 4, 0.3, 3))?;
+        fst.add_tr(2, Tr::new(5, 5, 1.2, 3))?;
+        fst.add_tr(3, Tr::new(6, 6, 0.7, 4))?;
+        fst.set_final(4, 0.2)?;
+
+        let expected = shortest_distance(&fst, false)?;
+        let config = ShortestDistanceConfig::default();
+        let tr_filter = AnyTrFilter {};
+        let check = |distance: Vec<LogWeight>| {
+            assert_eq!(distance.len(), expected.len());
+            for (d, e) in distance.iter().zip(expected.iter()) {
+                assert!(d.approx_equal(e, config.delta), "{:?} != {:?}", d, e);
+            }
+        };
+        check(shortest_distance_with_queue(
+            &fst,
+            FifoQueue::default(),
+            config,
+        )?);
+        check(shortest_distance_with_queue(
+            &fst,
+            LifoQueue::default(),
+            config,
+        )?);
+        check(shortest_distance_with_queue(
+            &fst,
+            StateOrderQueue::default(),
+            config,
+        )?);
+        check(shortest_distance_with_queue(
+            &fst,
+            TopOrderQueue::new(&fst, &tr_filter),
+            config,
+        )?);
+        check(shortest_distance_with_queue(
+            &fst,
+            AutoQueue::new(&fst, None, &tr_filter)?,
+            config,
+        )?);
+        Ok(())
+    }
+}
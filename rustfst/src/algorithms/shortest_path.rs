@@ -1,32 +1,56 @@
 use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, VecDeque};
 
 use anyhow::Result;
 use unsafe_unwrap::UnsafeUnwrap;
 
-use crate::algorithms::determinize::determinize_with_distance;
+use crate::algorithms::determinize::{determinize, determinize_with_distance};
 use crate::algorithms::queues::AutoQueue;
+use crate::algorithms::rm_epsilon::rm_epsilon;
+use crate::algorithms::shortest_distance::ensure_no_negative_cycle;
 use crate::algorithms::tr_filters::AnyTrFilter;
 use crate::algorithms::{
-    connect, reverse, shortest_distance_with_config, Queue, ShortestDistanceConfig,
+    connect, fst_convert_from_ref, project, reverse, shortest_distance_with_config, ProjectType,
+    Queue, ShortestDistanceConfig,
 };
 use crate::fst_impls::VectorFst;
+use crate::fst_path::FstPath;
 use crate::fst_properties::mutable_properties::shortest_path_properties;
 use crate::fst_properties::FstProperties;
-use crate::fst_traits::{CoreFst, ExpandedFst, MutableFst};
+use crate::fst_traits::{CoreFst, ExpandedFst, Fst, MutableFst};
 use crate::semirings::{
     ReverseBack, Semiring, SemiringProperties, WeaklyDivisibleSemiring, WeightQuantize,
 };
 use crate::Tr;
-use crate::{StateId, Trs, KSHORTESTDELTA};
+use crate::{Label, StateId, Trs, EPS_LABEL, KSHORTESTDELTA};
 use bitflags::_core::fmt::Formatter;
 use std::fmt::Debug;
 
+/// How to choose between several paths with the same weight.
+#[derive(Debug, Clone, Copy, PartialOrd, PartialEq, Default)]
+pub enum TieBreak {
+    /// The path found first by the algorithm is kept.
+    #[default]
+    Disabled,
+    /// The path whose sequence of labels is the smallest is kept, the labels of a transition
+    /// being compared by input label, then output label. A path is smaller than its
+    /// extensions. Among the paths with the same labels, the one whose sequence of state ids
+    /// is the smallest is kept. Only the paths with less transitions than the number of
+    /// states are considered.
+    Lexicographic,
+}
+
 /// Configuration for N-shortest path computation
 #[derive(Debug, Clone, Copy, PartialOrd, PartialEq)]
 pub struct ShortestPathConfig {
     pub delta: f32,
     pub nshortest: usize,
     pub unique: bool,
+    /// Only used when `nshortest == 1`. With several paths, the paths with the same weight
+    /// are ordered by the exploration of the FST, which only depends on the input FST so that
+    /// the result is reproducible.
+    pub tie_break: TieBreak,
 }
 
 impl Default for ShortestPathConfig {
@@ -35,6 +59,7 @@ impl Default for ShortestPathConfig {
             delta: KSHORTESTDELTA,
             nshortest: 1,
             unique: false,
+            tie_break: TieBreak::default(),
         }
     }
 }
@@ -45,6 +70,7 @@ impl ShortestPathConfig {
             delta,
             nshortest,
             unique,
+            tie_break: TieBreak::default(),
         }
     }
 
@@ -59,6 +85,10 @@ impl ShortestPathConfig {
     pub fn with_unique(self, unique: bool) -> Self {
         Self { unique, ..self }
     }
+
+    pub fn with_tie_break(self, tie_break: TieBreak) -> Self {
+        Self { tie_break, ..self }
+    }
 }
 
 /// Create an FST containing the single shortest path in the input
@@ -92,6 +122,9 @@ where
 /// FST. The n-shortest paths are the n-lowest weight paths w.r.t. the
 /// natural semiring order.
 ///
+/// Negative weights are supported but an error is returned if the FST contains
+/// a negative weight cycle, see [`shortest_distance_with_config`](fn.shortest_distance_with_config.html).
+///
 /// # Example
 ///
 /// ## Input
@@ -123,6 +156,12 @@ where
         return Ok(FO::new());
     }
 
+    if nshortest == 1 && config.tie_break == TieBreak::Lexicographic {
+        let mut fst_res: FO = lexicographic_shortest_path(ifst, delta)?;
+        fst_res.set_symts_from_fst(ifst);
+        return Ok(fst_res);
+    }
+
     if nshortest == 1 {
         let mut parent = vec![];
         let mut f_parent = None;
@@ -190,6 +229,7 @@ where
     }
     let mut enqueued = vec![];
     let mut queue = AutoQueue::new(ifst, None, &AnyTrFilter {})?;
+    ensure_no_negative_cycle(ifst, KSHORTESTDELTA)?;
     let source = unsafe { start.unsafe_unwrap() };
     let mut f_distance = W::zero();
     distance.clear();
@@ -283,6 +323,206 @@ where
     Ok(ofst)
 }
 
+/// Single shortest path using `TieBreak::Lexicographic`. Only the transitions belonging to a
+/// shortest path are considered. Among the paths they form, the one with the smallest sequence
+/// of labels is kept, then the one with the smallest sequence of state ids. The sets of states
+/// reached by the smallest label sequence are explored from the start state, ending as soon as
+/// one of them is final : a prefix is smaller than its extensions. The paths are limited to
+/// `num_states - 1` transitions so that the walk always ends, even in the presence of cycles
+/// with a weight of one(), which would otherwise allow ever smaller label sequences.
+fn lexicographic_shortest_path<W, FI, FO>(ifst: &FI, delta: f32) -> Result<FO>
+where
+    W: Semiring,
+    FI: ExpandedFst<W>,
+    FO: MutableFst<W>,
+{
+    let mut ofst = FO::new();
+    let start = match ifst.start() {
+        Some(start) => start,
+        None => return Ok(ofst),
+    };
+    if !W::properties().contains(SemiringProperties::PATH | SemiringProperties::RIGHT_SEMIRING) {
+        bail!(
+            "SingleShortestPath: Weight needs to have the path property and be right distributive"
+        )
+    }
+    // Shortest distance from each state to the final states.
+    let rdistance = shortest_distance_with_config(ifst, true, ShortestDistanceConfig::new(delta))?;
+    if rdistance
+        .get(start as usize)
+        .map_or(true, |best| best.is_zero())
+    {
+        return Ok(ofst);
+    }
+
+    // Number of transitions of the shortest paths from each co-accessible state to a final
+    // state, with the least transitions. Computed by a backward breadth-first search over the
+    // transitions belonging to a shortest path.
+    let num_states = ifst.num_states();
+    let mut predecessors = vec![vec![]; num_states];
+    let mut num_trs_to_final: Vec<Option<usize>> = vec![None; num_states];
+    let mut queue = VecDeque::new();
+    for state in 0..(num_states as StateId) {
+        if rdistance[state as usize].is_zero() {
+            continue;
+        }
+        if is_final_on_shortest_path(ifst, state, &rdistance, delta)? {
+            num_trs_to_final[state as usize] = Some(0);
+            queue.push_back(state);
+        }
+        for tr in ifst.get_trs(state)?.trs() {
+            if is_on_shortest_path(tr, state, &rdistance, delta)? {
+                predecessors[tr.nextstate as usize].push(state);
+            }
+        }
+    }
+    while let Some(state) = queue.pop_front() {
+        let n = num_trs_to_final[state as usize].unwrap();
+        for &pred in &predecessors[state as usize] {
+            if num_trs_to_final[pred as usize].is_none() {
+                num_trs_to_final[pred as usize] = Some(n + 1);
+                queue.push_back(pred);
+            }
+        }
+    }
+    // A state can be used if a final state can still be reached within the remaining budget.
+    let can_end_within = |state: StateId, budget: usize| {
+        num_trs_to_final[state as usize].map_or(false, |n| n <= budget)
+    };
+    let max_len = num_states - 1;
+    if !can_end_within(start, max_len) {
+        bail!("SingleShortestPath: Failed to backtrack the shortest path")
+    }
+
+    // Forward : sets of states reached by the smallest label sequence.
+    let mut layers: Vec<Vec<StateId>> = vec![vec![start]];
+    let mut labels: Vec<(Label, Label)> = vec![];
+    loop {
+        let current = layers.last().unwrap();
+        let mut ends = false;
+        for &state in current {
+            if is_final_on_shortest_path(ifst, state, &rdistance, delta)? {
+                ends = true;
+                break;
+            }
+        }
+        if ends {
+            break;
+        }
+        let budget = max_len - labels.len() - 1;
+        let mut best_label: Option<(Label, Label)> = None;
+        for &state in current {
+            for tr in ifst.get_trs(state)?.trs() {
+                if can_end_within(tr.nextstate, budget)
+                    && is_on_shortest_path(tr, state, &rdistance, delta)?
+                    && best_label.map_or(true, |l| (tr.ilabel, tr.olabel) < l)
+                {
+                    best_label = Some((tr.ilabel, tr.olabel));
+                }
+            }
+        }
+        // Every state of the layer can reach a final state within the budget.
+        let label = best_label.unwrap();
+        let mut next = vec![];
+        for &state in current {
+            for tr in ifst.get_trs(state)?.trs() {
+                if (tr.ilabel, tr.olabel) == label
+                    && can_end_within(tr.nextstate, budget)
+                    && is_on_shortest_path(tr, state, &rdistance, delta)?
+                {
+                    next.push(tr.nextstate);
+                }
+            }
+        }
+        next.sort_unstable();
+        next.dedup();
+        labels.push(label);
+        layers.push(next);
+    }
+
+    // Backward : only keep the states from which the label sequence can be completed.
+    let num_layers = layers.len();
+    let mut alive = vec![vec![]; num_layers];
+    for &state in &layers[num_layers - 1] {
+        if is_final_on_shortest_path(ifst, state, &rdistance, delta)? {
+            alive[num_layers - 1].push(state);
+        }
+    }
+    for idx in (0..num_layers - 1).rev() {
+        for &state in &layers[idx] {
+            for tr in ifst.get_trs(state)?.trs() {
+                if (tr.ilabel, tr.olabel) == labels[idx]
+                    && alive[idx + 1].binary_search(&tr.nextstate).is_ok()
+                    && is_on_shortest_path(tr, state, &rdistance, delta)?
+                {
+                    alive[idx].push(state);
+                    break;
+                }
+            }
+        }
+    }
+
+    // Forward again, following the smallest state ids.
+    let mut state = start;
+    let mut ostate = ofst.add_state();
+    ofst.set_start(ostate)?;
+    for idx in 0..num_layers - 1 {
+        let mut best_tr: Option<&Tr<W>> = None;
+        let trs = ifst.get_trs(state)?;
+        for tr in trs.trs() {
+            if (tr.ilabel, tr.olabel) == labels[idx]
+                && alive[idx + 1].binary_search(&tr.nextstate).is_ok()
+                && is_on_shortest_path(tr, state, &rdistance, delta)?
+                && best_tr.map_or(true, |b| tr.nextstate < b.nextstate)
+            {
+                best_tr = Some(tr);
+            }
+        }
+        // A transition exists by construction of alive.
+        let tr = best_tr.unwrap().clone();
+        state = tr.nextstate;
+        let next_ostate = ofst.add_state();
+        ofst.add_tr(
+            ostate,
+            Tr::new(tr.ilabel, tr.olabel, tr.weight, next_ostate),
+        )?;
+        ostate = next_ostate;
+    }
+    let final_weight = ifst.final_weight(state)?.unwrap();
+    ofst.set_final(ostate, final_weight)?;
+
+    ofst.set_properties_with_mask(
+        shortest_path_properties(ofst.properties(), true),
+        FstProperties::all_properties(),
+    );
+    Ok(ofst)
+}
+
+fn is_final_on_shortest_path<W: Semiring, F: ExpandedFst<W>>(
+    fst: &F,
+    state: StateId,
+    rdistance: &[W],
+    delta: f32,
+) -> Result<bool> {
+    Ok(fst
+        .final_weight(state)?
+        .map_or(false, |w| w.approx_equal(&rdistance[state as usize], delta)))
+}
+
+fn is_on_shortest_path<W: Semiring>(
+    tr: &Tr<W>,
+    state: StateId,
+    rdistance: &[W],
+    delta: f32,
+) -> Result<bool> {
+    let next_distance = &rdistance[tr.nextstate as usize];
+    Ok(!next_distance.is_zero()
+        && tr
+            .weight
+            .times(next_distance)?
+            .approx_equal(&rdistance[state as usize], delta))
+}
+
 pub fn natural_less<W: Semiring>(w1: &W, w2: &W) -> Result<bool> {
     Ok((&w1.plus(w2)? == w1) && (w1 != w2))
 }
@@ -521,3 +761,790 @@ where
     );
     Ok(ofst)
 }
+
+/// Lazily enumerates the paths of an FST by increasing weight w.r.t. the
+/// natural semiring order : the best path first, then the second best, etc.
+///
+/// The shortest distance from every state to the final states is computed once,
+/// when the first path is requested, unless the FST trivially has no path (no
+/// start state, or a start state that is neither final nor has any transition).
+/// Then, as it is used as an exact estimate of the remaining weight, each path is
+/// obtained by only expanding the states along the paths that are at least as good
+/// as it : pulling `k` paths doesn't require computing all the paths of the FST
+/// nor the `k`-shortest paths FST upfront.
+///
+/// Paths with equal weights are yielded in an unspecified but deterministic order. Paths
+/// are not unique w.r.t. their labels : two different paths with the same labels are
+/// both yielded. The weight needs to have the path property.
+///
+/// # Example
+///
+/// ```
+/// # use anyhow::Result;
+/// # use rustfst::fst_impls::VectorFst;
+/// # use rustfst::fst_traits::MutableFst;
+/// # use rustfst::semirings::{Semiring, TropicalWeight};
+/// # use rustfst::algorithms::shortest_path_iter;
+/// # use rustfst::Tr;
+/// # fn main() -> Result<()> {
+/// let mut fst = VectorFst::<TropicalWeight>::new();
+/// fst.add_states(2);
+/// fst.set_start(0)?;
+/// fst.set_final(1, TropicalWeight::one())?;
+/// fst.add_tr(0, Tr::new(1, 1, 3.0, 1))?;
+/// fst.add_tr(0, Tr::new(2, 2, 1.0, 1))?;
+/// fst.add_tr(0, Tr::new(3, 3, 2.0, 1))?;
+///
+/// let mut paths = shortest_path_iter(&fst);
+/// assert_eq!(paths.next().unwrap()?.ilabels, vec![2]);
+/// assert_eq!(paths.next().unwrap()?.ilabels, vec![3]);
+/// # Ok(())
+/// # }
+/// ```
+pub fn shortest_path_iter<'a, W, F>(fst: &'a F) -> impl Iterator<Item = Result<FstPath<W>>> + 'a
+where
+    W: Semiring,
+    F: ExpandedFst<W>,
+{
+    ShortestPathIter {
+        fst,
+        distance: None,
+        nodes: vec![],
+        heap: BinaryHeap::new(),
+        num_candidates: 0,
+        done: false,
+    }
+}
+
+/// Returns the `k` best distinct output strings of an FST along with their weights, by
+/// increasing weight w.r.t. the natural semiring order.
+///
+/// The FST is projected on its output labels, epsilons are removed and the result is
+/// determinized, so that two paths with the same output string are merged into a single
+/// entry whose weight is the sum (w.r.t. `plus`) of their weights. The `k` shortest paths
+/// of the determinized FST are then extracted. As determinization is involved, the FST
+/// must be determinizable once projected. The weight needs to have the path property.
+///
+/// # Example
+///
+/// ```
+/// # use anyhow::Result;
+/// # use rustfst::fst_impls::VectorFst;
+/// # use rustfst::fst_traits::MutableFst;
+/// # use rustfst::semirings::{Semiring, TropicalWeight};
+/// # use rustfst::algorithms::nbest_outputs;
+/// # use rustfst::Tr;
+/// # fn main() -> Result<()> {
+/// let mut fst = VectorFst::<TropicalWeight>::new();
+/// fst.add_states(2);
+/// fst.set_start(0)?;
+/// fst.set_final(1, TropicalWeight::one())?;
+/// fst.add_tr(0, Tr::new(1, 10, 3.0, 1))?;
+/// fst.add_tr(0, Tr::new(2, 10, 1.0, 1))?;
+/// fst.add_tr(0, Tr::new(3, 11, 2.0, 1))?;
+///
+/// assert_eq!(
+///     nbest_outputs(&fst, 3)?,
+///     vec![
+///         (vec![10], TropicalWeight::new(1.0)),
+///         (vec![11], TropicalWeight::new(2.0))
+///     ]
+/// );
+/// # Ok(())
+/// # }
+/// ```
+pub fn nbest_outputs<W, F>(fst: &F, k: usize) -> Result<Vec<(Vec<Label>, W)>>
+where
+    F: ExpandedFst<W>,
+    W: WeaklyDivisibleSemiring
+        + WeightQuantize
+        + Into<<W as Semiring>::ReverseWeight>
+        + From<<W as Semiring>::ReverseWeight>,
+    <W as Semiring>::ReverseWeight: WeightQuantize + WeaklyDivisibleSemiring,
+{
+    let mut ofst: VectorFst<W> = fst_convert_from_ref(fst);
+    project(&mut ofst, ProjectType::ProjectOutput);
+    rm_epsilon(&mut ofst)?;
+    let ofst: VectorFst<W> = determinize(&ofst)?;
+
+    let config = ShortestPathConfig::default()
+        .with_nshortest(k)
+        .with_unique(true);
+    let paths_fst: VectorFst<W> = shortest_path_with_config(&ofst, config)?;
+
+    let mut outputs: Vec<(Vec<Label>, W)> = Vec::with_capacity(k);
+    for path in paths_fst.paths_iter() {
+        let mut idx = outputs.len();
+        while idx > 0 && natural_less(&path.weight, &outputs[idx - 1].1)? {
+            idx -= 1;
+        }
+        outputs.insert(idx, (path.olabels, path.weight));
+    }
+    Ok(outputs)
+}
+
+/// Prunes the FST so that it only keeps the transitions and final weights lying on a path
+/// whose weight is at most the weight of the `n`-th best distinct string, as computed by
+/// [`shortest_path_with_config`](fn.shortest_path_with_config.html) with `unique` set.
+///
+/// Contrary to the n-shortest paths FST, the result shares the structure of the input FST :
+/// it is a sub-FST whose `n` best strings are the `n` best strings of the input, with the
+/// same weights. As paths can be recombined through the shared states, the result may also
+/// contain worse strings. Strings whose weight equals the weight of the `n`-th best string
+/// are kept too. The result is connected. The weight needs to have the path property.
+///
+/// # Example
+///
+/// ```
+/// # use anyhow::Result;
+/// # use rustfst::fst_impls::VectorFst;
+/// # use rustfst::fst_traits::{CoreFst, MutableFst};
+/// # use rustfst::semirings::{Semiring, TropicalWeight};
+/// # use rustfst::algorithms::prune_to_nbest;
+/// # use rustfst::{Tr, Trs};
+/// # fn main() -> Result<()> {
+/// let mut fst = VectorFst::<TropicalWeight>::new();
+/// fst.add_states(2);
+/// fst.set_start(0)?;
+/// fst.set_final(1, TropicalWeight::one())?;
+/// fst.add_tr(0, Tr::new(1, 1, 3.0, 1))?;
+/// fst.add_tr(0, Tr::new(2, 2, 1.0, 1))?;
+/// fst.add_tr(0, Tr::new(3, 3, 2.0, 1))?;
+///
+/// let pruned = prune_to_nbest(&fst, 2)?;
+/// assert_eq!(
+///     pruned.get_trs(0)?.trs(),
+///     &[Tr::new(2, 2, 1.0, 1), Tr::new(3, 3, 2.0, 1)]
+/// );
+/// # Ok(())
+/// # }
+/// ```
+pub fn prune_to_nbest<W, F>(fst: &F, n: usize) -> Result<VectorFst<W>>
+where
+    F: ExpandedFst<W>,
+    W: Semiring
+        + WeightQuantize
+        + Into<<W as Semiring>::ReverseWeight>
+        + From<<W as Semiring>::ReverseWeight>,
+    <W as Semiring>::ReverseWeight: WeightQuantize + WeaklyDivisibleSemiring,
+{
+    let config = ShortestPathConfig::default()
+        .with_nshortest(n)
+        .with_unique(true);
+    let paths_fst: VectorFst<W> = shortest_path_with_config(fst, config)?;
+
+    let mut threshold: Option<W> = None;
+    for path in paths_fst.paths_iter() {
+        threshold = match threshold {
+            Some(t) if !natural_less(&t, &path.weight)? => Some(t),
+            _ => Some(path.weight),
+        };
+    }
+
+    let mut ofst: VectorFst<W> = fst_convert_from_ref(fst);
+    let threshold = match threshold {
+        Some(t) => t,
+        None => {
+            ofst.del_all_states();
+            return Ok(ofst);
+        }
+    };
+    let within_threshold = |w: W| -> Result<bool> {
+        Ok(!natural_less(&threshold, &w)? || w.approx_equal(&threshold, KSHORTESTDELTA))
+    };
+
+    let distance = shortest_distance_with_config(fst, false, ShortestDistanceConfig::default())?;
+    let rdistance = shortest_distance_with_config(fst, true, ShortestDistanceConfig::default())?;
+    let zero = W::zero();
+    let get_distance = |d: &[W], s: StateId| d.get(s as usize).cloned().unwrap_or_else(W::zero);
+
+    for state in 0..(ofst.num_states() as StateId) {
+        let d = get_distance(&distance, state);
+        if d == zero {
+            continue;
+        }
+        let trs = ofst.pop_trs(state)?;
+        for tr in trs {
+            let w = d
+                .times(&tr.weight)?
+                .times(get_distance(&rdistance, tr.nextstate))?;
+            if within_threshold(w)? {
+                ofst.add_tr(state, tr)?;
+            }
+        }
+        if let Some(final_weight) = ofst.final_weight(state)? {
+            if !within_threshold(d.times(final_weight)?)? {
+                ofst.delete_final_weight(state)?;
+            }
+        }
+    }
+
+    connect(&mut ofst)?;
+    Ok(ofst)
+}
+
+/// Finds the shortest path of the FST and returns the sum of the feature vectors computed by
+/// `per_tr` on each of its transitions.
+///
+/// An error is returned if the FST has no accepting path or if the feature vectors don't all
+/// have the same length. Empty feature vectors are ignored. See
+/// [`best_path_features_with_final`](fn.best_path_features_with_final.html) to also take
+/// the final weight of the path into account.
+///
+/// # Example
+///
+/// ```
+/// # use anyhow::Result;
+/// # use rustfst::fst_impls::VectorFst;
+/// # use rustfst::fst_traits::MutableFst;
+/// # use rustfst::semirings::{Semiring, TropicalWeight};
+/// # use rustfst::algorithms::best_path_features;
+/// # use rustfst::Tr;
+/// # fn main() -> Result<()> {
+/// let mut fst = VectorFst::<TropicalWeight>::new();
+/// fst.add_states(3);
+/// fst.set_start(0)?;
+/// fst.set_final(2, TropicalWeight::one())?;
+/// fst.add_tr(0, Tr::new(1, 1, 1.0, 1))?;
+/// fst.add_tr(0, Tr::new(2, 2, 3.0, 1))?;
+/// fst.add_tr(1, Tr::new(3, 3, 2.0, 2))?;
+///
+/// // Number of transitions and total weight.
+/// let features = best_path_features(&fst, |tr| vec![1.0, *tr.weight.value()])?;
+/// assert_eq!(features, vec![2.0, 3.0]);
+/// # Ok(())
+/// # }
+/// ```
+pub fn best_path_features<W, F, FT>(fst: &F, per_tr: FT) -> Result<Vec<f32>>
+where
+    F: ExpandedFst<W>,
+    W: Semiring
+        + WeightQuantize
+        + Into<<W as Semiring>::ReverseWeight>
+        + From<<W as Semiring>::ReverseWeight>,
+    <W as Semiring>::ReverseWeight: WeightQuantize + WeaklyDivisibleSemiring,
+    FT: Fn(&Tr<W>) -> Vec<f32>,
+{
+    best_path_features_with_final(fst, per_tr, |_: &W| vec![])
+}
+
+/// Same as [`best_path_features`](fn.best_path_features.html) but the feature vector
+/// computed by `per_final` on the final weight of the shortest path is added to the total.
+pub fn best_path_features_with_final<W, F, FT, FF>(
+    fst: &F,
+    per_tr: FT,
+    per_final: FF,
+) -> Result<Vec<f32>>
+where
+    F: ExpandedFst<W>,
+    W: Semiring
+        + WeightQuantize
+        + Into<<W as Semiring>::ReverseWeight>
+        + From<<W as Semiring>::ReverseWeight>,
+    <W as Semiring>::ReverseWeight: WeightQuantize + WeaklyDivisibleSemiring,
+    FT: Fn(&Tr<W>) -> Vec<f32>,
+    FF: Fn(&W) -> Vec<f32>,
+{
+    let path_fst: VectorFst<W> = shortest_path(fst)?;
+    let mut state = match path_fst.start() {
+        Some(s) => s,
+        None => bail!("BestPathFeatures : The FST has no accepting path"),
+    };
+
+    let mut features = vec![];
+    // The shortest path FST is a single path starting at the start state.
+    for _ in 0..path_fst.num_states() {
+        let trs = path_fst.get_trs(state)?;
+        match trs.trs() {
+            [] => {
+                let final_weight = path_fst.final_weight(state)?.ok_or_else(|| {
+                    format_err!("BestPathFeatures : The shortest path has no final state")
+                })?;
+                add_features(&mut features, per_final(&final_weight))?;
+                return Ok(features);
+            }
+            [tr] => {
+                add_features(&mut features, per_tr(tr))?;
+                state = tr.nextstate;
+            }
+            _ => bail!("BestPathFeatures : The shortest path FST should be a single path"),
+        }
+    }
+    bail!("BestPathFeatures : The shortest path FST should be acyclic")
+}
+
+fn add_features(total: &mut Vec<f32>, features: Vec<f32>) -> Result<()> {
+    if features.is_empty() {
+        return Ok(());
+    }
+    if total.is_empty() {
+        *total = features;
+        return Ok(());
+    }
+    if total.len() != features.len() {
+        bail!(
+            "BestPathFeatures : Feature vectors must have the same length : {} != {}",
+            total.len(),
+            features.len()
+        );
+    }
+    for (t, f) in total.iter_mut().zip(features) {
+        *t += f;
+    }
+    Ok(())
+}
+
+/// Partial or complete path waiting to be expanded by `ShortestPathIter`.
+struct PathCandidate<W> {
+    /// Weight of the path, times the shortest distance to a final state.
+    priority: W,
+    /// Weight of the path.
+    weight: W,
+    /// Last state of the path. `None` if the path is complete (final weight included).
+    state: Option<StateId>,
+    /// Last transition of the path, as an index in `ShortestPathIter::nodes`.
+    node: Option<usize>,
+    /// Insertion order, used to break ties.
+    id: usize,
+}
+
+impl<W: Semiring> Ord for PathCandidate<W> {
+    // Greater means better, as `BinaryHeap` is a max-heap.
+    fn cmp(&self, other: &Self) -> Ordering {
+        if natural_less(&self.priority, &other.priority).unwrap_or(false) {
+            Ordering::Greater
+        } else if natural_less(&other.priority, &self.priority).unwrap_or(false) {
+            Ordering::Less
+        } else {
+            // Complete paths first, then the oldest candidates.
+            other
+                .state
+                .is_some()
+                .cmp(&self.state.is_some())
+                .then_with(|| other.id.cmp(&self.id))
+        }
+    }
+}
+
+impl<W: Semiring> PartialOrd for PathCandidate<W> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<W: Semiring> PartialEq for PathCandidate<W> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<W: Semiring> Eq for PathCandidate<W> {}
+
+struct ShortestPathIter<'a, W: Semiring, F: ExpandedFst<W>> {
+    fst: &'a F,
+    /// Shortest distance from every state to the final states.
+    distance: Option<Vec<W>>,
+    /// Transitions of the explored paths : (previous node, ilabel, olabel).
+    nodes: Vec<(Option<usize>, Label, Label)>,
+    heap: BinaryHeap<PathCandidate<W>>,
+    num_candidates: usize,
+    done: bool,
+}
+
+impl<'a, W: Semiring, F: ExpandedFst<W>> ShortestPathIter<'a, W, F> {
+    fn push(&mut self, priority: W, weight: W, state: Option<StateId>, node: Option<usize>) {
+        self.heap.push(PathCandidate {
+            priority,
+            weight,
+            state,
+            node,
+            id: self.num_candidates,
+        });
+        self.num_candidates += 1;
+    }
+
+    fn init(&mut self) -> Result<()> {
+        if !W::properties().contains(SemiringProperties::PATH | SemiringProperties::SEMIRING) {
+            bail!("ShortestPathIter: Weight needs to have the path property and be distributive")
+        }
+        // Without a start state or if the start state is a dead end, there is no path :
+        // no need to compute the distances.
+        let start = match self.fst.start() {
+            Some(start) => start,
+            None => {
+                self.distance = Some(vec![]);
+                return Ok(());
+            }
+        };
+        let start_is_final = self
+            .fst
+            .final_weight(start)?
+            .map_or(false, |w| !w.is_zero());
+        if !start_is_final && self.fst.num_trs(start)? == 0 {
+            self.distance = Some(vec![]);
+            return Ok(());
+        }
+        let distance = shortest_distance_with_config(
+            self.fst,
+            true,
+            ShortestDistanceConfig::new(KSHORTESTDELTA),
+        )?;
+        if let Some(d) = distance.get(start as usize) {
+            if !d.is_zero() {
+                self.push(d.clone(), W::one(), Some(start), None);
+            }
+        }
+        self.distance = Some(distance);
+        Ok(())
+    }
+
+    fn path(&self, node: Option<usize>, weight: W) -> FstPath<W> {
+        let mut ilabels = vec![];
+        let mut olabels = vec![];
+        let mut node = node;
+        while let Some(idx) = node {
+            let (parent, ilabel, olabel) = self.nodes[idx];
+            if ilabel != EPS_LABEL {
+                ilabels.push(ilabel);
+            }
+            if olabel != EPS_LABEL {
+                olabels.push(olabel);
+            }
+            node = parent;
+        }
+        ilabels.reverse();
+        olabels.reverse();
+        FstPath::new(ilabels, olabels, weight)
+    }
+
+    fn next_path(&mut self) -> Result<Option<FstPath<W>>> {
+        if self.distance.is_none() {
+            self.init()?;
+        }
+        while let Some(candidate) = self.heap.pop() {
+            let state = match candidate.state {
+                Some(state) => state,
+                None => return Ok(Some(self.path(candidate.node, candidate.weight))),
+            };
+            if let Some(final_weight) = self.fst.final_weight(state)? {
+                if !final_weight.is_zero() {
+                    let weight = candidate.weight.times(&final_weight)?;
+                    self.push(weight.clone(), weight, None, candidate.node);
+                }
+            }
+            for tr in self.fst.get_trs(state)?.trs() {
+                let d = &self.distance.as_ref().unwrap()[tr.nextstate as usize];
+                if d.is_zero() {
+                    continue;
+                }
+                let weight = candidate.weight.times(&tr.weight)?;
+                let priority = weight.times(d)?;
+                self.nodes.push((candidate.node, tr.ilabel, tr.olabel));
+                let node = Some(self.nodes.len() - 1);
+                self.push(priority, weight, Some(tr.nextstate), node);
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl<'a, W: Semiring, F: ExpandedFst<W>> Iterator for ShortestPathIter<'a, W, F> {
+    type Item = Result<FstPath<W>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        match self.next_path() {
+            Ok(Some(path)) => Some(Ok(path)),
+            Ok(None) => {
+                self.done = true;
+                None
+            }
+            Err(e) => {
+                self.done = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::state_sort;
+    use crate::semirings::TropicalWeight;
+
+    #[test]
+    fn test_shortest_path_iter() -> Result<()> {
+        let mut fst = VectorFst::<TropicalWeight>::new();
+        fst.add_states(4);
+        fst.set_start(0)?;
+        fst.add_tr(0, Tr::new(1, 1, 1.0, 1))?;
+        fst.add_tr(0, Tr::new(2, 2, 4.0, 1))?;
+        fst.add_tr(0, Tr::new(3, 3, 2.5, 2))?;
+        fst.add_tr(1, Tr::new(4, 4, 1.0, 3))?;
+        fst.add_tr(1, Tr::new(5, 5, 3.0, 3))?;
+        fst.add_tr(2, Tr::new(6, 6, 0.0, 3))?;
+        fst.add_tr(2, Tr::new(7, 7, 1.0, 1))?;
+        fst.set_final(3, 0.5)?;
+        fst.set_final(1, 10.0)?;
+
+        let paths = shortest_path_iter(&fst)
+            .take(3)
+            .collect::<Result<Vec<_>>>()?;
+
+        let nbest: VectorFst<_> =
+            shortest_path_with_config(&fst, ShortestPathConfig::default().with_nshortest(3))?;
+        let mut expected: Vec<_> = nbest.paths_iter().collect();
+        expected.sort_by(|p1, p2| p1.weight.value().partial_cmp(p2.weight.value()).unwrap());
+
+        assert_eq!(paths, expected);
+        for (p1, p2) in paths.iter().zip(paths.iter().skip(1)) {
+            assert!(!natural_less(&p2.weight, &p1.weight)?);
+        }
+
+        // All the paths are eventually yielded.
+        assert_eq!(shortest_path_iter(&fst).count(), fst.paths_iter().count());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_shortest_path_iter_no_path() -> Result<()> {
+        let mut fst = VectorFst::<TropicalWeight>::new();
+        fst.add_states(2);
+        fst.add_tr(1, Tr::new(1, 1, 1.0, 0))?;
+        fst.set_final(0, TropicalWeight::one())?;
+        assert_eq!(shortest_path_iter(&fst).count(), 0);
+
+        fst.set_start(0)?;
+        assert_eq!(shortest_path_iter(&fst).count(), 1);
+
+        fst.delete_final_weight(0)?;
+        assert_eq!(shortest_path_iter(&fst).count(), 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_shortest_path_tie_break() -> Result<()> {
+        // Three paths with weight 1.0 : [3], [2, 5] and [1].
+        let mut fst = VectorFst::<TropicalWeight>::new();
+        fst.add_states(4);
+        fst.set_start(0)?;
+        fst.add_tr(0, Tr::new(3, 3, 1.0, 1))?;
+        fst.add_tr(0, Tr::new(2, 2, 0.5, 2))?;
+        fst.add_tr(0, Tr::new(1, 1, 1.0, 3))?;
+        fst.add_tr(2, Tr::new(5, 5, 0.5, 3))?;
+        fst.add_tr(0, Tr::new(4, 4, 2.0, 3))?;
+        fst.set_final(1, TropicalWeight::one())?;
+        fst.set_final(3, TropicalWeight::one())?;
+
+        let mut renamed = fst.clone();
+        state_sort(&mut renamed, &[2, 0, 3, 1])?;
+        let mut reordered = VectorFst::<TropicalWeight>::new();
+        reordered.add_states(4);
+        reordered.set_start(0)?;
+        for tr in fst.get_trs(0)?.trs().iter().rev() {
+            reordered.add_tr(0, tr.clone())?;
+        }
+        reordered.add_tr(2, Tr::new(5, 5, 0.5, 3))?;
+        reordered.set_final(1, TropicalWeight::one())?;
+        reordered.set_final(3, TropicalWeight::one())?;
+
+        let config = ShortestPathConfig::default().with_tie_break(TieBreak::Lexicographic);
+        for ifst in &[&fst, &renamed, &reordered] {
+            let path: VectorFst<_> = shortest_path_with_config(*ifst, config)?;
+            let paths: Vec<_> = path.paths_iter().collect();
+            assert_eq!(paths, vec![FstPath::new(vec![1], vec![1], 1.0.into())]);
+        }
+
+        // Without ties, the same path is returned with and without tie breaking.
+        fst.add_tr(0, Tr::new(6, 6, 0.25, 1))?;
+        let path: VectorFst<_> = shortest_path_with_config(&fst, config)?;
+        let expected: VectorFst<_> = shortest_path(&fst)?;
+        assert_eq!(
+            path.paths_iter().collect::<Vec<_>>(),
+            expected.paths_iter().collect::<Vec<_>>()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_shortest_path_tie_break_labels_then_states() -> Result<()> {
+        // Paths with weight 1.0 : [2] and [1, 5] twice, through the states 3 and 2.
+        let mut fst = VectorFst::<TropicalWeight>::new();
+        fst.add_states(5);
+        fst.set_start(0)?;
+        fst.add_tr(0, Tr::new(2, 2, 1.0, 4))?;
+        fst.add_tr(0, Tr::new(1, 1, 0.25, 3))?;
+        fst.add_tr(0, Tr::new(1, 1, 0.5, 2))?;
+        fst.add_tr(3, Tr::new(5, 5, 0.75, 4))?;
+        fst.add_tr(2, Tr::new(5, 5, 0.5, 4))?;
+        fst.set_final(4, TropicalWeight::one())?;
+
+        // The longest path has the smallest labels and the path through state 2 is kept.
+        let config = ShortestPathConfig::default().with_tie_break(TieBreak::Lexicographic);
+        let path: VectorFst<_> = shortest_path_with_config(&fst, config)?;
+        assert_eq!(path.num_states(), 3);
+        assert_eq!(path.get_trs(0)?.trs(), &[Tr::new(1, 1, 0.5, 1)]);
+        assert_eq!(path.get_trs(1)?.trs(), &[Tr::new(5, 5, 0.5, 2)]);
+
+        // A prefix is smaller than its extensions.
+        fst.set_final(2, 0.5)?;
+        fst.set_final(3, 0.75)?;
+        let path: VectorFst<_> = shortest_path_with_config(&fst, config)?;
+        let paths: Vec<_> = path.paths_iter().collect();
+        assert_eq!(paths, vec![FstPath::new(vec![1], vec![1], 1.0.into())]);
+        assert_eq!(path.get_trs(0)?.trs(), &[Tr::new(1, 1, 0.5, 1)]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_shortest_path_tie_break_one_weight_cycle() -> Result<()> {
+        // The smallest transition of state 0 leads to a cycle of weight one() which doesn't
+        // reach any final state.
+        let mut fst = VectorFst::<TropicalWeight>::new();
+        fst.add_states(3);
+        fst.set_start(0)?;
+        fst.add_tr(0, Tr::new(1, 1, 0.0, 1))?;
+        fst.add_tr(1, Tr::new(2, 2, 0.0, 0))?;
+        fst.add_tr(0, Tr::new(3, 3, 0.0, 2))?;
+        fst.set_final(2, TropicalWeight::one())?;
+
+        let config = ShortestPathConfig::default().with_tie_break(TieBreak::Lexicographic);
+        let path: VectorFst<_> = shortest_path_with_config(&fst, config)?;
+        let paths: Vec<_> = path.paths_iter().collect();
+        assert_eq!(paths, vec![FstPath::new(vec![3], vec![3], 0.0.into())]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_nshortest_unique_ties_are_reproducible() -> Result<()> {
+        // Non-deterministic acceptor where the strings [1, 2], [1, 3], [1, 4] and [1, 5] have
+        // the same weight, each of them being accepted by two paths.
+        let mut fst = VectorFst::<TropicalWeight>::new();
+        fst.add_states(6);
+        fst.set_start(0)?;
+        fst.set_final(5, TropicalWeight::one())?;
+        for s in 1..5 {
+            fst.add_tr(0, Tr::new(1, 1, 1.0, s))?;
+            for label in 2..6 {
+                let weight = if label == 2 { 2.0 } else { 1.0 };
+                fst.add_tr(s, Tr::new(label, label, weight, 5))?;
+            }
+        }
+
+        let config = ShortestPathConfig::default()
+            .with_nshortest(3)
+            .with_unique(true);
+        let reference: VectorFst<_> = shortest_path_with_config(&fst, config)?;
+        let mut paths: Vec<_> = reference.paths_iter().map(|p| p.ilabels).collect();
+        paths.sort();
+        paths.dedup();
+        assert_eq!(paths.len(), 3);
+        assert!(!paths.contains(&vec![1, 2]));
+
+        for _ in 0..20 {
+            let res: VectorFst<_> = shortest_path_with_config(&fst, config)?;
+            assert_eq!(res, reference);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_nbest_outputs_distinct_strings() -> Result<()> {
+        // Input paths 1 2 and 3 4 both output 10 11, input path 5 outputs 12.
+        let mut fst = VectorFst::<TropicalWeight>::new();
+        fst.add_states(5);
+        fst.set_start(0)?;
+        fst.set_final(4, 0.5)?;
+        fst.add_tr(0, Tr::new(1, 10, 2.0, 1))?;
+        fst.add_tr(1, Tr::new(2, 11, 2.0, 4))?;
+        fst.add_tr(0, Tr::new(3, 10, 1.0, 2))?;
+        fst.add_tr(2, Tr::new(4, EPS_LABEL, 1.0, 3))?;
+        fst.add_tr(3, Tr::new(EPS_LABEL, 11, 1.5, 4))?;
+        fst.add_tr(0, Tr::new(5, 12, 3.0, 4))?;
+
+        let outputs = nbest_outputs(&fst, 3)?;
+        assert_eq!(
+            outputs,
+            vec![
+                (vec![12], TropicalWeight::new(3.5)),
+                (vec![10, 11], TropicalWeight::new(4.0)),
+            ]
+        );
+        assert_eq!(
+            nbest_outputs(&fst, 1)?,
+            vec![(vec![12], TropicalWeight::new(3.5))]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_best_path_features() -> Result<()> {
+        let mut fst = VectorFst::<TropicalWeight>::new();
+        fst.add_states(4);
+        fst.set_start(0)?;
+        fst.set_final(2, 4.0)?;
+        fst.set_final(3, 0.5)?;
+        fst.add_tr(0, Tr::new(1, 1, 1.0, 1))?;
+        fst.add_tr(0, Tr::new(2, 2, 0.5, 2))?;
+        fst.add_tr(1, Tr::new(3, 3, 1.0, 3))?;
+        fst.add_tr(1, Tr::new(4, 4, 3.0, 2))?;
+
+        // Best path : 0 -1-> 1 -3-> 3, total weight 2.5.
+        let per_tr = |tr: &Tr<TropicalWeight>| vec![1.0, tr.ilabel as f32, *tr.weight.value()];
+        assert_eq!(best_path_features(&fst, per_tr)?, vec![2.0, 4.0, 2.0]);
+        assert_eq!(
+            best_path_features_with_final(&fst, per_tr, |w| vec![0.0, 0.0, *w.value()])?,
+            vec![2.0, 4.0, 2.5]
+        );
+
+        // Mismatching lengths.
+        assert!(best_path_features_with_final(&fst, per_tr, |_| vec![1.0]).is_err());
+
+        // No accepting path.
+        fst.delete_final_weight(2)?;
+        fst.delete_final_weight(3)?;
+        assert!(best_path_features(&fst, per_tr).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_prune_to_nbest() -> Result<()> {
+        // Lattice with 2 alternatives on each of the 3 positions.
+        let mut fst = VectorFst::<TropicalWeight>::new();
+        fst.add_states(5);
+        fst.set_start(0)?;
+        fst.set_final(3, TropicalWeight::one())?;
+        for s in 0..3 {
+            fst.add_tr(s, Tr::new(10 * s + 1, 10 * s + 1, 0.1 * s as f32, s + 1))?;
+            fst.add_tr(s, Tr::new(10 * s + 2, 10 * s + 2, 1.0 + s as f32, s + 1))?;
+        }
+        // Dead end.
+        fst.add_tr(0, Tr::new(5, 5, 0.0, 4))?;
+
+        for n in 1..=8 {
+            let pruned = prune_to_nbest(&fst, n)?;
+            assert_eq!(nbest_outputs(&pruned, n)?, nbest_outputs(&fst, n)?);
+
+            let mut connected = pruned.clone();
+            connect(&mut connected)?;
+            assert_eq!(connected, pruned);
+            // Weights of the strings : 0.3, 1.3, 2.2, 3.1, ...
+            if n <= 3 {
+                assert!(pruned.num_trs(0)? + pruned.num_trs(1)? + pruned.num_trs(2)? < 6);
+            }
+        }
+
+        assert_eq!(prune_to_nbest(&fst, 0)?.num_states(), 0);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,222 @@
+use anyhow::Result;
+
+use crate::fst_impls::VectorFst;
+use crate::fst_traits::{ExpandedFst, MutableFst};
+use crate::semirings::Semiring;
+use crate::{StateId, Tr, Trs};
+
+/// Configuration for splitting an FST into its connected components.
+#[derive(Clone, Debug, Copy, PartialOrd, PartialEq)]
+pub struct SplitComponentsConfig {
+    /// Whether the components that don't contain any final state are returned.
+    pub keep_non_final: bool,
+}
+
+impl Default for SplitComponentsConfig {
+    fn default() -> Self {
+        Self {
+            keep_non_final: true,
+        }
+    }
+}
+
+impl SplitComponentsConfig {
+    pub fn new(keep_non_final: bool) -> Self {
+        Self { keep_non_final }
+    }
+
+    pub fn with_keep_non_final(self, keep_non_final: bool) -> Self {
+        Self { keep_non_final }
+    }
+}
+
+/// Disjoint-set forest over the states of an FST.
+pub(crate) struct UnionFind {
+    parent: Vec<StateId>,
+    rank: Vec<u8>,
+}
+
+impl UnionFind {
+    pub(crate) fn new(n: usize) -> Self {
+        Self {
+            parent: (0..n as StateId).collect(),
+            rank: vec![0; n],
+        }
+    }
+
+    pub(crate) fn find(&mut self, s: StateId) -> StateId {
+        let mut root = s;
+        while self.parent[root as usize] != root {
+            root = self.parent[root as usize];
+        }
+        // Path compression.
+        let mut s = s;
+        while self.parent[s as usize] != root {
+            let next = self.parent[s as usize];
+            self.parent[s as usize] = root;
+            s = next;
+        }
+        root
+    }
+
+    pub(crate) fn union(&mut self, s1: StateId, s2: StateId) {
+        let r1 = self.find(s1);
+        let r2 = self.find(s2);
+        if r1 == r2 {
+            return;
+        }
+        let (rank1, rank2) = (self.rank[r1 as usize], self.rank[r2 as usize]);
+        if rank1 < rank2 {
+            self.parent[r1 as usize] = r2;
+        } else {
+            self.parent[r2 as usize] = r1;
+            if rank1 == rank2 {
+                self.rank[r1 as usize] += 1;
+            }
+        }
+    }
+}
+
+/// Split an FST into its weakly connected components, each one returned as its own FST.
+///
+/// All the components are kept, see
+/// [`split_components_with_config`](fn.split_components_with_config.html) for more details.
+pub fn split_components<W, F>(fst: &F) -> Result<Vec<VectorFst<W>>>
+where
+    W: Semiring,
+    F: ExpandedFst<W>,
+{
+    split_components_with_config(fst, SplitComponentsConfig::default())
+}
+
+/// Split an FST into its weakly connected components, each one returned as its own FST.
+///
+/// The component containing the start state of the input FST comes first and keeps it
+/// as start state. The other components follow in the order of their smallest state id,
+/// which is used as their start state. Within a component, states keep the relative
+/// order they had in the input FST. Components without any final state are dropped
+/// unless `config.keep_non_final` is set. Symbol tables are copied to every component.
+pub fn split_components_with_config<W, F>(
+    fst: &F,
+    config: SplitComponentsConfig,
+) -> Result<Vec<VectorFst<W>>>
+where
+    W: Semiring,
+    F: ExpandedFst<W>,
+{
+    let num_states = fst.num_states();
+    let mut uf = UnionFind::new(num_states);
+    for s in fst.states_range() {
+        for tr in unsafe { fst.get_trs_unchecked(s).trs() } {
+            uf.union(s, tr.nextstate);
+        }
+    }
+
+    // Assign a component id to every root, the start state's component being the first.
+    let mut component_of_root = vec![None; num_states];
+    let mut components: Vec<Vec<StateId>> = vec![];
+    let roots = fst
+        .start()
+        .into_iter()
+        .chain(fst.states_range())
+        .collect::<Vec<_>>();
+    for s in roots {
+        let root = uf.find(s);
+        if component_of_root[root as usize].is_none() {
+            component_of_root[root as usize] = Some(components.len());
+            components.push(vec![]);
+        }
+    }
+    for s in fst.states_range() {
+        let root = uf.find(s);
+        let c = component_of_root[root as usize].unwrap();
+        components[c].push(s);
+    }
+
+    let mut new_ids = vec![0 as StateId; num_states];
+    let mut res = Vec::with_capacity(components.len());
+    for (c, states) in components.iter().enumerate() {
+        if !config.keep_non_final && !states.iter().any(|s| unsafe { fst.is_final_unchecked(*s) }) {
+            continue;
+        }
+        let mut ofst = VectorFst::<W>::new();
+        ofst.add_states(states.len());
+        for (new_id, s) in states.iter().enumerate() {
+            new_ids[*s as usize] = new_id as StateId;
+        }
+        for s in states {
+            let new_s = new_ids[*s as usize];
+            if let Some(w) = unsafe { fst.final_weight_unchecked(*s) } {
+                unsafe { ofst.set_final_unchecked(new_s, w) };
+            }
+            for tr in unsafe { fst.get_trs_unchecked(*s).trs() } {
+                unsafe {
+                    ofst.add_tr_unchecked(
+                        new_s,
+                        Tr::new(
+                            tr.ilabel,
+                            tr.olabel,
+                            tr.weight.clone(),
+                            new_ids[tr.nextstate as usize],
+                        ),
+                    )
+                };
+            }
+        }
+        let start = match fst.start() {
+            Some(start) if c == 0 => new_ids[start as usize],
+            _ => 0,
+        };
+        ofst.set_start(start)?;
+        ofst.set_symts_from_fst(fst);
+        res.push(ofst);
+    }
+
+    Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fst_traits::CoreFst;
+    use crate::semirings::TropicalWeight;
+
+    #[test]
+    fn test_split_components() -> Result<()> {
+        let mut fst = VectorFst::<TropicalWeight>::new();
+        fst.add_states(6);
+        // First region: 0 -> 2, without any final state.
+        fst.add_tr(0, Tr::new(1, 1, 1.0, 2))?;
+        // Second region: 1 -> 3 -> 4 <- 5, containing the start state.
+        fst.add_tr(1, Tr::new(2, 2, 2.0, 3))?;
+        fst.add_tr(3, Tr::new(3, 3, 3.0, 4))?;
+        fst.add_tr(5, Tr::new(4, 4, 4.0, 4))?;
+        fst.set_start(3)?;
+        fst.set_final(4, 0.5)?;
+
+        let components = split_components(&fst)?;
+        assert_eq!(components.len(), 2);
+
+        let mut first = VectorFst::<TropicalWeight>::new();
+        first.add_states(4);
+        first.add_tr(0, Tr::new(2, 2, 2.0, 1))?;
+        first.add_tr(1, Tr::new(3, 3, 3.0, 2))?;
+        first.add_tr(3, Tr::new(4, 4, 4.0, 2))?;
+        first.set_start(1)?;
+        first.set_final(2, 0.5)?;
+        assert_eq!(components[0], first);
+
+        let mut second = VectorFst::<TropicalWeight>::new();
+        second.add_states(2);
+        second.add_tr(0, Tr::new(1, 1, 1.0, 1))?;
+        second.set_start(0)?;
+        assert_eq!(components[1], second);
+
+        let components = split_components_with_config(&fst, SplitComponentsConfig::new(false))?;
+        assert_eq!(components.len(), 1);
+        assert_eq!(components[0].start(), Some(1));
+        assert_eq!(components[0], first);
+
+        Ok(())
+    }
+}
@@ -11,7 +11,7 @@ use crate::{StateId, Trs};
 ///
 /// `order[i]` gives the the state ID after sorting that corresponds
 /// to the state ID i before sorting; it must therefore be a
-/// permutation of the input FST's states ID sequence.
+/// permutation of the input FST's states ID sequence, otherwise an error is returned.
 pub fn state_sort<W, F>(fst: &mut F, order: &[StateId]) -> Result<()>
 where
     W: Semiring,
@@ -23,6 +23,15 @@ where
         order.len(),
         fst.num_states()
     );
+    let mut seen = vec![false; order.len()];
+    for &s in order {
+        ensure!(
+            (s as usize) < order.len() && !seen[s as usize],
+            "StateSort : The order vector is not a permutation : state {} is out of range or appears several times",
+            s
+        );
+        seen[s as usize] = true;
+    }
     if fst.start().is_none() {
         return Ok(());
     }
@@ -33,9 +42,6 @@ where
 
     let mut done = vec![false; order.len()];
 
-    debug_assert!((start_state as usize) < order.len());
-    debug_assert!((order[start_state as usize] as usize) < fst.num_states());
-
     fst.set_start(order[start_state as usize])?;
 
     for mut s1 in fst.states_range() {
@@ -75,3 +81,50 @@ where
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::fst_impls::VectorFst;
+    use crate::semirings::{Semiring, TropicalWeight};
+    use crate::Tr;
+
+    #[test]
+    fn test_state_sort() -> Result<()> {
+        let mut fst = VectorFst::<TropicalWeight>::new();
+        fst.add_states(3);
+        fst.set_start(0)?;
+        fst.set_final(2, 1.0)?;
+        fst.add_tr(0, Tr::new(1, 1, 1.0, 1))?;
+        fst.add_tr(1, Tr::new(2, 2, 2.0, 2))?;
+        fst.add_tr(2, Tr::new(3, 3, 3.0, 0))?;
+
+        let mut fst_ref = VectorFst::<TropicalWeight>::new();
+        fst_ref.add_states(3);
+        fst_ref.set_start(2)?;
+        fst_ref.set_final(1, 1.0)?;
+        fst_ref.add_tr(2, Tr::new(1, 1, 1.0, 0))?;
+        fst_ref.add_tr(0, Tr::new(2, 2, 2.0, 1))?;
+        fst_ref.add_tr(1, Tr::new(3, 3, 3.0, 2))?;
+
+        state_sort(&mut fst, &[2, 0, 1])?;
+        assert_eq!(fst, fst_ref);
+        Ok(())
+    }
+
+    #[test]
+    fn test_state_sort_invalid_order() -> Result<()> {
+        let mut fst = VectorFst::<TropicalWeight>::new();
+        fst.add_states(3);
+        fst.set_start(0)?;
+        fst.set_final(2, TropicalWeight::one())?;
+        let fst_ref = fst.clone();
+
+        assert!(state_sort(&mut fst, &[0, 1]).is_err());
+        assert!(state_sort(&mut fst, &[0, 1, 1]).is_err());
+        assert!(state_sort(&mut fst, &[0, 1, 3]).is_err());
+        assert_eq!(fst, fst_ref);
+        Ok(())
+    }
+}
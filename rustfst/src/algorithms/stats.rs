@@ -0,0 +1,74 @@
+use crate::fst_traits::ExpandedFst;
+use crate::semirings::Semiring;
+use crate::Trs;
+
+/// Computes the out-degree and the in-degree of every state of an FST, in a single pass
+/// over its transitions. A self-loop counts in both degrees of its state.
+///
+/// # Example
+///
+/// ```
+/// # use anyhow::Result;
+/// # use rustfst::fst_impls::VectorFst;
+/// # use rustfst::fst_traits::MutableFst;
+/// # use rustfst::semirings::TropicalWeight;
+/// # use rustfst::algorithms::degrees;
+/// # use rustfst::Tr;
+/// # fn main() -> Result<()> {
+/// let mut fst = VectorFst::<TropicalWeight>::new();
+/// fst.add_states(2);
+/// fst.add_tr(0, Tr::new(1, 1, 1.0, 1))?;
+/// fst.add_tr(1, Tr::new(2, 2, 2.0, 1))?;
+///
+/// let (out_degrees, in_degrees) = degrees(&fst);
+/// assert_eq!(out_degrees, vec![1, 1]);
+/// assert_eq!(in_degrees, vec![0, 2]);
+/// # Ok(())
+/// # }
+/// ```
+pub fn degrees<W: Semiring, F: ExpandedFst<W>>(fst: &F) -> (Vec<usize>, Vec<usize>) {
+    let num_states = fst.num_states();
+    let mut out_degrees = Vec::with_capacity(num_states);
+    let mut in_degrees = vec![0; num_states];
+    for s in fst.states_range() {
+        let trs = unsafe { fst.get_trs_unchecked(s) };
+        out_degrees.push(trs.len());
+        for tr in trs.trs() {
+            in_degrees[tr.nextstate as usize] += 1;
+        }
+    }
+    (out_degrees, in_degrees)
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use super::*;
+    use crate::fst_impls::VectorFst;
+    use crate::fst_traits::{CoreFst, MutableFst};
+    use crate::semirings::TropicalWeight;
+    use crate::Tr;
+
+    #[test]
+    fn test_degrees() -> Result<()> {
+        let mut fst = VectorFst::<TropicalWeight>::new();
+        fst.add_states(4);
+        fst.set_start(0)?;
+        fst.add_tr(0, Tr::new(1, 1, 1.0, 1))?;
+        fst.add_tr(0, Tr::new(2, 2, 1.0, 2))?;
+        fst.add_tr(1, Tr::new(3, 3, 1.0, 1))?;
+        fst.add_tr(1, Tr::new(4, 4, 1.0, 2))?;
+        fst.add_tr(2, Tr::new(5, 5, 1.0, 0))?;
+
+        let (out_degrees, in_degrees) = degrees(&fst);
+        assert_eq!(out_degrees, vec![2, 2, 1, 0]);
+        assert_eq!(in_degrees, vec![1, 2, 2, 0]);
+
+        let num_trs: usize = fst.states_range().map(|s| fst.num_trs(s).unwrap()).sum();
+        assert_eq!(in_degrees.iter().sum::<usize>(), num_trs);
+        assert_eq!(out_degrees.iter().sum::<usize>(), num_trs);
+
+        Ok(())
+    }
+}
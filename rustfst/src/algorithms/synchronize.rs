@@ -0,0 +1,317 @@
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, VecDeque};
+
+use anyhow::Result;
+
+use crate::algorithms::{connect, fst_convert_from_ref};
+use crate::fst_impls::VectorFst;
+use crate::fst_properties::mutable_properties::synchronization_properties;
+use crate::fst_traits::{CoreFst, ExpandedFst, MutableFst};
+use crate::semirings::Semiring;
+use crate::{Label, StateId, Tr, Trs, EPS_LABEL, NO_STATE_ID};
+
+/// State of the synchronized FST : a state of the input FST and the input and output labels
+/// read but not emitted yet. At most one of the two strings is non-empty.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct Element {
+    /// `NO_STATE_ID` once the final weight has been consumed.
+    state: StateId,
+    istring: Vec<Label>,
+    ostring: Vec<Label>,
+}
+
+/// Whether the concatenation of `string` and `label` is empty.
+fn is_empty(string: &[Label], label: Label) -> bool {
+    string.is_empty() && label == EPS_LABEL
+}
+
+/// First label of the concatenation of `string` and `label`.
+fn car(string: &[Label], label: Label) -> Label {
+    string.first().cloned().unwrap_or(label)
+}
+
+/// Concatenation of `string` and `label`.
+fn concat(string: &[Label], label: Label) -> Vec<Label> {
+    let mut res = string.to_vec();
+    if label != EPS_LABEL {
+        res.push(label);
+    }
+    res
+}
+
+/// Concatenation of `string` and `label` without its first label.
+fn cdr(string: &[Label], label: Label) -> Vec<Label> {
+    let mut res = concat(string, label);
+    if !res.is_empty() {
+        res.remove(0);
+    }
+    res
+}
+
+struct Synchronizer<W: Semiring> {
+    ofst: VectorFst<W>,
+    elements: Vec<Element>,
+    element_to_state: HashMap<Element, StateId>,
+    queue: VecDeque<StateId>,
+    max_delay: usize,
+}
+
+impl<W: Semiring> Synchronizer<W> {
+    fn find_state(&mut self, element: Element) -> Result<StateId> {
+        if element.istring.len() > self.max_delay || element.ostring.len() > self.max_delay {
+            bail!("Synchronize : The FST doesn't have a bounded delay");
+        }
+        match self.element_to_state.entry(element) {
+            Entry::Occupied(e) => Ok(*e.get()),
+            Entry::Vacant(e) => {
+                let s = self.ofst.add_state();
+                self.elements.push(e.key().clone());
+                self.queue.push_back(s);
+                e.insert(s);
+                Ok(s)
+            }
+        }
+    }
+}
+
+/// Synchronizes an FST : the output labels are delayed or moved forward so that, as much as
+/// possible, each transition reads an input label and writes an output label. The epsilons
+/// remaining on a successful path are moved to its end.
+///
+/// The FST must have a bounded delay, i.e. the difference between the number of input and
+/// output labels read along a path must be bounded, otherwise an error is returned. For a
+/// trimmed FST this means that the cycles have as many input labels as output labels.
+///
+/// # Example
+/// ```
+/// # use rustfst::fst_impls::VectorFst;
+/// # use rustfst::fst_traits::{CoreFst, MutableFst};
+/// # use rustfst::semirings::{Semiring, TropicalWeight};
+/// # use rustfst::algorithms::synchronize;
+/// # use rustfst::{Tr, Trs, EPS_LABEL};
+/// # use anyhow::Result;
+/// # fn main() -> Result<()> {
+/// let mut fst = VectorFst::<TropicalWeight>::new();
+/// fst.add_states(3);
+/// fst.set_start(0)?;
+/// fst.set_final(2, TropicalWeight::one())?;
+/// fst.add_tr(0, Tr::new(1, EPS_LABEL, 1.0, 1))?;
+/// fst.add_tr(1, Tr::new(EPS_LABEL, 2, 2.0, 2))?;
+///
+/// let res = synchronize(&fst)?;
+/// let trs = res.get_trs(res.start().unwrap())?;
+/// assert_eq!(trs.trs()[0].ilabel, EPS_LABEL);
+/// let trs = res.get_trs(trs.trs()[0].nextstate)?;
+/// assert_eq!((trs.trs()[0].ilabel, trs.trs()[0].olabel), (1, 2));
+/// # Ok(())
+/// # }
+/// ```
+pub fn synchronize<W, F>(ifst: &F) -> Result<VectorFst<W>>
+where
+    W: Semiring,
+    F: ExpandedFst<W>,
+{
+    // The delay of a trimmed FST is bounded by its number of states if it is bounded.
+    let mut fst: VectorFst<W> = fst_convert_from_ref(ifst);
+    connect(&mut fst)?;
+
+    let mut synchronizer = Synchronizer {
+        ofst: VectorFst::new(),
+        elements: vec![],
+        element_to_state: HashMap::new(),
+        queue: VecDeque::new(),
+        max_delay: fst.num_states(),
+    };
+    synchronizer.ofst.set_symts_from_fst(ifst);
+    let start = match fst.start() {
+        Some(start) => start,
+        None => return Ok(synchronizer.ofst),
+    };
+    let start = synchronizer.find_state(Element {
+        state: start,
+        istring: vec![],
+        ostring: vec![],
+    })?;
+    synchronizer.ofst.set_start(start)?;
+
+    while let Some(s) = synchronizer.queue.pop_front() {
+        let element = synchronizer.elements[s as usize].clone();
+        let final_weight = if element.state == NO_STATE_ID {
+            Some(W::one())
+        } else {
+            for tr in fst.get_trs(element.state)?.trs() {
+                let (ilabel, olabel, istring, ostring) = if !is_empty(&element.istring, tr.ilabel)
+                    && !is_empty(&element.ostring, tr.olabel)
+                {
+                    (
+                        car(&element.istring, tr.ilabel),
+                        car(&element.ostring, tr.olabel),
+                        cdr(&element.istring, tr.ilabel),
+                        cdr(&element.ostring, tr.olabel),
+                    )
+                } else {
+                    (
+                        EPS_LABEL,
+                        EPS_LABEL,
+                        concat(&element.istring, tr.ilabel),
+                        concat(&element.ostring, tr.olabel),
+                    )
+                };
+                let nextstate = synchronizer.find_state(Element {
+                    state: tr.nextstate,
+                    istring,
+                    ostring,
+                })?;
+                synchronizer
+                    .ofst
+                    .add_tr(s, Tr::new(ilabel, olabel, tr.weight.clone(), nextstate))?;
+            }
+            fst.final_weight(element.state)?
+        };
+
+        if let Some(final_weight) = final_weight {
+            if element.istring.is_empty() && element.ostring.is_empty() {
+                synchronizer.ofst.set_final(s, final_weight)?;
+            } else {
+                // The labels left are emitted after the final weight.
+                let nextstate = synchronizer.find_state(Element {
+                    state: NO_STATE_ID,
+                    istring: cdr(&element.istring, EPS_LABEL),
+                    ostring: cdr(&element.ostring, EPS_LABEL),
+                })?;
+                let tr = Tr::new(
+                    car(&element.istring, EPS_LABEL),
+                    car(&element.ostring, EPS_LABEL),
+                    final_weight,
+                    nextstate,
+                );
+                synchronizer.ofst.add_tr(s, tr)?;
+            }
+        }
+    }
+
+    let mut ofst = synchronizer.ofst;
+    ofst.set_properties(synchronization_properties(fst.properties()));
+    Ok(ofst)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::fst_traits::Fst;
+    use crate::semirings::TropicalWeight;
+
+    type F = VectorFst<TropicalWeight>;
+
+    /// Labels and weights along the single path of an acyclic FST without branching.
+    fn single_path(fst: &F) -> Result<(Vec<(Label, Label)>, TropicalWeight)> {
+        let mut labels = vec![];
+        let mut weight = TropicalWeight::one();
+        let mut s = fst.start().unwrap();
+        loop {
+            let trs = fst.get_trs(s)?;
+            if trs.is_empty() {
+                weight.times_assign(fst.final_weight(s)?.unwrap())?;
+                return Ok((labels, weight));
+            }
+            assert_eq!(trs.len(), 1);
+            labels.push((trs[0].ilabel, trs[0].olabel));
+            weight.times_assign(&trs[0].weight)?;
+            s = trs[0].nextstate;
+        }
+    }
+
+    fn delayed_output_fst() -> Result<F> {
+        // 1:eps 2:eps eps:3 eps:4
+        let mut fst = F::new();
+        fst.add_states(5);
+        fst.set_start(0)?;
+        fst.set_final(4, 0.5)?;
+        fst.add_tr(0, Tr::new(1, EPS_LABEL, 1.0, 1))?;
+        fst.add_tr(1, Tr::new(2, EPS_LABEL, 2.0, 2))?;
+        fst.add_tr(2, Tr::new(EPS_LABEL, 3, 3.0, 3))?;
+        fst.add_tr(3, Tr::new(EPS_LABEL, 4, 4.0, 4))?;
+        Ok(fst)
+    }
+
+    #[test]
+    fn test_synchronize_delayed_output() -> Result<()> {
+        let fst = delayed_output_fst()?;
+        let res = synchronize(&fst)?;
+        assert_eq!(
+            single_path(&res)?,
+            (
+                vec![(0, 0), (0, 0), (1, 3), (2, 4)],
+                TropicalWeight::new(10.5)
+            )
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_synchronize_bounded_delay_cycle() -> Result<()> {
+        // A loop 5:5 on the state where two input labels are delayed.
+        let mut fst = delayed_output_fst()?;
+        fst.add_tr(2, Tr::new(5, 5, 1.0, 2))?;
+        let res = synchronize(&fst)?;
+
+        // The delayed labels are shifted along the loop.
+        let paths: Vec<_> = res
+            .paths_iter()
+            .take(3)
+            .map(|p| (p.ilabels, p.olabels))
+            .collect();
+        assert!(paths.contains(&(vec![1, 2], vec![3, 4])));
+        assert!(paths.contains(&(vec![1, 2, 5], vec![5, 3, 4])));
+        for s in res.states_range() {
+            for tr in res.get_trs(s)?.trs() {
+                if tr.ilabel == EPS_LABEL || tr.olabel == EPS_LABEL {
+                    assert_eq!((tr.ilabel, tr.olabel), (EPS_LABEL, EPS_LABEL));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_synchronize_remaining_labels_at_the_end() -> Result<()> {
+        // 1:eps 2:3, the input label 2 is emitted after the final weight.
+        let mut fst = F::new();
+        fst.add_states(3);
+        fst.set_start(0)?;
+        fst.set_final(2, 1.5)?;
+        fst.add_tr(0, Tr::new(1, EPS_LABEL, 1.0, 1))?;
+        fst.add_tr(1, Tr::new(2, 3, 2.0, 2))?;
+
+        let res = synchronize(&fst)?;
+        assert_eq!(
+            single_path(&res)?,
+            (vec![(0, 0), (1, 3), (2, 0)], TropicalWeight::new(4.5))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_synchronize_unbounded_delay() -> Result<()> {
+        // (1:eps)* eps:2
+        let mut fst = F::new();
+        fst.add_states(3);
+        fst.set_start(0)?;
+        fst.set_final(1, TropicalWeight::one())?;
+        fst.add_tr(0, Tr::new(1, EPS_LABEL, 1.0, 0))?;
+        fst.add_tr(0, Tr::new(EPS_LABEL, 2, 1.0, 1))?;
+        assert!(synchronize(&fst).is_err());
+
+        // An unbounded delay in a useless part of the FST is fine.
+        fst.delete_trs(0)?;
+        fst.add_tr(0, Tr::new(1, 2, 1.0, 1))?;
+        fst.add_tr(0, Tr::new(3, 3, 1.0, 2))?;
+        fst.add_tr(2, Tr::new(1, EPS_LABEL, 1.0, 2))?;
+        let res = synchronize(&fst)?;
+        assert_eq!(res.paths_iter().count(), 1);
+
+        assert_eq!(synchronize(&F::new())?.num_states(), 0);
+        Ok(())
+    }
+}
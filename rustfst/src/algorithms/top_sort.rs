@@ -4,7 +4,7 @@ use crate::algorithms::dfs_visit::{dfs_visit, Visitor};
 use crate::algorithms::state_sort;
 use crate::algorithms::tr_filters::AnyTrFilter;
 use crate::fst_properties::FstProperties;
-use crate::fst_traits::{Fst, MutableFst};
+use crate::fst_traits::{ExpandedFst, Fst, MutableFst};
 use crate::semirings::Semiring;
 use crate::StateId;
 use crate::Tr;
@@ -13,6 +13,8 @@ pub struct TopOrderVisitor {
     pub order: Vec<StateId>,
     pub acyclic: bool,
     pub finish: Vec<StateId>,
+    /// If true, the transitions from a state to itself are not considered as cycles.
+    pub ignore_self_loops: bool,
 }
 
 impl TopOrderVisitor {
@@ -21,6 +23,14 @@ impl TopOrderVisitor {
             order: vec![],
             acyclic: true,
             finish: vec![],
+            ignore_self_loops: false,
+        }
+    }
+
+    pub fn new_ignoring_self_loops() -> Self {
+        Self {
+            ignore_self_loops: true,
+            ..Self::new()
         }
     }
 }
@@ -36,7 +46,10 @@ impl<'a, W: Semiring, F: 'a + Fst<W>> Visitor<'a, W, F> for TopOrderVisitor {
         true
     }
 
-    fn back_tr(&mut self, _s: StateId, _tr: &Tr<W>) -> bool {
+    fn back_tr(&mut self, s: StateId, tr: &Tr<W>) -> bool {
+        if self.ignore_self_loops && tr.nextstate == s {
+            return true;
+        }
         self.acyclic = false;
         false
     }
@@ -78,10 +91,8 @@ where
     W: Semiring,
     F: MutableFst<W>,
 {
-    let mut visitor = TopOrderVisitor::new();
-    dfs_visit(fst, &mut visitor, &AnyTrFilter {}, false);
-    if visitor.acyclic {
-        state_sort(fst, &visitor.order)?;
+    if let Some(order) = top_sort_order(fst)? {
+        state_sort(fst, &order)?;
         let props =
             FstProperties::ACYCLIC | FstProperties::INITIAL_ACYCLIC | FstProperties::TOP_SORTED;
         fst.set_properties_with_mask(props, props);
@@ -92,3 +103,192 @@ where
 
     Ok(())
 }
+
+/// Computes a topological order of the states of the FST without modifying it.
+///
+/// Returns `None` if the FST is cyclic. Otherwise, `order[s]` is the position of state `s`
+/// in the topological order and can be passed to [`state_sort`](fn.state_sort.html) to
+/// renumber the states accordingly, which is what [`top_sort`](fn.top_sort.html) does.
+///
+/// # Example
+/// ```
+/// # use rustfst::fst_impls::VectorFst;
+/// # use rustfst::fst_traits::MutableFst;
+/// # use rustfst::semirings::{Semiring, TropicalWeight};
+/// # use rustfst::algorithms::top_sort_order;
+/// # use rustfst::Tr;
+/// # use anyhow::Result;
+/// # fn main() -> Result<()> {
+/// let mut fst = VectorFst::<TropicalWeight>::new();
+/// fst.add_states(2);
+/// fst.set_start(1)?;
+/// fst.set_final(0, TropicalWeight::one())?;
+/// fst.add_tr(1, Tr::new(1, 1, 1.0, 0))?;
+///
+/// assert_eq!(top_sort_order(&fst)?, Some(vec![1, 0]));
+///
+/// fst.add_tr(0, Tr::new(2, 2, 1.0, 0))?;
+/// assert_eq!(top_sort_order(&fst)?, None);
+/// # Ok(())
+/// # }
+/// ```
+pub fn top_sort_order<W, F>(fst: &F) -> Result<Option<Vec<StateId>>>
+where
+    W: Semiring,
+    F: ExpandedFst<W>,
+{
+    let mut visitor = TopOrderVisitor::new();
+    dfs_visit(fst, &mut visitor, &AnyTrFilter {}, false);
+    if visitor.acyclic {
+        Ok(Some(visitor.order))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Computes a topological order of the states of the FST where the self-loops are ignored,
+/// i.e. transitions from a state to itself are treated as non-edges. This is useful for
+/// FSTs that are acyclic except for intentional self-loops (e.g HMM states).
+///
+/// Returns `None` if the FST contains a cycle going through more than one state. Otherwise,
+/// `order[s]` is the position of state `s` in the topological order and can be passed to
+/// [`state_sort`](fn.state_sort.html) to renumber the states accordingly.
+///
+/// # Example
+/// ```
+/// # use rustfst::fst_impls::VectorFst;
+/// # use rustfst::fst_traits::MutableFst;
+/// # use rustfst::semirings::{Semiring, TropicalWeight};
+/// # use rustfst::algorithms::top_order_ignoring_self_loops;
+/// # use rustfst::Tr;
+/// # use anyhow::Result;
+/// # fn main() -> Result<()> {
+/// let mut fst = VectorFst::<TropicalWeight>::new();
+/// fst.add_states(2);
+/// fst.set_start(1)?;
+/// fst.set_final(0, TropicalWeight::one())?;
+/// fst.add_tr(1, Tr::new(1, 1, 1.0, 1))?;
+/// fst.add_tr(1, Tr::new(2, 2, 1.0, 0))?;
+///
+/// assert_eq!(top_order_ignoring_self_loops(&fst), Some(vec![1, 0]));
+///
+/// fst.add_tr(0, Tr::new(3, 3, 1.0, 1))?;
+/// assert_eq!(top_order_ignoring_self_loops(&fst), None);
+/// # Ok(())
+/// # }
+/// ```
+pub fn top_order_ignoring_self_loops<W, F>(fst: &F) -> Option<Vec<StateId>>
+where
+    W: Semiring,
+    F: ExpandedFst<W>,
+{
+    let mut visitor = TopOrderVisitor::new_ignoring_self_loops();
+    dfs_visit(fst, &mut visitor, &AnyTrFilter {}, false);
+    if visitor.acyclic {
+        Some(visitor.order)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fst_impls::VectorFst;
+    use crate::fst_traits::CoreFst;
+    use crate::semirings::TropicalWeight;
+    use crate::Trs;
+
+    #[test]
+    fn test_top_sort_order() -> Result<()> {
+        let mut fst = VectorFst::<TropicalWeight>::new();
+        fst.add_states(3);
+        fst.set_start(2)?;
+        fst.set_final(0, TropicalWeight::one())?;
+        fst.add_tr(2, Tr::new(1, 1, 1.0, 1))?;
+        fst.add_tr(2, Tr::new(2, 2, 1.0, 0))?;
+        fst.add_tr(1, Tr::new(3, 3, 1.0, 0))?;
+        let fst_ref = fst.clone();
+
+        let order = top_sort_order(&fst)?.unwrap();
+        assert_eq!(order, vec![2, 1, 0]);
+        assert_eq!(fst, fst_ref);
+
+        let mut fst_sorted = fst.clone();
+        top_sort(&mut fst_sorted)?;
+        let mut fst_state_sorted = fst.clone();
+        state_sort(&mut fst_state_sorted, &order)?;
+        assert_eq!(fst_sorted, fst_state_sorted);
+
+        fst.add_tr(0, Tr::new(4, 4, 1.0, 0))?;
+        assert_eq!(top_sort_order(&fst)?, None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_top_sort_order_non_involutive() -> Result<()> {
+        // Topological order 1 -> 2 -> 0 : the order and its inverse differ.
+        let mut fst = VectorFst::<TropicalWeight>::new();
+        fst.add_states(3);
+        fst.set_start(1)?;
+        fst.set_final(0, TropicalWeight::one())?;
+        fst.add_tr(1, Tr::new(1, 1, 1.0, 2))?;
+        fst.add_tr(2, Tr::new(2, 2, 2.0, 0))?;
+
+        let order = top_sort_order(&fst)?.unwrap();
+        assert_eq!(order, vec![2, 0, 1]);
+
+        let mut fst_sorted = fst.clone();
+        top_sort(&mut fst_sorted)?;
+        state_sort(&mut fst, &order)?;
+        assert_eq!(fst.start(), Some(0));
+        assert_eq!(fst.get_trs(0)?.trs(), &[Tr::new(1, 1, 1.0, 1)]);
+        assert_eq!(fst.get_trs(1)?.trs(), &[Tr::new(2, 2, 2.0, 2)]);
+        assert_eq!(fst.final_weight(2)?, Some(TropicalWeight::one()));
+        assert_eq!(fst, fst_sorted);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_top_order_ignoring_self_loops() -> Result<()> {
+        // HMM-like FST : three emitting states with self-loops, in reverse order.
+        let mut fst = VectorFst::<TropicalWeight>::new();
+        fst.add_states(5);
+        fst.set_start(4)?;
+        fst.set_final(0, TropicalWeight::one())?;
+        for s in 1..4 {
+            fst.add_tr(s, Tr::new(s, s, 0.5, s))?;
+            fst.add_tr(s, Tr::new(s, s, 0.5, s - 1))?;
+        }
+        fst.add_tr(4, Tr::new(4, 4, 1.0, 3))?;
+        fst.add_tr(4, Tr::new(5, 5, 1.0, 1))?;
+
+        let mut fst_cyclic = fst.clone();
+        top_sort(&mut fst_cyclic)?;
+        assert!(fst_cyclic.properties().contains(FstProperties::CYCLIC));
+
+        let order = top_order_ignoring_self_loops(&fst).unwrap();
+        for s in fst.states_range() {
+            for tr in fst.get_trs(s)?.trs() {
+                assert!(tr.nextstate == s || order[s as usize] < order[tr.nextstate as usize]);
+            }
+        }
+
+        let mut fst_sorted = fst.clone();
+        state_sort(&mut fst_sorted, &order)?;
+        assert_eq!(fst_sorted.start(), Some(0));
+        for s in fst_sorted.states_range() {
+            for tr in fst_sorted.get_trs(s)?.trs() {
+                assert!(tr.nextstate >= s);
+            }
+        }
+
+        // A cycle between two states.
+        fst.add_tr(1, Tr::new(6, 6, 1.0, 2))?;
+        assert_eq!(top_order_ignoring_self_loops(&fst), None);
+
+        Ok(())
+    }
+}
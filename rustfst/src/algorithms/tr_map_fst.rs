@@ -0,0 +1,421 @@
+use std::borrow::Borrow;
+use std::fmt::Debug;
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use anyhow::Result;
+
+use crate::algorithms::lazy::{FstOp, LazyFst, SimpleVecCache};
+use crate::algorithms::{FinalTr, MapFinalAction, TrMapper};
+use crate::fst_properties::FstProperties;
+use crate::fst_traits::{
+    AllocableFst, CoreFst, ExpandedFst, Fst, FstIterator, MutableFst, StateIterator,
+};
+use crate::semirings::Semiring;
+use crate::{StateId, SymbolTable, Tr, Trs, TrsVec, EPS_LABEL};
+
+pub struct TrMapFstOp<W, F, B, M>
+where
+    W: Semiring,
+    F: ExpandedFst<W>,
+    B: Borrow<F>,
+    M: TrMapper<W>,
+{
+    fst: B,
+    mapper: M,
+    superfinal: Option<StateId>,
+    properties: FstProperties,
+    ghost: PhantomData<(W, F)>,
+}
+
+impl<W, F, B, M> Debug for TrMapFstOp<W, F, B, M>
+where
+    W: Semiring,
+    F: ExpandedFst<W>,
+    B: Borrow<F>,
+    M: TrMapper<W>,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TrMapFstOp")
+            .field("superfinal", &self.superfinal)
+            .field("properties", &self.properties)
+            .finish()
+    }
+}
+
+impl<W, F, B, M> TrMapFstOp<W, F, B, M>
+where
+    W: Semiring,
+    F: ExpandedFst<W>,
+    B: Borrow<F>,
+    M: TrMapper<W>,
+{
+    fn new(fst: B, mapper: M) -> Self {
+        let superfinal = if mapper.final_action() != MapFinalAction::MapNoSuperfinal
+            && fst.borrow().start().is_some()
+        {
+            Some(fst.borrow().num_states() as StateId)
+        } else {
+            None
+        };
+        let properties = mapper.properties(fst.borrow().properties());
+        Self {
+            fst,
+            mapper,
+            superfinal,
+            properties,
+            ghost: PhantomData,
+        }
+    }
+
+    /// Map the final weight of `state` of the input FST.
+    fn map_final(&self, state: StateId) -> Result<Option<(W, FinalTr<W>)>> {
+        match self.fst.borrow().final_weight(state)? {
+            Some(weight) => {
+                let mut final_tr = FinalTr {
+                    ilabel: EPS_LABEL,
+                    olabel: EPS_LABEL,
+                    weight: weight.clone(),
+                };
+                self.mapper.final_tr_map(&mut final_tr)?;
+                Ok(Some((weight, final_tr)))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+impl<W, F, B, M> FstOp<W> for TrMapFstOp<W, F, B, M>
+where
+    W: Semiring,
+    F: ExpandedFst<W>,
+    B: Borrow<F>,
+    M: TrMapper<W>,
+{
+    fn compute_start(&self) -> Result<Option<StateId>> {
+        Ok(self.fst.borrow().start())
+    }
+
+    fn compute_trs(&self, id: StateId) -> Result<TrsVec<W>> {
+        if Some(id) == self.superfinal {
+            return Ok(TrsVec::default());
+        }
+        let mut trs = self.fst.borrow().get_trs(id)?.trs().to_vec();
+        for tr in trs.iter_mut() {
+            self.mapper.tr_map(tr)?;
+        }
+        if let Some((_, final_tr)) = self.map_final(id)? {
+            let has_labels = final_tr.ilabel != EPS_LABEL || final_tr.olabel != EPS_LABEL;
+            let to_superfinal = match self.mapper.final_action() {
+                MapFinalAction::MapNoSuperfinal => false,
+                MapFinalAction::MapAllowSuperfinal => has_labels,
+                MapFinalAction::MapRequireSuperfinal => has_labels || !final_tr.weight.is_zero(),
+            };
+            if to_superfinal {
+                trs.push(Tr::new(
+                    final_tr.ilabel,
+                    final_tr.olabel,
+                    final_tr.weight,
+                    self.superfinal.unwrap(),
+                ));
+            }
+        }
+        Ok(TrsVec(Arc::new(trs)))
+    }
+
+    fn compute_final_weight(&self, id: StateId) -> Result<Option<W>> {
+        if Some(id) == self.superfinal {
+            return Ok(Some(W::one()));
+        }
+        let (weight, final_tr) = match self.map_final(id)? {
+            Some(e) => e,
+            None => return Ok(None),
+        };
+        let has_labels = final_tr.ilabel != EPS_LABEL || final_tr.olabel != EPS_LABEL;
+        match self.mapper.final_action() {
+            MapFinalAction::MapNoSuperfinal => {
+                if has_labels {
+                    bail!("TrMapFst: Non-zero tr labels for superfinal tr")
+                }
+                Ok(Some(final_tr.weight))
+            }
+            MapFinalAction::MapAllowSuperfinal => {
+                if has_labels {
+                    Ok(None)
+                } else {
+                    Ok(Some(final_tr.weight))
+                }
+            }
+            MapFinalAction::MapRequireSuperfinal => {
+                if has_labels || !final_tr.weight.is_zero() {
+                    Ok(None)
+                } else {
+                    Ok(Some(weight))
+                }
+            }
+        }
+    }
+
+    fn properties(&self) -> FstProperties {
+        self.properties
+    }
+}
+
+type InnerLazyFst<W, F, B, M> = LazyFst<W, TrMapFstOp<W, F, B, M>, SimpleVecCache<W>>;
+
+/// Lazy version of [`tr_map`](fn.tr_map.html) : the transitions and final weights of a state
+/// are mapped only when the state is expanded.
+///
+/// The states keep their ids. When the mapper requires a superfinal state, its id is the
+/// number of states of the input FST, as with `tr_map`.
+pub struct TrMapFst<W, F, B, M>(InnerLazyFst<W, F, B, M>)
+where
+    W: Semiring,
+    F: ExpandedFst<W>,
+    B: Borrow<F>,
+    M: TrMapper<W>;
+
+impl<W, F, B, M> TrMapFst<W, F, B, M>
+where
+    W: Semiring,
+    F: ExpandedFst<W>,
+    B: Borrow<F>,
+    M: TrMapper<W>,
+{
+    pub fn new(fst: B, mapper: M) -> Self {
+        let isymt = fst.borrow().input_symbols().cloned();
+        let osymt = fst.borrow().output_symbols().cloned();
+        let fst_op = TrMapFstOp::new(fst, mapper);
+        let fst_cache = SimpleVecCache::default();
+        TrMapFst(LazyFst::from_op_and_cache(fst_op, fst_cache, isymt, osymt))
+    }
+
+    /// Turns the Lazy FST into a static one.
+    pub fn compute<F2: MutableFst<W> + AllocableFst<W>>(&self) -> Result<F2> {
+        self.0.compute()
+    }
+}
+
+impl<W, F, B, M> CoreFst<W> for TrMapFst<W, F, B, M>
+where
+    W: Semiring,
+    F: ExpandedFst<W>,
+    B: Borrow<F>,
+    M: TrMapper<W>,
+{
+    type TRS = TrsVec<W>;
+
+    fn start(&self) -> Option<StateId> {
+        self.0.start()
+    }
+
+    fn final_weight(&self, state_id: StateId) -> Result<Option<W>> {
+        self.0.final_weight(state_id)
+    }
+
+    unsafe fn final_weight_unchecked(&self, state_id: StateId) -> Option<W> {
+        self.0.final_weight_unchecked(state_id)
+    }
+
+    fn num_trs(&self, s: StateId) -> Result<usize> {
+        self.0.num_trs(s)
+    }
+
+    unsafe fn num_trs_unchecked(&self, s: StateId) -> usize {
+        self.0.num_trs_unchecked(s)
+    }
+
+    fn get_trs(&self, state_id: StateId) -> Result<Self::TRS> {
+        self.0.get_trs(state_id)
+    }
+
+    unsafe fn get_trs_unchecked(&self, state_id: StateId) -> Self::TRS {
+        self.0.get_trs_unchecked(state_id)
+    }
+
+    fn properties(&self) -> FstProperties {
+        self.0.properties()
+    }
+
+    fn num_input_epsilons(&self, state: StateId) -> Result<usize> {
+        self.0.num_input_epsilons(state)
+    }
+
+    fn num_output_epsilons(&self, state: StateId) -> Result<usize> {
+        self.0.num_output_epsilons(state)
+    }
+}
+
+impl<'a, W, F, B, M> StateIterator<'a> for TrMapFst<W, F, B, M>
+where
+    W: Semiring,
+    F: ExpandedFst<W> + 'a,
+    B: Borrow<F> + 'a,
+    M: TrMapper<W> + 'a,
+{
+    type Iter = <InnerLazyFst<W, F, B, M> as StateIterator<'a>>::Iter;
+
+    fn states_iter(&'a self) -> Self::Iter {
+        self.0.states_iter()
+    }
+}
+
+impl<'a, W, F, B, M> FstIterator<'a, W> for TrMapFst<W, F, B, M>
+where
+    W: Semiring,
+    F: ExpandedFst<W> + 'a,
+    B: Borrow<F> + 'a,
+    M: TrMapper<W> + 'a,
+{
+    type FstIter = <InnerLazyFst<W, F, B, M> as FstIterator<'a, W>>::FstIter;
+
+    fn fst_iter(&'a self) -> Self::FstIter {
+        self.0.fst_iter()
+    }
+}
+
+impl<W, F, B, M> Fst<W> for TrMapFst<W, F, B, M>
+where
+    W: Semiring,
+    F: ExpandedFst<W> + 'static,
+    B: Borrow<F> + 'static,
+    M: TrMapper<W> + 'static,
+{
+    fn input_symbols(&self) -> Option<&Arc<SymbolTable>> {
+        self.0.input_symbols()
+    }
+
+    fn output_symbols(&self) -> Option<&Arc<SymbolTable>> {
+        self.0.output_symbols()
+    }
+
+    fn set_input_symbols(&mut self, symt: Arc<SymbolTable>) {
+        self.0.set_input_symbols(symt)
+    }
+
+    fn set_output_symbols(&mut self, symt: Arc<SymbolTable>) {
+        self.0.set_output_symbols(symt)
+    }
+
+    fn take_input_symbols(&mut self) -> Option<Arc<SymbolTable>> {
+        self.0.take_input_symbols()
+    }
+
+    fn take_output_symbols(&mut self) -> Option<Arc<SymbolTable>> {
+        self.0.take_output_symbols()
+    }
+}
+
+impl<W, F, B, M> Debug for TrMapFst<W, F, B, M>
+where
+    W: Semiring,
+    F: ExpandedFst<W>,
+    B: Borrow<F>,
+    M: TrMapper<W>,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::algorithms::tr_map;
+    use crate::algorithms::tr_mappers::{QuantizeMapper, TimesMapper};
+    use crate::fst_impls::VectorFst;
+    use crate::semirings::TropicalWeight;
+
+    fn build_fst() -> Result<VectorFst<TropicalWeight>> {
+        let mut fst = VectorFst::new();
+        fst.add_states(3);
+        fst.set_start(0)?;
+        fst.add_tr(0, Tr::new(1, 2, 1.23456, 1))?;
+        fst.add_tr(0, Tr::new(3, 4, 2.5, 2))?;
+        fst.add_tr(1, Tr::new(5, 6, 0.3333, 2))?;
+        fst.set_final(1, 0.5)?;
+        fst.set_final(2, 1.75)?;
+        Ok(fst)
+    }
+
+    #[test]
+    fn test_tr_map_fst_sync() {
+        fn is_sync<T: Sync>() {}
+        is_sync::<TrMapFst<TropicalWeight, VectorFst<_>, Arc<VectorFst<_>>, Arc<QuantizeMapper>>>();
+    }
+
+    #[test]
+    fn test_tr_map_fst() -> Result<()> {
+        let fst = build_fst()?;
+
+        let mapper = QuantizeMapper::new(0.1);
+        let mut expected = fst.clone();
+        tr_map(&mut expected, &mapper)?;
+        let lazy = TrMapFst::<_, VectorFst<_>, _, _>::new(&fst, &mapper);
+        assert_eq!(lazy.compute::<VectorFst<_>>()?, expected);
+
+        let mapper = TimesMapper::new(2.0);
+        let mut expected = fst.clone();
+        tr_map(&mut expected, &mapper)?;
+        let lazy = TrMapFst::<_, VectorFst<_>, _, _>::new(&fst, &mapper);
+        // Only the expanded states are mapped.
+        assert_eq!(lazy.get_trs(1)?.trs(), expected.get_trs(1)?.trs());
+        assert_eq!(lazy.compute::<VectorFst<_>>()?, expected);
+        Ok(())
+    }
+
+    struct FinalLabelMapper {
+        final_action: MapFinalAction,
+    }
+
+    impl TrMapper<TropicalWeight> for FinalLabelMapper {
+        fn tr_map(&self, _tr: &mut Tr<TropicalWeight>) -> Result<()> {
+            Ok(())
+        }
+
+        fn final_tr_map(&self, final_tr: &mut FinalTr<TropicalWeight>) -> Result<()> {
+            // Only the final weight of the state 2 gets a label.
+            if final_tr.weight == TropicalWeight::new(1.75) {
+                final_tr.ilabel = 7;
+                final_tr.olabel = 7;
+            }
+            Ok(())
+        }
+
+        fn final_action(&self) -> MapFinalAction {
+            match self.final_action {
+                MapFinalAction::MapNoSuperfinal => MapFinalAction::MapNoSuperfinal,
+                MapFinalAction::MapAllowSuperfinal => MapFinalAction::MapAllowSuperfinal,
+                MapFinalAction::MapRequireSuperfinal => MapFinalAction::MapRequireSuperfinal,
+            }
+        }
+
+        fn properties(&self, _inprops: FstProperties) -> FstProperties {
+            FstProperties::empty()
+        }
+    }
+
+    #[test]
+    fn test_tr_map_fst_superfinal() -> Result<()> {
+        let fst = build_fst()?;
+        for final_action in [
+            MapFinalAction::MapAllowSuperfinal,
+            MapFinalAction::MapRequireSuperfinal,
+        ] {
+            let mapper = FinalLabelMapper { final_action };
+            let mut expected = fst.clone();
+            tr_map(&mut expected, &mapper)?;
+            assert_eq!(expected.num_states(), 4);
+            let lazy = TrMapFst::<_, VectorFst<_>, _, _>::new(&fst, &mapper);
+            assert_eq!(lazy.compute::<VectorFst<_>>()?, expected);
+        }
+
+        let mapper = FinalLabelMapper {
+            final_action: MapFinalAction::MapNoSuperfinal,
+        };
+        let lazy = TrMapFst::<_, VectorFst<_>, _, _>::new(&fst, &mapper);
+        assert!(lazy.final_weight(1).is_ok());
+        assert!(lazy.final_weight(2).is_err());
+        Ok(())
+    }
+}
@@ -46,6 +46,36 @@ impl TrCompare for OLabelCompare {
     }
 }
 
+/// Compare input labels, then weights.
+pub struct ILabelWeightCompare {}
+
+impl TrCompare for ILabelWeightCompare {
+    fn compare<W: Semiring>(a: &Tr<W>, b: &Tr<W>) -> Ordering {
+        ILabelCompare::compare(a, b).then_with(|| compare_weights(a, b))
+    }
+
+    fn properties(inprops: FstProperties) -> FstProperties {
+        ILabelCompare::properties(inprops)
+    }
+}
+
+/// Compare output labels, then weights.
+pub struct OLabelWeightCompare {}
+
+impl TrCompare for OLabelWeightCompare {
+    fn compare<W: Semiring>(a: &Tr<W>, b: &Tr<W>) -> Ordering {
+        OLabelCompare::compare(a, b).then_with(|| compare_weights(a, b))
+    }
+
+    fn properties(inprops: FstProperties) -> FstProperties {
+        OLabelCompare::properties(inprops)
+    }
+}
+
+fn compare_weights<W: Semiring>(a: &Tr<W>, b: &Tr<W>) -> Ordering {
+    a.weight.partial_cmp(&b.weight).unwrap_or(Ordering::Equal)
+}
+
 /// Sorts trs leaving each state of the FST using a compare function
 // The compare function could be passed only with the generic parameters but it seems less intuitive.
 pub fn tr_sort<W, F, C>(fst: &mut F, _comp: C)
@@ -60,3 +90,84 @@ where
     }
     fst.set_properties_with_mask(C::properties(props), FstProperties::all_properties());
 }
+
+/// Sorts trs leaving each state of the FST using an arbitrary compare function.
+///
+/// As the order defined by `cmp` is unknown, the `I_LABEL_SORTED` and `O_LABEL_SORTED`
+/// properties are cleared, even if the trs end up sorted by label. Use
+/// [`tr_sort`](fn.tr_sort.html) with a [`TrCompare`](tr_compares/trait.TrCompare.html)
+/// (e.g. `OLabelWeightCompare`) to keep them.
+pub fn tr_sort_by<W, F, C>(fst: &mut F, cmp: C)
+where
+    W: Semiring,
+    F: MutableFst<W>,
+    C: Fn(&Tr<W>, &Tr<W>) -> Ordering,
+{
+    let props = fst.properties();
+    for state in 0..(fst.num_states() as StateId) {
+        fst.sort_trs_unchecked(state, &cmp);
+    }
+    fst.set_properties_with_mask(
+        props & FstProperties::arcsort_properties(),
+        FstProperties::all_properties(),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fst_impls::VectorFst;
+    use crate::fst_traits::CoreFst;
+    use crate::semirings::TropicalWeight;
+    use crate::Label;
+    use anyhow::Result;
+
+    fn fst() -> Result<VectorFst<TropicalWeight>> {
+        let mut fst = VectorFst::new();
+        fst.add_states(2);
+        fst.set_start(0)?;
+        fst.emplace_tr(0, 1, 2, 3.0, 1)?;
+        fst.emplace_tr(0, 2, 1, 2.0, 1)?;
+        fst.emplace_tr(0, 3, 1, 1.0, 1)?;
+        fst.set_final(1, 0.0)?;
+        Ok(fst)
+    }
+
+    fn olabels_and_weights(fst: &VectorFst<TropicalWeight>) -> Result<Vec<(Label, f32)>> {
+        Ok(fst
+            .get_trs(0)?
+            .iter()
+            .map(|tr| (tr.olabel, *tr.weight.value()))
+            .collect())
+    }
+
+    #[test]
+    fn test_tr_sort_olabel_weight() -> Result<()> {
+        let mut fst = fst()?;
+        tr_sort(&mut fst, OLabelWeightCompare {});
+        assert_eq!(
+            olabels_and_weights(&fst)?,
+            vec![(1, 1.0), (1, 2.0), (2, 3.0)]
+        );
+        assert!(fst.properties().contains(FstProperties::O_LABEL_SORTED));
+        Ok(())
+    }
+
+    #[test]
+    fn test_tr_sort_by_clears_sorted_properties() -> Result<()> {
+        let mut fst = fst()?;
+        tr_sort(&mut fst, ILabelCompare {});
+        assert!(fst.properties().contains(FstProperties::I_LABEL_SORTED));
+
+        tr_sort_by(&mut fst, |a, b| {
+            b.weight.partial_cmp(&a.weight).unwrap_or(Ordering::Equal)
+        });
+        assert_eq!(
+            olabels_and_weights(&fst)?,
+            vec![(2, 3.0), (1, 2.0), (1, 1.0)]
+        );
+        assert!(!fst.properties().contains(FstProperties::I_LABEL_SORTED));
+        assert!(!fst.properties().contains(FstProperties::O_LABEL_SORTED));
+        Ok(())
+    }
+}
@@ -0,0 +1,292 @@
+use std::borrow::Borrow;
+use std::fmt::Debug;
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use anyhow::Result;
+
+use crate::algorithms::lazy::{FstOp2, LazyFst2, SimpleVecCache};
+use crate::algorithms::tr_compares::TrCompare;
+use crate::fst_properties::FstProperties;
+use crate::fst_traits::{CoreFst, Fst, FstIterator, MutableFst, StateIterator};
+use crate::semirings::Semiring;
+use crate::{StateId, SymbolTable, Trs, TrsVec};
+
+pub struct TrSortOp<W: Semiring, F: CoreFst<W>, B: Borrow<F>, C: TrCompare> {
+    fst: B,
+    properties: FstProperties,
+    w: PhantomData<W>,
+    f: PhantomData<F>,
+    c: PhantomData<C>,
+}
+
+impl<W: Semiring, F: CoreFst<W>, B: Borrow<F>, C: TrCompare> Debug for TrSortOp<W, F, B, C> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "TrSortOp {{ properties : {:?} }}", self.properties)
+    }
+}
+
+impl<W: Semiring, F: CoreFst<W>, B: Borrow<F>, C: TrCompare> TrSortOp<W, F, B, C> {
+    pub fn new(fst: B) -> Self {
+        let properties = C::properties(fst.borrow().properties());
+        Self {
+            fst,
+            properties,
+            w: PhantomData,
+            f: PhantomData,
+            c: PhantomData,
+        }
+    }
+}
+
+impl<W: Semiring, F: CoreFst<W>, B: Borrow<F>, C: TrCompare> FstOp2<W> for TrSortOp<W, F, B, C> {
+    fn compute_start(&self) -> Result<Option<StateId>> {
+        Ok(self.fst.borrow().start())
+    }
+
+    fn compute_trs_and_final_weight(&self, state: StateId) -> Result<(TrsVec<W>, Option<W>)> {
+        let fst = self.fst.borrow();
+        let mut trs = fst.get_trs(state)?.trs().to_vec();
+        trs.sort_by(C::compare);
+        Ok((TrsVec(Arc::new(trs)), fst.final_weight(state)?))
+    }
+
+    fn properties(&self) -> FstProperties {
+        self.properties
+    }
+}
+
+type InnerLazyFst<W, F, B, C> = LazyFst2<W, TrSortOp<W, F, B, C>, SimpleVecCache<W>>;
+
+/// Read-only view of an FST whose trs are sorted using a compare function, without
+/// modifying or copying the underlying FST. The trs of a state are sorted the first
+/// time they are accessed and then cached. This is the delayed version of
+/// [`tr_sort`](fn.tr_sort.html).
+///
+/// # Example
+/// ```
+/// # use rustfst::fst_impls::VectorFst;
+/// # use rustfst::fst_traits::{CoreFst, MutableFst};
+/// # use rustfst::semirings::{Semiring, TropicalWeight};
+/// # use rustfst::algorithms::TrSortFst;
+/// # use rustfst::algorithms::tr_compares::ILabelCompare;
+/// # use rustfst::{Tr, Trs};
+/// # use anyhow::Result;
+/// # fn main() -> Result<()> {
+/// let mut fst = VectorFst::<TropicalWeight>::new();
+/// fst.add_states(2);
+/// fst.set_start(0)?;
+/// fst.set_final(1, TropicalWeight::one())?;
+/// fst.add_tr(0, Tr::new(3, 3, 1.0, 1))?;
+/// fst.add_tr(0, Tr::new(1, 1, 2.0, 1))?;
+///
+/// let sorted_fst = TrSortFst::<_, VectorFst<_>, _, _>::new(&fst, ILabelCompare {})?;
+/// assert_eq!(
+///     sorted_fst.get_trs(0)?.trs(),
+///     &[Tr::new(1, 1, 2.0, 1), Tr::new(3, 3, 1.0, 1)]
+/// );
+/// # Ok(())
+/// # }
+/// ```
+pub struct TrSortFst<W: Semiring, F: CoreFst<W>, B: Borrow<F>, C: TrCompare>(
+    InnerLazyFst<W, F, B, C>,
+);
+
+impl<W, F, B, C> CoreFst<W> for TrSortFst<W, F, B, C>
+where
+    W: Semiring,
+    F: CoreFst<W>,
+    B: Borrow<F>,
+    C: TrCompare,
+{
+    type TRS = TrsVec<W>;
+
+    fn start(&self) -> Option<StateId> {
+        self.0.start()
+    }
+
+    fn final_weight(&self, state_id: StateId) -> Result<Option<W>> {
+        self.0.final_weight(state_id)
+    }
+
+    unsafe fn final_weight_unchecked(&self, state_id: StateId) -> Option<W> {
+        self.0.final_weight_unchecked(state_id)
+    }
+
+    fn num_trs(&self, s: StateId) -> Result<usize> {
+        self.0.num_trs(s)
+    }
+
+    unsafe fn num_trs_unchecked(&self, s: StateId) -> usize {
+        self.0.num_trs_unchecked(s)
+    }
+
+    fn get_trs(&self, state_id: StateId) -> Result<Self::TRS> {
+        self.0.get_trs(state_id)
+    }
+
+    unsafe fn get_trs_unchecked(&self, state_id: StateId) -> Self::TRS {
+        self.0.get_trs_unchecked(state_id)
+    }
+
+    fn properties(&self) -> FstProperties {
+        self.0.properties()
+    }
+
+    fn num_input_epsilons(&self, state: StateId) -> Result<usize> {
+        self.0.num_input_epsilons(state)
+    }
+
+    fn num_output_epsilons(&self, state: StateId) -> Result<usize> {
+        self.0.num_output_epsilons(state)
+    }
+}
+
+impl<'a, W, F, B, C> StateIterator<'a> for TrSortFst<W, F, B, C>
+where
+    W: Semiring,
+    F: CoreFst<W> + 'a,
+    B: Borrow<F> + 'a,
+    C: TrCompare + 'a,
+{
+    type Iter = <InnerLazyFst<W, F, B, C> as StateIterator<'a>>::Iter;
+
+    fn states_iter(&'a self) -> Self::Iter {
+        self.0.states_iter()
+    }
+}
+
+impl<'a, W, F, B, C> FstIterator<'a, W> for TrSortFst<W, F, B, C>
+where
+    W: Semiring,
+    F: CoreFst<W> + 'a,
+    B: Borrow<F> + 'a,
+    C: TrCompare + 'a,
+{
+    type FstIter = <InnerLazyFst<W, F, B, C> as FstIterator<'a, W>>::FstIter;
+
+    fn fst_iter(&'a self) -> Self::FstIter {
+        self.0.fst_iter()
+    }
+}
+
+impl<W, F, B, C> Fst<W> for TrSortFst<W, F, B, C>
+where
+    W: Semiring,
+    F: CoreFst<W> + 'static,
+    B: Borrow<F> + 'static,
+    C: TrCompare + 'static,
+{
+    fn input_symbols(&self) -> Option<&Arc<SymbolTable>> {
+        self.0.input_symbols()
+    }
+
+    fn output_symbols(&self) -> Option<&Arc<SymbolTable>> {
+        self.0.output_symbols()
+    }
+
+    fn set_input_symbols(&mut self, symt: Arc<SymbolTable>) {
+        self.0.set_input_symbols(symt)
+    }
+
+    fn set_output_symbols(&mut self, symt: Arc<SymbolTable>) {
+        self.0.set_output_symbols(symt)
+    }
+
+    fn take_input_symbols(&mut self) -> Option<Arc<SymbolTable>> {
+        self.0.take_input_symbols()
+    }
+
+    fn take_output_symbols(&mut self) -> Option<Arc<SymbolTable>> {
+        self.0.take_output_symbols()
+    }
+}
+
+impl<W, F, B, C> Debug for TrSortFst<W, F, B, C>
+where
+    W: Semiring,
+    F: CoreFst<W>,
+    B: Borrow<F>,
+    C: TrCompare,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<W, F, B, C> TrSortFst<W, F, B, C>
+where
+    W: Semiring,
+    F: Fst<W>,
+    B: Borrow<F>,
+    C: TrCompare,
+{
+    // The compare function could be passed only with the generic parameters but it seems less intuitive.
+    pub fn new(fst: B, _comp: C) -> Result<Self> {
+        let isymt = fst.borrow().input_symbols().cloned();
+        let osymt = fst.borrow().output_symbols().cloned();
+        let fst_op = TrSortOp::new(fst);
+        let fst_cache = SimpleVecCache::default();
+        let lazy_fst = LazyFst2::from_op_and_cache(fst_op, fst_cache, isymt, osymt);
+        Ok(TrSortFst(lazy_fst))
+    }
+
+    /// Turns the Lazy FST into a static one.
+    pub fn compute<F2: MutableFst<W>>(&self) -> Result<F2> {
+        self.0.compute()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::tr_compares::{ILabelCompare, OLabelCompare};
+    use crate::algorithms::tr_sort;
+    use crate::fst_impls::VectorFst;
+    use crate::fst_traits::ExpandedFst;
+    use crate::semirings::TropicalWeight;
+    use crate::Tr;
+
+    #[test]
+    fn test_tr_sort_fst() -> Result<()> {
+        let mut fst = VectorFst::<TropicalWeight>::new();
+        fst.add_states(3);
+        fst.set_start(0)?;
+        fst.set_final(2, 0.5)?;
+        fst.add_tr(0, Tr::new(3, 1, 1.0, 1))?;
+        fst.add_tr(0, Tr::new(1, 2, 2.0, 2))?;
+        fst.add_tr(0, Tr::new(2, 3, 3.0, 1))?;
+        fst.add_tr(1, Tr::new(5, 1, 1.0, 2))?;
+        fst.add_tr(1, Tr::new(4, 2, 1.0, 0))?;
+        fst.add_tr(2, Tr::new(2, 1, 1.0, 0))?;
+        let fst_ref = fst.clone();
+
+        let mut fst_sorted = fst.clone();
+        tr_sort(&mut fst_sorted, ILabelCompare {});
+
+        let sorted_fst = TrSortFst::<_, VectorFst<_>, _, _>::new(&fst, ILabelCompare {})?;
+        for s in fst.states_range() {
+            assert_eq!(sorted_fst.get_trs(s)?.trs(), fst_sorted.get_trs(s)?.trs());
+            assert_eq!(sorted_fst.final_weight(s)?, fst_sorted.final_weight(s)?);
+        }
+        assert!(sorted_fst
+            .properties()
+            .contains(FstProperties::I_LABEL_SORTED));
+
+        // The sorted trs are cached.
+        let trs_1 = sorted_fst.get_trs(0)?;
+        let trs_2 = sorted_fst.get_trs(0)?;
+        assert!(Arc::ptr_eq(&trs_1.0, &trs_2.0));
+
+        // The underlying FST is untouched.
+        assert_eq!(fst, fst_ref);
+
+        let mut fst_sorted = fst.clone();
+        tr_sort(&mut fst_sorted, OLabelCompare {});
+        let computed: VectorFst<_> =
+            TrSortFst::<_, VectorFst<_>, _, _>::new(&fst, OLabelCompare {})?.compute()?;
+        assert_eq!(computed, fst_sorted);
+
+        Ok(())
+    }
+}
@@ -2,4 +2,4 @@ mod union_fst;
 mod union_static;
 
 pub use union_fst::UnionFst;
-pub use union_static::union;
+pub use union_static::{union, union_slice};
@@ -117,3 +117,154 @@ where
     );
     Ok(())
 }
+
+/// Performs the union of a sequence of wFSTs in one go : a new start state is created with an
+/// epsilon transition to the start state of each operand. Contrary to chaining calls to
+/// [`union`](fn.union.html), the operands are only copied once, which matters when
+/// combining many FSTs.
+///
+/// Operands without a start state are ignored. If none has a start state, the result is
+/// the empty FST.
+///
+/// # Example
+/// ```
+/// # use anyhow::Result;
+/// # use rustfst::utils::acceptor;
+/// # use rustfst::semirings::{Semiring, TropicalWeight};
+/// # use rustfst::fst_impls::VectorFst;
+/// # use rustfst::fst_traits::Fst;
+/// # use rustfst::algorithms::union::union_slice;
+/// # fn main() -> Result<()> {
+/// let pronunciations: Vec<VectorFst<TropicalWeight>> = vec![
+///     acceptor(&[1, 2], TropicalWeight::one()),
+///     acceptor(&[1, 3], TropicalWeight::new(0.5)),
+///     acceptor(&[4], TropicalWeight::new(1.0)),
+/// ];
+///
+/// let fst: VectorFst<_> = union_slice(&pronunciations)?;
+/// let mut paths: Vec<_> = fst.paths_iter().map(|p| p.ilabels).collect();
+/// paths.sort();
+/// assert_eq!(paths, vec![vec![1, 2], vec![1, 3], vec![4]]);
+/// # Ok(())
+/// # }
+/// ```
+pub fn union_slice<W, F1, F2>(fsts: &[F1]) -> Result<F2>
+where
+    W: Semiring,
+    F1: ExpandedFst<W>,
+    F2: MutableFst<W> + AllocableFst<W>,
+{
+    let mut res = F2::new();
+    let operands: Vec<_> = fsts
+        .iter()
+        .filter_map(|fst| fst.start().map(|start| (fst, start)))
+        .collect();
+    if operands.is_empty() {
+        return Ok(res);
+    }
+
+    let num_states = 1 + operands
+        .iter()
+        .map(|(fst, _)| fst.num_states())
+        .sum::<usize>();
+    res.reserve_states(num_states);
+    let nstart = res.add_state();
+    unsafe {
+        res.set_start_unchecked(nstart);
+        res.reserve_trs_unchecked(nstart, operands.len());
+    }
+
+    let mut props = None;
+    for (fst, start) in operands {
+        let offset = res.num_states() as StateId;
+        for s in 0..(fst.num_states() as StateId) {
+            let new_s = res.add_state();
+            if let Some(final_weight) = unsafe { fst.final_weight_unchecked(s) } {
+                unsafe { res.set_final_unchecked(new_s, final_weight) };
+            }
+            unsafe { res.reserve_trs_unchecked(new_s, fst.num_trs_unchecked(s)) };
+            for tr in unsafe { fst.get_trs_unchecked(s).trs() } {
+                let mut new_tr = tr.clone();
+                new_tr.nextstate += offset;
+                unsafe { res.add_tr_unchecked(new_s, new_tr) };
+            }
+        }
+        unsafe {
+            res.add_tr_unchecked(
+                nstart,
+                Tr::new(EPS_LABEL, EPS_LABEL, W::one(), start + offset),
+            )
+        };
+
+        let fst_props = fst.properties();
+        props = Some(match props {
+            // The union of an FST with itself describes the new start state with a single
+            // epsilon transition as well.
+            None => union_properties(fst_props, fst_props, false),
+            Some(props) => union_properties(props, fst_props, false),
+        });
+    }
+
+    if let Some(props) = props {
+        res.set_properties_with_mask(props, FstProperties::all_properties());
+    }
+    Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::determinize::determinize;
+    use crate::algorithms::rm_epsilon::rm_epsilon;
+    use crate::algorithms::{equivalent, verify};
+    use crate::fst_impls::VectorFst;
+    use crate::fst_traits::CoreFst;
+    use crate::semirings::TropicalWeight;
+    use crate::utils::acceptor;
+    use crate::KDELTA;
+
+    #[test]
+    fn test_union_slice_matches_union() -> Result<()> {
+        let mut cyclic: VectorFst<TropicalWeight> = acceptor(&[5], TropicalWeight::new(0.5));
+        cyclic.add_tr(1, Tr::new(6, 6, 1.0, 0))?;
+        let fsts = vec![
+            acceptor(&[1, 2], TropicalWeight::new(1.0)),
+            VectorFst::new(),
+            cyclic,
+            acceptor(&[1, 3], TropicalWeight::new(2.0)),
+        ];
+
+        let fst_slice: VectorFst<_> = union_slice(&fsts)?;
+        let mut fst_chained = fsts[0].clone();
+        for fst in fsts[1..].iter() {
+            union(&mut fst_chained, fst)?;
+        }
+
+        assert_eq!(
+            fst_slice.num_states(),
+            1 + fsts.iter().map(|f| f.num_states()).sum::<usize>()
+        );
+        assert_eq!(fst_slice.num_trs(fst_slice.start().unwrap())?, 3);
+
+        verify(&fst_slice)?;
+
+        let mut fst_slice = fst_slice;
+        rm_epsilon(&mut fst_slice)?;
+        rm_epsilon(&mut fst_chained)?;
+        let fst_slice: VectorFst<_> = determinize(&fst_slice)?;
+        let fst_chained: VectorFst<_> = determinize(&fst_chained)?;
+        assert!(equivalent(&fst_slice, &fst_chained, KDELTA)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_union_slice_empty() -> Result<()> {
+        let fst: VectorFst<TropicalWeight> = union_slice::<_, VectorFst<_>, _>(&[])?;
+        assert_eq!(fst.start(), None);
+        assert_eq!(fst.num_states(), 0);
+
+        let fst: VectorFst<TropicalWeight> = union_slice(&[VectorFst::new(), VectorFst::new()])?;
+        assert_eq!(fst.start(), None);
+        Ok(())
+    }
+}
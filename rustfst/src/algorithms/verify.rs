@@ -0,0 +1,192 @@
+use anyhow::Result;
+
+use crate::fst_properties::{
+    compat_properties, compute_fst_properties, known_properties, FstProperties,
+};
+use crate::fst_traits::ExpandedFst;
+use crate::semirings::Semiring;
+use crate::Trs;
+
+/// Checks the sanity of an FST : the start state and the destination states of all the
+/// transitions must exist and all the weights must be members of the semiring
+/// (see [`Semiring::is_member`](../semirings/trait.Semiring.html#method.is_member)).
+/// Finally, the properties stored in the FST must hold : they are compared with the
+/// properties computed from the FST.
+///
+/// This is typically useful to detect a corrupted FST after deserialization.
+///
+/// # Example
+///
+/// ```
+/// # use anyhow::Result;
+/// # use rustfst::fst_impls::VectorFst;
+/// # use rustfst::fst_traits::MutableFst;
+/// # use rustfst::semirings::{Semiring, TropicalWeight};
+/// # use rustfst::algorithms::verify;
+/// # use rustfst::Tr;
+/// # fn main() -> Result<()> {
+/// let mut fst = VectorFst::<TropicalWeight>::new();
+/// fst.add_states(2);
+/// fst.set_start(0)?;
+/// fst.add_tr(0, Tr::new(1, 1, 1.0, 1))?;
+/// fst.set_final(1, TropicalWeight::one())?;
+/// assert!(verify(&fst).is_ok());
+///
+/// fst.add_tr(0, Tr::new(1, 1, std::f32::NAN, 1))?;
+/// assert!(verify(&fst).is_err());
+/// # Ok(())
+/// # }
+/// ```
+pub fn verify<W: Semiring, F: ExpandedFst<W>>(fst: &F) -> Result<()> {
+    let num_states = fst.num_states();
+    if let Some(start) = fst.start() {
+        if start as usize >= num_states {
+            bail!("Verify: FST start state ID exceeds number of states");
+        }
+    }
+
+    for s in fst.states_range() {
+        for (idx, tr) in fst.get_trs(s)?.trs().iter().enumerate() {
+            if tr.nextstate as usize >= num_states {
+                bail!(
+                    "Verify: FST transition destination state ID {} exceeds number of states at state ID {}, transition {}",
+                    tr.nextstate,
+                    s,
+                    idx
+                );
+            }
+            if !tr.weight.is_member() {
+                bail!(
+                    "Verify: FST weight {:?} is not a member of the semiring at state ID {}, transition {}",
+                    tr.weight,
+                    s,
+                    idx
+                );
+            }
+        }
+        if let Some(final_weight) = fst.final_weight(s)? {
+            if !final_weight.is_member() {
+                bail!(
+                    "Verify: FST final weight {:?} is not a member of the semiring at state ID {}",
+                    final_weight,
+                    s
+                );
+            }
+        }
+    }
+
+    let stored_props = fst.properties();
+    let computed_props = compute_fst_properties(
+        fst,
+        FstProperties::all_properties(),
+        &mut FstProperties::empty(),
+        false,
+    )?;
+    if !compat_properties(stored_props, computed_props) {
+        let known_props = known_properties(stored_props);
+        bail!(
+            "Verify: FST properties {:?} are stored but don't hold. Computed properties : {:?}",
+            (stored_props & known_props) ^ (computed_props & known_props),
+            computed_props
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fst_impls::VectorFst;
+    use crate::fst_traits::{CoreFst, MutableFst};
+    use crate::semirings::{
+        LogWeight, ProbabilityWeight, StringWeightLeft, StringWeightVariant, TropicalWeight,
+    };
+    use crate::Tr;
+
+    #[test]
+    fn test_is_member() {
+        assert!(TropicalWeight::one().is_member());
+        assert!(TropicalWeight::zero().is_member());
+        assert!(!TropicalWeight::new(f32::NAN).is_member());
+        assert!(!TropicalWeight::new(f32::NEG_INFINITY).is_member());
+        assert!(LogWeight::zero().is_member());
+        assert!(!LogWeight::new(f32::NAN).is_member());
+        assert!(ProbabilityWeight::new(f32::INFINITY).is_member());
+        assert!(!ProbabilityWeight::new(f32::NAN).is_member());
+        assert!(StringWeightLeft::zero().is_member());
+        assert!(StringWeightLeft::from(vec![1, 2]).is_member());
+        assert!(!StringWeightLeft::new(StringWeightVariant::Labels(vec![1, 0])).is_member());
+    }
+
+    #[test]
+    fn test_verify_nan_weight() -> Result<()> {
+        let mut fst = VectorFst::<TropicalWeight>::new();
+        fst.add_states(2);
+        fst.set_start(0)?;
+        fst.set_final(1, TropicalWeight::one())?;
+        fst.add_tr(0, Tr::new(1, 1, TropicalWeight::zero(), 1))?;
+        verify(&fst)?;
+
+        fst.add_tr(0, Tr::new(2, 2, f32::NAN, 1))?;
+        assert!(verify(&fst).is_err());
+
+        let mut fst = VectorFst::<TropicalWeight>::new();
+        fst.add_states(1);
+        fst.set_start(0)?;
+        fst.set_final(0, f32::NAN)?;
+        assert!(verify(&fst).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_verify_structure() -> Result<()> {
+        let mut fst = VectorFst::<TropicalWeight>::new();
+        fst.add_states(2);
+        fst.set_start(0)?;
+        fst.set_final(1, TropicalWeight::one())?;
+        verify(&fst)?;
+
+        // The mutable API rejects invalid states, the trs are modified directly to mimic
+        // a corrupted FST.
+        let mut fst_bad_tr = fst.clone();
+        unsafe { fst_bad_tr.add_tr_unchecked(0, Tr::new(1, 1, 1.0, 5)) };
+        let err = verify(&fst_bad_tr).unwrap_err().to_string();
+        assert!(err.contains("state ID 0, transition 0"), "{}", err);
+
+        let mut fst_bad_start = fst.clone();
+        unsafe { fst_bad_start.set_start_unchecked(3) };
+        assert!(verify(&fst_bad_start).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_verify_properties() -> Result<()> {
+        let mut fst = VectorFst::<TropicalWeight>::new();
+        fst.add_states(2);
+        fst.set_start(0)?;
+        fst.set_final(1, TropicalWeight::one())?;
+        fst.add_tr(0, Tr::new(1, 2, 1.0, 1))?;
+        fst.add_tr(1, Tr::new(1, 1, 1.0, 0))?;
+        verify(&fst)?;
+
+        // Wrongly claimed properties.
+        let mut fst_bad_props = fst.clone();
+        fst_bad_props.set_properties_with_mask(
+            FstProperties::ACCEPTOR | FstProperties::ACYCLIC,
+            FstProperties::ACCEPTOR
+                | FstProperties::NOT_ACCEPTOR
+                | FstProperties::ACYCLIC
+                | FstProperties::CYCLIC,
+        );
+        assert!(fst_bad_props.properties().contains(FstProperties::ACCEPTOR));
+        assert!(verify(&fst_bad_props).is_err());
+
+        // Unknown properties are not checked.
+        let mut fst_no_props = fst.clone();
+        fst_no_props.set_properties(FstProperties::empty());
+        verify(&fst_no_props)?;
+        Ok(())
+    }
+}
@@ -9,6 +9,13 @@ use unsafe_unwrap::UnsafeUnwrap;
 
 /// The WeightConverter interfaces defines how a weight should be turned into another one.
 /// Useful for changing the semiring of an FST.
+///
+/// Built-in converters are available in [`weight_converters`](weight_converters/index.html) :
+/// - [`SimpleWeightConverter`](weight_converters/struct.SimpleWeightConverter.html) between
+///   semirings sharing the same underlying value, e.g. `TropicalWeight` and `LogWeight`.
+/// - [`ToGallicConverter`](weight_converters/struct.ToGallicConverter.html) and
+///   [`FromGallicConverter`](weight_converters/struct.FromGallicConverter.html) to turn a
+///   transducer into an acceptor over a Gallic semiring and back.
 pub trait WeightConverter<SI: Semiring, SO: Semiring> {
     fn tr_map(&mut self, tr: &Tr<SI>) -> Result<Tr<SO>>;
     fn final_tr_map(&mut self, final_tr: &FinalTr<SI>) -> Result<FinalTr<SO>>;
@@ -18,6 +25,31 @@ pub trait WeightConverter<SI: Semiring, SO: Semiring> {
 
 /// Convert an FST in a given Semiring to another Semiring using a WeightConverter
 /// to specify how the conversion should be performed.
+///
+/// # Example
+/// ```
+/// # use anyhow::Result;
+/// # use rustfst::algorithms::weight_convert;
+/// # use rustfst::algorithms::weight_converters::SimpleWeightConverter;
+/// # use rustfst::fst_impls::VectorFst;
+/// # use rustfst::fst_traits::{CoreFst, MutableFst};
+/// # use rustfst::semirings::{LogWeight, Semiring, TropicalWeight};
+/// # use rustfst::Tr;
+/// # fn main() -> Result<()> {
+/// let mut fst = VectorFst::<TropicalWeight>::new();
+/// fst.add_states(2);
+/// fst.set_start(0)?;
+/// fst.add_tr(0, Tr::new(1, 2, 1.5, 1))?;
+/// fst.set_final(1, 0.5)?;
+///
+/// let log_fst: VectorFst<LogWeight> = weight_convert(&fst, &mut SimpleWeightConverter {})?;
+/// assert_eq!(log_fst.final_weight(1)?, Some(LogWeight::new(0.5)));
+///
+/// let back: VectorFst<TropicalWeight> = weight_convert(&log_fst, &mut SimpleWeightConverter {})?;
+/// assert_eq!(back, fst);
+/// # Ok(())
+/// # }
+/// ```
 pub fn weight_convert<W1, W2, F1, F2, M>(fst_in: &F1, mapper: &mut M) -> Result<F2>
 where
     W1: Semiring,
@@ -139,7 +171,7 @@ where
 mod tests {
     use super::*;
     use crate::algorithms::weight_converters::SimpleWeightConverter;
-    use crate::fst_traits::Fst;
+    use crate::fst_traits::{CoreFst, Fst};
     use crate::prelude::{TropicalWeight, VectorFst};
     use crate::SymbolTable;
     use proptest::prelude::any;
@@ -160,4 +192,28 @@ mod tests {
             assert!(fst.output_symbols().is_some());
         }
     }
+
+    #[test]
+    fn test_weight_convert_gallic_round_trip() -> Result<()> {
+        use crate::algorithms::weight_converters::{FromGallicConverter, ToGallicConverter};
+        use crate::semirings::GallicWeightLeft;
+
+        let mut fst = VectorFst::<TropicalWeight>::new();
+        fst.add_states(3);
+        fst.set_start(0)?;
+        fst.add_tr(0, Tr::new(1, 2, 1.5, 1))?;
+        fst.add_tr(1, Tr::new(3, 0, 2.5, 2))?;
+        fst.set_final(2, 0.5)?;
+
+        let gallic_fst: VectorFst<GallicWeightLeft<TropicalWeight>> =
+            weight_convert(&fst, &mut ToGallicConverter {})?;
+        assert!(gallic_fst.properties().contains(FstProperties::ACCEPTOR));
+
+        let mut from_gallic = FromGallicConverter {
+            superfinal_label: EPS_LABEL,
+        };
+        let back: VectorFst<TropicalWeight> = weight_convert(&gallic_fst, &mut from_gallic)?;
+        assert_eq!(back, fst);
+        Ok(())
+    }
 }
@@ -7,8 +7,14 @@ use crate::semirings::{
 use crate::{Label, Tr, EPS_LABEL};
 use anyhow::Result;
 
-/// Mapper from GallicWeight<W> to W.
+/// Converter from a Gallic weight over `W` to `W`, inverse of
+/// [`ToGallicConverter`](struct.ToGallicConverter.html).
+///
+/// The string part of the weight is moved back to the output label. It must contain at most
+/// one label, otherwise the conversion fails. A final weight with a non-empty string part is
+/// turned into a transition to a superfinal state.
 pub struct FromGallicConverter {
+    /// Input label of the transitions to the superfinal state.
     pub superfinal_label: Label,
 }
 
@@ -80,9 +86,9 @@ macro_rules! impl_weight_converter_gallic {
             }
 
             fn final_tr_map(&mut self, final_tr: &FinalTr<$gallic<W>>) -> Result<FinalTr<W>> {
-                let (extracted_w, extracted_l) = $fextract(&final_tr.weight).expect("Fail");
+                let (extracted_w, extracted_l) = $fextract(&final_tr.weight)?;
                 if final_tr.ilabel != final_tr.olabel {
-                    panic!("Unrepresentable weight : {:?}", &final_tr);
+                    bail!("Unrepresentable weight : {:?}", &final_tr);
                 }
 
                 let new_final_tr = if final_tr.ilabel == EPS_LABEL && extracted_l != EPS_LABEL {
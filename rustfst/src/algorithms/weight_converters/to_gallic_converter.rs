@@ -10,7 +10,11 @@ use crate::semirings::{
 };
 use crate::{Tr, EPS_LABEL};
 
-/// Mapper from W to GallicTr<W>.
+/// Converter from `W` to a Gallic weight over `W`.
+///
+/// The output label of every transition is moved into the string part of the weight, turning
+/// a transducer into an acceptor on the input labels. The original FST can be recovered with
+/// [`FromGallicConverter`](struct.FromGallicConverter.html). Final weights must not be zero.
 pub struct ToGallicConverter {}
 
 macro_rules! impl_to_gallic_converter {
@@ -0,0 +1,297 @@
+use std::fmt::Debug;
+use std::io::Write;
+
+use anyhow::Result;
+use nom::IResult;
+
+use crate::fst_properties::FstProperties;
+use crate::parsers::nom_utils::NomCustomError;
+use crate::parsers::{parse_bin_i32, write_bin_i32};
+use crate::semirings::{Semiring, SerializableSemiring};
+use crate::{Label, StateId, Tr, NO_LABEL, NO_STATE_ID};
+
+/// Defines how the transitions of a [`CompactFst`](struct.CompactFst.html) are stored.
+///
+/// Each transition leaving a state is stored as an `Element`, from which the `Tr` is
+/// recomputed on the fly. The final weight of a final state is stored as an additional
+/// element placed before the others, expanded to a `Tr` with `NO_LABEL` labels and
+/// `NO_STATE_ID` as nextstate.
+///
+/// The binary serialization of the elements follows the memory layout used by OpenFST so
+/// that the files can be exchanged with it, which holds for 32 bits weights.
+pub trait Compactor<W: Semiring>: Debug + Clone + PartialEq + Sync + 'static {
+    type Element: Debug + Clone + PartialEq + Sync + 'static;
+
+    /// Name of the compactor. The type of the FST is `compact_` followed by this name.
+    fn compactor_type() -> String;
+
+    /// Number of elements per state if it is always the same, e.g. `Some(1)` for strings.
+    fn size() -> Option<usize>;
+
+    /// Properties of all the FSTs using this compactor.
+    fn properties() -> FstProperties;
+
+    /// Stores the transition leaving `state`.
+    fn compact(state: StateId, tr: &Tr<W>) -> Self::Element;
+
+    /// Recomputes the transition leaving `state`.
+    fn expand(state: StateId, element: &Self::Element) -> Tr<W>;
+
+    fn write_element<O: Write>(element: &Self::Element, output: &mut O) -> Result<()>
+    where
+        W: SerializableSemiring;
+
+    fn parse_element(i: &[u8]) -> IResult<&[u8], Self::Element, NomCustomError<&[u8]>>
+    where
+        W: SerializableSemiring;
+}
+
+fn write_label<O: Write>(output: &mut O, label: Label) -> Result<()> {
+    // NO_LABEL is written as -1 like in OpenFST.
+    write_bin_i32(output, label as i32)
+}
+
+fn parse_label(i: &[u8]) -> IResult<&[u8], Label, NomCustomError<&[u8]>> {
+    let (i, label) = parse_bin_i32(i)?;
+    Ok((i, label as Label))
+}
+
+fn final_tr<W: Semiring>(weight: W) -> Tr<W> {
+    Tr::new(NO_LABEL, NO_LABEL, weight, NO_STATE_ID)
+}
+
+/// Compactor for unweighted strings : a state has a single transition, to the next state,
+/// or is final with a weight of one. Only the label is stored.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct StringCompactor {}
+
+impl<W: Semiring> Compactor<W> for StringCompactor {
+    type Element = Label;
+
+    fn compactor_type() -> String {
+        "string".to_string()
+    }
+
+    fn size() -> Option<usize> {
+        Some(1)
+    }
+
+    fn properties() -> FstProperties {
+        FstProperties::STRING | FstProperties::ACCEPTOR | FstProperties::UNWEIGHTED
+    }
+
+    fn compact(_state: StateId, tr: &Tr<W>) -> Self::Element {
+        tr.ilabel
+    }
+
+    fn expand(state: StateId, element: &Self::Element) -> Tr<W> {
+        if *element == NO_LABEL {
+            final_tr(W::one())
+        } else {
+            Tr::new(*element, *element, W::one(), state + 1)
+        }
+    }
+
+    fn write_element<O: Write>(element: &Self::Element, output: &mut O) -> Result<()>
+    where
+        W: SerializableSemiring,
+    {
+        write_label(output, *element)
+    }
+
+    fn parse_element(i: &[u8]) -> IResult<&[u8], Self::Element, NomCustomError<&[u8]>>
+    where
+        W: SerializableSemiring,
+    {
+        parse_label(i)
+    }
+}
+
+/// Compactor for weighted strings : a state has a single transition, to the next state,
+/// or is final. The label and the weight are stored.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct WeightedStringCompactor {}
+
+impl<W: Semiring> Compactor<W> for WeightedStringCompactor {
+    type Element = (Label, W);
+
+    fn compactor_type() -> String {
+        "weighted_string".to_string()
+    }
+
+    fn size() -> Option<usize> {
+        Some(1)
+    }
+
+    fn properties() -> FstProperties {
+        FstProperties::STRING | FstProperties::ACCEPTOR
+    }
+
+    fn compact(_state: StateId, tr: &Tr<W>) -> Self::Element {
+        (tr.ilabel, tr.weight.clone())
+    }
+
+    fn expand(state: StateId, element: &Self::Element) -> Tr<W> {
+        if element.0 == NO_LABEL {
+            final_tr(element.1.clone())
+        } else {
+            Tr::new(element.0, element.0, element.1.clone(), state + 1)
+        }
+    }
+
+    fn write_element<O: Write>(element: &Self::Element, output: &mut O) -> Result<()>
+    where
+        W: SerializableSemiring,
+    {
+        write_label(output, element.0)?;
+        element.1.write_binary(output)
+    }
+
+    fn parse_element(i: &[u8]) -> IResult<&[u8], Self::Element, NomCustomError<&[u8]>>
+    where
+        W: SerializableSemiring,
+    {
+        let (i, label) = parse_label(i)?;
+        let (i, weight) = W::parse_binary(i)?;
+        Ok((i, (label, weight)))
+    }
+}
+
+/// Compactor for unweighted acceptors : the label and the nextstate are stored.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct UnweightedAcceptorCompactor {}
+
+impl<W: Semiring> Compactor<W> for UnweightedAcceptorCompactor {
+    type Element = (Label, StateId);
+
+    fn compactor_type() -> String {
+        "unweighted_acceptor".to_string()
+    }
+
+    fn size() -> Option<usize> {
+        None
+    }
+
+    fn properties() -> FstProperties {
+        FstProperties::ACCEPTOR | FstProperties::UNWEIGHTED
+    }
+
+    fn compact(_state: StateId, tr: &Tr<W>) -> Self::Element {
+        (tr.ilabel, tr.nextstate)
+    }
+
+    fn expand(_state: StateId, element: &Self::Element) -> Tr<W> {
+        Tr::new(element.0, element.0, W::one(), element.1)
+    }
+
+    fn write_element<O: Write>(element: &Self::Element, output: &mut O) -> Result<()>
+    where
+        W: SerializableSemiring,
+    {
+        write_label(output, element.0)?;
+        write_bin_i32(output, element.1 as i32)
+    }
+
+    fn parse_element(i: &[u8]) -> IResult<&[u8], Self::Element, NomCustomError<&[u8]>>
+    where
+        W: SerializableSemiring,
+    {
+        let (i, label) = parse_label(i)?;
+        let (i, nextstate) = parse_bin_i32(i)?;
+        Ok((i, (label, nextstate as StateId)))
+    }
+}
+
+/// Compactor for weighted acceptors : the label, the weight and the nextstate are stored.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct AcceptorCompactor {}
+
+impl<W: Semiring> Compactor<W> for AcceptorCompactor {
+    type Element = (Label, W, StateId);
+
+    fn compactor_type() -> String {
+        "acceptor".to_string()
+    }
+
+    fn size() -> Option<usize> {
+        None
+    }
+
+    fn properties() -> FstProperties {
+        FstProperties::ACCEPTOR
+    }
+
+    fn compact(_state: StateId, tr: &Tr<W>) -> Self::Element {
+        (tr.ilabel, tr.weight.clone(), tr.nextstate)
+    }
+
+    fn expand(_state: StateId, element: &Self::Element) -> Tr<W> {
+        Tr::new(element.0, element.0, element.1.clone(), element.2)
+    }
+
+    fn write_element<O: Write>(element: &Self::Element, output: &mut O) -> Result<()>
+    where
+        W: SerializableSemiring,
+    {
+        write_label(output, element.0)?;
+        element.1.write_binary(output)?;
+        write_bin_i32(output, element.2 as i32)
+    }
+
+    fn parse_element(i: &[u8]) -> IResult<&[u8], Self::Element, NomCustomError<&[u8]>>
+    where
+        W: SerializableSemiring,
+    {
+        let (i, label) = parse_label(i)?;
+        let (i, weight) = W::parse_binary(i)?;
+        let (i, nextstate) = parse_bin_i32(i)?;
+        Ok((i, (label, weight, nextstate as StateId)))
+    }
+}
+
+/// Compactor for unweighted transducers : both labels and the nextstate are stored.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct UnweightedCompactor {}
+
+impl<W: Semiring> Compactor<W> for UnweightedCompactor {
+    type Element = (Label, Label, StateId);
+
+    fn compactor_type() -> String {
+        "unweighted".to_string()
+    }
+
+    fn size() -> Option<usize> {
+        None
+    }
+
+    fn properties() -> FstProperties {
+        FstProperties::UNWEIGHTED
+    }
+
+    fn compact(_state: StateId, tr: &Tr<W>) -> Self::Element {
+        (tr.ilabel, tr.olabel, tr.nextstate)
+    }
+
+    fn expand(_state: StateId, element: &Self::Element) -> Tr<W> {
+        Tr::new(element.0, element.1, W::one(), element.2)
+    }
+
+    fn write_element<O: Write>(element: &Self::Element, output: &mut O) -> Result<()>
+    where
+        W: SerializableSemiring,
+    {
+        write_label(output, element.0)?;
+        write_label(output, element.1)?;
+        write_bin_i32(output, element.2 as i32)
+    }
+
+    fn parse_element(i: &[u8]) -> IResult<&[u8], Self::Element, NomCustomError<&[u8]>>
+    where
+        W: SerializableSemiring,
+    {
+        let (i, ilabel) = parse_label(i)?;
+        let (i, olabel) = parse_label(i)?;
+        let (i, nextstate) = parse_bin_i32(i)?;
+        Ok((i, (ilabel, olabel, nextstate as StateId)))
+    }
+}
@@ -0,0 +1,136 @@
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use anyhow::Result;
+
+use crate::fst_impls::compact_fst::Compactor;
+use crate::fst_properties::properties::EXPANDED;
+use crate::fst_properties::FstProperties;
+use crate::fst_traits::ExpandedFst;
+use crate::semirings::Semiring;
+use crate::{StateId, SymbolTable, Tr, Trs, NO_LABEL, NO_STATE_ID};
+
+/// Immutable FST whose transitions are stored in a compact form defined by a
+/// [`Compactor`](trait.Compactor.html), e.g. without weights for unweighted acceptors.
+/// The transitions are recomputed each time they are accessed, trading speed for memory.
+#[derive(Debug, Clone)]
+pub struct CompactFst<W: Semiring, C: Compactor<W>> {
+    /// Position of the first element of each state in `compacts` followed by the total number
+    /// of elements. `None` if the compactor has a fixed number of elements per state.
+    pub(crate) states: Option<Vec<u32>>,
+    pub(crate) compacts: Vec<C::Element>,
+    pub(crate) num_states: usize,
+    pub(crate) num_trs: usize,
+    pub(crate) start: Option<StateId>,
+    pub(crate) isymt: Option<Arc<SymbolTable>>,
+    pub(crate) osymt: Option<Arc<SymbolTable>>,
+    pub(crate) properties: FstProperties,
+    pub(crate) ghost: PhantomData<C>,
+}
+
+impl<W: Semiring, C: Compactor<W>> CompactFst<W, C> {
+    pub(crate) fn static_properties() -> u64 {
+        EXPANDED
+    }
+
+    /// Compacts an FST. Fails if one of its transitions or final weights can't be represented
+    /// by the compactor, e.g. a transition with different labels for an acceptor compactor.
+    pub fn from_fst<F: ExpandedFst<W>>(fst: &F) -> Result<Self> {
+        let num_states = fst.num_states();
+        let mut states = vec![];
+        let mut compacts = vec![];
+        let mut num_trs = 0;
+        for s in 0..(num_states as StateId) {
+            states.push(compacts.len() as u32);
+            let mut trs = vec![];
+            if let Some(final_weight) = fst.final_weight(s)? {
+                trs.push(Tr::new(NO_LABEL, NO_LABEL, final_weight, NO_STATE_ID));
+            }
+            let fst_trs = fst.get_trs(s)?;
+            num_trs += fst_trs.len();
+            trs.extend(fst_trs.trs().iter().cloned());
+            if let Some(size) = C::size() {
+                if trs.len() != size {
+                    bail!(
+                        "CompactFst : State {} has {} transitions and final weights instead of {}",
+                        s,
+                        trs.len(),
+                        size
+                    );
+                }
+            }
+            for tr in trs {
+                let element = C::compact(s, &tr);
+                if C::expand(s, &element) != tr {
+                    bail!(
+                        "CompactFst : Transition {:?} of state {} can't be represented by the {} compactor",
+                        tr,
+                        s,
+                        C::compactor_type()
+                    );
+                }
+                compacts.push(element);
+            }
+        }
+        if compacts.len() > u32::MAX as usize {
+            bail!("CompactFst : Too many transitions");
+        }
+        states.push(compacts.len() as u32);
+
+        let mut res = Self {
+            states: C::size().map_or(Some(states), |_| None),
+            compacts,
+            num_states,
+            num_trs,
+            start: fst.start(),
+            isymt: fst.input_symbols().cloned(),
+            osymt: fst.output_symbols().cloned(),
+            properties: FstProperties::empty(),
+            ghost: PhantomData,
+        };
+        // The properties are computed once as they won't be modified in the CompactFst.
+        let mut known = FstProperties::empty();
+        res.properties = crate::fst_properties::compute_fst_properties(
+            &res,
+            FstProperties::all_properties(),
+            &mut known,
+            false,
+        )?;
+        Ok(res)
+    }
+
+    /// Elements stored for the state `state`, which must exist.
+    pub(crate) fn elements(&self, state: StateId) -> &[C::Element] {
+        let s = state as usize;
+        match (&self.states, C::size()) {
+            (Some(states), _) => &self.compacts[states[s] as usize..states[s + 1] as usize],
+            (None, Some(size)) => &self.compacts[s * size..(s + 1) * size],
+            (None, None) => unreachable!(),
+        }
+    }
+
+    /// Final weight and transitions of the state `state`, which must exist.
+    pub(crate) fn expand_state(&self, state: StateId) -> (Option<W>, Vec<Tr<W>>) {
+        let mut final_weight = None;
+        let mut trs = vec![];
+        for element in self.elements(state) {
+            let tr = C::expand(state, element);
+            if tr.ilabel == NO_LABEL {
+                final_weight = Some(tr.weight);
+            } else {
+                trs.push(tr);
+            }
+        }
+        (final_weight, trs)
+    }
+}
+
+impl<W: Semiring, C: Compactor<W>> PartialEq for CompactFst<W, C> {
+    fn eq(&self, other: &Self) -> bool {
+        // Indended: Doesn't check symt and properties
+        self.states == other.states
+            && self.compacts == other.compacts
+            && self.num_states == other.num_states
+            && self.start == other.start
+    }
+}
@@ -0,0 +1,10 @@
+use crate::fst_impls::compact_fst::Compactor;
+use crate::fst_impls::CompactFst;
+use crate::fst_traits::ExpandedFst;
+use crate::semirings::Semiring;
+
+impl<W: 'static + Semiring, C: Compactor<W>> ExpandedFst<W> for CompactFst<W, C> {
+    fn num_states(&self) -> usize {
+        self.num_states
+    }
+}
@@ -0,0 +1,110 @@
+use std::sync::Arc;
+
+use anyhow::{format_err, Result};
+
+use crate::fst_impls::compact_fst::Compactor;
+use crate::fst_impls::CompactFst;
+use crate::fst_properties::FstProperties;
+use crate::fst_traits::{CoreFst, Fst};
+use crate::semirings::Semiring;
+use crate::{StateId, SymbolTable, TrsVec, EPS_LABEL, NO_LABEL};
+
+impl<W: Semiring, C: Compactor<W>> CompactFst<W, C> {
+    fn check_state(&self, state_id: StateId) -> Result<()> {
+        if (state_id as usize) < self.num_states {
+            Ok(())
+        } else {
+            Err(format_err!("State {:?} doesn't exist", state_id))
+        }
+    }
+}
+
+impl<W: Semiring, C: Compactor<W>> Fst<W> for CompactFst<W, C> {
+    fn input_symbols(&self) -> Option<&Arc<SymbolTable>> {
+        self.isymt.as_ref()
+    }
+
+    fn output_symbols(&self) -> Option<&Arc<SymbolTable>> {
+        self.osymt.as_ref()
+    }
+
+    fn set_input_symbols(&mut self, symt: Arc<SymbolTable>) {
+        self.isymt = Some(symt)
+    }
+
+    fn set_output_symbols(&mut self, symt: Arc<SymbolTable>) {
+        self.osymt = Some(symt);
+    }
+
+    fn take_input_symbols(&mut self) -> Option<Arc<SymbolTable>> {
+        self.isymt.take()
+    }
+
+    fn take_output_symbols(&mut self) -> Option<Arc<SymbolTable>> {
+        self.osymt.take()
+    }
+}
+
+impl<W: Semiring, C: Compactor<W>> CoreFst<W> for CompactFst<W, C> {
+    type TRS = TrsVec<W>;
+
+    fn start(&self) -> Option<StateId> {
+        self.start
+    }
+
+    fn final_weight(&self, state_id: StateId) -> Result<Option<W>> {
+        self.check_state(state_id)?;
+        Ok(unsafe { self.final_weight_unchecked(state_id) })
+    }
+
+    unsafe fn final_weight_unchecked(&self, state_id: StateId) -> Option<W> {
+        // The final weight is stored in the first element.
+        self.elements(state_id).first().and_then(|element| {
+            let tr = C::expand(state_id, element);
+            if tr.ilabel == NO_LABEL {
+                Some(tr.weight)
+            } else {
+                None
+            }
+        })
+    }
+
+    fn num_trs(&self, s: StateId) -> Result<usize> {
+        self.check_state(s)?;
+        Ok(unsafe { self.num_trs_unchecked(s) })
+    }
+
+    unsafe fn num_trs_unchecked(&self, s: StateId) -> usize {
+        let num_elements = self.elements(s).len();
+        if self.final_weight_unchecked(s).is_some() {
+            num_elements - 1
+        } else {
+            num_elements
+        }
+    }
+
+    fn get_trs(&self, state_id: StateId) -> Result<Self::TRS> {
+        self.check_state(state_id)?;
+        Ok(unsafe { self.get_trs_unchecked(state_id) })
+    }
+
+    unsafe fn get_trs_unchecked(&self, state_id: StateId) -> Self::TRS {
+        TrsVec(Arc::new(self.expand_state(state_id).1))
+    }
+
+    fn properties(&self) -> FstProperties {
+        self.properties
+    }
+
+    fn num_input_epsilons(&self, state: StateId) -> Result<usize> {
+        self.check_state(state)?;
+        let (_, trs) = self.expand_state(state);
+        Ok(trs.iter().filter(|tr| tr.ilabel == EPS_LABEL).count())
+    }
+
+    fn num_output_epsilons(&self, state: StateId) -> Result<usize> {
+        self.check_state(state)?;
+        let (_, trs) = self.expand_state(state);
+        Ok(trs.iter().filter(|tr| tr.olabel == EPS_LABEL).count())
+    }
+}
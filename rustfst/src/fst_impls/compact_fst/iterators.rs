@@ -0,0 +1,54 @@
+use std::ops::Range;
+use std::sync::Arc;
+
+use crate::fst_impls::compact_fst::Compactor;
+use crate::fst_impls::CompactFst;
+use crate::fst_traits::FstIterData;
+use crate::fst_traits::{FstIntoIterator, FstIterator, StateIterator};
+use crate::semirings::Semiring;
+use crate::{StateId, Tr, TrsVec};
+
+impl<W: Semiring, C: Compactor<W>> FstIntoIterator<W> for CompactFst<W, C>
+where
+    W: 'static,
+{
+    type TrsIter = std::vec::IntoIter<Tr<W>>;
+    // TODO: Change this to impl once the feature has been stabilized
+    // #![feature(type_alias_impl_trait)]
+    // https://github.com/rust-lang/rust/issues/63063)
+    type FstIter = Box<dyn Iterator<Item = FstIterData<W, Self::TrsIter>>>;
+
+    fn fst_into_iter(self) -> Self::FstIter {
+        Box::new((0..self.num_states as StateId).map(move |state_id| {
+            let (final_weight, trs) = self.expand_state(state_id);
+            FstIterData {
+                state_id,
+                num_trs: trs.len(),
+                trs: trs.into_iter(),
+                final_weight,
+            }
+        }))
+    }
+}
+
+impl<'a, W: Semiring, C: Compactor<W>> StateIterator<'a> for CompactFst<W, C> {
+    type Iter = Range<StateId>;
+    fn states_iter(&'a self) -> Self::Iter {
+        0..(self.num_states as StateId)
+    }
+}
+
+impl<'a, W: Semiring + 'static, C: Compactor<W>> FstIterator<'a, W> for CompactFst<W, C> {
+    type FstIter = Box<dyn Iterator<Item = FstIterData<W, TrsVec<W>>> + 'a>;
+    fn fst_iter(&'a self) -> Self::FstIter {
+        Box::new((0..self.num_states as StateId).map(move |state_id| {
+            let (final_weight, trs) = self.expand_state(state_id);
+            FstIterData {
+                state_id,
+                num_trs: trs.len(),
+                trs: TrsVec(Arc::new(trs)),
+                final_weight,
+            }
+        }))
+    }
+}
@@ -0,0 +1,16 @@
+use std::fmt;
+
+use unsafe_unwrap::UnsafeUnwrap;
+
+use crate::fst_impls::compact_fst::Compactor;
+use crate::fst_impls::CompactFst;
+use crate::fst_traits::{CoreFst, Fst, StateIterator};
+use crate::semirings::SerializableSemiring;
+use crate::Trs;
+
+impl<W: 'static + SerializableSemiring, C: Compactor<W>> fmt::Display for CompactFst<W, C> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_fst!(self, f, true, true);
+        Ok(())
+    }
+}
@@ -0,0 +1,427 @@
+use std::convert::TryFrom;
+use std::io::{Read, Write};
+use std::marker::PhantomData;
+
+use anyhow::Result;
+use nom::bytes::complete::take;
+use nom::error::{ErrorKind, ParseError};
+use nom::multi::count;
+use nom::IResult;
+
+use crate::fst_impls::compact_fst::{
+    Compactor, COMPACT_ARCH_ALIGNMENT, COMPACT_FILE_VERSION, COMPACT_MIN_FILE_VERSION,
+};
+use crate::fst_impls::{CompactFst, VectorFst};
+use crate::fst_properties::FstProperties;
+use crate::fst_traits::{ExpandedFst, Fst, SerializableFst};
+use crate::parsers::bin_fst::bin_fst_reader::BinFstReader;
+use crate::parsers::bin_fst::fst_header::{FstFlags, FstHeader, OpenFstString, FST_MAGIC_NUMBER};
+use crate::parsers::bin_fst::utils_parsing::{
+    check_scalable_weight, parse_start_state, scale_weight, BinParsingOptions,
+};
+use crate::parsers::nom_utils::NomCustomError;
+use crate::parsers::parse_bin_u32;
+use crate::parsers::text_fst::ParsedTextFst;
+use crate::parsers::write_bin_u32;
+use crate::semirings::SerializableSemiring;
+use crate::{Label, StateId, NO_LABEL};
+
+impl<W: SerializableSemiring, C: Compactor<W>> SerializableFst<W> for CompactFst<W, C> {
+    fn fst_type() -> String {
+        format!("compact_{}", C::compactor_type())
+    }
+
+    fn load(data: &[u8]) -> Result<Self> {
+        load_compact_fst(data, &BinParsingOptions::default())
+    }
+
+    fn load_scaled(data: &[u8], scale: f32) -> Result<Self> {
+        check_scalable_weight::<W>()?;
+        load_compact_fst(data, &BinParsingOptions::scaled(scale))
+    }
+
+    fn read_from_reader<R: Read>(reader: R) -> Result<Self> {
+        let mut reader = BinFstReader::new(reader);
+        let hdr = reader.read_header(&[&Self::fst_type()], Self::arc_type())?;
+        let aligned = hdr.flags.contains(FstFlags::IS_ALIGNED);
+        let num_compacts = match C::size() {
+            Some(size) => (hdr.num_states as usize)
+                .checked_mul(size)
+                .ok_or_else(|| format_err!("CompactFst : Too many states {}", hdr.num_states))?,
+            None => {
+                if aligned {
+                    reader.align(COMPACT_ARCH_ALIGNMENT)?;
+                }
+                let mut num_compacts = 0;
+                for _ in 0..=hdr.num_states {
+                    num_compacts = reader.read_u32()?;
+                }
+                num_compacts as usize
+            }
+        };
+        if aligned && num_compacts > 0 {
+            reader.align(COMPACT_ARCH_ALIGNMENT)?;
+        }
+        for _ in 0..num_compacts {
+            reader.read_parsed(C::parse_element)?;
+        }
+        Self::load(reader.data())
+    }
+
+    fn load_relabeled(
+        data: &[u8],
+        ipairs: &[(Label, Label)],
+        opairs: &[(Label, Label)],
+    ) -> Result<Self> {
+        let opts = BinParsingOptions::relabeled(ipairs, opairs)?;
+        load_compact_fst(data, &opts)
+    }
+
+    fn store<O: Write>(&self, mut output: O) -> Result<()> {
+        let mut flags = FstFlags::empty();
+        if self.input_symbols().is_some() {
+            flags |= FstFlags::HAS_ISYMBOLS;
+        }
+        if self.output_symbols().is_some() {
+            flags |= FstFlags::HAS_OSYMBOLS;
+        }
+
+        let hdr = FstHeader {
+            magic_number: FST_MAGIC_NUMBER,
+            fst_type: OpenFstString::new(Self::fst_type()),
+            tr_type: OpenFstString::new(Self::arc_type()),
+            version: COMPACT_FILE_VERSION,
+            flags,
+            properties: self.properties.bits() | Self::static_properties(),
+            start: self.start.map(|v| v as i64).unwrap_or(-1),
+            num_states: self.num_states() as i64,
+            num_trs: self.num_trs as i64,
+            isymt: self.input_symbols().cloned(),
+            osymt: self.output_symbols().cloned(),
+        };
+        hdr.write(&mut output)?;
+
+        if let Some(states) = &self.states {
+            for pos in states {
+                write_bin_u32(&mut output, *pos)?;
+            }
+        }
+        for element in &self.compacts {
+            C::write_element(element, &mut output)?;
+        }
+        Ok(())
+    }
+
+    fn from_parsed_fst_text(parsed_fst_text: ParsedTextFst<W>) -> Result<Self> {
+        let fst = VectorFst::from_parsed_fst_text(parsed_fst_text)?;
+        Self::from_fst(&fst)
+    }
+}
+
+/// Content of a binary CompactFst : the header, the positions of the states if the compactor
+/// doesn't have a fixed size and the elements.
+type ParsedCompactFst<E> = (FstHeader, Option<Vec<u32>>, Vec<E>);
+
+/// Number of bytes to skip after `pos` to align the input.
+fn padding(pos: usize) -> usize {
+    (COMPACT_ARCH_ALIGNMENT - pos % COMPACT_ARCH_ALIGNMENT) % COMPACT_ARCH_ALIGNMENT
+}
+
+fn parse_compact_fst<W: SerializableSemiring, C: Compactor<W>>(
+    i: &[u8],
+) -> IResult<&[u8], ParsedCompactFst<C::Element>, NomCustomError<&[u8]>> {
+    let stream_len = i.len();
+
+    let (mut i, hdr) = FstHeader::parse(
+        i,
+        COMPACT_MIN_FILE_VERSION,
+        CompactFst::<W, C>::fst_type(),
+        CompactFst::<W, C>::arc_type(),
+    )?;
+    let aligned = hdr.flags.contains(FstFlags::IS_ALIGNED);
+
+    // The number of states is checked before being used to size anything.
+    let num_states = usize::try_from(hdr.num_states)
+        .ok()
+        .filter(|n| *n < usize::MAX)
+        .ok_or_else(|| nom::Err::Error(NomCustomError::from_error_kind(i, ErrorKind::Verify)))?;
+
+    let (i, states) = if C::size().is_none() {
+        let pos = stream_len - i.len();
+        if aligned {
+            i = take(padding(pos))(i)?.0;
+        }
+        let (i, states) = count(parse_bin_u32, num_states + 1)(i)?;
+        (i, Some(states))
+    } else {
+        (i, None)
+    };
+    let num_compacts = match (&states, C::size()) {
+        (Some(states), _) => Some(states[num_states] as usize),
+        (None, Some(size)) => num_states.checked_mul(size),
+        (None, None) => unreachable!(),
+    };
+    let num_compacts = num_compacts
+        .ok_or_else(|| nom::Err::Error(NomCustomError::from_error_kind(i, ErrorKind::Verify)))?;
+
+    let mut i = i;
+    let pos = stream_len - i.len();
+    if aligned && num_compacts > 0 {
+        i = take(padding(pos))(i)?.0;
+    }
+    let (i, compacts) = count(C::parse_element, num_compacts)(i)?;
+
+    Ok((i, (hdr, states, compacts)))
+}
+
+fn load_compact_fst<W: SerializableSemiring, C: Compactor<W>>(
+    data: &[u8],
+    opts: &BinParsingOptions,
+) -> Result<CompactFst<W, C>> {
+    let (_, (hdr, states, mut compacts)) = parse_compact_fst::<W, C>(data)
+        .map_err(|_| format_err!("Error while parsing binary CompactFst"))?;
+
+    let num_states = hdr.num_states as usize;
+    if let Some(states) = &states {
+        if states.windows(2).any(|w| w[0] > w[1]) {
+            bail!("CompactFst : The positions of the states are not sorted");
+        }
+    }
+
+    let rewrite = opts.scale != 1.0 || opts.relabels();
+    let mut s = 0;
+    for (idx, element) in compacts.iter_mut().enumerate() {
+        let state = match (&states, C::size()) {
+            (Some(states), _) => {
+                while states[s + 1] as usize <= idx {
+                    s += 1;
+                }
+                s
+            }
+            (None, Some(size)) => idx / size,
+            (None, None) => unreachable!(),
+        } as StateId;
+        let mut tr = C::expand(state, element);
+        // The final weight of a state is stored as a transition without nextstate.
+        if tr.ilabel != NO_LABEL && tr.nextstate as usize >= num_states {
+            bail!(
+                "CompactFst : Invalid nextstate {} of state {}",
+                tr.nextstate,
+                state
+            );
+        }
+        if !rewrite {
+            continue;
+        }
+        tr.weight = scale_weight(tr.weight, opts.scale);
+        if tr.ilabel != NO_LABEL {
+            tr.ilabel = opts.ilabel(tr.ilabel);
+            tr.olabel = opts.olabel(tr.olabel);
+        }
+        let new_element = C::compact(state, &tr);
+        if C::expand(state, &new_element) != tr {
+            bail!(
+                "CompactFst : Relabeled transition {:?} can't be represented by the {} compactor",
+                tr,
+                C::compactor_type()
+            );
+        }
+        *element = new_element;
+    }
+
+    Ok(CompactFst {
+        states,
+        compacts,
+        num_states,
+        num_trs: hdr.num_trs as usize,
+        start: parse_start_state(hdr.start),
+        isymt: hdr.isymt,
+        osymt: hdr.osymt,
+        properties: opts.properties(FstProperties::from_bits_truncate(hdr.properties))
+            | C::properties(),
+        ghost: PhantomData,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::fst_impls::compact_fst::{
+        AcceptorCompactor, StringCompactor, UnweightedAcceptorCompactor, UnweightedCompactor,
+        WeightedStringCompactor,
+    };
+    use crate::fst_traits::{CoreFst, MutableFst};
+    use crate::semirings::{Semiring, TropicalWeight};
+    use crate::utils::{acceptor, transducer};
+    use crate::{Tr, Trs};
+
+    type F = VectorFst<TropicalWeight>;
+
+    fn weighted_acceptor() -> Result<F> {
+        let mut fst = F::new();
+        fst.add_states(4);
+        fst.set_start(0)?;
+        fst.set_final(2, 0.5)?;
+        fst.set_final(3, TropicalWeight::one())?;
+        fst.add_tr(0, Tr::new(1, 1, 1.0, 1))?;
+        fst.add_tr(0, Tr::new(2, 2, 2.0, 2))?;
+        fst.add_tr(1, Tr::new(3, 3, 1.5, 3))?;
+        fst.add_tr(2, Tr::new(0, 0, TropicalWeight::one(), 3))?;
+        fst.add_tr(3, Tr::new(4, 4, 3.0, 0))?;
+        Ok(fst)
+    }
+
+    fn check_round_trip<C: Compactor<TropicalWeight>>(fst: &F) -> Result<()> {
+        let compact_fst = CompactFst::<TropicalWeight, C>::from_fst(fst)?;
+        let expanded: F = crate::algorithms::fst_convert_from_ref(&compact_fst);
+        assert_eq!(&expanded, fst);
+        let num_trs: usize = (0..fst.num_states() as StateId)
+            .map(|s| fst.num_trs(s).unwrap())
+            .sum();
+        assert_eq!(compact_fst.num_trs, num_trs);
+
+        let mut data = vec![];
+        compact_fst.store(&mut data)?;
+        let loaded = CompactFst::<TropicalWeight, C>::load(&data)?;
+        assert_eq!(loaded, compact_fst);
+        assert!(loaded.properties().contains(compact_fst.properties()));
+        let expanded: F = crate::algorithms::fst_convert_from_ref(&loaded);
+        assert_eq!(&expanded, fst);
+
+        // Two FSTs one after the other in a stream.
+        compact_fst.store(&mut data)?;
+        let mut reader = &data[..];
+        for _ in 0..2 {
+            let read = CompactFst::<TropicalWeight, C>::read_from_reader(&mut reader)?;
+            assert_eq!(read, compact_fst);
+        }
+        assert!(reader.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_compact_fst_acceptors() -> Result<()> {
+        let fst = weighted_acceptor()?;
+        check_round_trip::<AcceptorCompactor>(&fst)?;
+        assert!(CompactFst::<_, UnweightedAcceptorCompactor>::from_fst(&fst).is_err());
+        assert!(CompactFst::<_, UnweightedCompactor>::from_fst(&fst).is_err());
+        assert!(CompactFst::<_, StringCompactor>::from_fst(&fst).is_err());
+
+        let mut fst = fst;
+        crate::algorithms::tr_map(&mut fst, &crate::algorithms::tr_mappers::RmWeightMapper {})?;
+        check_round_trip::<UnweightedAcceptorCompactor>(&fst)?;
+        check_round_trip::<UnweightedCompactor>(&fst)?;
+
+        let transducer: F = transducer(&[1, 2], &[3, 4], TropicalWeight::one());
+        assert!(CompactFst::<_, UnweightedAcceptorCompactor>::from_fst(&transducer).is_err());
+        let compact_fst = CompactFst::<_, UnweightedCompactor>::from_fst(&transducer)?;
+        assert_eq!(
+            compact_fst.get_trs(1)?.trs(),
+            &[Tr::new(2, 4, TropicalWeight::one(), 2)]
+        );
+        assert_eq!(compact_fst.final_weight(2)?, Some(TropicalWeight::one()));
+        assert_eq!(compact_fst.final_weight(1)?, None);
+        assert_eq!(compact_fst.num_trs(2)?, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_compact_fst_strings() -> Result<()> {
+        let fst: F = acceptor(&[1, 2, 3], TropicalWeight::new(2.0));
+        assert!(CompactFst::<_, StringCompactor>::from_fst(&fst).is_err());
+        let compact_fst = CompactFst::<_, WeightedStringCompactor>::from_fst(&fst)?;
+        assert_eq!(compact_fst.compacts.len(), 4);
+        assert!(compact_fst.states.is_none());
+        assert!(compact_fst.properties().contains(FstProperties::STRING));
+
+        let mut data = vec![];
+        compact_fst.store(&mut data)?;
+        let loaded = CompactFst::<TropicalWeight, WeightedStringCompactor>::load(&data)?;
+        assert_eq!(loaded, compact_fst);
+        let expanded: F = crate::algorithms::fst_convert_from_ref(&loaded);
+        assert_eq!(expanded, fst);
+
+        let fst: F = acceptor(&[1, 2, 3], TropicalWeight::one());
+        let compact_fst = CompactFst::<_, StringCompactor>::from_fst(&fst)?;
+        let mut data = vec![];
+        compact_fst.store(&mut data)?;
+        let loaded = CompactFst::<TropicalWeight, StringCompactor>::load(&data)?;
+        let expanded: F = crate::algorithms::fst_convert_from_ref(&loaded);
+        assert_eq!(expanded, fst);
+
+        // The type of the compactor is checked when loading.
+        assert!(CompactFst::<TropicalWeight, AcceptorCompactor>::load(&data).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_compact_fst_load_relabeled() -> Result<()> {
+        let fst = weighted_acceptor()?;
+        let compact_fst = CompactFst::<_, AcceptorCompactor>::from_fst(&fst)?;
+        let mut data = vec![];
+        compact_fst.store(&mut data)?;
+
+        let loaded = CompactFst::<TropicalWeight, AcceptorCompactor>::load_relabeled(
+            &data,
+            &[(1, 5)],
+            &[(1, 5)],
+        )?;
+        assert_eq!(loaded.get_trs(0)?.trs()[0], Tr::new(5, 5, 1.0, 1));
+        assert!(
+            CompactFst::<TropicalWeight, AcceptorCompactor>::load_relabeled(&data, &[(1, 5)], &[])
+                .is_err()
+        );
+
+        let loaded = CompactFst::<TropicalWeight, AcceptorCompactor>::load_scaled(&data, 2.0)?;
+        assert_eq!(loaded.get_trs(0)?.trs()[1], Tr::new(2, 2, 4.0, 2));
+        assert_eq!(loaded.final_weight(2)?, Some(TropicalWeight::new(1.0)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_compact_fst_load_invalid_nextstate() -> Result<()> {
+        let fst = weighted_acceptor()?;
+        let mut compact_fst = CompactFst::<_, AcceptorCompactor>::from_fst(&fst)?;
+        let last = compact_fst.compacts.len() - 1;
+        compact_fst.compacts[last].2 = 4;
+        let mut data = vec![];
+        compact_fst.store(&mut data)?;
+        assert!(CompactFst::<TropicalWeight, AcceptorCompactor>::load(&data).is_err());
+
+        // The transition of the last state of a string can't go past it.
+        let fst: F = acceptor(&[1, 2], TropicalWeight::one());
+        let mut compact_fst = CompactFst::<_, StringCompactor>::from_fst(&fst)?;
+        compact_fst.compacts[2] = 3;
+        let mut data = vec![];
+        compact_fst.store(&mut data)?;
+        assert!(CompactFst::<TropicalWeight, StringCompactor>::load(&data).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_compact_fst_load_invalid_num_states() -> Result<()> {
+        fn check<C: Compactor<TropicalWeight>>(fst: &F) -> Result<()> {
+            let mut data = vec![];
+            CompactFst::<_, C>::from_fst(fst)?.store(&mut data)?;
+            let (rest, _) = FstHeader::parse(
+                &data,
+                COMPACT_MIN_FILE_VERSION,
+                CompactFst::<TropicalWeight, C>::fst_type(),
+                CompactFst::<TropicalWeight, C>::arc_type(),
+            )
+            .unwrap();
+            // The number of states is followed by the number of trs at the end of the header.
+            let num_states_pos = data.len() - rest.len() - 16;
+            for num_states in &[-1i64, i64::MIN, i64::MAX] {
+                data[num_states_pos..num_states_pos + 8].copy_from_slice(&num_states.to_le_bytes());
+                assert!(CompactFst::<TropicalWeight, C>::load(&data).is_err());
+            }
+            Ok(())
+        }
+
+        check::<AcceptorCompactor>(&weighted_acceptor()?)?;
+        check::<WeightedStringCompactor>(&acceptor(&[1, 2], TropicalWeight::one()))?;
+        Ok(())
+    }
+}
@@ -5,6 +5,39 @@ use crate::fst_properties::FstProperties;
 use crate::{Semiring, StateId, SymbolTable, Tr};
 
 /// Immutable FST whose states and trs each implemented by single arrays,
+///
+/// `ConstFst` implements [`ExpandedFst`](../fst_traits/trait.ExpandedFst.html), so the
+/// algorithms that only read their input (e.g. `shortest_distance`, `isomorphic` or the lazy
+/// FSTs) can be called on it directly: no conversion is needed and the trs of a state are
+/// borrowed from the shared array without being copied. Only the algorithms modifying the FST
+/// in place require a [`MutableFst`](../fst_traits/trait.MutableFst.html) such as a
+/// [`VectorFst`](struct.VectorFst.html), obtained with
+/// [`fst_convert`](../algorithms/fn.fst_convert.html) which copies every tr.
+///
+/// # Example
+///
+/// ```
+/// # use anyhow::Result;
+/// # use rustfst::prelude::*;
+/// # use rustfst::algorithms::shortest_distance;
+/// # fn main() -> Result<()> {
+/// let mut fst = VectorFst::<TropicalWeight>::new();
+/// fst.add_states(3);
+/// fst.set_start(0)?;
+/// fst.set_final(2, TropicalWeight::one())?;
+/// fst.add_tr(0, Tr::new(1, 1, 1.0, 1))?;
+/// fst.add_tr(1, Tr::new(2, 2, 2.0, 2))?;
+/// let const_fst: ConstFst<_> = fst.into();
+///
+/// let dists = shortest_distance(&const_fst, false)?;
+/// assert_eq!(dists[2], TropicalWeight::new(3.0));
+///
+/// // Modifying the FST requires a copy.
+/// let mut vector_fst: VectorFst<_> = fst_convert_from_ref(&const_fst);
+/// connect(&mut vector_fst)?;
+/// # Ok(())
+/// # }
+/// ```
 #[derive(Debug, Clone)]
 pub struct ConstFst<W> {
     pub(crate) states: Vec<ConstState<W>>,
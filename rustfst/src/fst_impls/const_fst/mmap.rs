@@ -0,0 +1,549 @@
+use std::fmt;
+use std::fs::File;
+use std::marker::PhantomData;
+use std::mem::{align_of, size_of};
+use std::ops::Range;
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::{format_err, Context, Result};
+use memmap2::Mmap;
+
+use crate::algorithms::fst_convert_from_ref;
+use crate::fst_impls::const_fst::CONST_ALIGNED_FILE_VERSION;
+use crate::fst_impls::{ConstFst, VectorFst};
+use crate::fst_properties::FstProperties;
+use crate::fst_traits::{
+    CoreFst, ExpandedFst, Fst, FstIntoIterator, FstIterData, FstIterator, MutableFst,
+    SerializableFst, StateIterator,
+};
+use crate::parsers::bin_fst::fst_header::FstHeader;
+use crate::parsers::bin_fst::utils_parsing::parse_start_state;
+use crate::semirings::{LogWeight, SerializableSemiring, TropicalWeight};
+use crate::{StateId, SymbolTable, Tr, Trs, TrsVec, EPS_LABEL};
+
+/// Weights represented in memory as a single `f32`, like in the ConstFst binary format.
+/// The trs of a memory-mapped ConstFst with such weights are used in place, without being
+/// copied.
+///
+/// # Safety
+///
+/// The weight must have the same size, alignment and representation as an `f32`.
+pub unsafe trait MmapWeight: SerializableSemiring {}
+
+unsafe impl MmapWeight for TropicalWeight {}
+unsafe impl MmapWeight for LogWeight {}
+
+/// State of a ConstFst as stored in the binary format, for 32 bits weights.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+struct RawConstState {
+    final_weight: f32,
+    pos: i32,
+    ntrs: i32,
+    niepsilons: i32,
+    noepsilons: i32,
+}
+
+/// Immutable FST backed by a memory-mapped [`ConstFst`](struct.ConstFst.html) file, created
+/// with [`ConstFst::open_mmap`](struct.ConstFst.html#method.open_mmap).
+///
+/// The states and the trs are read directly from the mapped file, which is shared between
+/// all the processes mapping it. Cloning a `MmapConstFst` doesn't copy the mapped file.
+pub struct MmapConstFst<W> {
+    mmap: Arc<Mmap>,
+    states_offset: usize,
+    num_states: usize,
+    trs_offset: usize,
+    num_trs: usize,
+    start: Option<StateId>,
+    isymt: Option<Arc<SymbolTable>>,
+    osymt: Option<Arc<SymbolTable>>,
+    properties: FstProperties,
+    weight: PhantomData<W>,
+}
+
+/// Returns the offset of the slice of `n` elements of type `T` starting at the first
+/// position after `pos` aligned on `alignment` bytes, checking that it lies in the data and
+/// that the elements are properly aligned in memory.
+fn check_slice<T>(data: &[u8], pos: usize, n: usize, alignment: usize) -> Result<usize> {
+    let offset = if n > 0 {
+        pos + (alignment - pos % alignment) % alignment
+    } else {
+        pos
+    };
+    let end = n
+        .checked_mul(size_of::<T>())
+        .and_then(|size| size.checked_add(offset))
+        .ok_or_else(|| format_err!("ConstFst file is too large"))?;
+    if end > data.len() {
+        bail!(
+            "ConstFst file is truncated : expected at least {} bytes, found {}",
+            end,
+            data.len()
+        );
+    }
+    if n > 0 && data[offset..].as_ptr().align_offset(align_of::<T>()) != 0 {
+        bail!("ConstFst file is not aligned, it can be written with `ConstFst::write_aligned`");
+    }
+    Ok(offset)
+}
+
+impl<W: MmapWeight> ConstFst<W> {
+    /// Opens a ConstFst binary file by memory-mapping it instead of reading it into memory.
+    /// This allows using FSTs larger than the memory and sharing them between processes.
+    ///
+    /// The file must have been written in the aligned format, e.g. with
+    /// [`write_aligned`](#method.write_aligned) or by OpenFST with `--fst_align`, by a little
+    /// endian machine. An error is returned if the file doesn't meet those requirements or
+    /// is invalid.
+    ///
+    /// # Safety
+    ///
+    /// The file must not be modified, by this process or another one, as long as the
+    /// returned FST or any of its trs are alive.
+    pub unsafe fn open_mmap<P: AsRef<Path>>(path: P) -> Result<MmapConstFst<W>> {
+        if cfg!(target_endian = "big") {
+            bail!("ConstFst files can only be memory-mapped on little endian machines");
+        }
+        if size_of::<Tr<W>>() != 4 * size_of::<u32>() {
+            bail!("Unexpected memory layout of the trs");
+        }
+
+        let file = File::open(path.as_ref())
+            .with_context(|| format!("Can't open ConstFst binary file : {:?}", path.as_ref()))?;
+        let mmap = Mmap::map(&file)
+            .with_context(|| format!("Can't map ConstFst binary file : {:?}", path.as_ref()))?;
+        let data: &[u8] = &mmap;
+
+        let (rest, hdr) = FstHeader::parse(
+            data,
+            CONST_ALIGNED_FILE_VERSION,
+            ConstFst::<W>::fst_type(),
+            ConstFst::<W>::arc_type(),
+        )
+        .map_err(|_| format_err!("Error while parsing the header of the ConstFst file"))?;
+        if hdr.num_states < 0 || hdr.num_trs < 0 {
+            bail!("Invalid number of states or trs in the ConstFst file");
+        }
+        let num_states = hdr.num_states as usize;
+        let num_trs = hdr.num_trs as usize;
+        let alignment = if hdr.version == CONST_ALIGNED_FILE_VERSION {
+            super::CONST_ARCH_ALIGNMENT
+        } else {
+            1
+        };
+
+        let states_offset =
+            check_slice::<RawConstState>(data, data.len() - rest.len(), num_states, alignment)?;
+        let trs_offset = check_slice::<Tr<W>>(
+            data,
+            states_offset + num_states * size_of::<RawConstState>(),
+            num_trs,
+            alignment,
+        )?;
+
+        let fst = MmapConstFst {
+            start: parse_start_state(hdr.start),
+            isymt: hdr.isymt,
+            osymt: hdr.osymt,
+            properties: FstProperties::from_bits_truncate(hdr.properties),
+            mmap: Arc::new(mmap),
+            states_offset,
+            num_states,
+            trs_offset,
+            num_trs,
+            weight: PhantomData,
+        };
+        // Checks that the trs of the states lie in the file to avoid any out of bounds access
+        // and that they are consistent with the states.
+        for (s, state) in fst.raw_states().iter().enumerate() {
+            let (pos, ntrs) = (state.pos as usize, state.ntrs as usize);
+            if state.pos < 0 || state.ntrs < 0 || pos + ntrs > num_trs {
+                bail!("Invalid state {} in the ConstFst file", s);
+            }
+            let trs = &fst.raw_trs()[pos..pos + ntrs];
+            if let Some(tr) = trs.iter().find(|tr| tr.nextstate as usize >= num_states) {
+                bail!(
+                    "Invalid nextstate {} of state {} in the ConstFst file",
+                    tr.nextstate,
+                    s
+                );
+            }
+            let niepsilons = trs.iter().filter(|tr| tr.ilabel == EPS_LABEL).count();
+            let noepsilons = trs.iter().filter(|tr| tr.olabel == EPS_LABEL).count();
+            if state.niepsilons as usize != niepsilons || state.noepsilons as usize != noepsilons {
+                bail!(
+                    "Invalid number of epsilons of state {} in the ConstFst file",
+                    s
+                );
+            }
+        }
+        if let Some(start) = fst.start {
+            if start as usize >= num_states {
+                bail!("Invalid start state in the ConstFst file");
+            }
+        }
+        Ok(fst)
+    }
+}
+
+impl<W: MmapWeight> MmapConstFst<W> {
+    fn raw_states(&self) -> &[RawConstState] {
+        // The bounds and the alignment have been checked when opening the file.
+        unsafe {
+            std::slice::from_raw_parts(
+                self.mmap.as_ptr().add(self.states_offset) as *const RawConstState,
+                self.num_states,
+            )
+        }
+    }
+
+    fn raw_trs(&self) -> &[Tr<W>] {
+        // The bounds and the alignment have been checked when opening the file.
+        unsafe {
+            std::slice::from_raw_parts(
+                self.mmap.as_ptr().add(self.trs_offset) as *const Tr<W>,
+                self.num_trs,
+            )
+        }
+    }
+
+    fn raw_state(&self, state: StateId) -> Result<&RawConstState> {
+        self.raw_states()
+            .get(state as usize)
+            .ok_or_else(|| format_err!("State {:?} doesn't exist", state))
+    }
+
+    fn trs_of(&self, state: &RawConstState) -> TrsMmap<W> {
+        TrsMmap {
+            mmap: Arc::clone(&self.mmap),
+            offset: self.trs_offset + state.pos as usize * size_of::<Tr<W>>(),
+            n: state.ntrs as usize,
+            weight: PhantomData,
+        }
+    }
+
+    fn final_weight_of(state: &RawConstState) -> Option<W> {
+        // Can't fail as the weight is represented as an `f32`.
+        let weight = W::from_f32(state.final_weight).unwrap();
+        if weight == W::zero() {
+            None
+        } else {
+            Some(weight)
+        }
+    }
+
+    /// Copies the FST into memory.
+    pub fn to_const_fst(&self) -> Result<ConstFst<W>> {
+        let mut fst: VectorFst<W> = fst_convert_from_ref(self);
+        fst.set_properties(self.properties);
+        Ok(fst.into())
+    }
+}
+
+impl<W> Clone for MmapConstFst<W> {
+    fn clone(&self) -> Self {
+        Self {
+            mmap: Arc::clone(&self.mmap),
+            states_offset: self.states_offset,
+            num_states: self.num_states,
+            trs_offset: self.trs_offset,
+            num_trs: self.num_trs,
+            start: self.start,
+            isymt: self.isymt.clone(),
+            osymt: self.osymt.clone(),
+            properties: self.properties,
+            weight: PhantomData,
+        }
+    }
+}
+
+impl<W> fmt::Debug for MmapConstFst<W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "MmapConstFst {{ num_states: {}, num_trs: {}, start: {:?} }}",
+            self.num_states, self.num_trs, self.start
+        )
+    }
+}
+
+impl<W: MmapWeight> PartialEq for MmapConstFst<W> {
+    fn eq(&self, other: &Self) -> bool {
+        // Indended: Doesn't check symt and properties
+        self.num_states == other.num_states
+            && self.start == other.start
+            && self.states_iter().all(|s| unsafe {
+                self.final_weight_unchecked(s) == other.final_weight_unchecked(s)
+                    && self.get_trs_unchecked(s).trs() == other.get_trs_unchecked(s).trs()
+            })
+    }
+}
+
+impl<W: MmapWeight> CoreFst<W> for MmapConstFst<W> {
+    type TRS = TrsMmap<W>;
+
+    fn start(&self) -> Option<StateId> {
+        self.start
+    }
+
+    fn final_weight(&self, state_id: StateId) -> Result<Option<W>> {
+        Ok(Self::final_weight_of(self.raw_state(state_id)?))
+    }
+
+    unsafe fn final_weight_unchecked(&self, state_id: StateId) -> Option<W> {
+        Self::final_weight_of(self.raw_states().get_unchecked(state_id as usize))
+    }
+
+    fn num_trs(&self, s: StateId) -> Result<usize> {
+        Ok(self.raw_state(s)?.ntrs as usize)
+    }
+
+    unsafe fn num_trs_unchecked(&self, s: StateId) -> usize {
+        self.raw_states().get_unchecked(s as usize).ntrs as usize
+    }
+
+    fn get_trs(&self, state_id: StateId) -> Result<Self::TRS> {
+        Ok(self.trs_of(self.raw_state(state_id)?))
+    }
+
+    unsafe fn get_trs_unchecked(&self, state_id: StateId) -> Self::TRS {
+        self.trs_of(self.raw_states().get_unchecked(state_id as usize))
+    }
+
+    fn properties(&self) -> FstProperties {
+        self.properties
+    }
+
+    fn num_input_epsilons(&self, state: StateId) -> Result<usize> {
+        Ok(self.raw_state(state)?.niepsilons as usize)
+    }
+
+    fn num_output_epsilons(&self, state: StateId) -> Result<usize> {
+        Ok(self.raw_state(state)?.noepsilons as usize)
+    }
+}
+
+impl<W: MmapWeight> Fst<W> for MmapConstFst<W> {
+    fn input_symbols(&self) -> Option<&Arc<SymbolTable>> {
+        self.isymt.as_ref()
+    }
+
+    fn output_symbols(&self) -> Option<&Arc<SymbolTable>> {
+        self.osymt.as_ref()
+    }
+
+    fn set_input_symbols(&mut self, symt: Arc<SymbolTable>) {
+        self.isymt = Some(symt)
+    }
+
+    fn set_output_symbols(&mut self, symt: Arc<SymbolTable>) {
+        self.osymt = Some(symt);
+    }
+
+    fn take_input_symbols(&mut self) -> Option<Arc<SymbolTable>> {
+        self.isymt.take()
+    }
+
+    fn take_output_symbols(&mut self) -> Option<Arc<SymbolTable>> {
+        self.osymt.take()
+    }
+}
+
+impl<W: MmapWeight> ExpandedFst<W> for MmapConstFst<W> {
+    fn num_states(&self) -> usize {
+        self.num_states
+    }
+}
+
+impl<'a, W> StateIterator<'a> for MmapConstFst<W> {
+    type Iter = Range<StateId>;
+    fn states_iter(&'a self) -> Self::Iter {
+        0..(self.num_states as StateId)
+    }
+}
+
+impl<'a, W: MmapWeight> FstIterator<'a, W> for MmapConstFst<W> {
+    type FstIter = Box<dyn Iterator<Item = FstIterData<W, TrsMmap<W>>> + 'a>;
+    fn fst_iter(&'a self) -> Self::FstIter {
+        Box::new(
+            self.raw_states()
+                .iter()
+                .enumerate()
+                .map(move |(state_id, state)| FstIterData {
+                    state_id: state_id as StateId,
+                    trs: self.trs_of(state),
+                    final_weight: Self::final_weight_of(state),
+                    num_trs: state.ntrs as usize,
+                }),
+        )
+    }
+}
+
+impl<W: MmapWeight> FstIntoIterator<W> for MmapConstFst<W> {
+    type TrsIter = std::vec::IntoIter<Tr<W>>;
+    type FstIter = Box<dyn Iterator<Item = FstIterData<W, Self::TrsIter>>>;
+
+    fn fst_into_iter(self) -> Self::FstIter {
+        Box::new((0..self.num_states).map(move |state_id| {
+            let state = &self.raw_states()[state_id];
+            FstIterData {
+                state_id: state_id as StateId,
+                trs: self.trs_of(state).to_vec().into_iter(),
+                final_weight: Self::final_weight_of(state),
+                num_trs: state.ntrs as usize,
+            }
+        }))
+    }
+}
+
+/// Trs of a state of a [`MmapConstFst`](struct.MmapConstFst.html), read in place from the
+/// mapped file. Cloning them doesn't copy the trs.
+pub struct TrsMmap<W> {
+    mmap: Arc<Mmap>,
+    offset: usize,
+    n: usize,
+    weight: PhantomData<W>,
+}
+
+impl<W: MmapWeight> Trs<W> for TrsMmap<W> {
+    fn trs(&self) -> &[Tr<W>] {
+        // The bounds, the alignment and the trs have been checked when opening the file.
+        unsafe {
+            std::slice::from_raw_parts(self.mmap.as_ptr().add(self.offset) as *const Tr<W>, self.n)
+        }
+    }
+
+    fn to_trs_vec(&self) -> TrsVec<W> {
+        TrsVec(Arc::new(self.trs().to_vec()))
+    }
+
+    fn shallow_clone(&self) -> Self {
+        Self {
+            mmap: Arc::clone(&self.mmap),
+            offset: self.offset,
+            n: self.n,
+            weight: PhantomData,
+        }
+    }
+}
+
+impl<W: MmapWeight> Clone for TrsMmap<W> {
+    fn clone(&self) -> Self {
+        self.shallow_clone()
+    }
+}
+
+impl<W: MmapWeight> std::ops::Deref for TrsMmap<W> {
+    type Target = [Tr<W>];
+    fn deref(&self) -> &Self::Target {
+        self.trs()
+    }
+}
+
+impl<W: MmapWeight> fmt::Debug for TrsMmap<W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.trs()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::semirings::Semiring;
+
+    fn fst() -> Result<ConstFst<TropicalWeight>> {
+        let mut fst = VectorFst::<TropicalWeight>::new();
+        fst.add_states(3);
+        fst.set_start(0)?;
+        fst.set_final(2, 0.5)?;
+        fst.add_tr(0, Tr::new(1, 2, 1.0, 1))?;
+        fst.add_tr(0, Tr::new(EPS_LABEL, 3, 2.0, 2))?;
+        fst.add_tr(1, Tr::new(3, EPS_LABEL, TropicalWeight::one(), 2))?;
+        fst.add_tr(2, Tr::new(4, 4, 3.0, 0))?;
+        Ok(fst.into())
+    }
+
+    #[test]
+    fn test_const_fst_open_mmap() -> Result<()> {
+        let fst = fst()?;
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("fst.bin");
+        fst.write_aligned(&path)?;
+
+        let mmap_fst = unsafe { ConstFst::<TropicalWeight>::open_mmap(&path)? };
+        assert_eq!(mmap_fst.num_states(), 3);
+        assert_eq!(mmap_fst.start(), Some(0));
+        assert_eq!(mmap_fst.final_weight(2)?, Some(TropicalWeight::new(0.5)));
+        assert_eq!(mmap_fst.final_weight(0)?, None);
+        assert_eq!(mmap_fst.get_trs(0)?.trs(), fst.get_trs(0)?.trs());
+        assert_eq!(mmap_fst.num_input_epsilons(0)?, 1);
+        assert_eq!(mmap_fst.num_output_epsilons(1)?, 1);
+        assert!(mmap_fst.get_trs(3).is_err());
+        assert!(mmap_fst.properties().contains(fst.properties()));
+        assert_eq!(mmap_fst.to_const_fst()?, fst);
+        assert_eq!(mmap_fst.clone(), mmap_fst);
+
+        // The aligned format can also be read in memory.
+        assert_eq!(ConstFst::<TropicalWeight>::read(&path)?, fst);
+        Ok(())
+    }
+
+    #[test]
+    fn test_const_fst_open_mmap_errors() -> Result<()> {
+        let fst = fst()?;
+        let dir = tempfile::tempdir()?;
+
+        let mut data = vec![];
+        fst.store_aligned(&mut data)?;
+        let path = dir.path().join("truncated.bin");
+        std::fs::write(&path, &data[..data.len() - 1])?;
+        assert!(unsafe { ConstFst::<TropicalWeight>::open_mmap(&path) }.is_err());
+
+        let path = dir.path().join("aligned.bin");
+        std::fs::write(&path, &data)?;
+        assert!(unsafe { ConstFst::<LogWeight>::open_mmap(&path) }.is_err());
+
+        // The unaligned format is only accepted if the data happens to be aligned.
+        let mut data = vec![];
+        fst.store(&mut data)?;
+        let path = dir.path().join("unaligned.bin");
+        std::fs::write(&path, &data)?;
+        if let Ok(mmap_fst) = unsafe { ConstFst::<TropicalWeight>::open_mmap(&path) } {
+            assert_eq!(mmap_fst.to_const_fst()?, fst);
+        }
+
+        let mut data = vec![];
+        fst.store_delta_encoded(&mut data)?;
+        let path = dir.path().join("delta.bin");
+        std::fs::write(&path, &data)?;
+        assert!(unsafe { ConstFst::<TropicalWeight>::open_mmap(&path) }.is_err());
+
+        // Overwrites the serialized tr `tr` of the file.
+        let corrupt = |tr: [u32; 4], new_tr: [u32; 4]| -> Vec<u8> {
+            let mut data = vec![];
+            fst.store_aligned(&mut data).unwrap();
+            let bytes = |tr: [u32; 4]| tr.iter().flat_map(|v| v.to_le_bytes()).collect::<Vec<_>>();
+            let pattern = bytes(tr);
+            let pos = data.windows(16).position(|w| w == &pattern[..]).unwrap();
+            data[pos..pos + 16].copy_from_slice(&bytes(new_tr));
+            data
+        };
+        let three = 3.0f32.to_bits();
+        let one = 1.0f32.to_bits();
+
+        let path = dir.path().join("nextstate.bin");
+        std::fs::write(&path, corrupt([4, 4, three, 0], [4, 4, three, 3]))?;
+        assert!(unsafe { ConstFst::<TropicalWeight>::open_mmap(&path) }.is_err());
+
+        let path = dir.path().join("epsilons.bin");
+        std::fs::write(&path, corrupt([1, 2, one, 1], [EPS_LABEL, 2, one, 1]))?;
+        assert!(unsafe { ConstFst::<TropicalWeight>::open_mmap(&path) }.is_err());
+
+        let path = dir.path().join("missing.bin");
+        assert!(unsafe { ConstFst::<TropicalWeight>::open_mmap(&path) }.is_err());
+        Ok(())
+    }
+}
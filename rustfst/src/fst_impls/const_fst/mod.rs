@@ -1,4 +1,5 @@
 pub use self::data_structure::ConstFst;
+pub use self::mmap::{MmapConstFst, MmapWeight, TrsMmap};
 
 mod converters;
 mod data_structure;
@@ -6,9 +7,13 @@ mod expanded_fst;
 mod fst;
 mod iterators;
 mod misc;
+mod mmap;
 mod serializable_fst;
 
 pub(super) static CONST_MIN_FILE_VERSION: i32 = 1;
 pub(super) static CONST_ALIGNED_FILE_VERSION: i32 = 1;
 pub(super) static CONST_FILE_VERSION: i32 = 2;
 pub(super) static CONST_ARCH_ALIGNMENT: usize = 16;
+/// FST type of the delta encoded binary format, distinct from the one of OpenFST so that
+/// OpenFST refuses to load it.
+pub(super) static CONST_DELTA_ENCODED_FST_TYPE: &str = "const_delta";
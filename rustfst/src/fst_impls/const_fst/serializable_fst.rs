@@ -1,29 +1,36 @@
-use std::io::Write;
+use std::convert::TryFrom;
+use std::fs::File;
+use std::io::{BufWriter, Read, Write};
+use std::path::Path;
 use std::sync::Arc;
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use itertools::Itertools;
 use nom::bytes::complete::take;
+use nom::error::{ErrorKind, ParseError};
 use nom::multi::count;
 use nom::IResult;
 
 use crate::fst_impls::const_fst::data_structure::ConstState;
 use crate::fst_impls::const_fst::{
-    CONST_ALIGNED_FILE_VERSION, CONST_ARCH_ALIGNMENT, CONST_FILE_VERSION, CONST_MIN_FILE_VERSION,
+    CONST_ALIGNED_FILE_VERSION, CONST_ARCH_ALIGNMENT, CONST_DELTA_ENCODED_FST_TYPE,
+    CONST_FILE_VERSION, CONST_MIN_FILE_VERSION,
 };
 use crate::fst_impls::ConstFst;
 use crate::fst_properties::FstProperties;
 use crate::fst_traits::{ExpandedFst, Fst, SerializableFst};
+use crate::parsers::bin_fst::bin_fst_reader::BinFstReader;
 use crate::parsers::bin_fst::fst_header::{FstFlags, FstHeader, OpenFstString, FST_MAGIC_NUMBER};
 use crate::parsers::bin_fst::utils_parsing::{
-    parse_bin_fst_tr, parse_final_weight, parse_start_state,
+    check_scalable_weight, parse_bin_fst_tr_with_options, parse_final_weight, parse_start_state,
+    scale_weight, BinParsingOptions,
 };
 use crate::parsers::nom_utils::NomCustomError;
-use crate::parsers::parse_bin_i32;
 use crate::parsers::text_fst::ParsedTextFst;
-use crate::parsers::write_bin_i32;
+use crate::parsers::{parse_bin_i32, parse_bin_varint_u64};
+use crate::parsers::{write_bin_i32, write_bin_varint_u64};
 use crate::semirings::SerializableSemiring;
-use crate::{Tr, EPS_LABEL};
+use crate::{Label, StateId, Tr, EPS_LABEL};
 
 impl<W: SerializableSemiring> SerializableFst<W> for ConstFst<W> {
     fn fst_type() -> String {
@@ -31,56 +38,65 @@ impl<W: SerializableSemiring> SerializableFst<W> for ConstFst<W> {
     }
 
     fn load(data: &[u8]) -> Result<Self> {
-        let (_, parsed_fst) = parse_const_fst(data)
+        let (_, parsed_fst) = parse_const_fst(data, &BinParsingOptions::default())
             .map_err(|_| format_err!("Error while parsing binary ConstFst"))?;
 
         Ok(parsed_fst)
     }
 
-    fn store<O: Write>(&self, mut output: O) -> Result<()> {
-        let mut flags = FstFlags::empty();
-        if self.input_symbols().is_some() {
-            flags |= FstFlags::HAS_ISYMBOLS;
-        }
-        if self.output_symbols().is_some() {
-            flags |= FstFlags::HAS_OSYMBOLS;
-        }
-
-        let hdr = FstHeader {
-            magic_number: FST_MAGIC_NUMBER,
-            fst_type: OpenFstString::new(Self::fst_type()),
-            tr_type: OpenFstString::new(Tr::<W>::tr_type()),
-            version: CONST_FILE_VERSION,
-            // TODO: Set flags if the content is aligned
-            flags,
-            properties: self.properties.bits() | ConstFst::<W>::static_properties(),
-            start: self.start.map(|v| v as i64).unwrap_or(-1),
-            num_states: self.num_states() as i64,
-            num_trs: self.trs.len() as i64,
-            isymt: self.input_symbols().cloned(),
-            osymt: self.output_symbols().cloned(),
-        };
-        hdr.write(&mut output)?;
+    fn load_scaled(data: &[u8], scale: f32) -> Result<Self> {
+        check_scalable_weight::<W>()?;
+        let (_, parsed_fst) = parse_const_fst(data, &BinParsingOptions::scaled(scale))
+            .map_err(|_| format_err!("Error while parsing binary ConstFst"))?;
 
-        let zero = W::zero();
-        for const_state in &self.states {
-            let f_weight = const_state.final_weight.as_ref().unwrap_or(&zero);
-            f_weight.write_binary(&mut output)?;
+        Ok(parsed_fst)
+    }
 
-            write_bin_i32(&mut output, const_state.pos as i32)?;
-            write_bin_i32(&mut output, const_state.ntrs as i32)?;
-            write_bin_i32(&mut output, const_state.niepsilons as i32)?;
-            write_bin_i32(&mut output, const_state.noepsilons as i32)?;
+    fn read_from_reader<R: Read>(reader: R) -> Result<Self> {
+        let mut reader = BinFstReader::new(reader);
+        let hdr = reader.read_header(
+            &[&Self::fst_type(), CONST_DELTA_ENCODED_FST_TYPE],
+            Self::arc_type(),
+        )?;
+        let delta_encoded = hdr.fst_type.as_str() == CONST_DELTA_ENCODED_FST_TYPE;
+        let aligned = hdr.version == CONST_ALIGNED_FILE_VERSION;
+        if aligned && hdr.num_states > 0 {
+            reader.align(CONST_ARCH_ALIGNMENT)?;
         }
-
-        for tr in &*self.trs {
-            write_bin_i32(&mut output, tr.ilabel as i32)?;
-            write_bin_i32(&mut output, tr.olabel as i32)?;
-            tr.weight.write_binary(&mut output)?;
-            write_bin_i32(&mut output, tr.nextstate as i32)?;
+        for _ in 0..hdr.num_states {
+            reader.read_parsed(W::parse_binary)?;
+            reader.read_bytes(16)?;
+        }
+        if aligned && hdr.num_trs > 0 {
+            reader.align(CONST_ARCH_ALIGNMENT)?;
+        }
+        for _ in 0..hdr.num_trs {
+            if delta_encoded {
+                reader.read_parsed(parse_bin_varint_u64)?;
+                reader.read_parsed(parse_bin_varint_u64)?;
+                reader.read_parsed(W::parse_binary)?;
+                reader.read_parsed(parse_bin_varint_u64)?;
+            } else {
+                reader.read_tr::<W>()?;
+            }
         }
+        Self::load(reader.data())
+    }
 
-        Ok(())
+    fn load_relabeled(
+        data: &[u8],
+        ipairs: &[(Label, Label)],
+        opairs: &[(Label, Label)],
+    ) -> Result<Self> {
+        let opts = BinParsingOptions::relabeled(ipairs, opairs)?;
+        let (_, parsed_fst) = parse_const_fst(data, &opts)
+            .map_err(|_| format_err!("Error while parsing binary ConstFst"))?;
+
+        Ok(parsed_fst)
+    }
+
+    fn store<O: Write>(&self, output: O) -> Result<()> {
+        store_const_fst(self, output, false, false)
     }
 
     fn from_parsed_fst_text(mut parsed_fst_text: ParsedTextFst<W>) -> Result<Self> {
@@ -175,10 +191,225 @@ impl<W: SerializableSemiring> SerializableFst<W> for ConstFst<W> {
     }
 }
 
-fn parse_const_state<W: SerializableSemiring>(
-    i: &[u8],
-) -> IResult<&[u8], ConstState<W>, NomCustomError<&[u8]>> {
+/// Counts the bytes written to compute the padding of the aligned format.
+struct CountingWriter<O: Write> {
+    output: O,
+    pos: usize,
+}
+
+impl<O: Write> Write for CountingWriter<O> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let n = self.output.write(buf)?;
+        self.pos += n;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.output.flush()
+    }
+}
+
+impl<O: Write> CountingWriter<O> {
+    fn align(&mut self) -> Result<()> {
+        let padding =
+            (CONST_ARCH_ALIGNMENT - self.pos % CONST_ARCH_ALIGNMENT) % CONST_ARCH_ALIGNMENT;
+        self.write_all(&vec![0; padding])?;
+        Ok(())
+    }
+}
+
+fn store_const_fst<W: SerializableSemiring, O: Write>(
+    fst: &ConstFst<W>,
+    output: O,
+    delta_encoded: bool,
+    aligned: bool,
+) -> Result<()> {
+    let mut output = CountingWriter { output, pos: 0 };
+    let mut flags = FstFlags::empty();
+    if fst.input_symbols().is_some() {
+        flags |= FstFlags::HAS_ISYMBOLS;
+    }
+    if fst.output_symbols().is_some() {
+        flags |= FstFlags::HAS_OSYMBOLS;
+    }
+    if aligned {
+        flags |= FstFlags::IS_ALIGNED;
+    }
+
+    let hdr = FstHeader {
+        magic_number: FST_MAGIC_NUMBER,
+        fst_type: if delta_encoded {
+            OpenFstString::new(CONST_DELTA_ENCODED_FST_TYPE)
+        } else {
+            OpenFstString::new(ConstFst::<W>::fst_type())
+        },
+        tr_type: OpenFstString::new(ConstFst::<W>::arc_type()),
+        version: if aligned {
+            CONST_ALIGNED_FILE_VERSION
+        } else {
+            CONST_FILE_VERSION
+        },
+        flags,
+        properties: fst.properties.bits() | ConstFst::<W>::static_properties(),
+        start: fst.start.map(|v| v as i64).unwrap_or(-1),
+        num_states: fst.num_states() as i64,
+        num_trs: fst.trs.len() as i64,
+        isymt: fst.input_symbols().cloned(),
+        osymt: fst.output_symbols().cloned(),
+    };
+    hdr.write(&mut output)?;
+
+    if aligned && !fst.states.is_empty() {
+        output.align()?;
+    }
+    let zero = W::zero();
+    for const_state in &fst.states {
+        let f_weight = const_state.final_weight.as_ref().unwrap_or(&zero);
+        f_weight.write_binary(&mut output)?;
+
+        write_bin_i32(&mut output, const_state.pos as i32)?;
+        write_bin_i32(&mut output, const_state.ntrs as i32)?;
+        write_bin_i32(&mut output, const_state.niepsilons as i32)?;
+        write_bin_i32(&mut output, const_state.noepsilons as i32)?;
+    }
+
+    if aligned && !fst.trs.is_empty() {
+        output.align()?;
+    }
+    if delta_encoded {
+        let sources = tr_sources(&fst.states, fst.trs.len());
+        for (tr, source) in fst.trs.iter().zip(sources) {
+            write_bin_varint_u64(&mut output, tr.ilabel as u64)?;
+            write_bin_varint_u64(&mut output, tr.olabel as u64)?;
+            tr.weight.write_binary(&mut output)?;
+            let delta = tr.nextstate as i64 - source as i64;
+            write_bin_varint_u64(&mut output, zigzag_encode(delta))?;
+        }
+    } else {
+        for tr in &*fst.trs {
+            write_bin_i32(&mut output, tr.ilabel as i32)?;
+            write_bin_i32(&mut output, tr.olabel as i32)?;
+            tr.weight.write_binary(&mut output)?;
+            write_bin_i32(&mut output, tr.nextstate as i32)?;
+        }
+    }
+
+    Ok(())
+}
+
+impl<W: SerializableSemiring> ConstFst<W> {
+    /// Serializes the FST in binary format, storing the labels and the nextstates of the trs
+    /// as variable length integers, the nextstates being relative to their source state.
+    /// This reduces the size of the file for FSTs where the transitions mostly point to close
+    /// states. The resulting file can be loaded with `load` but is not compatible with OpenFST.
+    pub fn store_delta_encoded<O: Write>(&self, output: O) -> Result<()> {
+        store_const_fst(self, output, true, false)
+    }
+
+    /// Writes the FST to a file in the binary format of
+    /// [`store_delta_encoded`](#method.store_delta_encoded).
+    pub fn write_delta_encoded<P: AsRef<Path>>(&self, path_bin_fst: P) -> Result<()> {
+        let output = File::create(path_bin_fst.as_ref()).with_context(|| {
+            format!(
+                "Cannot create ConstFst binary file : {:?}",
+                path_bin_fst.as_ref()
+            )
+        })?;
+        self.store_delta_encoded(BufWriter::new(output))
+    }
+
+    /// Serializes the FST in the aligned binary format of OpenFST, where the states and the
+    /// trs start at offsets multiple of 16 bytes. Such a file can be memory-mapped with
+    /// [`open_mmap`](#method.open_mmap).
+    pub fn store_aligned<O: Write>(&self, output: O) -> Result<()> {
+        store_const_fst(self, output, false, true)
+    }
+
+    /// Writes the FST to a file in the binary format of
+    /// [`store_aligned`](#method.store_aligned).
+    pub fn write_aligned<P: AsRef<Path>>(&self, path_bin_fst: P) -> Result<()> {
+        let output = File::create(path_bin_fst.as_ref()).with_context(|| {
+            format!(
+                "Cannot create ConstFst binary file : {:?}",
+                path_bin_fst.as_ref()
+            )
+        })?;
+        self.store_aligned(BufWriter::new(output))
+    }
+}
+
+/// Returns the source state of each tr of the FST.
+fn tr_sources<W>(states: &[ConstState<W>], num_trs: usize) -> Vec<StateId> {
+    let mut sources = vec![0; num_trs];
+    for (state, const_state) in states.iter().enumerate() {
+        for source in sources
+            .iter_mut()
+            .skip(const_state.pos)
+            .take(const_state.ntrs)
+        {
+            *source = state as StateId;
+        }
+    }
+    sources
+}
+
+fn zigzag_encode(v: i64) -> u64 {
+    ((v << 1) ^ (v >> 63)) as u64
+}
+
+fn zigzag_decode(v: u64) -> i64 {
+    ((v >> 1) as i64) ^ -((v & 1) as i64)
+}
+
+/// Parses a varint label, failing if it doesn't fit in a `Label`.
+fn parse_label_varint(i: &[u8]) -> IResult<&[u8], Label, NomCustomError<&[u8]>> {
+    let (rest, label) = parse_bin_varint_u64(i)?;
+    match Label::try_from(label) {
+        Ok(label) => Ok((rest, label)),
+        Err(_) => Err(nom::Err::Error(NomCustomError::from_error_kind(
+            i,
+            ErrorKind::Verify,
+        ))),
+    }
+}
+
+fn parse_delta_encoded_tr<'a, W: SerializableSemiring>(
+    i: &'a [u8],
+    source: StateId,
+    num_states: i64,
+    opts: &BinParsingOptions,
+) -> IResult<&'a [u8], Tr<W>, NomCustomError<&'a [u8]>> {
+    let (i, ilabel) = parse_label_varint(i)?;
+    let (i, olabel) = parse_label_varint(i)?;
+    let (i, weight) = W::parse_binary(i)?;
+    let (rest, delta) = parse_bin_varint_u64(i)?;
+    let nextstate = (source as i64).checked_add(zigzag_decode(delta));
+    let nextstate = match nextstate {
+        Some(nextstate) if nextstate >= 0 && nextstate < num_states => nextstate as StateId,
+        _ => {
+            return Err(nom::Err::Error(NomCustomError::from_error_kind(
+                i,
+                ErrorKind::Verify,
+            )))
+        }
+    };
+    Ok((
+        rest,
+        Tr {
+            ilabel: opts.ilabel(ilabel),
+            olabel: opts.olabel(olabel),
+            weight: scale_weight(weight, opts.scale),
+            nextstate,
+        },
+    ))
+}
+
+fn parse_const_state<'a, W: SerializableSemiring>(
+    i: &'a [u8],
+    opts: &BinParsingOptions,
+) -> IResult<&'a [u8], ConstState<W>, NomCustomError<&'a [u8]>> {
     let (i, final_weight) = W::parse_binary(i)?;
+    let final_weight = scale_weight(final_weight, opts.scale);
     let (i, pos) = parse_bin_i32(i)?;
     let (i, ntrs) = parse_bin_i32(i)?;
     let (i, niepsilons) = parse_bin_i32(i)?;
@@ -196,17 +427,27 @@ fn parse_const_state<W: SerializableSemiring>(
     ))
 }
 
-fn parse_const_fst<W: SerializableSemiring>(
-    i: &[u8],
-) -> IResult<&[u8], ConstFst<W>, NomCustomError<&[u8]>> {
+fn parse_const_fst<'a, W: SerializableSemiring>(
+    i: &'a [u8],
+    opts: &BinParsingOptions,
+) -> IResult<&'a [u8], ConstFst<W>, NomCustomError<&'a [u8]>> {
     let stream_len = i.len();
 
     let (mut i, hdr) = FstHeader::parse(
         i,
         CONST_MIN_FILE_VERSION,
         ConstFst::<W>::fst_type(),
-        Tr::<W>::tr_type(),
-    )?;
+        ConstFst::<W>::arc_type(),
+    )
+    .or_else(|_| {
+        FstHeader::parse(
+            i,
+            CONST_MIN_FILE_VERSION,
+            CONST_DELTA_ENCODED_FST_TYPE,
+            ConstFst::<W>::arc_type(),
+        )
+    })?;
+    let delta_encoded = hdr.fst_type.as_str() == CONST_DELTA_ENCODED_FST_TYPE;
     let aligned = hdr.version == CONST_ALIGNED_FILE_VERSION;
     let pos = stream_len - i.len();
 
@@ -214,14 +455,49 @@ fn parse_const_fst<W: SerializableSemiring>(
     if aligned && hdr.num_states > 0 && pos % CONST_ARCH_ALIGNMENT > 0 {
         i = take(CONST_ARCH_ALIGNMENT - (pos % CONST_ARCH_ALIGNMENT))(i)?.0;
     }
-    let (mut i, const_states) = count(parse_const_state, hdr.num_states as usize)(i)?;
+    let (mut i, const_states) = count(|i| parse_const_state(i, opts), hdr.num_states as usize)(i)?;
     let pos = stream_len - i.len();
 
     // Align input
     if aligned && hdr.num_trs > 0 && pos % CONST_ARCH_ALIGNMENT > 0 {
         i = take(CONST_ARCH_ALIGNMENT - (pos % CONST_ARCH_ALIGNMENT))(i)?.0;
     }
-    let (i, const_trs) = count(parse_bin_fst_tr, hdr.num_trs as usize)(i)?;
+    let (i, const_trs) = if delta_encoded {
+        // Each tr takes at least three bytes : check the number of trs against the size of
+        // the input before allocating anything for them.
+        if hdr.num_trs < 0 || hdr.num_trs as u64 > (i.len() / 3) as u64 {
+            return Err(nom::Err::Error(NomCustomError::from_error_kind(
+                i,
+                ErrorKind::Verify,
+            )));
+        }
+        let sources = tr_sources(&const_states, hdr.num_trs as usize);
+        let mut const_trs = Vec::with_capacity(sources.len());
+        let mut i = i;
+        for source in sources {
+            let (rest, tr) = parse_delta_encoded_tr(i, source, hdr.num_states, opts)?;
+            const_trs.push(tr);
+            i = rest;
+        }
+        (i, const_trs)
+    } else {
+        count(
+            |i| parse_bin_fst_tr_with_options(i, opts),
+            hdr.num_trs as usize,
+        )(i)?
+    };
+
+    let mut const_states = const_states;
+    if opts.relabels() {
+        // The number of epsilons stored in the file are those before the relabeling.
+        for const_state in const_states.iter_mut() {
+            let trs = const_trs
+                .get(const_state.pos..const_state.pos + const_state.ntrs)
+                .unwrap_or(&[]);
+            const_state.niepsilons = trs.iter().filter(|tr| tr.ilabel == EPS_LABEL).count();
+            const_state.noepsilons = trs.iter().filter(|tr| tr.olabel == EPS_LABEL).count();
+        }
+    }
 
     Ok((
         i,
@@ -231,7 +507,182 @@ fn parse_const_fst<W: SerializableSemiring>(
             trs: Arc::new(const_trs),
             isymt: hdr.isymt,
             osymt: hdr.osymt,
-            properties: FstProperties::from_bits_truncate(hdr.properties),
+            properties: opts.properties(FstProperties::from_bits_truncate(hdr.properties)),
         },
     ))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fst_impls::VectorFst;
+    use crate::fst_traits::MutableFst;
+    use crate::semirings::{LogWeight, Semiring, TropicalWeight};
+    use crate::SymbolTable;
+
+    #[test]
+    fn test_const_fst_delta_encoded_round_trip() -> Result<()> {
+        // Large FST with locality : transitions to the next states and a few back edges.
+        let num_states = 10_000;
+        let mut fst = VectorFst::<TropicalWeight>::new();
+        fst.add_states(num_states);
+        fst.set_start(0)?;
+        fst.set_final(num_states as StateId - 1, 0.5)?;
+        for s in 0..(num_states as StateId - 2) {
+            fst.add_tr(s, Tr::new(s % 50 + 1, s % 30, 1.0, s + 1))?;
+            fst.add_tr(s, Tr::new(s % 7, EPS_LABEL, 2.0, s + 2))?;
+            if s >= 3 {
+                fst.add_tr(s, Tr::new(3, 4, TropicalWeight::one(), s - 3))?;
+            }
+        }
+        fst.add_tr(num_states as StateId - 1, Tr::new(1, 1, 1.0, 0))?;
+        let fst: ConstFst<_> = fst.into();
+
+        let mut plain = vec![];
+        fst.store(&mut plain)?;
+        let mut delta_encoded = vec![];
+        fst.store_delta_encoded(&mut delta_encoded)?;
+
+        // Labels and relative nextstates fit in a single byte instead of four.
+        assert!(delta_encoded.len() * 10 < plain.len() * 7);
+
+        let fst_plain = ConstFst::<TropicalWeight>::load(&plain)?;
+        let fst_delta_encoded = ConstFst::<TropicalWeight>::load(&delta_encoded)?;
+        assert_eq!(fst_delta_encoded, fst_plain);
+        assert_eq!(fst_delta_encoded, fst);
+        assert_eq!(fst_delta_encoded.properties, fst_plain.properties);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_const_fst_delta_encoded_invalid_nextstate() -> Result<()> {
+        let mut fst = VectorFst::<TropicalWeight>::new();
+        fst.add_states(2);
+        fst.set_start(0)?;
+        fst.add_tr(0, Tr::new(1, 2, 0.5, 1))?;
+        fst.set_final(1, 1.5)?;
+        let fst: ConstFst<_> = fst.into();
+
+        let mut data = vec![];
+        fst.store_delta_encoded(&mut data)?;
+        assert_eq!(ConstFst::<TropicalWeight>::load(&data)?, fst);
+        // The FST type differs from the one of the OpenFST format.
+        assert!(VectorFst::<TropicalWeight>::load(&data).is_err());
+
+        // The relative nextstate of the only tr is the last byte of the file.
+        for delta in &[2, -1] {
+            *data.last_mut().unwrap() = zigzag_encode(*delta) as u8;
+            assert!(ConstFst::<TropicalWeight>::load(&data).is_err());
+        }
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "state-label-u32")]
+    fn test_const_fst_delta_encoded_label_overflow() -> Result<()> {
+        let mut fst = VectorFst::<TropicalWeight>::new();
+        fst.add_states(2);
+        fst.set_start(0)?;
+        fst.add_tr(0, Tr::new(1, 2, 0.5, 1))?;
+        fst.set_final(1, 1.5)?;
+        let fst: ConstFst<_> = fst.into();
+
+        let mut data = vec![];
+        fst.store_delta_encoded(&mut data)?;
+
+        // The only tr is encoded in the last 7 bytes : ilabel, olabel, weight and nextstate.
+        let tr_pos = data.len() - 7;
+        let mut corrupted = data[..tr_pos].to_vec();
+        write_bin_varint_u64(&mut corrupted, u32::MAX as u64 + 1)?;
+        corrupted.extend_from_slice(&data[tr_pos + 1..]);
+        assert!(ConstFst::<TropicalWeight>::load(&corrupted).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_const_fst_delta_encoded_invalid_num_trs() -> Result<()> {
+        let mut fst = VectorFst::<TropicalWeight>::new();
+        fst.add_states(2);
+        fst.set_start(0)?;
+        fst.add_tr(0, Tr::new(1, 2, 0.5, 1))?;
+        fst.set_final(1, 1.5)?;
+        let fst: ConstFst<_> = fst.into();
+
+        let mut data = vec![];
+        fst.store_delta_encoded(&mut data)?;
+        let (rest, _) = FstHeader::parse(
+            &data,
+            CONST_MIN_FILE_VERSION,
+            CONST_DELTA_ENCODED_FST_TYPE,
+            ConstFst::<TropicalWeight>::arc_type(),
+        )
+        .unwrap();
+        // The number of trs is the last field of the header.
+        let num_trs_pos = data.len() - rest.len() - 8;
+
+        for num_trs in &[-1i64, i64::MAX, 1 << 40] {
+            data[num_trs_pos..num_trs_pos + 8].copy_from_slice(&num_trs.to_le_bytes());
+            assert!(ConstFst::<TropicalWeight>::load(&data).is_err());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_const_fst_read_from_reader() -> Result<()> {
+        let mut fst = VectorFst::<TropicalWeight>::new();
+        fst.add_states(2);
+        fst.set_start(0)?;
+        fst.add_tr(0, Tr::new(1, 2, 0.5, 1))?;
+        fst.set_final(1, 1.5)?;
+        let fst: ConstFst<_> = fst.into();
+
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("fst.bin");
+        fst.write(&path)?;
+        let mut buffer = vec![];
+        fst.write_to_writer(&mut buffer)?;
+        assert_eq!(buffer, std::fs::read(&path)?);
+
+        // FSTs one after the other in a larger stream, in all the formats.
+        let mut vector_fst: VectorFst<_> = crate::algorithms::fst_convert_from_ref(&fst);
+        let symt = Arc::new(crate::symt!["a", "b"]);
+        vector_fst.set_input_symbols(Arc::clone(&symt));
+        vector_fst.set_output_symbols(symt);
+        vector_fst.write_to_writer(&mut buffer)?;
+        fst.store_aligned(&mut buffer)?;
+        fst.store_delta_encoded(&mut buffer)?;
+        buffer.extend_from_slice(b"trailing data");
+        let mut reader = std::io::Cursor::new(buffer);
+        assert_eq!(ConstFst::read_from_reader(&mut reader)?, fst);
+        assert_eq!(VectorFst::read_from_reader(&mut reader)?, vector_fst);
+        for _ in 0..2 {
+            assert_eq!(ConstFst::read_from_reader(&mut reader)?, fst);
+        }
+        let mut rest = String::new();
+        reader.read_to_string(&mut rest)?;
+        assert_eq!(rest, "trailing data");
+
+        // Same checks as when reading a file.
+        assert!(ConstFst::<LogWeight>::read_from_reader(&std::fs::read(&path)?[..]).is_err());
+        assert!(VectorFst::<TropicalWeight>::read_from_reader(&std::fs::read(&path)?[..]).is_err());
+        assert!(ConstFst::<LogWeight>::read(&path).is_err());
+        assert!(ConstFst::<TropicalWeight>::read_from_reader(&b"not an fst"[..]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_varint_zigzag() -> Result<()> {
+        for v in &[0, 1, -1, 63, -64, 64, i32::MAX as i64, i32::MIN as i64] {
+            assert_eq!(zigzag_decode(zigzag_encode(*v)), *v);
+        }
+        for v in &[0, 1, 127, 128, 300, u32::MAX as u64, u64::MAX] {
+            let mut buffer = vec![];
+            write_bin_varint_u64(&mut buffer, *v)?;
+            let (rest, parsed) = parse_bin_varint_u64(&buffer).unwrap();
+            assert!(rest.is_empty());
+            assert_eq!(parsed, *v);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,146 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::Result;
+
+use crate::fst_impls::vector_fst::VectorFstState;
+use crate::fst_impls::VectorFst;
+use crate::fst_properties::FstProperties;
+use crate::fst_traits::{CoreFst, ExpandedFst, Fst, MutableFst};
+use crate::semirings::Semiring;
+use crate::{StateId, SymbolTable, Trs};
+
+/// Mutable FST layered over an immutable base FST, typically a [`ConstFst`](crate::fst_impls::ConstFst).
+///
+/// The base is shared and never copied : a state of the base is only copied when it is
+/// modified for the first time. The new states and the modified states are stored in an
+/// internal `VectorFst`, the other states are read from the base. This makes it possible
+/// to apply a few modifications to a huge FST at a low cost.
+///
+/// Deleting states requires to renumber all the states : in that case, the whole base is
+/// copied and the FST no longer depends on it.
+#[derive(Debug, Clone)]
+pub struct EditFst<W: Semiring, F: ExpandedFst<W>> {
+    /// Immutable FST, `None` once all the states have been copied in `edits`.
+    pub(crate) base: Option<Arc<F>>,
+    /// New and modified states.
+    pub(crate) edits: VectorFst<W>,
+    /// Id in `edits` of the new and modified states. Empty if there is no base : the ids in
+    /// `edits` are then the ids of the states.
+    pub(crate) external_to_internal: HashMap<StateId, StateId>,
+    pub(crate) num_states: usize,
+    pub(crate) start: Option<StateId>,
+    pub(crate) isymt: Option<Arc<SymbolTable>>,
+    pub(crate) osymt: Option<Arc<SymbolTable>>,
+    pub(crate) properties: FstProperties,
+}
+
+impl<W: Semiring, F: ExpandedFst<W>> EditFst<W, F> {
+    /// Creates an `EditFst` over `base`, without copying it.
+    pub fn from_base(base: Arc<F>) -> Self {
+        Self {
+            num_states: base.num_states(),
+            start: base.start(),
+            isymt: base.input_symbols().cloned(),
+            osymt: base.output_symbols().cloned(),
+            properties: base.properties(),
+            edits: VectorFst::new(),
+            external_to_internal: HashMap::new(),
+            base: Some(base),
+        }
+    }
+
+    /// Returns the base FST, `None` if all its states have been copied.
+    pub fn base(&self) -> Option<&Arc<F>> {
+        self.base.as_ref()
+    }
+
+    /// Returns the number of states that are not read from the base, i.e. the new states
+    /// and the modified ones.
+    pub fn num_edited_states(&self) -> usize {
+        self.edits.num_states()
+    }
+
+    /// Copies all the states of the base so that the FST no longer depends on it.
+    pub fn materialize(&mut self) -> Result<()> {
+        if let Some(base) = self.base.take() {
+            let mut edits = VectorFst::new();
+            edits.add_states(self.num_states);
+            for s in 0..(self.num_states as StateId) {
+                let state = match self.external_to_internal.get(&s) {
+                    Some(&internal) => std::mem::take(&mut self.edits.states[internal as usize]),
+                    None => base_state(&*base, s)?,
+                };
+                edits.states[s as usize] = state;
+            }
+            self.edits = edits;
+            self.external_to_internal.clear();
+        }
+        if let Some(start) = self.start {
+            self.edits.set_start(start)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the id of the state `state` in `edits`, `None` if it must be read from the base.
+    #[inline]
+    pub(crate) fn internal_id(&self, state: StateId) -> Option<StateId> {
+        if self.base.is_some() {
+            self.external_to_internal.get(&state).cloned()
+        } else {
+            Some(state)
+        }
+    }
+
+    /// Returns the id of the state `state` in `edits`, copying it from the base if needed.
+    pub(crate) fn edit_state(&mut self, state: StateId) -> Result<StateId> {
+        if let Some(internal) = self.internal_id(state) {
+            ensure!(
+                (internal as usize) < self.edits.num_states(),
+                "State {:?} doesn't exist",
+                state
+            );
+            return Ok(internal);
+        }
+        // Can't fail as internal_id returns a value when there is no base.
+        let base = self.base.as_ref().unwrap();
+        ensure!(
+            (state as usize) < base.num_states(),
+            "State {:?} doesn't exist",
+            state
+        );
+        let internal = self.edits.states.len() as StateId;
+        self.edits.states.push(base_state(&**base, state)?);
+        self.external_to_internal.insert(state, internal);
+        Ok(internal)
+    }
+}
+
+/// Copies a state of the base. The trs are only shallow copied when possible.
+fn base_state<W: Semiring, F: ExpandedFst<W>>(
+    base: &F,
+    state: StateId,
+) -> Result<VectorFstState<W>> {
+    Ok(VectorFstState {
+        final_weight: base.final_weight(state)?,
+        trs: base.get_trs(state)?.to_trs_vec(),
+        niepsilons: base.num_input_epsilons(state)?,
+        noepsilons: base.num_output_epsilons(state)?,
+    })
+}
+
+impl<W: Semiring, F: ExpandedFst<W>> PartialEq for EditFst<W, F> {
+    fn eq(&self, other: &Self) -> bool {
+        // Indended: Doesn't check properties and symbol tables.
+        if self.num_states != other.num_states || self.start != other.start {
+            return false;
+        }
+        (0..self.num_states as StateId).all(|s| {
+            self.final_weight(s).ok() == other.final_weight(s).ok()
+                && match (self.get_trs(s), other.get_trs(s)) {
+                    (Ok(trs_1), Ok(trs_2)) => trs_1.trs() == trs_2.trs(),
+                    _ => false,
+                }
+        })
+    }
+}
@@ -0,0 +1,9 @@
+use crate::fst_impls::EditFst;
+use crate::fst_traits::ExpandedFst;
+use crate::semirings::Semiring;
+
+impl<W: 'static + Semiring, F: 'static + ExpandedFst<W>> ExpandedFst<W> for EditFst<W, F> {
+    fn num_states(&self) -> usize {
+        self.num_states
+    }
+}
@@ -0,0 +1,149 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+
+use crate::fst_impls::EditFst;
+use crate::fst_properties::FstProperties;
+use crate::fst_traits::{CoreFst, ExpandedFst, Fst};
+use crate::semirings::Semiring;
+use crate::{StateId, SymbolTable, Tr, Trs, TrsVec};
+
+impl<W: Semiring, F: ExpandedFst<W>> Fst<W> for EditFst<W, F> {
+    fn input_symbols(&self) -> Option<&Arc<SymbolTable>> {
+        self.isymt.as_ref()
+    }
+
+    fn output_symbols(&self) -> Option<&Arc<SymbolTable>> {
+        self.osymt.as_ref()
+    }
+
+    fn set_input_symbols(&mut self, symt: Arc<SymbolTable>) {
+        self.isymt = Some(symt)
+    }
+
+    fn set_output_symbols(&mut self, symt: Arc<SymbolTable>) {
+        self.osymt = Some(symt)
+    }
+
+    fn take_input_symbols(&mut self) -> Option<Arc<SymbolTable>> {
+        self.isymt.take()
+    }
+
+    fn take_output_symbols(&mut self) -> Option<Arc<SymbolTable>> {
+        self.osymt.take()
+    }
+}
+
+/// Trs of a state of an [`EditFst`](struct.EditFst.html). The trs of the states that haven't
+/// been edited are the ones of the base FST, they are not copied.
+#[derive(Debug)]
+pub enum TrsEdit<W: Semiring, T: Trs<W>> {
+    Base(T),
+    Edited(TrsVec<W>),
+}
+
+impl<W: Semiring, T: Trs<W>> Trs<W> for TrsEdit<W, T> {
+    fn trs(&self) -> &[Tr<W>] {
+        match self {
+            TrsEdit::Base(trs) => trs.trs(),
+            TrsEdit::Edited(trs) => trs.trs(),
+        }
+    }
+
+    fn to_trs_vec(&self) -> TrsVec<W> {
+        match self {
+            TrsEdit::Base(trs) => trs.to_trs_vec(),
+            TrsEdit::Edited(trs) => trs.to_trs_vec(),
+        }
+    }
+
+    fn shallow_clone(&self) -> Self {
+        match self {
+            TrsEdit::Base(trs) => TrsEdit::Base(trs.shallow_clone()),
+            TrsEdit::Edited(trs) => TrsEdit::Edited(trs.shallow_clone()),
+        }
+    }
+}
+
+impl<W: Semiring, T: Trs<W>> std::ops::Deref for TrsEdit<W, T> {
+    type Target = [Tr<W>];
+    fn deref(&self) -> &Self::Target {
+        self.trs()
+    }
+}
+
+impl<W: Semiring, F: ExpandedFst<W>> CoreFst<W> for EditFst<W, F> {
+    type TRS = TrsEdit<W, F::TRS>;
+
+    fn start(&self) -> Option<StateId> {
+        self.start
+    }
+
+    fn final_weight(&self, state_id: StateId) -> Result<Option<W>> {
+        match (self.internal_id(state_id), &self.base) {
+            (Some(s), _) => self.edits.final_weight(s),
+            (None, Some(base)) => base.final_weight(state_id),
+            (None, None) => unreachable!(),
+        }
+    }
+
+    unsafe fn final_weight_unchecked(&self, state_id: StateId) -> Option<W> {
+        match (self.internal_id(state_id), &self.base) {
+            (Some(s), _) => self.edits.final_weight_unchecked(s),
+            (None, Some(base)) => base.final_weight_unchecked(state_id),
+            (None, None) => unreachable!(),
+        }
+    }
+
+    fn num_trs(&self, state_id: StateId) -> Result<usize> {
+        match (self.internal_id(state_id), &self.base) {
+            (Some(s), _) => self.edits.num_trs(s),
+            (None, Some(base)) => base.num_trs(state_id),
+            (None, None) => unreachable!(),
+        }
+    }
+
+    unsafe fn num_trs_unchecked(&self, state_id: StateId) -> usize {
+        match (self.internal_id(state_id), &self.base) {
+            (Some(s), _) => self.edits.num_trs_unchecked(s),
+            (None, Some(base)) => base.num_trs_unchecked(state_id),
+            (None, None) => unreachable!(),
+        }
+    }
+
+    fn get_trs(&self, state_id: StateId) -> Result<Self::TRS> {
+        match (self.internal_id(state_id), &self.base) {
+            (Some(s), _) => Ok(TrsEdit::Edited(self.edits.get_trs(s)?)),
+            (None, Some(base)) => Ok(TrsEdit::Base(base.get_trs(state_id)?)),
+            (None, None) => unreachable!(),
+        }
+    }
+
+    unsafe fn get_trs_unchecked(&self, state_id: StateId) -> Self::TRS {
+        match (self.internal_id(state_id), &self.base) {
+            (Some(s), _) => TrsEdit::Edited(self.edits.get_trs_unchecked(s)),
+            (None, Some(base)) => TrsEdit::Base(base.get_trs_unchecked(state_id)),
+            (None, None) => unreachable!(),
+        }
+    }
+
+    fn properties(&self) -> FstProperties {
+        self.properties
+    }
+
+    fn num_input_epsilons(&self, state_id: StateId) -> Result<usize> {
+        match (self.internal_id(state_id), &self.base) {
+            (Some(s), _) => self.edits.num_input_epsilons(s),
+            (None, Some(base)) => base.num_input_epsilons(state_id),
+            (None, None) => unreachable!(),
+        }
+    }
+
+    fn num_output_epsilons(&self, state_id: StateId) -> Result<usize> {
+        match (self.internal_id(state_id), &self.base) {
+            (Some(s), _) => self.edits.num_output_epsilons(s),
+            (None, Some(base)) => base.num_output_epsilons(state_id),
+            (None, None) => unreachable!(),
+        }
+    }
+}
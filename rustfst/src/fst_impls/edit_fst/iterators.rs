@@ -0,0 +1,49 @@
+use std::ops::Range;
+
+use crate::fst_impls::EditFst;
+use crate::fst_traits::FstIterData;
+use crate::fst_traits::{CoreFst, ExpandedFst, FstIntoIterator, FstIterator, StateIterator};
+use crate::semirings::Semiring;
+use crate::{StateId, Tr, Trs};
+
+impl<'a, W: Semiring, F: ExpandedFst<W>> StateIterator<'a> for EditFst<W, F> {
+    type Iter = Range<StateId>;
+    fn states_iter(&'a self) -> Self::Iter {
+        0..(self.num_states as StateId)
+    }
+}
+
+impl<W: Semiring + 'static, F: ExpandedFst<W> + 'static> FstIntoIterator<W> for EditFst<W, F> {
+    type TrsIter = std::vec::IntoIter<Tr<W>>;
+    // TODO: Change this to impl once the feature has been stabilized
+    // #![feature(type_alias_impl_trait)]
+    // https://github.com/rust-lang/rust/issues/63063)
+    type FstIter = Box<dyn Iterator<Item = FstIterData<W, Self::TrsIter>>>;
+
+    fn fst_into_iter(self) -> Self::FstIter {
+        Box::new((0..self.num_states as StateId).map(move |state_id| {
+            let trs = unsafe { self.get_trs_unchecked(state_id) }.trs().to_vec();
+            FstIterData {
+                state_id,
+                num_trs: trs.len(),
+                trs: trs.into_iter(),
+                final_weight: unsafe { self.final_weight_unchecked(state_id) },
+            }
+        }))
+    }
+}
+
+impl<'a, W: Semiring + 'static, F: ExpandedFst<W>> FstIterator<'a, W> for EditFst<W, F> {
+    type FstIter = Box<dyn Iterator<Item = FstIterData<W, Self::TRS>> + 'a>;
+    fn fst_iter(&'a self) -> Self::FstIter {
+        Box::new((0..self.num_states as StateId).map(move |state_id| {
+            let trs = unsafe { self.get_trs_unchecked(state_id) };
+            FstIterData {
+                state_id,
+                num_trs: trs.len(),
+                trs,
+                final_weight: unsafe { self.final_weight_unchecked(state_id) },
+            }
+        }))
+    }
+}
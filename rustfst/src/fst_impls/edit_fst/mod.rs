@@ -0,0 +1,9 @@
+pub use data_structure::EditFst;
+pub use fst::TrsEdit;
+
+mod data_structure;
+mod expanded_fst;
+mod fst;
+mod iterators;
+mod mutable_fst;
+mod test;
@@ -0,0 +1,234 @@
+use std::cmp::Ordering;
+use std::ops::Range;
+use std::sync::Arc;
+
+use anyhow::Result;
+use unsafe_unwrap::UnsafeUnwrap;
+
+use crate::fst_impls::{EditFst, VectorFst};
+use crate::fst_properties::mutable_properties::{
+    add_state_properties, add_tr_properties, delete_all_states_properties,
+    delete_states_properties, set_start_properties,
+};
+use crate::fst_properties::FstProperties;
+use crate::fst_traits::{CoreFst, ExpandedFst, MutableFst};
+use crate::semirings::Semiring;
+use crate::trs_iter_mut::TrsIterMut;
+use crate::{StateId, Tr, Trs};
+
+impl<W: Semiring, F: ExpandedFst<W>> EditFst<W, F> {
+    /// Applies `f` to the copy of the state `state` in `edits`. The properties are updated by
+    /// `f` so it must not depend on the ids of the states.
+    fn edit_with<R, M: FnOnce(&mut VectorFst<W>, StateId) -> R>(
+        &mut self,
+        state: StateId,
+        f: M,
+    ) -> Result<R> {
+        let internal = self.edit_state(state)?;
+        self.edits.properties = self.properties;
+        let res = f(&mut self.edits, internal);
+        self.properties = self.edits.properties;
+        Ok(res)
+    }
+}
+
+impl<W: Semiring, F: ExpandedFst<W> + 'static> MutableFst<W> for EditFst<W, F> {
+    fn new() -> Self {
+        EditFst {
+            base: None,
+            edits: VectorFst::new(),
+            external_to_internal: Default::default(),
+            num_states: 0,
+            start: None,
+            isymt: None,
+            osymt: None,
+            properties: FstProperties::null_properties(),
+        }
+    }
+
+    fn set_start(&mut self, state_id: StateId) -> Result<()> {
+        ensure!(
+            (state_id as usize) < self.num_states,
+            "The state {:?} doesn't exist",
+            state_id
+        );
+        self.start = Some(state_id);
+        self.properties = set_start_properties(self.properties);
+        Ok(())
+    }
+
+    unsafe fn set_start_unchecked(&mut self, state_id: StateId) {
+        self.start = Some(state_id);
+        self.properties = set_start_properties(self.properties);
+    }
+
+    fn set_final<S: Into<W>>(&mut self, state_id: StateId, final_weight: S) -> Result<()> {
+        self.edit_with(state_id, |edits, s| unsafe {
+            edits.set_final_unchecked(s, final_weight)
+        })
+    }
+
+    unsafe fn set_final_unchecked<S: Into<W>>(&mut self, state_id: StateId, final_weight: S) {
+        self.set_final(state_id, final_weight).unsafe_unwrap()
+    }
+
+    fn add_state(&mut self) -> StateId {
+        let id = self.num_states as StateId;
+        let internal = self.edits.add_state();
+        if self.base.is_some() {
+            self.external_to_internal.insert(id, internal);
+        }
+        self.num_states += 1;
+        self.properties = add_state_properties(self.properties);
+        id
+    }
+
+    fn add_states(&mut self, n: usize) -> Range<StateId> {
+        let start = self.num_states as StateId;
+        for _ in 0..n {
+            self.add_state();
+        }
+        start..(start + n as StateId)
+    }
+
+    fn tr_iter_mut(&mut self, state_id: StateId) -> Result<TrsIterMut<W>> {
+        let internal = self.edit_state(state_id)?;
+        let state = &mut self.edits.states[internal as usize];
+        let trs = Arc::make_mut(&mut state.trs.0);
+        Ok(TrsIterMut::new(
+            trs,
+            &mut self.properties,
+            &mut state.niepsilons,
+            &mut state.noepsilons,
+        ))
+    }
+
+    unsafe fn tr_iter_unchecked_mut(&mut self, state_id: StateId) -> TrsIterMut<W> {
+        self.tr_iter_mut(state_id).unsafe_unwrap()
+    }
+
+    fn del_state(&mut self, state_id: StateId) -> Result<()> {
+        ensure!(
+            (state_id as usize) < self.num_states,
+            "State id {:?} doesn't exist",
+            state_id
+        );
+        self.del_states(vec![state_id])
+    }
+
+    fn del_states<T: IntoIterator<Item = StateId>>(&mut self, dstates: T) -> Result<()> {
+        // The states are renumbered so the base can't be used anymore.
+        self.materialize()?;
+        self.edits.del_states(dstates)?;
+        self.start = self.edits.start();
+        self.num_states = self.edits.num_states();
+        self.properties = delete_states_properties(self.properties);
+        Ok(())
+    }
+
+    fn del_all_states(&mut self) {
+        self.base = None;
+        self.edits.del_all_states();
+        self.external_to_internal.clear();
+        self.num_states = 0;
+        self.start = None;
+        self.properties = delete_all_states_properties();
+    }
+
+    unsafe fn del_trs_id_sorted_unchecked(&mut self, state: StateId, to_del: &[usize]) {
+        self.edit_with(state, |edits, s| {
+            edits.del_trs_id_sorted_unchecked(s, to_del)
+        })
+        .unsafe_unwrap()
+    }
+
+    fn add_tr(&mut self, source: StateId, tr: Tr<W>) -> Result<()> {
+        let internal = self.edit_state(source)?;
+        let state = &mut self.edits.states[internal as usize];
+        state.increment_num_epsilons(&tr);
+        state.trs.push(tr);
+        // The properties are computed with the id of the state in the FST, not in `edits`.
+        let trs = state.trs.trs();
+        let old_tr = if trs.len() > 1 {
+            Some(&trs[trs.len() - 2])
+        } else {
+            None
+        };
+        self.properties = add_tr_properties(self.properties, source, &trs[trs.len() - 1], old_tr);
+        Ok(())
+    }
+
+    unsafe fn add_tr_unchecked(&mut self, source: StateId, tr: Tr<W>) {
+        self.add_tr(source, tr).unsafe_unwrap()
+    }
+
+    unsafe fn set_trs_unchecked(&mut self, source: StateId, trs: Vec<Tr<W>>) {
+        let internal = self.edit_state(source).unsafe_unwrap();
+        let mut properties = self.properties;
+        for i in 0..trs.len() {
+            let old_tr = if i >= 1 { Some(&trs[i - 1]) } else { None };
+            properties = add_tr_properties(properties, source, &trs[i], old_tr);
+        }
+        self.edits.set_trs_unchecked(internal, trs);
+        self.properties = properties;
+    }
+
+    fn delete_final_weight(&mut self, source: StateId) -> Result<()> {
+        self.edit_with(source, |edits, s| unsafe {
+            edits.delete_final_weight_unchecked(s)
+        })
+    }
+
+    unsafe fn delete_final_weight_unchecked(&mut self, source: StateId) {
+        self.delete_final_weight(source).unsafe_unwrap()
+    }
+
+    fn delete_trs(&mut self, source: StateId) -> Result<()> {
+        self.edit_with(source, |edits, s| edits.delete_trs(s))?
+    }
+
+    fn pop_trs(&mut self, source: StateId) -> Result<Vec<Tr<W>>> {
+        self.edit_with(source, |edits, s| edits.pop_trs(s))?
+    }
+
+    unsafe fn pop_trs_unchecked(&mut self, source: StateId) -> Vec<Tr<W>> {
+        self.pop_trs(source).unsafe_unwrap()
+    }
+
+    fn take_final_weight(&mut self, state_id: StateId) -> Result<Option<W>> {
+        self.edit_with(state_id, |edits, s| edits.take_final_weight(s))?
+    }
+
+    unsafe fn take_final_weight_unchecked(&mut self, state_id: StateId) -> Option<W> {
+        self.take_final_weight(state_id).unsafe_unwrap()
+    }
+
+    /// DOESN'T MODIFY THE PROPERTIES
+    fn sort_trs_unchecked<C: Fn(&Tr<W>, &Tr<W>) -> Ordering>(&mut self, state: StateId, f: C) {
+        unsafe {
+            self.edit_with(state, |edits, s| edits.sort_trs_unchecked(s, f))
+                .unsafe_unwrap()
+        }
+    }
+
+    /// DOESN'T MODIFY THE PROPERTIES
+    unsafe fn unique_trs_unchecked(&mut self, state: StateId) {
+        self.edit_with(state, |edits, s| edits.unique_trs_unchecked(s))
+            .unsafe_unwrap()
+    }
+
+    /// DOESN'T MODIFY THE PROPERTIES
+    unsafe fn sum_trs_unchecked(&mut self, state: StateId) {
+        self.edit_with(state, |edits, s| edits.sum_trs_unchecked(s))
+            .unsafe_unwrap()
+    }
+
+    fn set_properties(&mut self, props: FstProperties) {
+        self.properties = props;
+    }
+
+    fn set_properties_with_mask(&mut self, props: FstProperties, mask: FstProperties) {
+        self.properties &= !mask;
+        self.properties |= props & mask;
+    }
+}
@@ -0,0 +1,167 @@
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use anyhow::Result;
+
+    use crate::algorithms::tr_compares::ILabelCompare;
+    use crate::algorithms::{connect, fst_convert_from_ref, tr_sort};
+    use crate::fst_impls::{ConstFst, EditFst, TrsEdit, VectorFst};
+    use crate::fst_properties::FstProperties;
+    use crate::fst_traits::{CoreFst, ExpandedFst, Fst, MutableFst};
+    use crate::semirings::{Semiring, TropicalWeight};
+    use crate::{Tr, Trs};
+
+    type W = TropicalWeight;
+
+    fn base_fst() -> Result<ConstFst<W>> {
+        // 0 -1-> 1 -2-> 2 -3-> 3
+        let mut fst = VectorFst::<W>::new();
+        fst.add_states(4);
+        fst.set_start(0)?;
+        fst.set_final(3, W::one())?;
+        for s in 0..3 {
+            fst.add_tr(s, Tr::new(s + 1, s + 1, 1.0, s + 1))?;
+        }
+        fst.compute_and_update_properties_all()?;
+        Ok(fst.into())
+    }
+
+    fn paths<F: Fst<W>>(fst: &F) -> Vec<(Vec<u32>, W)> {
+        let mut paths: Vec<_> = fst.paths_iter().map(|p| (p.ilabels, p.weight)).collect();
+        paths.sort_by(|a, b| a.0.cmp(&b.0));
+        paths
+    }
+
+    #[test]
+    fn test_edit_fst_reads_base() -> Result<()> {
+        let base = Arc::new(base_fst()?);
+        let fst = EditFst::from_base(Arc::clone(&base));
+
+        assert_eq!(fst.num_states(), 4);
+        assert_eq!(fst.start(), Some(0));
+        assert_eq!(fst.num_edited_states(), 0);
+        assert_eq!(fst.properties(), base.properties());
+        assert_eq!(paths(&fst), paths(&*base));
+        assert!(fst.final_weight(4).is_err());
+
+        // The trs of the base are not copied.
+        match fst.get_trs(0)? {
+            TrsEdit::Base(trs) => assert!(Arc::ptr_eq(&trs.trs, &base.get_trs(0)?.trs)),
+            TrsEdit::Edited(_) => panic!("The state 0 hasn't been edited"),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_edit_fst_modifications() -> Result<()> {
+        let base = Arc::new(base_fst()?);
+        let mut fst = EditFst::from_base(Arc::clone(&base));
+
+        // Only the modified and new states are stored.
+        let s = fst.add_state();
+        assert_eq!(s, 4);
+        fst.add_tr(1, Tr::new(4, 4, 2.0, s))?;
+        assert!(matches!(fst.get_trs(1)?, TrsEdit::Edited(_)));
+        fst.set_final(s, 0.5)?;
+        fst.set_final(2, 3.0)?;
+        assert_eq!(fst.num_states(), 5);
+        assert_eq!(fst.num_edited_states(), 3);
+        assert_eq!(fst.num_trs(1)?, 2);
+        assert_eq!(fst.num_trs(0)?, 1);
+
+        assert_eq!(
+            paths(&fst),
+            vec![
+                (vec![1, 2], W::new(5.0)),
+                (vec![1, 2, 3], W::new(3.0)),
+                (vec![1, 4], W::new(3.5)),
+            ]
+        );
+        // The base is untouched.
+        assert_eq!(base.num_states(), 4);
+        assert_eq!(base.num_trs(1)?, 1);
+        assert_eq!(base.final_weight(2)?, None);
+
+        // Same result as the modifications applied on a copy.
+        let mut vfst: VectorFst<W> = fst_convert_from_ref(&*base);
+        let s = vfst.add_state();
+        vfst.add_tr(1, Tr::new(4, 4, 2.0, s))?;
+        vfst.set_final(s, 0.5)?;
+        vfst.set_final(2, 3.0)?;
+        assert_eq!(paths(&fst), paths(&vfst));
+
+        let mut it = fst.tr_iter_mut(0)?;
+        it.set_weight(0, W::new(10.0))?;
+        assert_eq!(fst.get_trs(0)?.trs()[0].weight, W::new(10.0));
+        assert_eq!(base.get_trs(0)?.trs()[0].weight, W::new(1.0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_edit_fst_properties() -> Result<()> {
+        let base = Arc::new(base_fst()?);
+        let mut fst = EditFst::from_base(base);
+        assert!(fst.properties().contains(FstProperties::TOP_SORTED));
+
+        // Properties are computed with the ids of the FST, not the ids of the copies.
+        fst.add_tr(3, Tr::new(5, 5, 1.0, 0))?;
+        assert!(!fst.properties().contains(FstProperties::TOP_SORTED));
+        assert!(!fst.properties().contains(FstProperties::ACYCLIC));
+        assert!(fst
+            .compute_and_update_properties_all()?
+            .contains(FstProperties::CYCLIC));
+
+        tr_sort(&mut fst, ILabelCompare {});
+        assert!(fst.properties().contains(FstProperties::I_LABEL_SORTED));
+        Ok(())
+    }
+
+    #[test]
+    fn test_edit_fst_delete_states() -> Result<()> {
+        let base = Arc::new(base_fst()?);
+        let mut fst = EditFst::from_base(base);
+        let s = fst.add_state();
+        fst.add_tr(0, Tr::new(5, 5, 1.0, s))?;
+
+        // The state added is not coaccessible.
+        connect(&mut fst)?;
+        assert!(fst.base().is_none());
+        assert_eq!(fst.num_states(), 4);
+        assert_eq!(paths(&fst), vec![(vec![1, 2, 3], W::new(3.0))]);
+
+        fst.del_all_states();
+        assert_eq!(fst.num_states(), 0);
+        assert_eq!(fst.start(), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_edit_fst_equality() -> Result<()> {
+        let base = Arc::new(base_fst()?);
+        let mut fst_1 = EditFst::from_base(Arc::clone(&base));
+        let mut fst_2 = EditFst::from_base(base);
+        assert_eq!(fst_1, fst_2);
+
+        fst_1.set_final(0, 1.0)?;
+        assert_ne!(fst_1, fst_2);
+        fst_2.materialize()?;
+        fst_2.set_final(0, 1.0)?;
+        assert_eq!(fst_1, fst_2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_edit_fst_add_states_range() -> Result<()> {
+        let mut fst = EditFst::from_base(Arc::new(base_fst()?));
+        let states = fst.add_states(2);
+        assert_eq!(states, 4..6);
+        assert_eq!(fst.num_states(), 6);
+        for s in states {
+            fst.add_tr(3, Tr::new(s, s, 1.0, s))?;
+        }
+        assert_eq!(fst.num_trs(3)?, 2);
+        assert_eq!(fst.base().unwrap().num_states(), 4);
+        Ok(())
+    }
+}
@@ -1,6 +1,10 @@
 mod arc;
+pub mod compact_fst;
 pub(crate) mod const_fst;
+pub(crate) mod edit_fst;
 pub(crate) mod vector_fst;
 
-pub use self::const_fst::ConstFst;
+pub use self::compact_fst::CompactFst;
+pub use self::const_fst::{ConstFst, MmapConstFst, MmapWeight, TrsMmap};
+pub use self::edit_fst::{EditFst, TrsEdit};
 pub use self::vector_fst::VectorFst;
@@ -0,0 +1,144 @@
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::fst_impls::VectorFst;
+use crate::fst_traits::{CoreFst, ExpandedFst, Fst, MutableFst, StateIterator};
+use crate::semirings::SerializableSemiring;
+use crate::{Label, StateId, SymbolTable, Tr};
+
+/// JSON representation of a `VectorFst`. Weights are stored in their text form so that
+/// infinities and composite weights are preserved.
+#[derive(Serialize, Deserialize)]
+struct JsonFst {
+    weight_type: String,
+    start: Option<StateId>,
+    states: Vec<JsonState>,
+    input_symbols: Option<Vec<String>>,
+    output_symbols: Option<Vec<String>>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct JsonState {
+    final_weight: Option<String>,
+    trs: Vec<JsonTr>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct JsonTr {
+    ilabel: Label,
+    olabel: Label,
+    weight: String,
+    nextstate: StateId,
+}
+
+fn symt_to_json(symt: Option<&Arc<SymbolTable>>) -> Option<Vec<String>> {
+    symt.map(|symt| symt.symbols().map(|s| s.to_string()).collect())
+}
+
+fn symt_from_json(symbols: Option<Vec<String>>) -> Result<Option<Arc<SymbolTable>>> {
+    symbols
+        .map(|symbols| {
+            let mut symt = SymbolTable::empty();
+            for (label, symbol) in symbols.into_iter().enumerate() {
+                if symt.add_symbol(symbol.as_str()) as usize != label {
+                    bail!("Duplicate symbol in symbol table : {}", symbol);
+                }
+            }
+            Ok(Arc::new(symt))
+        })
+        .transpose()
+}
+
+fn parse_weight<W: SerializableSemiring>(weight: &str) -> Result<W> {
+    let (rest, w) =
+        W::parse_text(weight).map_err(|e| format_err!("Can't parse weight {} : {}", weight, e))?;
+    if !rest.is_empty() {
+        bail!("Can't parse weight {} : trailing characters", weight);
+    }
+    Ok(w)
+}
+
+impl<W: SerializableSemiring> VectorFst<W> {
+    /// Serializes the FST to a human-readable JSON string : the start state, the final
+    /// weights and transitions of every state and the symbol tables if any. Meant for
+    /// debugging and interop, the binary format should be preferred otherwise.
+    ///
+    /// Requires the `serde` feature.
+    pub fn to_json(&self) -> Result<String> {
+        let states = self
+            .states_iter()
+            .map(|state| {
+                let final_weight = self.final_weight(state)?.map(|w| w.to_string());
+                let trs = self
+                    .get_trs(state)?
+                    .iter()
+                    .map(|tr| JsonTr {
+                        ilabel: tr.ilabel,
+                        olabel: tr.olabel,
+                        weight: tr.weight.to_string(),
+                        nextstate: tr.nextstate,
+                    })
+                    .collect();
+                Ok(JsonState { final_weight, trs })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let json_fst = JsonFst {
+            weight_type: W::weight_type(),
+            start: self.start(),
+            states,
+            input_symbols: symt_to_json(self.input_symbols()),
+            output_symbols: symt_to_json(self.output_symbols()),
+        };
+        Ok(serde_json::to_string_pretty(&json_fst)?)
+    }
+
+    /// Loads an FST from the JSON representation produced by [`to_json`](Self::to_json).
+    ///
+    /// Requires the `serde` feature.
+    pub fn from_json(json: &str) -> Result<Self> {
+        let json_fst: JsonFst = serde_json::from_str(json).context("Invalid JSON FST")?;
+        if json_fst.weight_type != W::weight_type() {
+            bail!(
+                "Wrong weight type : expected {}, got {}",
+                W::weight_type(),
+                json_fst.weight_type
+            );
+        }
+        let mut fst = VectorFst::new();
+        fst.add_states(json_fst.states.len());
+        if let Some(start) = json_fst.start {
+            fst.set_start(start)?;
+        }
+        for (state, json_state) in json_fst.states.into_iter().enumerate() {
+            let state = state as StateId;
+            if let Some(final_weight) = json_state.final_weight {
+                fst.set_final(state, parse_weight::<W>(&final_weight)?)?;
+            }
+            for tr in json_state.trs {
+                ensure!(
+                    (tr.nextstate as usize) < fst.num_states(),
+                    "Transition to unknown state {}",
+                    tr.nextstate
+                );
+                fst.add_tr(
+                    state,
+                    Tr::new(
+                        tr.ilabel,
+                        tr.olabel,
+                        parse_weight::<W>(&tr.weight)?,
+                        tr.nextstate,
+                    ),
+                )?;
+            }
+        }
+        if let Some(symt) = symt_from_json(json_fst.input_symbols)? {
+            fst.set_input_symbols(symt);
+        }
+        if let Some(symt) = symt_from_json(json_fst.output_symbols)? {
+            fst.set_output_symbols(symt);
+        }
+        Ok(fst)
+    }
+}
@@ -1,11 +1,14 @@
 pub use data_structure::VectorFst;
 pub(crate) use data_structure::VectorFstState;
+pub(crate) use serializable_fst::load_vector_fst_prefix;
 
 mod allocable_fst;
 mod data_structure;
 mod expanded_fst;
 mod fst;
 mod iterators;
+#[cfg(feature = "serde")]
+mod json;
 mod misc;
 mod mutable_fst;
 mod parse_const;
@@ -1,4 +1,5 @@
 use std::cmp::Ordering;
+use std::ops::Range;
 use std::sync::Arc;
 
 use anyhow::Result;
@@ -84,10 +85,11 @@ impl<W: Semiring> MutableFst<W> for VectorFst<W> {
         id as StateId
     }
 
-    fn add_states(&mut self, n: usize) {
+    fn add_states(&mut self, n: usize) -> Range<StateId> {
         let len = self.states.len();
         self.states.resize_with(len + n, VectorFstState::new);
         self.properties = add_state_properties(self.properties);
+        (len as StateId)..((len + n) as StateId)
     }
 
     fn tr_iter_mut(&mut self, state_id: StateId) -> Result<TrsIterMut<W>> {
@@ -132,7 +134,13 @@ impl<W: Semiring> MutableFst<W> for VectorFst<W> {
     fn del_states<T: IntoIterator<Item = StateId>>(&mut self, dstates: T) -> Result<()> {
         let mut new_id = vec![0_i32; self.states.len()];
 
+        // Checks all the ids before modifying anything.
         for s in dstates {
+            ensure!(
+                (s as usize) < new_id.len(),
+                "State id {:?} doesn't exist",
+                s
+            );
             new_id[s as usize] = -1;
         }
 
@@ -150,27 +158,24 @@ impl<W: Semiring> MutableFst<W> for VectorFst<W> {
 
         self.states.truncate(nstates);
 
-        for s in 0..self.states.len() {
-            let mut to_delete = vec![];
-            let state = &mut self.states[s];
+        for state in self.states.iter_mut() {
+            let niepsilons = &mut state.niepsilons;
+            let noepsilons = &mut state.noepsilons;
             let trs_mut = Arc::make_mut(&mut state.trs.0);
-            for (idx, tr) in trs_mut.iter_mut().enumerate() {
-                let t = new_id[tr.nextstate as usize];
-                if t != -1 {
-                    tr.nextstate = t as StateId
-                } else {
-                    to_delete.push(idx);
-                    if tr.ilabel == EPS_LABEL {
-                        state.niepsilons -= 1;
-                    }
-                    if tr.olabel == EPS_LABEL {
-                        state.noepsilons -= 1;
-                    }
+            trs_mut.retain(|tr| {
+                if new_id[tr.nextstate as usize] != -1 {
+                    return true;
                 }
-            }
-
-            for i in to_delete.iter().rev() {
-                self.states[s].trs.remove(*i);
+                if tr.ilabel == EPS_LABEL {
+                    *niepsilons -= 1;
+                }
+                if tr.olabel == EPS_LABEL {
+                    *noepsilons -= 1;
+                }
+                false
+            });
+            for tr in trs_mut.iter_mut() {
+                tr.nextstate = new_id[tr.nextstate as usize] as StateId;
             }
         }
 
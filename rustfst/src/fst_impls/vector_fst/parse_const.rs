@@ -21,7 +21,7 @@ use crate::parsers::bin_fst::utils_parsing::{
 };
 use crate::parsers::nom_utils::NomCustomError;
 use crate::semirings::SerializableSemiring;
-use crate::{Tr, TrsVec};
+use crate::TrsVec;
 
 impl<W: SerializableSemiring> VectorFst<W> {
     /// Load a VectorFst directly from a ConstFst file.
@@ -77,7 +77,7 @@ fn parse_const_fst<W: SerializableSemiring>(
         CONST_MIN_FILE_VERSION,
         // Intentional as the ConstFst file is being parsed.
         ConstFst::<W>::fst_type(),
-        Tr::<W>::tr_type(),
+        ConstFst::<W>::arc_type(),
     )?;
     let aligned = hdr.version == CONST_ALIGNED_FILE_VERSION;
     let pos = stream_len - i.len();
@@ -1,4 +1,4 @@
-use std::io::Write;
+use std::io::{Read, Write};
 use std::sync::Arc;
 
 use anyhow::Result;
@@ -10,16 +10,18 @@ use crate::fst_impls::vector_fst::VectorFstState;
 use crate::fst_impls::VectorFst;
 use crate::fst_properties::FstProperties;
 use crate::fst_traits::{CoreFst, ExpandedFst, Fst, MutableFst, SerializableFst};
+use crate::parsers::bin_fst::bin_fst_reader::BinFstReader;
 use crate::parsers::bin_fst::fst_header::{FstFlags, FstHeader, OpenFstString, FST_MAGIC_NUMBER};
 use crate::parsers::bin_fst::utils_parsing::{
-    parse_bin_fst_tr, parse_final_weight, parse_start_state,
+    check_scalable_weight, parse_bin_fst_tr_with_options, parse_final_weight, parse_start_state,
+    scale_weight, BinParsingOptions,
 };
 use crate::parsers::bin_fst::utils_serialization::write_bin_fst_tr;
 use crate::parsers::nom_utils::NomCustomError;
 use crate::parsers::text_fst::ParsedTextFst;
 use crate::parsers::write_bin_i64;
 use crate::semirings::SerializableSemiring;
-use crate::{StateId, Tr, Trs, TrsVec, EPS_LABEL};
+use crate::{Label, StateId, Tr, Trs, TrsVec, EPS_LABEL};
 
 impl<W: SerializableSemiring> SerializableFst<W> for VectorFst<W> {
     fn fst_type() -> String {
@@ -27,19 +29,32 @@ impl<W: SerializableSemiring> SerializableFst<W> for VectorFst<W> {
     }
 
     fn load(data: &[u8]) -> Result<Self> {
-        let (_, parsed_fst) = parse_vector_fst(data).map_err(|e| {
-            e.map(|e_inner| match e_inner {
-                NomCustomError::Nom(_, k) => {
-                    format_err!("Error while parsing binary VectorFst. Error kind {:?}", k)
-                }
-                NomCustomError::SymbolTableError(e) => format_err!(
-                    "Error while parsing symbolTable from binary VectorFst : {}",
-                    e
-                ),
-            })
-        })?;
-
-        Ok(parsed_fst)
+        load_vector_fst(data, &BinParsingOptions::default())
+    }
+
+    fn load_scaled(data: &[u8], scale: f32) -> Result<Self> {
+        check_scalable_weight::<W>()?;
+        load_vector_fst(data, &BinParsingOptions::scaled(scale))
+    }
+
+    fn load_relabeled(
+        data: &[u8],
+        ipairs: &[(Label, Label)],
+        opairs: &[(Label, Label)],
+    ) -> Result<Self> {
+        load_vector_fst(data, &BinParsingOptions::relabeled(ipairs, opairs)?)
+    }
+
+    fn read_from_reader<R: Read>(reader: R) -> Result<Self> {
+        let mut reader = BinFstReader::new(reader);
+        let hdr = reader.read_header(&[&Self::fst_type()], Self::arc_type())?;
+        for _ in 0..hdr.num_states {
+            reader.read_parsed(W::parse_binary)?;
+            for _ in 0..reader.read_size()? {
+                reader.read_tr::<W>()?;
+            }
+        }
+        Self::load(reader.data())
     }
 
     fn store<O: Write>(&self, mut output: O) -> Result<()> {
@@ -58,7 +73,7 @@ impl<W: SerializableSemiring> SerializableFst<W> for VectorFst<W> {
         let hdr = FstHeader {
             magic_number: FST_MAGIC_NUMBER,
             fst_type: OpenFstString::new(Self::fst_type()),
-            tr_type: OpenFstString::new(Tr::<W>::tr_type()),
+            tr_type: OpenFstString::new(Self::arc_type()),
             version: 2i32,
             // TODO: Set flags if the content is aligned
             flags,
@@ -126,6 +141,34 @@ impl<W: SerializableSemiring> SerializableFst<W> for VectorFst<W> {
 
 static VECTOR_MIN_FILE_VERSION: i32 = 2;
 
+fn load_vector_fst<W: SerializableSemiring>(
+    data: &[u8],
+    opts: &BinParsingOptions,
+) -> Result<VectorFst<W>> {
+    let (_, parsed_fst) = load_vector_fst_prefix(data, opts)?;
+    Ok(parsed_fst)
+}
+
+/// Parses a binary VectorFst at the beginning of `data` and returns the remaining bytes along
+/// with the FST, e.g. when several FSTs are stored one after the other.
+pub(crate) fn load_vector_fst_prefix<'a, W: SerializableSemiring>(
+    data: &'a [u8],
+    opts: &BinParsingOptions,
+) -> Result<(&'a [u8], VectorFst<W>)> {
+    parse_vector_fst(data, opts).map_err(|e| {
+        e.map(|e_inner| match e_inner {
+            NomCustomError::Nom(_, k) => {
+                format_err!("Error while parsing binary VectorFst. Error kind {:?}", k)
+            }
+            NomCustomError::SymbolTableError(e) => format_err!(
+                "Error while parsing symbolTable from binary VectorFst : {}",
+                e
+            ),
+        })
+        .into()
+    })
+}
+
 #[derive(Debug, PartialEq)]
 struct Transition {
     ilabel: i32,
@@ -134,12 +177,14 @@ struct Transition {
     nextstate: i32,
 }
 
-fn parse_vector_fst_state<W: SerializableSemiring>(
-    i: &[u8],
-) -> IResult<&[u8], VectorFstState<W>, NomCustomError<&[u8]>> {
+fn parse_vector_fst_state<'a, W: SerializableSemiring>(
+    i: &'a [u8],
+    opts: &BinParsingOptions,
+) -> IResult<&'a [u8], VectorFstState<W>, NomCustomError<&'a [u8]>> {
     let (i, final_weight) = W::parse_binary(i)?;
+    let final_weight = scale_weight(final_weight, opts.scale);
     let (i, num_trs) = le_i64(i)?;
-    let (i, trs) = count(parse_bin_fst_tr, num_trs as usize)(i)?;
+    let (i, trs) = count(|i| parse_bin_fst_tr_with_options(i, opts), num_trs as usize)(i)?;
     let niepsilons = trs.iter().filter(|t| t.ilabel == EPS_LABEL).count();
     let noepsilons = trs.iter().filter(|t| t.olabel == EPS_LABEL).count();
     Ok((
@@ -153,16 +198,20 @@ fn parse_vector_fst_state<W: SerializableSemiring>(
     ))
 }
 
-fn parse_vector_fst<W: SerializableSemiring>(
-    i: &[u8],
-) -> IResult<&[u8], VectorFst<W>, NomCustomError<&[u8]>> {
+fn parse_vector_fst<'a, W: SerializableSemiring>(
+    i: &'a [u8],
+    opts: &BinParsingOptions,
+) -> IResult<&'a [u8], VectorFst<W>, NomCustomError<&'a [u8]>> {
     let (i, header) = FstHeader::parse(
         i,
         VECTOR_MIN_FILE_VERSION,
         VectorFst::<W>::fst_type(),
-        Tr::<W>::tr_type(),
+        VectorFst::<W>::arc_type(),
     )?;
-    let (i, states) = count(parse_vector_fst_state, header.num_states as usize)(i)?;
+    let (i, states) = count(
+        |i| parse_vector_fst_state(i, opts),
+        header.num_states as usize,
+    )(i)?;
     Ok((
         i,
         VectorFst {
@@ -170,7 +219,7 @@ fn parse_vector_fst<W: SerializableSemiring>(
             states,
             isymt: header.isymt,
             osymt: header.osymt,
-            properties: FstProperties::from_bits_truncate(header.properties),
+            properties: opts.properties(FstProperties::from_bits_truncate(header.properties)),
         },
     ))
 }
@@ -4,15 +4,21 @@ mod tests {
 
     use anyhow::Result;
 
-    use crate::fst_impls::VectorFst;
+    use crate::algorithms::{isomorphic, relabel_pairs, state_sort, verify};
+    use crate::fst_impls::{ConstFst, VectorFst};
+    use crate::fst_properties::mutable_properties::relabel_properties;
+    use crate::fst_properties::FstProperties;
     use crate::fst_traits::{
-        CoreFst, ExpandedFst, Fst, MutableFst, SerializableFst, StateIterator,
+        AllocableFst, CoreFst, ExpandedFst, Fst, MutableFst, SerializableFst, StateIterator,
+        StateOrder,
     };
-    use crate::semirings::{ProbabilityWeight, Semiring, TropicalWeight};
+    use crate::semirings::{LogWeight, ProbabilityWeight, Semiring, TropicalWeight};
     use crate::tr::Tr;
-    use crate::{SymbolTable, Trs};
+    use crate::{DrawingConfig, SymbolTable, Trs};
     use rand::seq::SliceRandom;
+    use std::fs::read;
     use std::sync::Arc;
+    use tempfile::tempdir;
 
     #[test]
     fn test_small_fst() -> Result<()> {
@@ -250,6 +256,43 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_del_states_renumbering() -> Result<()> {
+        // 0 -> 1 -> 2 -> 3 -> 4 with epsilon transitions to 1 and 3.
+        let mut fst = VectorFst::<TropicalWeight>::new();
+        fst.add_states(5);
+        fst.set_start(0)?;
+        fst.set_final(4, 1.0)?;
+        for s in 0..4 {
+            fst.add_tr(s, Tr::new(s + 1, s + 1, 1.0, s + 1))?;
+            fst.add_tr(s, Tr::new(0, 0, 2.0, if s < 2 { 1 } else { 3 }))?;
+        }
+        let fst_ref = fst.clone();
+
+        assert!(fst.del_states(vec![1, 5]).is_err());
+        assert_eq!(fst, fst_ref);
+
+        // Duplicated ids are allowed.
+        fst.del_states(vec![3, 3])?;
+        assert_eq!(fst.num_states(), 4);
+        assert_eq!(fst.start(), Some(0));
+        assert_eq!(fst.final_weight(3)?, Some(TropicalWeight::new(1.0)));
+        assert_eq!(
+            fst.get_trs(1)?.trs(),
+            &[Tr::new(2, 2, 1.0, 2), Tr::new(0, 0, 2.0, 1)]
+        );
+        assert_eq!(fst.num_trs(2)?, 0);
+        assert_eq!(fst.num_input_epsilons(1)?, 1);
+        assert_eq!(fst.num_input_epsilons(2)?, 0);
+        assert_eq!(fst.num_output_epsilons(2)?, 0);
+
+        fst.delete_trs(1)?;
+        assert_eq!(fst.num_trs(1)?, 0);
+        assert_eq!(fst.num_input_epsilons(1)?, 0);
+        assert!(fst.delete_trs(4).is_err());
+        Ok(())
+    }
+
     #[test]
     fn test_parse_single_final_state() -> Result<()> {
         let parsed_fst = VectorFst::<TropicalWeight>::from_text_string("0\tInfinity\n")?;
@@ -329,4 +372,430 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_write_quantized() -> Result<()> {
+        let build = |eps: f32| -> Result<VectorFst<TropicalWeight>> {
+            let mut fst = VectorFst::<TropicalWeight>::new();
+            fst.add_states(3);
+            fst.set_start(0)?;
+            fst.add_tr(0, Tr::new(1, 1, 1.3 + eps, 1))?;
+            fst.add_tr(0, Tr::new(2, 2, TropicalWeight::one(), 2))?;
+            fst.add_tr(1, Tr::new(3, 3, TropicalWeight::zero(), 2))?;
+            fst.set_final(2, 0.7 - eps)?;
+            Ok(fst)
+        };
+        // Sub-delta differences, as produced by two different machines.
+        let fst_1 = build(0.0)?;
+        let fst_2 = build(1e-5)?;
+        let delta = 1.0 / 64.0;
+
+        let dir = tempdir()?;
+        let path_1 = dir.path().join("fst_1.fst");
+        let path_2 = dir.path().join("fst_2.fst");
+        fst_1.write_quantized(&path_1, delta)?;
+        fst_2.write_quantized(&path_2, delta)?;
+        assert_eq!(read(&path_1)?, read(&path_2)?);
+
+        let fst_read = VectorFst::<TropicalWeight>::read(&path_1)?;
+        let trs = fst_read.get_trs(0)?;
+        assert!(trs.trs()[0]
+            .weight
+            .approx_equal(TropicalWeight::new(1.3), delta));
+        assert_eq!(trs.trs()[1].weight, TropicalWeight::one());
+        let trs = fst_read.get_trs(1)?;
+        assert_eq!(trs.trs()[0].weight, TropicalWeight::zero());
+        assert!(fst_read
+            .final_weight(2)?
+            .unwrap()
+            .approx_equal(TropicalWeight::new(0.7), delta));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_text_ordered() -> Result<()> {
+        let mut fst = VectorFst::<TropicalWeight>::new();
+        fst.add_states(5);
+        fst.set_start(0)?;
+        fst.add_tr(0, Tr::new(1, 1, 1.0, 3))?;
+        fst.add_tr(0, Tr::new(2, 2, 2.0, 1))?;
+        fst.add_tr(3, Tr::new(3, 3, 3.0, 2))?;
+        fst.add_tr(1, Tr::new(4, 4, 4.0, 2))?;
+        fst.add_tr(2, Tr::new(5, 5, 5.0, 4))?;
+        fst.set_final(4, 0.5)?;
+
+        // Same FST with renamed states.
+        let mut renamed = fst.clone();
+        state_sort(&mut renamed, &[3, 0, 4, 2, 1])?;
+        assert_ne!(fst.text()?, renamed.text()?);
+
+        assert_eq!(fst.text_ordered(StateOrder::AsIs)?, fst.text()?);
+        for order in &[StateOrder::Bfs, StateOrder::TopOrder] {
+            let text = fst.text_ordered(*order)?;
+            assert_eq!(text, renamed.text_ordered(*order)?);
+            let fst_read = VectorFst::<TropicalWeight>::from_text_string(&text)?;
+            assert!(isomorphic(&fst, &fst_read)?);
+        }
+
+        fst.add_tr(4, Tr::new(6, 6, 6.0, 0))?;
+        assert!(fst.text_ordered(StateOrder::TopOrder).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_text_with_symt() -> Result<()> {
+        let mut isymt = SymbolTable::new();
+        isymt.add_symbols(vec!["a", "b"]);
+        // Epsilon has a different name in the output symbol table.
+        let mut osymt = SymbolTable::empty();
+        osymt.add_symbols(vec!["<epsilon>", "x", "y z"]);
+        let isymt = Arc::new(isymt);
+        let osymt = Arc::new(osymt);
+
+        let mut fst = VectorFst::<TropicalWeight>::new();
+        fst.add_states(3);
+        fst.set_start(0)?;
+        fst.add_tr(0, Tr::new(1, 0, 0.5, 1))?;
+        fst.add_tr(1, Tr::new(0, 2, 1.0, 2))?;
+        fst.add_tr(1, Tr::new(2, 1, 1.5, 0))?;
+        fst.set_final(2, 0.25)?;
+        fst.set_input_symbols(Arc::clone(&isymt));
+        fst.set_output_symbols(Arc::clone(&osymt));
+
+        let text = fst.text_with_symt()?;
+        assert_eq!(
+            text,
+            "0\t1\ta\t<epsilon>\t0.5\n1\t2\t<eps>\ty z\t1\n1\t0\tb\tx\t1.5\n2\t0.25\n"
+        );
+        let fst_read = VectorFst::<TropicalWeight>::from_text_string_with_symt(
+            &text,
+            Some(Arc::clone(&isymt)),
+            Some(Arc::clone(&osymt)),
+        )?;
+        assert!(isomorphic(&fst, &fst_read)?);
+        assert_eq!(fst_read.input_symbols(), Some(&isymt));
+        assert_eq!(fst_read.output_symbols(), Some(&osymt));
+
+        // A tape without symbol table uses integers.
+        let mut fst_no_osymt = fst.clone();
+        fst_no_osymt.take_output_symbols();
+        let text = fst_no_osymt.text_with_symt()?;
+        assert!(text.starts_with("0\t1\ta\t0\t0.5\n"));
+        let fst_read = VectorFst::<TropicalWeight>::from_text_string_with_symt(
+            &text,
+            Some(Arc::clone(&isymt)),
+            None,
+        )?;
+        assert!(isomorphic(&fst, &fst_read)?);
+
+        // Unknown labels and symbols are errors.
+        let mut fst_unknown = fst.clone();
+        fst_unknown.add_tr(2, Tr::new(3, 1, 1.0, 2))?;
+        assert!(fst_unknown.text_with_symt().is_err());
+        assert!(VectorFst::<TropicalWeight>::from_text_string_with_symt(
+            "0\t1\ta\tx\n1\t2\tc\tx\n2\n",
+            Some(Arc::clone(&isymt)),
+            Some(Arc::clone(&osymt)),
+        )
+        .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_draw_into() -> Result<()> {
+        let mut fst = VectorFst::<TropicalWeight>::new();
+        fst.add_states(2);
+        fst.set_start(0)?;
+        fst.add_tr(0, Tr::new(1, 2, 0.5, 1))?;
+        fst.add_tr(0, Tr::new(2, 2, TropicalWeight::one(), 1))?;
+        fst.set_final(1, TropicalWeight::one())?;
+        let mut symt = SymbolTable::new();
+        symt.add_symbol("a"); // 1
+        symt.add_symbol("b"); // 2
+        fst.set_input_symbols(Arc::new(symt));
+
+        let config = DrawingConfig {
+            acceptor: true,
+            portrait: true,
+            show_weight_one: false,
+            ..DrawingConfig::default()
+        };
+        let mut dot = vec![];
+        fst.draw_into(&mut dot, &config)?;
+        let expected = "digraph FST {
+rankdir = LR;
+label = \"\";
+center = 1;
+orientation = Portrait;
+0 [label = \"0\", shape = circle, style = bold, fontsize = 14]
+\t0 -> 1 [label = \"a/0.5\", fontsize = 14];
+\t0 -> 1 [label = \"b\", fontsize = 14];
+1 [label = \"1\", shape = doublecircle, style = solid, fontsize = 14]
+}
+";
+        assert_eq!(String::from_utf8(dot)?, expected);
+
+        // Without symbol table for the output labels.
+        let mut dot = vec![];
+        fst.draw_into(&mut dot, &DrawingConfig::default())?;
+        let dot = String::from_utf8(dot)?;
+        assert!(dot.contains("\t0 -> 1 [label = \"a:2/0.5\", fontsize = 14];"));
+        assert!(dot.contains("1 [label = \"1/0\", shape = doublecircle"));
+
+        // Nothing is drawn without a start state.
+        let mut dot = vec![];
+        VectorFst::<TropicalWeight>::new().draw_into(&mut dot, &config)?;
+        assert!(dot.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_scaled() -> Result<()> {
+        let mut fst = VectorFst::<TropicalWeight>::new();
+        fst.add_states(3);
+        fst.set_start(0)?;
+        fst.add_tr(0, Tr::new(1, 1, 1.5, 1))?;
+        fst.add_tr(0, Tr::new(2, 2, TropicalWeight::one(), 2))?;
+        fst.add_tr(1, Tr::new(3, 3, TropicalWeight::zero(), 2))?;
+        fst.add_tr(1, Tr::new(4, 4, -0.25, 2))?;
+        fst.set_final(2, 0.75)?;
+
+        let dir = tempdir()?;
+        let path = dir.path().join("fst.fst");
+        fst.write(&path)?;
+
+        // Raising a tropical weight to the power 2 multiplies its value by 2.
+        let mut fst_ref = VectorFst::<TropicalWeight>::read(&path)?;
+        for s in fst_ref.states_range() {
+            let mut it_tr = fst_ref.tr_iter_mut(s)?;
+            for idx in 0..it_tr.len() {
+                let weight = unsafe { it_tr.get_unchecked(idx) }.weight;
+                unsafe { it_tr.set_weight_unchecked(idx, weight.times(weight)?) };
+            }
+            if let Some(final_weight) = fst_ref.final_weight(s)? {
+                fst_ref.set_final(s, final_weight.times(final_weight)?)?;
+            }
+        }
+
+        assert_eq!(
+            VectorFst::<TropicalWeight>::read_scaled(&path, 2.0)?,
+            fst_ref
+        );
+        assert_eq!(
+            VectorFst::<TropicalWeight>::read_scaled(&path, 1.0)?,
+            VectorFst::<TropicalWeight>::read(&path)?
+        );
+        assert!(VectorFst::<ProbabilityWeight>::read_scaled(&path, 2.0).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_relabeled() -> Result<()> {
+        let mut fst = VectorFst::<TropicalWeight>::new();
+        fst.add_states(3);
+        fst.set_start(0)?;
+        fst.add_tr(0, Tr::new(1, 2, 1.5, 1))?;
+        fst.add_tr(0, Tr::new(2, 0, TropicalWeight::one(), 2))?;
+        fst.add_tr(1, Tr::new(0, 3, 2.0, 2))?;
+        fst.add_tr(1, Tr::new(3, 1, 0.5, 2))?;
+        fst.set_final(2, 0.75)?;
+
+        let dir = tempdir()?;
+        let path = dir.path().join("fst.fst");
+        fst.write(&path)?;
+
+        // Swaps 1 and 2 on the input side and 0 and 3 on the output side.
+        let ipairs = vec![(1, 2), (2, 1)];
+        let opairs = vec![(0, 3), (3, 0)];
+        let mut fst_ref = VectorFst::<TropicalWeight>::read(&path)?;
+        relabel_pairs(&mut fst_ref, ipairs.clone(), opairs.clone())?;
+
+        let fst_relabeled = VectorFst::<TropicalWeight>::read_relabeled(&path, &ipairs, &opairs)?;
+        assert_eq!(fst_relabeled, fst_ref);
+        for s in fst_ref.states_range() {
+            assert_eq!(
+                fst_relabeled.num_input_epsilons(s)?,
+                fst_ref.num_input_epsilons(s)?
+            );
+            assert_eq!(
+                fst_relabeled.num_output_epsilons(s)?,
+                fst_ref.num_output_epsilons(s)?
+            );
+        }
+        // Properties depending on the labels are not kept.
+        assert_eq!(
+            fst_relabeled.properties(),
+            relabel_properties(fst.properties())
+        );
+
+        let const_fst: ConstFst<TropicalWeight> = fst.into();
+        let const_path = dir.path().join("const_fst.fst");
+        const_fst.write(&const_path)?;
+        let const_fst_ref: ConstFst<TropicalWeight> = fst_ref.clone().into();
+        let const_fst_relabeled =
+            ConstFst::<TropicalWeight>::read_relabeled(&const_path, &ipairs, &opairs)?;
+        assert_eq!(const_fst_relabeled, const_fst_ref);
+        for s in fst_ref.states_range() {
+            assert_eq!(
+                const_fst_relabeled.num_input_epsilons(s)?,
+                fst_ref.num_input_epsilons(s)?
+            );
+        }
+
+        // A label can't be relabeled twice.
+        assert!(
+            VectorFst::<TropicalWeight>::read_relabeled(&path, &[(1, 2), (1, 3)], &[]).is_err()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_arc_type() -> Result<()> {
+        assert_eq!(VectorFst::<TropicalWeight>::arc_type(), "standard");
+        assert_eq!(ConstFst::<TropicalWeight>::arc_type(), "standard");
+        assert_eq!(VectorFst::<LogWeight>::arc_type(), "log");
+        assert_eq!(ConstFst::<LogWeight>::arc_type(), "log");
+
+        // The arc type is written in the header.
+        let mut fst = VectorFst::<LogWeight>::new();
+        let s = fst.add_state();
+        fst.set_start(s)?;
+        fst.set_final(s, LogWeight::one())?;
+        let mut data = vec![];
+        fst.store(&mut data)?;
+        assert!(data.windows(3).any(|w| w == b"log"));
+        assert!(VectorFst::<TropicalWeight>::load(&data).is_err());
+        assert_eq!(VectorFst::<LogWeight>::load(&data)?, fst);
+
+        Ok(())
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_json_round_trip() -> Result<()> {
+        let mut isymt = SymbolTable::new();
+        isymt.add_symbols(vec!["a", "b"]);
+        let isymt = Arc::new(isymt);
+
+        let mut fst = VectorFst::<TropicalWeight>::new();
+        fst.add_states(3);
+        fst.set_start(1)?;
+        fst.add_tr(1, Tr::new(1, 2, 0.5, 0))?;
+        fst.add_tr(0, Tr::new(2, 0, TropicalWeight::zero(), 2))?;
+        fst.add_tr(0, Tr::new(0, 1, 1.5, 0))?;
+        fst.set_final(2, 0.25)?;
+        fst.set_final(1, TropicalWeight::one())?;
+        fst.set_input_symbols(Arc::clone(&isymt));
+
+        let json = fst.to_json()?;
+        let fst_read = VectorFst::<TropicalWeight>::from_json(&json)?;
+        assert_eq!(fst_read, fst);
+        assert_eq!(fst_read.input_symbols(), Some(&isymt));
+        assert!(fst_read.output_symbols().is_none());
+
+        assert!(VectorFst::<LogWeight>::from_json(&json).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_properties_updated_on_mutation() -> Result<()> {
+        let mut fst = VectorFst::<TropicalWeight>::new();
+        fst.add_states(2);
+        fst.set_start(0)?;
+        fst.add_tr(0, Tr::new(1, 1, 1.0, 1))?;
+        fst.add_tr(0, Tr::new(3, 3, 1.0, 1))?;
+        assert!(fst.properties().contains(FstProperties::I_LABEL_SORTED));
+
+        // Out-of-order transition.
+        fst.add_tr(0, Tr::new(2, 2, 1.0, 1))?;
+        assert!(fst.properties().contains(FstProperties::NOT_I_LABEL_SORTED));
+        assert!(!fst.properties().contains(FstProperties::I_LABEL_SORTED));
+
+        // Wrong stored properties are trusted by compute_and_update_properties but not by
+        // compute_properties.
+        fst.set_properties_with_mask(
+            FstProperties::I_LABEL_SORTED,
+            FstProperties::I_LABEL_SORTED | FstProperties::NOT_I_LABEL_SORTED,
+        );
+        let mask = FstProperties::I_LABEL_SORTED | FstProperties::NOT_I_LABEL_SORTED;
+        assert_eq!(
+            fst.compute_and_update_properties(mask)?,
+            FstProperties::I_LABEL_SORTED
+        );
+        assert_eq!(
+            fst.compute_properties(mask)?,
+            FstProperties::NOT_I_LABEL_SORTED
+        );
+        assert!(fst.properties().contains(FstProperties::NOT_I_LABEL_SORTED));
+        Ok(())
+    }
+
+    #[test]
+    fn test_add_states_range() -> Result<()> {
+        let mut fst = VectorFst::<TropicalWeight>::new();
+        fst.reserve_states(5);
+        assert!(fst.states_capacity() >= 5);
+
+        assert_eq!(fst.add_states(0), 0..0);
+        let s0 = fst.add_state();
+        let states = fst.add_states(4);
+        assert_eq!(states, 1..5);
+        assert_eq!(fst.num_states(), 5);
+
+        fst.set_start(s0)?;
+        fst.reserve_trs(s0, states.len())?;
+        assert!(fst.trs_capacity(s0)? >= 4);
+        for s in states.clone() {
+            fst.add_tr(s0, Tr::new(s, s, 1.0, s))?;
+            fst.set_final(s, TropicalWeight::one())?;
+        }
+        assert_eq!(fst.num_trs(s0)?, 4);
+        assert_eq!(
+            fst.final_states_iter().collect::<Vec<_>>(),
+            states.collect::<Vec<_>>()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_tr_iter_mut_for_each_mut() -> Result<()> {
+        let mut fst = VectorFst::<TropicalWeight>::new();
+        fst.add_states(3);
+        fst.set_start(0)?;
+        fst.set_final(2, TropicalWeight::one())?;
+        fst.add_tr(0, Tr::new(1, 1, 2.0, 1))?;
+        fst.add_tr(0, Tr::new(2, 0, 4.0, 2))?;
+        fst.add_tr(1, Tr::new(3, 3, 1.0, 2))?;
+        fst.compute_and_update_properties_all()?;
+        assert!(fst.properties().contains(FstProperties::ACYCLIC));
+        assert_eq!(fst.num_output_epsilons(0)?, 1);
+
+        fst.tr_iter_mut(0)?
+            .for_each_mut(|tr| tr.weight = (*tr.weight.value() / 2.0).into());
+        verify(&fst)?;
+        fst.compute_and_update_properties_all()?;
+        let weights: Vec<_> = fst.get_trs(0)?.iter().map(|tr| tr.weight).collect();
+        assert_eq!(
+            weights,
+            vec![TropicalWeight::new(1.0), TropicalWeight::new(2.0)]
+        );
+
+        // Redirecting a transition creates a cycle.
+        fst.tr_iter_mut(1)?.for_each_mut(|tr| {
+            tr.nextstate = 0;
+            tr.olabel = 0;
+        });
+        assert!(!fst.properties().contains(FstProperties::ACYCLIC));
+        assert!(!fst.properties().contains(FstProperties::ACCEPTOR));
+        assert_eq!(fst.num_output_epsilons(1)?, 1);
+        verify(&fst)?;
+
+        let computed_props = fst.compute_properties(FstProperties::all_properties())?;
+        assert!(computed_props.contains(FstProperties::CYCLIC));
+        Ok(())
+    }
 }
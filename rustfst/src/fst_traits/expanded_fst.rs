@@ -36,6 +36,8 @@ pub trait ExpandedFst<W: Semiring>: Fst<W> + Clone + PartialEq + FstIntoIterator
         0..(self.num_states() as StateId)
     }
 
+    /// Returns true if both FSTs have the same states and transitions in the same order, the
+    /// weights being compared with `Semiring::approx_equal` and the tolerance `delta`.
     fn approx_equal<F2: ExpandedFst<W>>(&self, fst2: &F2, delta: f32) -> bool {
         let n = self.num_states();
         if fst2.num_states() != n {
@@ -4,7 +4,7 @@ pub use self::fst::{CoreFst, Fst};
 pub use self::iterators::{FstIntoIterator, FstIterData, FstIterator, StateIterator};
 pub use self::mutable_fst::MutableFst;
 pub use self::paths_iterator::PathsIterator;
-pub use self::serializable_fst::SerializableFst;
+pub use self::serializable_fst::{SerializableFst, StateOrder};
 pub use self::string_paths_iterator::StringPathsIterator;
 
 #[macro_use]
@@ -1,15 +1,17 @@
 use anyhow::Result;
 use std::cmp::Ordering;
+use std::collections::BTreeSet;
+use std::ops::Range;
 use std::sync::Arc;
 
 use crate::algorithms::closure::ClosureType;
-use crate::algorithms::TrMapper;
+use crate::algorithms::{relabel_pairs, TrMapper};
 use crate::fst_properties::FstProperties;
 use crate::fst_traits::{ExpandedFst, Fst};
 use crate::semirings::Semiring;
 use crate::tr::Tr;
 use crate::trs_iter_mut::TrsIterMut;
-use crate::{Label, StateId, SymbolTable};
+use crate::{Label, StateId, SymbolTable, Trs};
 
 /// Trait defining the methods to modify a wFST.
 pub trait MutableFst<W: Semiring>: ExpandedFst<W> {
@@ -102,10 +104,37 @@ pub trait MutableFst<W: Semiring>: ExpandedFst<W> {
     /// ```
     fn add_state(&mut self) -> StateId;
 
-    /// Add `n` states to the Fst.
-    fn add_states(&mut self, n: usize);
+    /// Adds `n` states to the FST. The identifiers of the new states are contiguous and
+    /// returned as a range, which can be used directly to add transitions.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use anyhow::Result;
+    /// # use rustfst::fst_traits::{CoreFst, MutableFst, ExpandedFst};
+    /// # use rustfst::fst_impls::VectorFst;
+    /// # use rustfst::semirings::{BooleanWeight, Semiring};
+    /// # use rustfst::Tr;
+    /// # fn main() -> Result<()> {
+    /// let mut fst = VectorFst::<BooleanWeight>::new();
+    /// fst.add_state();
+    ///
+    /// let states = fst.add_states(3);
+    /// assert_eq!(states, 1..4);
+    /// assert_eq!(fst.num_states(), 4);
+    ///
+    /// for s in states {
+    ///     fst.add_tr(s - 1, Tr::new(1, 1, BooleanWeight::one(), s))?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    fn add_states(&mut self, n: usize) -> Range<StateId>;
 
-    /// Return a mutable iterator on the `Tr`s of the state `state`.
+    /// Return a mutable iterator on the `Tr`s of the state `state`. The `Tr`s can be
+    /// modified in place either one at a time with the `set_*` methods or all together with
+    /// [`for_each_mut`](../struct.TrsIterMut.html#method.for_each_mut). In both cases, the
+    /// properties of the FST are kept up to date.
     fn tr_iter_mut(&mut self, state: StateId) -> Result<TrsIterMut<W>>;
     /// Return a mutable iterator on the `Tr`s of the state `state`.
     ///
@@ -139,8 +168,10 @@ pub trait MutableFst<W: Semiring>: ExpandedFst<W> {
     /// ```
     fn del_state(&mut self, state_id: StateId) -> Result<()>;
 
-    // TODO: Need to define a correct behaviour is the same state is present multiple times in the iterator
-    /// Removes multiple states from an FST. If one of the states doesn't exist, an error is raised.
+    /// Removes multiple states from an FST. If one of the states doesn't exist, an error is raised
+    /// and the FST is left unchanged. A state present several times is only removed once.
+    /// The remaining states are renumbered in a single pass, keeping their relative order,
+    /// which is much faster than deleting the states one by one.
     ///
     /// # Warning
     ///
@@ -440,6 +471,17 @@ pub trait MutableFst<W: Semiring>: ExpandedFst<W> {
         Ok(testprops & mask)
     }
 
+    /// Compute the properties verified by the Fst (with a mask) and update the internal
+    /// property bits. Unlike `compute_and_update_properties`, the stored properties are not
+    /// trusted : they are always recomputed from the states and transitions.
+    fn compute_properties(&mut self, mask: FstProperties) -> Result<FstProperties> {
+        let mut knownprops = FstProperties::empty();
+        let testprops =
+            crate::fst_properties::compute_fst_properties(self, mask, &mut knownprops, false)?;
+        self.set_properties_with_mask(testprops, knownprops);
+        Ok(testprops & mask)
+    }
+
     /// Compute all the properties verified by the Fst and update the internal property bits.
     fn compute_and_update_properties_all(&mut self) -> Result<FstProperties> {
         self.compute_and_update_properties(FstProperties::all_properties())
@@ -466,6 +508,9 @@ pub trait MutableFst<W: Semiring>: ExpandedFst<W> {
     /// If the Fst has a label `1` corresponding to the symbol "alpha" in the current symbol table and "alpha"
     /// is mapped to 4 in a new SymbolTable, then all the 1 are going to be mapped to 4.
     ///
+    /// If some symbols of the Fst are missing from the new `SymbolTable`s, an error listing all of
+    /// them is returned and the Fst is left untouched.
+    ///
     /// Parameters :
     /// - old_isymbols: Input `SymbolTable` used to build the Fst. If `None`, uses the Input `SymbolTable` attached to the Fst.
     /// - new_isymbols: New Input `SymbolTable` to use.
@@ -498,37 +543,17 @@ pub trait MutableFst<W: Semiring>: ExpandedFst<W> {
         };
         let old_osymbols = Arc::clone(old_osymbols);
 
+        let mut ilabels = BTreeSet::new();
+        let mut olabels = BTreeSet::new();
         for s in 0..self.num_states() {
-            let mut trs = self.tr_iter_mut(s as StateId)?;
-            for i in 0..trs.len() {
-                let tr = trs.get(i).unwrap();
-
-                let old_ilabel = tr.ilabel;
-                let isymbol = old_isymbols.get_symbol(old_ilabel).ok_or_else(|| {
-                    format_err!("Old Input SymbolTable doesn't contain label {}", old_ilabel)
-                })?;
-                let new_ilabel = new_isymbols.get_label(isymbol).ok_or_else(|| {
-                    format_err!("New Input SymbolTable doesn't contain symbol {:?}", isymbol)
-                })?;
-
-                let old_olabel = tr.olabel;
-                let osymbol = old_osymbols.get_symbol(old_olabel).ok_or_else(|| {
-                    format_err!(
-                        "Old Output SymbolTable doesn't contain label {}",
-                        old_olabel
-                    )
-                })?;
-                let new_olabel = new_osymbols.get_label(osymbol).ok_or_else(|| {
-                    format_err!(
-                        "New Output SymbolTable doesn't contain symbol {:?}",
-                        osymbol
-                    )
-                })?;
-
-                trs.set_ilabel(i, new_ilabel)?;
-                trs.set_olabel(i, new_olabel)?;
+            for tr in self.get_trs(s as StateId)?.trs() {
+                ilabels.insert(tr.ilabel);
+                olabels.insert(tr.olabel);
             }
         }
+        let ipairs = relabel_pairs_from_tables(&ilabels, &old_isymbols, new_isymbols, "Input")?;
+        let opairs = relabel_pairs_from_tables(&olabels, &old_osymbols, new_osymbols, "Output")?;
+        relabel_pairs(self, ipairs, opairs)?;
 
         self.take_input_symbols();
         self.take_output_symbols();
@@ -545,6 +570,43 @@ pub trait MutableFst<W: Semiring>: ExpandedFst<W> {
     }
 }
 
+/// Computes the pairs of labels mapping each label of `labels` to the label of the same
+/// symbol in `new_symt`. All the symbols missing from `new_symt` are reported at once.
+fn relabel_pairs_from_tables(
+    labels: &BTreeSet<Label>,
+    old_symt: &SymbolTable,
+    new_symt: &SymbolTable,
+    side: &str,
+) -> Result<Vec<(Label, Label)>> {
+    let mut pairs = Vec::with_capacity(labels.len());
+    let mut unknown_labels = vec![];
+    let mut missing_symbols = vec![];
+    for &label in labels {
+        match old_symt.get_symbol(label) {
+            Some(symbol) => match new_symt.get_label(symbol) {
+                Some(new_label) => pairs.push((label, new_label)),
+                None => missing_symbols.push(symbol),
+            },
+            None => unknown_labels.push(label),
+        }
+    }
+    if !unknown_labels.is_empty() {
+        bail!(
+            "Old {} SymbolTable doesn't contain the labels {:?}",
+            side,
+            unknown_labels
+        );
+    }
+    if !missing_symbols.is_empty() {
+        bail!(
+            "New {} SymbolTable doesn't contain the symbols {:?}",
+            side,
+            missing_symbols
+        );
+    }
+    Ok(pairs)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -609,4 +671,42 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_relabel_tables_missing_symbols() -> Result<()> {
+        let fst: VectorFst<TropicalWeight> = fst![1, 2, 3 => 3, 1, 1; 0.1];
+
+        let old_isymt = Arc::new(symt!["a", "b", "c"]);
+        let new_isymt = Arc::new(symt!["b"]);
+        let old_osymt = Arc::new(symt!["aa", "bb", "cc"]);
+
+        let mut fst_2 = fst.clone();
+        let err = fst_2
+            .relabel_tables(
+                Some(&old_isymt),
+                &new_isymt,
+                true,
+                Some(&old_osymt),
+                &old_osymt,
+                true,
+            )
+            .unwrap_err();
+        assert!(err.to_string().contains(r#"["a", "c"]"#));
+        // The FST is left untouched.
+        assert_eq!(fst_2, fst);
+
+        // Labels missing from the old SymbolTable are reported too.
+        let small_isymt = Arc::new(symt!["a"]);
+        assert!(fst_2
+            .relabel_tables(
+                Some(&small_isymt),
+                &old_isymt,
+                true,
+                Some(&old_osymt),
+                &old_osymt,
+                true
+            )
+            .is_err());
+        Ok(())
+    }
 }
@@ -1,15 +1,35 @@
+use std::collections::{HashSet, VecDeque};
 use std::fs::File;
-use std::io::{BufWriter, LineWriter, Write};
+use std::io::{BufWriter, LineWriter, Read, Write};
 use std::path::Path;
+use std::sync::{Arc, Mutex};
 
 use anyhow::{Context, Result};
+use once_cell::sync::Lazy;
 use unsafe_unwrap::UnsafeUnwrap;
 
-use crate::fst_traits::ExpandedFst;
+use crate::algorithms::tr_mappers::QuantizeMapper;
+use crate::algorithms::{state_sort, top_sort};
+use crate::fst_properties::FstProperties;
+use crate::fst_traits::{ExpandedFst, MutableFst};
 use crate::parsers::text_fst::ParsedTextFst;
-use crate::semirings::SerializableSemiring;
-use crate::Trs;
-use crate::{DrawingConfig, StateId};
+use crate::semirings::{SerializableSemiring, WeightQuantize};
+use crate::{DrawingConfig, Label, StateId, SymbolTable, EPS_LABEL, EPS_SYMBOL};
+use crate::{Tr, Trs};
+
+/// Order in which the states of an FST are numbered when it is serialized as text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StateOrder {
+    /// Keep the state ids of the FST.
+    #[default]
+    AsIs,
+    /// Number the states in breadth-first order from the start state, following the
+    /// transitions in the order they are stored. Unreachable states come last,
+    /// in the order of their original ids.
+    Bfs,
+    /// Number the states in topological order. Fails if the FST is cyclic.
+    TopOrder,
+}
 
 /// Trait definining the methods an Fst must implement to be serialized and deserialized.
 pub trait SerializableFst<W: SerializableSemiring>: ExpandedFst<W> {
@@ -17,11 +37,40 @@ pub trait SerializableFst<W: SerializableSemiring>: ExpandedFst<W> {
     /// deserializing an FST in binary format.
     fn fst_type() -> String;
 
+    /// String identifying the type of the transitions of the FST, derived from the weight.
+    /// It is written in the header when serializing the FST in binary format and checked
+    /// when deserializing it. As in OpenFST, it is `"standard"` for tropical weights and the
+    /// weight type otherwise (e.g `"log"`).
+    fn arc_type() -> &'static str {
+        intern_arc_type(Tr::<W>::tr_type())
+    }
+
     // BINARY
 
-    /// Loads an FST from the binary format data in a `Read`.
+    /// Loads an FST from data in binary format.
     fn load(input: &[u8]) -> Result<Self>;
 
+    /// Loads an FST from data in binary format, multiplying the underlying
+    /// value of all the weights by `scale` as they are parsed. Only tropical and log weights
+    /// are supported.
+    ///
+    /// The default implementation only supports a `scale` of 1 and returns an error
+    /// otherwise.
+    fn load_scaled(input: &[u8], scale: f32) -> Result<Self> {
+        if (scale - 1.0).abs() > f32::EPSILON {
+            bail!("Scaling the weights while loading is not supported by this FST type");
+        }
+        Self::load(input)
+    }
+
+    /// Loads an FST from data in binary format, relabeling the input and output
+    /// labels of the transitions with the pairs `(old_label, new_label)` as they are parsed.
+    fn load_relabeled(
+        input: &[u8],
+        ipairs: &[(Label, Label)],
+        opairs: &[(Label, Label)],
+    ) -> Result<Self>;
+
     /// Store the FST in binary format to a `Write`.
     fn store<O: Write>(&self, output: O) -> Result<()>;
 
@@ -36,6 +85,68 @@ pub trait SerializableFst<W: SerializableSemiring>: ExpandedFst<W> {
         })?;
         Self::load(&data)
     }
+    /// Loads an FST from a file in binary format, multiplying the underlying value of all
+    /// the weights by `scale` as they are parsed.
+    ///
+    /// This is equivalent to reading the FST and then raising all its weights to the power
+    /// `scale`, typically to apply an acoustic scale, without an extra pass over the transitions.
+    /// Infinite weights (e.g. `zero()`) are left untouched. Only tropical and log weights
+    /// are supported.
+    fn read_scaled<P: AsRef<Path>>(path_bin_fst: P, scale: f32) -> Result<Self> {
+        let data: Vec<u8> = std::fs::read(path_bin_fst.as_ref()).with_context(|| {
+            format!(
+                "Can't open {}Fst binary file : {:?}",
+                Self::fst_type(),
+                path_bin_fst.as_ref()
+            )
+        })?;
+        Self::load_scaled(&data, scale)
+    }
+
+    /// Loads an FST from a file in binary format, relabeling the input and output labels of
+    /// the transitions with the pairs `(old_label, new_label)` as they are parsed. Unlisted
+    /// labels are left unchanged.
+    ///
+    /// This is equivalent to reading the FST and then calling
+    /// [`relabel_pairs`](../algorithms/fn.relabel_pairs.html), without an extra pass over
+    /// the transitions. An error is returned if a label appears twice in the same pairs.
+    fn read_relabeled<P: AsRef<Path>>(
+        path_bin_fst: P,
+        ipairs: &[(Label, Label)],
+        opairs: &[(Label, Label)],
+    ) -> Result<Self> {
+        let data: Vec<u8> = std::fs::read(path_bin_fst.as_ref()).with_context(|| {
+            format!(
+                "Can't open {}Fst binary file : {:?}",
+                Self::fst_type(),
+                path_bin_fst.as_ref()
+            )
+        })?;
+        Self::load_relabeled(&data, ipairs, opairs)
+    }
+
+    /// Loads an FST in binary format from a `Read`, e.g. a network stream. The checks are the
+    /// same as in [`read`](#method.read).
+    ///
+    /// The FSTs of this crate only read the bytes of the FST, leaving `reader` just after it :
+    /// several FSTs stored one after the other can be read in sequence from the same reader.
+    /// The default implementation reads everything until the end of `reader`.
+    fn read_from_reader<R: Read>(mut reader: R) -> Result<Self> {
+        let mut data = vec![];
+        reader
+            .read_to_end(&mut data)
+            .with_context(|| format!("Can't read {}Fst binary data", Self::fst_type()))?;
+        Self::load(&data)
+    }
+
+    /// Writes the FST in binary format into a `Write`, e.g. an in-memory buffer, and flushes it.
+    /// The data is the same as the one written by [`write`](#method.write).
+    fn write_to_writer<O: Write>(&self, mut writer: O) -> Result<()> {
+        self.store(&mut writer)?;
+        writer.flush()?;
+        Ok(())
+    }
+
     /// Writes the FST to a file in binary format.
     fn write<P: AsRef<Path>>(&self, path_bin_fst: P) -> Result<()> {
         let output = std::fs::File::create(path_bin_fst.as_ref()).with_context(|| {
@@ -48,6 +159,23 @@ pub trait SerializableFst<W: SerializableSemiring>: ExpandedFst<W> {
         self.store(BufWriter::new(output))
     }
 
+    /// Writes the FST to a file in binary format after quantizing all its weights
+    /// to multiples of `delta`.
+    ///
+    /// FSTs whose weights only differ by float rounding errors (e.g. when computed on
+    /// different machines) are written to the exact same bytes, as long as the weights
+    /// don't straddle a multiple of `delta`. `one()`, `zero()` and infinite weights
+    /// are left untouched.
+    fn write_quantized<P: AsRef<Path>>(&self, path_bin_fst: P, delta: f32) -> Result<()>
+    where
+        W: WeightQuantize,
+        Self: MutableFst<W>,
+    {
+        let mut fst = self.clone();
+        fst.tr_map(&mut QuantizeMapper::new(delta))?;
+        fst.write(path_bin_fst)
+    }
+
     // TEXT
 
     /// Turns a generic wFST format into the one of the wFST.
@@ -59,6 +187,26 @@ pub trait SerializableFst<W: SerializableSemiring>: ExpandedFst<W> {
         Self::from_parsed_fst_text(parsed_text_fst)
     }
 
+    /// Deserializes a wFST in text whose labels are symbols of `isymt` and `osymt`, as written
+    /// by [`text_with_symt`](#method.text_with_symt). The symbol tables are attached to the
+    /// returned wFST. A tape without symbol table uses integer labels.
+    fn from_text_string_with_symt(
+        fst_string: &str,
+        isymt: Option<Arc<SymbolTable>>,
+        osymt: Option<Arc<SymbolTable>>,
+    ) -> Result<Self> {
+        let parsed_text_fst =
+            ParsedTextFst::from_string_with_symt(fst_string, isymt.as_deref(), osymt.as_deref())?;
+        let mut fst = Self::from_parsed_fst_text(parsed_text_fst)?;
+        if let Some(isymt) = isymt {
+            fst.set_input_symbols(isymt);
+        }
+        if let Some(osymt) = osymt {
+            fst.set_output_symbols(osymt);
+        }
+        Ok(fst)
+    }
+
     /// Deserializes a wFST in text from a path and returns a loaded wFST.
     fn read_text<P: AsRef<Path>>(path_text_fst: P) -> Result<Self> {
         let parsed_text_fst = ParsedTextFst::from_path(path_text_fst)?;
@@ -81,10 +229,64 @@ pub trait SerializableFst<W: SerializableSemiring>: ExpandedFst<W> {
         Ok(String::from_utf8(line_writer.into_inner()?)?)
     }
 
+    /// Serializes the FST as a text file, the labels being written with the symbols of the
+    /// attached symbol tables like `fstprint` does. See [`text_with_symt`](#method.text_with_symt).
+    fn write_text_with_symt<P: AsRef<Path>>(&self, path_output: P) -> Result<()> {
+        let buffer = File::create(path_output.as_ref())?;
+        let mut line_writer = LineWriter::new(buffer);
+        write_text_with_symt(self, &mut line_writer)
+    }
+
+    /// Writes the text representation of the FST into a String, the labels being written with
+    /// the symbols of the attached symbol tables. A tape without symbol table uses integers.
+    /// `EPS_LABEL` is written as `EPS_SYMBOL` if the symbol table doesn't contain it. Fails if a
+    /// label is missing from its symbol table.
+    fn text_with_symt(&self) -> Result<String> {
+        let mut buffer = Vec::<u8>::new();
+        write_text_with_symt(self, &mut buffer)?;
+        Ok(String::from_utf8(buffer)?)
+    }
+
+    /// Serializes the FST as a text file after renumbering its states according to `order`.
+    ///
+    /// The numbering only depends on the structure of the FST, not on its original state
+    /// ids (except for the unreachable states), which makes the output of two isomorphic
+    /// FSTs comparable line by line. `StateOrder::AsIs` behaves like `write_text`.
+    fn write_text_ordered<P: AsRef<Path>>(&self, path_output: P, order: StateOrder) -> Result<()>
+    where
+        Self: MutableFst<W>,
+    {
+        if order == StateOrder::AsIs {
+            return self.write_text(path_output);
+        }
+        reorder_states(self, order)?.write_text(path_output)
+    }
+
+    /// Writes the text representation of the FST into a String after renumbering its
+    /// states according to `order`. See [`write_text_ordered`](#method.write_text_ordered).
+    fn text_ordered(&self, order: StateOrder) -> Result<String>
+    where
+        Self: MutableFst<W>,
+    {
+        if order == StateOrder::AsIs {
+            return self.text();
+        }
+        reorder_states(self, order)?.text()
+    }
+
     /// Serializes the FST as a DOT file compatible with GraphViz binaries.
     fn draw<P: AsRef<Path>>(&self, path_output: P, config: &DrawingConfig) -> Result<()> {
         let buffer = File::create(path_output.as_ref())?;
-        let mut f = BufWriter::new(LineWriter::new(buffer));
+        self.draw_into(BufWriter::new(LineWriter::new(buffer)), config)
+    }
+
+    /// Writes the DOT representation of the FST, as `fstdraw` does, into `output`.
+    ///
+    /// The labels are displayed using the symbol tables attached to the FST if any. Final states
+    /// are drawn with a double circle and the start state in bold. Nothing is written if the FST
+    /// has no start state.
+    fn draw_into<O: Write>(&self, output: O, config: &DrawingConfig) -> Result<()> {
+        let mut f = output;
 
         if let Some(start_state) = self.start() {
             writeln!(f, "digraph FST {{")?;
@@ -127,10 +329,113 @@ pub trait SerializableFst<W: SerializableSemiring>: ExpandedFst<W> {
 
             writeln!(f, "}}")?;
         }
+        f.flush()?;
         Ok(())
     }
 }
 
+/// Returns a static string equal to `arc_type`. Each arc type is only allocated once and
+/// never freed, there are as few of them as weight types.
+fn intern_arc_type(arc_type: String) -> &'static str {
+    static ARC_TYPES: Lazy<Mutex<HashSet<&'static str>>> = Lazy::new(Default::default);
+    let mut arc_types = ARC_TYPES.lock().unwrap();
+    if let Some(interned) = arc_types.get(arc_type.as_str()) {
+        return interned;
+    }
+    let interned: &'static str = Box::leak(arc_type.into_boxed_str());
+    arc_types.insert(interned);
+    interned
+}
+
+fn label_to_symbol(label: Label, symt: Option<&Arc<SymbolTable>>) -> Result<String> {
+    match symt {
+        None => Ok(format!("{}", label)),
+        Some(symt) => match symt.get_symbol(label) {
+            Some(symbol) => Ok(symbol.to_string()),
+            None if label == EPS_LABEL => Ok(EPS_SYMBOL.to_string()),
+            None => bail!("Missing {} in SymbolTable", label),
+        },
+    }
+}
+
+fn write_text_with_symt<W, F, O>(fst: &F, f: &mut O) -> Result<()>
+where
+    W: SerializableSemiring,
+    F: SerializableFst<W>,
+    O: Write,
+{
+    if let Some(start_state) = fst.start() {
+        let states =
+            std::iter::once(start_state).chain(fst.states_iter().filter(|s| *s != start_state));
+        for state in states {
+            for tr in fst.get_trs(state)?.trs() {
+                let ilabel = label_to_symbol(tr.ilabel, fst.input_symbols())
+                    .with_context(|| format!("Input label of a transition leaving {}", state))?;
+                let olabel = label_to_symbol(tr.olabel, fst.output_symbols())
+                    .with_context(|| format!("Output label of a transition leaving {}", state))?;
+                writeln!(
+                    f,
+                    "{}\t{}\t{}\t{}\t{}",
+                    state, tr.nextstate, ilabel, olabel, tr.weight
+                )?;
+            }
+        }
+        for final_state in fst.final_states_iter() {
+            let final_weight = unsafe { fst.final_weight_unchecked(final_state).unsafe_unwrap() };
+            writeln!(f, "{}\t{}", final_state, final_weight)?;
+        }
+    }
+    Ok(())
+}
+
+fn reorder_states<W, F>(fst: &F, order: StateOrder) -> Result<F>
+where
+    W: SerializableSemiring,
+    F: SerializableFst<W> + MutableFst<W>,
+{
+    let mut ofst = fst.clone();
+    match order {
+        StateOrder::AsIs => {}
+        StateOrder::Bfs => {
+            let num_states = fst.num_states();
+            let mut new_ids: Vec<Option<StateId>> = vec![None; num_states];
+            let mut next_id = 0;
+            if let Some(start) = fst.start() {
+                let mut queue = VecDeque::new();
+                new_ids[start as usize] = Some(next_id);
+                next_id += 1;
+                queue.push_back(start);
+                while let Some(s) = queue.pop_front() {
+                    for tr in fst.get_trs(s)?.trs() {
+                        if new_ids[tr.nextstate as usize].is_none() {
+                            new_ids[tr.nextstate as usize] = Some(next_id);
+                            next_id += 1;
+                            queue.push_back(tr.nextstate);
+                        }
+                    }
+                }
+            }
+            let order: Vec<StateId> = new_ids
+                .into_iter()
+                .map(|id| {
+                    id.unwrap_or_else(|| {
+                        next_id += 1;
+                        next_id - 1
+                    })
+                })
+                .collect();
+            state_sort(&mut ofst, &order)?;
+        }
+        StateOrder::TopOrder => {
+            top_sort(&mut ofst)?;
+            if !ofst.properties().contains(FstProperties::TOP_SORTED) {
+                bail!("StateOrder::TopOrder can't be used on a cyclic FST");
+            }
+        }
+    };
+    Ok(ofst)
+}
+
 fn draw_single_fst_state<S: SerializableSemiring, F: SerializableFst<S>, W: Write>(
     fst: &F,
     writer: &mut W,
@@ -159,7 +464,7 @@ fn draw_single_fst_state<S: SerializableSemiring, F: SerializableFst<S>, W: Writ
 
     writeln!(writer, " fontsize = {}]", config.fontsize)?;
 
-    for tr in fst.get_trs(state_id).unwrap().trs() {
+    for tr in fst.get_trs(state_id)?.trs() {
         write!(writer, "\t{} -> {}", state_id, tr.nextstate)?;
 
         let ilabel = opt_isymt.map_or_else(
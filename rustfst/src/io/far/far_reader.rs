@@ -0,0 +1,205 @@
+use std::fs::File;
+use std::marker::PhantomData;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use memmap2::Mmap;
+
+use crate::fst_impls::vector_fst::load_vector_fst_prefix;
+use crate::fst_impls::VectorFst;
+use crate::io::far::{
+    FarType, STLIST_FILE_VERSION, STLIST_MAGIC_NUMBER, STTABLE_FILE_VERSION, STTABLE_MAGIC_NUMBER,
+};
+use crate::parsers::bin_fst::fst_header::OpenFstString;
+use crate::parsers::bin_fst::utils_parsing::BinParsingOptions;
+use crate::parsers::{parse_bin_i32, parse_bin_i64};
+use crate::semirings::SerializableSemiring;
+
+/// Reads an FST archive written by OpenFST or by [`FarWriter`](struct.FarWriter.html), in the
+/// `STTable` or `STList` format.
+///
+/// The file is memory-mapped and the reader iterates lazily over the `(key, fst)` pairs in the
+/// order they are stored, i.e. in the increasing order of the keys. Only the FSTs stored as
+/// `VectorFst` are supported, which is the default of `farcreate`.
+pub struct FarReader<W: SerializableSemiring> {
+    mmap: Mmap,
+    far_type: FarType,
+    /// Offsets of the entries for the `STTable` format.
+    positions: Vec<usize>,
+    /// Index of the next entry for the `STTable` format.
+    next_entry: usize,
+    /// Offset of the next entry for the `STList` format.
+    next_offset: usize,
+    done: bool,
+    weight: PhantomData<W>,
+}
+
+impl<W: SerializableSemiring> FarReader<W> {
+    /// Opens the archive `path` and checks its header. No FST is parsed at this point.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let file = File::open(path.as_ref())
+            .with_context(|| format!("Can't open FST archive : {:?}", path.as_ref()))?;
+        let mmap = unsafe { Mmap::map(&file) }
+            .with_context(|| format!("Can't map FST archive : {:?}", path.as_ref()))?;
+        let mut reader = Self {
+            mmap,
+            far_type: FarType::STTable,
+            positions: vec![],
+            next_entry: 0,
+            next_offset: 8,
+            done: false,
+            weight: PhantomData,
+        };
+        reader.parse_header()?;
+        Ok(reader)
+    }
+
+    fn parse_header(&mut self) -> Result<()> {
+        let data: &[u8] = &self.mmap;
+        let header = parse_bin_i32(data).and_then(|(i, magic)| {
+            let (i, version) = parse_bin_i32(i)?;
+            Ok((i, (magic, version)))
+        });
+        let (magic_number, file_version) = header
+            .map(|(_, header)| header)
+            .map_err(|_| format_err!("FST archive too short"))?;
+        match magic_number {
+            STTABLE_MAGIC_NUMBER => {
+                if file_version != STTABLE_FILE_VERSION {
+                    bail!("Unsupported STTable version : {}", file_version);
+                }
+                self.far_type = FarType::STTable;
+                self.positions = parse_sttable_index(data)?;
+            }
+            STLIST_MAGIC_NUMBER => {
+                if file_version != STLIST_FILE_VERSION {
+                    bail!("Unsupported STList version : {}", file_version);
+                }
+                self.far_type = FarType::STList;
+            }
+            _ => bail!("Unknown FST archive type, magic number : {}", magic_number),
+        };
+        Ok(())
+    }
+
+    /// Type of the archive.
+    pub fn far_type(&self) -> FarType {
+        self.far_type
+    }
+
+    /// Parses the FST with the key `key` if any. This is a binary search in the `STTable`
+    /// format and a linear scan in the `STList` format.
+    pub fn get(&self, key: &str) -> Result<Option<VectorFst<W>>> {
+        match self.far_type {
+            FarType::STTable => {
+                let mut low = 0;
+                let mut high = self.positions.len();
+                while low < high {
+                    let mid = (low + high) / 2;
+                    let (rest, entry_key) = parse_key(&self.mmap[self.positions[mid]..])?;
+                    match entry_key.as_str().cmp(key) {
+                        std::cmp::Ordering::Less => low = mid + 1,
+                        std::cmp::Ordering::Greater => high = mid,
+                        std::cmp::Ordering::Equal => return Ok(Some(parse_fst(rest)?.1)),
+                    }
+                }
+                Ok(None)
+            }
+            FarType::STList => {
+                let mut data = &self.mmap[8..];
+                while !data.is_empty() {
+                    let (rest, entry_key) = parse_key(data)?;
+                    // The entries end with an empty key.
+                    if entry_key.is_empty() {
+                        break;
+                    }
+                    let (rest, fst) = parse_fst(rest)?;
+                    if entry_key == key {
+                        return Ok(Some(fst));
+                    }
+                    data = rest;
+                }
+                Ok(None)
+            }
+        }
+    }
+
+    fn next_entry(&mut self) -> Option<Result<(String, VectorFst<W>)>> {
+        let offset = match self.far_type {
+            FarType::STTable => *self.positions.get(self.next_entry)?,
+            FarType::STList if self.next_offset >= self.mmap.len() => return None,
+            FarType::STList => self.next_offset,
+        };
+        let data: &[u8] = &self.mmap;
+        let (rest, key) = match parse_key(&data[offset..]) {
+            Ok(key) => key,
+            Err(e) => return Some(Err(e)),
+        };
+        // The entries of the STList format end with an empty key.
+        if key.is_empty() && self.far_type == FarType::STList {
+            return None;
+        }
+        let entry = parse_fst(rest).map(|(rest, fst)| (data.len() - rest.len(), (key, fst)));
+        Some(entry.map(|(next_offset, entry)| {
+            self.next_entry += 1;
+            self.next_offset = next_offset;
+            entry
+        }))
+    }
+}
+
+impl<W: SerializableSemiring> Iterator for FarReader<W> {
+    type Item = Result<(String, VectorFst<W>)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let entry = self.next_entry();
+        // Stops after an error as the position of the next entry is unknown.
+        self.done = !matches!(entry, Some(Ok(_)));
+        entry
+    }
+}
+
+/// Parses the index at the end of an archive in the `STTable` format.
+fn parse_sttable_index(data: &[u8]) -> Result<Vec<usize>> {
+    let invalid_index = || format_err!("Invalid index in STTable archive");
+    if data.len() < 16 {
+        bail!(invalid_index());
+    }
+    let (_, num_entries) = parse_bin_i64(&data[data.len() - 8..]).map_err(|_| invalid_index())?;
+    // The index is stored as a vector (its size followed by the positions).
+    let index_len = (num_entries as usize)
+        .checked_add(2)
+        .and_then(|n| n.checked_mul(8))
+        .filter(|n| num_entries >= 0 && *n <= data.len() - 8)
+        .ok_or_else(invalid_index)?;
+    let mut i = &data[data.len() - index_len..];
+    let (rest, size) = parse_bin_i64(i).map_err(|_| invalid_index())?;
+    if size != num_entries {
+        bail!(invalid_index());
+    }
+    i = rest;
+    let mut positions = Vec::with_capacity(num_entries as usize);
+    for _ in 0..num_entries {
+        let (rest, position) = parse_bin_i64(i).map_err(|_| invalid_index())?;
+        if position < 8 || position as usize >= data.len() - index_len {
+            bail!(invalid_index());
+        }
+        positions.push(position as usize);
+        i = rest;
+    }
+    Ok(positions)
+}
+
+fn parse_key(data: &[u8]) -> Result<(&[u8], String)> {
+    let (rest, key) =
+        OpenFstString::parse(data).map_err(|_| format_err!("Can't parse key in FST archive"))?;
+    Ok((rest, key.into()))
+}
+
+fn parse_fst<W: SerializableSemiring>(data: &[u8]) -> Result<(&[u8], VectorFst<W>)> {
+    load_vector_fst_prefix(data, &BinParsingOptions::default())
+        .context("Can't parse FST in FST archive")
+}
@@ -0,0 +1,148 @@
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::marker::PhantomData;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+
+use crate::fst_traits::SerializableFst;
+use crate::io::far::{
+    FarType, STLIST_FILE_VERSION, STLIST_MAGIC_NUMBER, STTABLE_FILE_VERSION, STTABLE_MAGIC_NUMBER,
+};
+use crate::parsers::bin_fst::fst_header::OpenFstString;
+use crate::parsers::{write_bin_i32, write_bin_i64};
+use crate::semirings::SerializableSemiring;
+
+/// Writes FSTs in an FST archive compatible with OpenFST.
+///
+/// The FSTs can be added in any order, they are written in the increasing order of their keys
+/// by [`finish`](#method.finish). The keys must be unique and not empty. The FSTs are kept
+/// serialized in memory until then. If `finish` is not called, the FSTs are written when the
+/// writer is dropped and the errors are ignored.
+///
+/// # Example
+///
+/// ```
+/// # use anyhow::Result;
+/// # use rustfst::prelude::*;
+/// # use rustfst::io::far::{FarReader, FarWriter, FarType};
+/// # fn main() -> Result<()> {
+/// # let dir = tempfile::tempdir()?;
+/// # let path = dir.path().join("archive.far");
+/// let mut fst = VectorFst::<TropicalWeight>::new();
+/// let s = fst.add_state();
+/// fst.set_start(s)?;
+/// fst.set_final(s, TropicalWeight::one())?;
+///
+/// let mut writer = FarWriter::create(&path, FarType::STTable)?;
+/// writer.add("fst_2", &fst)?;
+/// writer.add("fst_1", &fst)?;
+/// writer.finish()?;
+///
+/// let keys = FarReader::<TropicalWeight>::open(&path)?
+///     .map(|entry| entry.map(|(key, _)| key))
+///     .collect::<Result<Vec<_>>>()?;
+/// assert_eq!(keys, vec!["fst_1", "fst_2"]);
+/// # Ok(())
+/// # }
+/// ```
+pub struct FarWriter<W: SerializableSemiring, O: Write = BufWriter<File>> {
+    output: Option<O>,
+    far_type: FarType,
+    /// Serialized FSTs, sorted by key.
+    entries: BTreeMap<String, Vec<u8>>,
+    weight: PhantomData<W>,
+}
+
+impl<W: SerializableSemiring> FarWriter<W, BufWriter<File>> {
+    /// Creates the file `path` and writes the header of an archive of type `far_type` in it.
+    pub fn create<P: AsRef<Path>>(path: P, far_type: FarType) -> Result<Self> {
+        let file = File::create(path.as_ref())
+            .with_context(|| format!("Can't create FST archive : {:?}", path.as_ref()))?;
+        Self::new(BufWriter::new(file), far_type)
+    }
+}
+
+impl<W: SerializableSemiring, O: Write> FarWriter<W, O> {
+    /// Writes the header of an archive of type `far_type` in `output`.
+    pub fn new(mut output: O, far_type: FarType) -> Result<Self> {
+        let (magic_number, file_version) = match far_type {
+            FarType::STTable => (STTABLE_MAGIC_NUMBER, STTABLE_FILE_VERSION),
+            FarType::STList => (STLIST_MAGIC_NUMBER, STLIST_FILE_VERSION),
+        };
+        write_bin_i32(&mut output, magic_number)?;
+        write_bin_i32(&mut output, file_version)?;
+        Ok(Self {
+            output: Some(output),
+            far_type,
+            entries: BTreeMap::new(),
+            weight: PhantomData,
+        })
+    }
+
+    /// Type of the archive being written.
+    pub fn far_type(&self) -> FarType {
+        self.far_type
+    }
+
+    /// Adds `fst` to the archive with the key `key`.
+    pub fn add<F: SerializableFst<W>>(&mut self, key: &str, fst: &F) -> Result<()> {
+        if key.is_empty() {
+            bail!("FarWriter : Empty key");
+        }
+        if self.entries.contains_key(key) {
+            bail!("FarWriter : Key {:?} added twice", key);
+        }
+        if self.output.is_none() {
+            bail!("FarWriter : Archive already finished");
+        }
+        let mut buffer = vec![];
+        fst.store(&mut buffer)?;
+        self.entries.insert(key.to_string(), buffer);
+        Ok(())
+    }
+
+    /// Writes the FSTs and the end of the archive and returns the underlying output.
+    pub fn finish(mut self) -> Result<O> {
+        self.write_entries()
+    }
+
+    fn write_entries(&mut self) -> Result<O> {
+        let mut output = self
+            .output
+            .take()
+            .ok_or_else(|| format_err!("FarWriter : Archive already finished"))?;
+        let mut position = 8;
+        let mut positions = Vec::with_capacity(self.entries.len());
+        for (key, fst) in std::mem::take(&mut self.entries) {
+            let mut buffer = vec![];
+            OpenFstString::new(key).write(&mut buffer)?;
+            buffer.extend_from_slice(&fst);
+            output.write_all(&buffer)?;
+            positions.push(position);
+            position += buffer.len() as i64;
+        }
+        match self.far_type {
+            FarType::STTable => {
+                write_bin_i64(&mut output, positions.len() as i64)?;
+                for position in positions.iter() {
+                    write_bin_i64(&mut output, *position)?;
+                }
+                write_bin_i64(&mut output, positions.len() as i64)?;
+            }
+            // The entries end with an empty key, as in OpenFST.
+            FarType::STList => OpenFstString::new("").write(&mut output)?,
+        }
+        output.flush()?;
+        Ok(output)
+    }
+}
+
+impl<W: SerializableSemiring, O: Write> Drop for FarWriter<W, O> {
+    fn drop(&mut self) {
+        if self.output.is_some() {
+            let _ = self.write_entries();
+        }
+    }
+}
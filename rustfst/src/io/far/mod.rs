@@ -0,0 +1,168 @@
+pub use self::far_reader::FarReader;
+pub use self::far_writer::FarWriter;
+
+mod far_reader;
+mod far_writer;
+
+pub(crate) const STTABLE_MAGIC_NUMBER: i32 = 2_125_656_924;
+pub(crate) const STTABLE_FILE_VERSION: i32 = 1;
+pub(crate) const STLIST_MAGIC_NUMBER: i32 = 5_656_924;
+pub(crate) const STLIST_FILE_VERSION: i32 = 1;
+
+/// Format of an FST archive, same as in OpenFST.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FarType {
+    /// The FSTs are stored one after the other, followed by an index of their positions.
+    /// This is the default format of OpenFST.
+    #[default]
+    STTable,
+    /// The FSTs are stored one after the other, without index.
+    STList,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::convert::TryInto;
+
+    use anyhow::Result;
+    use tempfile::tempdir;
+
+    use crate::fst_impls::VectorFst;
+    use crate::fst_traits::MutableFst;
+    use crate::semirings::{LogWeight, Semiring};
+    use crate::Tr;
+
+    fn build_fst(n: u32) -> Result<VectorFst<LogWeight>> {
+        let mut fst = VectorFst::new();
+        fst.add_states(n as usize + 1);
+        fst.set_start(0)?;
+        for s in 0..n {
+            fst.add_tr(
+                s,
+                Tr::new(s + 1, s + 2, LogWeight::new(s as f32 * 0.5), s + 1),
+            )?;
+        }
+        fst.set_final(n, LogWeight::one())?;
+        Ok(fst)
+    }
+
+    #[test]
+    fn test_far_round_trip() -> Result<()> {
+        let dir = tempdir()?;
+        let fsts = vec![
+            ("a".to_string(), build_fst(0)?),
+            ("b".to_string(), build_fst(3)?),
+            ("c/d".to_string(), build_fst(1)?),
+        ];
+        for far_type in &[FarType::STTable, FarType::STList] {
+            let path = dir.path().join(format!("{:?}.far", far_type));
+            let mut writer = FarWriter::create(&path, *far_type)?;
+            for (key, fst) in fsts.iter() {
+                writer.add(key, fst)?;
+            }
+            writer.finish()?;
+
+            let reader = FarReader::<LogWeight>::open(&path)?;
+            assert_eq!(reader.far_type(), *far_type);
+            let fsts_read = reader.collect::<Result<Vec<_>>>()?;
+            assert_eq!(fsts_read, fsts);
+
+            let reader = FarReader::<LogWeight>::open(&path)?;
+            assert_eq!(reader.get("b")?, Some(fsts[1].1.clone()));
+            assert_eq!(reader.get("c")?, None);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_far_lazy_iteration() -> Result<()> {
+        let dir = tempdir()?;
+        let path = dir.path().join("lazy.far");
+        let mut writer = FarWriter::create(&path, FarType::STList)?;
+        for i in 0..100 {
+            writer.add(&format!("{:03}", i), &build_fst(i % 5)?)?;
+        }
+        writer.finish()?;
+
+        let mut reader = FarReader::<LogWeight>::open(&path)?;
+        let (key, fst) = reader.next().unwrap()?;
+        assert_eq!(key, "000");
+        assert_eq!(fst, build_fst(0)?);
+        assert_eq!(reader.nth(41).unwrap()?.0, "042");
+        assert_eq!(reader.count(), 57);
+        Ok(())
+    }
+
+    #[test]
+    fn test_far_sttable_layout() -> Result<()> {
+        let mut writer = FarWriter::new(vec![], FarType::STTable)?;
+        writer.add("k1", &build_fst(1)?)?;
+        writer.add("k2", &build_fst(2)?)?;
+        let data = writer.finish()?;
+
+        // Header, then the index of the entries followed by the number of entries.
+        assert_eq!(data[0..4], STTABLE_MAGIC_NUMBER.to_le_bytes());
+        assert_eq!(data[4..8], STTABLE_FILE_VERSION.to_le_bytes());
+        assert_eq!(data[data.len() - 8..], 2i64.to_le_bytes());
+        let index = &data[data.len() - 32..data.len() - 8];
+        assert_eq!(index[0..8], 2i64.to_le_bytes());
+        assert_eq!(index[8..16], 8i64.to_le_bytes());
+        // The second entry starts with its key.
+        let pos = i64::from_le_bytes(index[16..24].try_into()?) as usize;
+        assert_eq!(data[pos..pos + 4], 2i32.to_le_bytes());
+        assert_eq!(&data[pos + 4..pos + 6], b"k2");
+        Ok(())
+    }
+
+    #[test]
+    fn test_far_keys_sorted() -> Result<()> {
+        let dir = tempdir()?;
+        for far_type in &[FarType::STTable, FarType::STList] {
+            let path = dir.path().join(format!("{:?}.far", far_type));
+            let mut writer = FarWriter::create(&path, *far_type)?;
+            writer.add("b", &build_fst(1)?)?;
+            writer.add("a", &build_fst(0)?)?;
+            assert!(writer.add("b", &build_fst(1)?).is_err());
+            assert!(writer.add("", &build_fst(1)?).is_err());
+            writer.add("c", &build_fst(2)?)?;
+            writer.finish()?;
+
+            let reader = FarReader::<LogWeight>::open(&path)?;
+            let fsts_read = reader.collect::<Result<Vec<_>>>()?;
+            assert_eq!(
+                fsts_read,
+                vec![
+                    ("a".to_string(), build_fst(0)?),
+                    ("b".to_string(), build_fst(1)?),
+                    ("c".to_string(), build_fst(2)?),
+                ]
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_far_stlist_end() -> Result<()> {
+        let mut writer = FarWriter::new(vec![], FarType::STList)?;
+        writer.add("k1", &build_fst(1)?)?;
+        let mut data = writer.finish()?;
+        // The entries end with an empty key.
+        assert_eq!(data[data.len() - 4..], 0i32.to_le_bytes());
+
+        // Nothing is read past the empty key.
+        data.extend_from_slice(b"trailing data");
+        let dir = tempdir()?;
+        let path = dir.path().join("stlist.far");
+        std::fs::write(&path, &data)?;
+        let reader = FarReader::<LogWeight>::open(&path)?;
+        assert_eq!(reader.get("k1")?, Some(build_fst(1)?));
+        assert_eq!(reader.get("k2")?, None);
+        let keys = reader
+            .map(|entry| entry.map(|(key, _)| key))
+            .collect::<Result<Vec<_>>>()?;
+        assert_eq!(keys, vec!["k1"]);
+        Ok(())
+    }
+}
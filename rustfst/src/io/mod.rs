@@ -0,0 +1,2 @@
+/// Reading and writing of FST archives (FAR), i.e. files containing many FSTs indexed by a key.
+pub mod far;
@@ -254,6 +254,8 @@ mod tr;
 pub mod fst_traits;
 /// Implementation of the wFST traits with different data structures.
 pub mod fst_impls;
+/// Reading and writing of collections of wFSTs.
+pub mod io;
 /// Provides a trait that shall be implemented for all weights stored inside a wFST.
 pub mod semirings;
 
@@ -266,6 +268,9 @@ mod string_path;
 pub use crate::parsers::nom_utils::NomCustomError;
 
 /// A representable float near .001. (Used in Quantize)
+///
+/// This is also the default tolerance of `Semiring::approx_equal` and `ExpandedFst::approx_equal`
+/// as well as of the equality of float weights.
 pub const KDELTA: f32 = 1.0f32 / 1024.0f32;
 /// Default tolerance value used in floating-point comparisons.
 pub const KSHORTESTDELTA: f32 = 1e-6;
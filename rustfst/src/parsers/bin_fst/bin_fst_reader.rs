@@ -0,0 +1,152 @@
+use std::convert::TryInto;
+use std::io::Read;
+
+use anyhow::{Context, Result};
+use nom::IResult;
+
+use crate::parsers::bin_fst::fst_header::{FstFlags, FstHeader};
+use crate::parsers::nom_utils::NomCustomError;
+use crate::semirings::SerializableSemiring;
+
+/// Reads an FST in binary format from a `Read` without reading past its end.
+///
+/// The layout of the FST is followed to know how many bytes to read. The bytes are kept to be
+/// parsed as a whole once the FST has been read.
+pub(crate) struct BinFstReader<R: Read> {
+    reader: R,
+    data: Vec<u8>,
+}
+
+impl<R: Read> BinFstReader<R> {
+    pub(crate) fn new(reader: R) -> Self {
+        Self {
+            reader,
+            data: vec![],
+        }
+    }
+
+    /// Bytes read so far.
+    pub(crate) fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Reads exactly `n` bytes and returns them.
+    pub(crate) fn read_bytes(&mut self, n: usize) -> Result<&[u8]> {
+        let start = self.data.len();
+        // The bytes are appended as they come so that a corrupted size can't trigger a huge
+        // allocation.
+        let read = (&mut self.reader)
+            .take(n as u64)
+            .read_to_end(&mut self.data)
+            .context("Can't read FST binary data")?;
+        if read < n {
+            bail!("Unexpected end of the FST binary data");
+        }
+        Ok(&self.data[start..])
+    }
+
+    pub(crate) fn read_i32(&mut self) -> Result<i32> {
+        Ok(i32::from_le_bytes(self.read_bytes(4)?.try_into()?))
+    }
+
+    pub(crate) fn read_i64(&mut self) -> Result<i64> {
+        Ok(i64::from_le_bytes(self.read_bytes(8)?.try_into()?))
+    }
+
+    pub(crate) fn read_u32(&mut self) -> Result<u32> {
+        Ok(u32::from_le_bytes(self.read_bytes(4)?.try_into()?))
+    }
+
+    /// Reads a size, failing if it is negative.
+    pub(crate) fn read_size(&mut self) -> Result<usize> {
+        let n = self.read_i64()?;
+        if n < 0 {
+            bail!("Negative size in the FST binary data : {}", n);
+        }
+        Ok(n as usize)
+    }
+
+    fn read_string(&mut self) -> Result<String> {
+        let n = self.read_i32()?;
+        if n < 0 {
+            bail!("Negative string length in the FST binary data : {}", n);
+        }
+        Ok(String::from_utf8_lossy(self.read_bytes(n as usize)?).into_owned())
+    }
+
+    /// Reads an item whose size is only known once parsed, e.g. a weight or a varint. The
+    /// bytes are read one at a time until `parser` succeeds.
+    pub(crate) fn read_parsed<T, P>(&mut self, parser: P) -> Result<()>
+    where
+        P: Fn(&[u8]) -> IResult<&[u8], T, NomCustomError<&[u8]>>,
+    {
+        let start = self.data.len();
+        while parser(&self.data[start..]).is_err() {
+            self.read_bytes(1)?;
+        }
+        Ok(())
+    }
+
+    /// Reads a tr stored with fixed size labels and nextstate.
+    pub(crate) fn read_tr<W: SerializableSemiring>(&mut self) -> Result<()> {
+        self.read_bytes(8)?;
+        self.read_parsed(W::parse_binary)?;
+        self.read_bytes(4)?;
+        Ok(())
+    }
+
+    /// Reads the padding required for the next bytes to start at an offset multiple of
+    /// `alignment`.
+    pub(crate) fn align(&mut self, alignment: usize) -> Result<()> {
+        let pos = self.data.len();
+        self.read_bytes((alignment - pos % alignment) % alignment)?;
+        Ok(())
+    }
+
+    /// Reads the header of the FST, checking that its FST type is one of `fst_types` and that
+    /// its arc type is `arc_type` before going any further.
+    pub(crate) fn read_header(&mut self, fst_types: &[&str], arc_type: &str) -> Result<FstHeader> {
+        self.read_i32()?;
+        let fst_type = self.read_string()?;
+        if !fst_types.contains(&fst_type.as_str()) {
+            bail!("Unexpected FST type : {:?}", fst_type);
+        }
+        let tr_type = self.read_string()?;
+        if tr_type != arc_type {
+            bail!("Unexpected arc type : {:?}", tr_type);
+        }
+        // Version, flags, properties, start state and number of states and trs.
+        self.read_i32()?;
+        let flags = FstFlags::from_bits_truncate(self.read_u32()?);
+        self.read_bytes(32)?;
+        if flags.contains(FstFlags::HAS_ISYMBOLS) {
+            self.read_symbol_table()?;
+        }
+        if flags.contains(FstFlags::HAS_OSYMBOLS) {
+            self.read_symbol_table()?;
+        }
+        let (_, hdr) = FstHeader::parse(&self.data, i32::MIN, fst_type, tr_type)
+            .map_err(|_| format_err!("Error while parsing the header of the FST"))?;
+        if hdr.num_states < 0 || hdr.num_trs < 0 {
+            bail!(
+                "Negative number of states or trs in the FST binary data : {}, {}",
+                hdr.num_states,
+                hdr.num_trs
+            );
+        }
+        Ok(hdr)
+    }
+
+    fn read_symbol_table(&mut self) -> Result<()> {
+        // Magic number, name and available key.
+        self.read_i32()?;
+        self.read_string()?;
+        self.read_i64()?;
+        let num_symbols = self.read_size()?;
+        for _ in 0..num_symbols {
+            self.read_string()?;
+            self.read_i64()?;
+        }
+        Ok(())
+    }
+}
@@ -155,6 +155,10 @@ impl OpenFstString {
         ))
     }
 
+    pub(crate) fn as_str(&self) -> &str {
+        &self.s
+    }
+
     pub(crate) fn write<W: Write>(&self, file: &mut W) -> Result<()> {
         write_bin_i32(file, self.n)?;
         file.write_all(self.s.as_bytes()).map_err(|e| e.into())
@@ -1,3 +1,4 @@
+pub(crate) mod bin_fst_reader;
 pub(crate) mod fst_header;
 pub(crate) mod utils_parsing;
 pub(crate) mod utils_serialization;
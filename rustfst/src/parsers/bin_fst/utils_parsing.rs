@@ -1,10 +1,79 @@
+use std::collections::HashMap;
+
+use anyhow::{format_err, Context, Result};
 use nom::number::complete::le_i32;
 use nom::IResult;
 
+use crate::algorithms::relabel_pairs::iterator_to_hashmap;
+use crate::fst_properties::mutable_properties::relabel_properties;
+use crate::fst_properties::FstProperties;
 use crate::parsers::nom_utils::NomCustomError;
 use crate::semirings::SerializableSemiring;
 use crate::{Label, StateId, Tr};
 
+/// Transformations applied to the weights and labels of a binary FST while it is parsed.
+pub(crate) struct BinParsingOptions {
+    /// Multiplier of the underlying value of the weights.
+    pub(crate) scale: f32,
+    /// Relabeling of the input labels, unlisted labels are left unchanged.
+    pub(crate) ilabel_map: HashMap<Label, Label>,
+    /// Relabeling of the output labels, unlisted labels are left unchanged.
+    pub(crate) olabel_map: HashMap<Label, Label>,
+}
+
+impl Default for BinParsingOptions {
+    fn default() -> Self {
+        Self {
+            scale: 1.0,
+            ilabel_map: HashMap::new(),
+            olabel_map: HashMap::new(),
+        }
+    }
+}
+
+impl BinParsingOptions {
+    pub(crate) fn scaled(scale: f32) -> Self {
+        Self {
+            scale,
+            ..Self::default()
+        }
+    }
+
+    pub(crate) fn relabeled(ipairs: &[(Label, Label)], opairs: &[(Label, Label)]) -> Result<Self> {
+        Ok(Self {
+            ilabel_map: iterator_to_hashmap(ipairs.iter().cloned())
+                .with_context(|| format_err!("Error while creating the HashMap for ipairs"))?,
+            olabel_map: iterator_to_hashmap(opairs.iter().cloned())
+                .with_context(|| format_err!("Error while creating the HashMap for opairs"))?,
+            ..Self::default()
+        })
+    }
+
+    /// Returns true if the labels are modified while parsing.
+    pub(crate) fn relabels(&self) -> bool {
+        !self.ilabel_map.is_empty() || !self.olabel_map.is_empty()
+    }
+
+    /// Properties of the parsed FST given the properties stored in the header.
+    pub(crate) fn properties(&self, props: FstProperties) -> FstProperties {
+        if self.relabels() {
+            relabel_properties(props)
+        } else {
+            props
+        }
+    }
+
+    #[inline]
+    pub(crate) fn ilabel(&self, label: Label) -> Label {
+        *self.ilabel_map.get(&label).unwrap_or(&label)
+    }
+
+    #[inline]
+    pub(crate) fn olabel(&self, label: Label) -> Label {
+        *self.olabel_map.get(&label).unwrap_or(&label)
+    }
+}
+
 #[inline]
 pub(crate) fn parse_start_state(s: i64) -> Option<StateId> {
     if s == -1 {
@@ -25,9 +94,43 @@ pub(crate) fn parse_final_weight<W: SerializableSemiring>(weight: W) -> Option<W
     }
 }
 
+/// Checks that the weights of the semiring can be scaled while being parsed, i.e.
+/// that `W` is the tropical or the log semiring.
+pub(crate) fn check_scalable_weight<W: SerializableSemiring>() -> Result<()> {
+    let weight_type = W::weight_type();
+    if weight_type != "tropical" && weight_type != "log" {
+        bail!(
+            "Only tropical and log weights can be scaled, not {} weights",
+            weight_type
+        );
+    }
+    Ok(())
+}
+
+/// Multiplies the underlying value of a weight by `scale`. Infinite weights are left
+/// untouched, as well as all the weights when `scale` is `1.0`.
+#[inline]
+pub(crate) fn scale_weight<W: SerializableSemiring>(weight: W, scale: f32) -> W {
+    if scale == 1.0 {
+        return weight;
+    }
+    match weight.as_f32() {
+        Some(value) if value.is_finite() => W::from_f32(value * scale).unwrap_or(weight),
+        _ => weight,
+    }
+}
+
 pub(crate) fn parse_bin_fst_tr<W: SerializableSemiring>(
     i: &[u8],
 ) -> IResult<&[u8], Tr<W>, NomCustomError<&[u8]>> {
+    parse_bin_fst_tr_with_options(i, &BinParsingOptions::default())
+}
+
+/// Parses a transition, applying the scaling and the relabeling of `opts`.
+pub(crate) fn parse_bin_fst_tr_with_options<'a, W: SerializableSemiring>(
+    i: &'a [u8],
+    opts: &BinParsingOptions,
+) -> IResult<&'a [u8], Tr<W>, NomCustomError<&'a [u8]>> {
     let (i, ilabel) = le_i32(i)?;
     let (i, olabel) = le_i32(i)?;
     let (i, weight) = W::parse_binary(i)?;
@@ -35,9 +138,9 @@ pub(crate) fn parse_bin_fst_tr<W: SerializableSemiring>(
     Ok((
         i,
         Tr {
-            ilabel: ilabel as Label,
-            olabel: olabel as Label,
-            weight,
+            ilabel: opts.ilabel(ilabel as Label),
+            olabel: opts.olabel(olabel as Label),
+            weight: scale_weight(weight, opts.scale),
             nextstate: nextstate as StateId,
         },
     ))
@@ -9,6 +9,7 @@ use crate::parsers::{write_bin_i32, write_bin_i64};
 use crate::{Label, SymbolTable};
 use anyhow::Result;
 use bitflags::_core::hash::BuildHasher;
+use std::convert::TryFrom;
 use std::io::Write;
 
 static SYMBOL_TABLE_MAGIC_NUMBER: i32 = 2_125_658_996;
@@ -30,12 +31,14 @@ pub(crate) fn parse_symbol_table_bin(
 
     let mut symt = SymbolTable::empty();
     for (key, symbol) in pairs_idx_symbols.into_iter() {
-        let inserted_label = symt.add_symbol(symbol);
-        if inserted_label != key as Label {
-            return Err(nom::Err::Error(NomCustomError::SymbolTableError(
-                format!("SymbolTable must contain increasing labels with no hole. Expected : {} and Got : {}", inserted_label, key)
-            )));
-        }
+        let label = Label::try_from(key).map_err(|_| {
+            nom::Err::Error(NomCustomError::SymbolTableError(format!(
+                "Invalid label in SymbolTable : {}",
+                key
+            )))
+        })?;
+        symt.add_symbol_with_key(symbol, label)
+            .map_err(|e| nom::Err::Error(NomCustomError::SymbolTableError(e.to_string())))?;
     }
 
     Ok((i, symt))
@@ -47,8 +50,7 @@ pub(crate) fn write_bin_symt<W: Write, H: BuildHasher>(
 ) -> Result<()> {
     write_bin_i32(file, SYMBOL_TABLE_MAGIC_NUMBER)?;
     OpenFstString::new("rustfst_symboltable").write(file)?;
-    // TODO: Might not be available
-    write_bin_i64(file, symt.len() as i64)?;
+    write_bin_i64(file, symt.available_key() as i64)?;
     write_bin_i64(file, symt.len() as i64)?;
     for (label, symbol) in symt.iter() {
         OpenFstString::new(symbol).write(file)?;
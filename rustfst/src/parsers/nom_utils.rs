@@ -1,4 +1,3 @@
-use nom::bytes::complete::take_while;
 use nom::character::complete::digit1;
 use nom::combinator::map_res;
 use nom::IResult;
@@ -32,8 +31,3 @@ impl<I, E> FromExternalError<I, E> for NomCustomError<I> {
 pub fn num<V: FromStr>(i: &str) -> IResult<&str, V> {
     map_res(digit1, |s: &str| s.parse())(i)
 }
-
-pub fn word(i: &str) -> IResult<&str, String> {
-    let (i, letters) = take_while(|c: char| (c != ' ') && (c != '\t') && (c != '\n'))(i)?;
-    Ok((i, letters.to_string()))
-}
@@ -1,7 +1,7 @@
 use nom::branch::alt;
-use nom::bytes::complete::tag;
+use nom::bytes::complete::{tag, take_while1};
 use nom::character::complete::tab;
-use nom::combinator::opt;
+use nom::combinator::{map_res, opt};
 use nom::multi::separated_list0;
 use nom::sequence::preceded;
 use nom::IResult;
@@ -9,19 +9,40 @@ use nom::IResult;
 use crate::parsers::nom_utils::num;
 use crate::parsers::text_fst::parsed_text_fst::{FinalState, RowParsed, Transition};
 use crate::semirings::SerializableSemiring;
+use crate::{Label, SymbolTable, EPS_LABEL, EPS_SYMBOL};
 
 fn optional_weight<W: SerializableSemiring>(i: &str) -> IResult<&str, Option<W>> {
     opt(preceded(tab, W::parse_text))(i)
 }
 
-fn transition<W: SerializableSemiring>(i: &str) -> IResult<&str, RowParsed<W>> {
+/// Parses a label, either as an integer or as a symbol of `symt` if provided. `EPS_SYMBOL` is
+/// always parsed as `EPS_LABEL`.
+fn label<'a>(i: &'a str, symt: Option<&SymbolTable>) -> IResult<&'a str, Label> {
+    match symt {
+        None => num(i),
+        Some(symt) => map_res(
+            take_while1(|c: char| c != '\t' && c != '\n'),
+            |symbol: &str| match symt.get_label(symbol) {
+                Some(label) => Ok(label),
+                None if symbol == EPS_SYMBOL => Ok(EPS_LABEL),
+                None => Err(format_err!("Missing {} in SymbolTable", symbol)),
+            },
+        )(i),
+    }
+}
+
+fn transition<'a, W: SerializableSemiring>(
+    i: &'a str,
+    isymt: Option<&SymbolTable>,
+    osymt: Option<&SymbolTable>,
+) -> IResult<&'a str, RowParsed<W>> {
     let (i, state) = num(i)?;
     let (i, _) = tab(i)?;
     let (i, nextstate) = num(i)?;
     let (i, _) = tab(i)?;
-    let (i, ilabel) = num(i)?;
+    let (i, ilabel) = label(i, isymt)?;
     let (i, _) = tab(i)?;
-    let (i, olabel) = num(i)?;
+    let (i, olabel) = label(i, osymt)?;
     let (i, weight) = optional_weight(i)?;
 
     Ok((
@@ -49,10 +70,26 @@ fn infinity_final_state<W: SerializableSemiring>(i: &str) -> IResult<&str, RowPa
     Ok((i, RowParsed::InfinityFinalState(state)))
 }
 
-fn row_parsed<W: SerializableSemiring>(i: &str) -> IResult<&str, RowParsed<W>> {
-    alt((transition, infinity_final_state, final_state))(i)
+fn row_parsed<'a, W: SerializableSemiring>(
+    i: &'a str,
+    isymt: Option<&SymbolTable>,
+    osymt: Option<&SymbolTable>,
+) -> IResult<&'a str, RowParsed<W>> {
+    alt((
+        |i| transition(i, isymt, osymt),
+        infinity_final_state,
+        final_state,
+    ))(i)
 }
 
 pub fn vec_rows_parsed<W: SerializableSemiring>(i: &str) -> IResult<&str, Vec<RowParsed<W>>> {
-    separated_list0(tag("\n"), row_parsed)(i)
+    vec_rows_parsed_with_symt(i, None, None)
+}
+
+pub fn vec_rows_parsed_with_symt<'a, W: SerializableSemiring>(
+    i: &'a str,
+    isymt: Option<&SymbolTable>,
+    osymt: Option<&SymbolTable>,
+) -> IResult<&'a str, Vec<RowParsed<W>>> {
+    separated_list0(tag("\n"), |i| row_parsed(i, isymt, osymt))(i)
 }
@@ -3,9 +3,9 @@ use std::path::Path;
 
 use anyhow::Result;
 
-use crate::parsers::text_fst::nom_parser::vec_rows_parsed;
+use crate::parsers::text_fst::nom_parser::{vec_rows_parsed, vec_rows_parsed_with_symt};
 use crate::semirings::SerializableSemiring;
-use crate::{Label, StateId};
+use crate::{Label, StateId, SymbolTable};
 
 #[derive(Debug, PartialEq)]
 pub enum RowParsed<W: SerializableSemiring> {
@@ -91,6 +91,24 @@ impl<W: SerializableSemiring> ParsedTextFst<W> {
         Ok(Self::from_vec_rows_parsed(vec_rows_parsed))
     }
 
+    /// Same as `from_string` but the labels are symbols of the provided symbol tables instead
+    /// of integers. A tape without symbol table still uses integers. `EPS_SYMBOL` is parsed
+    /// as `EPS_LABEL` even if it isn't in the symbol table.
+    pub fn from_string_with_symt(
+        fst_string: &str,
+        isymt: Option<&SymbolTable>,
+        osymt: Option<&SymbolTable>,
+    ) -> Result<Self> {
+        let (rest, vec_rows_parsed) = vec_rows_parsed_with_symt(fst_string, isymt, osymt)
+            .map_err(|_| format_err!("Error while parsing text fst"))?;
+        // Unknown symbols stop the parsing.
+        if let Some(line) = rest.lines().find(|l| !l.trim().is_empty()) {
+            bail!("Error while parsing text fst, invalid line : {:?}", line);
+        }
+
+        Ok(Self::from_vec_rows_parsed(vec_rows_parsed))
+    }
+
     fn from_vec_rows_parsed(v: Vec<RowParsed<W>>) -> Self {
         let mut parsed_fst = Self {
             start_state: v.first().map(|v| match v {
@@ -1,25 +1,70 @@
-use nom::bytes::complete::tag;
-use nom::character::complete::space1;
-use nom::multi::many0;
-use nom::sequence::terminated;
+use anyhow::Result;
+use nom::bytes::complete::take_while1;
+use nom::character::complete::{space0, space1};
 use nom::IResult;
 
-use crate::parsers::nom_utils::{num, word};
+use crate::parsers::nom_utils::num;
 use crate::parsers::text_symt::parsed_text_symt::ParsedTextSymt;
 use crate::{Label, Symbol};
 
+fn symbol(i: &str) -> IResult<&str, Symbol> {
+    let (i, letters) = take_while1(|c: char| !c.is_whitespace())(i)?;
+    Ok((i, letters.to_string()))
+}
+
 fn row(i: &str) -> IResult<&str, (Symbol, Label)> {
-    let (i, symbol) = word(i)?;
+    let (i, _) = space0(i)?;
+    let (i, symbol) = symbol(i)?;
     let (i, _) = space1(i)?;
     let (i, label) = num(i)?;
+    let (i, _) = space0(i)?;
     Ok((i, (symbol, label)))
 }
 
-fn vec_rows(i: &str) -> IResult<&str, Vec<(Symbol, Label)>> {
-    many0(terminated(row, tag("\n")))(i)
+/// Parses the `symbol label` rows of a text symbol table, one per line. The columns can be
+/// separated by any number of spaces or tabs. Empty lines are skipped, as well as comment lines
+/// i.e. lines starting with `#` that are not a valid row : `#0 12` is the Kaldi disambiguation
+/// symbol `#0` and not a comment.
+pub(crate) fn parse_text_symt(i: &str) -> Result<ParsedTextSymt> {
+    let mut pairs = vec![];
+    for (idx_line, line) in i.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match row(line) {
+            Ok(("", pair)) => pairs.push(pair),
+            _ if line.trim_start().starts_with('#') => continue,
+            _ => bail!(
+                "Error while parsing text symt at line {} : {:?}",
+                idx_line + 1,
+                line
+            ),
+        }
+    }
+    Ok(ParsedTextSymt { pairs })
 }
 
-pub(crate) fn parse_text_symt(i: &str) -> IResult<&str, ParsedTextSymt> {
-    let (i, pairs) = vec_rows(i)?;
-    Ok((i, ParsedTextSymt { pairs }))
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_text_symt() -> Result<()> {
+        let text = "# Kaldi words.txt\r\n<eps>\t0\r\n  hello   1 \n\n#0 2\nworld\t \t3";
+        let parsed = parse_text_symt(text)?;
+        assert_eq!(
+            parsed.pairs,
+            vec![
+                ("<eps>".to_string(), 0),
+                ("hello".to_string(), 1),
+                ("#0".to_string(), 2),
+                ("world".to_string(), 3)
+            ]
+        );
+
+        assert!(parse_text_symt("a 0\nb\n").is_err());
+        assert!(parse_text_symt("a 0\nb 1 c\n").is_err());
+        assert!(parse_text_symt("a -1\n").is_err());
+        Ok(())
+    }
 }
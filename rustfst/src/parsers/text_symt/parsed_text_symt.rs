@@ -1,7 +1,7 @@
 use std::fs::read_to_string;
 use std::path::Path;
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 
 use crate::parsers::text_symt::nom_parser::parse_text_symt;
 use crate::{Label, Symbol};
@@ -13,13 +13,13 @@ pub(crate) struct ParsedTextSymt {
 
 impl ParsedTextSymt {
     pub(crate) fn from_string(symt_string: &str) -> Result<Self> {
-        let (_, parsed_symt) = parse_text_symt(symt_string)
-            .map_err(|_| format_err!("Error while parsing text symt"))?;
-        Ok(parsed_symt)
+        parse_text_symt(symt_string)
     }
 
     pub(crate) fn from_path<P: AsRef<Path>>(path_symt_text: P) -> Result<Self> {
-        let symt_string = read_to_string(path_symt_text)?;
+        let symt_string = read_to_string(path_symt_text.as_ref()).with_context(|| {
+            format!("Can't read text symt {}", path_symt_text.as_ref().display())
+        })?;
         Self::from_string(&symt_string)
     }
 }
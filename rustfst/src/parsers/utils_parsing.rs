@@ -1,3 +1,4 @@
+use nom::error::{ErrorKind, ParseError};
 use nom::number::complete::{le_f32, le_i32, le_i64, le_u32, le_u64, le_u8};
 use nom::IResult;
 
@@ -32,3 +33,25 @@ pub fn parse_bin_f32(i: &[u8]) -> IResult<&[u8], f32, NomCustomError<&[u8]>> {
 pub fn parse_bin_u8(i: &[u8]) -> IResult<&[u8], u8, NomCustomError<&[u8]>> {
     le_u8(i)
 }
+
+/// Parses a LEB128 variable length integer written by `write_bin_varint_u64`.
+pub(crate) fn parse_bin_varint_u64(i: &[u8]) -> IResult<&[u8], u64, NomCustomError<&[u8]>> {
+    let mut res = 0u64;
+    let mut shift = 0;
+    let mut input = i;
+    loop {
+        if shift > 63 {
+            return Err(nom::Err::Error(NomCustomError::from_error_kind(
+                i,
+                ErrorKind::Verify,
+            )));
+        }
+        let (rest, byte) = le_u8(input)?;
+        input = rest;
+        res |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Ok((input, res));
+        }
+        shift += 7;
+    }
+}
@@ -31,3 +31,23 @@ pub fn write_bin_f32<F: Write>(file: &mut F, i: f32) -> Result<()> {
 pub(crate) fn write_bin_u8<F: Write>(file: &mut F, i: u8) -> Result<()> {
     file.write_all(&i.to_le_bytes()).map_err(|e| e.into())
 }
+
+/// Writes `i` as a LEB128 variable length integer : 7 bits per byte, the high bit being set on
+/// all the bytes but the last one.
+#[inline]
+pub(crate) fn write_bin_varint_u64<F: Write>(file: &mut F, mut i: u64) -> Result<()> {
+    let mut buffer = [0u8; 10];
+    let mut n = 0;
+    loop {
+        let byte = (i & 0x7f) as u8;
+        i >>= 7;
+        if i == 0 {
+            buffer[n] = byte;
+            n += 1;
+            break;
+        }
+        buffer[n] = byte | 0x80;
+        n += 1;
+    }
+    file.write_all(&buffer[..n]).map_err(|e| e.into())
+}
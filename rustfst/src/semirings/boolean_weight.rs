@@ -103,6 +103,10 @@ mod tests {
         assert_eq!(b_true.times(&b_false)?, b_false);
         assert_eq!(b_false.times(&b_true)?, b_false);
         assert_eq!(b_false.times(&b_false)?, b_false);
+
+        // Test approx_equal, the delta is ignored
+        assert!(b_true.approx_equal(&b_true, 0.0));
+        assert!(!b_true.approx_equal(&b_false, 10.0));
         Ok(())
     }
 }
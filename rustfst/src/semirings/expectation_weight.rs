@@ -0,0 +1,295 @@
+use std::borrow::Borrow;
+use std::fmt;
+use std::fmt::Debug;
+use std::io::Write;
+
+use anyhow::Result;
+use nom::IResult;
+
+use crate::parsers::nom_utils::NomCustomError;
+#[cfg(test)]
+use crate::semirings::{LogWeight, SignedLogWeight};
+use crate::semirings::{
+    ReverseBack, Semiring, SemiringProperties, SerializableSemiring, WeightQuantize,
+};
+
+/// Expectation semiring: (W1 x W2, plus, times, (0, 0), (1, 0)) with
+/// - `(p1, v1) + (p2, v2) = (p1 + p2, v1 + v2)`
+/// - `(p1, v1) * (p2, v2) = (p1 * p2, p1 * v2 + v1 * p2)`
+///
+/// The first component is a probability and the second one accumulates `probability x value`.
+/// The shortest distance to the final states then gives the total probability along with the
+/// expected value, e.g. the expected feature counts. W2 must be a module over W1 : the
+/// product of `w1` and `w2` is `W2::from(w1) * w2`.
+///
+/// The zero is `(0, 0)`, a weight with a zero probability and a non-zero value is not zero.
+#[derive(Debug, Eq, PartialOrd, PartialEq, Clone, Default, Hash)]
+pub struct ExpectationWeight<W1, W2>
+where
+    W1: Semiring,
+    W2: Semiring,
+{
+    pub(crate) weight: (W1, W2),
+}
+
+impl<W1, W2> AsRef<Self> for ExpectationWeight<W1, W2>
+where
+    W1: Semiring<ReverseWeight = W1>,
+    W2: Semiring<ReverseWeight = W2> + From<W1>,
+{
+    fn as_ref(&self) -> &ExpectationWeight<W1, W2> {
+        self
+    }
+}
+
+impl<W1, W2> Semiring for ExpectationWeight<W1, W2>
+where
+    W1: Semiring<ReverseWeight = W1>,
+    W2: Semiring<ReverseWeight = W2> + From<W1>,
+{
+    type Type = (W1, W2);
+    type ReverseWeight = ExpectationWeight<W1, W2>;
+
+    fn zero() -> Self {
+        Self {
+            weight: (W1::zero(), W2::zero()),
+        }
+    }
+
+    fn one() -> Self {
+        Self {
+            weight: (W1::one(), W2::zero()),
+        }
+    }
+
+    fn new(weight: <Self as Semiring>::Type) -> Self {
+        Self { weight }
+    }
+
+    fn plus_assign<P: Borrow<Self>>(&mut self, rhs: P) -> Result<()> {
+        self.weight.0.plus_assign(&rhs.borrow().weight.0)?;
+        self.weight.1.plus_assign(&rhs.borrow().weight.1)?;
+        Ok(())
+    }
+
+    fn times_assign<P: Borrow<Self>>(&mut self, rhs: P) -> Result<()> {
+        let (p2, v2) = &rhs.borrow().weight;
+        let mut v = W2::from(self.weight.0.clone()).times(v2)?;
+        v.plus_assign(self.weight.1.times(W2::from(p2.clone()))?)?;
+        self.weight.0.times_assign(p2)?;
+        self.weight.1 = v;
+        Ok(())
+    }
+
+    fn approx_equal<P: Borrow<Self>>(&self, rhs: P, delta: f32) -> bool {
+        self.value1().approx_equal(rhs.borrow().value1(), delta)
+            && self.value2().approx_equal(rhs.borrow().value2(), delta)
+    }
+
+    fn value(&self) -> &<Self as Semiring>::Type {
+        &self.weight
+    }
+
+    fn take_value(self) -> <Self as Semiring>::Type {
+        self.weight
+    }
+
+    fn set_value(&mut self, value: <Self as Semiring>::Type) {
+        self.set_value1(value.0);
+        self.set_value2(value.1);
+    }
+
+    fn is_member(&self) -> bool {
+        self.value1().is_member() && self.value2().is_member()
+    }
+
+    fn reverse(&self) -> Result<Self::ReverseWeight> {
+        Ok((self.value1().reverse()?, self.value2().reverse()?).into())
+    }
+
+    fn properties() -> SemiringProperties {
+        W1::properties()
+            & W2::properties()
+            & (SemiringProperties::LEFT_SEMIRING
+                | SemiringProperties::RIGHT_SEMIRING
+                | SemiringProperties::COMMUTATIVE)
+    }
+}
+
+impl<W1, W2> ReverseBack<ExpectationWeight<W1, W2>> for ExpectationWeight<W1, W2>
+where
+    W1: Semiring<ReverseWeight = W1>,
+    W2: Semiring<ReverseWeight = W2> + From<W1>,
+{
+    fn reverse_back(&self) -> Result<ExpectationWeight<W1, W2>> {
+        Ok((self.value1().reverse()?, self.value2().reverse()?).into())
+    }
+}
+
+impl<W1, W2> ExpectationWeight<W1, W2>
+where
+    W1: Semiring,
+    W2: Semiring,
+{
+    pub fn value1(&self) -> &W1 {
+        &self.weight.0
+    }
+
+    pub fn value2(&self) -> &W2 {
+        &self.weight.1
+    }
+
+    pub fn set_value1(&mut self, new_weight: W1) {
+        self.weight.0 = new_weight;
+    }
+
+    pub fn set_value2(&mut self, new_weight: W2) {
+        self.weight.1 = new_weight;
+    }
+}
+
+impl<W1, W2> From<(W1, W2)> for ExpectationWeight<W1, W2>
+where
+    W1: Semiring,
+    W2: Semiring,
+{
+    fn from(t: (W1, W2)) -> Self {
+        Self { weight: t }
+    }
+}
+
+impl<W1, W2> WeightQuantize for ExpectationWeight<W1, W2>
+where
+    W1: WeightQuantize<ReverseWeight = W1>,
+    W2: WeightQuantize<ReverseWeight = W2> + From<W1>,
+{
+    fn quantize_assign(&mut self, delta: f32) -> Result<()> {
+        self.set_value1(self.value1().quantize(delta)?);
+        self.set_value2(self.value2().quantize(delta)?);
+        Ok(())
+    }
+}
+
+impl<W1, W2> fmt::Display for ExpectationWeight<W1, W2>
+where
+    W1: SerializableSemiring,
+    W2: SerializableSemiring,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{},{}", self.value1(), self.value2())?;
+        Ok(())
+    }
+}
+
+impl<W1, W2> SerializableSemiring for ExpectationWeight<W1, W2>
+where
+    W1: SerializableSemiring<ReverseWeight = W1>,
+    W2: SerializableSemiring<ReverseWeight = W2> + From<W1>,
+{
+    fn weight_type() -> String {
+        format!("expectation_{}_{}", W1::weight_type(), W2::weight_type())
+    }
+
+    fn parse_binary(i: &[u8]) -> IResult<&[u8], Self, NomCustomError<&[u8]>> {
+        let (i, weight_1) = W1::parse_binary(i)?;
+        let (i, weight_2) = W2::parse_binary(i)?;
+        Ok((i, Self::new((weight_1, weight_2))))
+    }
+
+    fn write_binary<F: Write>(&self, file: &mut F) -> Result<()> {
+        self.value1().write_binary(file)?;
+        self.value2().write_binary(file)?;
+        Ok(())
+    }
+
+    fn parse_text(i: &str) -> IResult<&str, Self> {
+        let (i, weight_1) = W1::parse_text(i)?;
+        let (i, _) = nom::bytes::complete::tag(",")(i)?;
+        let (i, weight_2) = W2::parse_text(i)?;
+        Ok((i, Self::new((weight_1, weight_2))))
+    }
+}
+
+test_semiring_serializable!(
+    tests_expectation_weight_serializable,
+    ExpectationWeight::<LogWeight, SignedLogWeight>,
+    ExpectationWeight::new((LogWeight::new(0.2), SignedLogWeight::new((false, 1.7))))
+    ExpectationWeight::zero()
+    ExpectationWeight::one()
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::shortest_distance;
+    use crate::fst_impls::VectorFst;
+    use crate::fst_traits::MutableFst;
+    use crate::{Tr, KDELTA};
+
+    type ExpWeight = ExpectationWeight<LogWeight, LogWeight>;
+
+    /// Weight of a transition with probability `p` and a feature fired `count` times.
+    fn exp(p: f32, count: f32) -> ExpWeight {
+        ExpectationWeight::new((LogWeight::new(-p.ln()), LogWeight::new(-(p * count).ln())))
+    }
+
+    fn real(w: &LogWeight) -> f32 {
+        (-w.value()).exp()
+    }
+
+    #[test]
+    fn test_expectation_weight() -> Result<()> {
+        let w1 = exp(0.5, 2.0);
+        let w2 = exp(0.25, 4.0);
+
+        // (0.5, 1.0) * (0.25, 1.0) = (0.125, 0.5 * 1.0 + 1.0 * 0.25)
+        let product = w1.times(&w2)?;
+        assert!((real(product.value1()) - 0.125).abs() < 1e-6);
+        assert!((real(product.value2()) - 0.75).abs() < 1e-6);
+
+        let sum = w1.plus(&w2)?;
+        assert!((real(sum.value1()) - 0.75).abs() < 1e-6);
+        assert!((real(sum.value2()) - 2.0).abs() < 1e-6);
+
+        assert_eq!(w1.times(ExpWeight::one())?, w1);
+        assert_eq!(ExpWeight::one().times(&w1)?, w1);
+        assert_eq!(w1.plus(ExpWeight::zero())?, w1);
+        assert!(w1.times(ExpWeight::zero())?.is_zero());
+        assert!(ExpWeight::zero().times(&w1)?.is_zero());
+
+        // A weight with a zero probability but a value is not zero.
+        let w = ExpWeight::new((LogWeight::zero(), LogWeight::one()));
+        assert!(!w.is_zero());
+        assert!(w.times(&w)?.is_zero());
+        Ok(())
+    }
+
+    #[test]
+    fn test_expectation_weight_shortest_distance() -> Result<()> {
+        // Two paths : 0.6 * 0.5 with the feature fired once on the first transition, and
+        // 0.4 with the feature fired twice.
+        let mut fst = VectorFst::<ExpWeight>::new();
+        fst.add_states(3);
+        fst.set_start(0)?;
+        fst.set_final(2, ExpWeight::one())?;
+        fst.add_tr(0, Tr::new(1, 1, exp(0.6, 1.0), 1))?;
+        fst.add_tr(1, Tr::new(2, 2, exp(0.5, 0.0), 2))?;
+        fst.add_tr(0, Tr::new(3, 3, exp(0.4, 2.0), 2))?;
+
+        let distance = shortest_distance(&fst, false)?;
+        let total = &distance[2];
+        assert!((real(total.value1()) - 0.7).abs() < 1e-6);
+        assert!((real(total.value2()) - (0.3 + 0.8)).abs() < 1e-6);
+
+        // With a signed second component, the values can be negative.
+        let w: ExpectationWeight<LogWeight, SignedLogWeight> = ExpectationWeight::new((
+            LogWeight::new(-(0.5f32).ln()),
+            SignedLogWeight::from_real(-1.0),
+        ));
+        let w2 = w.times(&w)?;
+        assert!(w2
+            .value2()
+            .approx_equal(SignedLogWeight::from_real(-1.0), KDELTA));
+        Ok(())
+    }
+}
@@ -133,6 +133,10 @@ macro_rules! gallic_weight {
                 self.0 = value;
             }
 
+            fn is_member(&self) -> bool {
+                self.0.is_member()
+            }
+
             fn reverse(&self) -> Result<Self::ReverseWeight> {
                 Ok(Self::ReverseWeight::new(self.0.reverse()?))
             }
@@ -387,6 +391,10 @@ impl<W: Semiring> Semiring for GallicWeight<W> {
         self.0.set_value(value)
     }
 
+    fn is_member(&self) -> bool {
+        self.0.is_member()
+    }
+
     fn reverse(&self) -> Result<Self::ReverseWeight> {
         Ok(GallicWeight(self.0.reverse()?))
     }
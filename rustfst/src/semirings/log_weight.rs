@@ -22,6 +22,7 @@ use crate::semirings::{
 use crate::KDELTA;
 
 /// Log semiring: (log(e^-x + e^-y), +, inf, 0).
+#[repr(transparent)]
 #[derive(Clone, Debug, PartialOrd, Default, Copy, Eq)]
 pub struct LogWeight {
     value: OrderedFloat<f32>,
@@ -95,6 +96,10 @@ impl Semiring for LogWeight {
         self.value.0 = value
     }
 
+    fn is_member(&self) -> bool {
+        !self.value.0.is_nan() && self.value.0 != f32::NEG_INFINITY
+    }
+
     fn reverse(&self) -> Result<Self::ReverseWeight> {
         Ok(*self)
     }
@@ -163,6 +168,14 @@ impl SerializableSemiring for LogWeight {
         let (i, f) = alt((map(tag_no_case("infinity"), |_| f32::INFINITY), float))(i)?;
         Ok((i, Self::new(f)))
     }
+
+    fn as_f32(&self) -> Option<f32> {
+        Some(*self.value())
+    }
+
+    fn from_f32(value: f32) -> Option<Self> {
+        Some(Self::new(value))
+    }
 }
 
 test_semiring_serializable!(
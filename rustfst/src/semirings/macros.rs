@@ -36,6 +36,17 @@ macro_rules! test_semiring_serializable {
 
                 Ok(())
             }
+
+            #[test]
+            fn test_serializable_f32() -> Result<()> {
+                for weight in &[ $( $weight ),* ] {
+                    if let Some(value) = weight.as_f32() {
+                        assert_eq!(<$semiring>::from_f32(value).as_ref(), Some(weight));
+                    }
+                }
+
+                Ok(())
+            }
         }
 
     };
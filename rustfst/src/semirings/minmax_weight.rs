@@ -0,0 +1,251 @@
+use std::borrow::Borrow;
+use std::f32;
+use std::hash::{Hash, Hasher};
+use std::io::Write;
+
+use anyhow::Result;
+use nom::branch::alt;
+use nom::bytes::complete::tag_no_case;
+use nom::combinator::map;
+use nom::number::complete::float;
+use nom::IResult;
+use ordered_float::OrderedFloat;
+
+use crate::parsers::nom_utils::NomCustomError;
+use crate::parsers::parse_bin_f32;
+use crate::parsers::write_bin_f32;
+use crate::semirings::semiring::SerializableSemiring;
+use crate::semirings::utils_float::float_approx_equal;
+use crate::semirings::{
+    CompleteSemiring, DivideType, ReverseBack, Semiring, SemiringProperties, StarSemiring,
+    WeaklyDivisibleSemiring, WeightQuantize,
+};
+use crate::KDELTA;
+
+/// MinMax semiring: (min, max, inf, -inf).
+///
+/// The weight of a path is the largest weight of its transitions and the shortest distance is
+/// the smallest of those bottlenecks, i.e. the minimax path. The widest path (max, min) is
+/// obtained by negating the weights.
+#[derive(Clone, Debug, PartialOrd, Default, Copy, Eq)]
+pub struct MinMaxWeight {
+    value: OrderedFloat<f32>,
+}
+
+impl Semiring for MinMaxWeight {
+    type Type = f32;
+    type ReverseWeight = MinMaxWeight;
+
+    fn zero() -> Self {
+        Self {
+            value: OrderedFloat(f32::INFINITY),
+        }
+    }
+
+    fn one() -> Self {
+        Self {
+            value: OrderedFloat(f32::NEG_INFINITY),
+        }
+    }
+
+    fn new(value: <Self as Semiring>::Type) -> Self {
+        MinMaxWeight {
+            value: OrderedFloat(value),
+        }
+    }
+
+    fn plus_assign<P: Borrow<Self>>(&mut self, rhs: P) -> Result<()> {
+        if rhs.borrow().value < self.value {
+            self.value = rhs.borrow().value;
+        }
+        Ok(())
+    }
+
+    fn times_assign<P: Borrow<Self>>(&mut self, rhs: P) -> Result<()> {
+        if rhs.borrow().value > self.value {
+            self.value = rhs.borrow().value;
+        }
+        Ok(())
+    }
+
+    fn approx_equal<P: Borrow<Self>>(&self, rhs: P, delta: f32) -> bool {
+        float_approx_equal(self.value.0, rhs.borrow().value.0, delta)
+    }
+
+    fn value(&self) -> &Self::Type {
+        &self.value.0
+    }
+
+    fn take_value(self) -> Self::Type {
+        self.value.0
+    }
+
+    fn set_value(&mut self, value: <Self as Semiring>::Type) {
+        self.value.0 = value
+    }
+
+    fn is_member(&self) -> bool {
+        !self.value.0.is_nan()
+    }
+
+    fn reverse(&self) -> Result<Self::ReverseWeight> {
+        Ok(*self)
+    }
+
+    fn properties() -> SemiringProperties {
+        SemiringProperties::LEFT_SEMIRING
+            | SemiringProperties::RIGHT_SEMIRING
+            | SemiringProperties::COMMUTATIVE
+            | SemiringProperties::PATH
+            | SemiringProperties::IDEMPOTENT
+    }
+}
+
+impl ReverseBack<MinMaxWeight> for MinMaxWeight {
+    fn reverse_back(&self) -> Result<MinMaxWeight> {
+        Ok(*self)
+    }
+}
+
+impl AsRef<MinMaxWeight> for MinMaxWeight {
+    fn as_ref(&self) -> &MinMaxWeight {
+        self
+    }
+}
+
+display_semiring!(MinMaxWeight);
+
+impl CompleteSemiring for MinMaxWeight {}
+
+impl StarSemiring for MinMaxWeight {
+    fn closure(&self) -> Self {
+        Self::one()
+    }
+}
+
+impl WeaklyDivisibleSemiring for MinMaxWeight {
+    /// `w1 / w2` is only defined when `w1 >= w2`, in which case any `w` verifying
+    /// `max(w, w2) = w1` is a valid quotient : `w1` is returned.
+    fn divide_assign(&mut self, rhs: &Self, _divide_type: DivideType) -> Result<()> {
+        if self.value < rhs.value {
+            bail!(
+                "MinMaxWeight : Division undefined for {} / {}",
+                self.value.0,
+                rhs.value.0
+            );
+        }
+        Ok(())
+    }
+}
+
+impl_quantize_f32!(MinMaxWeight);
+
+partial_eq_and_hash_f32!(MinMaxWeight);
+
+impl SerializableSemiring for MinMaxWeight {
+    fn weight_type() -> String {
+        "minmax".to_string()
+    }
+
+    fn parse_binary(i: &[u8]) -> IResult<&[u8], Self, NomCustomError<&[u8]>> {
+        let (i, weight) = parse_bin_f32(i)?;
+        Ok((i, Self::new(weight)))
+    }
+
+    fn write_binary<F: Write>(&self, file: &mut F) -> Result<()> {
+        write_bin_f32(file, *self.value())
+    }
+
+    fn parse_text(i: &str) -> IResult<&str, Self> {
+        // FIXME: nom 7 does not fully parse "infinity", see TropicalWeight::parse_text. It
+        // does not parse negative infinities either.
+        let (i, f) = alt((
+            map(tag_no_case("infinity"), |_| f32::INFINITY),
+            map(tag_no_case("-infinity"), |_| f32::NEG_INFINITY),
+            map(tag_no_case("-inf"), |_| f32::NEG_INFINITY),
+            float,
+        ))(i)?;
+        Ok((i, Self::new(f)))
+    }
+
+    fn as_f32(&self) -> Option<f32> {
+        Some(*self.value())
+    }
+
+    fn from_f32(value: f32) -> Option<Self> {
+        Some(Self::new(value))
+    }
+}
+
+test_semiring_serializable!(
+    tests_minmax_weight_serializable,
+    MinMaxWeight,
+    MinMaxWeight::one() MinMaxWeight::zero() MinMaxWeight::new(0.3) MinMaxWeight::new(0.5) MinMaxWeight::new(0.0) MinMaxWeight::new(-1.2)
+);
+
+impl From<f32> for MinMaxWeight {
+    fn from(f: f32) -> Self {
+        Self::new(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::{shortest_distance, shortest_path};
+    use crate::fst_impls::VectorFst;
+    use crate::fst_traits::{Fst, MutableFst};
+    use crate::Tr;
+
+    #[test]
+    fn test_minmax_weight() -> Result<()> {
+        let w1 = MinMaxWeight::new(1.0);
+        let w2 = MinMaxWeight::new(3.0);
+        assert_eq!(w1.plus(w2)?, w1);
+        assert_eq!(w1.times(w2)?, w2);
+
+        // Identities and annihilator.
+        assert_eq!(w1.plus(MinMaxWeight::zero())?, w1);
+        assert_eq!(MinMaxWeight::zero().plus(w1)?, w1);
+        assert_eq!(w1.times(MinMaxWeight::one())?, w1);
+        assert_eq!(MinMaxWeight::one().times(w1)?, w1);
+        assert!(w1.times(MinMaxWeight::zero())?.is_zero());
+        assert!(MinMaxWeight::zero().times(w1)?.is_zero());
+        assert!(MinMaxWeight::one().is_member());
+        assert!(MinMaxWeight::zero().is_member());
+
+        // Idempotency.
+        assert_eq!(w1.plus(w1)?, w1);
+        assert!(MinMaxWeight::properties().contains(SemiringProperties::IDEMPOTENT));
+        assert!(MinMaxWeight::properties().contains(SemiringProperties::PATH));
+
+        assert_eq!(w2.divide(&w1, DivideType::DivideAny)?, w2);
+        assert!(w1.divide(&w2, DivideType::DivideAny).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_minmax_weight_bottleneck() -> Result<()> {
+        // Path 1 has a bottleneck of 5, path 3-4-5 has a bottleneck of 4. The self-loop on the
+        // final state doesn't change the bottlenecks.
+        let mut fst = VectorFst::<MinMaxWeight>::new();
+        fst.add_states(4);
+        fst.set_start(0)?;
+        fst.set_final(3, MinMaxWeight::one())?;
+        fst.add_tr(0, Tr::new(1, 1, 5.0, 3))?;
+        fst.add_tr(3, Tr::new(2, 2, 1.0, 3))?;
+        fst.add_tr(0, Tr::new(3, 3, 2.0, 1))?;
+        fst.add_tr(1, Tr::new(4, 4, 4.0, 2))?;
+        fst.add_tr(2, Tr::new(5, 5, 3.0, 3))?;
+
+        let distance = shortest_distance(&fst, false)?;
+        assert_eq!(distance[3], MinMaxWeight::new(4.0));
+
+        let path: VectorFst<MinMaxWeight> = shortest_path(&fst)?;
+        let paths: Vec<_> = path.paths_iter().collect();
+        assert_eq!(paths.len(), 1);
+        assert_eq!(paths[0].ilabels, vec![3, 4, 5]);
+        assert_eq!(paths[0].weight, MinMaxWeight::new(4.0));
+        Ok(())
+    }
+}
@@ -1,194 +1,303 @@
-#![allow(unused)]
-
-use std::cmp::Ordering;
+use std::borrow::Borrow;
+use std::convert::TryInto;
 use std::fmt;
 use std::fmt::Debug;
-use std::hash::Hash;
-use std::hash::Hasher;
+use std::io::Write;
 
 use anyhow::Result;
+use nom::IResult;
 
-use generic_array::ArrayLength;
-use generic_array::GenericArray;
-
+use crate::parsers::nom_utils::NomCustomError;
 use crate::semirings::{
-    DivideType, Semiring, SemiringProperties, WeaklyDivisibleSemiring, WeightQuantize,
+    DivideType, ProductWeight, ReverseBack, Semiring, SemiringProperties, SerializableSemiring,
+    WeaklyDivisibleSemiring, WeightQuantize,
 };
+#[cfg(test)]
+use crate::semirings::{LogWeight, TropicalWeight};
 
-/// Cartesian power semiring: W ^ n.
-pub struct PowerWeight<W, N>
-where
-    W: Semiring,
-    N: ArrayLength<W>,
-{
-    weights: GenericArray<W, N>,
+/// Cartesian power semiring: W ^ N.
+///
+/// Plus and times are component-wise, e.g. to carry N scores through an algorithm at once.
+/// `PowerWeight<W, 2>` behaves like `ProductWeight<W, W>` and uses the same binary layout.
+#[derive(Debug, Eq, PartialOrd, PartialEq, Clone, Hash)]
+pub struct PowerWeight<W: Semiring, const N: usize> {
+    pub(crate) weights: [W; N],
 }
 
-impl<W, N> fmt::Display for PowerWeight<W, N>
-where
-    W: Semiring,
-    N: ArrayLength<W>,
-{
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        self.weights.as_slice().fmt(f)
+impl<W: Semiring, const N: usize> AsRef<Self> for PowerWeight<W, N> {
+    fn as_ref(&self) -> &PowerWeight<W, N> {
+        self
     }
 }
 
-impl<W, N> fmt::Debug for PowerWeight<W, N>
-where
-    W: Semiring,
-    N: ArrayLength<W>,
-{
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        self.weights.as_slice().fmt(f)
+impl<W: Semiring, const N: usize> Semiring for PowerWeight<W, N> {
+    type Type = [W; N];
+    type ReverseWeight = PowerWeight<W::ReverseWeight, N>;
+
+    fn zero() -> Self {
+        Self {
+            weights: to_array(vec![W::zero(); N]),
+        }
+    }
+
+    fn one() -> Self {
+        Self {
+            weights: to_array(vec![W::one(); N]),
+        }
+    }
+
+    fn new(weights: <Self as Semiring>::Type) -> Self {
+        Self { weights }
+    }
+
+    fn plus_assign<P: Borrow<Self>>(&mut self, rhs: P) -> Result<()> {
+        for (w, w_rhs) in self.weights.iter_mut().zip(rhs.borrow().weights.iter()) {
+            w.plus_assign(w_rhs)?;
+        }
+        Ok(())
+    }
+
+    fn times_assign<P: Borrow<Self>>(&mut self, rhs: P) -> Result<()> {
+        for (w, w_rhs) in self.weights.iter_mut().zip(rhs.borrow().weights.iter()) {
+            w.times_assign(w_rhs)?;
+        }
+        Ok(())
+    }
+
+    fn approx_equal<P: Borrow<Self>>(&self, rhs: P, delta: f32) -> bool {
+        self.weights
+            .iter()
+            .zip(rhs.borrow().weights.iter())
+            .all(|(w, w_rhs)| w.approx_equal(w_rhs, delta))
+    }
+
+    fn value(&self) -> &<Self as Semiring>::Type {
+        &self.weights
+    }
+
+    fn take_value(self) -> <Self as Semiring>::Type {
+        self.weights
+    }
+
+    fn set_value(&mut self, value: <Self as Semiring>::Type) {
+        self.weights = value;
+    }
+
+    fn is_member(&self) -> bool {
+        self.weights.iter().all(|w| w.is_member())
+    }
+
+    fn reverse(&self) -> Result<Self::ReverseWeight> {
+        let weights = self
+            .weights
+            .iter()
+            .map(|w| w.reverse())
+            .collect::<Result<Vec<_>>>()?;
+        Ok(PowerWeight::new(to_array(weights)))
+    }
+
+    fn properties() -> SemiringProperties {
+        W::properties()
+            & (SemiringProperties::LEFT_SEMIRING
+                | SemiringProperties::RIGHT_SEMIRING
+                | SemiringProperties::COMMUTATIVE
+                | SemiringProperties::IDEMPOTENT)
     }
 }
 
-impl<W, N> Hash for PowerWeight<W, N>
-where
-    W: Semiring,
-    N: ArrayLength<W>,
+impl<W: Semiring, const N: usize> ReverseBack<PowerWeight<W, N>>
+    for <PowerWeight<W, N> as Semiring>::ReverseWeight
 {
-    fn hash<H: Hasher>(&self, state: &mut H) {
-        self.weights.as_slice().hash(state);
+    fn reverse_back(&self) -> Result<PowerWeight<W, N>> {
+        let weights = self
+            .weights
+            .iter()
+            .map(|w| w.reverse_back())
+            .collect::<Result<Vec<_>>>()?;
+        Ok(PowerWeight::new(to_array(weights)))
     }
 }
 
-impl<W, N> Clone for PowerWeight<W, N>
-where
-    W: Semiring,
-    N: ArrayLength<W>,
-{
-    fn clone(&self) -> Self {
-        PowerWeight {
-            weights: self.weights.clone(),
-        }
+/// Converts a `Vec` built from the N components of a `PowerWeight` back into an array.
+fn to_array<W, const N: usize>(weights: Vec<W>) -> [W; N] {
+    match weights.try_into() {
+        Ok(weights) => weights,
+        Err(_) => unreachable!("PowerWeight : expected {} components", N),
     }
 }
 
-impl<W, N> PartialOrd for PowerWeight<W, N>
-where
-    W: Semiring,
-    N: ArrayLength<W>,
-{
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        self.weights.partial_cmp(&other.weights)
+impl<W: Semiring, const N: usize> PowerWeight<W, N> {
+    pub fn value_at(&self, i: usize) -> &W {
+        &self.weights[i]
+    }
+
+    pub fn set_value_at(&mut self, i: usize, new_weight: W) {
+        self.weights[i] = new_weight;
     }
 }
 
-impl<W, N> PartialEq for PowerWeight<W, N>
-where
-    W: Semiring,
-    N: ArrayLength<W>,
-{
-    fn eq(&self, other: &Self) -> bool {
-        self.weights.eq(&other.weights)
+impl<W: Semiring, const N: usize> From<[W; N]> for PowerWeight<W, N> {
+    fn from(t: [W; N]) -> Self {
+        Self::new(t)
     }
 }
 
-impl<W, N> AsRef<Self> for PowerWeight<W, N>
-where
-    W: Semiring,
-    N: ArrayLength<W>,
-{
-    fn as_ref(&self) -> &PowerWeight<W, N> {
-        self
+impl<W: Semiring> From<ProductWeight<W, W>> for PowerWeight<W, 2> {
+    fn from(w: ProductWeight<W, W>) -> Self {
+        let (w1, w2) = w.take_value();
+        Self::new([w1, w2])
     }
 }
 
-impl<W, N> Eq for PowerWeight<W, N>
-where
-    W: Semiring,
-    N: ArrayLength<W>,
-{
+impl<W: Semiring> From<PowerWeight<W, 2>> for ProductWeight<W, W> {
+    fn from(w: PowerWeight<W, 2>) -> Self {
+        let [w1, w2] = w.take_value();
+        Self::new((w1, w2))
+    }
+}
+
+impl<W: WeaklyDivisibleSemiring, const N: usize> WeaklyDivisibleSemiring for PowerWeight<W, N> {
+    fn divide_assign(&mut self, rhs: &Self, divide_type: DivideType) -> Result<()> {
+        for (w, w_rhs) in self.weights.iter_mut().zip(rhs.weights.iter()) {
+            w.divide_assign(w_rhs, divide_type)?;
+        }
+        Ok(())
+    }
+}
+
+impl<W: WeightQuantize, const N: usize> WeightQuantize for PowerWeight<W, N> {
+    fn quantize_assign(&mut self, delta: f32) -> Result<()> {
+        for w in self.weights.iter_mut() {
+            w.quantize_assign(delta)?;
+        }
+        Ok(())
+    }
+}
+
+impl<W: SerializableSemiring, const N: usize> fmt::Display for PowerWeight<W, N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, w) in self.weights.iter().enumerate() {
+            if i > 0 {
+                write!(f, ",")?;
+            }
+            write!(f, "{}", w)?;
+        }
+        Ok(())
+    }
+}
+
+impl<W: SerializableSemiring, const N: usize> SerializableSemiring for PowerWeight<W, N> {
+    fn weight_type() -> String {
+        format!("{}_^{}", W::weight_type(), N)
+    }
+
+    fn parse_binary(i: &[u8]) -> IResult<&[u8], Self, NomCustomError<&[u8]>> {
+        let mut i = i;
+        let mut weights = Vec::with_capacity(N);
+        for _ in 0..N {
+            let (i_next, w) = W::parse_binary(i)?;
+            weights.push(w);
+            i = i_next;
+        }
+        Ok((i, Self::new(to_array(weights))))
+    }
+
+    fn write_binary<F: Write>(&self, file: &mut F) -> Result<()> {
+        for w in self.weights.iter() {
+            w.write_binary(file)?;
+        }
+        Ok(())
+    }
+
+    fn parse_text(i: &str) -> IResult<&str, Self> {
+        let mut i = i;
+        let mut weights = Vec::with_capacity(N);
+        for idx in 0..N {
+            if idx > 0 {
+                let (i_next, _) = nom::bytes::complete::tag(",")(i)?;
+                i = i_next;
+            }
+            let (i_next, w) = W::parse_text(i)?;
+            weights.push(w);
+            i = i_next;
+        }
+        Ok((i, Self::new(to_array(weights))))
+    }
 }
 
-//impl<W, N> Semiring for PowerWeight<W, N>
-//where
-//    W: Semiring,
-//    N: ArrayLength<W>,
-//{
-//    type Type = GenericArray<W, N>;
-//    type ReverseSemiring<P> = PowerWeight<W::ReverseSemiring, P>;
-//
-//    fn zero() -> Self {
-//        Self {
-//            weights: GenericArray::clone_from_slice(&[W::zero()]),
-//        }
-//    }
-//
-//    fn one() -> Self {
-//        Self {
-//            weights: GenericArray::clone_from_slice(&[W::one()]),
-//        }
-//    }
-//
-//    fn new(value: <Self as Semiring>::Type) -> Self {
-//        Self { weights: value }
-//    }
-//
-//    fn plus_assign<P: AsRef<Self>>(&mut self, rhs: P) -> Result<()> {
-//        for i in 0..self.weights.len() {
-//            self.weights[i].plus_assign(&rhs.as_ref().weights[i])?;
-//        }
-//        Ok(())
-//    }
-//
-//    fn times_assign<P: AsRef<Self>>(&mut self, rhs: P) -> Result<()> {
-//        for i in 0..self.weights.len() {
-//            self.weights[i].times_assign(&rhs.as_ref().weights[i])?;
-//        }
-//        Ok(())
-//    }
-//
-//    fn value(&self) -> <Self as Semiring>::Type {
-//        self.weights.clone()
-//    }
-//
-//    fn set_value(&mut self, value: <Self as Semiring>::Type) {
-//        self.weights = value;
-//    }
-//
-//    fn reverse(&self) -> Self::ReverseSemiring {
-//        let mut rw = Vec::with_capacity(self.weights.len());
-//        for i in 0..self.weights.len() {
-//            rw.push(self.weights[i].reverse());
-//        }
-//        PowerWeight::new(GenericArray::clone_from(rw))
-//    }
-//
-//    fn properties() -> SemiringProperties {
-//        W::properties()
-//            & (SemiringProperties::LEFT_SEMIRING
-//                | SemiringProperties::RIGHT_SEMIRING
-//                | SemiringProperties::COMMUTATIVE
-//                | SemiringProperties::IDEMPOTENT)
-//    }
-//}
-//
-//impl<W, N> WeaklyDivisibleSemiring for PowerWeight<W, N>
-//where
-//    W: WeaklyDivisibleSemiring,
-//    N: ArrayLength<W>,
-//{
-//    fn divide(&self, rhs: &Self, divide_type: DivideType) -> Result<Self> {
-//        let mut mul = self.clone();
-//        for i in 0..self.weights.len() {
-//            mul.weights[i] = self.weights[i].divide(&rhs.weights[i], divide_type)?;
-//        }
-//        Ok(mul)
-//    }
-//}
-//
-//impl<W, N> WeightQuantize for PowerWeight<W, N>
-//where
-//    W: WeightQuantize,
-//    N: ArrayLength<W>,
-//{
-//    fn quantize_assign(&mut self, delta: f32) -> Result<()> {
-//        for i in 0..self.weights.len() {
-//            unsafe { self.weights.get_unchecked_mut(i).quantize_assign(delta)? };
-//        }
-//        Ok(())
-//    }
-//}
+test_semiring_serializable!(
+    tests_power_weight_serializable,
+    PowerWeight::<TropicalWeight, 3>,
+    PowerWeight::new([TropicalWeight::new(0.2), TropicalWeight::new(1.7), TropicalWeight::new(-0.3)])
+    PowerWeight::zero()
+    PowerWeight::one()
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_power_weight() -> Result<()> {
+        let w1 = PowerWeight::new([
+            LogWeight::new(0.5),
+            LogWeight::new(1.0),
+            LogWeight::new(2.0),
+        ]);
+        let w2 = PowerWeight::new([
+            LogWeight::new(1.0),
+            LogWeight::new(0.5),
+            LogWeight::new(3.0),
+        ]);
+        let product = w1.times(&w2)?;
+        let sum = w1.plus(&w2)?;
+        for i in 0..3 {
+            assert_eq!(product.value_at(i), &w1.value_at(i).times(w2.value_at(i))?);
+            assert_eq!(sum.value_at(i), &w1.value_at(i).plus(w2.value_at(i))?);
+        }
+        assert_eq!(w1.times(PowerWeight::one())?, w1);
+        assert_eq!(w1.plus(PowerWeight::zero())?, w1);
+        assert!(w1.times(PowerWeight::zero())?.is_zero());
+        assert!(product
+            .divide(&w2, DivideType::DivideAny)?
+            .approx_equal(&w1, 1e-5));
+        assert_eq!(
+            w1.quantize(0.5)?,
+            PowerWeight::new([
+                LogWeight::new(0.5),
+                LogWeight::new(1.0),
+                LogWeight::new(2.0)
+            ])
+        );
+        assert_eq!(
+            PowerWeight::<LogWeight, 3>::weight_type(),
+            "log_^3".to_string()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_power_weight_matches_product_weight() -> Result<()> {
+        let p1 = ProductWeight::new((TropicalWeight::new(0.2), TropicalWeight::new(1.7)));
+        let p2 = ProductWeight::new((TropicalWeight::new(1.3), TropicalWeight::new(0.4)));
+        let w1: PowerWeight<TropicalWeight, 2> = p1.clone().into();
+        let w2: PowerWeight<TropicalWeight, 2> = p2.clone().into();
+
+        let product: ProductWeight<_, _> = w1.times(&w2)?.into();
+        assert_eq!(product, p1.times(&p2)?);
+        let sum: ProductWeight<_, _> = w1.plus(&w2)?.into();
+        assert_eq!(sum, p1.plus(&p2)?);
+        assert_eq!(
+            PowerWeight::<TropicalWeight, 2>::properties(),
+            ProductWeight::<TropicalWeight, TropicalWeight>::properties()
+        );
+
+        // Same binary layout.
+        let mut power_serialization = vec![];
+        w1.write_binary(&mut power_serialization)?;
+        let mut product_serialization = vec![];
+        p1.write_binary(&mut product_serialization)?;
+        assert_eq!(power_serialization, product_serialization);
+        Ok(())
+    }
+}
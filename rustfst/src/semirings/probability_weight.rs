@@ -71,6 +71,10 @@ impl Semiring for ProbabilityWeight {
         self.value.0 = value
     }
 
+    fn is_member(&self) -> bool {
+        !self.value.0.is_nan()
+    }
+
     fn reverse(&self) -> Result<Self::ReverseWeight> {
         Ok(*self)
     }
@@ -116,6 +120,14 @@ impl SerializableSemiring for ProbabilityWeight {
         let (i, f) = float(i)?;
         Ok((i, Self::new(f)))
     }
+
+    fn as_f32(&self) -> Option<f32> {
+        Some(*self.value())
+    }
+
+    fn from_f32(value: f32) -> Option<Self> {
+        Some(Self::new(value))
+    }
 }
 
 impl StarSemiring for ProbabilityWeight {
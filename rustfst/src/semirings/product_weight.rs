@@ -88,6 +88,10 @@ where
         self.set_value2(value.1);
     }
 
+    fn is_member(&self) -> bool {
+        self.value1().is_member() && self.value2().is_member()
+    }
+
     fn reverse(&self) -> Result<Self::ReverseWeight> {
         Ok((self.value1().reverse()?, self.value2().reverse()?).into())
     }
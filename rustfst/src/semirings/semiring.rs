@@ -57,6 +57,11 @@ pub trait Semiring: Clone + PartialEq + PartialOrd + Debug + Hash + Eq + Sync +
     }
     fn times_assign<P: Borrow<Self>>(&mut self, rhs: P) -> Result<()>;
 
+    /// Compares two weights up to `delta`. Weights based on floats (e.g. `TropicalWeight`,
+    /// `LogWeight`) are equal if their values differ by at most `delta`, other weights
+    /// (e.g. `BooleanWeight`, `IntegerWeight`, string weights) ignore `delta` and compare
+    /// exactly. Composite weights compare each of their components. `KDELTA` is a sensible
+    /// default, which is also the one used by `==` for float weights.
     fn approx_equal<P: Borrow<Self>>(&self, rhs: P, delta: f32) -> bool;
 
     /// Borrow underneath value.
@@ -70,6 +75,11 @@ pub trait Semiring: Clone + PartialEq + PartialOrd + Debug + Hash + Eq + Sync +
     fn is_zero(&self) -> bool {
         *self == Self::zero()
     }
+    /// Returns false if the weight is not a valid element of the semiring
+    /// (e.g. a NaN float). `zero()` and `one()` are always members.
+    fn is_member(&self) -> bool {
+        true
+    }
     fn reverse(&self) -> Result<Self::ReverseWeight>;
     fn properties() -> SemiringProperties;
 }
@@ -186,4 +196,21 @@ pub trait SerializableSemiring: Semiring + Display {
         write!(file, "{}", self)?;
         Ok(())
     }
+
+    /// Returns the value of the weight as a `f32` if the semiring is real-valued,
+    /// `None` otherwise (e.g. for string, gallic or product weights).
+    fn as_f32(&self) -> Option<f32> {
+        None
+    }
+
+    /// Builds a weight from its `f32` value, the reverse of [`as_f32`](#method.as_f32).
+    ///
+    /// For the real-valued semirings (`TropicalWeight`, `LogWeight`, `ProbabilityWeight` and
+    /// `MinMaxWeight`), it never fails and `W::from_f32(w.as_f32().unwrap())` is `Some(w)`,
+    /// including for `zero()` (e.g. infinity for `TropicalWeight`). `None` is returned for
+    /// every value when the semiring is not real-valued, as it is the case by default
+    /// (e.g. string, gallic or product weights) : such weights can't be built from a `f32`.
+    fn from_f32(_value: f32) -> Option<Self> {
+        None
+    }
 }
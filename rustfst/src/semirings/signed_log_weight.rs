@@ -0,0 +1,335 @@
+use std::borrow::Borrow;
+use std::f32;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::io::Write;
+
+use anyhow::Result;
+use nom::branch::alt;
+use nom::bytes::complete::{tag, tag_no_case};
+use nom::combinator::map;
+use nom::number::complete::float;
+use nom::IResult;
+use ordered_float::OrderedFloat;
+
+use crate::parsers::nom_utils::NomCustomError;
+use crate::parsers::parse_bin_f32;
+use crate::parsers::write_bin_f32;
+use crate::semirings::utils_float::float_approx_equal;
+use crate::semirings::{
+    DivideType, LogWeight, ReverseBack, Semiring, SemiringProperties, SerializableSemiring,
+    WeaklyDivisibleSemiring, WeightQuantize,
+};
+use crate::KDELTA;
+
+/// Signed log semiring: the weight `(positive, v)` represents the real number `e^-v` if
+/// `positive` is true and `-e^-v` otherwise.
+///
+/// Unlike the `LogWeight`, plus can subtract probabilities which is needed to compute
+/// gradients or expectations. The additive identity is `(true, inf)` : the sign of zero is
+/// always positive, so that there is a single representation of zero. When a weight is added
+/// to its opposite, the magnitudes cancel out and the result is exactly zero. A partial
+/// cancellation, e.g. due to rounding errors, leaves a small but non-zero weight.
+#[derive(Clone, Debug, PartialOrd, Copy)]
+pub struct SignedLogWeight {
+    value: (bool, f32),
+}
+
+impl Default for SignedLogWeight {
+    fn default() -> Self {
+        Self::zero()
+    }
+}
+
+fn ln_pos_exp(x: f32) -> f32 {
+    ((-x).exp()).ln_1p()
+}
+
+fn ln_minus_exp(x: f32) -> f32 {
+    (-((-x).exp())).ln_1p()
+}
+
+impl SignedLogWeight {
+    /// Whether the real number represented by the weight is positive or zero.
+    pub fn is_positive(&self) -> bool {
+        self.value.0
+    }
+
+    /// Builds the weight representing the real number `x`.
+    pub fn from_real(x: f32) -> Self {
+        Self::new((x >= 0.0, -x.abs().ln()))
+    }
+
+    /// Returns the real number represented by the weight.
+    pub fn to_real(&self) -> f32 {
+        let magnitude = (-self.value.1).exp();
+        if self.value.0 {
+            magnitude
+        } else {
+            -magnitude
+        }
+    }
+}
+
+impl Semiring for SignedLogWeight {
+    type Type = (bool, f32);
+    type ReverseWeight = SignedLogWeight;
+
+    fn zero() -> Self {
+        Self {
+            value: (true, f32::INFINITY),
+        }
+    }
+
+    fn one() -> Self {
+        Self { value: (true, 0.0) }
+    }
+
+    fn new(value: <Self as Semiring>::Type) -> Self {
+        // Zero is always positive.
+        Self {
+            value: (value.0 || value.1 == f32::INFINITY, value.1),
+        }
+    }
+
+    fn plus_assign<P: Borrow<Self>>(&mut self, rhs: P) -> Result<()> {
+        let (s1, f1) = self.value;
+        let (s2, f2) = rhs.borrow().value;
+        if f2 == f32::INFINITY {
+            return Ok(());
+        }
+        if f1 == f32::INFINITY {
+            self.value = (s2, f2);
+            return Ok(());
+        }
+        // The sign of the result is the one of the largest magnitude, i.e. smallest value.
+        let (min, max, sign) = if f1 <= f2 { (f1, f2, s1) } else { (f2, f1, s2) };
+        *self = if s1 == s2 {
+            Self::new((sign, min - ln_pos_exp(max - min)))
+        } else if f1 == f2 {
+            Self::zero()
+        } else {
+            Self::new((sign, min - ln_minus_exp(max - min)))
+        };
+        Ok(())
+    }
+
+    fn times_assign<P: Borrow<Self>>(&mut self, rhs: P) -> Result<()> {
+        let (s1, f1) = self.value;
+        let (s2, f2) = rhs.borrow().value;
+        *self = if f1 == f32::INFINITY || f2 == f32::INFINITY {
+            Self::zero()
+        } else {
+            Self::new((s1 == s2, f1 + f2))
+        };
+        Ok(())
+    }
+
+    fn approx_equal<P: Borrow<Self>>(&self, rhs: P, delta: f32) -> bool {
+        let rhs = rhs.borrow();
+        self.value.0 == rhs.value.0 && float_approx_equal(self.value.1, rhs.value.1, delta)
+    }
+
+    fn value(&self) -> &Self::Type {
+        &self.value
+    }
+
+    fn take_value(self) -> Self::Type {
+        self.value
+    }
+
+    fn set_value(&mut self, value: <Self as Semiring>::Type) {
+        *self = Self::new(value)
+    }
+
+    fn is_member(&self) -> bool {
+        !self.value.1.is_nan() && self.value.1 != f32::NEG_INFINITY
+    }
+
+    fn reverse(&self) -> Result<Self::ReverseWeight> {
+        Ok(*self)
+    }
+
+    fn properties() -> SemiringProperties {
+        SemiringProperties::LEFT_SEMIRING
+            | SemiringProperties::RIGHT_SEMIRING
+            | SemiringProperties::COMMUTATIVE
+    }
+}
+
+impl ReverseBack<SignedLogWeight> for SignedLogWeight {
+    fn reverse_back(&self) -> Result<SignedLogWeight> {
+        Ok(*self)
+    }
+}
+
+impl AsRef<SignedLogWeight> for SignedLogWeight {
+    fn as_ref(&self) -> &SignedLogWeight {
+        self
+    }
+}
+
+impl From<LogWeight> for SignedLogWeight {
+    fn from(w: LogWeight) -> Self {
+        Self::new((true, *w.value()))
+    }
+}
+
+impl PartialEq for SignedLogWeight {
+    fn eq(&self, other: &Self) -> bool {
+        let (s1, w1) = self.value;
+        let (s2, w2) = other.value;
+        s1 == s2 && w1 <= (w2 + KDELTA) && w2 <= (w1 + KDELTA)
+    }
+}
+
+impl Eq for SignedLogWeight {}
+
+impl Hash for SignedLogWeight {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.value.0.hash(state);
+        OrderedFloat(self.value.1).hash(state);
+    }
+}
+
+impl WeaklyDivisibleSemiring for SignedLogWeight {
+    fn divide_assign(&mut self, rhs: &Self, _divide_type: DivideType) -> Result<()> {
+        let (s1, f1) = self.value;
+        let (s2, f2) = rhs.value;
+        if f2 == f32::INFINITY {
+            bail!("SignedLogWeight : Division by zero");
+        }
+        *self = if f1 == f32::INFINITY {
+            Self::zero()
+        } else {
+            Self::new((s1 == s2, f1 - f2))
+        };
+        Ok(())
+    }
+}
+
+impl WeightQuantize for SignedLogWeight {
+    fn quantize_assign(&mut self, delta: f32) -> Result<()> {
+        let (s, v) = self.value;
+        if v == f32::INFINITY || v == f32::NEG_INFINITY {
+            return Ok(());
+        }
+        self.set_value((s, ((v / delta) + 0.5).floor() * delta));
+        Ok(())
+    }
+}
+
+impl fmt::Display for SignedLogWeight {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let sign = if self.value.0 { 1 } else { -1 };
+        write!(f, "{},{}", sign, self.value.1)?;
+        Ok(())
+    }
+}
+
+impl SerializableSemiring for SignedLogWeight {
+    fn weight_type() -> String {
+        "signed_log".to_string()
+    }
+
+    fn parse_binary(i: &[u8]) -> IResult<&[u8], Self, NomCustomError<&[u8]>> {
+        let (i, sign) = parse_bin_f32(i)?;
+        let (i, weight) = parse_bin_f32(i)?;
+        Ok((i, Self::new((sign > 0.0, weight))))
+    }
+
+    fn write_binary<F: Write>(&self, file: &mut F) -> Result<()> {
+        write_bin_f32(file, if self.value.0 { 1.0 } else { -1.0 })?;
+        write_bin_f32(file, self.value.1)
+    }
+
+    fn parse_text(i: &str) -> IResult<&str, Self> {
+        let (i, sign) = float(i)?;
+        let (i, _) = tag(",")(i)?;
+        // FIXME: nom 7 does not fully parse "infinity", see LogWeight::parse_text.
+        let (i, f) = alt((map(tag_no_case("infinity"), |_| f32::INFINITY), float))(i)?;
+        Ok((i, Self::new((sign > 0.0, f))))
+    }
+}
+
+test_semiring_serializable!(
+    tests_signed_log_weight_serializable,
+    SignedLogWeight,
+    SignedLogWeight::new((true, 0.3)) SignedLogWeight::new((false, 0.5)) SignedLogWeight::one()
+    SignedLogWeight::new((false, -1.2)) SignedLogWeight::zero()
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::shortest_distance;
+    use crate::fst_impls::VectorFst;
+    use crate::fst_traits::MutableFst;
+    use crate::Tr;
+
+    #[test]
+    fn test_signed_log_weight() -> Result<()> {
+        let w1 = SignedLogWeight::from_real(0.75);
+        let w2 = SignedLogWeight::from_real(-0.25);
+        assert!(w1
+            .plus(w2)?
+            .approx_equal(SignedLogWeight::from_real(0.5), KDELTA));
+        assert!(w2
+            .plus(w2)?
+            .approx_equal(SignedLogWeight::from_real(-0.5), KDELTA));
+        assert!(w1
+            .times(w2)?
+            .approx_equal(SignedLogWeight::from_real(-0.1875), KDELTA));
+        assert!(w2
+            .times(w2)?
+            .approx_equal(SignedLogWeight::from_real(0.0625), KDELTA));
+        assert!(w1
+            .divide(&w2, DivideType::DivideAny)?
+            .approx_equal(SignedLogWeight::from_real(-3.0), KDELTA));
+        assert!((w1.plus(w2)?.to_real() - 0.5).abs() < 1e-6);
+        assert!(w1
+            .divide(&SignedLogWeight::zero(), DivideType::DivideAny)
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_signed_log_weight_zero() -> Result<()> {
+        let zero = SignedLogWeight::zero();
+        // Zero has a single representation, whatever the sign.
+        assert_eq!(SignedLogWeight::new((false, f32::INFINITY)), zero);
+        assert_eq!(SignedLogWeight::from_real(-0.0), zero);
+        assert!(zero.is_positive());
+
+        // Exact cancellation gives zero.
+        let w = SignedLogWeight::from_real(0.3);
+        let minus_w = SignedLogWeight::from_real(-0.3);
+        let sum = w.plus(minus_w)?;
+        assert!(sum.is_zero());
+        assert!(sum.is_positive());
+        assert_eq!(minus_w.plus(w)?, zero);
+
+        // Zero is the identity of plus and absorbing for times.
+        assert_eq!(minus_w.plus(zero)?, minus_w);
+        assert_eq!(zero.plus(minus_w)?, minus_w);
+        assert_eq!(minus_w.times(zero)?, zero);
+        assert!(minus_w.times(zero)?.is_positive());
+        Ok(())
+    }
+
+    #[test]
+    fn test_signed_log_weight_shortest_distance() -> Result<()> {
+        // Two paths from 0 to 2 with weights 0.5 * 0.5 and -0.5.
+        let mut fst = VectorFst::<SignedLogWeight>::new();
+        fst.add_states(3);
+        fst.set_start(0)?;
+        fst.set_final(2, SignedLogWeight::one())?;
+        fst.add_tr(0, Tr::new(1, 1, SignedLogWeight::from_real(0.5), 1))?;
+        fst.add_tr(1, Tr::new(2, 2, SignedLogWeight::from_real(0.5), 2))?;
+        fst.add_tr(0, Tr::new(3, 3, SignedLogWeight::from_real(-0.5), 2))?;
+
+        let distance = shortest_distance(&fst, false)?;
+        assert!((distance[2].to_real() + 0.25).abs() < 1e-6);
+        Ok(())
+    }
+}
@@ -16,7 +16,7 @@ use crate::semirings::{
     DivideType, ReverseBack, Semiring, SemiringProperties, SerializableSemiring,
     WeaklyDivisibleSemiring, WeightQuantize,
 };
-use crate::Label;
+use crate::{Label, EPS_LABEL};
 
 /// String semiring: (identity, ., Infinity, Epsilon)
 #[derive(Clone, Debug, PartialOrd, PartialEq, Eq, Hash)]
@@ -155,6 +155,13 @@ macro_rules! string_semiring {
                 self.value = value;
             }
 
+            fn is_member(&self) -> bool {
+                match &self.value {
+                    StringWeightVariant::Infinity => true,
+                    StringWeightVariant::Labels(l) => !l.contains(&EPS_LABEL),
+                }
+            }
+
             fn reverse(&self) -> Result<Self::ReverseWeight> {
                 Ok(self.value().reverse().into())
             }
@@ -393,3 +400,64 @@ test_semiring_serializable!(
     StringWeightRestrict::new(StringWeightVariant::Labels(vec![1]))
     StringWeightRestrict::new(StringWeightVariant::Labels(vec![4, 5, 2]))
 );
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_string_weight_left() -> Result<()> {
+        let zero = StringWeightLeft::zero();
+        let one = StringWeightLeft::one();
+        let w1 = StringWeightLeft::from(vec![1, 2, 3]);
+        let w2 = StringWeightLeft::from(vec![1, 2, 4]);
+
+        // Test plus
+        assert_eq!(w1.plus(&w2)?, StringWeightLeft::from(vec![1, 2]));
+        assert_eq!(w1.plus(&one)?, one);
+        assert_eq!(one.plus(&w1)?, one);
+        assert_eq!(w1.plus(&zero)?, w1);
+        assert_eq!(zero.plus(&w1)?, w1);
+
+        // Test times
+        assert_eq!(
+            w1.times(&w2)?,
+            StringWeightLeft::from(vec![1, 2, 3, 1, 2, 4])
+        );
+        assert_eq!(w1.times(&one)?, w1);
+        assert_eq!(one.times(&w1)?, w1);
+        assert_eq!(w1.times(&zero)?, zero);
+        assert_eq!(zero.times(&w1)?, zero);
+        assert_eq!(zero.times(&one)?, zero);
+
+        // Test divide
+        assert_eq!(
+            w1.divide(&StringWeightLeft::from(vec![1]), DivideType::DivideLeft)?,
+            StringWeightLeft::from(vec![2, 3])
+        );
+
+        // Test approx_equal, the delta is ignored
+        assert!(w1.approx_equal(&StringWeightLeft::from(vec![1, 2, 3]), 0.0));
+        assert!(!w1.approx_equal(&w2, 10.0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_string_weight_right() -> Result<()> {
+        let zero = StringWeightRight::zero();
+        let one = StringWeightRight::one();
+        let w1 = StringWeightRight::from(vec![3, 2, 1]);
+        let w2 = StringWeightRight::from(vec![4, 2, 1]);
+
+        assert_eq!(w1.plus(&w2)?, StringWeightRight::from(vec![2, 1]));
+        assert_eq!(w1.plus(&one)?, one);
+        assert_eq!(w1.plus(&zero)?, w1);
+        assert_eq!(w1.times(&zero)?, zero);
+        assert_eq!(zero.times(&w1)?, zero);
+        assert_eq!(
+            w1.divide(&StringWeightRight::from(vec![1]), DivideType::DivideRight)?,
+            StringWeightRight::from(vec![3, 2])
+        );
+        Ok(())
+    }
+}
@@ -23,6 +23,7 @@ use crate::semirings::{
 use crate::KDELTA;
 
 /// Tropical semiring: (min, +, inf, 0).
+#[repr(transparent)]
 #[derive(Clone, Debug, PartialOrd, Default, Copy, Eq)]
 pub struct TropicalWeight {
     value: OrderedFloat<f32>,
@@ -85,6 +86,10 @@ impl Semiring for TropicalWeight {
         self.value.0 = value
     }
 
+    fn is_member(&self) -> bool {
+        !self.value.0.is_nan() && self.value.0 != f32::NEG_INFINITY
+    }
+
     fn reverse(&self) -> Result<Self::ReverseWeight> {
         Ok(*self)
     }
@@ -155,6 +160,14 @@ impl SerializableSemiring for TropicalWeight {
         let (i, f) = alt((map(tag_no_case("infinity"), |_| f32::INFINITY), float))(i)?;
         Ok((i, Self::new(f)))
     }
+
+    fn as_f32(&self) -> Option<f32> {
+        Some(*self.value())
+    }
+
+    fn from_f32(value: f32) -> Option<Self> {
+        Some(Self::new(value))
+    }
 }
 
 test_semiring_serializable!(
@@ -168,3 +181,20 @@ impl From<f32> for TropicalWeight {
         Self::new(f)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tropical_weight_approx_equal() {
+        let w = TropicalWeight::new(1.0);
+        let w_ulp = TropicalWeight::new(f32::from_bits(1.0f32.to_bits() + 1));
+        assert!(w.approx_equal(&w_ulp, KDELTA));
+        assert!(!w.approx_equal(&w_ulp, 0.0));
+        assert!(w.approx_equal(TropicalWeight::new(1.5), 0.5));
+        assert!(!w.approx_equal(TropicalWeight::new(1.5), KDELTA));
+        assert!(TropicalWeight::zero().approx_equal(TropicalWeight::zero(), KDELTA));
+        assert!(!TropicalWeight::zero().approx_equal(TropicalWeight::new(f32::MAX), KDELTA));
+    }
+}
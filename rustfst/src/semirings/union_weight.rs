@@ -154,6 +154,12 @@ impl<W: Semiring, O: UnionWeightOption<W>> Semiring for UnionWeight<W, O> {
         self.list = value;
     }
 
+    fn is_member(&self) -> bool {
+        // The list must be made of members, sorted and without duplicates.
+        self.list.iter().all(|w| w.is_member())
+            && self.list.windows(2).all(|w| O::compare(&w[0], &w[1]))
+    }
+
     fn reverse(&self) -> Result<Self::ReverseWeight> {
         let mut rw = Self::ReverseWeight::zero();
         for v in self.iter() {
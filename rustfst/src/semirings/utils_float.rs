@@ -1,3 +1,4 @@
 pub(crate) fn float_approx_equal(w1: f32, w2: f32, delta: f32) -> bool {
-    (w1 - w2).abs() <= delta
+    // Infinities are only equal to themselves, their difference would be NaN.
+    w1 == w2 || (w1 - w2).abs() <= delta
 }
@@ -15,9 +15,21 @@ use std::collections::HashMap;
 use std::hash::BuildHasher;
 
 /// A symbol table stores a bidirectional mapping between transition labels and "symbols" (strings).
+///
+/// The labels are usually contiguous, starting from 0, but arbitrary labels can be assigned
+/// with [`add_symbol_with_key`](#method.add_symbol_with_key).
 #[derive(Debug, Clone)]
 pub struct SymbolTable<H: BuildHasher = RandomState> {
+    /// Mapping between the symbols and their index, i.e. their order of insertion.
     bimap: BiHashMapString<H>,
+    /// The label of a symbol whose index is lower than this limit is its index.
+    dense_key_limit: usize,
+    /// Labels of the symbols whose index is greater than or equal to `dense_key_limit`.
+    idx_key: Vec<Label>,
+    /// Index of the symbols whose index is greater than or equal to `dense_key_limit`.
+    key_idx: HashMap<Label, usize>,
+    /// Label used for the next symbol added with `add_symbol`.
+    available_key: Label,
 }
 
 impl Default for SymbolTable {
@@ -43,21 +55,18 @@ impl SymbolTable {
     }
 
     pub fn empty() -> Self {
-        SymbolTable {
-            bimap: BiHashMapString::new(),
-        }
+        Self::from_bimap(BiHashMapString::new())
     }
 
     fn from_parsed_symt_text(parsed_symt_text: ParsedTextSymt) -> Result<Self> {
-        let mut bimap = BiHashMapString::new();
-        for (symbol, label) in parsed_symt_text.pairs.into_iter() {
-            let inserted_label = bimap.get_id_or_insert(symbol);
-            if inserted_label as Label != label {
-                bail!("The SymbolTable should contain labels with increasing ids and no hole. Expected {} and got {}", inserted_label, label)
-            }
+        let mut symt = SymbolTable::empty();
+        let mut pairs = parsed_symt_text.pairs;
+        pairs.sort_by_key(|(_, label)| *label);
+        for (symbol, label) in pairs.into_iter() {
+            symt.add_symbol_with_key(symbol, label)?;
         }
 
-        Ok(SymbolTable { bimap })
+        Ok(symt)
     }
 
     pub fn from_text_string(symt_string: &str) -> Result<Self> {
@@ -65,6 +74,10 @@ impl SymbolTable {
         Self::from_parsed_symt_text(parsed_symt)
     }
 
+    /// Reads a symbol table in the text format used by OpenFst and Kaldi : one `symbol label`
+    /// pair per line, separated by spaces or tabs. Empty lines and comment lines starting with
+    /// `#` are skipped. The rows can be in any order but the labels must be contiguous
+    /// starting from 0.
     pub fn read_text<P: AsRef<Path>>(path_text_symt: P) -> Result<Self> {
         let parsed_symt = ParsedTextSymt::from_path(path_text_symt)?;
         Self::from_parsed_symt_text(parsed_symt)
@@ -95,9 +108,48 @@ impl SymbolTable {
 
 impl<H: BuildHasher> SymbolTable<H> {
     pub fn with_hasher(hasher_builder: H) -> Self {
-        let mut bimap = BiHashMapString::with_hasher(hasher_builder);
-        bimap.get_id_or_insert(EPS_SYMBOL);
-        Self { bimap }
+        let mut symt = Self::from_bimap(BiHashMapString::with_hasher(hasher_builder));
+        symt.add_symbol(EPS_SYMBOL);
+        symt
+    }
+
+    fn from_bimap(bimap: BiHashMapString<H>) -> Self {
+        Self {
+            bimap,
+            dense_key_limit: 0,
+            idx_key: vec![],
+            key_idx: HashMap::new(),
+            available_key: 0,
+        }
+    }
+
+    fn index_label(&self, idx: usize) -> Label {
+        if idx < self.dense_key_limit {
+            idx as Label
+        } else {
+            self.idx_key[idx - self.dense_key_limit]
+        }
+    }
+
+    fn label_index(&self, label: Label) -> Option<usize> {
+        if (label as usize) < self.dense_key_limit {
+            Some(label as usize)
+        } else {
+            self.key_idx.get(&label).cloned()
+        }
+    }
+
+    /// Inserts a symbol which is not in the table yet with a label which is not used.
+    fn insert(&mut self, sym: String, key: Label) {
+        let idx = self.bimap.get_id_or_insert(sym);
+        // The labels stay dense as long as they are equal to the indices.
+        if self.idx_key.is_empty() && idx == key as usize {
+            self.dense_key_limit = idx + 1;
+        } else {
+            self.idx_key.push(key);
+            self.key_idx.insert(key, idx);
+        }
+        self.available_key = self.available_key.max(key.saturating_add(1));
     }
 
     pub fn is_empty(&self) -> bool {
@@ -123,7 +175,93 @@ impl<H: BuildHasher> SymbolTable<H> {
     /// # }
     /// ```
     pub fn add_symbol(&mut self, sym: impl Into<String>) -> Label {
-        self.bimap.get_id_or_insert(sym.into()) as Label
+        let sym = sym.into();
+        if let Some(label) = self.get_label(&sym) {
+            return label;
+        }
+        let label = self.available_key;
+        self.insert(sym, label);
+        label
+    }
+
+    /// Adds a symbol to the symbol table and returns its label. Unlike `add_symbol`, an error
+    /// is returned if the symbol is already present in the table.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # #[macro_use] extern crate rustfst; fn main() {
+    /// # use rustfst::SymbolTable;
+    /// let mut symt = symt!["a", "b"];
+    /// assert_eq!(symt.add_symbol_checked("c").unwrap(), 3);
+    /// assert!(symt.add_symbol_checked("a").is_err());
+    /// # }
+    /// ```
+    pub fn add_symbol_checked(&mut self, sym: impl Into<String>) -> Result<Label> {
+        let sym = sym.into();
+        if let Some(label) = self.get_label(&sym) {
+            bail!(
+                "Symbol {} is already in the SymbolTable with label {}",
+                sym,
+                label
+            );
+        }
+        Ok(self.add_symbol(sym))
+    }
+
+    /// Adds a symbol to the symbol table with a given label, e.g. to match an externally
+    /// specified assignment of the labels. The label can leave gaps : the symbols added
+    /// afterwards with `add_symbol` get labels greater than all the labels of the table. An
+    /// error is returned if the label is already used or if the symbol is already present.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # #[macro_use] extern crate rustfst; fn main() {
+    /// # use rustfst::SymbolTable;
+    /// let mut symt = symt!["a", "b"];
+    /// symt.add_symbol_with_key("c", 10).unwrap();
+    /// assert_eq!(symt.get_label("c"), Some(10));
+    /// assert_eq!(symt.get_symbol(3), None);
+    /// assert_eq!(symt.add_symbol("d"), 11);
+    ///
+    /// // Collision.
+    /// assert!(symt.add_symbol_with_key("e", 2).is_err());
+    /// # }
+    /// ```
+    pub fn add_symbol_with_key(&mut self, sym: impl Into<String>, key: Label) -> Result<()> {
+        let sym = sym.into();
+        if let Some(existing) = self.get_symbol(key) {
+            bail!(
+                "Label {} is already used by the symbol {} in the SymbolTable",
+                key,
+                existing
+            );
+        }
+        if let Some(label) = self.get_label(&sym) {
+            bail!(
+                "Symbol {} is already in the SymbolTable with label {}",
+                sym,
+                label
+            );
+        }
+        self.insert(sym, key);
+        Ok(())
+    }
+
+    /// Returns the label given to the next symbol added with `add_symbol`, i.e. one more than
+    /// the greatest label of the table.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # #[macro_use] extern crate rustfst; fn main() {
+    /// # use rustfst::SymbolTable;
+    /// let mut symt = symt!["a", "b"];
+    /// assert_eq!(symt.available_key(), 3);
+    /// symt.add_symbol_with_key("c", 10).unwrap();
+    /// assert_eq!(symt.available_key(), 11);
+    /// # }
+    /// ```
+    pub fn available_key(&self) -> Label {
+        self.available_key
     }
 
     pub fn add_symbols<S: Into<String>, P: IntoIterator<Item = S>>(&mut self, symbols: P) {
@@ -160,7 +298,7 @@ impl<H: BuildHasher> SymbolTable<H> {
     /// # }
     /// ```
     pub fn get_label(&self, sym: impl AsRef<str>) -> Option<Label> {
-        self.bimap.get_id(sym).map(|it| it as Label)
+        self.bimap.get_id(sym).map(|idx| self.index_label(idx))
     }
 
     /// Given a label, returns the symbol corresponding.
@@ -177,7 +315,8 @@ impl<H: BuildHasher> SymbolTable<H> {
     /// # }
     /// ```
     pub fn get_symbol(&self, label: Label) -> Option<&str> {
-        self.bimap.get_string(label as usize)
+        self.label_index(label)
+            .and_then(|idx| self.bimap.get_string(idx))
     }
 
     /// Given a symbol, returns whether it is present in the table.
@@ -205,6 +344,7 @@ impl<H: BuildHasher> SymbolTable<H> {
     /// assert!(symt.contains_label(label));
     /// assert!(!symt.contains_label(label+1));
     /// # }
+    /// ```
     pub fn contains_label(&self, label: Label) -> bool {
         self.get_symbol(label).is_some()
     }
@@ -227,8 +367,8 @@ impl<H: BuildHasher> SymbolTable<H> {
     ///
     /// # }
     /// ```
-    pub fn labels(&self) -> impl Iterator<Item = Label> {
-        self.bimap.iter_ids().map(|it| it as Label)
+    pub fn labels(&self) -> impl Iterator<Item = Label> + '_ {
+        self.bimap.iter_ids().map(move |idx| self.index_label(idx))
     }
 
     /// An iterator on all the symbols stored in the `SymbolTable`.
@@ -250,10 +390,51 @@ impl<H: BuildHasher> SymbolTable<H> {
         self.bimap.iter_strings()
     }
 
-    /// An iterator on all the labels stored in the `SymbolTable`.
-    /// The iterator element is `(&'a Label, &'a Symbol)`.
+    /// An iterator on all the (label, symbol) pairs stored in the `SymbolTable`, in the order
+    /// the symbols were added. This is the increasing order of the labels unless
+    /// `add_symbol_with_key` was used with a label lower than some labels of the table.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # #[macro_use] extern crate rustfst; fn main() {
+    /// # use rustfst::SymbolTable;
+    /// let symt = symt!["a", "b"];
+    /// let pairs: Vec<_> = symt.iter().collect();
+    /// assert_eq!(pairs, vec![(0, "<eps>"), (1, "a"), (2, "b")]);
+    /// # }
+    /// ```
     pub fn iter(&self) -> impl Iterator<Item = (Label, &str)> {
-        self.bimap.iter().map(|(label, sym)| (label as Label, sym))
+        self.bimap
+            .iter()
+            .map(move |(idx, sym)| (self.index_label(idx), sym))
+    }
+
+    /// Returns a checksum of the (label, symbol) pairs stored in the table. Two tables
+    /// mapping the same labels to the same symbols have the same checksum.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # #[macro_use] extern crate rustfst; fn main() {
+    /// # use rustfst::SymbolTable;
+    /// assert_eq!(symt!["a", "b"].labeled_checksum(), symt!["a", "b"].labeled_checksum());
+    /// assert_ne!(symt!["a", "b"].labeled_checksum(), symt!["b", "a"].labeled_checksum());
+    /// # }
+    /// ```
+    pub fn labeled_checksum(&self) -> u64 {
+        // FNV-1a : stable across runs and platforms, unlike the std hashers.
+        let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+        let mut feed = |bytes: &[u8]| {
+            for b in bytes {
+                hash ^= u64::from(*b);
+                hash = hash.wrapping_mul(0x0100_0000_01b3);
+            }
+        };
+        for (label, symbol) in self.iter() {
+            feed(&label.to_le_bytes());
+            feed(symbol.as_bytes());
+            feed(&[0]);
+        }
+        hash
     }
 
     /// Adds another SymbolTable to this table.
@@ -263,11 +444,49 @@ impl<H: BuildHasher> SymbolTable<H> {
         }
     }
 
+    /// Adds all the symbols of another SymbolTable to this table and returns the mapping from
+    /// the labels of `other` to the labels of this table, only for the labels that differ.
+    ///
+    /// Symbols present in both tables keep the label they have in this table, even when
+    /// `other` assigns them a different one. The mapping can be passed to `relabel_pairs` to
+    /// relabel an FST using `other` so that it uses the merged table.
+    ///
+    /// # Examples
+    /// ```rust
+    /// # #[macro_use] extern crate rustfst; fn main() {
+    /// # use rustfst::SymbolTable;
+    /// let mut symt = symt!["a", "b"];
+    /// let other = symt!["b", "c"];
+    ///
+    /// let mapping = symt.merge(&other);
+    /// // Elements in the table : `<eps>`, `a`, `b`, `c`
+    /// assert_eq!(symt.len(), 4);
+    /// // `b` : 1 -> 2, `c` : 2 -> 3
+    /// assert_eq!(mapping, vec![(1, 2), (2, 3)]);
+    /// # }
+    /// ```
+    pub fn merge(&mut self, other: &SymbolTable) -> Vec<(Label, Label)> {
+        other
+            .iter()
+            .filter_map(|(label, symbol)| {
+                let new_label = self.add_symbol(symbol);
+                if new_label != label {
+                    Some((label, new_label))
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Writes the symbol table in the text format used by OpenFst and Kaldi : one
+    /// `symbol\tlabel` pair per line, in increasing order of the labels.
     pub fn write_text<P: AsRef<Path>>(&self, path_output: P) -> Result<()> {
         let buffer = File::create(path_output.as_ref())?;
         let mut writer = BufWriter::new(LineWriter::new(buffer));
 
         write!(writer, "{}", self)?;
+        writer.flush()?;
 
         Ok(())
     }
@@ -301,7 +520,7 @@ impl<H: BuildHasher> fmt::Display for SymbolTable<H> {
 
 impl<H: BuildHasher> PartialEq for SymbolTable<H> {
     fn eq(&self, other: &Self) -> bool {
-        self.bimap.eq(&other.bimap)
+        self.bimap.eq(&other.bimap) && self.labels().eq(other.labels())
     }
 }
 
@@ -404,6 +623,111 @@ impl<H: BuildHasher> BiHashMapString<H> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_symt_text_round_trip() -> Result<()> {
+        let text = "# Kaldi words.txt\n<eps> 0\n#0   3\n  hello\t1\r\n\nworld 2\n";
+        let symt = SymbolTable::from_text_string(text)?;
+        assert_eq!(
+            symt.iter().collect::<Vec<_>>(),
+            vec![(0, EPS_SYMBOL), (1, "hello"), (2, "world"), (3, "#0")]
+        );
+
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("words.txt");
+        symt.write_text(&path)?;
+        assert_eq!(
+            std::fs::read_to_string(&path)?,
+            "<eps>\t0\nhello\t1\nworld\t2\n#0\t3\n"
+        );
+        assert_eq!(SymbolTable::read_text(&path)?, symt);
+
+        assert!(SymbolTable::from_text_string("a 0\nb\n").is_err());
+        assert!(SymbolTable::from_text_string("a 0\na 1\n").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_symt_checked() -> Result<()> {
+        // Labels of an external table, e.g. a Kaldi words.txt.
+        let words = vec![(0, "<eps>"), (1, "hello"), (2, "world")];
+        let mut symt = SymbolTable::empty();
+        for (label, word) in words.iter() {
+            symt.add_symbol_with_key(*word, *label)?;
+        }
+        assert_eq!(symt.iter().collect::<Vec<_>>(), words);
+
+        assert!(symt.add_symbol_checked("hello").is_err());
+        assert!(symt.add_symbol_with_key("hello", 3).is_err());
+        assert!(symt.add_symbol_with_key("foo", 1).is_err());
+        // Failed insertions don't modify the table.
+        assert_eq!(symt.len(), 3);
+
+        assert_eq!(symt.add_symbol_checked("foo")?, 3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_symt_with_gaps() -> Result<()> {
+        let mut symt = SymbolTable::new();
+        symt.add_symbol_with_key("a", 5)?;
+        symt.add_symbol_with_key("b", 2)?;
+        assert_eq!(symt.add_symbol("c"), 6);
+        assert_eq!(symt.add_symbol("a"), 5);
+        assert!(symt.add_symbol_with_key("d", 2).is_err());
+        assert!(symt.add_symbol_with_key("b", 3).is_err());
+
+        assert_eq!(symt.len(), 4);
+        assert_eq!(symt.available_key(), 7);
+        assert_eq!(
+            symt.iter().collect::<Vec<_>>(),
+            vec![(0, EPS_SYMBOL), (5, "a"), (2, "b"), (6, "c")]
+        );
+        assert_eq!(symt.labels().collect::<Vec<_>>(), vec![0, 5, 2, 6]);
+        assert_eq!(symt.get_label("b"), Some(2));
+        assert_eq!(symt.get_symbol(2), Some("b"));
+        for label in [1, 3, 4, 7] {
+            assert!(!symt.contains_label(label));
+        }
+
+        let text = symt.text()?;
+        assert_eq!(text, "<eps>\t0\nb\t2\na\t5\nc\t6\n");
+        let symt_text = SymbolTable::from_text_string(&text)?;
+        assert_eq!(
+            symt_text.iter().sorted().collect::<Vec<_>>(),
+            symt.iter().sorted().collect::<Vec<_>>()
+        );
+
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("symt.bin");
+        symt.write(&path)?;
+        assert_eq!(SymbolTable::read(&path)?, symt);
+        Ok(())
+    }
+
+    #[test]
+    fn test_symt_merge() {
+        let mut symt = SymbolTable::new();
+        symt.add_symbols(vec!["a", "b", "c"]);
+        let mut other = SymbolTable::empty();
+        other.add_symbols(vec!["c", EPS_SYMBOL, "d", "a"]);
+
+        let mapping = symt.merge(&other);
+
+        // Shared symbols keep the labels of `symt`.
+        assert_eq!(mapping, vec![(0, 3), (1, 0), (2, 4), (3, 1)]);
+        assert_eq!(symt.len(), 5);
+        for (label, new_label) in mapping {
+            assert_eq!(other.get_symbol(label), symt.get_symbol(new_label));
+        }
+        assert_eq!(symt.get_label("c"), Some(3));
+        assert_eq!(symt.get_label("d"), Some(4));
+
+        // Merging a table with itself is a no-op.
+        let copy = symt.clone();
+        assert!(symt.merge(&copy).is_empty());
+        assert_eq!(symt, copy);
+    }
+
     #[test]
     fn test_symt() {
         let mut symt = SymbolTable::new();
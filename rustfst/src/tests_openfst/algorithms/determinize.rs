@@ -5,13 +5,15 @@ use anyhow::{format_err, Result};
 use bitflags::_core::marker::PhantomData;
 use serde::{Deserialize, Serialize};
 
-use crate::algorithms::determinize::{determinize_with_config, DeterminizeConfig, DeterminizeType};
+use crate::algorithms::determinize::{
+    determinize_with_config, DeterminizeConfig, DeterminizeFst, DeterminizeType,
+};
 use crate::fst_properties::FstProperties;
 use crate::fst_traits::{AllocableFst, MutableFst, SerializableFst};
 use crate::semirings::SerializableSemiring;
 use crate::semirings::WeaklyDivisibleSemiring;
 use crate::semirings::WeightQuantize;
-use crate::tests_openfst::utils::test_isomorphic_fst;
+use crate::tests_openfst::utils::{compare_fst_static_lazy, test_eq_fst, test_isomorphic_fst};
 use crate::tests_openfst::FstTestData;
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -60,7 +62,7 @@ where
 {
     for determinize_data in &test_data.determinize {
         let config = DeterminizeConfig::default().with_det_type(determinize_data.det_type);
-        let fst_res: Result<F> = determinize_with_config(&test_data.raw, config);
+        let fst_res: Result<F> = determinize_with_config(&test_data.raw, config.clone());
 
         match (&determinize_data.result, fst_res) {
             (Ok(fst_expected), Ok(ref fst_determinized)) => {
@@ -78,6 +80,29 @@ where
                         determinize_data.det_type
                     ),
                 );
+
+                if test_data.raw.properties().contains(FstProperties::ACCEPTOR) {
+                    let fst_lazy: F =
+                        DeterminizeFst::<_, F, _>::new(&test_data.raw, config.delta)?.compute()?;
+                    test_eq_fst(
+                        fst_determinized,
+                        &fst_lazy,
+                        format!(
+                            "Lazy determinize fail for det_type = {:?} ",
+                            determinize_data.det_type
+                        ),
+                    );
+
+                    // Expands the lazy FST state by state instead of computing it all at once.
+                    compare_fst_static_lazy(
+                        fst_determinized,
+                        &DeterminizeFst::<_, F, _>::new(&test_data.raw, config.delta)?,
+                        format!(
+                            "Lazy determinize fail for det_type = {:?} ",
+                            determinize_data.det_type
+                        ),
+                    )?;
+                }
             }
             (Ok(_fst_expected), Err(_)) => panic!(
                 "Determinize fail for det_type {:?}. Got Err. Expected Ok",
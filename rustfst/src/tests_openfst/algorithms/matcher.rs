@@ -1,14 +1,13 @@
 use anyhow::Result;
-use itertools::Itertools;
-use serde_derive::{Deserialize, Serialize};
+use serde::{Deserialize, Serialize};
 
-use crate::algorithms::tr_compares::{ilabel_compare, olabel_compare};
-use crate::algorithms::tr_sort;
 use crate::algorithms::compose::matchers::{MatchType, Matcher, SortedMatcher};
-use crate::fst_traits::{AllocableFst, MutableFst, SerializableFst};
-use crate::semirings::{SerializableSemiring, WeaklyDivisibleSemiring, WeightQuantize};
+use crate::algorithms::tr_compares::{ILabelCompare, OLabelCompare};
+use crate::algorithms::tr_sort;
+use crate::fst_traits::{MutableFst, SerializableFst};
+use crate::semirings::SerializableSemiring;
 use crate::tests_openfst::FstTestData;
-use crate::{Tr, Label, StateId, NO_LABEL, NO_STATE_ID};
+use crate::{Label, StateId, Tr, NO_LABEL, NO_STATE_ID};
 
 #[derive(Serialize, Deserialize, Debug)]
 struct SerializedTr {
@@ -20,29 +19,21 @@ struct SerializedTr {
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct MatcherOperationResult {
-    state: usize,
-    label: usize,
+    state: StateId,
+    label: Label,
     match_type: usize,
     trs: Vec<SerializedTr>,
 }
 
-pub struct MatcherTestData<F>
-where
-    F: SerializableFst,
-    F::W: SerializableSemiring,
-{
+pub struct MatcherTestData<W: SerializableSemiring> {
     label: Label,
     state: StateId,
     match_type: MatchType,
-    trs: Vec<Tr<F::W>>,
+    trs: Vec<Tr<W>>,
 }
 
 impl MatcherOperationResult {
-    pub fn parse<F>(&self) -> MatcherTestData<F>
-    where
-        F: SerializableFst,
-        F::W: SerializableSemiring,
-    {
+    pub fn parse<W: SerializableSemiring>(&self) -> MatcherTestData<W> {
         MatcherTestData {
             label: self.label,
             state: self.state,
@@ -58,25 +49,25 @@ impl MatcherOperationResult {
                     let ilabel = if s.ilabel == -1 {
                         NO_LABEL
                     } else {
-                        s.ilabel as usize
+                        s.ilabel as Label
                     };
 
                     let olabel = if s.olabel == -1 {
                         NO_LABEL
                     } else {
-                        s.olabel as usize
+                        s.olabel as Label
                     };
 
                     let nextstate = if s.nextstate == -1 {
                         NO_STATE_ID
                     } else {
-                        s.nextstate as usize
+                        s.nextstate as StateId
                     };
 
                     Tr::new(
                         ilabel,
                         olabel,
-                        F::W::parse_text(s.weight.as_str()).unwrap().1,
+                        W::parse_text(s.weight.as_str()).unwrap().1,
                         nextstate,
                     )
                 })
@@ -85,42 +76,35 @@ impl MatcherOperationResult {
     }
 }
 
-pub fn test_sorted_matcher<F>(test_data: &FstTestData<F>) -> Result<()>
+pub fn test_sorted_matcher<W, F>(test_data: &FstTestData<W, F>) -> Result<()>
 where
-    F: SerializableFst + MutableFst + AllocableFst,
-    F::W: SerializableSemiring + WeaklyDivisibleSemiring + WeightQuantize + 'static,
+    F: SerializableFst<W> + MutableFst<W>,
+    W: SerializableSemiring,
 {
-    unimplemented!()
-    // let mut fst_isorted = test_data.raw.clone();
-    // tr_sort(&mut fst_isorted, ilabel_compare);
-    //
-    // let mut fst_osorted = test_data.raw.clone();
-    // tr_sort(&mut fst_osorted, olabel_compare);
-    //
-    // for matcher_data in &test_data.matcher {
-    //     let fst = match matcher_data.match_type {
-    //         MatchType::MatchInput => &fst_isorted,
-    //         MatchType::MatchOutput => &fst_osorted,
-    //         _ => bail!("Unsupported match_type : {:?}", matcher_data.match_type),
-    //     };
-    //
-    //     let matcher = SortedMatcher::new(fst, matcher_data.match_type)?;
-    //     let trs: Vec<Tr<_>> = matcher
-    //         .iter(matcher_data.state, matcher_data.label)?
-    //         .map(|f| {
-    //             f.into_tr(matcher_data.state, matcher_data.match_type)
-    //                 .unwrap()
-    //         })
-    //         .collect();
-    //
-    //     assert_eq!(
-    //         trs,
-    //         matcher_data.trs.iter().cloned().collect_vec(),
-    //         "Test matcher failed {:?} {:?} {:?}",
-    //         matcher_data.state,
-    //         matcher_data.label,
-    //         matcher_data.match_type
-    //     );
-    // }
-    // Ok(())
+    let mut fst_isorted = test_data.raw.clone();
+    tr_sort(&mut fst_isorted, ILabelCompare {});
+
+    let mut fst_osorted = test_data.raw.clone();
+    tr_sort(&mut fst_osorted, OLabelCompare {});
+
+    for matcher_data in &test_data.matcher {
+        let fst = match matcher_data.match_type {
+            MatchType::MatchInput => &fst_isorted,
+            MatchType::MatchOutput => &fst_osorted,
+            _ => bail!("Unsupported match_type : {:?}", matcher_data.match_type),
+        };
+
+        let matcher = SortedMatcher::<W, F, _>::new(fst, matcher_data.match_type)?;
+        let trs = matcher
+            .iter(matcher_data.state, matcher_data.label)?
+            .map(|f| f.into_tr(matcher_data.state, matcher_data.match_type))
+            .collect::<Result<Vec<_>>>()?;
+
+        assert_eq!(
+            trs, matcher_data.trs,
+            "Test matcher failed {:?} {:?} {:?}",
+            matcher_data.state, matcher_data.label, matcher_data.match_type
+        );
+    }
+    Ok(())
 }
@@ -10,9 +10,7 @@ pub mod factor_weight_identity;
 pub mod fst_convert;
 pub mod gallic_encode_decode;
 pub mod inverse;
-pub mod tr_map;
-pub mod tr_sort;
-// pub mod matcher;
+pub mod matcher;
 pub mod minimize;
 pub mod optimize;
 pub mod project;
@@ -27,5 +25,7 @@ pub mod shortest_path;
 pub mod state_map;
 pub mod state_reachable;
 pub mod topsort;
+pub mod tr_map;
+pub mod tr_sort;
 pub mod union;
 pub mod weight_pushing;
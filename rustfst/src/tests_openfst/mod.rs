@@ -39,9 +39,9 @@ use crate::tests_openfst::algorithms::fst_convert::test_fst_convert;
 use crate::tests_openfst::algorithms::gallic_encode_decode::test_gallic_encode_decode;
 use crate::tests_openfst::algorithms::gallic_encode_decode::GallicOperationResult;
 use crate::tests_openfst::algorithms::gallic_encode_decode::GallicTestData;
+use crate::tests_openfst::algorithms::matcher::test_sorted_matcher;
+use crate::tests_openfst::algorithms::matcher::{MatcherOperationResult, MatcherTestData};
 use crate::tests_openfst::algorithms::optimize::test_optimize;
-// use crate::tests_openfst::algorithms::matcher::test_sorted_matcher;
-// use crate::tests_openfst::algorithms::matcher::{MatcherOperationResult, MatcherTestData};
 use crate::tests_openfst::algorithms::state_reachable::{
     test_state_reachable, StateReachableOperationResult, StateReachableTestData,
 };
@@ -173,7 +173,7 @@ pub struct ParsedFstTestData {
     closure_plus: SimpleStaticLazyOperationResult,
     closure_star: SimpleStaticLazyOperationResult,
     raw_vector_with_symt_bin_path: String,
-    // matcher: Vec<MatcherOperationResult>,
+    matcher: Vec<MatcherOperationResult>,
     compose: Vec<ComposeOperationResult>,
     state_reachable: StateReachableOperationResult,
     queue: QueueOperationResult,
@@ -230,7 +230,7 @@ where
     pub closure_plus: SimpleStaticLazyTestData<W, F>,
     pub closure_star: SimpleStaticLazyTestData<W, F>,
     pub raw_vector_with_symt_bin_path: PathBuf,
-    // pub matcher: Vec<MatcherTestData<F>>,
+    pub matcher: Vec<MatcherTestData<W>>,
     pub compose: Vec<ComposeTestData<W, F>>,
     pub state_reachable: StateReachableTestData,
     pub queue: QueueOperationResult,
@@ -345,7 +345,7 @@ where
             raw_vector_with_symt_bin_path: absolute_path_folder
                 .join(&data.raw_vector_with_symt_bin_path)
                 .to_path_buf(),
-            // matcher: data.matcher.iter().map(|v| v.parse()).collect(),
+            matcher: data.matcher.iter().map(|v| v.parse()).collect(),
             compose: data
                 .compose
                 .iter()
@@ -776,12 +776,11 @@ macro_rules! test_fst {
                 Ok(())
             }
 
-            // #[test]
-            // #[ignore]
-            // fn test_fst_sorted_matcher_openfst() -> Result<()> {
-            //     do_run!(test_sorted_matcher, $fst_name);
-            //     Ok(())
-            // }
+            #[test]
+            fn test_fst_sorted_matcher_openfst() -> Result<()> {
+                do_run!(test_sorted_matcher, $fst_name);
+                Ok(())
+            }
 
             #[test]
             fn test_fst_compose_openfst() -> Result<()> {
@@ -855,3 +854,4 @@ test_fst!(test_openfst_fst_017, "fst_017");
 test_fst!(test_openfst_fst_018, "fst_018");
 test_fst!(test_openfst_fst_019, "fst_019");
 test_fst!(test_openfst_fst_020, "fst_020");
+test_fst!(test_openfst_fst_021, "fst_021");
@@ -3,6 +3,8 @@ use std::fs::read_to_string;
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 
+use crate::fst_impls::{ConstFst, VectorFst};
+use crate::fst_traits::SerializableFst;
 use crate::semirings::{
     GallicWeight, GallicWeightLeft, GallicWeightMin, GallicWeightRestrict, GallicWeightRight,
     LogWeight, ProductWeight, ReverseBack, SerializableSemiring, StringWeightLeft,
@@ -77,6 +79,8 @@ fn do_run_test_openfst_weight<W: SerializableSemiring + WeightQuantize>(
     );
     assert_eq!(W::weight_type(), test_data.weight_type);
     assert_eq!(Tr::<W>::tr_type(), test_data.tr_type);
+    assert_eq!(VectorFst::<W>::arc_type(), test_data.tr_type);
+    assert_eq!(ConstFst::<W>::arc_type(), test_data.tr_type);
 
     assert_eq!(
         test_data.weight_1.reverse()?.reverse_back()?,
@@ -2,10 +2,11 @@ use std::fmt::Display;
 
 use crate::algorithms::isomorphic;
 use crate::fst_properties::FstProperties;
-use crate::fst_traits::ExpandedFst;
+use crate::fst_traits::{CoreFst, ExpandedFst};
 use crate::semirings::WeightQuantize;
 use crate::Semiring;
 use crate::KDELTA;
+use anyhow::Result;
 
 pub fn test_correctness_properties<W: Semiring, FREF: ExpandedFst<W>, FPRED: ExpandedFst<W>>(
     fst_ref: &FREF,
@@ -76,11 +77,26 @@ pub fn test_eq_fst<
     fst_ref: &FREF,
     fst_pred: &FPRED,
     s: I,
+) {
+    test_eq_fst_with_delta(fst_ref, fst_pred, s, KDELTA)
+}
+
+/// Same as `test_eq_fst` but the weights are compared with the tolerance `delta`.
+pub fn test_eq_fst_with_delta<
+    W: Semiring + WeightQuantize,
+    FREF: ExpandedFst<W> + Display,
+    FPRED: ExpandedFst<W> + Display,
+    I: Into<String>,
+>(
+    fst_ref: &FREF,
+    fst_pred: &FPRED,
+    s: I,
+    delta: f32,
 ) {
     let s = s.into();
     let message = format!("Test {} with openfst failing : \nREF = \n{}\nPRED = \n{}\n \nREF = \n{:?}\nPRED = \n{:?}\n",
                           s, fst_ref, fst_pred, fst_ref, fst_pred);
-    assert!(fst_ref.approx_equal(fst_pred, KDELTA), "{}", message);
+    assert!(fst_ref.approx_equal(fst_pred, delta), "{}", message);
     test_num_epsilons(fst_ref, fst_pred, message);
     test_correctness_properties(
         fst_ref,
@@ -110,3 +126,56 @@ pub fn test_isomorphic_fst<
         format!("Test properties {} with openfst failing", s),
     )
 }
+
+/// Checks a lazy FST against its static counterpart state by state, only expanding the lazy
+/// states through `get_trs` and `final_weight`. The states of the lazy FST must be numbered
+/// in the same order as the static ones, which is the case when the latter has been obtained
+/// with `compute`.
+pub fn compare_fst_static_lazy<W, FS, FL, I>(fst_static: &FS, fst_lazy: &FL, s: I) -> Result<()>
+where
+    W: Semiring,
+    FS: ExpandedFst<W> + Display,
+    FL: CoreFst<W>,
+    I: Into<String>,
+{
+    let s = s.into();
+    assert_eq!(
+        fst_static.start(),
+        fst_lazy.start(),
+        "Test {} static vs lazy : different start states",
+        s
+    );
+    for state in fst_static.states_range() {
+        let final_static = fst_static.final_weight(state)?;
+        let final_lazy = fst_lazy.final_weight(state)?;
+        let final_eq = match (&final_static, &final_lazy) {
+            (Some(w_static), Some(w_lazy)) => w_static.approx_equal(w_lazy, KDELTA),
+            (None, None) => true,
+            _ => false,
+        };
+        assert!(
+            final_eq,
+            "Test {} static vs lazy : different final weights for state {} : {:?} vs {:?}\nSTATIC = \n{}",
+            s, state, final_static, final_lazy, fst_static
+        );
+
+        let trs_static = fst_static.get_trs(state)?;
+        let trs_lazy = fst_lazy.get_trs(state)?;
+        let trs_eq = trs_static.len() == trs_lazy.len()
+            && trs_static
+                .iter()
+                .zip(trs_lazy.iter())
+                .all(|(tr_static, tr_lazy)| {
+                    tr_static.ilabel == tr_lazy.ilabel
+                        && tr_static.olabel == tr_lazy.olabel
+                        && tr_static.nextstate == tr_lazy.nextstate
+                        && tr_static.weight.approx_equal(&tr_lazy.weight, KDELTA)
+                });
+        assert!(
+            trs_eq,
+            "Test {} static vs lazy : different transitions for state {} : {:?} vs {:?}\nSTATIC = \n{}",
+            s, state, trs_static, trs_lazy, fst_static
+        );
+    }
+    Ok(())
+}
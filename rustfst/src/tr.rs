@@ -2,6 +2,7 @@ use crate::semirings::SerializableSemiring;
 use crate::{Label, StateId};
 
 /// Structure representing a transition from a state to another state in a FST.
+#[repr(C)]
 #[derive(Debug, Clone, PartialEq, PartialOrd, Eq, Hash)]
 pub struct Tr<W> {
     /// Input label.
@@ -77,7 +77,8 @@ impl<W: Semiring> Trs<W> for TrsConst<W> {
     }
 
     fn to_trs_vec(&self) -> TrsVec<W> {
-        TrsVec(Arc::clone(&self.trs))
+        // The trs of all the states are stored together, only the ones of this state are copied.
+        TrsVec(Arc::new(self.trs().to_vec()))
     }
 
     // Doesn't clone the data, only the Arc
@@ -116,3 +117,30 @@ impl<W: Semiring> Default for TrsConst<W> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fst_impls::{ConstFst, VectorFst};
+    use crate::fst_traits::{CoreFst, MutableFst};
+    use crate::semirings::TropicalWeight;
+    use anyhow::Result;
+
+    #[test]
+    fn test_trs_const_to_trs_vec() -> Result<()> {
+        let mut fst = VectorFst::<TropicalWeight>::new();
+        fst.add_states(3);
+        fst.set_start(0)?;
+        fst.add_tr(0, Tr::new(1, 1, 1.0, 1))?;
+        fst.add_tr(1, Tr::new(2, 2, 2.0, 2))?;
+        fst.add_tr(1, Tr::new(3, 3, 3.0, 2))?;
+        fst.add_tr(2, Tr::new(4, 4, 4.0, 0))?;
+        let const_fst: ConstFst<_> = fst.clone().into();
+
+        for s in 0..3 {
+            let trs_vec = const_fst.get_trs(s)?.to_trs_vec();
+            assert_eq!(trs_vec.trs(), fst.get_trs(s)?.trs());
+        }
+        Ok(())
+    }
+}
@@ -146,6 +146,41 @@ impl<'a, W: Semiring> TrsIterMut<'a, W> {
         Ok(())
     }
 
+    /// Calls `f` on each `Tr` with a mutable reference, which allows rewriting the labels, the
+    /// weight or the nextstate in place. The properties of the FST are updated after each
+    /// modified `Tr` the same way as with [`set_tr`](#method.set_tr).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use anyhow::Result;
+    /// # use rustfst::prelude::*;
+    /// # fn main() -> Result<()> {
+    /// let mut fst = VectorFst::<TropicalWeight>::new();
+    /// let s0 = fst.add_state();
+    /// let s1 = fst.add_state();
+    /// fst.add_tr(s0, Tr::new(1, 1, 2.0, s1))?;
+    /// fst.add_tr(s0, Tr::new(2, 2, 4.0, s1))?;
+    ///
+    /// fst.tr_iter_mut(s0)?.for_each_mut(|tr| tr.weight = (*tr.weight.value() / 2.0).into());
+    ///
+    /// let weights: Vec<_> = fst.get_trs(s0)?.iter().map(|tr| tr.weight).collect();
+    /// assert_eq!(weights, vec![TropicalWeight::new(1.0), TropicalWeight::new(2.0)]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn for_each_mut<F: FnMut(&mut Tr<W>)>(&mut self, mut f: F) {
+        for tr in self.trs.iter_mut() {
+            let old_tr = tr.clone();
+            f(tr);
+            if *tr != old_tr {
+                *self.properties = compute_new_properties_all(*self.properties, &old_tr, tr);
+                updt_nieps!(self, old_tr.ilabel, tr.ilabel);
+                updt_noeps!(self, old_tr.olabel, tr.olabel);
+            }
+        }
+    }
+
     /// Modify the ilabel of the  number `idx` `Tr` of the object.
     ///
     /// # Safety
@@ -0,0 +1,194 @@
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+
+use anyhow::Result;
+
+use crate::fst_traits::MutableFst;
+use crate::semirings::Semiring;
+use crate::{Label, StateId, Tr, EPS_LABEL};
+
+/// Returns the label of the context-dependent phone corresponding to `window`,
+/// as used by [`context_dependency_transducer`](fn.context_dependency_transducer.html).
+///
+/// `window` contains the left context, the central phone and the right context. Each
+/// element is either a label of `phones` or `EPS_LABEL` for the padding at the sentence
+/// boundaries. With `B = phones.len() + 1` and `d_i` being `0` for the padding and the
+/// position of the phone in `phones` plus one otherwise, the label is
+/// `1 + sum(d_i * B^i)`.
+pub fn context_dependent_phone(phones: &[Label], window: &[Label]) -> Result<Label> {
+    let base = phones.len() as u64 + 1;
+    let mut label = 0u64;
+    let mut factor = 1u64;
+    for phone in window {
+        let digit = if *phone == EPS_LABEL {
+            0
+        } else {
+            match phones.iter().position(|p| p == phone) {
+                Some(idx) => idx as u64 + 1,
+                None => bail!("ContextDependency : Unknown phone {}", phone),
+            }
+        };
+        label += digit * factor;
+        factor *= base;
+        if factor > Label::MAX as u64 {
+            bail!(
+                "ContextDependency : Too many context-dependent phones for {} phones and a window of {}",
+                phones.len(),
+                window.len()
+            );
+        }
+    }
+    Ok(label as Label + 1)
+}
+
+/// Builds the context-dependency transducer (C in HCLG) mapping sequences of
+/// context-dependent phones to sequences of phones.
+///
+/// A context-dependent phone is a phone along with its `left_context` previous phones and
+/// its `right_context` next phones, its label is computed by
+/// [`context_dependent_phone`](fn.context_dependent_phone.html). The context
+/// crosses word boundaries and is padded with `EPS_LABEL` at the sentence boundaries.
+///
+/// The transducer is deterministic on its output side : as the right context of a phone is
+/// only known after reading the next phones, the context-dependent phones are emitted
+/// with a delay of `right_context` transitions. The first transitions have an epsilon input
+/// label and the last ones, flushing the remaining phones with padding as right context,
+/// an epsilon output label. All the weights are `one()`.
+///
+/// It is meant to be composed on the left of a lexicon transducer (L), whose input labels
+/// are phones.
+pub fn context_dependency_transducer<W: Semiring, F: MutableFst<W>>(
+    phones: &[Label],
+    left_context: usize,
+    right_context: usize,
+) -> Result<F> {
+    if phones.contains(&EPS_LABEL) {
+        bail!("ContextDependency : Epsilon can't be a phone")
+    }
+    for (idx, phone) in phones.iter().enumerate() {
+        if phones[..idx].contains(phone) {
+            bail!("ContextDependency : Duplicate phone {}", phone)
+        }
+    }
+    let window_len = left_context + right_context + 1;
+    // Make sure all the context-dependent phones have a valid label.
+    context_dependent_phone(
+        phones,
+        &vec![phones.last().cloned().unwrap_or(0); window_len],
+    )?;
+
+    // A state stores the last `left_context + right_context` phones read. The last
+    // `right_context` ones haven't been emitted yet as central phones.
+    let mut fst = F::new();
+    let mut states: HashMap<Vec<Label>, StateId> = HashMap::new();
+    let start_history = vec![EPS_LABEL; window_len - 1];
+    let start = fst.add_state();
+    fst.set_start(start)?;
+    states.insert(start_history.clone(), start);
+    let mut queue = vec![start_history];
+
+    while let Some(history) = queue.pop() {
+        let state = states[&history];
+        let pending = &history[left_context..];
+        if pending.iter().all(|p| *p == EPS_LABEL) {
+            fst.set_final(state, W::one())?;
+        }
+
+        // Padding has already been added after a phone : the sentence is being flushed.
+        let flushing = history
+            .windows(2)
+            .any(|w| w[0] != EPS_LABEL && w[1] == EPS_LABEL);
+        let mut next_phones = vec![];
+        if !flushing {
+            next_phones.extend(phones.iter().cloned());
+        }
+        if right_context > 0 && history[left_context] != EPS_LABEL {
+            next_phones.push(EPS_LABEL);
+        }
+
+        for phone in next_phones {
+            let mut window = history.clone();
+            window.push(phone);
+            let ilabel = if window[left_context] == EPS_LABEL {
+                EPS_LABEL
+            } else {
+                context_dependent_phone(phones, &window)?
+            };
+            let next_history = window[1..].to_vec();
+            let nextstate = match states.entry(next_history) {
+                Entry::Occupied(e) => *e.get(),
+                Entry::Vacant(e) => {
+                    queue.push(e.key().clone());
+                    *e.insert(fst.add_state())
+                }
+            };
+            fst.add_tr(state, Tr::new(ilabel, phone, W::one(), nextstate))?;
+        }
+    }
+
+    Ok(fst)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::compose::compose;
+    use crate::algorithms::tr_compares::OLabelCompare;
+    use crate::algorithms::tr_sort;
+    use crate::algorithms::union::union;
+    use crate::fst_impls::VectorFst;
+    use crate::fst_path::FstPath;
+    use crate::fst_traits::Fst;
+    use crate::semirings::TropicalWeight;
+    use crate::utils::transducer;
+
+    #[test]
+    fn test_context_dependency_compose_lexicon() -> Result<()> {
+        let (a, b) = (1, 2);
+        let phones = [a, b];
+        let mut c: VectorFst<TropicalWeight> = context_dependency_transducer(&phones, 1, 1)?;
+        tr_sort(&mut c, OLabelCompare {});
+
+        // Lexicon with two words : 10 = a b, 11 = b.
+        let mut l: VectorFst<TropicalWeight> =
+            transducer(&[a, b], &[10, EPS_LABEL], TropicalWeight::one());
+        let l2: VectorFst<TropicalWeight> = transducer(&[b], &[11], TropicalWeight::one());
+        union(&mut l, &l2)?;
+
+        let cl: VectorFst<TropicalWeight> = compose(c, l)?;
+        let mut paths: Vec<_> = cl.paths_iter().collect();
+        paths.sort_by(|p1, p2| p1.olabels.cmp(&p2.olabels));
+
+        let cd = |window: &[Label]| context_dependent_phone(&phones, window).unwrap();
+        assert_eq!(
+            paths,
+            vec![
+                FstPath::new(
+                    vec![cd(&[0, a, b]), cd(&[a, b, 0])],
+                    vec![10],
+                    TropicalWeight::one()
+                ),
+                FstPath::new(vec![cd(&[0, b, 0])], vec![11], TropicalWeight::one()),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_context_dependency_no_right_context() -> Result<()> {
+        let c: VectorFst<TropicalWeight> = context_dependency_transducer(&[1, 2], 1, 0)?;
+        let input: VectorFst<TropicalWeight> = transducer(&[2, 1], &[2, 1], TropicalWeight::one());
+        let res: VectorFst<TropicalWeight> = compose(c, input)?;
+        let paths: Vec<_> = res.paths_iter().collect();
+        assert_eq!(paths.len(), 1);
+        assert_eq!(
+            paths[0].ilabels,
+            vec![
+                context_dependent_phone(&[1, 2], &[0, 2])?,
+                context_dependent_phone(&[1, 2], &[2, 1])?
+            ]
+        );
+        Ok(())
+    }
+}
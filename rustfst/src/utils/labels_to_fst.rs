@@ -1,9 +1,10 @@
 use std::cmp;
 
+use crate::fst_impls::VectorFst;
 use crate::fst_traits::MutableFst;
 use crate::semirings::Semiring;
 use crate::tr::Tr;
-use crate::Label;
+use crate::{Label, StateId};
 
 /// Turns a list of input labels and output labels into a linear FST.
 /// The only accepted path in the FST has for input `labels_input` and for output `labels_output`.
@@ -130,6 +131,44 @@ pub fn acceptor<W: Semiring, F: MutableFst<W>>(labels: &[Label], weight: W) -> F
     fst
 }
 
+/// Builds the linear FST whose only path is made of the transitions `(ilabel, olabel, weight)`
+/// of `labels`. The final state has a final weight equal to `one()`.
+///
+/// This produces the same path as concatenating one FST per transition but in a single pass
+/// and with all the states allocated at once.
+///
+/// # Example
+///
+/// ```
+/// # use rustfst::fst_traits::{CoreFst, ExpandedFst};
+/// # use rustfst::fst_impls::VectorFst;
+/// # use rustfst::semirings::{Semiring, TropicalWeight};
+/// # use rustfst::utils::chain;
+/// # use rustfst::{Tr, Trs};
+/// let fst = chain(&[(1, 2, TropicalWeight::new(0.5)), (3, 4, TropicalWeight::one())]);
+///
+/// assert_eq!(fst.num_states(), 3);
+/// assert_eq!(fst.start(), Some(0));
+/// assert_eq!(fst.get_trs(0).unwrap().trs(), &[Tr::new(1, 2, 0.5, 1)]);
+/// assert_eq!(fst.get_trs(1).unwrap().trs(), &[Tr::new(3, 4, TropicalWeight::one(), 2)]);
+/// assert_eq!(fst.final_weight(2).unwrap(), Some(TropicalWeight::one()));
+/// ```
+pub fn chain<W: Semiring>(labels: &[(Label, Label, W)]) -> VectorFst<W> {
+    let mut fst = VectorFst::new();
+    fst.add_states(labels.len() + 1);
+
+    unsafe {
+        fst.set_start_unchecked(0);
+        for (state, (ilabel, olabel, weight)) in labels.iter().enumerate() {
+            let state = state as StateId;
+            fst.add_tr_unchecked(state, Tr::new(*ilabel, *olabel, weight.clone(), state + 1));
+        }
+        fst.set_final_unchecked(labels.len() as StateId, W::one());
+    }
+
+    fst
+}
+
 /// Creates a linear Fst containing the arguments.
 ///
 /// There are multiple forms to this macro :
@@ -259,3 +298,46 @@ macro_rules! fst {
         }
     };
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use anyhow::Result;
+
+    use crate::algorithms::concat::concat;
+    use crate::fst_properties::{compute_fst_properties, FstProperties};
+    use crate::fst_traits::{CoreFst, ExpandedFst, Fst};
+    use crate::semirings::TropicalWeight;
+
+    #[test]
+    fn test_chain_same_as_repeated_concat() -> Result<()> {
+        let labels: Vec<_> = (1..=10_000)
+            .map(|l| (l, l + 1, TropicalWeight::new((l % 3) as f32)))
+            .collect();
+
+        let fst = chain(&labels);
+        assert_eq!(fst.num_states(), labels.len() + 1);
+        let mut known = FstProperties::empty();
+        let props =
+            compute_fst_properties(&fst, FstProperties::all_properties(), &mut known, false)?;
+        assert!(props.contains(FstProperties::ACYCLIC | FstProperties::I_DETERMINISTIC));
+        assert_eq!(
+            fst.final_weight(labels.len() as StateId)?,
+            Some(TropicalWeight::one())
+        );
+
+        let mut fst_ref: VectorFst<_> = acceptor(&[], TropicalWeight::one());
+        for (ilabel, olabel, weight) in &labels {
+            let fst_tr: VectorFst<_> = transducer(&[*ilabel], &[*olabel], *weight);
+            concat(&mut fst_ref, &fst_tr)?;
+        }
+
+        let paths: Vec<_> = fst.paths_iter().collect();
+        let paths_ref: Vec<_> = fst_ref.paths_iter().collect();
+        assert_eq!(paths.len(), 1);
+        assert_eq!(paths, paths_ref);
+
+        Ok(())
+    }
+}
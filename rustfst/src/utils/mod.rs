@@ -1,7 +1,13 @@
+mod context_dependency;
 mod epsilon_machine;
 mod fst_to_labels;
 mod labels_to_fst;
+mod sigma_transducer;
+mod tagger_transducer;
 
+pub use self::context_dependency::{context_dependency_transducer, context_dependent_phone};
 pub use self::epsilon_machine::epsilon_machine;
 pub use self::fst_to_labels::decode_linear_fst;
-pub use self::labels_to_fst::{acceptor, transducer};
+pub use self::labels_to_fst::{acceptor, chain, transducer};
+pub use self::sigma_transducer::sigma_transducer;
+pub use self::tagger_transducer::tagger_transducer;
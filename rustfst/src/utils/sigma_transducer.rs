@@ -0,0 +1,77 @@
+use crate::fst_traits::MutableFst;
+use crate::semirings::Semiring;
+use crate::{Label, Tr, EPS_LABEL};
+
+/// Builds a one-state transducer with an identity self-loop for every label of the
+/// alphabet, each one carrying `weight`. The state is both initial and final with
+/// weight `one()`.
+///
+/// Composing an FST with it keeps the paths whose labels are all in the alphabet,
+/// with `weight` multiplied once per transition. Epsilon is never part of the alphabet
+/// and duplicate labels are ignored. The self-loops are sorted by label.
+///
+/// # Example
+///
+/// ```
+/// # use rustfst::fst_impls::VectorFst;
+/// # use rustfst::fst_traits::{CoreFst, ExpandedFst};
+/// # use rustfst::semirings::{Semiring, TropicalWeight};
+/// # use rustfst::utils::sigma_transducer;
+/// let fst: VectorFst<TropicalWeight> = sigma_transducer(&[3, 0, 1, 3], TropicalWeight::new(0.5));
+///
+/// assert_eq!(fst.num_states(), 1);
+/// assert_eq!(fst.num_trs(0).unwrap(), 2);
+/// ```
+pub fn sigma_transducer<W: Semiring, F: MutableFst<W>>(alphabet: &[Label], weight: W) -> F {
+    let mut labels: Vec<Label> = alphabet
+        .iter()
+        .filter(|l| **l != EPS_LABEL)
+        .cloned()
+        .collect();
+    labels.sort_unstable();
+    labels.dedup();
+
+    let mut fst = F::new();
+    let s = fst.add_state();
+    fst.set_start(s).unwrap();
+    fst.set_final(s, W::one()).unwrap();
+    for label in labels {
+        fst.add_tr(s, Tr::new(label, label, weight.clone(), s))
+            .unwrap();
+    }
+    fst
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use super::*;
+    use crate::algorithms::compose::compose;
+    use crate::fst_impls::VectorFst;
+    use crate::fst_path::FstPath;
+    use crate::fst_traits::Fst;
+    use crate::semirings::TropicalWeight;
+    use crate::utils::{acceptor, transducer};
+
+    #[test]
+    fn test_sigma_transducer_compose() -> Result<()> {
+        let sigma: VectorFst<TropicalWeight> =
+            sigma_transducer(&[4, 3, EPS_LABEL, 2, 1], TropicalWeight::new(0.5));
+
+        let input: VectorFst<TropicalWeight> = transducer(&[1, 2, 3], &[3, 4, 1], 1.0.into());
+        let res: VectorFst<TropicalWeight> = compose(input, sigma.clone())?;
+        let paths: Vec<_> = res.paths_iter().collect();
+        assert_eq!(
+            paths,
+            vec![FstPath::new(vec![1, 2, 3], vec![3, 4, 1], 2.5.into())]
+        );
+
+        // Labels outside of the alphabet are filtered out.
+        let input: VectorFst<TropicalWeight> = acceptor(&[1, 5], TropicalWeight::one());
+        let res: VectorFst<TropicalWeight> = compose(input, sigma)?;
+        assert_eq!(res.paths_iter().count(), 0);
+
+        Ok(())
+    }
+}
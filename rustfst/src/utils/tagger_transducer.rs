@@ -0,0 +1,141 @@
+use std::collections::HashSet;
+
+use anyhow::Result;
+
+use crate::fst_traits::MutableFst;
+use crate::semirings::Semiring;
+use crate::tr::Tr;
+use crate::{Label, StateId, EPS_LABEL};
+
+/// Builds a transducer tagging every input label with one of the `tags`.
+///
+/// Each input label of `input_alphabet` is mapped to any label of `tags`, one tag
+/// being emitted per input label. The transducer remembers the last emitted tag :
+/// a pair `(t1, t2)` in `forbidden_bigrams` means that the tag `t2` can't directly follow
+/// the tag `t1`, the corresponding transitions are simply not created.
+///
+/// Composing a linear acceptor with this transducer yields all the valid taggings of
+/// the input. All the weights are `one()`. The transitions of every state are sorted
+/// by input label, so that the transducer can be used directly on the right side of
+/// a composition.
+///
+/// # Example
+///
+/// ```
+/// # use anyhow::Result;
+/// # use rustfst::fst_impls::VectorFst;
+/// # use rustfst::fst_traits::ExpandedFst;
+/// # use rustfst::semirings::TropicalWeight;
+/// # use rustfst::utils::tagger_transducer;
+/// # fn main() -> Result<()> {
+/// // Two words, two tags. Tag 11 can't follow tag 10.
+/// let fst: VectorFst<TropicalWeight> = tagger_transducer(&[1, 2], &[10, 11], &[(10, 11)])?;
+///
+/// // One state per tag plus the start state.
+/// assert_eq!(fst.num_states(), 3);
+/// # Ok(())
+/// # }
+/// ```
+pub fn tagger_transducer<W: Semiring, F: MutableFst<W>>(
+    input_alphabet: &[Label],
+    tags: &[Label],
+    forbidden_bigrams: &[(Label, Label)],
+) -> Result<F> {
+    if input_alphabet.contains(&EPS_LABEL) || tags.contains(&EPS_LABEL) {
+        bail!("TaggerTransducer : Epsilon can't be part of the input alphabet nor of the tags")
+    }
+
+    let mut input_alphabet = input_alphabet.to_vec();
+    input_alphabet.sort_unstable();
+    input_alphabet.dedup();
+
+    let mut unique_tags = HashSet::with_capacity(tags.len());
+    let tags: Vec<Label> = tags
+        .iter()
+        .filter(|t| unique_tags.insert(**t))
+        .cloned()
+        .collect();
+
+    let mut forbidden = HashSet::with_capacity(forbidden_bigrams.len());
+    for (t1, t2) in forbidden_bigrams {
+        if !unique_tags.contains(t1) || !unique_tags.contains(t2) {
+            bail!(
+                "TaggerTransducer : Forbidden bigram ({}, {}) refers to an unknown tag",
+                t1,
+                t2
+            )
+        }
+        forbidden.insert((*t1, *t2));
+    }
+
+    let mut fst = F::new();
+    // State 0 is the start state, state i + 1 is reached after emitting tags[i].
+    fst.add_states(tags.len() + 1);
+    fst.set_start(0)?;
+
+    for state in 0..=(tags.len() as StateId) {
+        fst.set_final(state, W::one())?;
+        let previous_tag = if state == 0 {
+            None
+        } else {
+            Some(tags[state as usize - 1])
+        };
+        for ilabel in &input_alphabet {
+            for (idx, tag) in tags.iter().enumerate() {
+                if let Some(previous_tag) = previous_tag {
+                    if forbidden.contains(&(previous_tag, *tag)) {
+                        continue;
+                    }
+                }
+                fst.add_tr(state, Tr::new(*ilabel, *tag, W::one(), idx as StateId + 1))?;
+            }
+        }
+    }
+
+    Ok(fst)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::compose::compose;
+    use crate::fst_impls::VectorFst;
+    use crate::fst_traits::Fst;
+    use crate::semirings::TropicalWeight;
+    use crate::utils::acceptor;
+
+    #[test]
+    fn test_tagger_transducer_forbidden_bigram() -> Result<()> {
+        let tagger: VectorFst<TropicalWeight> =
+            tagger_transducer(&[2, 1], &[10, 11, 12], &[(10, 11), (12, 12)])?;
+        let input: VectorFst<TropicalWeight> = acceptor(&[1, 2, 1], TropicalWeight::one());
+
+        let taggings: VectorFst<TropicalWeight> = compose(input, tagger)?;
+        let mut outputs: Vec<_> = taggings.paths_iter().map(|p| p.olabels).collect();
+        outputs.sort();
+
+        let mut expected = vec![];
+        for t1 in &[10, 11, 12] {
+            for t2 in &[10, 11, 12] {
+                for t3 in &[10, 11, 12] {
+                    let tagging = vec![*t1, *t2, *t3];
+                    let forbidden = tagging
+                        .windows(2)
+                        .any(|w| (w[0] == 10 && w[1] == 11) || (w[0] == 12 && w[1] == 12));
+                    if !forbidden {
+                        expected.push(tagging);
+                    }
+                }
+            }
+        }
+
+        assert_eq!(outputs, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_tagger_transducer_unknown_tag() {
+        let res: Result<VectorFst<TropicalWeight>> = tagger_transducer(&[1], &[10], &[(10, 11)]);
+        assert!(res.is_err());
+    }
+}